@@ -0,0 +1,79 @@
+//! 比较 libwifi 与内置管理帧解析器提取 Remote ID 负载的开销
+//!
+//! cargo bench --bench frame_parser
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+#[allow(dead_code)]
+#[path = "../src/remote_id.rs"]
+mod remote_id;
+#[allow(dead_code, unused_imports)]
+#[path = "../src/mgt_parser.rs"]
+mod mgt_parser;
+
+/// 构造一个带 SSID、速率、DS 参数集和 Remote ID 厂商 IE (3 条消息) 的信标帧
+fn sample_beacon() -> Vec<u8> {
+    let mut frame = vec![0x80, 0x00, 0x00, 0x00];
+    frame.extend_from_slice(&[0xff; 6]);
+    frame.extend_from_slice(&[0x02, 0x11, 0x22, 0x33, 0x44, 0x55]);
+    frame.extend_from_slice(&[0x02, 0x11, 0x22, 0x33, 0x44, 0x55]);
+    frame.extend_from_slice(&[0x10, 0x00]);
+    frame.extend_from_slice(&[0u8; 8]);
+    frame.extend_from_slice(&[0x64, 0x00, 0x21, 0x04]);
+    frame.extend_from_slice(&[0x00, 0x0a]);
+    frame.extend_from_slice(b"RID-123456");
+    frame.extend_from_slice(&[0x01, 0x08, 0x82, 0x84, 0x8b, 0x96, 0x0c, 0x12, 0x18, 0x24]);
+    frame.extend_from_slice(&[0x03, 0x01, 0x06]);
+
+    let mut payload = vec![0x01, 0xf1, remote_id::MESSAGE_SIZE as u8, 3];
+    for message_type in [0x0u8, 0x1, 0x4] {
+        let mut message = [0u8; remote_id::MESSAGE_SIZE];
+        message[0] = message_type << 4;
+        payload.extend_from_slice(&message);
+    }
+    frame.push(remote_id::VENDOR_ELEMENT_ID);
+    frame.push((payload.len() + 4) as u8);
+    frame.extend_from_slice(&[0xfa, 0x0b, 0xbc, remote_id::ODID_OUI_TYPE]);
+    frame.extend_from_slice(&payload);
+    frame
+}
+
+/// 不含 Remote ID 的普通信标：同样的固定字段和 IE，厂商 IE 为 WMM 参数
+fn plain_beacon() -> Vec<u8> {
+    let mut frame = sample_beacon();
+    let rid_ie = frame.iter().rposition(|b| *b == remote_id::VENDOR_ELEMENT_ID).unwrap();
+    frame.truncate(rid_ie);
+    frame.extend_from_slice(&[0xdd, 0x18, 0x00, 0x50, 0xf2, 0x02, 0x01, 0x01, 0x00, 0x00]);
+    frame.extend_from_slice(&[0x03, 0xa4, 0x00, 0x00, 0x27, 0xa4, 0x00, 0x00, 0x42, 0x43, 0x5e, 0x00, 0x62, 0x32, 0x2f, 0x00]);
+    frame
+}
+
+fn bench_parsers(c: &mut Criterion) {
+    let frame = sample_beacon();
+    let mut group = c.benchmark_group("beacon_remote_id");
+    group.bench_function("libwifi", |b| {
+        b.iter(|| match libwifi::parse_frame(black_box(&frame), false) {
+            Ok(libwifi::Frame::Beacon(beacon)) => remote_id::reassemble(&beacon.station_info.vendor_specific),
+            _ => None,
+        })
+    });
+    group.bench_function("builtin", |b| {
+        b.iter(|| mgt_parser::parse_management(black_box(&frame))
+            .and_then(|f| remote_id::reassemble(&f.vendor_specific)))
+    });
+    group.finish();
+
+    // 非 Remote ID 信标：完整解析与预过滤的签名查找
+    let frame = plain_beacon();
+    let mut group = c.benchmark_group("beacon_without_remote_id");
+    group.bench_function("libwifi", |b| {
+        b.iter(|| matches!(libwifi::parse_frame(black_box(&frame), false), Ok(libwifi::Frame::Beacon(_))))
+    });
+    group.bench_function("signature_scan", |b| {
+        b.iter(|| remote_id::has_rid_signature(black_box(&frame)))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_parsers);
+criterion_main!(benches);
@@ -0,0 +1,71 @@
+//! 比较按 AnyMessage 枚举分发与按 Box<dyn Message> 分发解码单条消息的开销
+//!
+//! cargo bench --bench message_dispatch
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+#[allow(dead_code, unused_imports, clippy::module_inception, clippy::enum_variant_names)]
+#[path = "../src/message/mod.rs"]
+mod message;
+
+use message::base_message::BaseMessage;
+use message::message::{Message, MessageError};
+use message::position_vector_message::PositionVectorMessage;
+use message::system_message::SystemMessage;
+use message::{AnyMessage, DecodeOptions, DecodedMessage, MESSAGE_LEN};
+
+/// 旧的装箱分发方式，仅作对照
+fn boxed(data: &[u8]) -> Result<Box<dyn Message>, MessageError> {
+    let content = &data[1..];
+    match (data[0] >> 4) & 0x0f {
+        BaseMessage::MESSAGE_TYPE => Ok(Box::new(BaseMessage::from_bytes(content)?)),
+        PositionVectorMessage::MESSAGE_TYPE => Ok(Box::new(PositionVectorMessage::from_bytes(content)?)),
+        SystemMessage::MESSAGE_TYPE => Ok(Box::new(SystemMessage::from_bytes(content)?)),
+        t => Err(MessageError::UnknownMessageType(t)),
+    }
+}
+
+/// 基本 ID、位置向量、系统消息各一条
+fn sample_messages() -> Vec<[u8; MESSAGE_LEN]> {
+    let mut base = [0u8; MESSAGE_LEN];
+    base[1] = 0x12;
+    base[2..18].copy_from_slice(b"1581F5FKD229400A");
+    let mut position = [0u8; MESSAGE_LEN];
+    position[0] = 0x10;
+    position[5..9].copy_from_slice(&301_234_567i32.to_le_bytes());
+    let mut system = [0u8; MESSAGE_LEN];
+    system[0] = 0x40;
+    system[1] = 0x08;
+    vec![base, position, system]
+}
+
+fn bench_dispatch(c: &mut Criterion) {
+    let messages = sample_messages();
+    let options = DecodeOptions::default();
+    let mut group = c.benchmark_group("message_dispatch");
+    group.bench_function("enum", |b| {
+        b.iter(|| {
+            for data in &messages {
+                let _ = black_box(AnyMessage::from_bytes_with(black_box(data), &options));
+            }
+        })
+    });
+    group.bench_function("decoded_message", |b| {
+        b.iter(|| {
+            for data in &messages {
+                let _ = black_box(DecodedMessage::decode(black_box(data), &options));
+            }
+        })
+    });
+    group.bench_function("boxed", |b| {
+        b.iter(|| {
+            for data in &messages {
+                let _ = black_box(boxed(black_box(data)));
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_dispatch);
+criterion_main!(benches);
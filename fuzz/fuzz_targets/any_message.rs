@@ -0,0 +1,17 @@
+//! 任意字节按两种字段布局交给单条消息解码，只允许返回错误，不允许 panic
+//!
+//! cargo +nightly fuzz run any_message
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use wifi_capture::message::profile::FormatProfile;
+use wifi_capture::message::{AnyMessage, DecodeOptions};
+
+fuzz_target!(|data: &[u8]| {
+    for profile in [FormatProfile::National, FormatProfile::Astm] {
+        for lossy_utf8 in [false, true] {
+            let _ = AnyMessage::from_bytes_with(data, &DecodeOptions { lossy_utf8, profile });
+        }
+    }
+});
@@ -0,0 +1,26 @@
+//! 任意字节作为信标帧的 IE 列表：遍历 IE、重组 Remote ID 负载并解码消息包，
+//! 再把同样的字节当作完整的 radiotap 帧走一遍抓包解码路径
+//!
+//! 初始语料可用 tests/fixtures 中的样例（去掉注释转成二进制）：
+//!
+//! cargo +nightly fuzz run vendor_ie
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use wifi_capture::decode::{self, DecodeContext};
+
+/// 信标帧的 MAC 头 (24 字节) 与固定字段 (12 字节)
+const BEACON_HEADER: [u8; 36] = {
+    let mut header = [0u8; 36];
+    header[0] = 0x80;
+    header
+};
+
+fuzz_target!(|data: &[u8]| {
+    let mut frame = BEACON_HEADER.to_vec();
+    frame.extend_from_slice(data);
+    let _ = decode::parse_remote_id_frame(&frame);
+
+    decode::process_packet(data, &mut DecodeContext::default());
+});
@@ -0,0 +1,330 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use chrono::SecondsFormat;
+use serde_json::{json, Value};
+
+use crate::geofence::Zone;
+use crate::playback::parse_timestamp_ms;
+use crate::remote_id::ua_type_style;
+use crate::storage::FixSample;
+use crate::time_format;
+
+const HOUR_MS: i64 = 3_600_000;
+const DAY_MS: i64 = 24 * HOUR_MS;
+/// 单次聚合允许的最长时间段
+pub const MAX_WINDOW_MS: i64 = 31 * DAY_MS;
+
+/// 聚合的时段长度，按机器输出时区的整点/零点对齐
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Bucket {
+    Hour,
+    Day,
+}
+
+impl Bucket {
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "hour" => Some(Self::Hour),
+            "day" => Some(Self::Day),
+            _ => None,
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Hour => "hour",
+            Self::Day => "day",
+        }
+    }
+
+    fn len_ms(&self) -> i64 {
+        match self {
+            Self::Hour => HOUR_MS,
+            Self::Day => DAY_MS,
+        }
+    }
+
+    /// 包含该时刻的时段的起点
+    fn start_of(&self, ms: i64) -> i64 {
+        let offset_ms = time_format::output_timezone().offset_at(ms).local_minus_utc() as i64 * 1000;
+        (ms + offset_ms).div_euclid(self.len_ms()) * self.len_ms() - offset_ms
+    }
+}
+
+/// 时段内再按哪个维度分组
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GroupBy {
+    UaType,
+    Operator,
+    Zone,
+}
+
+impl GroupBy {
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "ua_type" => Some(Self::UaType),
+            "operator" => Some(Self::Operator),
+            "zone" => Some(Self::Zone),
+            _ => None,
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::UaType => "ua_type",
+            Self::Operator => "operator",
+            Self::Zone => "zone",
+        }
+    }
+}
+
+/// 聚合查询，也是结果缓存的键
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AggregateQuery {
+    pub from_ms: i64,
+    pub to_ms: i64,
+    pub bucket: Bucket,
+    pub group_by: Option<GroupBy>,
+}
+
+impl AggregateQuery {
+    /// 解析 `from=&to=&bucket=hour|day&group_by=ua_type|operator|zone`
+    ///
+    /// 时间为毫秒或 RFC 3339。`to` 默认为当前时段的结束，`from` 默认向前 24 个小时段或 30 个日时段，
+    /// 省略时间的查询在同一时段内得到相同的键，可以命中缓存。
+    pub fn parse(query: &str, now_ms: i64) -> Result<Self, String> {
+        let param = |name: &str| query.split('&').find_map(|kv| kv.strip_prefix(name)?.strip_prefix('='));
+        let time = |name: &str| match param(name) {
+            Some(value) => parse_timestamp_ms(value).map(Some).ok_or_else(|| format!("{} 应为毫秒或 RFC 3339 时间", name)),
+            None => Ok(None),
+        };
+        let bucket = match param("bucket") {
+            Some(value) => Bucket::parse(value).ok_or("bucket 应为 hour 或 day")?,
+            None => Bucket::Hour,
+        };
+        let group_by = match param("group_by") {
+            Some(value) => Some(GroupBy::parse(value).ok_or("group_by 应为 ua_type、operator 或 zone")?),
+            None => None,
+        };
+        let to_ms = time("to")?.unwrap_or_else(|| bucket.start_of(now_ms) + bucket.len_ms());
+        let default_span = match bucket {
+            Bucket::Hour => 24 * HOUR_MS,
+            Bucket::Day => 30 * DAY_MS,
+        };
+        let from_ms = time("from")?.unwrap_or(to_ms - default_span);
+        if from_ms >= to_ms {
+            return Err("from 应早于 to".into());
+        }
+        if to_ms - from_ms > MAX_WINDOW_MS {
+            return Err(format!("时间段不能超过 {} 天", MAX_WINDOW_MS / DAY_MS));
+        }
+        Ok(Self { from_ms, to_ms, bucket, group_by })
+    }
+}
+
+/// 按时段（及分组）统计出现的无人机数和定位记录数
+///
+/// 没有 Basic ID 的记录沿用该无人机在查询时间段内已知的 UA 类型；按区域分组时只统计
+/// 位于区域内的定位，同时位于多个区域的定位计入每个区域。
+pub fn aggregate(samples: &[FixSample], query: &AggregateQuery, zones: &[Zone]) -> Value {
+    let mut ua_types: HashMap<&str, u8> = HashMap::new();
+    for sample in samples {
+        if let Some(ua_type) = sample.ua_type {
+            ua_types.entry(&sample.uas_id).or_insert(ua_type);
+        }
+    }
+    let mut buckets: BTreeMap<(i64, String), (HashSet<&str>, u64)> = BTreeMap::new();
+    for sample in samples.iter().filter(|s| s.received_at_ms >= query.from_ms && s.received_at_ms < query.to_ms) {
+        let keys: Vec<String> = match query.group_by {
+            None => vec![String::new()],
+            Some(GroupBy::UaType) => vec![ua_type_style(ua_types.get(sample.uas_id.as_str()).copied()).0.to_string()],
+            Some(GroupBy::Operator) => vec![sample.operator_id.clone().unwrap_or_else(|| "unknown".into())],
+            Some(GroupBy::Zone) => match sample.position {
+                Some((lat, lon)) => zones.iter().filter(|z| z.contains(lat, lon)).map(|z| z.name.clone()).collect(),
+                None => Vec::new(),
+            },
+        };
+        let start = query.bucket.start_of(sample.received_at_ms);
+        for key in keys {
+            let (drones, fixes) = buckets.entry((start, key)).or_default();
+            drones.insert(&sample.uas_id);
+            *fixes += 1;
+        }
+    }
+    let rows: Vec<Value> = buckets.into_iter().map(|((start, key), (drones, fixes))| {
+        let mut row = json!({
+            "start": time_format::format_ms(start, SecondsFormat::Secs),
+            "start_ms": start,
+            "drones": drones.len(),
+            "fixes": fixes,
+        });
+        if query.group_by.is_some() {
+            row["key"] = json!(key);
+        }
+        row
+    }).collect();
+    json!({
+        "from_ms": query.from_ms,
+        "to_ms": query.to_ms,
+        "bucket": query.bucket.as_str(),
+        "group_by": query.group_by.map(|g| g.as_str()),
+        "rows": rows,
+    })
+}
+
+/// 聚合结果缓存：同一查询在 `ttl_ms` 内直接返回上次的结果，不再查询数据库
+pub struct AggregateCache {
+    ttl_ms: i64,
+    entries: HashMap<AggregateQuery, (i64, Value)>,
+}
+
+impl AggregateCache {
+    pub fn new(ttl_ms: i64) -> Self {
+        Self { ttl_ms, entries: HashMap::new() }
+    }
+
+    pub fn get_or_compute<E>(&mut self, query: &AggregateQuery, now_ms: i64,
+                             compute: impl FnOnce() -> Result<Value, E>) -> Result<Value, E> {
+        self.entries.retain(|_, (at, _)| now_ms - *at < self.ttl_ms);
+        if let Some((_, value)) = self.entries.get(query) {
+            return Ok(value.clone());
+        }
+        let value = compute()?;
+        self.entries.insert(query.clone(), (now_ms, value.clone()));
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geofence::Shape;
+
+    fn sample(at: i64, uas_id: &str, ua_type: Option<u8>, position: Option<(f64, f64)>) -> FixSample {
+        FixSample { received_at_ms: at, uas_id: uas_id.into(), operator_id: None, ua_type, position }
+    }
+
+    // 2025-06-01T08:00:00Z
+    const START: i64 = 1_748_764_800_000;
+
+    fn samples() -> Vec<FixSample> {
+        vec![
+            sample(START + 60_000, "A", Some(2), Some((31.2, 121.4))),
+            sample(START + 120_000, "A", None, Some((31.2, 121.4))),
+            sample(START + 180_000, "B", None, None),
+            sample(START + HOUR_MS + 5_000, "A", None, Some((40.0, 116.0))),
+        ]
+    }
+
+    fn query(group_by: Option<GroupBy>) -> AggregateQuery {
+        AggregateQuery { from_ms: START, to_ms: START + 2 * HOUR_MS, bucket: Bucket::Hour, group_by }
+    }
+
+    fn keyed_fixes(result: &Value) -> Vec<(String, u64)> {
+        result["rows"].as_array().unwrap().iter()
+            .map(|r| (r["key"].as_str().unwrap().to_string(), r["fixes"].as_u64().unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_explicit_window() {
+        let parsed = AggregateQuery::parse(&format!("from={}&to={}&bucket=hour", START, START + 2 * HOUR_MS), 0).unwrap();
+        assert_eq!(parsed, query(None));
+        let parsed = AggregateQuery::parse("from=2025-06-01T08:00:00Z&to=2025-06-01T10:00:00Z&group_by=zone", 0).unwrap();
+        assert_eq!(parsed, query(Some(GroupBy::Zone)));
+    }
+
+    #[test]
+    fn test_parse_default_window_aligned_to_bucket() {
+        // 省略时间时按当前时段对齐，同一时段内得到相同的键
+        let now = START + 30 * 60_000;
+        let hourly = AggregateQuery::parse("", now).unwrap();
+        assert_eq!((hourly.from_ms, hourly.to_ms), (START + HOUR_MS - 24 * HOUR_MS, START + HOUR_MS));
+        let daily = AggregateQuery::parse("bucket=day&group_by=operator", now).unwrap();
+        assert_eq!(daily, AggregateQuery::parse("group_by=operator&bucket=day", now + 60_000).unwrap());
+        assert_eq!(daily.to_ms - daily.from_ms, 30 * DAY_MS);
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_parameters() {
+        assert!(AggregateQuery::parse("bucket=week", START).is_err());
+        assert!(AggregateQuery::parse("group_by=serial", START).is_err());
+        assert!(AggregateQuery::parse("from=yesterday", START).is_err());
+        assert!(AggregateQuery::parse("from=2&to=1", START).is_err());
+        assert!(AggregateQuery::parse(&format!("from=0&to={}", MAX_WINDOW_MS + 1), START).is_err());
+        assert!(AggregateQuery::parse(&format!("from=0&to={}", MAX_WINDOW_MS), START).is_ok());
+    }
+
+    #[test]
+    fn test_aggregate_by_hour() {
+        let result = aggregate(&samples(), &query(None), &[]);
+        let rows = result["rows"].as_array().unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0]["start"], "2025-06-01T08:00:00Z");
+        assert_eq!((rows[0]["drones"].as_u64(), rows[0]["fixes"].as_u64()), (Some(2), Some(3)));
+        assert_eq!((rows[1]["drones"].as_u64(), rows[1]["fixes"].as_u64()), (Some(1), Some(1)));
+        assert!(rows[0]["key"].is_null());
+        assert_eq!(result["bucket"], "hour");
+        assert!(result["group_by"].is_null());
+    }
+
+    #[test]
+    fn test_aggregate_skips_samples_outside_window() {
+        let mut samples = samples();
+        samples.push(sample(START - 1, "C", None, None));
+        samples.push(sample(START + 2 * HOUR_MS, "C", None, None));
+        let result = aggregate(&samples, &query(None), &[]);
+        assert_eq!(result["rows"][0]["drones"].as_u64(), Some(2));
+        assert_eq!(result["rows"].as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_group_by_ua_type_inherits_known_type() {
+        let result = aggregate(&samples(), &query(Some(GroupBy::UaType)), &[]);
+        assert_eq!(result["group_by"], "ua_type");
+        assert_eq!(keyed_fixes(&result), [
+            ("multirotor".to_string(), 2), ("unknown".to_string(), 1), ("multirotor".to_string(), 1),
+        ]);
+    }
+
+    #[test]
+    fn test_group_by_operator() {
+        let mut samples = samples();
+        samples[0].operator_id = Some("OP-1".into());
+        samples[1].operator_id = Some("OP-1".into());
+        let result = aggregate(&samples, &query(Some(GroupBy::Operator)), &[]);
+        assert_eq!(keyed_fixes(&result), [
+            ("OP-1".to_string(), 2), ("unknown".to_string(), 1), ("unknown".to_string(), 1),
+        ]);
+    }
+
+    #[test]
+    fn test_group_by_zone_counts_only_inside_fixes() {
+        let zone = |name: &str, radius_m| Zone {
+            name: name.into(),
+            shape: Shape::Circle { lat: 31.2, lon: 121.4, radius_m },
+        };
+        let zones = [zone("airport", 500.0), zone("district", 5_000.0)];
+        let result = aggregate(&samples(), &query(Some(GroupBy::Zone)), &zones);
+        assert_eq!(keyed_fixes(&result), [("airport".to_string(), 2), ("district".to_string(), 2)]);
+    }
+
+    #[test]
+    fn test_cache_expires_after_ttl() {
+        let mut cache = AggregateCache::new(60_000);
+        let mut computed = 0;
+        for at in [START, START + 1_000, START + 61_000] {
+            cache.get_or_compute(&query(None), at, || { computed += 1; Ok::<_, ()>(json!(computed)) }).unwrap();
+        }
+        assert_eq!(computed, 2);
+    }
+
+    #[test]
+    fn test_cache_keys_by_query_and_skips_errors() {
+        let mut cache = AggregateCache::new(60_000);
+        assert!(cache.get_or_compute(&query(None), START, || Err("db")).is_err());
+        assert_eq!(cache.get_or_compute(&query(None), START, || Ok::<_, ()>(json!(1))), Ok(json!(1)));
+        assert_eq!(cache.get_or_compute(&query(Some(GroupBy::Zone)), START, || Ok::<_, ()>(json!(2))), Ok(json!(2)));
+        assert_eq!(cache.get_or_compute(&query(None), START, || Ok::<_, ()>(json!(3))), Ok(json!(1)));
+    }
+}
@@ -0,0 +1,637 @@
+use std::collections::HashMap;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tracing::{error, warn};
+
+use crate::auth_verify::AuthVerification;
+use crate::authorization::AuthorizationStatus;
+use crate::egress::{self, Priority};
+use crate::event_log::DecodedEvent;
+use crate::geofence::FenceSide;
+use crate::logging::ALERT_TARGET;
+use crate::remote_id::ua_type_style;
+
+/// 告警条件
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "condition", rename_all = "snake_case")]
+pub enum Condition {
+    /// 同时活动（最近 60 秒内出现）的无人机数超过 `above`
+    ConcurrentDrones { above: usize },
+    /// 任一无人机距地高度超过 `above_m` 米
+    AltitudeAgl { above_m: f64 },
+    /// 检测到指定 UA 类型 (Basic ID 中的编码)
+    UaType { types: Vec<u8> },
+    /// 授权查询结果为未授权的无人机
+    Unauthorized,
+    /// 无人机与控制站水平距离超过 `above_m` 米，疑似超视距 (BVLOS) 飞行
+    OperatorDistance { above_m: f64 },
+    /// 无人机位于电子围栏内 (`side = "inside"`) 或围栏外，需配置 `[geofence]`；
+    /// `operator = true` 时按系统消息中的控制站位置判断
+    Geofence {
+        side: FenceSide,
+        #[serde(default)]
+        operator: bool,
+    },
+    /// 机队标注表中没有的 UAS ID，需配置 `fleet`
+    UnknownUas,
+    /// 认证消息未通过校验或无法校验的发射端，需配置 `auth_trust_store`；`invalid_only` 时只在签名不符时告警
+    Unverified {
+        #[serde(default)]
+        invalid_only: bool,
+    },
+}
+
+/// 告警通知渠道，日志总是写入
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Channel {
+    Webhook,   // alert_webhook
+    Mqtt,      // [mqtt] 的 alert_topic
+    Command,   // [alert_command]
+}
+
+/// 告警级别
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Info,
+    #[default]
+    Warning,
+    Critical,
+}
+
+/// 阈值告警规则
+///
+/// ```toml
+/// [[alert]]
+/// name = "crowded"
+/// condition = "concurrent_drones"
+/// above = 5
+///
+/// [[alert]]
+/// name = "too-high"
+/// condition = "altitude_agl"
+/// above_m = 120
+/// cooldown_s = 600       # 同一对象重复告警的最小间隔，默认 300 秒
+/// severity = "critical"  # info/warning/critical，默认 warning
+///
+/// [[alert]]
+/// name = "bvlos"
+/// condition = "operator_distance"
+/// above_m = 500
+///
+/// [[alert]]
+/// name = "intrusion"
+/// condition = "geofence"
+/// side = "inside"
+///
+/// [[alert]]
+/// name = "pilot-in-zone"
+/// condition = "geofence"
+/// side = "inside"
+/// operator = true                  # 按控制站位置判断
+///
+/// [[alert]]
+/// name = "stranger"
+/// condition = "unknown_uas"
+/// channels = ["mqtt", "command"]   # 只经这些渠道通知，默认所有已配置的渠道
+///
+/// [[alert]]
+/// name = "spoofed"
+/// condition = "unverified"
+/// invalid_only = true
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AlertRule {
+    pub name: String,
+    #[serde(flatten)]
+    pub condition: Condition,
+    #[serde(default = "default_cooldown_s")]
+    pub cooldown_s: u64,
+    #[serde(default)]
+    pub severity: Severity,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub channels: Vec<Channel>,
+}
+
+fn default_cooldown_s() -> u64 {
+    300
+}
+
+/// 触发的告警
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Alert {
+    pub rule: String,
+    pub severity: Severity,
+    pub subject: String,   // 触发对象：无人机 ID，聚合条件为 "*"
+    pub message: String,
+    pub at_ms: i64,
+    /// 单架无人机的告警附带其飞行授权状态（配置了授权查询时）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub authorization: Option<AuthorizationStatus>,
+    /// 规则限定的通知渠道，为空时发往所有渠道
+    #[serde(skip)]
+    pub channels: Vec<Channel>,
+}
+
+impl Alert {
+    pub fn notifies(&self, channel: Channel) -> bool {
+        self.channels.is_empty() || self.channels.contains(&channel)
+    }
+}
+
+/// 每条记录更新后评估告警规则
+#[derive(Default)]
+pub struct AlertEngine {
+    rules: Vec<AlertRule>,
+    last_seen: HashMap<String, i64>,
+    last_fired: HashMap<(usize, String), i64>,
+    pruned_ms: i64,
+}
+
+impl AlertEngine {
+    const ACTIVE_WINDOW_MS: i64 = 60_000;
+    /// 清理已过冷却期的触发记录的间隔
+    const PRUNE_INTERVAL_MS: i64 = 60_000;
+
+    pub fn new(rules: Vec<AlertRule>) -> Self {
+        Self { rules, ..Default::default() }
+    }
+
+    /// 各规则最近一次触发的 (规则名, 对象, 时间)，保存后重启时恢复冷却状态
+    pub fn cooldowns(&self) -> Vec<(String, String, i64)> {
+        self.last_fired.iter()
+            .filter_map(|((index, subject), at)| Some((self.rules.get(*index)?.name.clone(), subject.clone(), *at)))
+            .collect()
+    }
+
+    /// 恢复保存的冷却状态，按规则名对应到当前规则，已不存在的规则忽略
+    pub fn restore_cooldowns(&mut self, cooldowns: Vec<(String, String, i64)>) {
+        for (rule, subject, at) in cooldowns {
+            if let Some(index) = self.rules.iter().position(|r| r.name == rule) {
+                let fired = self.last_fired.entry((index, subject)).or_insert(at);
+                *fired = (*fired).max(at);
+            }
+        }
+    }
+
+    pub fn observe(&mut self, event: &DecodedEvent) -> Vec<Alert> {
+        if self.rules.is_empty() {
+            return Vec::new();
+        }
+        let r = &event.record;
+        let now = event.received_at_ms;
+        let drone = if r.rid.is_empty() { r.track_id.clone() } else { r.rid.clone() };
+        self.last_seen.insert(drone.clone(), now);
+        self.last_seen.retain(|_, t| now - *t <= Self::ACTIVE_WINDOW_MS);
+        if now - self.pruned_ms >= Self::PRUNE_INTERVAL_MS {
+            self.prune(now);
+        }
+
+        let mut alerts = Vec::new();
+        for (index, rule) in self.rules.iter().enumerate() {
+            let triggered = match &rule.condition {
+                Condition::ConcurrentDrones { above } => (self.last_seen.len() > *above)
+                    .then(|| ("*".to_string(), format!("{} 架无人机同时活动 (阈值 {})", self.last_seen.len(), above))),
+                Condition::AltitudeAgl { above_m } => (r.ground_altitude as f64 > *above_m)
+                    .then(|| (drone.clone(), format!("{} 距地高度 {} 米 (阈值 {})", drone, r.ground_altitude, above_m))),
+                Condition::UaType { types } => r.ua_type.filter(|t| types.contains(t))
+                    .map(|t| (drone.clone(), format!("检测到 {} 类型无人机 {}", ua_type_style(Some(t)).0, drone))),
+                Condition::Unauthorized => (r.authorization == Some(AuthorizationStatus::Unauthorized))
+                    .then(|| (drone.clone(), format!("{} 未获飞行授权", drone))),
+                Condition::OperatorDistance { above_m } => r.operator_distance_m.filter(|d| *d as f64 > *above_m)
+                    .map(|d| (drone.clone(), format!("{} 距控制站 {:.0} 米，疑似超视距飞行 (阈值 {})", drone, d, above_m))),
+                Condition::Geofence { side, operator: false } => (r.inside_geofence == Some(*side == FenceSide::Inside))
+                    .then(|| (drone.clone(), match side {
+                        FenceSide::Inside => format!("{} 位于围栏区域 {}", drone, r.geofence_zones.join("、")),
+                        FenceSide::Outside => format!("{} 位于围栏外", drone),
+                    })),
+                Condition::Geofence { side, operator: true } => (r.operator_inside_geofence == Some(*side == FenceSide::Inside))
+                    .then(|| (drone.clone(), match side {
+                        FenceSide::Inside => format!("{} 的控制站位于围栏区域 {}", drone, r.operator_geofence_zones.join("、")),
+                        FenceSide::Outside => format!("{} 的控制站位于围栏外", drone),
+                    })),
+                Condition::UnknownUas => (!r.rid.is_empty() && r.annotation.is_none())
+                    .then(|| (drone.clone(), format!("出现机队外的无人机 {}", drone))),
+                Condition::Unverified { invalid_only } => match r.auth_verification {
+                    Some(AuthVerification::Invalid) => Some((drone.clone(), format!("{} 认证签名校验失败", drone))),
+                    Some(AuthVerification::Unverified) if !invalid_only => Some((drone.clone(), format!("{} 未通过认证校验", drone))),
+                    _ => None,
+                },
+            };
+            let Some((subject, message)) = triggered else { continue };
+            let key = (index, subject.clone());
+            if self.last_fired.get(&key).is_some_and(|t| now - t < rule.cooldown_s as i64 * 1000) {
+                continue;
+            }
+            self.last_fired.insert(key, now);
+            let authorization = if subject == drone { r.authorization } else { None };
+            alerts.push(Alert {
+                rule: rule.name.clone(),
+                severity: rule.severity,
+                subject,
+                message,
+                at_ms: now,
+                authorization,
+                channels: rule.channels.clone(),
+            });
+        }
+        alerts
+    }
+
+    /// 丢弃已过冷却期的触发记录，长期运行时不随出现过的无人机数增长
+    fn prune(&mut self, now: i64) {
+        let rules = &self.rules;
+        self.last_fired.retain(|(index, _), at| rules.get(*index).is_some_and(|r| now - *at < r.cooldown_s as i64 * 1000));
+        self.pruned_ms = now;
+    }
+}
+
+/// 告警时执行的本地命令
+///
+/// 命令不经过 shell，告警字段通过环境变量 `ALERT_RULE`、`ALERT_SEVERITY`、`ALERT_SUBJECT`、
+/// `ALERT_MESSAGE`、`ALERT_AT_MS` 传入，标准输入为告警的 JSON。
+///
+/// ```toml
+/// [alert_command]
+/// program = "/usr/local/bin/page-oncall"
+/// args = ["--team", "airspace"]
+/// timeout_s = 30        # 超时未退出时结束进程
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AlertCommand {
+    pub program: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default = "default_timeout_s")]
+    pub timeout_s: u64,
+}
+
+fn default_timeout_s() -> u64 {
+    30
+}
+
+impl AlertCommand {
+    /// 执行一次命令并等待退出
+    pub fn run(&self, alert: &Alert) -> Result<(), String> {
+        let severity = serde_json::to_value(alert.severity).ok().and_then(|v| v.as_str().map(String::from)).unwrap_or_default();
+        let mut child = Command::new(&self.program)
+            .args(&self.args)
+            .env("ALERT_RULE", &alert.rule)
+            .env("ALERT_SEVERITY", severity)
+            .env("ALERT_SUBJECT", &alert.subject)
+            .env("ALERT_MESSAGE", &alert.message)
+            .env("ALERT_AT_MS", alert.at_ms.to_string())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .spawn()
+            .map_err(|e| e.to_string())?;
+        if let Some(mut stdin) = child.stdin.take() {
+            let _ = stdin.write_all(&serde_json::to_vec(alert).unwrap_or_default());
+        }
+        let deadline = Instant::now() + Duration::from_secs(self.timeout_s);
+        loop {
+            match child.try_wait().map_err(|e| e.to_string())? {
+                Some(status) if status.success() => return Ok(()),
+                Some(status) => return Err(format!("退出状态 {}", status)),
+                None if Instant::now() >= deadline => {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    return Err(format!("{} 秒内未退出", self.timeout_s));
+                }
+                None => thread::sleep(Duration::from_millis(50)),
+            }
+        }
+    }
+}
+
+/// 启动后台线程，逐条为告警执行命令，避免阻塞解码
+fn spawn_command(command: AlertCommand) -> mpsc::Sender<Alert> {
+    let (sender, receiver) = mpsc::channel::<Alert>();
+    thread::spawn(move || {
+        for alert in receiver {
+            if let Err(e) = command.run(&alert) {
+                error!("告警命令 {} 执行失败: {}", command.program, e);
+            }
+        }
+    });
+    sender
+}
+
+/// 告警去向：写入日志，配置了 webhook 或命令时由后台线程以 JSON POST 发送或执行命令；
+/// MQTT 告警主题由持有连接的发布端发送，见 [`Alert::notifies`]
+#[derive(Default)]
+pub struct AlertRouter {
+    webhook: Option<mpsc::Sender<Alert>>,
+    command: Option<mpsc::Sender<Alert>>,
+}
+
+impl AlertRouter {
+    pub fn new(webhook: Option<String>, command: Option<AlertCommand>) -> Self {
+        Self {
+            webhook: webhook.map(|url| spawn_webhook(url, Priority::Alert)),
+            command: command.map(spawn_command),
+        }
+    }
+
+    pub fn route(&self, alert: Alert) {
+        warn!(target: ALERT_TARGET, "alert {}: {}", alert.rule, alert.message);
+        if let Some(command) = self.command.as_ref().filter(|_| alert.notifies(Channel::Command)) {
+            let _ = command.send(alert.clone());
+        }
+        if let Some(webhook) = self.webhook.as_ref().filter(|_| alert.notifies(Channel::Webhook)) {
+            let _ = webhook.send(alert);
+        }
+    }
+}
+
+/// 启动后台线程，把收到的消息依次以 JSON POST 到 `url`，按 `priority` 计入出站预算
+pub fn spawn_webhook<T: Serialize + Send + 'static>(url: String, priority: Priority) -> mpsc::Sender<T> {
+    let (sender, receiver) = mpsc::channel::<T>();
+    thread::spawn(move || {
+        let client = reqwest::blocking::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .unwrap();
+        for message in receiver {
+            let size = serde_json::to_vec(&message).map_or(0, |body| body.len());
+            if !egress::allow(priority, size + egress::HTTP_OVERHEAD) {
+                continue;
+            }
+            if let Err(e) = client.post(&url).json(&message).send().and_then(|r| r.error_for_status()) {
+                error!("通知发送失败 {}: {}", url, e);
+            }
+        }
+    });
+    sender
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::upload_data::UploadData;
+
+    fn rules(config: &str) -> Vec<AlertRule> {
+        toml::from_str::<HashMap<String, Vec<AlertRule>>>(config).unwrap().remove("alert").unwrap()
+    }
+
+    fn engine(config: &str) -> AlertEngine {
+        AlertEngine::new(rules(config))
+    }
+
+    fn fix(rid: &str, at: i64) -> DecodedEvent {
+        DecodedEvent { received_at_ms: at, record: UploadData { rid: rid.into(), ..Default::default() } }
+    }
+
+    fn fired(alerts: &[Alert]) -> Vec<&str> {
+        alerts.iter().map(|a| a.rule.as_str()).collect()
+    }
+
+    #[test]
+    fn test_rule_defaults() {
+        let rules = rules(r#"
+            [[alert]]
+            name = "too-high"
+            condition = "altitude_agl"
+            above_m = 120
+
+            [[alert]]
+            name = "crowded"
+            condition = "concurrent_drones"
+            above = 1
+            cooldown_s = 60
+            severity = "critical"
+            channels = ["mqtt"]
+        "#);
+        assert_eq!(rules[0].condition, Condition::AltitudeAgl { above_m: 120.0 });
+        assert_eq!((rules[0].cooldown_s, rules[0].severity, rules[0].channels.len()), (300, Severity::Warning, 0));
+        assert_eq!((rules[1].cooldown_s, rules[1].severity, rules[1].channels.clone()), (60, Severity::Critical, vec![Channel::Mqtt]));
+    }
+
+    #[test]
+    fn test_concurrent_drones() {
+        let mut engine = engine(r#"
+            [[alert]]
+            name = "crowded"
+            condition = "concurrent_drones"
+            above = 1
+            cooldown_s = 0
+        "#);
+        assert!(engine.observe(&fix("A", 0)).is_empty());
+        let alerts = engine.observe(&fix("B", 1_000));
+        assert_eq!(fired(&alerts), ["crowded"]);
+        assert_eq!(alerts[0].subject, "*");
+        // A 已超出活跃窗口
+        assert!(engine.observe(&fix("B", 62_000)).is_empty());
+    }
+
+    #[test]
+    fn test_altitude_agl_respects_cooldown() {
+        let mut engine = engine(r#"
+            [[alert]]
+            name = "too-high"
+            condition = "altitude_agl"
+            above_m = 120
+            cooldown_s = 60
+        "#);
+        let high = |at| {
+            let mut event = fix("A", at);
+            event.record.ground_altitude = 150;
+            event
+        };
+        assert!(engine.observe(&fix("A", 0)).is_empty());
+        assert_eq!(engine.observe(&high(1_000))[0].message, "A 距地高度 150 米 (阈值 120)");
+        assert!(engine.observe(&high(30_000)).is_empty());
+        assert_eq!(engine.observe(&high(61_000)).len(), 1);
+    }
+
+    #[test]
+    fn test_ua_type() {
+        let mut engine = engine(r#"
+            [[alert]]
+            name = "fixed-wing"
+            condition = "ua_type"
+            types = [1, 4]
+        "#);
+        let mut event = fix("A", 0);
+        assert!(engine.observe(&event).is_empty());
+        event.record.ua_type = Some(2);
+        assert!(engine.observe(&event).is_empty());
+        event.record.ua_type = Some(4);
+        assert_eq!(fired(&engine.observe(&event)), ["fixed-wing"]);
+    }
+
+    #[test]
+    fn test_unauthorized_carries_status() {
+        let mut engine = engine(r#"
+            [[alert]]
+            name = "no-permit"
+            condition = "unauthorized"
+        "#);
+        let mut event = fix("C", 0);
+        event.record.authorization = Some(AuthorizationStatus::Authorized);
+        assert!(engine.observe(&event).is_empty());
+        event.record.authorization = Some(AuthorizationStatus::Unauthorized);
+        let alerts = engine.observe(&event);
+        assert_eq!(fired(&alerts), ["no-permit"]);
+        assert_eq!(alerts[0].authorization, Some(AuthorizationStatus::Unauthorized));
+    }
+
+    #[test]
+    fn test_operator_distance() {
+        let mut engine = engine(r#"
+            [[alert]]
+            name = "bvlos"
+            condition = "operator_distance"
+            above_m = 500
+        "#);
+        let mut event = fix("C", 0);
+        event.record.operator_distance_m = Some(450.0);
+        assert!(engine.observe(&event).is_empty());
+        event.record.operator_distance_m = Some(812.0);
+        assert_eq!(engine.observe(&event)[0].message, "C 距控制站 812 米，疑似超视距飞行 (阈值 500)");
+    }
+
+    #[test]
+    fn test_geofence_inside_and_outside() {
+        let mut engine = engine(r#"
+            [[alert]]
+            name = "intrusion"
+            condition = "geofence"
+            side = "inside"
+
+            [[alert]]
+            name = "escaped"
+            condition = "geofence"
+            side = "outside"
+        "#);
+        // 未配置围栏或没有位置时不判断
+        assert!(engine.observe(&fix("D", 0)).is_empty());
+        let mut event = fix("D", 1_000);
+        event.record.inside_geofence = Some(true);
+        event.record.geofence_zones = vec!["plant".into(), "yard".into()];
+        let alerts = engine.observe(&event);
+        assert_eq!(fired(&alerts), ["intrusion"]);
+        assert_eq!(alerts[0].message, "D 位于围栏区域 plant、yard");
+        event.record.inside_geofence = Some(false);
+        assert_eq!(engine.observe(&event)[0].message, "D 位于围栏外");
+    }
+
+    #[test]
+    fn test_unknown_uas() {
+        let mut engine = engine(r#"
+            [[alert]]
+            name = "stranger"
+            condition = "unknown_uas"
+        "#);
+        let mut known = fix("F", 0);
+        known.record.annotation = Some(Default::default());
+        assert!(engine.observe(&known).is_empty());
+        // 没有 UAS ID 的记录无从比对
+        assert!(engine.observe(&fix("", 0)).is_empty());
+        assert_eq!(engine.observe(&fix("G", 0))[0].message, "出现机队外的无人机 G");
+    }
+
+    #[test]
+    fn test_unverified() {
+        let mut strict = engine(r#"
+            [[alert]]
+            name = "spoofed"
+            condition = "unverified"
+            invalid_only = true
+        "#);
+        let mut any = engine(r#"
+            [[alert]]
+            name = "unsigned"
+            condition = "unverified"
+        "#);
+        let mut event = fix("E", 0);
+        event.record.auth_verification = Some(AuthVerification::Unverified);
+        assert!(strict.observe(&event).is_empty());
+        assert_eq!(any.observe(&event)[0].message, "E 未通过认证校验");
+        event.record.auth_verification = Some(AuthVerification::Invalid);
+        assert_eq!(strict.observe(&event)[0].message, "E 认证签名校验失败");
+    }
+
+    #[test]
+    fn test_channels_limit_notifications() {
+        let mut engine = engine(r#"
+            [[alert]]
+            name = "stranger"
+            condition = "unknown_uas"
+            channels = ["mqtt"]
+
+            [[alert]]
+            name = "stranger-everywhere"
+            condition = "unknown_uas"
+        "#);
+        let alerts = engine.observe(&fix("G", 0));
+        assert!(alerts[0].notifies(Channel::Mqtt) && !alerts[0].notifies(Channel::Webhook));
+        assert!(alerts[1].notifies(Channel::Webhook) && alerts[1].notifies(Channel::Command));
+    }
+
+    #[test]
+    fn test_restored_cooldowns_suppress_alerts() {
+        let mut engine = engine(r#"
+            [[alert]]
+            name = "stranger"
+            condition = "unknown_uas"
+            cooldown_s = 60
+        "#);
+        engine.restore_cooldowns(vec![
+            ("stranger".into(), "G".into(), 10_000),
+            ("removed-rule".into(), "G".into(), 10_000),
+        ]);
+        assert_eq!(engine.cooldowns(), vec![("stranger".to_string(), "G".to_string(), 10_000)]);
+        assert!(engine.observe(&fix("G", 20_000)).is_empty());
+        assert_eq!(engine.observe(&fix("G", 70_000)).len(), 1);
+    }
+
+    #[test]
+    fn test_operator_geofence_rule() {
+        let mut engine = AlertEngine::new(toml::from_str::<HashMap<String, Vec<AlertRule>>>(r#"
+            [[alert]]
+            name = "pilot-in-zone"
+            condition = "geofence"
+            side = "inside"
+            operator = true
+        "#).unwrap().remove("alert").unwrap());
+        let mut event = DecodedEvent {
+            received_at_ms: 0,
+            record: UploadData { rid: "P".into(), inside_geofence: Some(true), ..Default::default() },
+        };
+        // 无人机在围栏内不触发控制站规则
+        assert!(engine.observe(&event).is_empty());
+        event.record.operator_inside_geofence = Some(true);
+        event.record.operator_geofence_zones = vec!["plant".into()];
+        assert_eq!(engine.observe(&event)[0].message, "P 的控制站位于围栏区域 plant");
+    }
+
+    #[test]
+    fn test_expired_cooldowns_are_pruned() {
+        let rule = AlertRule {
+            name: "too-high".into(),
+            condition: Condition::AltitudeAgl { above_m: 120.0 },
+            cooldown_s: 60,
+            severity: Severity::Warning,
+            channels: Vec::new(),
+        };
+        let mut engine = AlertEngine::new(vec![rule]);
+        let fix = |rid: String, at| DecodedEvent {
+            received_at_ms: at,
+            record: UploadData { rid, ground_altitude: 150, ..Default::default() },
+        };
+        for i in 0..100 {
+            assert_eq!(engine.observe(&fix(format!("D{}", i), i)).len(), 1);
+        }
+        assert_eq!(engine.cooldowns().len(), 100);
+        // 冷却期过后，之前触发过的无人机不再留在表中
+        engine.observe(&fix("LATE".into(), 120_000));
+        assert_eq!(engine.cooldowns().iter().map(|c| c.1.as_str()).collect::<Vec<_>>(), ["LATE"]);
+    }
+}
@@ -0,0 +1,220 @@
+use std::collections::HashMap;
+use std::net::UdpSocket;
+
+use chrono::{DateTime, Timelike, Utc};
+use tracing::{info, warn};
+
+use crate::egress::{self, Priority};
+use crate::event_log::DecodedEvent;
+use crate::position;
+
+/// ASTERIX 类别号
+pub const CATEGORY: u8 = 129;
+
+const FEET_PER_METER: f64 = 3.280_84;
+const MPS_PER_KNOT: f64 = 0.514_444;
+
+/// 实验性的 ASTERIX 风格 UAS 监视报文编码器（类别 129）
+///
+/// 按 ASTERIX 通用结构编码（CAT, LEN, FSPEC, 数据项），各数据项编码参照 CAT 062：
+///
+/// | FRN | 数据项    | 内容                              | 长度 |
+/// |-----|-----------|-----------------------------------|------|
+/// | 1   | I129/010  | 数据源 SAC/SIC                    | 2    |
+/// | 2   | I129/015  | 服务标识                          | 1    |
+/// | 3   | I129/070  | 日时间 (UTC, 1/128 s)             | 3    |
+/// | 4   | I129/040  | 航迹号                            | 2    |
+/// | 5   | I129/105  | WGS-84 位置 (180/2^25 度)         | 8    |
+/// | 6   | I129/130  | 几何高度 (6.25 ft)                | 2    |
+/// | 7   | I129/185  | 速度 Vx/Vy (0.25 m/s)             | 4    |
+/// | 8   | I129/245  | UAS ID (长度 + ASCII，最多 20 字节) | 1+n  |
+///
+/// 这不是正式发布的 EUROCONTROL 规范，接收端需按上表配置解码。
+pub struct AsterixEncoder {
+    sac: u8,
+    sic: u8,
+    tracks: HashMap<String, u16>,
+}
+
+impl AsterixEncoder {
+    pub fn new(sac: u8, sic: u8) -> Self {
+        Self { sac, sic, tracks: HashMap::new() }
+    }
+
+    /// 按首次出现顺序分配航迹号
+    fn track_number(&mut self, uas_id: &str) -> u16 {
+        let next = self.tracks.len() as u16 & 0x0fff;
+        *self.tracks.entry(uas_id.to_string()).or_insert(next)
+    }
+
+    /// 编码一个数据块（单条记录）；无有效位置时返回 None
+    pub fn encode(&mut self, event: &DecodedEvent) -> Option<Vec<u8>> {
+        let r = &event.record;
+        if !position::has_coordinates(r) {
+            return None;
+        }
+        let uas_id = if r.rid.is_empty() { &r.track_id } else { &r.rid };
+        let track = self.track_number(uas_id);
+
+        let mut items = Vec::new();
+        // I129/010
+        items.extend_from_slice(&[self.sac, self.sic]);
+        // I129/015
+        items.push(1);
+        // I129/070
+        let time = DateTime::<Utc>::from_timestamp_millis(event.received_at_ms).unwrap_or_default();
+        let seconds = time.num_seconds_from_midnight() as f64 + time.nanosecond() as f64 / 1e9;
+        items.extend_from_slice(&((seconds * 128.0) as u32).to_be_bytes()[1..]);
+        // I129/040
+        items.extend_from_slice(&track.to_be_bytes());
+        // I129/105：经纬度编码 1e-7 度转换为 180/2^25 度
+        let lsb = 180.0 / (1u32 << 25) as f64;
+        for e7 in [r.latitude, r.longitude] {
+            items.extend_from_slice(&((e7 as f64 * 1e-7 / lsb).round() as i32).to_be_bytes());
+        }
+        // I129/130
+        let altitude = (r.geometric_altitude as f64 * FEET_PER_METER / 6.25).round() as i16;
+        items.extend_from_slice(&altitude.to_be_bytes());
+        // I129/185：由地速和航迹角分解为东向/北向速度
+        let speed = r.ground_speed_knots() * MPS_PER_KNOT;
+        let track_deg = (r.track_angle as f64 + if r.track_direction { 180.0 } else { 0.0 }).to_radians();
+        for v in [speed * track_deg.sin(), speed * track_deg.cos()] {
+            items.extend_from_slice(&((v / 0.25).round() as i16).to_be_bytes());
+        }
+        // I129/245
+        let id = &uas_id.as_bytes()[..uas_id.len().min(20)];
+        items.push(id.len() as u8);
+        items.extend_from_slice(id);
+
+        // FSPEC：FRN1-7 全部存在且 FX=1，第二字节 FRN8 存在
+        let fspec = [0b1111_1111, 0b1000_0000];
+        let len = (3 + fspec.len() + items.len()) as u16;
+        let mut block = vec![CATEGORY];
+        block.extend_from_slice(&len.to_be_bytes());
+        block.extend_from_slice(&fspec);
+        block.extend_from_slice(&items);
+        Some(block)
+    }
+}
+
+/// 通过 UDP 发送 ASTERIX 数据块
+pub struct AsterixSender {
+    socket: UdpSocket,
+    target: String,
+    encoder: AsterixEncoder,
+}
+
+impl AsterixSender {
+    pub fn new(target: &str, sac: u8, sic: u8) -> std::io::Result<Self> {
+        let socket = UdpSocket::bind("0.0.0.0:0")?;
+        info!("asterix cat {} output to udp://{}", CATEGORY, target);
+        Ok(Self { socket, target: target.to_string(), encoder: AsterixEncoder::new(sac, sic) })
+    }
+
+    pub fn publish(&mut self, event: &DecodedEvent) {
+        let Some(block) = self.encoder.encode(event) else { return };
+        // IP + UDP 头 28 字节
+        if !egress::allow(Priority::Bulk, block.len() + 28) {
+            return;
+        }
+        if let Err(e) = self.socket.send_to(&block, &self.target) {
+            warn!("asterix send to {} failed: {}", self.target, e);
+        }
+    }
+}
+
+/// 解析 "SAC:SIC"
+pub fn parse_sac_sic(s: &str) -> Option<(u8, u8)> {
+    let (sac, sic) = s.split_once(':')?;
+    Some((sac.parse().ok()?, sic.parse().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::upload_data::UploadData;
+
+    fn event(rid: &str) -> DecodedEvent {
+        let record = UploadData {
+            rid: rid.into(),
+            latitude: 450_000_000,
+            longitude: -900_000_000,
+            geometric_altitude: 100,
+            ground_speed: 10,
+            track_angle: 90,
+            ..Default::default()
+        };
+        DecodedEvent { received_at_ms: 3_600_000, record }
+    }
+
+    fn i16_at(block: &[u8], offset: usize) -> i16 {
+        i16::from_be_bytes([block[offset], block[offset + 1]])
+    }
+
+    #[test]
+    fn test_block_header_and_fspec() {
+        let block = AsterixEncoder::new(25, 7).encode(&event("1581F5FKD229400A")).unwrap();
+        assert_eq!(block[0], CATEGORY);
+        assert_eq!(u16::from_be_bytes([block[1], block[2]]) as usize, block.len());
+        assert_eq!(&block[3..8], &[0xff, 0x80, 25, 7, 1]);
+    }
+
+    #[test]
+    fn test_time_of_day() {
+        let mut event = event("A");
+        event.received_at_ms = 86_400_000 + 3_600_500;   // 次日 01:00:00.5
+        let block = AsterixEncoder::new(0, 0).encode(&event).unwrap();
+        assert_eq!(&block[8..11], &(3600u32 * 128 + 64).to_be_bytes()[1..]);
+    }
+
+    #[test]
+    fn test_track_numbers_follow_first_appearance() {
+        let mut encoder = AsterixEncoder::new(0, 0);
+        let track = |block: Vec<u8>| u16::from_be_bytes([block[11], block[12]]);
+        assert_eq!(track(encoder.encode(&event("A")).unwrap()), 0);
+        assert_eq!(track(encoder.encode(&event("B")).unwrap()), 1);
+        assert_eq!(track(encoder.encode(&event("A")).unwrap()), 0);
+    }
+
+    #[test]
+    fn test_position_and_altitude() {
+        let block = AsterixEncoder::new(0, 0).encode(&event("A")).unwrap();
+        assert_eq!(&block[13..17], &(1i32 << 23).to_be_bytes());   // 45°
+        assert_eq!(&block[17..21], &(-(1i32 << 24)).to_be_bytes()); // -90°
+        assert_eq!(i16_at(&block, 21), 52);                        // 100 米 ≈ 328 英尺 / 6.25
+    }
+
+    #[test]
+    fn test_velocity_components() {
+        // 10 节向东：Vx ≈ 5.14 m/s，Vy ≈ 0
+        let block = AsterixEncoder::new(0, 0).encode(&event("A")).unwrap();
+        assert_eq!((i16_at(&block, 23), i16_at(&block, 25)), (21, 0));
+        // 西向标志使航迹角加 180 度
+        let mut west = event("A");
+        west.record.track_direction = true;
+        let block = AsterixEncoder::new(0, 0).encode(&west).unwrap();
+        assert_eq!((i16_at(&block, 23), i16_at(&block, 25)), (-21, 0));
+    }
+
+    #[test]
+    fn test_uas_id_is_length_prefixed_and_capped() {
+        let block = AsterixEncoder::new(0, 0).encode(&event("1581F5FKD229400A")).unwrap();
+        assert_eq!(block[27], 16);
+        assert!(block.ends_with(b"1581F5FKD229400A"));
+        let block = AsterixEncoder::new(0, 0).encode(&event(&"X".repeat(25))).unwrap();
+        assert_eq!((block[27], block.len()), (20, 28 + 20));
+    }
+
+    #[test]
+    fn test_no_block_without_fix() {
+        let mut encoder = AsterixEncoder::new(25, 7);
+        assert!(encoder.encode(&DecodedEvent { received_at_ms: 0, record: UploadData::default() }).is_none());
+    }
+
+    #[test]
+    fn test_parse_sac_sic() {
+        assert_eq!(parse_sac_sic("25:7"), Some((25, 7)));
+        assert_eq!(parse_sac_sic("25"), None);
+        assert_eq!(parse_sac_sic("256:1"), None);
+    }
+}
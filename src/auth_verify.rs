@@ -0,0 +1,462 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use ring::signature::{UnparsedPublicKey, ECDSA_P256_SHA256_FIXED, ED25519};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::message::profile::ASTM_EPOCH;
+use crate::remote_id::from_hex;
+use crate::upload_data::{Authentication, UploadData};
+
+/// 认证类型：UAS ID 签名
+pub const UAS_ID_SIGNATURE: u8 = 1;
+/// 认证类型：运营人 ID 签名
+pub const OPERATOR_ID_SIGNATURE: u8 = 2;
+/// 认证类型：消息集签名
+pub const MESSAGE_SET_SIGNATURE: u8 = 3;
+
+/// 签名长度 (字节)，Ed25519 与 P-256 (r||s) 相同
+const SIGNATURE_LEN: usize = 64;
+const AUTH_MESSAGE_TYPE: u8 = 0x02;
+/// 认证时间戳与接收时间相差超过该值 (秒) 的签名视为重放
+const TIMESTAMP_WINDOW_S: i64 = 300;
+/// 校验结果在最后一次认证完成后保留的时长 (毫秒)，之后的记录重新视为未校验
+const RESULT_TTL_MS: i64 = 60_000;
+/// 未收齐的认证页在最后一页到达后保留的时长 (毫秒)
+const PENDING_TTL_MS: i64 = 30_000;
+/// 同时跟踪的 UAS ID 上限，超出时先清掉过期的，再淘汰最久未更新的
+const MAX_TRACKED: usize = 4096;
+
+/// 认证消息的签名校验结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AuthVerification {
+    Verified,     // 信任库中的公钥校验通过
+    Unverified,   // 没有认证数据、数据未收齐、类型不支持或信任库中没有对应公钥
+    Invalid,      // 有对应公钥但签名不符
+}
+
+/// 签名算法
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SignatureScheme {
+    Ed25519,
+    EcdsaP256,   // SHA-256，签名为定长 r||s，公钥为未压缩点 (65 字节)
+}
+
+/// 信任库中的一把公钥，按 UAS ID 或运营人 ID 匹配记录
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct TrustedKey {
+    #[serde(default)]
+    pub uas_id: Option<String>,
+    #[serde(default)]
+    pub operator_id: Option<String>,
+    pub scheme: SignatureScheme,
+    pub public_key: String,   // 十六进制
+}
+
+impl TrustedKey {
+    fn matches(&self, record: &UploadData) -> bool {
+        self.uas_id.as_deref() == Some(record.rid.as_str())
+            || (self.operator_id.is_some() && self.operator_id == record.operator_id)
+    }
+
+    fn verify(&self, message: &[u8], signature: &[u8]) -> bool {
+        let Some(key) = from_hex(&self.public_key) else { return false };
+        let algorithm: &'static dyn ring::signature::VerificationAlgorithm = match self.scheme {
+            SignatureScheme::Ed25519 => &ED25519,
+            SignatureScheme::EcdsaP256 => &ECDSA_P256_SHA256_FIXED,
+        };
+        UnparsedPublicKey::new(algorithm, key).verify(message, signature).is_ok()
+    }
+}
+
+#[derive(Deserialize)]
+struct TrustStoreFile {
+    #[serde(default)]
+    keys: Vec<TrustedKey>,
+}
+
+/// 把各页认证数据按页号拼接并截到第 0 页声明的长度；页未收齐时返回 `None`
+pub fn reassemble(auth: &Authentication) -> Option<Vec<u8>> {
+    let (last, length) = (auth.last_page_index?, auth.length?);
+    let mut data = Vec::new();
+    for page in 0..=last {
+        let page = auth.pages.iter().find(|p| p.page == page)?;
+        data.extend(from_hex(&page.data)?);
+    }
+    if data.len() < length as usize {
+        return None;
+    }
+    data.truncate(length as usize);
+    Some(data)
+}
+
+/// 各认证类型的签名内容，末尾附加认证时间戳 (小端 4 字节)：
+///
+/// - UAS ID 签名：Basic ID 中的 20 字节 UAS ID
+/// - 运营人 ID 签名：20 字节运营人 ID
+/// - 消息集签名：同一消息包中除认证消息外的各条 25 字节消息，按收到的顺序拼接
+///
+/// ASTM F3411 只规定认证消息的分页格式和认证类型，签名覆盖哪些字节由具体的认证方案决定。
+/// 以上布局是本库约定的格式，不是标准规定的；发射端须按此格式签名，
+/// 使用其它方案 (如 IETF DRIP) 签名的认证数据在这里只会判为 invalid。
+fn signed_content(auth_type: u8, timestamp: u32, record: &UploadData) -> Option<Vec<u8>> {
+    let mut content = match auth_type {
+        UAS_ID_SIGNATURE => match record.rid_raw.as_deref() {
+            Some(raw) => from_hex(raw)?,
+            None => padded_id(&record.rid),
+        },
+        OPERATOR_ID_SIGNATURE => padded_id(record.operator_id.as_deref()?),
+        MESSAGE_SET_SIGNATURE => {
+            let messages: Vec<Vec<u8>> = record.raw_messages.iter()
+                .filter_map(|hex| from_hex(hex))
+                .filter(|raw| raw.first().is_some_and(|b| b >> 4 != AUTH_MESSAGE_TYPE))
+                .collect();
+            if messages.is_empty() {
+                return None;
+            }
+            messages.concat()
+        }
+        _ => return None,
+    };
+    content.extend_from_slice(&timestamp.to_le_bytes());
+    Some(content)
+}
+
+fn padded_id(id: &str) -> Vec<u8> {
+    let mut bytes = id.as_bytes().to_vec();
+    bytes.resize(20, 0);
+    bytes
+}
+
+/// 认证消息校验：按 UAS ID 跨帧重组多页认证数据，收齐后用信任库中的运营人公钥校验签名，
+/// 结果在 [`RESULT_TTL_MS`] 内标注到该 UAS ID 之后的记录上
+///
+/// 认证时间戳与接收时间相差超过 [`TIMESTAMP_WINDOW_S`]，或不晚于该 UAS ID 上次通过校验的时间戳时，
+/// 即使签名正确也判为 invalid，防止截获的签名被重放。发射端在两次签名之间重复广播的同一轮认证
+/// (时间戳与上次通过校验的相同) 不重新校验，也不延长已有结果的有效期。UAS ID 和运营人 ID 签名的
+/// 结果只在记录中的 ID 与签名内容一致时沿用。签名内容的布局见 `signed_content`。
+///
+/// 信任库为 TOML 文件：
+///
+/// ```toml
+/// [[keys]]
+/// uas_id = "1581F5FKD229400A"   # 或 operator_id = "FIN87astrdge12k8"
+/// scheme = "ed25519"            # ed25519 / ecdsa_p256
+/// public_key = "3b6a27bc..."
+/// ```
+#[derive(Debug, Default)]
+pub struct AuthVerifier {
+    keys: Vec<TrustedKey>,
+    pending: HashMap<String, Pending>,
+    results: HashMap<String, Outcome>,
+}
+
+/// 正在重组的认证数据
+#[derive(Debug)]
+struct Pending {
+    auth: Authentication,
+    updated_ms: i64,
+}
+
+/// 一次认证完成后的校验结果
+#[derive(Debug)]
+struct Outcome {
+    result: AuthVerification,
+    auth_type: u8,
+    timestamp: u32,
+    /// 签名覆盖的内容，沿用结果前与后续记录比较
+    content: Option<Vec<u8>>,
+    /// 最近一次通过校验的认证时间戳
+    verified_timestamp: Option<u32>,
+    completed_ms: i64,
+}
+
+impl AuthVerifier {
+    pub fn new(keys: Vec<TrustedKey>) -> Self {
+        Self { keys, ..Default::default() }
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let file: TrustStoreFile = toml::from_str(&text).map_err(|e| e.to_string())?;
+        if let Some(key) = file.keys.iter().find(|k| from_hex(&k.public_key).is_none()) {
+            return Err(format!("公钥不是有效的十六进制: {}", key.public_key));
+        }
+        Ok(Self::new(file.keys))
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// 合并记录中的认证页，返回该 UAS ID 当前的校验结果；`now_ms` 为记录的接收时间 (Unix 毫秒)
+    pub fn observe(&mut self, record: &UploadData, now_ms: i64) -> AuthVerification {
+        if record.rid.is_empty() {
+            return AuthVerification::Unverified;
+        }
+        if let Some(auth) = &record.auth {
+            if !self.pending.contains_key(&record.rid) {
+                self.evict(now_ms);
+            }
+            let pending = self.pending.entry(record.rid.clone())
+                .or_insert_with(|| Pending { auth: auth.clone(), updated_ms: now_ms });
+            // 新一轮认证 (时间戳或类型变化) 或上一轮早已过期时丢弃之前未收齐的页
+            if pending.auth.auth_type != auth.auth_type
+                || matches!((pending.auth.timestamp, auth.timestamp), (Some(a), Some(b)) if a != b)
+                || now_ms - pending.updated_ms > PENDING_TTL_MS
+            {
+                pending.auth = auth.clone();
+            }
+            pending.updated_ms = now_ms;
+            let pending = &mut pending.auth;
+            for page in &auth.pages {
+                if !pending.pages.iter().any(|p| p.page == page.page) {
+                    pending.pages.push(page.clone());
+                }
+            }
+            pending.pages.sort_by_key(|p| p.page);
+            if auth.last_page_index.is_some() {
+                pending.last_page_index = auth.last_page_index;
+                pending.length = auth.length;
+                pending.timestamp = auth.timestamp;
+            }
+            if let Some(data) = reassemble(pending) {
+                let pending = self.pending.remove(&record.rid).unwrap().auth;
+                let repeated = pending.timestamp.is_some()
+                    && self.results.get(&record.rid).is_some_and(|o| o.verified_timestamp == pending.timestamp);
+                if !repeated {
+                    if !self.results.contains_key(&record.rid) {
+                        self.evict(now_ms);
+                    }
+                    let outcome = self.verify(&pending, &data, record, now_ms);
+                    self.results.insert(record.rid.clone(), outcome);
+                }
+            }
+        }
+        match self.results.get(&record.rid) {
+            Some(outcome) if now_ms - outcome.completed_ms <= RESULT_TTL_MS && outcome.applies_to(record) => outcome.result,
+            _ => AuthVerification::Unverified,
+        }
+    }
+
+    fn verify(&self, auth: &Authentication, data: &[u8], record: &UploadData, now_ms: i64) -> Outcome {
+        let previous = self.results.get(&record.rid).and_then(|o| o.verified_timestamp);
+        let timestamp = auth.timestamp.unwrap_or_default();
+        let content = auth.timestamp.and_then(|t| signed_content(auth.auth_type, t, record));
+        let mut outcome = Outcome {
+            result: AuthVerification::Unverified,
+            auth_type: auth.auth_type,
+            timestamp,
+            content: content.clone(),
+            verified_timestamp: previous,
+            completed_ms: now_ms,
+        };
+        let keys: Vec<&TrustedKey> = self.keys.iter().filter(|k| k.matches(record)).collect();
+        let Some(content) = content.filter(|_| !keys.is_empty()) else {
+            return outcome;
+        };
+        let fresh = (ASTM_EPOCH as i64 + timestamp as i64 - now_ms / 1000).abs() <= TIMESTAMP_WINDOW_S
+            && previous.is_none_or(|p| timestamp > p);
+        let signed = data.len() >= SIGNATURE_LEN && keys.iter().any(|k| k.verify(&content, &data[..SIGNATURE_LEN]));
+        outcome.result = if signed && fresh {
+            outcome.verified_timestamp = Some(timestamp);
+            AuthVerification::Verified
+        } else {
+            AuthVerification::Invalid
+        };
+        outcome
+    }
+
+    /// 跟踪的 UAS ID 达到上限时先清掉过期的，仍然满时淘汰最久未更新的
+    fn evict(&mut self, now_ms: i64) {
+        if self.pending.len() >= MAX_TRACKED {
+            self.pending.retain(|_, p| now_ms - p.updated_ms <= PENDING_TTL_MS);
+            if self.pending.len() >= MAX_TRACKED
+                && let Some(oldest) = self.pending.iter().min_by_key(|(_, p)| p.updated_ms).map(|(k, _)| k.clone())
+            {
+                self.pending.remove(&oldest);
+            }
+        }
+        if self.results.len() >= MAX_TRACKED {
+            self.results.retain(|_, o| now_ms - o.completed_ms <= RESULT_TTL_MS);
+            if self.results.len() >= MAX_TRACKED
+                && let Some(oldest) = self.results.iter().min_by_key(|(_, o)| o.completed_ms).map(|(k, _)| k.clone())
+            {
+                self.results.remove(&oldest);
+            }
+        }
+    }
+}
+
+impl Outcome {
+    /// UAS ID 和运营人 ID 签名只覆盖 ID 本身，记录中的 ID 变了就不再沿用结果
+    fn applies_to(&self, record: &UploadData) -> bool {
+        match self.auth_type {
+            UAS_ID_SIGNATURE | OPERATOR_ID_SIGNATURE =>
+                self.content.is_some() && signed_content(self.auth_type, self.timestamp, record) == self.content,
+            _ => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message::auth_message::AuthMessage;
+    use crate::remote_id::to_hex;
+    use ring::signature::{Ed25519KeyPair, KeyPair};
+
+    /// 按认证消息的分页方式切分：第 0 页 17 字节，其余每页 23 字节
+    fn pages(auth_type: u8, timestamp: u32, data: &[u8]) -> Vec<AuthMessage> {
+        let mut chunks = vec![&data[..17]];
+        chunks.extend(data[17..].chunks(23));
+        let last = chunks.len() as u8 - 1;
+        chunks.into_iter().enumerate().map(|(i, chunk)| AuthMessage {
+            auth_type,
+            page_number: i as u8,
+            last_page_index: (i == 0).then_some(last),
+            length: (i == 0).then_some(data.len() as u8),
+            timestamp: (i == 0).then_some(timestamp),
+            data: chunk.to_vec(),
+        }).collect()
+    }
+
+    const UAS_ID: &str = "1581F5FKD229400A";
+
+    fn signer() -> (Ed25519KeyPair, AuthVerifier) {
+        let pair = Ed25519KeyPair::from_seed_unchecked(&[7u8; 32]).unwrap();
+        let key = TrustedKey {
+            uas_id: Some(UAS_ID.into()),
+            operator_id: None,
+            scheme: SignatureScheme::Ed25519,
+            public_key: to_hex(pair.public_key().as_ref()),
+        };
+        (pair, AuthVerifier::new(vec![key]))
+    }
+
+    /// 认证时间戳对应的 Unix 毫秒
+    fn unix_ms(timestamp: u32) -> i64 {
+        (ASTM_EPOCH as i64 + timestamp as i64) * 1000
+    }
+
+    fn sign(pair: &Ed25519KeyPair, timestamp: u32) -> Vec<u8> {
+        let content = signed_content(UAS_ID_SIGNATURE, timestamp, &UploadData { rid: UAS_ID.into(), ..Default::default() }).unwrap();
+        pair.sign(&content).as_ref().to_vec()
+    }
+
+    /// 依次送入一轮认证的全部页，返回最后一页后的结果
+    fn deliver(verifier: &mut AuthVerifier, record: &mut UploadData, timestamp: u32, signature: &[u8], now_ms: i64) -> AuthVerification {
+        let mut result = AuthVerification::Unverified;
+        for message in pages(UAS_ID_SIGNATURE, timestamp, signature) {
+            record.auth = Some(Authentication::from(&message));
+            result = verifier.observe(record, now_ms);
+        }
+        record.auth = None;
+        result
+    }
+
+    #[test]
+    fn test_uas_id_signature() {
+        let (pair, mut verifier) = signer();
+        let timestamp = 236_000_000;
+        let now_ms = unix_ms(timestamp) + 2_000;
+        let signature = sign(&pair, timestamp);
+
+        // 分页跨帧到达，收齐前为 unverified
+        let messages = pages(UAS_ID_SIGNATURE, timestamp, &signature);
+        let mut record = UploadData { rid: UAS_ID.into(), ..Default::default() };
+        let mut results = Vec::new();
+        for message in messages.iter().rev() {
+            record.auth = Some(Authentication::from(message));
+            results.push(verifier.observe(&record, now_ms));
+        }
+        assert_eq!(messages.len(), 4);
+        assert_eq!(results, [AuthVerification::Unverified, AuthVerification::Unverified, AuthVerification::Unverified, AuthVerification::Verified]);
+        record.auth = None;
+        assert_eq!(verifier.observe(&record, now_ms), AuthVerification::Verified);
+
+        // 信任库中没有该 UAS ID 的公钥
+        let other = UploadData { rid: "OTHER".into(), auth: Some(Authentication::from(&messages[0])), ..Default::default() };
+        assert_eq!(verifier.observe(&other, now_ms), AuthVerification::Unverified);
+    }
+
+    #[test]
+    fn test_forged_signature_is_invalid() {
+        let (pair, mut verifier) = signer();
+        let timestamp = 236_000_000;
+        let mut forged = sign(&pair, timestamp);
+        forged[0] ^= 1;
+        let mut record = UploadData { rid: UAS_ID.into(), ..Default::default() };
+        assert_eq!(deliver(&mut verifier, &mut record, timestamp, &forged, unix_ms(timestamp)), AuthVerification::Invalid);
+    }
+
+    #[test]
+    fn test_replayed_signature_is_invalid() {
+        let (pair, mut verifier) = signer();
+        let timestamp = 236_000_000;
+        let signature = sign(&pair, timestamp);
+        let mut record = UploadData { rid: UAS_ID.into(), ..Default::default() };
+
+        // 一天后重放截获的签名：签名本身正确，但时间戳超出窗口
+        let later = unix_ms(timestamp) + 86_400_000;
+        assert_eq!(deliver(&mut verifier, &mut record, timestamp, &signature, later), AuthVerification::Invalid);
+
+        // 窗口内通过后，再收到更早时间戳的签名也视为重放
+        let mut verifier = signer().1;
+        let newer = timestamp + 60;
+        assert_eq!(deliver(&mut verifier, &mut record, newer, &sign(&pair, newer), unix_ms(newer)), AuthVerification::Verified);
+        assert_eq!(deliver(&mut verifier, &mut record, timestamp, &signature, unix_ms(newer) + 1_000), AuthVerification::Invalid);
+    }
+
+    #[test]
+    fn test_repeated_signature_does_not_extend_result() {
+        let (pair, mut verifier) = signer();
+        let timestamp = 236_000_000;
+        let signature = sign(&pair, timestamp);
+        let now_ms = unix_ms(timestamp);
+        let mut record = UploadData { rid: UAS_ID.into(), ..Default::default() };
+        assert_eq!(deliver(&mut verifier, &mut record, timestamp, &signature, now_ms), AuthVerification::Verified);
+
+        // 同一轮认证再次收齐时不重新校验，结果仍在第一次通过后的有效期内到期
+        assert_eq!(deliver(&mut verifier, &mut record, timestamp, &signature, now_ms + 10_000), AuthVerification::Verified);
+        let expired = now_ms + RESULT_TTL_MS + 1;
+        assert_eq!(deliver(&mut verifier, &mut record, timestamp, &signature, expired), AuthVerification::Unverified);
+
+        // 时间戳更新的签名才会刷新结果
+        let newer = timestamp + 90;
+        assert_eq!(deliver(&mut verifier, &mut record, newer, &sign(&pair, newer), expired), AuthVerification::Verified);
+    }
+
+    #[test]
+    fn test_result_expires() {
+        let (pair, mut verifier) = signer();
+        let timestamp = 236_000_000;
+        let now_ms = unix_ms(timestamp);
+        let mut record = UploadData { rid: UAS_ID.into(), ..Default::default() };
+        assert_eq!(deliver(&mut verifier, &mut record, timestamp, &sign(&pair, timestamp), now_ms), AuthVerification::Verified);
+
+        // 之后不再发认证消息的记录 (如冒用该 UAS ID 的发射端) 不会一直沿用 verified
+        assert_eq!(verifier.observe(&record, now_ms + RESULT_TTL_MS), AuthVerification::Verified);
+        assert_eq!(verifier.observe(&record, now_ms + RESULT_TTL_MS + 1), AuthVerification::Unverified);
+    }
+
+    #[test]
+    fn test_tracked_ids_are_bounded() {
+        let mut verifier = AuthVerifier::default();
+        let message = pages(UAS_ID_SIGNATURE, 1, &[0u8; 64]).remove(0);
+        for i in 0..MAX_TRACKED + 10 {
+            let record = UploadData { rid: format!("RID-{}", i), auth: Some(Authentication::from(&message)), ..Default::default() };
+            verifier.observe(&record, i as i64);
+        }
+        assert_eq!(verifier.pending.len(), MAX_TRACKED);
+        assert!(verifier.pending.contains_key(&format!("RID-{}", MAX_TRACKED + 9)));
+        assert!(!verifier.pending.contains_key("RID-0"));
+    }
+}
@@ -0,0 +1,191 @@
+use std::collections::HashMap;
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use reqwest::blocking::Client;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+/// 飞行授权状态，由 USS/主管部门接口按 UAS ID 查询
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AuthorizationStatus {
+    Authorized,
+    Unknown,
+    Unauthorized,
+}
+
+/// 飞行授权查询接口
+///
+/// `url` 中的 `{uas_id}` 替换为 UAS ID；接口返回 `{"status": "authorized" | "unauthorized"}`，
+/// 其它结果（含查询失败）记为 unknown。
+///
+/// ```toml
+/// [authorization]
+/// url = "https://uss.example.com/v1/authorizations/{uas_id}"
+/// token = "..."          # 可选，以 Bearer 令牌发送
+/// cache_minutes = 10
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuthorizationConfig {
+    pub url: String,
+    #[serde(default)]
+    pub token: Option<String>,
+    #[serde(default = "default_cache_minutes")]
+    pub cache_minutes: u64,
+}
+
+fn default_cache_minutes() -> u64 {
+    10
+}
+
+#[derive(Deserialize)]
+struct StatusResponse {
+    status: String,
+}
+
+/// 解析接口响应
+pub fn parse_status(body: &str) -> AuthorizationStatus {
+    match serde_json::from_str::<StatusResponse>(body).map(|r| r.status.to_ascii_lowercase()).as_deref() {
+        Ok("authorized" | "approved" | "activated") => AuthorizationStatus::Authorized,
+        Ok("unauthorized" | "denied" | "rejected") => AuthorizationStatus::Unauthorized,
+        _ => AuthorizationStatus::Unknown,
+    }
+}
+
+/// 带缓存的授权查询客户端
+///
+/// 解码线程只读缓存；未命中或已过期时把查询交给后台线程，本次先记为 unknown，
+/// 结果返回后后续记录即带上查询结果。
+pub struct AuthorizationClient {
+    cache: Arc<Mutex<HashMap<String, (AuthorizationStatus, Instant)>>>,
+    lookups: mpsc::Sender<String>,
+    ttl: Duration,
+}
+
+impl AuthorizationClient {
+    pub fn new(config: AuthorizationConfig) -> Self {
+        let ttl = Duration::from_secs(config.cache_minutes.max(1) * 60);
+        let cache = Arc::new(Mutex::new(HashMap::new()));
+        let (lookups, receiver) = mpsc::channel::<String>();
+        let results = cache.clone();
+        thread::spawn(move || {
+            let client = Client::builder()
+                .timeout(Duration::from_secs(10))
+                .build()
+                .unwrap();
+            for uas_id in receiver {
+                let status = lookup(&client, &config, &uas_id);
+                results.lock().unwrap().insert(uas_id, (status, Instant::now()));
+            }
+        });
+        Self { cache, lookups, ttl }
+    }
+
+    pub fn status(&self, uas_id: &str) -> AuthorizationStatus {
+        let mut cache = self.cache.lock().unwrap();
+        if let Some((status, at)) = cache.get(uas_id)
+            && at.elapsed() < self.ttl
+        {
+            return *status;
+        }
+        // 查询期间占位，避免同一 UAS ID 重复排队
+        let stale = cache.insert(uas_id.to_string(), (AuthorizationStatus::Unknown, Instant::now()));
+        let _ = self.lookups.send(uas_id.to_string());
+        stale.map_or(AuthorizationStatus::Unknown, |(status, _)| status)
+    }
+}
+
+fn lookup(client: &Client, config: &AuthorizationConfig, uas_id: &str) -> AuthorizationStatus {
+    let url = config.url.replace("{uas_id}", uas_id);
+    let mut request = client.get(&url);
+    if let Some(token) = &config.token {
+        request = request.bearer_auth(token);
+    }
+    match request.send().and_then(|r| r.error_for_status()).and_then(|r| r.text()) {
+        Ok(body) => parse_status(&body),
+        Err(e) => {
+            warn!("authorization lookup for {} failed: {}", uas_id, e);
+            AuthorizationStatus::Unknown
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    /// 单次应答的本地接口，返回收到的请求头
+    fn serve_once(body: &'static str) -> (String, thread::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/v1/{{uas_id}}", listener.local_addr().unwrap());
+        let handle = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0u8; 2048];
+            let n = stream.read(&mut request).unwrap();
+            write!(stream, "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", body.len(), body).unwrap();
+            String::from_utf8_lossy(&request[..n]).into_owned()
+        });
+        (url, handle)
+    }
+
+    #[test]
+    fn test_parse_status_authorized() {
+        assert_eq!(parse_status(r#"{"status": "authorized", "valid_until": "2026-06-01T00:00:00Z"}"#),
+            AuthorizationStatus::Authorized);
+        assert_eq!(parse_status(r#"{"status": "APPROVED"}"#), AuthorizationStatus::Authorized);
+        assert_eq!(parse_status(r#"{"status": "activated"}"#), AuthorizationStatus::Authorized);
+    }
+
+    #[test]
+    fn test_parse_status_unauthorized() {
+        assert_eq!(parse_status(r#"{"status": "Unauthorized"}"#), AuthorizationStatus::Unauthorized);
+        assert_eq!(parse_status(r#"{"status": "denied"}"#), AuthorizationStatus::Unauthorized);
+        assert_eq!(parse_status(r#"{"status": "rejected"}"#), AuthorizationStatus::Unauthorized);
+    }
+
+    #[test]
+    fn test_parse_status_unknown() {
+        assert_eq!(parse_status(r#"{"status": "pending"}"#), AuthorizationStatus::Unknown);
+        assert_eq!(parse_status(r#"{"state": "authorized"}"#), AuthorizationStatus::Unknown);
+        assert_eq!(parse_status("<html>"), AuthorizationStatus::Unknown);
+    }
+
+    #[test]
+    fn test_config_defaults() {
+        let config: AuthorizationConfig = toml::from_str(r#"url = "https://uss.example.com/{uas_id}""#).unwrap();
+        assert_eq!((config.token, config.cache_minutes), (None, 10));
+    }
+
+    #[test]
+    fn test_client_queries_in_background() {
+        let (url, server) = serve_once(r#"{"status": "authorized"}"#);
+        let client = AuthorizationClient::new(AuthorizationConfig { url, token: Some("secret".into()), cache_minutes: 10 });
+        assert_eq!(client.status("UAS-1"), AuthorizationStatus::Unknown);
+
+        let request = server.join().unwrap();
+        assert!(request.starts_with("GET /v1/UAS-1 "));
+        assert!(request.to_ascii_lowercase().contains("authorization: bearer secret"));
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while client.status("UAS-1") != AuthorizationStatus::Authorized {
+            assert!(Instant::now() < deadline, "lookup result never cached");
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn test_client_failed_lookup_is_unknown() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/{{uas_id}}", listener.local_addr().unwrap());
+        drop(listener);
+        let client = AuthorizationClient::new(AuthorizationConfig { url, token: None, cache_minutes: 10 });
+        assert_eq!(client.status("UAS-1"), AuthorizationStatus::Unknown);
+        thread::sleep(Duration::from_millis(100));
+        assert_eq!(client.status("UAS-1"), AuthorizationStatus::Unknown);
+    }
+}
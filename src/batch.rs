@@ -0,0 +1,229 @@
+use std::collections::HashSet;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::decode::DecodeContext;
+use crate::event_log::DecodedEvent;
+use crate::import::ImportFormat;
+use crate::mapped_pcap::decode_pcap;
+use crate::stats::FrameClass;
+use crate::upload_data::UploadData;
+
+/// 单个 pcap 文件的解码统计
+#[derive(Debug, Default)]
+pub struct FileReport {
+    pub path: PathBuf,
+    pub frames: u64,
+    pub records: u64,
+    pub drones: HashSet<String>,
+    pub undecodable: u64,
+    pub malformed: u64,
+    pub elapsed: Duration,
+    pub error: Option<String>,
+}
+
+impl FileReport {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path, ..Default::default() }
+    }
+
+    pub fn add_record(&mut self, record: &UploadData) {
+        self.records += 1;
+        self.drones.insert(if record.rid.is_empty() { record.track_id.clone() } else { record.rid.clone() });
+    }
+}
+
+/// 目录下的 pcap 文件（不递归），按文件名排序
+pub fn list_pcaps(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.is_file())
+        .filter(|p| ImportFormat::detect(p) == Some(ImportFormat::Pcap))
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+/// 实际使用的解码线程数：不超过文件数，至少 1 个
+pub fn thread_count(jobs: usize, files: usize) -> usize {
+    jobs.clamp(1, files.max(1))
+}
+
+enum BatchMessage {
+    Event(Box<DecodedEvent>),
+    Done(FileReport),
+}
+
+/// 并行解码一组 pcap 文件
+///
+/// 每个工作线程一次处理一个文件，解码出的事件经通道交回当前线程由 `on_event` 统一处理
+/// （例如 SQLite 单写者写库）；每个文件处理完时调用 `on_file`。返回按路径排序的各文件统计。
+pub fn decode_files<E, F>(files: Vec<PathBuf>, jobs: usize, mut on_event: E, mut on_file: F) -> Vec<FileReport>
+where
+    E: FnMut(DecodedEvent),
+    F: FnMut(&FileReport),
+{
+    let jobs = thread_count(jobs, files.len());
+    let queue = Mutex::new(files.into_iter());
+    let (sender, receiver) = mpsc::sync_channel::<BatchMessage>(4096);
+    let mut reports = Vec::new();
+    thread::scope(|scope| {
+        for _ in 0..jobs {
+            let sender = sender.clone();
+            let queue = &queue;
+            scope.spawn(move || loop {
+                let Some(path) = queue.lock().unwrap().next() else { return };
+                let started = Instant::now();
+                let mut ctx = DecodeContext::default();
+                let mut report = FileReport::new(path.clone());
+                let result = decode_pcap(&path, &mut ctx, |event| {
+                    report.add_record(&event.record);
+                    sender.send(BatchMessage::Event(Box::new(event))).map_err(|e| e.to_string())
+                });
+                match result {
+                    Ok(frames) => report.frames = frames as u64,
+                    Err(e) => report.error = Some(e),
+                }
+                report.undecodable = ctx.stats.count(FrameClass::Undecodable);
+                report.malformed = ctx.stats.count(FrameClass::MalformedPack);
+                report.elapsed = started.elapsed();
+                if sender.send(BatchMessage::Done(report)).is_err() {
+                    return;
+                }
+            });
+        }
+        drop(sender);
+        for message in receiver {
+            match message {
+                BatchMessage::Event(event) => on_event(*event),
+                BatchMessage::Done(report) => {
+                    on_file(&report);
+                    reports.push(report);
+                }
+            }
+        }
+    });
+    reports.sort_by(|a, b| a.path.cmp(&b.path));
+    reports
+}
+
+/// 输出每个文件的统计及合计
+pub fn write_report<W: Write>(reports: &[FileReport], mut out: W) -> io::Result<()> {
+    writeln!(out, "{:<40} {:>10} {:>8} {:>8} {:>8} {:>8} {:>8}",
+        "文件", "帧数", "记录", "无人机", "无法解析", "包头错误", "耗时(s)")?;
+    for r in reports {
+        let name = r.path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+        match &r.error {
+            Some(error) => writeln!(out, "{:<40} 失败: {}", name, error)?,
+            None => writeln!(out, "{:<40} {:>10} {:>8} {:>8} {:>8} {:>8} {:>8.1}",
+                name, r.frames, r.records, r.drones.len(), r.undecodable, r.malformed, r.elapsed.as_secs_f64())?,
+        }
+    }
+    let drones: HashSet<&String> = reports.iter().flat_map(|r| &r.drones).collect();
+    writeln!(out, "\n共 {} 个文件 ({} 个失败)，{} 帧，{} 条记录，{} 架无人机",
+        reports.len(),
+        reports.iter().filter(|r| r.error.is_some()).count(),
+        reports.iter().map(|r| r.frames).sum::<u64>(),
+        reports.iter().map(|r| r.records).sum::<u64>(),
+        drones.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(reports: &[FileReport]) -> String {
+        let mut out = Vec::new();
+        write_report(reports, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_drones_by_rid_or_track() {
+        let mut report = FileReport::new(PathBuf::from("a.pcap"));
+        report.add_record(&UploadData { rid: "A".into(), track_id: "1".into(), ..Default::default() });
+        report.add_record(&UploadData { rid: "A".into(), track_id: "2".into(), ..Default::default() });
+        report.add_record(&UploadData { track_id: "aa:bb".into(), ..Default::default() });
+        assert_eq!(report.records, 3);
+        assert_eq!(report.drones, HashSet::from(["A".to_string(), "aa:bb".to_string()]));
+    }
+
+    #[test]
+    fn test_file_row() {
+        let mut a = FileReport { frames: 10, undecodable: 2, malformed: 1, elapsed: Duration::from_millis(1_300), ..FileReport::new(PathBuf::from("/data/a.pcap")) };
+        a.add_record(&UploadData { rid: "A".into(), ..Default::default() });
+        let text = report(&[a]);
+        let row = text.lines().nth(1).unwrap();
+        assert!(row.starts_with("a.pcap "), "{}", row);
+        let columns: Vec<&str> = row.split_whitespace().skip(1).collect();
+        assert_eq!(columns, ["10", "1", "1", "2", "1", "1.3"]);
+    }
+
+    #[test]
+    fn test_failed_file_row() {
+        let c = FileReport { error: Some("不支持的链路类型 1".into()), ..FileReport::new(PathBuf::from("c.pcap")) };
+        assert_eq!(report(&[c]).lines().nth(1).unwrap(), format!("{:<40} 失败: 不支持的链路类型 1", "c.pcap"));
+    }
+
+    #[test]
+    fn test_totals_count_drones_once() {
+        let mut a = FileReport { frames: 10, ..FileReport::new(PathBuf::from("a.pcap")) };
+        a.add_record(&UploadData { rid: "A".into(), ..Default::default() });
+        a.add_record(&UploadData { rid: "A".into(), ..Default::default() });
+        let mut b = FileReport::new(PathBuf::from("b.pcap"));
+        b.add_record(&UploadData { rid: "A".into(), ..Default::default() });
+        b.add_record(&UploadData { track_id: "aa:bb".into(), ..Default::default() });
+        let c = FileReport { error: Some("不支持的链路类型 1".into()), ..FileReport::new(PathBuf::from("c.pcap")) };
+        assert!(report(&[a, b, c]).ends_with("共 3 个文件 (1 个失败)，10 帧，4 条记录，2 架无人机\n"));
+    }
+
+    #[test]
+    fn test_decode_files() {
+        let dir = std::env::temp_dir().join("wifi-capture-batch-decode-test");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let frame = crate::test_support::Fixture::load("rid_beacon").frame;
+        let mut pcap = Vec::new();
+        let mut writer = crate::pcap_writer::PcapWriter::new(&mut pcap).unwrap();
+        writer.write(1_748_764_800_000_000, &frame).unwrap();
+        writer.write(1_748_764_801_000_000, &frame).unwrap();
+        fs::write(dir.join("b.pcap"), &pcap).unwrap();
+        fs::write(dir.join("a.pcap"), b"not a pcap").unwrap();
+
+        let (mut events, mut finished) = (0, Vec::new());
+        let reports = decode_files(list_pcaps(&dir).unwrap(), 4, |_| events += 1, |r| finished.push(r.path.clone()));
+        fs::remove_dir_all(&dir).ok();
+        assert_eq!(finished.len(), 2);
+        assert_eq!(reports.iter().map(|r| r.path.file_name().unwrap().to_str().unwrap()).collect::<Vec<_>>(), ["a.pcap", "b.pcap"]);
+        assert!(reports[0].error.is_some());
+        assert_eq!((reports[1].frames, reports[1].error.as_deref()), (2, None));
+        assert!(reports[1].records > 0);
+        assert_eq!(events, reports[1].records);
+    }
+
+    #[test]
+    fn test_thread_count() {
+        assert_eq!((thread_count(8, 3), thread_count(0, 3), thread_count(8, 0)), (3, 1, 1));
+    }
+
+    #[test]
+    fn test_list_pcaps() {
+        let dir = std::env::temp_dir().join("wifi-capture-batch-test");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("nested.pcap")).unwrap();
+        for name in ["b.pcapng", "a.pcap", "c.CAP", "notes.txt", "d"] {
+            fs::write(dir.join(name), b"").unwrap();
+        }
+        let names: Vec<String> = list_pcaps(&dir).unwrap().iter()
+            .map(|p| p.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        fs::remove_dir_all(&dir).unwrap();
+        // 不含目录和非 pcap 文件，按文件名排序
+        assert_eq!(names, ["a.pcap", "b.pcapng", "c.CAP"]);
+        assert!(list_pcaps(&dir).is_err());
+    }
+}
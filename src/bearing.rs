@@ -0,0 +1,246 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use tracing::{error, info, warn};
+
+/// 当前天线指向 (度, 0-360)，由外部输入线程更新
+#[derive(Clone, Default)]
+pub struct AntennaBearing {
+    current: Arc<Mutex<Option<(f32, Instant)>>>,
+}
+
+impl AntennaBearing {
+    /// 指向数据超过该时长未更新视为无效
+    const STALE_AFTER: Duration = Duration::from_secs(5);
+
+    pub fn set(&self, degrees: f32) {
+        *self.current.lock().unwrap() = Some((degrees.rem_euclid(360.0), Instant::now()));
+    }
+
+    pub fn get(&self) -> Option<f32> {
+        self.current.lock().unwrap()
+            .filter(|(_, t)| t.elapsed() < Self::STALE_AFTER)
+            .map(|(d, _)| d)
+    }
+
+    /// 从串口等字符设备逐行读取指向角度
+    pub fn spawn_line_reader(&self, path: String) {
+        let bearing = self.clone();
+        thread::spawn(move || {
+            let file = match File::open(&path) {
+                Ok(file) => file,
+                Err(e) => {
+                    error!("无法打开方位输入 {}: {}", path, e);
+                    return;
+                }
+            };
+            info!("reading antenna bearing from {}", path);
+            for line in BufReader::new(file).lines() {
+                let Ok(line) = line else { break };
+                match line.trim().parse::<f32>() {
+                    Ok(degrees) => bearing.set(degrees),
+                    Err(_) => warn!("无效方位数据: {:?}", line),
+                }
+            }
+            warn!("antenna bearing input {} closed", path);
+        });
+    }
+
+    /// 通过 HTTP 接收指向角度：请求体或查询参数 `deg` 为角度值
+    pub fn spawn_http_listener(&self, addr: String) {
+        let bearing = self.clone();
+        thread::spawn(move || {
+            let listener = match TcpListener::bind(&addr) {
+                Ok(listener) => listener,
+                Err(e) => {
+                    error!("无法监听方位输入 {}: {}", addr, e);
+                    return;
+                }
+            };
+            info!("accepting antenna bearing on http://{}/bearing", addr);
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else { continue };
+                let mut buf = [0u8; 1024];
+                let len = stream.read(&mut buf).unwrap_or(0);
+                let request = String::from_utf8_lossy(&buf[..len]);
+                let status = match parse_http_bearing(&request) {
+                    Some(degrees) => {
+                        bearing.set(degrees);
+                        "204 No Content"
+                    }
+                    None => "400 Bad Request",
+                };
+                let _ = write!(stream, "HTTP/1.1 {}\r\nContent-Length: 0\r\n\r\n", status);
+            }
+        });
+    }
+}
+
+fn parse_http_bearing(request: &str) -> Option<f32> {
+    let target = request.lines().next()?.split_whitespace().nth(1)?;
+    if let Some((_, query)) = target.split_once('?') {
+        return query.split('&')
+            .find_map(|kv| kv.strip_prefix("deg="))
+            .and_then(|v| v.parse().ok());
+    }
+    request.split("\r\n\r\n").nth(1)?.trim().parse().ok()
+}
+
+/// 方位估计结果
+#[derive(Debug, Clone, Copy)]
+pub struct BearingEstimate {
+    pub bearing_deg: f32,
+    pub confidence: f32,   // 0-1
+}
+
+const BUCKET_DEG: f32 = 10.0;
+const BUCKETS: usize = 36;
+
+/// 按方位分桶的 RSSI（指数平滑）
+struct BearingBuckets {
+    rssi: [Option<f32>; BUCKETS],
+    last_seen: Instant,
+}
+
+/// 定向天线扫描测向
+///
+/// 天线旋转时把每架无人机的 RSSI 按当时的天线指向分桶，
+/// 信号最强的方位即为估计方位；置信度由峰值相对其余方位的突出程度
+/// 及已扫描方位的覆盖率决定。
+pub struct BearingEstimator {
+    antenna: AntennaBearing,
+    tracks: HashMap<String, BearingBuckets>,
+}
+
+impl BearingEstimator {
+    const SMOOTHING: f32 = 0.3;
+    const TRACK_TTL: Duration = Duration::from_secs(600);
+
+    pub fn new(antenna: AntennaBearing) -> Self {
+        Self { antenna, tracks: HashMap::new() }
+    }
+
+    pub fn observe(&mut self, track_id: &str, rssi: f32) -> Option<BearingEstimate> {
+        let bearing = self.antenna.get()?;
+        let now = Instant::now();
+        self.tracks.retain(|_, b| now.duration_since(b.last_seen) < Self::TRACK_TTL);
+        let buckets = self.tracks.entry(track_id.to_string())
+            .or_insert_with(|| BearingBuckets { rssi: [None; BUCKETS], last_seen: now });
+        buckets.last_seen = now;
+        let index = (bearing / BUCKET_DEG) as usize % BUCKETS;
+        let slot = &mut buckets.rssi[index];
+        *slot = Some(match *slot {
+            Some(old) => old + Self::SMOOTHING * (rssi - old),
+            None => rssi,
+        });
+        estimate(&buckets.rssi)
+    }
+}
+
+fn estimate(buckets: &[Option<f32>; BUCKETS]) -> Option<BearingEstimate> {
+    let filled: Vec<(usize, f32)> = buckets.iter()
+        .enumerate()
+        .filter_map(|(i, r)| r.map(|r| (i, r)))
+        .collect();
+    if filled.len() < 3 {
+        return None;
+    }
+    let (peak_index, peak) = filled.iter()
+        .copied()
+        .max_by(|a, b| a.1.total_cmp(&b.1))?;
+    let mut others: Vec<f32> = filled.iter().filter(|(i, _)| *i != peak_index).map(|(_, r)| *r).collect();
+    others.sort_by(f32::total_cmp);
+    let median = others[others.len() / 2];
+    // 峰值高出中位数 10 dB 视为完全突出
+    let prominence = ((peak - median) / 10.0).clamp(0.0, 1.0);
+    let coverage = filled.len() as f32 / BUCKETS as f32;
+    Some(BearingEstimate {
+        bearing_deg: peak_index as f32 * BUCKET_DEG + BUCKET_DEG / 2.0,
+        confidence: prominence * coverage.sqrt(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_http_bearing_from_query_or_body() {
+        assert_eq!(parse_http_bearing("POST /bearing?deg=127.5 HTTP/1.1\r\n\r\n"), Some(127.5));
+        assert_eq!(parse_http_bearing("POST /bearing?x=1&deg=90 HTTP/1.1\r\n\r\n"), Some(90.0));
+        assert_eq!(parse_http_bearing("POST /bearing HTTP/1.1\r\nContent-Length: 4\r\n\r\n45.0"), Some(45.0));
+        assert_eq!(parse_http_bearing("POST /bearing?deg=north HTTP/1.1\r\n\r\n"), None);
+        assert_eq!(parse_http_bearing(""), None);
+    }
+
+    #[test]
+    fn test_antenna_bearing_is_normalized() {
+        let antenna = AntennaBearing::default();
+        assert_eq!(antenna.get(), None);
+        antenna.set(-10.0);
+        assert_eq!(antenna.get(), Some(350.0));
+        antenna.set(370.0);
+        assert_eq!(antenna.get(), Some(10.0));
+    }
+
+    #[test]
+    fn test_no_estimate_without_antenna_or_coverage() {
+        let antenna = AntennaBearing::default();
+        let mut estimator = BearingEstimator::new(antenna.clone());
+        assert!(estimator.observe("A", -60.0).is_none());
+        antenna.set(0.0);
+        assert!(estimator.observe("A", -60.0).is_none());
+        antenna.set(20.0);
+        assert!(estimator.observe("A", -60.0).is_none());   // 只覆盖两个方位
+    }
+
+    #[test]
+    fn test_full_sweep_finds_peak() {
+        let antenna = AntennaBearing::default();
+        let mut estimator = BearingEstimator::new(antenna.clone());
+        let mut last = None;
+        for step in 0..BUCKETS {
+            let bearing = step as f32 * BUCKET_DEG;
+            antenna.set(bearing);
+            let rssi = if bearing == 90.0 { -45.0 } else { -70.0 };
+            last = estimator.observe("A", rssi);
+        }
+        let estimate = last.unwrap();
+        assert_eq!(estimate.bearing_deg, 95.0);   // 桶的中心
+        assert!((estimate.confidence - 1.0).abs() < 1e-6);
+        // 其他无人机的分桶互不影响
+        assert!(estimator.observe("B", -50.0).is_none());
+    }
+
+    #[test]
+    fn test_flat_partial_sweep_has_low_confidence() {
+        let antenna = AntennaBearing::default();
+        let mut estimator = BearingEstimator::new(antenna.clone());
+        let mut last = None;
+        for bearing in [0.0, 10.0, 20.0, 30.0] {
+            antenna.set(bearing);
+            last = estimator.observe("A", -60.0);
+        }
+        assert_eq!(last.unwrap().confidence, 0.0);
+    }
+
+    #[test]
+    fn test_repeated_readings_are_smoothed() {
+        let mut buckets = [None; BUCKETS];
+        buckets[0] = Some(-80.0);
+        buckets[1] = Some(-80.0);
+        buckets[2] = Some(-60.0);
+        let antenna = AntennaBearing::default();
+        let mut estimator = BearingEstimator::new(antenna.clone());
+        estimator.tracks.insert("A".into(), BearingBuckets { rssi: buckets, last_seen: Instant::now() });
+        // 桶 0 单次读到更强的信号只平滑 30%，峰值仍在桶 2
+        antenna.set(5.0);
+        assert_eq!(estimator.observe("A", -40.0).unwrap().bearing_deg, 25.0);
+        assert_eq!(estimator.tracks["A"].rssi[0], Some(-68.0));
+    }
+}
@@ -0,0 +1,528 @@
+use std::collections::HashMap;
+
+use libwifi::frame::components::MacAddress;
+
+use crate::decode::{self, DecodeContext};
+use crate::message::message_pack::MessagePack;
+use crate::radiotap::RadiotapHeader;
+use crate::remote_id::MESSAGE_SIZE;
+use crate::upload_data::UploadData;
+
+/// ASTM F3411 蓝牙广播使用的 16 位服务 UUID
+pub const ODID_SERVICE_UUID: u16 = 0xFFFA;
+/// 服务数据中 UUID 之后的应用代码
+const ODID_APP_CODE: u8 = 0x0D;
+/// AD 类型：16 位 UUID 服务数据
+const AD_SERVICE_DATA_16: u8 = 0x16;
+
+/// HCI 包类型：事件
+const HCI_EVENT_PKT: u8 = 0x04;
+/// HCI 事件：LE Meta
+const EVT_LE_META: u8 = 0x3E;
+const LE_ADVERTISING_REPORT: u8 = 0x02;
+const LE_EXTENDED_ADVERTISING_REPORT: u8 = 0x0D;
+/// 广播报告中表示信号强度不可用的值
+const RSSI_UNAVAILABLE: i8 = 127;
+
+/// 一条 LE 广播报告
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Advertisement {
+    /// 广播地址，按显示顺序 (高字节在前)
+    pub address: [u8; 6],
+    pub rssi: Option<i8>,
+    /// AD 结构序列
+    pub data: Vec<u8>,
+}
+
+/// 从广播数据中取出 Remote ID 负载，转换为与 WiFi 厂商 IE 相同的 消息计数器 + 消息包 格式
+///
+/// 蓝牙 4 传统广播的服务数据只容纳一条消息，蓝牙 5 长距离/扩展广播携带消息包；
+/// 单条消息被包装为只含一条消息的消息包。
+pub fn extract_odid(data: &[u8]) -> Option<Vec<u8>> {
+    let mut rest = data;
+    while let Some((&len, tail)) = rest.split_first() {
+        if len == 0 {
+            break;
+        }
+        let structure = tail.get(..len as usize)?;
+        rest = &tail[len as usize..];
+        let [AD_SERVICE_DATA_16, uuid_lo, uuid_hi, ODID_APP_CODE, service_data @ ..] = structure else { continue };
+        if u16::from_le_bytes([*uuid_lo, *uuid_hi]) != ODID_SERVICE_UUID {
+            continue;
+        }
+        let (&counter, messages) = service_data.split_first()?;
+        let header = *messages.first()?;
+        if header >> 4 == MessagePack::MESSAGE_TYPE {
+            return Some(service_data.to_vec());
+        }
+        let message = messages.get(..MESSAGE_SIZE)?;
+        let mut payload = vec![counter, MessagePack::MESSAGE_TYPE << 4 | header & 0x0F, MESSAGE_SIZE as u8, 1];
+        payload.extend_from_slice(message);
+        return Some(payload);
+    }
+    None
+}
+
+/// 解析 HCI LE 广播报告事件（传统和扩展），扩展广播的分片按地址重组
+#[derive(Debug, Default)]
+pub struct ReportParser {
+    fragments: HashMap<[u8; 6], Vec<u8>>,
+}
+
+impl ReportParser {
+    /// 解析一个以 HCI 包类型开头的事件包，非广播报告事件返回空
+    pub fn parse(&mut self, packet: &[u8]) -> Vec<Advertisement> {
+        let [HCI_EVENT_PKT, EVT_LE_META, _len, subevent, params @ ..] = packet else { return Vec::new() };
+        let Some((&count, mut reports)) = params.split_first() else { return Vec::new() };
+        let mut advertisements = Vec::new();
+        for _ in 0..count {
+            let parsed = match *subevent {
+                LE_ADVERTISING_REPORT => Self::legacy_report(reports),
+                LE_EXTENDED_ADVERTISING_REPORT => self.extended_report(reports),
+                _ => None,
+            };
+            let Some((advertisement, consumed)) = parsed else { break };
+            advertisements.extend(advertisement);
+            reports = &reports[consumed..];
+        }
+        advertisements
+    }
+
+    /// 事件类型(1) 地址类型(1) 地址(6) 数据长度(1) 数据 RSSI(1)
+    fn legacy_report(report: &[u8]) -> Option<(Option<Advertisement>, usize)> {
+        let len = *report.get(8)? as usize;
+        let data = report.get(9..9 + len)?;
+        let rssi = *report.get(9 + len)? as i8;
+        let advertisement = Advertisement { address: address(&report[2..8]), rssi: signal(rssi), data: data.to_vec() };
+        Some((Some(advertisement), 10 + len))
+    }
+
+    /// 事件类型(2) 地址类型(1) 地址(6) 主/次 PHY(2) SID(1) 发射功率(1) RSSI(1) 周期间隔(2)
+    /// 定向地址类型(1) 定向地址(6) 数据长度(1) 数据
+    fn extended_report(&mut self, report: &[u8]) -> Option<(Option<Advertisement>, usize)> {
+        let event_type = u16::from_le_bytes([*report.first()?, *report.get(1)?]);
+        let len = *report.get(23)? as usize;
+        let data = report.get(24..24 + len)?;
+        let address = address(&report[3..9]);
+        let rssi = report[13] as i8;
+        // 数据状态：0 完整，1 未完待续，2 截断
+        let advertisement = match event_type >> 5 & 0b11 {
+            0 => {
+                let mut full = self.fragments.remove(&address).unwrap_or_default();
+                full.extend_from_slice(data);
+                Some(Advertisement { address, rssi: signal(rssi), data: full })
+            }
+            1 => {
+                self.fragments.entry(address).or_default().extend_from_slice(data);
+                None
+            }
+            _ => {
+                self.fragments.remove(&address);
+                None
+            }
+        };
+        Some((advertisement, 24 + len))
+    }
+}
+
+/// HCI 中地址为小端序
+fn address(bytes: &[u8]) -> [u8; 6] {
+    let mut address = [0u8; 6];
+    address.copy_from_slice(bytes);
+    address.reverse();
+    address
+}
+
+fn signal(rssi: i8) -> Option<i8> {
+    (rssi != RSSI_UNAVAILABLE).then_some(rssi)
+}
+
+/// 解码一条广播中的 Remote ID，与 WiFi 信标走同一解码流程；广播地址作为源 MAC
+pub fn decode_advertisement(advertisement: &Advertisement, ctx: &mut DecodeContext) -> Vec<UploadData> {
+    let Some(payload) = extract_odid(&advertisement.data) else { return Vec::new() };
+    let radiotap = RadiotapHeader { signal_dbm: advertisement.rssi, ..Default::default() };
+    decode::decode_payload(MacAddress(advertisement.address), payload, "", &radiotap, ctx)
+}
+
+#[cfg(feature = "bluetooth")]
+pub use hci::BleIngest;
+
+/// 通过 HCI 原始套接字被动扫描 LE 广播，需要 root 或 CAP_NET_RAW
+///
+/// 控制器支持时使用扩展扫描 (1M + Coded PHY)，以收到蓝牙 5 长距离广播，否则退回传统扫描。
+/// 扫描期间 bluetoothd 的扫描可能与之冲突，必要时先停止 bluetoothd 或执行 `hciconfig hci0 reset`。
+#[cfg(feature = "bluetooth")]
+mod hci {
+    use std::ffi::c_int;
+    use std::io;
+    use std::sync::mpsc;
+    use std::thread;
+    use std::time::Duration;
+
+    use tracing::{error, info, warn};
+
+    use super::{decode_advertisement, ReportParser, EVT_LE_META, HCI_EVENT_PKT};
+    use crate::capture::{CaptureError, Recovery};
+    use crate::decode::DecodeContext;
+    use crate::event_log::DecodedEvent;
+
+    const AF_BLUETOOTH: c_int = 31;
+    const BTPROTO_HCI: c_int = 1;
+    const SOL_HCI: c_int = 0;
+    const HCI_FILTER: c_int = 2;
+    const HCI_CHANNEL_RAW: u16 = 0;
+    const HCI_COMMAND_PKT: u8 = 0x01;
+    const EVT_CMD_COMPLETE: u8 = 0x0E;
+    const EVT_CMD_STATUS: u8 = 0x0F;
+    /// LE 控制器命令组
+    const OGF_LE: u16 = 0x08;
+    const LE_SET_SCAN_PARAMETERS: u16 = 0x000B;
+    const LE_SET_SCAN_ENABLE: u16 = 0x000C;
+    const LE_SET_EXT_SCAN_PARAMETERS: u16 = 0x0041;
+    const LE_SET_EXT_SCAN_ENABLE: u16 = 0x0042;
+    /// 等待命令完成事件的时间
+    const COMMAND_TIMEOUT_MS: c_int = 2000;
+
+    #[repr(C)]
+    struct SockaddrHci {
+        family: libc::sa_family_t,
+        dev: u16,
+        channel: u16,
+    }
+
+    #[repr(C)]
+    struct HciFilter {
+        type_mask: u32,
+        event_mask: [u32; 2],
+        opcode: u16,
+    }
+
+    fn opcode(ocf: u16) -> u16 {
+        OGF_LE << 10 | ocf
+    }
+
+    /// 打开的 HCI 设备，扫描在 Drop 时关闭
+    struct HciScanner {
+        fd: c_int,
+        device: String,
+        extended: bool,
+        buf: Vec<u8>,
+    }
+
+    impl HciScanner {
+        /// 打开 `hciN` 并开始被动扫描
+        fn open(device: &str) -> Result<Self, CaptureError> {
+            let invalid = || CaptureError::UnsupportedChannelType { interface: device.to_string() };
+            let dev: u16 = device.strip_prefix("hci").and_then(|n| n.parse().ok()).ok_or_else(invalid)?;
+            let fd = unsafe { libc::socket(AF_BLUETOOTH, libc::SOCK_RAW | libc::SOCK_CLOEXEC, BTPROTO_HCI) };
+            if fd < 0 {
+                return Err(CaptureError::from_io(device, io::Error::last_os_error()));
+            }
+            // 先交给 HciScanner，出错返回时由 Drop 关闭套接字
+            let mut scanner = Self { fd, device: device.to_string(), extended: false, buf: vec![0; 512] };
+            let addr = SockaddrHci { family: AF_BLUETOOTH as libc::sa_family_t, dev, channel: HCI_CHANNEL_RAW };
+            let filter = HciFilter {
+                type_mask: 1 << HCI_EVENT_PKT,
+                event_mask: [1 << EVT_CMD_COMPLETE | 1 << EVT_CMD_STATUS, 1 << (EVT_LE_META - 32)],
+                opcode: 0,
+            };
+            let status = unsafe {
+                let bound = libc::bind(fd, &addr as *const SockaddrHci as *const libc::sockaddr,
+                                       size_of::<SockaddrHci>() as libc::socklen_t);
+                if bound < 0 { bound } else {
+                    libc::setsockopt(fd, SOL_HCI, HCI_FILTER, &filter as *const HciFilter as *const libc::c_void,
+                                     size_of::<HciFilter>() as libc::socklen_t)
+                }
+            };
+            if status < 0 {
+                return Err(CaptureError::from_io(device, io::Error::last_os_error()));
+            }
+            scanner.start().map_err(|e| CaptureError::from_io(device, e))?;
+            Ok(scanner)
+        }
+
+        fn start(&mut self) -> io::Result<()> {
+            // 被动扫描，间隔 60 ms，窗口 30 ms
+            let phy = [0x00, 0x60, 0x00, 0x30, 0x00];
+            let mut ext_params = vec![0x00, 0x00, 0x05];  // 本机公共地址，不过滤，1M + Coded PHY
+            ext_params.extend_from_slice(&phy);
+            ext_params.extend_from_slice(&phy);
+            if self.command(LE_SET_EXT_SCAN_PARAMETERS, &ext_params)? == 0
+                && self.command(LE_SET_EXT_SCAN_ENABLE, &[0x01, 0x00, 0x00, 0x00, 0x00, 0x00])? == 0
+            {
+                self.extended = true;
+                info!("extended LE scan started on {}", self.device);
+                return Ok(());
+            }
+            let status = match self.command(LE_SET_SCAN_PARAMETERS, &[0x00, 0x60, 0x00, 0x30, 0x00, 0x00, 0x00])? {
+                0 => self.command(LE_SET_SCAN_ENABLE, &[0x01, 0x00])?,
+                status => status,
+            };
+            if status != 0 {
+                return Err(io::Error::other(format!("控制器拒绝 LE 扫描命令 (状态 0x{:02x})", status)));
+            }
+            info!("legacy LE scan started on {}", self.device);
+            Ok(())
+        }
+
+        /// 发送 LE 命令并等待其完成事件，返回状态码；期间收到的广播报告被丢弃
+        fn command(&mut self, ocf: u16, params: &[u8]) -> io::Result<u8> {
+            let opcode = opcode(ocf);
+            let mut packet = vec![HCI_COMMAND_PKT];
+            packet.extend_from_slice(&opcode.to_le_bytes());
+            packet.push(params.len() as u8);
+            packet.extend_from_slice(params);
+            if unsafe { libc::write(self.fd, packet.as_ptr().cast(), packet.len()) } < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            loop {
+                let mut pollfd = libc::pollfd { fd: self.fd, events: libc::POLLIN, revents: 0 };
+                match unsafe { libc::poll(&mut pollfd, 1, COMMAND_TIMEOUT_MS) } {
+                    0 => return Err(io::ErrorKind::TimedOut.into()),
+                    n if n < 0 => return Err(io::Error::last_os_error()),
+                    _ => {}
+                }
+                let event = self.read()?;
+                // 命令完成: 可发命令数(1) 操作码(2) 状态(1)；命令状态: 状态(1) 可发命令数(1) 操作码(2)
+                match event {
+                    [HCI_EVENT_PKT, EVT_CMD_COMPLETE, _, _, lo, hi, status, ..] if u16::from_le_bytes([*lo, *hi]) == opcode =>
+                        return Ok(*status),
+                    [HCI_EVENT_PKT, EVT_CMD_STATUS, _, status, _, lo, hi, ..] if u16::from_le_bytes([*lo, *hi]) == opcode =>
+                        return Ok(*status),
+                    _ => {}
+                }
+            }
+        }
+
+        fn read(&mut self) -> io::Result<&[u8]> {
+            let len = unsafe { libc::read(self.fd, self.buf.as_mut_ptr().cast(), self.buf.len()) };
+            if len < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(&self.buf[..len as usize])
+        }
+    }
+
+    impl Drop for HciScanner {
+        fn drop(&mut self) {
+            let disable = if self.extended {
+                self.command(LE_SET_EXT_SCAN_ENABLE, &[0x00, 0x00, 0x00, 0x00, 0x00, 0x00])
+            } else {
+                self.command(LE_SET_SCAN_ENABLE, &[0x00, 0x00])
+            };
+            if let Err(e) = disable {
+                warn!("failed to stop LE scan on {}: {}", self.device, e);
+            }
+            unsafe { libc::close(self.fd) };
+        }
+    }
+
+    /// 蓝牙 Remote ID 接入：后台线程扫描 LE 广播并解码，记录与 WiFi 记录一起进入输出
+    ///
+    /// 解码使用独立的上下文，ID 冲突检测和轨迹关联只在蓝牙记录之间进行。
+    pub struct BleIngest {
+        ingest: mpsc::Receiver<DecodedEvent>,
+    }
+
+    impl BleIngest {
+        pub fn spawn(device: String, mut ctx: DecodeContext) -> Self {
+            let (sender, ingest) = mpsc::channel();
+            thread::spawn(move || {
+                let mut parser = ReportParser::default();
+                loop {
+                    match scan(&device, &mut parser, &mut ctx, &sender) {
+                        Ok(()) => return,
+                        Err(e) if e.recovery() == Recovery::Abort => {
+                            error!("{} 停止蓝牙扫描: {}", device, e);
+                            return;
+                        }
+                        Err(e) => {
+                            warn!("{}, retrying in 5s", e);
+                            thread::sleep(Duration::from_secs(5));
+                        }
+                    }
+                }
+            });
+            Self { ingest }
+        }
+
+        /// 取出解码的蓝牙 Remote ID 事件
+        pub fn take_ingested(&self) -> Vec<DecodedEvent> {
+            self.ingest.try_iter().collect()
+        }
+    }
+
+    /// 扫描并解码，直到出错或接收端关闭
+    fn scan(device: &str, parser: &mut ReportParser, ctx: &mut DecodeContext,
+            sender: &mpsc::Sender<DecodedEvent>) -> Result<(), CaptureError> {
+        let mut scanner = HciScanner::open(device)?;
+        info!("Capturing Bluetooth LE on {}", device);
+        loop {
+            let packet = scanner.read().map_err(|e| CaptureError::from_io(device, e))?;
+            for advertisement in parser.parse(packet) {
+                for record in decode_advertisement(&advertisement, ctx) {
+                    if sender.send(DecodedEvent::now(record)).is_err() {
+                        return Ok(());
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn location() -> [u8; MESSAGE_SIZE] {
+        let mut message = [0u8; MESSAGE_SIZE];
+        message[0] = 0x12;
+        message[5..9].copy_from_slice(&399_042_000i32.to_le_bytes());
+        message
+    }
+
+    const ADDRESS: [u8; 6] = [0x55, 0x44, 0x33, 0x22, 0x11, 0xC0];   // HCI 小端序
+
+    /// Flags AD 结构 + Remote ID 服务数据
+    fn ad_data(messages: &[u8]) -> Vec<u8> {
+        let mut service_data = vec![0x16, 0xFA, 0xFF, 0x0D, 0x07];
+        service_data.extend_from_slice(messages);
+        let mut data = vec![0x02, 0x01, 0x06, service_data.len() as u8];
+        data.extend_from_slice(&service_data);
+        data
+    }
+
+    fn event(subevent: u8, reports: &[Vec<u8>]) -> Vec<u8> {
+        let mut params = vec![subevent, reports.len() as u8];
+        params.extend(reports.concat());
+        let mut packet = vec![HCI_EVENT_PKT, EVT_LE_META, params.len() as u8];
+        packet.extend_from_slice(&params);
+        packet
+    }
+
+    fn legacy_report(data: &[u8], rssi: i8) -> Vec<u8> {
+        let mut report = vec![0x03, 0x01];
+        report.extend_from_slice(&ADDRESS);
+        report.push(data.len() as u8);
+        report.extend_from_slice(data);
+        report.push(rssi as u8);
+        report
+    }
+
+    /// `status` 为数据状态：0 完整，1 未完待续，2 截断
+    fn extended_report(status: u16, data: &[u8]) -> Vec<u8> {
+        let mut report = (status << 5).to_le_bytes().to_vec();
+        report.push(0x01);
+        report.extend_from_slice(&ADDRESS);
+        report.extend_from_slice(&[0x01, 0x03, 0xff, 0x7f, -80i8 as u8, 0, 0, 0]);
+        report.extend_from_slice(&[0; 6]);
+        report.push(data.len() as u8);
+        report.extend_from_slice(data);
+        report
+    }
+
+    #[test]
+    fn test_single_message_is_wrapped_in_pack() {
+        let payload = extract_odid(&ad_data(&location())).unwrap();
+        assert_eq!(&payload[..4], &[0x07, 0xF2, MESSAGE_SIZE as u8, 1]);
+        assert_eq!(&payload[4..], &location());
+    }
+
+    #[test]
+    fn test_message_pack_is_passed_through() {
+        let mut pack = vec![0xF2, MESSAGE_SIZE as u8, 2];
+        pack.extend_from_slice(&location());
+        pack.extend_from_slice(&location());
+        let payload = extract_odid(&ad_data(&pack)).unwrap();
+        assert_eq!(payload, [&[0x07][..], &pack].concat());
+    }
+
+    #[test]
+    fn test_other_service_data_is_skipped() {
+        let mut data = vec![0x04, 0x16, 0x0F, 0x18, 0x64];   // 电池服务
+        data.extend(ad_data(&location()));
+        assert!(extract_odid(&data).is_some());
+        let mut other_app = ad_data(&location());
+        other_app[7] = 0x0C;
+        assert_eq!(extract_odid(&other_app), None);
+        assert_eq!(extract_odid(&[0x02, 0x01, 0x06]), None);
+    }
+
+    #[test]
+    fn test_truncated_service_data() {
+        assert_eq!(extract_odid(&ad_data(&location()[..MESSAGE_SIZE - 1])), None);
+        let mut data = ad_data(&location());
+        data.pop();
+        assert_eq!(extract_odid(&data), None);
+    }
+
+    #[test]
+    fn test_legacy_advertising_report() {
+        let packet = event(LE_ADVERTISING_REPORT, &[legacy_report(&ad_data(&location()), -70)]);
+        let advertisements = ReportParser::default().parse(&packet);
+        assert_eq!(advertisements, [Advertisement {
+            address: [0xC0, 0x11, 0x22, 0x33, 0x44, 0x55],
+            rssi: Some(-70),
+            data: ad_data(&location()),
+        }]);
+    }
+
+    #[test]
+    fn test_several_reports_in_one_event() {
+        let packet = event(LE_ADVERTISING_REPORT, &[legacy_report(&[0x02, 0x01, 0x06], -70), legacy_report(&ad_data(&location()), 127)]);
+        let advertisements = ReportParser::default().parse(&packet);
+        assert_eq!(advertisements.len(), 2);
+        assert_eq!(advertisements[1].data, ad_data(&location()));
+        // 127 表示信号强度不可用
+        assert_eq!(advertisements[1].rssi, None);
+    }
+
+    #[test]
+    fn test_extended_advertising_report() {
+        let packet = event(LE_EXTENDED_ADVERTISING_REPORT, &[extended_report(0, &ad_data(&location()))]);
+        let advertisements = ReportParser::default().parse(&packet);
+        assert_eq!(advertisements.len(), 1);
+        assert_eq!((advertisements[0].rssi, advertisements[0].data.clone()), (Some(-80), ad_data(&location())));
+    }
+
+    #[test]
+    fn test_extended_fragments_are_reassembled() {
+        let data = ad_data(&location());
+        let mut parser = ReportParser::default();
+        assert!(parser.parse(&event(LE_EXTENDED_ADVERTISING_REPORT, &[extended_report(1, &data[..10])])).is_empty());
+        let advertisements = parser.parse(&event(LE_EXTENDED_ADVERTISING_REPORT, &[extended_report(0, &data[10..])]));
+        assert_eq!(advertisements[0].data, data);
+    }
+
+    #[test]
+    fn test_truncated_extended_advertisement_is_dropped() {
+        let data = ad_data(&location());
+        let mut parser = ReportParser::default();
+        parser.parse(&event(LE_EXTENDED_ADVERTISING_REPORT, &[extended_report(1, &data[..10])]));
+        assert!(parser.parse(&event(LE_EXTENDED_ADVERTISING_REPORT, &[extended_report(2, &data[10..20])])).is_empty());
+        // 截断后丢弃已收到的分片，下一次广播重新开始
+        let advertisements = parser.parse(&event(LE_EXTENDED_ADVERTISING_REPORT, &[extended_report(0, &data)]));
+        assert_eq!(advertisements[0].data, data);
+    }
+
+    #[test]
+    fn test_other_events_are_ignored() {
+        let mut parser = ReportParser::default();
+        assert!(parser.parse(&event(0x01, &[legacy_report(&ad_data(&location()), -70)])).is_empty());   // 连接完成
+        assert!(parser.parse(&[HCI_EVENT_PKT, 0x0E, 0x00]).is_empty());
+        // 报告长度超出事件时停止解析
+        let mut packet = event(LE_ADVERTISING_REPORT, &[legacy_report(&ad_data(&location()), -70)]);
+        packet.truncate(packet.len() - 2);
+        assert!(parser.parse(&packet).is_empty());
+    }
+
+    #[test]
+    fn test_decode_advertisement() {
+        let advertisement = Advertisement { address: [0xC0, 0x11, 0x22, 0x33, 0x44, 0x55], rssi: Some(-70), data: ad_data(&location()) };
+        let records = decode_advertisement(&advertisement, &mut DecodeContext::default());
+        assert_eq!(records.len(), 1);
+        assert_eq!((records[0].latitude, records[0].rssi, records[0].message_counter), (399_042_000, Some(-70.0), 7));
+        assert_eq!(records[0].source_mac, "c0:11:22:33:44:55");
+        assert!(decode_advertisement(&Advertisement { data: vec![0x02, 0x01, 0x06], ..advertisement }, &mut DecodeContext::default()).is_empty());
+    }
+}
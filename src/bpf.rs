@@ -0,0 +1,203 @@
+/// 经典 BPF 指令，与内核的 `struct sock_filter` 及 libpcap 的 `struct bpf_insn` 布局相同
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Instruction {
+    pub code: u16,
+    pub jt: u8,
+    pub jf: u8,
+    pub k: u32,
+}
+
+const LD_W_LEN: u16 = 0x80;   // A = 帧长
+const LD_B_ABS: u16 = 0x30;   // A = pkt[k]
+const LD_B_IND: u16 = 0x50;   // A = pkt[X + k]
+const ALU_ADD_K: u16 = 0x04;  // A += k
+const ALU_ADD_X: u16 = 0x0c;  // A += X
+const ALU_LSH_K: u16 = 0x64;  // A <<= k
+const JMP_JA: u16 = 0x05;     // 跳过 k 条
+const JMP_JEQ_K: u16 = 0x15;
+const JMP_JGE_K: u16 = 0x35;
+const RET_K: u16 = 0x06;
+const MISC_TAX: u16 = 0x07;   // X = A
+const MISC_TXA: u16 = 0x87;   // A = X
+
+/// 放行时保留的字节数（整帧）
+const ACCEPT: u32 = 0xFFFF;
+const VENDOR_SPECIFIC_IE: u32 = 221;
+/// 最多检查的 IE 个数；经典 BPF 不能向后跳转，遍历按此展开
+const MAX_ELEMENTS: usize = 64;
+
+fn stmt(code: u16, k: u32) -> Instruction {
+    Instruction { code, jt: 0, jf: 0, k }
+}
+
+fn jump(code: u16, k: u32, jt: u8, jf: u8) -> Instruction {
+    Instruction { code, jt, jf, k }
+}
+
+/// 监听网卡上只放行可能带 Remote ID 的帧的过滤程序，挂到抓包套接字后在内核中丢弃其余帧
+///
+/// 帧以 radiotap 头开始。放行：
+///
+/// - 带厂商自定义 IE (221) 的信标、探测响应和探测请求帧（检查前 64 个 IE）
+/// - 厂商自定义的公共动作帧 (NAN 服务发现)
+///
+/// 短于 `min_packet_len` 的帧、数据帧和控制帧都被丢弃，需要深度扫描数据帧时不能使用。
+pub fn remote_id_filter(min_packet_len: usize) -> Vec<Instruction> {
+    let mut program = vec![
+        stmt(LD_W_LEN, 0),
+        jump(JMP_JGE_K, min_packet_len as u32, 1, 0),
+        stmt(RET_K, 0),
+        // X = radiotap 头长度 (小端 u16)
+        stmt(LD_B_ABS, 3),
+        stmt(ALU_LSH_K, 8),
+        stmt(MISC_TAX, 0),
+        stmt(LD_B_ABS, 2),
+        stmt(ALU_ADD_X, 0),
+        stmt(MISC_TAX, 0),
+        // 帧控制字段第 1 字节：子类型与类型
+        stmt(LD_B_IND, 0),
+        jump(JMP_JEQ_K, 0x80, 10, 0),   // 信标
+        jump(JMP_JEQ_K, 0x50, 9, 0),    // 探测响应
+        jump(JMP_JEQ_K, 0x40, 11, 0),   // 探测请求
+        jump(JMP_JEQ_K, 0xd0, 1, 0),    // 动作帧
+        stmt(RET_K, 0),
+        // 公共动作帧 (类别 4) 中的厂商自定义动作 (9)
+        stmt(LD_B_IND, 24),
+        jump(JMP_JEQ_K, 4, 0, 3),
+        stmt(LD_B_IND, 25),
+        jump(JMP_JEQ_K, 9, 0, 1),
+        stmt(RET_K, ACCEPT),
+        stmt(RET_K, 0),
+        // 信标/探测响应：24 字节 MAC 头 + 12 字节固定字段之后是 IE
+        stmt(MISC_TXA, 0),
+        stmt(ALU_ADD_K, 36),
+        stmt(JMP_JA, 2),
+        // 探测请求：MAC 头之后即是 IE
+        stmt(MISC_TXA, 0),
+        stmt(ALU_ADD_K, 24),
+        stmt(MISC_TAX, 0),
+    ];
+    // 逐个 IE 检查 ID，读到帧尾之外时内核按丢弃处理
+    for _ in 0..MAX_ELEMENTS {
+        program.extend([
+            stmt(LD_B_IND, 0),
+            jump(JMP_JEQ_K, VENDOR_SPECIFIC_IE, 0, 1),
+            stmt(RET_K, ACCEPT),
+            stmt(LD_B_IND, 1),
+            stmt(ALU_ADD_X, 0),
+            stmt(ALU_ADD_K, 2),
+            stmt(MISC_TAX, 0),
+        ]);
+    }
+    program.push(stmt(RET_K, 0));
+    program
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 只实现过滤程序用到的指令，越界读取按内核的行为返回 0
+    fn run(program: &[Instruction], packet: &[u8]) -> u32 {
+        let (mut a, mut x, mut pc) = (0u32, 0u32, 0usize);
+        loop {
+            let ins = program[pc];
+            pc += 1;
+            let load = |offset: u32| packet.get(offset as usize).map(|b| *b as u32);
+            match ins.code {
+                LD_W_LEN => a = packet.len() as u32,
+                LD_B_ABS => match load(ins.k) { Some(v) => a = v, None => return 0 },
+                LD_B_IND => match load(x + ins.k) { Some(v) => a = v, None => return 0 },
+                ALU_ADD_K => a += ins.k,
+                ALU_ADD_X => a += x,
+                ALU_LSH_K => a <<= ins.k,
+                JMP_JA => pc += ins.k as usize,
+                JMP_JEQ_K => pc += if a == ins.k { ins.jt } else { ins.jf } as usize,
+                JMP_JGE_K => pc += if a >= ins.k { ins.jt } else { ins.jf } as usize,
+                RET_K => return ins.k,
+                MISC_TAX => x = a,
+                MISC_TXA => a = x,
+                code => panic!("unexpected opcode {:#x}", code),
+            }
+        }
+    }
+
+    /// radiotap 头 + 24 字节 MAC 头 + 帧体
+    fn frame_with_radiotap(radiotap_len: usize, frame_control: u8, body: &[u8]) -> Vec<u8> {
+        let mut packet = vec![0; radiotap_len];
+        packet[2..4].copy_from_slice(&(radiotap_len as u16).to_le_bytes());
+        packet.push(frame_control);
+        packet.extend([0; 23]);
+        packet.extend(body);
+        packet
+    }
+
+    fn frame(frame_control: u8, body: &[u8]) -> Vec<u8> {
+        frame_with_radiotap(12, frame_control, body)
+    }
+
+    const FIXED: [u8; 12] = [0; 12];
+    const SSID: [u8; 6] = [0, 4, b'D', b'J', b'I', b'-'];
+    const VENDOR: [u8; 7] = [221, 5, 0xfa, 0x0b, 0xbc, 0x0d, 0x00];
+    const RATES: [u8; 4] = [1, 2, 0x82, 0x84];
+
+    fn accepts(packet: &[u8]) -> bool {
+        run(&remote_id_filter(40), packet) == ACCEPT
+    }
+
+    #[test]
+    fn test_beacon_and_probe_response_with_vendor_ie() {
+        assert!(accepts(&frame(0x80, &[&FIXED[..], &SSID, &RATES, &VENDOR].concat())));
+        assert!(accepts(&frame(0x50, &[&FIXED[..], &SSID, &VENDOR].concat())));
+    }
+
+    #[test]
+    fn test_probe_request_without_fixed_fields() {
+        assert!(accepts(&frame(0x40, &[&SSID[..], &VENDOR].concat())));
+        // 探测请求没有固定字段：SSID 内容恰好位于信标 IE 的偏移处时不能被当作厂商 IE
+        let ssid = [0, 12, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 221, 0];
+        assert!(!accepts(&frame(0x40, &[&ssid[..], &RATES].concat())));
+    }
+
+    #[test]
+    fn test_public_vendor_action() {
+        assert!(accepts(&frame(0xd0, &[4, 9, 0x50, 0x6f, 0x9a, 0x13, 0, 0, 0, 0])));
+        // 其它公共动作与其它类别
+        assert!(!accepts(&frame(0xd0, &[4, 10, 0x50, 0x6f, 0x9a, 0x13, 0, 0, 0, 0])));
+        assert!(!accepts(&frame(0xd0, &[127, 9, 0x50, 0x6f, 0x9a, 0x13, 0, 0, 0, 0])));
+    }
+
+    #[test]
+    fn test_beacon_without_vendor_ie() {
+        assert!(!accepts(&frame(0x80, &[&FIXED[..], &SSID, &RATES].concat())));
+    }
+
+    #[test]
+    fn test_vendor_ie_past_element_limit() {
+        let filler: Vec<u8> = (0..MAX_ELEMENTS).flat_map(|_| RATES).collect();
+        assert!(!accepts(&frame(0x80, &[&FIXED[..], &filler, &VENDOR].concat())));
+        assert!(accepts(&frame(0x80, &[&FIXED[..], &filler[RATES.len()..], &VENDOR].concat())));
+    }
+
+    #[test]
+    fn test_data_and_control_frames() {
+        assert!(!accepts(&frame(0x08, &[&FIXED[..], &VENDOR].concat())));
+        assert!(!accepts(&frame(0xd4, &[&FIXED[..], &VENDOR].concat())));   // ACK
+    }
+
+    #[test]
+    fn test_short_frames() {
+        let beacon = frame(0x80, &[&FIXED[..], &SSID, &VENDOR].concat());
+        assert_eq!(run(&remote_id_filter(beacon.len()), &beacon), ACCEPT);
+        assert_eq!(run(&remote_id_filter(beacon.len() + 1), &beacon), 0);
+        // 声明长度超出帧尾的 IE
+        assert!(!accepts(&frame(0x80, &[&FIXED[..], &[0, 200]].concat())));
+    }
+
+    #[test]
+    fn test_radiotap_length_is_little_endian() {
+        let beacon = frame_with_radiotap(0x120, 0x80, &[&FIXED[..], &SSID, &VENDOR].concat());
+        assert!(accepts(&beacon));
+    }
+}
@@ -0,0 +1,112 @@
+use serde::Serialize;
+
+/// FNV-1a 64 位哈希，结果不随 Rust 版本或进程变化
+pub fn fnv1a64(parts: &[&[u8]]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for part in parts {
+        for byte in part.iter().chain(&[0xff]) {   // 0xff 分隔各字段，避免拼接歧义
+            hash ^= *byte as u64;
+            hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+    hash
+}
+
+/// 记录的稳定 ID：同一传感器、接收时间、消息计数器和 UAS ID 总是得到相同的 16 位十六进制串，
+/// 供下游去重
+pub fn record_id(sensor: &str, received_at_ms: i64, message_counter: u8, uas_id: &str) -> String {
+    let hash = fnv1a64(&[
+        sensor.as_bytes(),
+        &received_at_ms.to_be_bytes(),
+        &[message_counter],
+        uas_id.as_bytes(),
+    ]);
+    format!("{:016x}", hash)
+}
+
+/// 传感器标识：配置值，否则为主机名
+pub fn sensor_id(configured: Option<&str>) -> String {
+    configured.map(str::to_string)
+        .or_else(|| std::fs::read_to_string("/proc/sys/kernel/hostname").ok()
+            .map(|h| h.trim().to_string())
+            .filter(|h| !h.is_empty()))
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// 键按字典序排列的 JSON，输出与结构体字段声明顺序无关，便于逐行比对
+pub fn to_json<T: Serialize>(value: &T) -> serde_json::Result<String> {
+    serde_json::to_string(&serde_json::to_value(value)?)
+}
+
+/// 同 [`to_json`]，带缩进
+pub fn to_json_pretty<T: Serialize>(value: &T) -> serde_json::Result<String> {
+    serde_json::to_string_pretty(&serde_json::to_value(value)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize)]
+    struct Unordered {
+        zulu: u8,
+        alpha: u8,
+    }
+
+    #[derive(Serialize)]
+    struct Nested {
+        outer: Unordered,
+        list: Vec<Unordered>,
+    }
+
+    #[test]
+    fn test_fnv1a64() {
+        assert_eq!(fnv1a64(&[]), 0xcbf2_9ce4_8422_2325);
+        // 单个字段等同于对 "a\xff" 做标准 FNV-1a
+        let mut expected: u64 = 0xcbf2_9ce4_8422_2325;
+        for byte in [b'a', 0xff] {
+            expected = (expected ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3);
+        }
+        assert_eq!(fnv1a64(&[b"a"]), expected);
+    }
+
+    #[test]
+    fn test_record_id_is_stable() {
+        let id = record_id("sensor-1", 1_700_000_000_000, 7, "1581F5FKD229400A");
+        assert_eq!(id.len(), 16);
+        assert!(id.chars().all(|c| c.is_ascii_hexdigit() && !c.is_ascii_uppercase()));
+        assert_eq!(id, record_id("sensor-1", 1_700_000_000_000, 7, "1581F5FKD229400A"));
+    }
+
+    #[test]
+    fn test_record_id_covers_every_field() {
+        let id = record_id("sensor-1", 1_700_000_000_000, 7, "1581F5FKD229400A");
+        assert_ne!(id, record_id("sensor-2", 1_700_000_000_000, 7, "1581F5FKD229400A"));
+        assert_ne!(id, record_id("sensor-1", 1_700_000_000_001, 7, "1581F5FKD229400A"));
+        assert_ne!(id, record_id("sensor-1", 1_700_000_000_000, 8, "1581F5FKD229400A"));
+        assert_ne!(id, record_id("sensor-1", 1_700_000_000_000, 7, "1581F5FKD229400B"));
+    }
+
+    #[test]
+    fn test_record_id_fields_are_separated() {
+        assert_ne!(record_id("ab", 0, 0, "c"), record_id("a", 0, 0, "bc"));
+    }
+
+    #[test]
+    fn test_configured_sensor_id() {
+        assert_eq!(sensor_id(Some("roof-east")), "roof-east");
+        assert!(!sensor_id(None).is_empty());
+    }
+
+    #[test]
+    fn test_json_keys_are_sorted() {
+        assert_eq!(to_json(&Unordered { zulu: 1, alpha: 2 }).unwrap(), r#"{"alpha":2,"zulu":1}"#);
+        let nested = Nested { outer: Unordered { zulu: 1, alpha: 2 }, list: vec![Unordered { zulu: 3, alpha: 4 }] };
+        assert_eq!(to_json(&nested).unwrap(), r#"{"list":[{"alpha":4,"zulu":3}],"outer":{"alpha":2,"zulu":1}}"#);
+    }
+
+    #[test]
+    fn test_pretty_json_keys_are_sorted() {
+        assert_eq!(to_json_pretty(&Unordered { zulu: 1, alpha: 2 }).unwrap(), "{\n  \"alpha\": 2,\n  \"zulu\": 1\n}");
+    }
+}
@@ -0,0 +1,480 @@
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use pnet::datalink::Channel;
+use pnet::datalink::{self, DataLinkReceiver, DataLinkSender, NetworkInterface};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::bpf::{self, Instruction};
+use crate::control::RuntimeControl;
+use crate::decode::{self, DecodeContext};
+use crate::event_log::DecodedEvent;
+use crate::message::DecodeOptions;
+use crate::radiotap;
+use crate::shutdown;
+use crate::wifi;
+use crate::wifi::hopper::Hopper;
+
+/// 抓包层错误，由监督循环决定重试、重新配置网卡还是放弃
+#[derive(Debug)]
+pub enum CaptureError {
+    /// 没有打开原始套接字的权限（需要 root 或 CAP_NET_RAW）
+    PermissionDenied { interface: String },
+    /// 网卡不是以太网类型的数据链路通道
+    UnsupportedChannelType { interface: String },
+    /// 网卡消失或已关闭（拔出、驱动重置）
+    DeviceGone { interface: String, source: io::Error },
+    IoError { interface: String, source: io::Error },
+}
+
+/// 监督循环对错误的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Recovery {
+    Retry,
+    Reconfigure,
+    Abort,
+}
+
+impl CaptureError {
+    /// 按 io 错误的类型和 errno 归类
+    pub fn from_io(interface: &str, source: io::Error) -> Self {
+        let interface = interface.to_string();
+        if source.kind() == io::ErrorKind::PermissionDenied {
+            return Self::PermissionDenied { interface };
+        }
+        // ENXIO / ENODEV / ENETDOWN
+        match source.raw_os_error() {
+            Some(6 | 19 | 100) => Self::DeviceGone { interface, source },
+            _ => Self::IoError { interface, source },
+        }
+    }
+
+    pub fn interface(&self) -> &str {
+        match self {
+            Self::PermissionDenied { interface }
+            | Self::UnsupportedChannelType { interface }
+            | Self::DeviceGone { interface, .. }
+            | Self::IoError { interface, .. } => interface,
+        }
+    }
+
+    pub fn recovery(&self) -> Recovery {
+        match self {
+            Self::PermissionDenied { .. } | Self::UnsupportedChannelType { .. } => Recovery::Abort,
+            Self::DeviceGone { .. } => Recovery::Reconfigure,
+            Self::IoError { .. } => Recovery::Retry,
+        }
+    }
+}
+
+impl fmt::Display for CaptureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::PermissionDenied { interface } => write!(f, "{}: 没有抓包权限 (需要 root 或 CAP_NET_RAW)", interface),
+            Self::UnsupportedChannelType { interface } => write!(f, "{}: 不支持的数据链路类型", interface),
+            Self::DeviceGone { interface, source } => write!(f, "{}: 网卡不可用: {}", interface, source),
+            Self::IoError { interface, source } => write!(f, "{}: 读写失败: {}", interface, source),
+        }
+    }
+}
+
+impl std::error::Error for CaptureError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::DeviceGone { source, .. } | Self::IoError { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+/// 抓包后端：逐帧读取监听网卡收到的 radiotap 帧
+pub trait FrameSource {
+    /// 读取下一帧；设置了读超时且超时时返回 `ErrorKind::TimedOut`
+    fn next_frame(&mut self) -> io::Result<&[u8]>;
+}
+
+impl FrameSource for Box<dyn DataLinkReceiver> {
+    fn next_frame(&mut self) -> io::Result<&[u8]> {
+        self.next()
+    }
+}
+
+/// 按名称查找网卡；网卡被拔出或重新枚举期间返回 `DeviceGone`
+///
+/// 重新枚举后网卡序号会变化，每次重新打开抓包前都应重新查找。
+pub fn find_interface(name: &str) -> Result<NetworkInterface, CaptureError> {
+    datalink::interfaces().into_iter().find(|i| i.name == name).ok_or_else(|| CaptureError::DeviceGone {
+        interface: name.to_string(),
+        source: io::Error::new(io::ErrorKind::NotFound, "找不到网卡"),
+    })
+}
+
+fn interface_present(name: &str) -> bool {
+    datalink::interfaces().iter().any(|i| i.name == name)
+}
+
+/// 打开网卡的抓包后端：启用 `libpcap` 特性时使用系统 libpcap (Windows/macOS 上必须启用，Windows 为 Npcap)，
+/// 否则使用 pnet 原始套接字
+pub fn open(interface: &NetworkInterface, read_timeout: Option<Duration>) -> Result<Box<dyn FrameSource>, CaptureError> {
+    open_filtered(interface, read_timeout, None)
+}
+
+/// 内核过滤是否可用：libpcap 后端总是可用，pnet 后端需要 `bpf` 特性
+pub const KERNEL_FILTER: bool = cfg!(any(feature = "libpcap", feature = "bpf"));
+
+/// 同 [`open`]，并在抓包套接字上挂载 BPF 过滤程序 (见 [`crate::bpf`])；
+/// 构建不支持内核过滤 ([`KERNEL_FILTER`]) 时忽略 `filter`
+pub fn open_filtered(interface: &NetworkInterface, read_timeout: Option<Duration>, filter: Option<&[Instruction]>)
+                     -> Result<Box<dyn FrameSource>, CaptureError> {
+    #[cfg(feature = "libpcap")]
+    {
+        crate::libpcap::PcapCapture::open(&interface.name, read_timeout, filter).map(|c| Box::new(c) as Box<dyn FrameSource>)
+    }
+    #[cfg(not(feature = "libpcap"))]
+    {
+        #[cfg(feature = "bpf")]
+        let socket_fd = filter.map(filtered_socket).transpose().map_err(|e| CaptureError::from_io(&interface.name, e))?;
+        #[cfg(not(feature = "bpf"))]
+        let (socket_fd, _) = (None, filter);
+        let config = datalink::Config { read_timeout, socket_fd, ..Default::default() };
+        match datalink::channel(interface, config) {
+            Ok(Channel::Ethernet(_, rx)) => Ok(Box::new(rx)),
+            Ok(_) => Err(CaptureError::UnsupportedChannelType { interface: interface.name.clone() }),
+            Err(e) => Err(CaptureError::from_io(&interface.name, e)),
+        }
+    }
+}
+
+/// 创建挂好过滤程序的 AF_PACKET 套接字，交给 pnet 绑定到网卡
+#[cfg(all(feature = "bpf", not(feature = "libpcap")))]
+fn filtered_socket(filter: &[Instruction]) -> io::Result<i32> {
+    let fd = unsafe { libc::socket(libc::AF_PACKET, libc::SOCK_RAW, (libc::ETH_P_ALL as u16).to_be() as i32) };
+    if fd < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let program = libc::sock_fprog { len: filter.len() as u16, filter: filter.as_ptr() as *mut libc::sock_filter };
+    let attached = unsafe {
+        libc::setsockopt(fd, libc::SOL_SOCKET, libc::SO_ATTACH_FILTER, &program as *const libc::sock_fprog as *const libc::c_void,
+                         size_of::<libc::sock_fprog>() as libc::socklen_t)
+    };
+    if attached < 0 {
+        let e = io::Error::last_os_error();
+        unsafe { libc::close(fd) };
+        return Err(e);
+    }
+    Ok(fd)
+}
+
+/// 打开监听模式网卡的注入通道，发送的帧需自带 radiotap 头
+pub fn open_injector(interface: &NetworkInterface) -> Result<Box<dyn DataLinkSender>, CaptureError> {
+    match datalink::channel(interface, datalink::Config::default()) {
+        Ok(Channel::Ethernet(tx, _)) => Ok(tx),
+        Ok(_) => Err(CaptureError::UnsupportedChannelType { interface: interface.name.clone() }),
+        Err(e) => Err(CaptureError::from_io(&interface.name, e)),
+    }
+}
+
+/// 判断驱动怪癖前观察的帧数
+const QUIRK_SAMPLE_FRAMES: u32 = 200;
+
+/// 运行时检测驱动的 radiotap 怪癖
+///
+/// 有的驱动不填信号强度或信道字段（部分 ath9k/mt76 固件），有的在帧尾附带 FCS。
+/// 观察开头若干帧后每项只报告一次；缺失的字段在解码时记为未知，FCS 在解析 IE 前去掉。
+#[derive(Debug, Default)]
+pub struct QuirkDetector {
+    frames: u32,
+    with_signal: u32,
+    with_channel: u32,
+    with_fcs: u32,
+    reported: bool,
+}
+
+/// 检测结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Quirks {
+    pub missing_signal: bool,
+    pub missing_channel: bool,
+    pub fcs_at_end: bool,
+}
+
+impl QuirkDetector {
+    /// 统计一帧，样本足够时返回一次检测结果
+    pub fn observe(&mut self, packet: &[u8]) -> Option<Quirks> {
+        if self.reported {
+            return None;
+        }
+        let (info, _) = radiotap::parse(packet)?;
+        self.frames += 1;
+        self.with_signal += info.signal_dbm.is_some() as u32;
+        self.with_channel += info.channel_freq.is_some() as u32;
+        self.with_fcs += info.fcs_included() as u32;
+        if self.frames < QUIRK_SAMPLE_FRAMES {
+            return None;
+        }
+        self.reported = true;
+        Some(Quirks {
+            missing_signal: self.with_signal == 0,
+            missing_channel: self.with_channel == 0,
+            fcs_at_end: self.with_fcs > 0,
+        })
+    }
+
+    /// 统计一帧，检测完成时记录发现的怪癖并返回检测结果
+    pub fn check(&mut self, interface: &str, packet: &[u8]) -> Option<Quirks> {
+        let quirks = self.observe(packet)?;
+        if quirks.missing_signal {
+            warn!("{}: driver reports no radiotap signal, RSSI filtering and ranging disabled", interface);
+        }
+        if quirks.missing_channel {
+            warn!("{}: driver reports no radiotap channel, records carry no channel", interface);
+        }
+        if quirks.fcs_at_end {
+            warn!("{}: driver appends FCS to frames, stripping it before decoding", interface);
+        }
+        Some(quirks)
+    }
+}
+
+/// 抓包出错后重新打开网卡的策略
+///
+/// ```toml
+/// [capture]
+/// max_retries = 0       # 连续失败多少次后放弃该网卡，0 为不限
+/// max_backoff_s = 60    # 重试间隔从 1 秒起倍增的上限
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetryPolicy {
+    #[serde(default)]
+    pub max_retries: u32,
+    #[serde(default = "default_max_backoff_s")]
+    pub max_backoff_s: u64,
+}
+
+fn default_max_backoff_s() -> u64 {
+    60
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self { max_retries: 0, max_backoff_s: default_max_backoff_s() }
+    }
+}
+
+impl RetryPolicy {
+    fn max_backoff(&self) -> Duration {
+        Duration::from_secs(self.max_backoff_s.max(1))
+    }
+
+    /// 连续失败 `failures` 次后是否放弃
+    fn exhausted(&self, failures: u32) -> bool {
+        self.max_retries > 0 && failures > self.max_retries
+    }
+}
+
+/// 反复运行抓包循环，直到正常结束、收到退出信号或遇到无法恢复的错误
+///
+/// 可恢复的错误按指数退避重试；网卡消失时等待它重新出现，再重新设置监听模式。
+/// 一次抓包持续超过最长退避时间后，退避时间和失败次数重新计算。连续失败超过
+/// `policy.max_retries` 次时返回最后一个错误。
+pub fn supervise<F>(interface: &str, policy: &RetryPolicy, mut capture: F) -> Result<(), CaptureError>
+where
+    F: FnMut() -> Result<(), CaptureError>,
+{
+    let mut backoff = Duration::from_secs(1);
+    let mut failures = 0;
+    loop {
+        let started = Instant::now();
+        let Err(e) = capture() else { return Ok(()) };
+        if started.elapsed() > policy.max_backoff() {
+            backoff = Duration::from_secs(1);
+            failures = 0;
+        }
+        failures += 1;
+        if e.recovery() == Recovery::Abort || policy.exhausted(failures) {
+            return Err(e);
+        }
+        match e.recovery() {
+            Recovery::Retry => warn!("{}, retrying in {:?}", e, backoff),
+            _ => warn!("{}, reinitializing monitor mode in {:?}", e, backoff),
+        }
+        thread::sleep(backoff);
+        backoff = (backoff * 2).min(policy.max_backoff());
+        if shutdown::requested() {
+            return Ok(());
+        }
+        if e.recovery() != Recovery::Reconfigure {
+            continue;
+        }
+        if !interface_present(interface) {
+            warn!("{} disappeared, waiting for it to reappear", interface);
+            let gone = Instant::now();
+            while !interface_present(interface) {
+                failures += 1;
+                if policy.exhausted(failures) {
+                    return Err(e);
+                }
+                thread::sleep(backoff);
+                backoff = (backoff * 2).min(policy.max_backoff());
+                if shutdown::requested() {
+                    return Ok(());
+                }
+            }
+            info!("{} reappeared after {:?}", interface, gone.elapsed());
+        }
+        if let Err(e) = wifi::reinit_monitor(interface) {
+            warn!("failed to reinitialize {}: {}", interface, e);
+        }
+    }
+}
+
+/// 在单个监听模式网卡上抓包并解码 Remote ID，供其他服务嵌入使用
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use wifi_capture::Capture;
+///
+/// Capture::new("wlan1")
+///     .channels(vec![1, 6, 11])
+///     .dwell(Duration::from_millis(300))
+///     .run(|event| println!("{} {}", event.received_at_ms, event.record.rid))
+///     .unwrap();
+/// ```
+///
+/// 网卡须已处于监听模式；[`run`](Capture::run) 阻塞当前线程，可恢复的错误按 [`supervise`] 重试。
+pub struct Capture {
+    interface: String,
+    channels: Vec<u8>,
+    dwell: Duration,
+    context: DecodeContext,
+    retry: RetryPolicy,
+}
+
+impl Capture {
+    pub fn new(interface: &str) -> Self {
+        Self {
+            interface: interface.to_string(),
+            channels: Vec::new(),
+            dwell: Duration::from_millis(250),
+            context: DecodeContext::default(),
+            retry: RetryPolicy::default(),
+        }
+    }
+
+    /// 在这些信道间轮换；为空时停留在网卡当前信道
+    pub fn channels(mut self, channels: Vec<u8>) -> Self {
+        self.channels = channels;
+        self
+    }
+
+    /// 轮换信道时每个信道的停留时间
+    pub fn dwell(mut self, dwell: Duration) -> Self {
+        self.dwell = dwell;
+        self
+    }
+
+    pub fn decode_options(mut self, options: DecodeOptions) -> Self {
+        self.context.options = options;
+        self
+    }
+
+    /// 启发式扫描明文数据帧中的 Remote ID
+    pub fn deep_scan(mut self, enabled: bool) -> Self {
+        self.context.deep_scan = enabled;
+        self
+    }
+
+    /// 抓包出错后的重试策略，默认不限次数
+    pub fn retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = policy;
+        self
+    }
+
+    /// 使用自定义的解码上下文（轨迹关联、测距、失败样本等）
+    pub fn context(mut self, context: DecodeContext) -> Self {
+        self.context = context;
+        self
+    }
+
+    /// 开始抓包，每条解出的记录以接收时间包装为事件交给 `on_event`
+    pub fn run<F>(mut self, mut on_event: F) -> Result<(), CaptureError>
+    where
+        F: FnMut(DecodedEvent),
+    {
+        find_interface(&self.interface)?;
+        let hopper = Hopper::start(&self.interface, self.channels.clone(), self.dwell, RuntimeControl::new(PathBuf::from(".")));
+        let ctx = &mut self.context;
+        let filter = (ctx.prefilter.kernel_filter && !ctx.deep_scan).then(|| bpf::remote_id_filter(ctx.prefilter.min_packet_len));
+        supervise(&self.interface, &self.retry, || {
+            let interface = find_interface(&self.interface)?;
+            let mut rx = open_filtered(&interface, None, filter.as_deref())?;
+            let mut quirks = QuirkDetector::default();
+            info!("Capturing on {}", interface.name);
+            loop {
+                let packet = rx.next_frame().map_err(|e| CaptureError::from_io(&interface.name, e))?;
+                quirks.check(&interface.name, packet);
+                for mut record in decode::process_packet(packet, ctx) {
+                    record.channel = record.channel.or(hopper.current());
+                    on_event(DecodedEvent::now(record));
+                }
+                ctx.stats.maybe_report();
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capture_error_recovery() {
+        let denied = CaptureError::from_io("wlan0", io::Error::from(io::ErrorKind::PermissionDenied));
+        assert!(matches!(denied, CaptureError::PermissionDenied { .. }));
+        assert_eq!(denied.recovery(), Recovery::Abort);
+
+        let gone = CaptureError::from_io("wlan0", io::Error::from_raw_os_error(100));
+        assert!(matches!(gone, CaptureError::DeviceGone { .. }));
+        assert_eq!(gone.recovery(), Recovery::Reconfigure);
+
+        let other = CaptureError::from_io("wlan0", io::Error::other("boom"));
+        assert_eq!(other.recovery(), Recovery::Retry);
+        assert_eq!(other.interface(), "wlan0");
+
+        let mut attempts = 0;
+        let result = supervise("wlan0", &RetryPolicy::default(), || {
+            attempts += 1;
+            Err(CaptureError::UnsupportedChannelType { interface: "wlan0".into() })
+        });
+        assert!(matches!(result, Err(CaptureError::UnsupportedChannelType { .. })));
+        assert_eq!(attempts, 1);
+
+        // 超过重试次数后放弃
+        let policy = RetryPolicy { max_retries: 1, max_backoff_s: 1 };
+        assert!(policy.exhausted(2) && !policy.exhausted(1) && !RetryPolicy::default().exhausted(1000));
+        let mut attempts = 0;
+        let result = supervise("wlan0", &policy, || {
+            attempts += 1;
+            Err(CaptureError::from_io("wlan0", io::Error::other("boom")))
+        });
+        assert!(matches!(result, Err(CaptureError::IoError { .. })));
+        assert_eq!(attempts, 2);
+    }
+
+    #[test]
+    fn test_quirk_detection() {
+        // present = flags | channel，没有信号强度字段
+        let packet = [0x00, 0x00, 0x0e, 0x00, 0x0a, 0x00, 0x00, 0x00, 0x10, 0x00, 0x85, 0x09, 0xa0, 0x00, 0x80, 0x00];
+        let mut detector = QuirkDetector::default();
+        for _ in 1..QUIRK_SAMPLE_FRAMES {
+            assert_eq!(detector.observe(&packet), None);
+        }
+        let quirks = detector.observe(&packet).unwrap();
+        assert_eq!(quirks, Quirks { missing_signal: true, missing_channel: false, fcs_at_end: true });
+        assert_eq!(detector.observe(&packet), None);
+    }
+}
@@ -0,0 +1,830 @@
+use std::path::PathBuf;
+
+use clap::error::ErrorKind;
+use clap::{CommandFactory, Parser, Subcommand};
+
+use crate::asterix::parse_sac_sic;
+use crate::client::ClientAction;
+use crate::geo::parse_lat_lon;
+use crate::clock::ClockMode;
+use crate::config::{InterfaceProfile, Oui};
+use crate::playback::parse_timestamp_ms;
+use crate::rssi::PathLossModel;
+#[cfg(feature = "database")]
+use crate::storage::{BoundingBox, FixQuery};
+use crate::time_format::OutputTimeZone;
+use crate::flight_export::{FlightFormat, TrackFormat};
+#[cfg(feature = "database")]
+use crate::import::ImportFormat;
+#[cfg(feature = "database")]
+use crate::incident::BundleFormat;
+use crate::traffic_stats::Bucket;
+use crate::units::Units;
+use crate::wifi::hopper::parse_channels;
+
+/// 子命令
+#[derive(Debug)]
+pub enum Command {
+    /// 从录制文件导出按时间段聚合的流量统计 (CSV)
+    StatsExport { input: PathBuf, bucket: Bucket },
+    /// 从录制文件导出单架无人机的带时间航迹
+    ExportFlight { input: PathBuf, id: String, format: FlightFormat },
+    /// 从录制文件、NDJSON 检测输出或数据库导出按无人机聚合的航迹
+    ExportTracks { input: PathBuf, format: TrackFormat, from_ms: Option<i64>, to_ms: Option<i64> },
+    /// 把录制文件、NDJSON 检测输出或数据库中的记录重新上传，`speed` 为 0 时尽快发送，否则按原始间隔的倍速
+    Replay { input: PathBuf, from_ms: Option<i64>, to_ms: Option<i64>, uas: Option<String>, speed: f64 },
+    /// 从录制文件生成区域占用报表 (CSV)
+    ZoneReport { input: PathBuf, zones: PathBuf },
+    /// 查询数据库中的历史记录，或按 ID 子串搜索无人机
+    #[cfg(feature = "database")]
+    /// `tracks` 时查询聚合的航段而不是逐条定位记录
+    Query { db: PathBuf, query: FixQuery, search: Option<String>, tracks: bool },
+    /// 从数据库导出指定时间段/区域的事件包
+    #[cfg(feature = "database")]
+    ExportIncident { db: PathBuf, output: PathBuf, format: BundleFormat, query: FixQuery },
+    /// 将外部工具的日志导入数据库
+    #[cfg(feature = "database")]
+    Import { db: PathBuf, input: PathBuf, format: ImportFormat },
+    /// 并行解码目录下的 pcap 文件并写入数据库
+    #[cfg(feature = "database")]
+    Batch { db: PathBuf, dir: PathBuf, jobs: usize },
+    /// 轮换所有信道勘测 Remote ID 活动并给出监听信道建议
+    Survey { interface: Option<String>, minutes: u64, dwell_ms: u64 },
+    /// 抓包一段时间，诊断网卡/驱动是否适合 Remote ID 监听
+    Diagnose { interface: Option<String>, seconds: u64 },
+    /// 抓包 (或读取抓包文件) 检查被测无人机的广播是否符合标准编码规则
+    Conformance { interface: Option<String>, seconds: u64, input: Option<PathBuf>, uas: Option<String> },
+    /// 检查数据库表结构版本与完整性
+    #[cfg(feature = "database")]
+    DbCheck { db: PathBuf },
+    /// 将数据库升级到最新表结构
+    #[cfg(feature = "database")]
+    DbMigrate { db: PathBuf },
+    /// 用两组解码选项 (或与保存的基线录制文件) 解码同一抓包文件，逐字段比较事件流
+    Compare {
+        input: PathBuf,
+        baseline: Option<PathBuf>,
+        save: Option<PathBuf>,
+        a: Vec<String>,
+        b: Vec<String>,
+        ignore: Vec<String>,
+    },
+    /// 输出实际生效的配置 (JSON)，`redacted` 时遮蔽 webhook 等机密
+    ConfigDump { redacted: bool },
+    /// 环回自测：在一块网卡上注入模拟信标，在另一块网卡上抓包并核对解码结果
+    LoopbackTest { tx: String, rx: String, count: u32, interval_ms: u64, channel: Option<u8> },
+    /// 列出网卡及其 MAC，标明哪些是无线网卡
+    ListInterfaces,
+    /// 通过 HTTP 接口查询和管理另一台 (无头) 接收站
+    Client { dashboard: Option<String>, control: Option<String>, action: ClientAction },
+}
+
+/// 命令行参数，由 [`Cli`] 解析后转换而来
+#[derive(Debug, Default)]
+pub struct Options {
+    pub command: Option<Command>,
+    pub print_schema: bool,
+    pub config: Option<PathBuf>,    // 配置文件 (TOML)
+    pub interface: Option<String>,  // 单网卡模式下的抓包网卡，未指定时只在恰好有一个无线网卡时自动选用
+    pub interfaces: Vec<InterfaceProfile>,  // 多次指定 --interface 时同时抓包的网卡，覆盖配置文件中的 [[interface]]
+    pub output: Option<PathBuf>,    // 检测逐行写为 JSON (NDJSON)，`-` 为标准输出，覆盖配置 [output] ndjson
+    pub upload_url: Option<String>, // 上传地址，覆盖配置文件
+    pub filter_ouis: Vec<Oui>,      // 追加到配置 ignore_ouis 的厂商 OUI，这些发射端的帧在解码前丢弃
+    pub verbose: bool,              // 控制台显示 debug 日志
+    pub tui: bool,                  // 以终端界面代替控制台日志
+    pub control_listen: Option<String>, // 运行时控制接口 HTTP 监听地址
+    pub record: Option<PathBuf>,    // 录制解码事件到文件
+    pub store: Option<PathBuf>,     // 解码记录写入 SQLite 数据库
+    pub pcapng: Option<PathBuf>,    // 原始帧写入 pcapng，Remote ID 帧附带解码摘要注释
+    pub pcap_dir: Option<PathBuf>,  // 原始帧存证目录，按大小/时长轮转的 pcap 文件
+    pub pcap_max_mb: Option<u64>,   // 存证文件超过该大小 (MB) 时轮转
+    pub pcap_max_minutes: Option<u64>,  // 存证文件超过该时长 (分钟) 时轮转
+    pub pcap_rid_only: bool,        // 只存证解出 Remote ID 的帧
+    pub replay: Option<PathBuf>,    // 从文件回放解码事件
+    pub read_file: Option<PathBuf>, // 离线读取 pcap/pcapng 抓包文件，逐帧走与实时抓包相同的解码流程
+    pub replay_speed: f64,          // 回放倍速（同样用于 --read-file）
+    pub lossy_uas_id: bool,         // UAS ID 宽松 UTF-8 解码
+    pub dump_failures: bool,        // 解码失败的帧写入十六进制样本文件
+    pub correlate_macs: bool,       // 启用 MAC 随机化关联
+    pub deep_scan: bool,            // 启发式扫描明文数据帧中内嵌的 Remote ID
+    pub path_loss: Option<PathLossModel>,   // 距离估计使用的路径损耗模型，覆盖配置文件
+    pub bearing_input: Option<String>,  // 天线方位输入设备（串口，每行一个角度）
+    pub bearing_listen: Option<String>, // 天线方位 HTTP 输入监听地址
+    pub timezone: Option<OutputTimeZone>,         // 日志、CSV、导出文件等机器输出的时区，默认 UTC
+    pub display_timezone: Option<OutputTimeZone>, // 面向人的显示时区，默认本地
+    pub clock: ClockMode,               // 记录时间戳的时钟策略
+    pub gpsd: Option<String>,           // gpsd 地址，用于 GPS 校时和接收站定位
+    pub gps_serial: Option<PathBuf>,    // NMEA 串口，用于接收站定位
+    pub watchdog_secs: u64,             // 网卡无帧超过该秒数即重新初始化，0 为关闭
+    pub units: Option<Units>,           // 所有输出使用的单位，覆盖配置文件
+    pub sbs_listen: Option<String>,     // SBS-1 (BaseStation) 输出监听地址，如 0.0.0.0:30003
+    pub asterix: Option<String>,        // ASTERIX CAT 129 (实验性) UDP 目标地址
+    pub asterix_sac_sic: (u8, u8),      // ASTERIX 数据源标识
+    pub geojson_listen: Option<String>, // 实时 GeoJSON 图层 HTTP 监听地址
+    pub live_listen: Option<String>,    // 实时检测推送 (SSE) 监听地址
+    pub basemap: Option<PathBuf>,       // 态势页面的离线底图 (GeoJSON 轮廓)
+    pub zones: Option<PathBuf>,         // 态势页面按区域聚合统计使用的区域 GeoJSON
+    pub mesh: Option<u16>,              // 组网端口：局域网内自动发现其他接收站并向选出的汇聚节点转发
+    pub netrid_listen: Option<String>,  // 网络 Remote ID 推送接入 HTTP 监听地址
+    pub netrid_poll: Option<String>,    // 定期拉取的 USS 网络 Remote ID 显示接口地址
+    pub ble: Option<String>,            // 同时扫描蓝牙 LE Remote ID 的 HCI 设备，如 hci0
+    pub site: Option<(f64, f64)>,       // 接收站位置 (纬度, 经度)，看板按距离排序；没有 GPS 定位时记入记录
+    pub hop: Vec<u8>,                   // 单网卡模式下轮换的信道，为空时停留在当前信道
+    pub dwell_ms: u64,                  // 轮换信道时每个信道的停留时间
+    pub monitor: bool,                  // 启动时通过 nl80211 把抓包网卡设为监听模式，退出时还原
+    pub no_color: bool,                 // 控制台输出不着色
+}
+
+impl Options {
+    /// 解析进程参数，`-h`/`--help` 时打印用法，参数无效时打印错误并以非零状态退出
+    pub fn parse() -> Self {
+        Self::try_parse_from(std::env::args().skip(1)).unwrap_or_else(|e| e.exit())
+    }
+
+    /// 解析不含程序名的参数列表
+    pub fn try_parse_from<I: IntoIterator<Item = String>>(args: I) -> Result<Self, clap::Error> {
+        let args = std::iter::once("wifi-capture".to_string()).chain(args);
+        Cli::try_parse_from(args)?.into_options()
+    }
+}
+
+/// 把返回 `Option` 的解析函数包装为 clap 的值解析器，解析失败时给出格式提示
+fn hint<T: 'static>(parse: fn(&str) -> Option<T>, message: &'static str) -> impl Fn(&str) -> Result<T, String> + Clone + Send + Sync + 'static {
+    move |s| parse(s).ok_or_else(|| message.to_string())
+}
+
+/// 信道列表，格式同 `--hop`
+#[derive(Debug, Clone)]
+struct Channels(Vec<u8>);
+
+fn positive(s: &str) -> Option<u64> {
+    s.parse().ok().filter(|n| *n > 0)
+}
+
+fn timestamp(s: &str) -> Result<i64, String> {
+    parse_timestamp_ms(s).ok_or_else(|| "应为 Unix 毫秒或 RFC 3339 时间".to_string())
+}
+
+#[cfg(feature = "database")]
+fn bbox(s: &str) -> Result<BoundingBox, String> {
+    BoundingBox::parse(s).ok_or_else(|| "格式应为 最小纬度,最小经度,最大纬度,最大经度".to_string())
+}
+
+/// 监听 WiFi/蓝牙 LE 广播的无人机 Remote ID，解码后输出、记录和上传
+///
+/// 不带子命令时实时抓包 (或用 --read-file/--replay 离线处理)；`--config` 等标为全局的参数也可写在子命令参数之后。
+#[derive(Parser)]
+#[command(name = "wifi-capture", version)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<CliCommand>,
+    /// 输出检测记录的 JSON Schema 后退出
+    #[arg(long)]
+    print_schema: bool,
+    /// 配置文件 (TOML)
+    #[arg(long, global = true, value_name = "FILE")]
+    config: Option<PathBuf>,
+    /// 抓包网卡及其信道或频段；只指定一次时为单网卡模式，多次指定时同时抓包，覆盖配置文件中的 [[interface]]
+    #[arg(long, global = true, value_name = "IFACE[=CHANNELS]",
+          value_parser = hint(parse_interface, "格式应为 网卡[=信道或频段]，例如 wlan1=2.4ghz"))]
+    interface: Vec<InterfaceProfile>,
+    /// 检测逐行写为 JSON (NDJSON)，`-` 为标准输出，覆盖配置 [output] ndjson
+    #[arg(long, global = true, value_name = "FILE")]
+    output: Option<PathBuf>,
+    /// 上传地址，覆盖配置文件
+    #[arg(long, global = true, value_name = "URL")]
+    upload_url: Option<String>,
+    /// 追加到配置 ignore_ouis 的厂商 OUI，这些发射端的帧在解码前丢弃；可多次指定
+    #[arg(long, global = true, value_name = "OUI", value_parser = hint(|s| Oui::try_from(s.to_string()).ok(), "应为 3 字节 OUI，例如 60:60:1F"))]
+    filter_oui: Vec<Oui>,
+    /// 控制台显示 debug 日志
+    #[arg(short, long, global = true)]
+    verbose: bool,
+    /// 控制台输出不着色
+    #[arg(long, global = true)]
+    no_color: bool,
+    /// 以终端界面代替控制台日志
+    #[arg(long)]
+    tui: bool,
+    /// 运行时控制接口 HTTP 监听地址
+    #[arg(long, value_name = "ADDR")]
+    control_listen: Option<String>,
+    /// 录制解码事件到文件
+    #[arg(long, value_name = "FILE")]
+    record: Option<PathBuf>,
+    /// 解码记录写入 SQLite 数据库
+    #[arg(long, value_name = "DB")]
+    store: Option<PathBuf>,
+    /// 原始帧写入 pcapng，Remote ID 帧附带解码摘要注释
+    #[arg(long, value_name = "FILE")]
+    pcapng: Option<PathBuf>,
+    /// 原始帧存证目录，按大小/时长轮转的 pcap 文件
+    #[arg(long, value_name = "DIR")]
+    pcap_dir: Option<PathBuf>,
+    /// 存证文件超过该大小 (MB) 时轮转
+    #[arg(long, value_name = "MB", value_parser = hint(positive, "应为正整数 (MB)"))]
+    pcap_max_mb: Option<u64>,
+    /// 存证文件超过该时长 (分钟) 时轮转
+    #[arg(long, value_name = "MINUTES", value_parser = hint(positive, "应为正整数 (分钟)"))]
+    pcap_max_minutes: Option<u64>,
+    /// 只存证解出 Remote ID 的帧
+    #[arg(long)]
+    pcap_rid_only: bool,
+    /// 从录制文件回放解码事件
+    #[arg(long, value_name = "FILE")]
+    replay: Option<PathBuf>,
+    /// 离线读取 pcap/pcapng 抓包文件，逐帧走与实时抓包相同的解码流程
+    #[arg(long, global = true, value_name = "FILE")]
+    read_file: Option<PathBuf>,
+    /// 回放倍速，0 为尽快回放；用于 --replay、--read-file 和 replay 子命令
+    #[arg(long, global = true, value_name = "SPEED", default_value_t = 1.0,
+          value_parser = hint(|s| s.parse().ok().filter(|s: &f64| *s >= 0.0), "应为非负的倍速"))]
+    speed: f64,
+    /// UAS ID 宽松 UTF-8 解码
+    #[arg(long)]
+    lossy_uas_id: bool,
+    /// 解码失败的帧写入十六进制样本文件
+    #[arg(long)]
+    dump_failures: bool,
+    /// 启用 MAC 随机化关联
+    #[arg(long)]
+    correlate_macs: bool,
+    /// 启发式扫描明文数据帧中内嵌的 Remote ID
+    #[arg(long)]
+    deep_scan: bool,
+    /// 距离估计使用的路径损耗模型 P0:n，覆盖配置文件
+    #[arg(long, value_name = "P0:N", value_parser = hint(PathLossModel::parse, "格式应为 P0:n，例如 -40:2.7"))]
+    path_loss: Option<PathLossModel>,
+    /// 天线方位输入设备（串口，每行一个角度）
+    #[arg(long, value_name = "DEVICE")]
+    bearing_input: Option<String>,
+    /// 天线方位 HTTP 输入监听地址
+    #[arg(long, value_name = "ADDR")]
+    bearing_listen: Option<String>,
+    /// 日志、CSV、导出文件等机器输出的时区，默认 UTC
+    #[arg(long, global = true, value_name = "TZ", value_parser = hint(OutputTimeZone::parse, "应为 utc、local 或 +08:00 形式的偏移"))]
+    timezone: Option<OutputTimeZone>,
+    /// 面向人的显示时区，默认本地
+    #[arg(long, global = true, value_name = "TZ", value_parser = hint(OutputTimeZone::parse, "应为 utc、local 或 +08:00 形式的偏移"))]
+    display_timezone: Option<OutputTimeZone>,
+    /// 记录时间戳的时钟策略：system、auto 或 gps
+    #[arg(long, global = true, value_name = "MODE", value_parser = hint(ClockMode::parse, "应为 system、auto 或 gps"))]
+    clock: Option<ClockMode>,
+    /// gpsd 地址，用于 GPS 校时和接收站定位
+    #[arg(long, value_name = "ADDR")]
+    gpsd: Option<String>,
+    /// NMEA 串口，用于接收站定位
+    #[arg(long, value_name = "DEVICE")]
+    gps_serial: Option<PathBuf>,
+    /// 网卡无帧超过该秒数即重新初始化，0 为关闭
+    #[arg(long, value_name = "SECONDS", default_value_t = 60)]
+    watchdog: u64,
+    /// 所有输出使用的单位，覆盖配置文件
+    #[arg(long, global = true, value_name = "UNITS",
+          value_parser = hint(Units::parse, "格式应为 alt=m|ft,speed=m/s|kt|km/h,coords=decimal|dms"))]
+    units: Option<Units>,
+    /// SBS-1 (BaseStation) 输出监听地址，如 0.0.0.0:30003
+    #[arg(long, value_name = "ADDR")]
+    sbs_listen: Option<String>,
+    /// ASTERIX CAT 129 (实验性) UDP 目标地址
+    #[arg(long, value_name = "ADDR")]
+    asterix: Option<String>,
+    /// ASTERIX 数据源标识 SAC:SIC
+    #[arg(long, value_name = "SAC:SIC", value_parser = hint(parse_sac_sic, "格式应为 SAC:SIC，例如 25:7"))]
+    asterix_sac_sic: Option<(u8, u8)>,
+    /// 实时 GeoJSON 图层及态势页面的 HTTP 监听地址
+    #[arg(long, value_name = "ADDR")]
+    geojson_listen: Option<String>,
+    /// 实时检测推送 (SSE) 监听地址
+    #[arg(long, value_name = "ADDR")]
+    live_listen: Option<String>,
+    /// 态势页面的离线底图 (GeoJSON 轮廓)
+    #[arg(long, value_name = "FILE")]
+    basemap: Option<PathBuf>,
+    /// 区域 GeoJSON：态势页面按区域聚合统计，以及 report zones 的区域
+    #[arg(long, global = true, value_name = "FILE")]
+    zones: Option<PathBuf>,
+    /// 组网端口：局域网内自动发现其他接收站并向选出的汇聚节点转发
+    #[arg(long, value_name = "PORT")]
+    mesh: Option<u16>,
+    /// 网络 Remote ID 推送接入 HTTP 监听地址
+    #[arg(long, value_name = "ADDR")]
+    netrid_listen: Option<String>,
+    /// 定期拉取的 USS 网络 Remote ID 显示接口地址
+    #[arg(long, value_name = "URL")]
+    netrid_poll: Option<String>,
+    /// 同时扫描蓝牙 LE Remote ID 的 HCI 设备，如 hci0
+    #[arg(long, value_name = "HCI")]
+    ble: Option<String>,
+    /// 接收站位置，看板按距离排序；没有 GPS 定位时记入记录
+    #[arg(long, value_name = "LAT,LON", value_parser = hint(parse_lat_lon, "格式应为 纬度,经度，例如 31.2304,121.4737"))]
+    site: Option<(f64, f64)>,
+    /// 单网卡模式下轮换的信道或频段，例如 1,6,11 或 2.4ghz,5ghz；未指定时停留在当前信道
+    #[arg(long, value_name = "CHANNELS",
+          value_parser = hint(|s| parse_channels(s).map(Channels), "应为逗号分隔的信道或频段，例如 1,6,11 或 2.4ghz,5ghz"))]
+    hop: Option<Channels>,
+    /// 固定在单个信道，等同于只有一个信道的 --hop
+    #[arg(long, value_name = "CHANNEL", conflicts_with = "hop",
+          value_parser = hint(|s| s.parse().ok().filter(|c: &u8| *c > 0), "应为信道号，例如 6"))]
+    channel: Option<u8>,
+    /// 轮换信道时每个信道的停留时间 (毫秒)
+    #[arg(long, value_name = "MS", default_value_t = 250)]
+    dwell_ms: u64,
+    /// 启动时通过 nl80211 把抓包网卡设为监听模式，退出时还原
+    #[arg(long)]
+    monitor: bool,
+}
+
+#[derive(Subcommand)]
+enum CliCommand {
+    /// 流量统计
+    Stats {
+        #[command(subcommand)]
+        command: StatsCommand,
+    },
+    /// 区域报表
+    Report {
+        #[command(subcommand)]
+        command: ReportCommand,
+    },
+    /// 数据库维护
+    #[cfg(feature = "database")]
+    Db {
+        #[command(subcommand)]
+        command: DbCommand,
+    },
+    /// 配置
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
+    },
+    /// 将外部工具的日志导入数据库
+    #[cfg(feature = "database")]
+    Import {
+        db: PathBuf,
+        input: PathBuf,
+        /// 日志格式，未指定时按文件扩展名判断
+        #[arg(long, value_parser = hint(ImportFormat::parse, "应为 csv、odid、drone-scanner 或 pcap"))]
+        format: Option<ImportFormat>,
+    },
+    /// 并行解码目录下的 pcap 文件并写入数据库
+    #[cfg(feature = "database")]
+    Batch {
+        db: PathBuf,
+        dir: PathBuf,
+        /// 线程数，默认为 CPU 核数
+        #[arg(long)]
+        jobs: Option<usize>,
+    },
+    /// 轮换所有信道勘测 Remote ID 活动并给出监听信道建议 (网卡用 --interface 指定)
+    Survey {
+        #[arg(long, default_value_t = 10)]
+        minutes: u64,
+        /// 每个信道的停留时间 (毫秒)
+        #[arg(long = "dwell", default_value_t = 500)]
+        dwell_ms: u64,
+    },
+    /// 抓包一段时间，诊断网卡/驱动是否适合 Remote ID 监听 (网卡用 --interface 指定)
+    Diagnose {
+        #[arg(long, default_value_t = 30)]
+        seconds: u64,
+    },
+    /// 查询数据库中的历史记录，或按 ID 子串搜索无人机
+    #[cfg(feature = "database")]
+    Query {
+        db: PathBuf,
+        /// UAS ID 前缀
+        #[arg(long)]
+        uas: Option<String>,
+        #[arg(long)]
+        operator: Option<String>,
+        #[arg(long)]
+        tenant: Option<String>,
+        /// 接收站名称
+        #[arg(long)]
+        site: Option<String>,
+        #[arg(long, value_parser = timestamp)]
+        from: Option<i64>,
+        #[arg(long, value_parser = timestamp)]
+        to: Option<i64>,
+        /// 最小纬度,最小经度,最大纬度,最大经度
+        #[arg(long, value_parser = bbox)]
+        bbox: Option<BoundingBox>,
+        #[arg(long)]
+        limit: Option<usize>,
+        /// 查询聚合的航段而不是逐条定位记录
+        #[arg(long)]
+        tracks: bool,
+        /// 按 ID 子串搜索无人机
+        #[arg(long)]
+        search: Option<String>,
+    },
+    /// 导出航迹或事件包
+    Export {
+        #[command(subcommand)]
+        command: ExportCommand,
+    },
+    /// 把录制文件、NDJSON 检测输出或数据库中的记录按原始间隔重新上传 (倍速用 --speed 指定)
+    Replay {
+        input: PathBuf,
+        #[arg(long, value_parser = timestamp)]
+        from: Option<i64>,
+        #[arg(long, value_parser = timestamp)]
+        to: Option<i64>,
+        #[arg(long)]
+        uas: Option<String>,
+        /// 尽快发送，不按原始间隔
+        #[arg(long)]
+        fast: bool,
+    },
+    /// 用两组解码选项 (或与保存的基线录制文件) 解码同一抓包文件，逐字段比较事件流
+    Compare {
+        input: PathBuf,
+        /// A 侧的解码选项，与命令行参数写法相同，例如 "--deep-scan"
+        #[arg(long = "a", allow_hyphen_values = true)]
+        a: Option<String>,
+        /// B 侧的解码选项
+        #[arg(long = "b", allow_hyphen_values = true)]
+        b: Option<String>,
+        /// 作为 A 侧的基线录制文件
+        #[arg(long)]
+        baseline: Option<PathBuf>,
+        /// 把 B 侧结果保存为录制文件
+        #[arg(long)]
+        save: Option<PathBuf>,
+        /// 不比较的字段，逗号分隔
+        #[arg(long)]
+        ignore: Option<String>,
+    },
+    /// 列出网卡及其 MAC，标明哪些是无线网卡
+    ListInterfaces,
+    /// 抓包 (或用 --read-file 读取抓包文件) 检查被测无人机的广播是否符合标准编码规则
+    Conformance {
+        #[arg(long, default_value_t = 60)]
+        seconds: u64,
+        #[arg(long)]
+        uas: Option<String>,
+    },
+    /// 环回自测：在一块网卡上注入模拟信标，在另一块网卡上抓包并核对解码结果
+    LoopbackTest {
+        /// 注入网卡
+        #[arg(long)]
+        tx: String,
+        /// 抓包网卡
+        #[arg(long)]
+        rx: String,
+        #[arg(long, default_value_t = 100, value_parser = hint(|s| s.parse().ok().filter(|n: &u32| *n > 0), "应为正整数"))]
+        count: u32,
+        #[arg(long, default_value_t = 100)]
+        interval_ms: u64,
+        #[arg(long)]
+        channel: Option<u8>,
+    },
+    /// 通过 HTTP 接口查询和管理另一台 (无头) 接收站
+    Client {
+        /// 远程看板地址 (--geojson-listen)
+        #[arg(long)]
+        dashboard: Option<String>,
+        /// 远程控制接口地址 (--control-listen)
+        #[arg(long)]
+        control: Option<String>,
+        #[command(subcommand)]
+        action: ClientCommand,
+    },
+}
+
+#[derive(Subcommand)]
+enum StatsCommand {
+    /// 从录制文件导出按时间段聚合的流量统计 (CSV)
+    Export {
+        input: PathBuf,
+        #[arg(long, default_value = "hour", value_parser = hint(Bucket::parse, "应为 hour 或 day"))]
+        bucket: Bucket,
+    },
+}
+
+#[derive(Subcommand)]
+enum ReportCommand {
+    /// 从录制文件生成区域占用报表 (CSV)，区域用 --zones 指定
+    Zones { input: PathBuf },
+}
+
+#[cfg(feature = "database")]
+#[derive(Subcommand)]
+enum DbCommand {
+    /// 检查数据库表结构版本与完整性
+    Check { db: PathBuf },
+    /// 将数据库升级到最新表结构
+    Migrate { db: PathBuf },
+}
+
+#[derive(Subcommand)]
+enum ConfigCommand {
+    /// 输出实际生效的配置 (JSON)
+    Dump {
+        /// 遮蔽 webhook 等机密
+        #[arg(long)]
+        redacted: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ExportCommand {
+    /// 从录制文件导出单架无人机的带时间航迹
+    Flight {
+        input: PathBuf,
+        /// UAS ID 或轨迹 ID
+        id: String,
+        #[arg(long, default_value = "geojson", value_parser = hint(FlightFormat::parse, "应为 geojson、czml 或 kml"))]
+        format: FlightFormat,
+    },
+    /// 从录制文件、NDJSON 检测输出或数据库导出按无人机聚合的航迹
+    Tracks {
+        input: PathBuf,
+        #[arg(long, default_value = "geojson", value_parser = hint(TrackFormat::parse, "应为 geojson 或 kml"))]
+        format: TrackFormat,
+        #[arg(long, value_parser = timestamp)]
+        from: Option<i64>,
+        #[arg(long, value_parser = timestamp)]
+        to: Option<i64>,
+    },
+    /// 从数据库导出指定时间段/区域的事件包，输出扩展名为 .db/.sqlite 或 .zip
+    #[cfg(feature = "database")]
+    Incident {
+        db: PathBuf,
+        output: PathBuf,
+        #[arg(long, value_parser = timestamp)]
+        from: Option<i64>,
+        #[arg(long, value_parser = timestamp)]
+        to: Option<i64>,
+        #[arg(long, value_parser = bbox)]
+        bbox: Option<BoundingBox>,
+    },
+}
+
+#[derive(Subcommand)]
+enum ClientCommand {
+    /// 列出当前跟踪的无人机
+    Drones,
+    /// 持续输出一架无人机的位置更新
+    Follow {
+        uas_id: String,
+        /// 轮询间隔 (秒)
+        #[arg(long, default_value_t = 2, value_parser = hint(positive, "应为正整数 (秒)"))]
+        interval: u64,
+    },
+    /// 运行状态与统计
+    Stats,
+    /// 实际生效的配置
+    Config,
+    /// 立即写出上传队列和录制缓冲
+    Flush,
+    /// 轮转录制和存证文件
+    Rotate,
+}
+
+impl ClientCommand {
+    fn action(self) -> ClientAction {
+        match self {
+            ClientCommand::Drones => ClientAction::Drones,
+            ClientCommand::Follow { uas_id, interval } => ClientAction::Follow { uas_id, interval_s: interval },
+            ClientCommand::Stats => ClientAction::Stats,
+            ClientCommand::Config => ClientAction::Config,
+            ClientCommand::Flush => ClientAction::Flush,
+            ClientCommand::Rotate => ClientAction::Rotate,
+        }
+    }
+}
+
+/// 参数之间的约束不满足时的错误，与 clap 的解析错误一样打印用法并以非零状态退出
+fn invalid(kind: ErrorKind, message: &str) -> clap::Error {
+    Cli::command().error(kind, message)
+}
+
+impl Cli {
+    fn into_options(self) -> Result<Options, clap::Error> {
+        let mut options = Options {
+            command: None,
+            print_schema: self.print_schema,
+            config: self.config,
+            interface: None,
+            interfaces: self.interface,
+            output: self.output,
+            upload_url: self.upload_url,
+            filter_ouis: self.filter_oui,
+            verbose: self.verbose,
+            tui: self.tui,
+            control_listen: self.control_listen,
+            record: self.record,
+            store: self.store,
+            pcapng: self.pcapng,
+            pcap_dir: self.pcap_dir,
+            pcap_max_mb: self.pcap_max_mb,
+            pcap_max_minutes: self.pcap_max_minutes,
+            pcap_rid_only: self.pcap_rid_only,
+            replay: self.replay,
+            read_file: self.read_file,
+            replay_speed: self.speed,
+            lossy_uas_id: self.lossy_uas_id,
+            dump_failures: self.dump_failures,
+            correlate_macs: self.correlate_macs,
+            deep_scan: self.deep_scan,
+            path_loss: self.path_loss,
+            bearing_input: self.bearing_input,
+            bearing_listen: self.bearing_listen,
+            timezone: self.timezone,
+            display_timezone: self.display_timezone,
+            clock: self.clock.unwrap_or_default(),
+            gpsd: self.gpsd,
+            gps_serial: self.gps_serial,
+            watchdog_secs: self.watchdog,
+            units: self.units,
+            sbs_listen: self.sbs_listen,
+            asterix: self.asterix,
+            asterix_sac_sic: self.asterix_sac_sic.unwrap_or_default(),
+            geojson_listen: self.geojson_listen,
+            live_listen: self.live_listen,
+            basemap: self.basemap,
+            zones: self.zones,
+            mesh: self.mesh,
+            netrid_listen: self.netrid_listen,
+            netrid_poll: self.netrid_poll,
+            ble: self.ble,
+            site: self.site,
+            hop: self.channel.map(|c| vec![c]).or(self.hop.map(|c| c.0)).unwrap_or_default(),
+            dwell_ms: self.dwell_ms,
+            monitor: self.monitor,
+            no_color: self.no_color,
+        };
+        // 只指定一个网卡时仍为单网卡模式，其信道列表等同于 --hop
+        for profile in &mut options.interfaces {
+            profile.dwell_ms = options.dwell_ms;
+        }
+        if let [profile] = options.interfaces.as_slice() {
+            options.interface = Some(profile.name.clone());
+            if !profile.channels.is_empty() {
+                options.hop = profile.channels.clone();
+            }
+            options.interfaces.clear();
+        }
+        options.command = self.command.map(|command| command.into_command(&options)).transpose()?;
+        Ok(options)
+    }
+}
+
+impl CliCommand {
+    /// 子命令共用的 `--interface`、`--read-file`、`--zones`、`--speed` 取自全局参数
+    fn into_command(self, options: &Options) -> Result<Command, clap::Error> {
+        Ok(match self {
+            CliCommand::Stats { command: StatsCommand::Export { input, bucket } } => Command::StatsExport { input, bucket },
+            CliCommand::Report { command: ReportCommand::Zones { input } } => {
+                let zones = options.zones.clone()
+                    .ok_or_else(|| invalid(ErrorKind::MissingRequiredArgument, "report zones 需要 --zones <区域 GeoJSON>"))?;
+                Command::ZoneReport { input, zones }
+            }
+            #[cfg(feature = "database")]
+            CliCommand::Db { command: DbCommand::Check { db } } => Command::DbCheck { db },
+            #[cfg(feature = "database")]
+            CliCommand::Db { command: DbCommand::Migrate { db } } => Command::DbMigrate { db },
+            CliCommand::Config { command: ConfigCommand::Dump { redacted } } => Command::ConfigDump { redacted },
+            #[cfg(feature = "database")]
+            CliCommand::Import { db, input, format } => {
+                let format = format.or_else(|| ImportFormat::detect(&input))
+                    .ok_or_else(|| invalid(ErrorKind::ValueValidation, "无法确定导入格式，请使用 --format 指定"))?;
+                Command::Import { db, input, format }
+            }
+            #[cfg(feature = "database")]
+            CliCommand::Batch { db, dir, jobs } => {
+                let jobs = jobs.unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |n| n.get()));
+                Command::Batch { db, dir, jobs }
+            }
+            CliCommand::Survey { minutes, dwell_ms } => Command::Survey { interface: options.interface.clone(), minutes, dwell_ms },
+            CliCommand::Diagnose { seconds } => Command::Diagnose { interface: options.interface.clone(), seconds },
+            #[cfg(feature = "database")]
+            CliCommand::Query { db, uas, operator, tenant, site, from, to, bbox, limit, tracks, search } => {
+                let query = FixQuery {
+                    uas_id_prefix: uas,
+                    operator_id: operator,
+                    tenant,
+                    site,
+                    from_ms: from,
+                    to_ms: to,
+                    bbox,
+                    limit,
+                };
+                Command::Query { db, query, search, tracks }
+            }
+            CliCommand::Export { command: ExportCommand::Flight { input, id, format } } => Command::ExportFlight { input, id, format },
+            CliCommand::Export { command: ExportCommand::Tracks { input, format, from, to } } => {
+                Command::ExportTracks { input, format, from_ms: from, to_ms: to }
+            }
+            #[cfg(feature = "database")]
+            CliCommand::Export { command: ExportCommand::Incident { db, output, from, to, bbox } } => {
+                let format = BundleFormat::detect(&output)
+                    .ok_or_else(|| invalid(ErrorKind::ValueValidation, "输出文件扩展名应为 .db/.sqlite 或 .zip"))?;
+                let query = FixQuery { from_ms: from, to_ms: to, bbox, ..Default::default() };
+                Command::ExportIncident { db, output, format, query }
+            }
+            CliCommand::Replay { input, from, to, uas, fast } => {
+                let speed = if fast { 0.0 } else { options.replay_speed };
+                Command::Replay { input, from_ms: from, to_ms: to, uas, speed }
+            }
+            CliCommand::Compare { input, a, b, baseline, save, ignore } => {
+                let words = |v: Option<String>| v.map(|v| v.split_whitespace().map(String::from).collect()).unwrap_or_default();
+                let ignore = ignore
+                    .map(|v| v.split(',').map(|f| f.trim().to_string()).filter(|f| !f.is_empty()).collect())
+                    .unwrap_or_default();
+                Command::Compare { input, baseline, save, a: words(a), b: words(b), ignore }
+            }
+            CliCommand::ListInterfaces => Command::ListInterfaces,
+            CliCommand::Conformance { seconds, uas } => Command::Conformance {
+                interface: options.interface.clone(),
+                seconds,
+                input: options.read_file.clone(),
+                uas,
+            },
+            CliCommand::LoopbackTest { tx, rx, count, interval_ms, channel } => Command::LoopbackTest { tx, rx, count, interval_ms, channel },
+            CliCommand::Client { dashboard, control, action } => Command::Client { dashboard, control, action: action.action() },
+        })
+    }
+}
+
+/// 解析 `--interface 网卡[=信道列表]`，信道列表格式同 `--hop`
+fn parse_interface(text: &str) -> Option<InterfaceProfile> {
+    let (name, channels) = match text.split_once('=') {
+        Some((name, channels)) => (name, parse_channels(channels)?),
+        None => (text, Vec::new()),
+    };
+    (!name.is_empty()).then(|| InterfaceProfile::new(name, channels))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Options, clap::Error> {
+        Options::try_parse_from(args.iter().map(|a| a.to_string()))
+    }
+
+    #[test]
+    fn test_parse_capture_options() {
+        let options = parse(&["--interface", "wlan2", "--channel", "149", "--output", "-", "--upload-url", "http://127.0.0.1:9000/api",
+                              "--filter-oui", "60:60:1f", "--filter-oui", "90-3A-E6", "--monitor", "-v"]).unwrap();
+        assert_eq!(options.interface.as_deref(), Some("wlan2"));
+        assert_eq!(options.hop, [149]);
+        assert_eq!(options.output, Some(PathBuf::from("-")));
+        assert_eq!(options.upload_url.as_deref(), Some("http://127.0.0.1:9000/api"));
+        assert_eq!(options.filter_ouis, [Oui([0x60, 0x60, 0x1f]), Oui([0x90, 0x3a, 0xe6])]);
+        assert!(options.monitor && options.verbose);
+        assert_eq!((options.replay_speed, options.watchdog_secs, options.dwell_ms), (1.0, 60, 250));
+
+        let options = parse(&["--interface", "wlan1=2.4ghz", "--interface", "wlan2=149", "--dwell-ms", "400"]).unwrap();
+        assert_eq!(options.interface, None);
+        let plans: Vec<_> = options.interfaces.iter().map(|p| (p.name.as_str(), p.channel_plan(), p.dwell_ms)).collect();
+        assert_eq!(plans, [("wlan1", vec![1, 6, 11], 400), ("wlan2", vec![149], 400)]);
+    }
+
+    #[test]
+    fn test_parse_subcommands() {
+        let options = parse(&["client", "--dashboard", "10.0.0.2:8080", "follow", "RID-1", "--interval", "5"]).unwrap();
+        let Some(Command::Client { dashboard, control: None, action }) = options.command else { panic!() };
+        assert_eq!(dashboard.as_deref(), Some("10.0.0.2:8080"));
+        assert_eq!(action, ClientAction::Follow { uas_id: "RID-1".into(), interval_s: 5 });
+
+        // 全局参数可以写在子命令参数之后
+        let options = parse(&["replay", "uploads.ndjson", "--from", "2024-05-01T00:00:00Z", "--uas", "RID-1", "--fast", "--config", "c.toml"]).unwrap();
+        let Some(Command::Replay { input, from_ms, to_ms: None, uas, speed }) = options.command else { panic!() };
+        assert_eq!((input, from_ms, uas, speed), (PathBuf::from("uploads.ndjson"), Some(1_714_521_600_000), Some("RID-1".into()), 0.0));
+        assert_eq!(options.config, Some(PathBuf::from("c.toml")));
+
+        let options = parse(&["diagnose", "--interface", "wlan1", "--seconds", "5"]).unwrap();
+        assert!(matches!(options.command, Some(Command::Diagnose { interface: Some(ref i), seconds: 5 }) if i == "wlan1"));
+
+        let options = parse(&["compare", "a.pcap", "--b", "--deep-scan --lossy-uas-id", "--ignore", "rssi, record_id"]).unwrap();
+        let Some(Command::Compare { a, b, ignore, .. }) = options.command else { panic!() };
+        assert!(a.is_empty());
+        assert_eq!((b, ignore), (vec!["--deep-scan".to_string(), "--lossy-uas-id".into()], vec!["rssi".to_string(), "record_id".into()]));
+
+        assert!(matches!(parse(&["list-interfaces"]).unwrap().command, Some(Command::ListInterfaces)));
+    }
+
+    #[test]
+    fn test_invalid_arguments_are_errors() {
+        assert_eq!(parse(&["--bogus"]).unwrap_err().kind(), ErrorKind::UnknownArgument);
+        assert_eq!(parse(&["--site", "north"]).unwrap_err().kind(), ErrorKind::ValueValidation);
+        assert_eq!(parse(&["--pcap-max-mb", "0"]).unwrap_err().kind(), ErrorKind::ValueValidation);
+        assert_eq!(parse(&["--hop", "1,6", "--channel", "6"]).unwrap_err().kind(), ErrorKind::ArgumentConflict);
+        assert_eq!(parse(&["report", "zones", "log.bin"]).unwrap_err().kind(), ErrorKind::MissingRequiredArgument);
+        assert_eq!(parse(&["loopback-test", "--tx", "wlan0"]).unwrap_err().kind(), ErrorKind::MissingRequiredArgument);
+        let error = parse(&["--speed", "fast"]).unwrap_err();
+        assert_eq!(error.exit_code(), 2);
+        assert!(error.to_string().contains("应为非负的倍速"));
+    }
+
+    #[test]
+    fn test_help_is_printed() {
+        assert_eq!(parse(&["--help"]).unwrap_err().kind(), ErrorKind::DisplayHelp);
+        assert_eq!(parse(&["export", "-h"]).unwrap_err().kind(), ErrorKind::DisplayHelp);
+        Cli::command().debug_assert();
+    }
+}
@@ -0,0 +1,223 @@
+use std::thread;
+use std::time::Duration;
+
+use reqwest::blocking::Client;
+use serde_json::Value;
+
+/// `client` 子命令对远程接收站执行的操作
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClientAction {
+    /// 列出当前跟踪的无人机（看板接口 `/api/board`）
+    Drones,
+    /// 持续输出一架无人机的位置更新（轮询 `/drones.geojson`）
+    Follow { uas_id: String, interval_s: u64 },
+    /// 运行状态与统计（控制接口 `/status`）
+    Stats,
+    /// 实际生效的配置（控制接口 `/api/config`）
+    Config,
+    /// 立即写出上传队列和录制缓冲（控制接口 `POST /flush`）
+    Flush,
+    /// 轮转录制和存证文件，便于取走已完成的文件（控制接口 `POST /rotate`）
+    Rotate,
+}
+
+/// 远程接收站的 HTTP 接口：`dashboard` 为 `--geojson-listen` 地址，`control` 为 `--control-listen` 地址
+///
+/// 地址可省略 `http://` 前缀，例如 `192.168.1.20:8080`。
+pub struct RemoteSensor {
+    client: Client,
+    dashboard: Option<String>,
+    control: Option<String>,
+}
+
+/// 补全 `http://` 前缀并去掉末尾的 `/`
+fn base_url(addr: &str) -> String {
+    let addr = addr.trim_end_matches('/');
+    if addr.contains("://") { addr.to_string() } else { format!("http://{}", addr) }
+}
+
+impl RemoteSensor {
+    pub fn new(dashboard: Option<&str>, control: Option<&str>) -> Self {
+        let client = Client::builder().timeout(Duration::from_secs(10)).build().unwrap_or_default();
+        Self { client, dashboard: dashboard.map(base_url), control: control.map(base_url) }
+    }
+
+    fn dashboard(&self, path: &str) -> Result<String, String> {
+        let base = self.dashboard.as_ref().ok_or("需要 --dashboard 指定远程看板地址 (--geojson-listen)")?;
+        Ok(format!("{}{}", base, path))
+    }
+
+    fn control(&self, path: &str) -> Result<String, String> {
+        let base = self.control.as_ref().ok_or("需要 --control 指定远程控制接口地址 (--control-listen)")?;
+        Ok(format!("{}{}", base, path))
+    }
+
+    fn get(&self, url: &str) -> Result<Value, String> {
+        self.client.get(url).send()
+            .and_then(|r| r.error_for_status())
+            .and_then(|r| r.json())
+            .map_err(|e| format!("请求 {} 失败: {}", url, e))
+    }
+
+    fn post(&self, url: &str) -> Result<Value, String> {
+        self.client.post(url).send()
+            .and_then(|r| r.error_for_status())
+            .and_then(|r| r.json())
+            .map_err(|e| format!("请求 {} 失败: {}", url, e))
+    }
+
+    /// 执行操作并把结果打印到标准输出；`Follow` 一直运行直到进程退出
+    pub fn run(&self, action: &ClientAction) -> Result<(), String> {
+        match action {
+            ClientAction::Drones => {
+                let board = self.get(&self.dashboard("/api/board")?)?;
+                let rows = board_rows(&board);
+                if rows.is_empty() {
+                    println!("当前没有跟踪的无人机");
+                }
+                for row in rows {
+                    println!("{}", row);
+                }
+            }
+            ClientAction::Follow { uas_id, interval_s } => {
+                let url = self.dashboard("/drones.geojson")?;
+                let mut last_seen = None;
+                loop {
+                    match self.get(&url) {
+                        Ok(layer) => match find_feature(&layer, uas_id) {
+                            Some(feature) if last_seen.as_ref() != Some(&feature["properties"]["last_seen"]) => {
+                                println!("{}", follow_line(feature));
+                                last_seen = Some(feature["properties"]["last_seen"].clone());
+                            }
+                            Some(_) => {}
+                            None if last_seen.is_some() => {
+                                println!("{} 已超出实时图层保留时间", uas_id);
+                                last_seen = None;
+                            }
+                            None => {}
+                        },
+                        Err(e) => eprintln!("{}", e),
+                    }
+                    thread::sleep(Duration::from_secs((*interval_s).max(1)));
+                }
+            }
+            ClientAction::Stats => print_json(&self.get(&self.control("/status")?)?),
+            ClientAction::Config => print_json(&self.get(&self.control("/api/config")?)?),
+            ClientAction::Flush => print_json(&self.post(&self.control("/flush")?)?),
+            ClientAction::Rotate => print_json(&self.post(&self.control("/rotate")?)?),
+        }
+        Ok(())
+    }
+}
+
+fn print_json(value: &Value) {
+    println!("{}", serde_json::to_string_pretty(value).unwrap_or_default());
+}
+
+/// 看板列表按行格式化：标签、类型、距离、高度、多少秒前更新
+fn board_rows(board: &Value) -> Vec<String> {
+    let Some(drones) = board["drones"].as_array() else { return Vec::new() };
+    drones.iter().map(|d| {
+        let distance = d["dist_m"].as_f64().map_or("-".to_string(), |m| format!("{:.0} m", m));
+        format!("{:<24} {:<16} {:>10} {:>8} {:>6}",
+            d["label"].as_str().unwrap_or_default(),
+            d["type"].as_str().unwrap_or_default(),
+            distance,
+            format!("{} m", d["alt_m"]),
+            format!("{} s", d["age_s"]))
+    }).collect()
+}
+
+/// 在实时图层中按 UAS ID 或轨迹 ID 查找无人机
+fn find_feature<'a>(layer: &'a Value, id: &str) -> Option<&'a Value> {
+    layer["features"].as_array()?.iter().find(|f| {
+        let properties = &f["properties"];
+        properties["uas_id"] == id || properties["track_id"] == id
+    })
+}
+
+fn follow_line(feature: &Value) -> String {
+    let properties = &feature["properties"];
+    let coordinates = &feature["geometry"]["coordinates"];
+    format!("{} {} {:.6},{:.6} {} m ({})",
+        properties["last_seen"].as_str().unwrap_or_default(),
+        properties["label"].as_str().unwrap_or_default(),
+        coordinates[1].as_f64().unwrap_or_default(),
+        coordinates[0].as_f64().unwrap_or_default(),
+        properties["altitude_m"],
+        properties["position_status"].as_str().unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn layer() -> Value {
+        json!({ "features": [{
+            "geometry": { "coordinates": [121.4, 31.2, 120.0] },
+            "properties": { "uas_id": "RID-1", "track_id": "02:11:22:33:44:55", "label": "RID-1",
+                            "last_seen": "2025-06-01T08:00:00Z", "altitude_m": 120.0, "position_status": "valid" },
+        }] })
+    }
+
+    #[test]
+    fn test_base_url() {
+        assert_eq!(base_url("192.168.1.20:8080/"), "http://192.168.1.20:8080");
+        assert_eq!(base_url("https://sensor.example.com"), "https://sensor.example.com");
+    }
+
+    #[test]
+    fn test_endpoints_need_address() {
+        let sensor = RemoteSensor::new(Some("10.0.0.2:8080"), None);
+        assert_eq!(sensor.dashboard("/api/board").unwrap(), "http://10.0.0.2:8080/api/board");
+        assert!(sensor.control("/status").unwrap_err().contains("--control"));
+        assert!(sensor.run(&ClientAction::Flush).unwrap_err().contains("--control"));
+        let sensor = RemoteSensor::new(None, Some("10.0.0.2:9090"));
+        assert_eq!(sensor.control("/status").unwrap(), "http://10.0.0.2:9090/status");
+        assert!(sensor.run(&ClientAction::Drones).unwrap_err().contains("--dashboard"));
+    }
+
+    #[test]
+    fn test_request_error_names_url() {
+        let addr = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        let sensor = RemoteSensor::new(None, Some(&addr.to_string()));
+        let error = sensor.run(&ClientAction::Stats).unwrap_err();
+        assert!(error.starts_with(&format!("请求 http://{}/status 失败", addr)), "{}", error);
+    }
+
+    #[test]
+    fn test_board_rows() {
+        let board = json!({ "drones": [
+            { "label": "RID-1", "type": "multirotor", "dist_m": 812.4, "alt_m": 120.0, "age_s": 3 },
+            { "label": "RID-2", "type": "unknown", "dist_m": null, "alt_m": 30, "age_s": 12 },
+        ] });
+        let rows = board_rows(&board);
+        let columns: Vec<Vec<&str>> = rows.iter().map(|row| row.split_whitespace().collect()).collect();
+        assert_eq!(columns, [
+            vec!["RID-1", "multirotor", "812", "m", "120.0", "m", "3", "s"],
+            vec!["RID-2", "unknown", "-", "30", "m", "12", "s"],
+        ]);
+    }
+
+    #[test]
+    fn test_empty_board() {
+        assert!(board_rows(&json!({ "drones": [] })).is_empty());
+        assert!(board_rows(&json!({})).is_empty());
+    }
+
+    #[test]
+    fn test_find_feature_by_uas_or_track_id() {
+        let layer = layer();
+        assert!(find_feature(&layer, "RID-1").is_some());
+        assert!(find_feature(&layer, "02:11:22:33:44:55").is_some());
+        assert!(find_feature(&layer, "RID-2").is_none());
+        assert!(find_feature(&json!({}), "RID-1").is_none());
+    }
+
+    #[test]
+    fn test_follow_line() {
+        let layer = layer();
+        assert_eq!(follow_line(&layer["features"][0]), "2025-06-01T08:00:00Z RID-1 31.200000,121.400000 120.0 m (valid)");
+    }
+}
@@ -0,0 +1,287 @@
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
+
+use crate::receiver;
+
+/// 记录时间戳的来源
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeSource {
+    #[default]
+    System,     // 本机系统时钟
+    Gpsd,       // 以 gpsd 报告的 GPS 时间校正
+    RemoteId,   // 以 Remote ID 系统报文中的时间戳校正（秒级精度）
+}
+
+/// 时钟策略
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ClockMode {
+    /// 始终使用系统时钟
+    System,
+    /// 系统时钟明显不可信（早于程序发布时间，如树莓派断电后无 RTC/NTP）时改用 GPS 时间
+    #[default]
+    Auto,
+    /// 有 GPS 参考时间时始终使用
+    Gps,
+}
+
+impl ClockMode {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "system" => Some(Self::System),
+            "auto" => Some(Self::Auto),
+            "gps" => Some(Self::Gps),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::System => "system",
+            Self::Auto => "auto",
+            Self::Gps => "gps",
+        }
+    }
+}
+
+/// 参考时间与系统时钟的差值
+#[derive(Debug, Clone, Copy)]
+struct Reference {
+    offset_ms: i64,
+    source: TimeSource,
+    updated: Instant,
+}
+
+/// 一个发射端报告的时间与系统时钟的差值
+#[derive(Debug, Clone, Copy)]
+struct Vote {
+    offset_ms: i64,
+    updated: Instant,
+}
+
+/// 可由 GPS 时间校正的时钟
+pub struct Clock {
+    mode: ClockMode,
+    reference: Mutex<Option<Reference>>,
+    /// 各发射端 Remote ID 时间戳给出的差值，按发射端 MAC
+    votes: Mutex<HashMap<String, Vote>>,
+}
+
+impl Clock {
+    /// 早于该时间 (2024-01-01) 的系统时钟视为未同步
+    const MIN_PLAUSIBLE_MS: i64 = 1_704_067_200_000;
+    /// 参考时间超过该时长未更新即失效
+    const REFERENCE_TTL: Duration = Duration::from_secs(3600);
+    /// 至少这么多个发射端的时间一致才采用 Remote ID 时间，单个 (可能伪造的) 发射端不能改时钟
+    const MIN_TRANSMITTERS: usize = 3;
+    /// 发射端之间的差值相差不超过该值 (毫秒) 视为一致；系统报文时间戳为秒级
+    const AGREEMENT_MS: i64 = 5_000;
+    /// 发射端的时间超过该时长未更新即不再计入
+    const VOTE_TTL: Duration = Duration::from_secs(600);
+    /// 同时记录的发射端上限
+    const MAX_VOTES: usize = 256;
+
+    pub fn new(mode: ClockMode) -> Self {
+        Self { mode, reference: Mutex::new(None), votes: Mutex::new(HashMap::new()) }
+    }
+
+    /// 收到一个参考时间（Unix 毫秒）
+    pub fn observe(&self, source: TimeSource, reference_ms: i64) {
+        self.observe_at(source, reference_ms, Utc::now().timestamp_millis());
+    }
+
+    fn observe_at(&self, source: TimeSource, reference_ms: i64, system_ms: i64) {
+        if self.mode == ClockMode::System {
+            return;
+        }
+        if reference_ms < Self::MIN_PLAUSIBLE_MS {
+            debug!("ignoring implausible clock reference {} ms from {:?}", reference_ms, source);
+            return;
+        }
+        let mut reference = self.reference.lock().unwrap();
+        // Remote ID 时间只在系统时钟不可信且没有有效的 gpsd 时间时使用
+        if source == TimeSource::RemoteId
+            && (system_ms >= Self::MIN_PLAUSIBLE_MS
+                || reference.is_some_and(|r| r.source == TimeSource::Gpsd && r.updated.elapsed() < Self::REFERENCE_TTL))
+        {
+            return;
+        }
+        if reference.is_none_or(|r| r.source != source) {
+            info!("clock reference from {:?}, offset {} ms", source, reference_ms - system_ms);
+        }
+        *reference = Some(Reference { offset_ms: reference_ms - system_ms, source, updated: Instant::now() });
+    }
+
+    /// 收到一个发射端的 Remote ID 时间（Unix 毫秒）
+    pub fn observe_transmitter(&self, transmitter: &str, reference_ms: i64) {
+        self.observe_transmitter_at(transmitter, reference_ms, Utc::now().timestamp_millis());
+    }
+
+    /// 记录该发射端给出的差值；有 [`Self::MIN_TRANSMITTERS`] 个发射端相互一致时取其中位数作为参考时间
+    fn observe_transmitter_at(&self, transmitter: &str, reference_ms: i64, system_ms: i64) {
+        if self.mode == ClockMode::System || reference_ms < Self::MIN_PLAUSIBLE_MS {
+            return;
+        }
+        let mut votes = self.votes.lock().unwrap();
+        votes.retain(|_, v| v.updated.elapsed() < Self::VOTE_TTL);
+        if votes.len() >= Self::MAX_VOTES && !votes.contains_key(transmitter) {
+            return;
+        }
+        votes.insert(transmitter.to_string(), Vote { offset_ms: reference_ms - system_ms, updated: Instant::now() });
+        let offsets: Vec<i64> = votes.values().map(|v| v.offset_ms).collect();
+        let agreeing = offsets.iter()
+            .map(|&o| {
+                let mut group: Vec<i64> = offsets.iter().copied().filter(|&x| (x - o).abs() <= Self::AGREEMENT_MS).collect();
+                group.sort_unstable();
+                group
+            })
+            .max_by_key(Vec::len)
+            .filter(|group| group.len() >= Self::MIN_TRANSMITTERS);
+        drop(votes);
+        if let Some(group) = agreeing {
+            self.observe_at(TimeSource::RemoteId, system_ms + group[group.len() / 2], system_ms);
+        }
+    }
+
+    /// 当前时间 (Unix 毫秒) 及其来源
+    pub fn now_ms(&self) -> (i64, TimeSource) {
+        self.resolve(Utc::now().timestamp_millis())
+    }
+
+    fn resolve(&self, system_ms: i64) -> (i64, TimeSource) {
+        let use_reference = match self.mode {
+            ClockMode::System => false,
+            ClockMode::Auto => system_ms < Self::MIN_PLAUSIBLE_MS,
+            ClockMode::Gps => true,
+        };
+        let reference = self.reference.lock().unwrap()
+            .filter(|r| use_reference && r.updated.elapsed() < Self::REFERENCE_TTL);
+        match reference {
+            Some(r) => (system_ms + r.offset_ms, r.source),
+            None => (system_ms, TimeSource::System),
+        }
+    }
+}
+
+static CLOCK: OnceLock<Clock> = OnceLock::new();
+
+/// 启动时设置一次时钟策略
+pub fn configure(mode: ClockMode) {
+    let _ = CLOCK.set(Clock::new(mode));
+}
+
+fn clock() -> &'static Clock {
+    CLOCK.get_or_init(|| Clock::new(ClockMode::default()))
+}
+
+/// 当前时间 (Unix 毫秒) 及其来源
+pub fn now_ms() -> (i64, TimeSource) {
+    clock().now_ms()
+}
+
+/// 发射端 (按 MAC) 的 Remote ID 系统报文时间戳（秒），按 ASTM F3411 自 2019-01-01 起计；
+/// 也兼容直接发送 Unix 秒的实现
+pub fn observe_remote_id(transmitter: &str, timestamp: u32) {
+    const EPOCH_2019: i64 = 1_546_300_800;
+    let seconds = timestamp as i64;
+    let unix = if seconds < EPOCH_2019 { seconds + EPOCH_2019 } else { seconds };
+    clock().observe_transmitter(transmitter, unix * 1000);
+}
+
+/// 连接 gpsd（如 127.0.0.1:2947），用 TPV 报告中的时间校正时钟、更新接收站位置，断开后重连
+pub fn spawn_gpsd_reader(addr: String) {
+    thread::spawn(move || loop {
+        match TcpStream::connect(&addr) {
+            Ok(mut stream) => {
+                info!("reading gps time and position from gpsd {}", addr);
+                if stream.write_all(b"?WATCH={\"enable\":true,\"json\":true}\n").is_ok() {
+                    for line in BufReader::new(stream).lines() {
+                        let Ok(line) = line else { break };
+                        if let Some(ms) = parse_tpv_time(&line) {
+                            clock().observe(TimeSource::Gpsd, ms);
+                        }
+                        if let Some(position) = receiver::parse_tpv(&line) {
+                            receiver::observe(position);
+                        }
+                    }
+                }
+                warn!("gpsd {} disconnected", addr);
+            }
+            Err(e) => warn!("无法连接 gpsd {}: {}", addr, e),
+        }
+        thread::sleep(Duration::from_secs(5));
+    });
+}
+
+fn parse_tpv_time(line: &str) -> Option<i64> {
+    let report: serde_json::Value = serde_json::from_str(line).ok()?;
+    if report["class"] != "TPV" {
+        return None;
+    }
+    let time = DateTime::parse_from_rfc3339(report["time"].as_str()?).ok()?;
+    Some(time.timestamp_millis())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gps_time_fallback() {
+        let gps_ms = 1_760_000_000_000;
+        let bogus_ms = 86_400_000; // 1970-01-02，断电后的系统时钟
+        let clock = Clock::new(ClockMode::Auto);
+        assert_eq!(clock.resolve(bogus_ms), (bogus_ms, TimeSource::System));
+
+        clock.observe_at(TimeSource::Gpsd, gps_ms, bogus_ms);
+        assert_eq!(clock.resolve(bogus_ms + 500), (gps_ms + 500, TimeSource::Gpsd));
+        // 系统时钟可信时不做校正
+        assert_eq!(clock.resolve(gps_ms), (gps_ms, TimeSource::System));
+        // gpsd 有效期内忽略 Remote ID 时间戳
+        clock.observe_at(TimeSource::RemoteId, gps_ms + 3_000, bogus_ms);
+        assert_eq!(clock.resolve(bogus_ms), (gps_ms, TimeSource::Gpsd));
+
+        let line = r#"{"class":"TPV","mode":3,"time":"2025-10-09T08:53:20.000Z","lat":31.2}"#;
+        assert_eq!(parse_tpv_time(line), Some(gps_ms));
+        assert_eq!(parse_tpv_time(r#"{"class":"SKY"}"#), None);
+    }
+
+    #[test]
+    fn test_implausible_reference_is_ignored() {
+        let system_ms = 1_760_000_000_000;
+        let clock = Clock::new(ClockMode::Gps);
+        // 2019-01-09，如某发射端未同步时钟的系统报文
+        clock.observe_at(TimeSource::Gpsd, 1_547_000_000_000, system_ms);
+        assert_eq!(clock.resolve(system_ms), (system_ms, TimeSource::System));
+    }
+
+    #[test]
+    fn test_remote_id_needs_agreeing_transmitters() {
+        let bogus_ms = 86_400_000;
+        let remote_ms = 1_760_000_000_000;
+        let clock = Clock::new(ClockMode::Auto);
+        clock.observe_transmitter_at("02:00:00:00:00:01", remote_ms, bogus_ms);
+        clock.observe_transmitter_at("02:00:00:00:00:02", remote_ms + 1_000, bogus_ms);
+        // 相差很远的发射端不计入一致
+        clock.observe_transmitter_at("02:00:00:00:00:03", remote_ms + 3_600_000, bogus_ms);
+        assert_eq!(clock.resolve(bogus_ms), (bogus_ms, TimeSource::System));
+        clock.observe_transmitter_at("02:00:00:00:00:04", remote_ms + 2_000, bogus_ms);
+        assert_eq!(clock.resolve(bogus_ms), (remote_ms + 1_000, TimeSource::RemoteId));
+
+        // 系统时钟可信时 Remote ID 时间不成为参考，Gps 模式下也不会被套用
+        let clock = Clock::new(ClockMode::Gps);
+        for i in 0..3 {
+            clock.observe_transmitter_at(&format!("02:00:00:00:00:0{}", i), remote_ms - 86_400_000, remote_ms);
+        }
+        assert_eq!(clock.resolve(remote_ms), (remote_ms, TimeSource::System));
+    }
+}
@@ -0,0 +1,307 @@
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::path::Path;
+
+use serde_json::Value;
+
+use crate::cli::Options;
+use crate::config::Config;
+use crate::event_log::{DecodedEvent, EventReader, EventWriter};
+use crate::mapped_pcap::decode_pcap;
+use crate::output::decode_context;
+
+/// 一个字段在两侧的取值，缺失的字段记为 null
+#[derive(Debug, PartialEq)]
+pub struct FieldDiff {
+    pub field: String,
+    pub a: Value,
+    pub b: Value,
+}
+
+/// 两侧都有但字段不同的事件
+#[derive(Debug)]
+pub struct EventDiff {
+    pub key: String,
+    pub fields: Vec<FieldDiff>,
+}
+
+/// 两个事件流的逐字段比较结果
+///
+/// 事件按 (接收时间, UAS ID, 同一时间同一 UAS ID 内的序号) 配对，只在一侧出现的事件单独列出；
+/// 字段以 `operator.latitude`、`raw_messages[1]` 形式的路径表示。
+#[derive(Debug, Default)]
+pub struct CompareReport {
+    pub matched: usize,
+    pub only_a: Vec<String>,
+    pub only_b: Vec<String>,
+    pub changed: Vec<EventDiff>,
+}
+
+type EventKey = (i64, String, usize);
+
+fn keyed(events: &[DecodedEvent]) -> BTreeMap<EventKey, &DecodedEvent> {
+    let mut seen: BTreeMap<(i64, &str), usize> = BTreeMap::new();
+    let mut keyed = BTreeMap::new();
+    for event in events {
+        let uas_id = if event.record.rid.is_empty() { &event.record.track_id } else { &event.record.rid };
+        let nth = seen.entry((event.received_at_ms, uas_id)).or_default();
+        keyed.insert((event.received_at_ms, uas_id.clone(), *nth), event);
+        *nth += 1;
+    }
+    keyed
+}
+
+fn display_key((received_at_ms, uas_id, nth): &EventKey) -> String {
+    format!("{} {}#{}", received_at_ms, uas_id, nth)
+}
+
+/// 展开为 路径 -> 叶子值
+fn flatten(value: &Value, path: String, out: &mut BTreeMap<String, Value>) {
+    match value {
+        Value::Object(map) => {
+            for (name, child) in map {
+                let child_path = if path.is_empty() { name.clone() } else { format!("{}.{}", path, name) };
+                flatten(child, child_path, out);
+            }
+        }
+        Value::Array(items) => {
+            for (i, child) in items.iter().enumerate() {
+                flatten(child, format!("{}[{}]", path, i), out);
+            }
+        }
+        _ => {
+            out.insert(path, value.clone());
+        }
+    }
+}
+
+fn ignored(field: &str, ignore: &[String]) -> bool {
+    ignore.iter().any(|name| {
+        field.strip_prefix(name.as_str()).is_some_and(|rest| rest.is_empty() || rest.starts_with(['.', '[']))
+    })
+}
+
+fn field_diffs(a: &DecodedEvent, b: &DecodedEvent, ignore: &[String]) -> Vec<FieldDiff> {
+    let (mut fields_a, mut fields_b) = (BTreeMap::new(), BTreeMap::new());
+    flatten(&serde_json::to_value(&a.record).unwrap_or_default(), String::new(), &mut fields_a);
+    flatten(&serde_json::to_value(&b.record).unwrap_or_default(), String::new(), &mut fields_b);
+    let mut names: Vec<&String> = fields_a.keys().chain(fields_b.keys()).collect();
+    names.sort();
+    names.dedup();
+    names.into_iter()
+        .filter(|name| !ignored(name, ignore))
+        .filter_map(|name| {
+            let a = fields_a.get(name).cloned().unwrap_or(Value::Null);
+            let b = fields_b.get(name).cloned().unwrap_or(Value::Null);
+            (a != b).then(|| FieldDiff { field: name.clone(), a, b })
+        })
+        .collect()
+}
+
+/// 比较两个事件流，`ignore` 中的字段 (及其子字段) 不参与比较
+pub fn compare(a: &[DecodedEvent], b: &[DecodedEvent], ignore: &[String]) -> CompareReport {
+    let (a, b) = (keyed(a), keyed(b));
+    let mut report = CompareReport::default();
+    for (key, event_a) in &a {
+        let Some(event_b) = b.get(key) else {
+            report.only_a.push(display_key(key));
+            continue;
+        };
+        let fields = field_diffs(event_a, event_b, ignore);
+        if fields.is_empty() {
+            report.matched += 1;
+        } else {
+            report.changed.push(EventDiff { key: display_key(key), fields });
+        }
+    }
+    report.only_b = b.keys().filter(|key| !a.contains_key(key)).map(display_key).collect();
+    report
+}
+
+/// 按一组解码选项 (与命令行参数写法相同) 解码抓包文件，收集全部事件
+pub fn decode_with(input: &Path, args: &[String], config: &Config) -> Result<Vec<DecodedEvent>, String> {
+    let options = Options::try_parse_from(args.iter().cloned()).map_err(|e| e.to_string())?;
+    let mut ctx = decode_context(&options, config);
+    let mut events = Vec::new();
+    decode_pcap(input, &mut ctx, |event| {
+        events.push(event);
+        Ok(())
+    })?;
+    Ok(events)
+}
+
+/// 比较同一抓包文件的两次解码结果
+///
+/// 给出基线录制文件时以其为 A 侧，只用 `b` 解码一次；否则分别用 `a`、`b` 解码。
+/// `save` 把 B 侧保存为录制文件，作为后续版本的基线。
+pub fn compare_pcap(input: &Path, baseline: Option<&Path>, save: Option<&Path>,
+                    a: &[String], b: &[String], ignore: &[String], config: &Config) -> Result<CompareReport, String> {
+    let events_a = match baseline {
+        Some(path) => EventReader::open(path)
+            .and_then(|reader| reader.collect::<io::Result<Vec<_>>>())
+            .map_err(|e| format!("读取基线 {} 失败: {}", path.display(), e))?,
+        None => decode_with(input, a, config)?,
+    };
+    let events_b = decode_with(input, b, config)?;
+    if let Some(path) = save {
+        let mut writer = EventWriter::create(path).map_err(|e| e.to_string())?;
+        for event in &events_b {
+            writer.write(event).map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(compare(&events_a, &events_b, ignore))
+}
+
+impl CompareReport {
+    pub fn is_identical(&self) -> bool {
+        self.only_a.is_empty() && self.only_b.is_empty() && self.changed.is_empty()
+    }
+
+    /// B 侧的事件数
+    pub fn total_b(&self) -> usize {
+        self.matched + self.changed.len() + self.only_b.len()
+    }
+
+    /// 各字段出现差异的事件数
+    pub fn field_counts(&self) -> BTreeMap<&str, usize> {
+        let mut counts = BTreeMap::new();
+        for diff in self.changed.iter().flat_map(|event| &event.fields) {
+            *counts.entry(diff.field.as_str()).or_default() += 1;
+        }
+        counts
+    }
+
+    /// 输出汇总和逐事件差异
+    pub fn write<W: Write>(&self, mut out: W) -> io::Result<()> {
+        writeln!(out, "一致 {} 条，不同 {} 条，仅 A {} 条，仅 B {} 条",
+            self.matched, self.changed.len(), self.only_a.len(), self.only_b.len())?;
+        for (field, count) in self.field_counts() {
+            writeln!(out, "  {}: {} 条不同", field, count)?;
+        }
+        for event in &self.changed {
+            writeln!(out, "{}", event.key)?;
+            for diff in &event.fields {
+                writeln!(out, "  {}: {} -> {}", diff.field, diff.a, diff.b)?;
+            }
+        }
+        for key in &self.only_a {
+            writeln!(out, "仅 A: {}", key)?;
+        }
+        for key in &self.only_b {
+            writeln!(out, "仅 B: {}", key)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::upload_data::UploadData;
+
+    fn event(received_at_ms: i64, rid: &str, latitude: i32) -> DecodedEvent {
+        let record = UploadData { rid: rid.to_string(), latitude, raw_messages: vec!["00".into()], ..Default::default() };
+        DecodedEvent { received_at_ms, record }
+    }
+
+    fn streams() -> (Vec<DecodedEvent>, Vec<DecodedEvent>) {
+        let a = vec![event(1_000, "A", 1), event(1_000, "A", 2), event(2_000, "B", 3), event(3_000, "C", 4)];
+        let mut b = vec![event(1_000, "A", 1), event(1_000, "A", 5), event(2_000, "B", 3), event(4_000, "D", 6)];
+        b[2].record.raw_messages.push("01".into());
+        b[2].record.record_id = "changed".into();
+        (a, b)
+    }
+
+    #[test]
+    fn test_identical_streams() {
+        let (a, _) = streams();
+        let report = compare(&a, &a, &[]);
+        assert!(report.is_identical());
+        assert_eq!(report.matched, 4);
+    }
+
+    #[test]
+    fn test_events_paired_by_order_within_key() {
+        let (a, b) = streams();
+        let report = compare(&a, &b, &["record_id".to_string(), "raw_messages".to_string()]);
+        assert_eq!(report.matched, 2);
+        assert_eq!(report.changed.len(), 1);
+        assert_eq!(report.changed[0].key, "1000 A#1");
+        assert_eq!(report.changed[0].fields, [FieldDiff { field: "latitude".into(), a: 2.into(), b: 5.into() }]);
+    }
+
+    #[test]
+    fn test_unpaired_events_listed_per_side() {
+        let (a, b) = streams();
+        let report = compare(&a, &b, &[]);
+        assert!(!report.is_identical());
+        assert_eq!(report.only_a, ["3000 C#0"]);
+        assert_eq!(report.only_b, ["4000 D#0"]);
+    }
+
+    #[test]
+    fn test_missing_array_element_is_null() {
+        let (a, b) = streams();
+        let report = compare(&a, &b, &["record_id".to_string()]);
+        assert_eq!(report.changed[1].fields, [FieldDiff { field: "raw_messages[1]".into(), a: Value::Null, b: "01".into() }]);
+        assert_eq!(report.field_counts().into_iter().collect::<Vec<_>>(), [("latitude", 1), ("raw_messages[1]", 1)]);
+    }
+
+    #[test]
+    fn test_ignore_matches_whole_path_segments() {
+        assert!(ignored("record_id", &["record_id".to_string()]));
+        assert!(ignored("raw_messages[1]", &["raw_messages".to_string()]));
+        assert!(ignored("operator.latitude", &["operator".to_string()]));
+        assert!(!ignored("operator_id", &["operator".to_string()]));
+        assert!(!ignored("raw_messages[1]", &["raw".to_string()]));
+    }
+
+    #[test]
+    fn test_flatten_nested_paths() {
+        let mut out = BTreeMap::new();
+        flatten(&serde_json::json!({"operator": {"latitude": 1}, "raw": ["a", "b"], "rid": "X"}), String::new(), &mut out);
+        assert_eq!(out.keys().collect::<Vec<_>>(), ["operator.latitude", "raw[0]", "raw[1]", "rid"]);
+    }
+
+    #[test]
+    fn test_track_id_keys_events_without_rid() {
+        let mut a = event(1_000, "", 1);
+        a.record.track_id = "mac-1".into();
+        let report = compare(&[a], &[], &[]);
+        assert_eq!(report.only_a, ["1000 mac-1#0"]);
+    }
+
+    #[test]
+    fn test_write_report() {
+        let (a, b) = streams();
+        let mut out = Vec::new();
+        compare(&a, &b, &["record_id".to_string()]).write(&mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "一致 1 条，不同 2 条，仅 A 1 条，仅 B 1 条");
+        assert!(lines.contains(&"  latitude: 2 -> 5"));
+        assert!(lines.contains(&"  raw_messages[1]: null -> \"01\""));
+        assert_eq!(lines[lines.len() - 2..], ["仅 A: 3000 C#0", "仅 B: 4000 D#0"]);
+    }
+
+    #[test]
+    fn test_compare_pcap_against_saved_baseline() {
+        let dir = std::env::temp_dir().join("wifi-capture-compare-pcap-test");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let frame = crate::test_support::Fixture::load("rid_beacon").frame;
+        let mut pcap = Vec::new();
+        crate::pcap_writer::PcapWriter::new(&mut pcap).unwrap().write(1_748_764_800_000_000, &frame).unwrap();
+        let (input, baseline) = (dir.join("capture.pcap"), dir.join("baseline.rec"));
+        std::fs::write(&input, &pcap).unwrap();
+
+        let config = Config::default();
+        let report = compare_pcap(&input, None, Some(&baseline), &[], &[], &[], &config).unwrap();
+        assert!(report.is_identical() && report.total_b() > 0);
+        let again = compare_pcap(&input, Some(&baseline), None, &[], &[], &[], &config).unwrap();
+        std::fs::remove_dir_all(&dir).ok();
+        assert!(again.is_identical());
+        assert_eq!(again.matched, report.matched);
+        assert!(compare_pcap(&dir.join("missing.pcap"), None, None, &[], &[], &[], &config).is_err());
+    }
+}
@@ -0,0 +1,464 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{self, Write};
+
+use crate::event_log::DecodedEvent;
+use crate::message::message_pack::MessagePack;
+use crate::message::MESSAGE_LEN;
+use crate::remote_id;
+
+/// 违反的编码规则类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Rule {
+    /// 取值超出标准规定的范围或使用了保留值
+    Range,
+    /// 取值全部落在比标准更粗的网格上（如高度按 1 m 而非 0.5 m 编码）
+    Resolution,
+    /// 缺少必须广播的消息类型
+    MissingMessage,
+    /// 广播间隔超过标准要求
+    BroadcastRate,
+}
+
+impl Rule {
+    fn label(self) -> &'static str {
+        match self {
+            Rule::Range => "取值范围",
+            Rule::Resolution => "分辨率",
+            Rule::MissingMessage => "缺少消息",
+            Rule::BroadcastRate => "广播频率",
+        }
+    }
+}
+
+/// 一项不符合
+#[derive(Debug, Clone, PartialEq)]
+pub struct Violation {
+    pub rule: Rule,
+    pub field: String,
+    pub detail: String,
+}
+
+const BASIC_ID: u8 = 0x0;
+const LOCATION: u8 = 0x1;
+const SELF_ID: u8 = 0x3;
+const SYSTEM: u8 = 0x4;
+const OPERATOR_ID: u8 = 0x5;
+
+/// 必须广播的消息类型及最大广播间隔 (毫秒)：位置向量为动态消息，至少 1 Hz；
+/// 其余为静态消息，至少每 3 秒一次 (ASTM F3411-22a 5.4.5, ASD-STAN EN 4709-002)
+const REQUIRED: [(u8, &str, i64); 4] = [
+    (BASIC_ID, "Basic ID", 3_000),
+    (LOCATION, "位置向量", 1_000),
+    (SYSTEM, "System", 3_000),
+    (OPERATOR_ID, "Operator ID", 3_000),
+];
+
+/// 判断分辨率至少需要的不同取值数，样本太少时公约数大于 1 可能只是巧合
+const RESOLUTION_SAMPLES: usize = 20;
+
+/// 某个数值字段的取值，用于判断编码分辨率
+#[derive(Debug, Default)]
+struct Samples {
+    gcd: u64,
+    distinct: BTreeSet<u64>,
+}
+
+impl Samples {
+    fn add(&mut self, value: u64) {
+        if value == 0 {
+            return;
+        }
+        self.gcd = gcd(self.gcd, value);
+        if self.distinct.len() < RESOLUTION_SAMPLES {
+            self.distinct.insert(value);
+        }
+    }
+
+    /// 取值足够多且有大于 1 的公约数时返回该公约数
+    fn coarse(&self) -> Option<u64> {
+        (self.distinct.len() >= RESOLUTION_SAMPLES && self.gcd > 1).then_some(self.gcd)
+    }
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+/// 单架无人机的检查结果
+#[derive(Debug, Default)]
+pub struct DroneConformance {
+    pub first_ms: i64,
+    pub last_ms: i64,
+    /// 消息类型 → 收到次数
+    pub messages: BTreeMap<u8, u64>,
+    /// 消息类型 → (上次收到时间, 最大间隔, 超过要求的间隔数)
+    gaps: BTreeMap<u8, (i64, i64, u64)>,
+    /// (规则, 字段) → (次数, 首个违规值)
+    range: BTreeMap<(Rule, &'static str), (u64, String)>,
+    resolution: BTreeMap<&'static str, Samples>,
+}
+
+impl DroneConformance {
+    fn observe(&mut self, at_ms: i64, raw: &[u8]) {
+        let message_type = raw[0] >> 4;
+        *self.messages.entry(message_type).or_default() += 1;
+        let limit = REQUIRED.iter().find(|(t, _, _)| *t == message_type).map_or(i64::MAX, |(_, _, limit)| *limit);
+        let gap = self.gaps.entry(message_type).or_insert((at_ms, 0, 0));
+        let interval = at_ms - gap.0;
+        gap.0 = at_ms;
+        gap.1 = gap.1.max(interval);
+        if interval > limit {
+            gap.2 += 1;
+        }
+
+        self.check(raw[0] & 0x0f <= 2, "header.protocol_version", raw[0] & 0x0f);
+        let p = &raw[1..];
+        match message_type {
+            BASIC_ID => {
+                self.check(p[0] >> 4 <= 4, "basic_id.id_type", p[0] >> 4);
+                self.check_text(p[0] >> 4 == 0, &p[1..21], "basic_id.uas_id");
+            }
+            LOCATION => self.check_location(p),
+            SELF_ID => {
+                self.check(p[0] <= 2 || p[0] >= 201, "self_id.description_type", p[0]);
+                self.check_text(true, &p[1..24], "self_id.description");
+            }
+            SYSTEM => {
+                self.check(p[0] & 0x03 <= 2, "system.operator_location_type", p[0] & 0x03);
+                self.check((p[0] >> 2) & 0x07 <= 2, "system.classification_region", (p[0] >> 2) & 0x07);
+                let (lat, lon) = (i32::from_le_bytes(p[1..5].try_into().unwrap()), i32::from_le_bytes(p[5..9].try_into().unwrap()));
+                self.check(lat.unsigned_abs() <= 900_000_000, "system.operator_latitude", lat);
+                self.check(lon.unsigned_abs() <= 1_800_000_000, "system.operator_longitude", lon);
+                // 仅 EU 分类时类别 (高 4 位) 和等级 (低 4 位) 有定义
+                if (p[0] >> 2) & 0x07 == 1 {
+                    self.check(p[16] >> 4 <= 3, "system.ua_category", p[16] >> 4);
+                    self.check(p[16] & 0x0f <= 7, "system.ua_class", p[16] & 0x0f);
+                }
+            }
+            OPERATOR_ID => self.check_text(true, &p[1..21], "operator_id.operator_id"),
+            _ => {}
+        }
+    }
+
+    fn check_location(&mut self, p: &[u8]) {
+        let u16_at = |i: usize| u16::from_le_bytes([p[i], p[i + 1]]);
+        let i32_at = |i: usize| i32::from_le_bytes(p[i..i + 4].try_into().unwrap());
+        self.check(p[0] >> 4 <= 4, "location.operational_status", p[0] >> 4);
+        // 航迹角 0..359，361 表示未知；E/W 位为 1 时加 180
+        let direction = u16::from(p[1]) + if p[0] & 0x02 != 0 { 180 } else { 0 };
+        self.check(direction < 360 || direction == 361, "location.track_direction", direction);
+        // 垂直速度 ×0.5 m/s，范围 ±62 m/s，63 m/s 表示未知
+        let vertical = p[3] as i8;
+        self.check((-124..=124).contains(&vertical) || vertical == 126, "location.vertical_speed", vertical);
+        let (lat, lon) = (i32_at(4), i32_at(8));
+        self.check(lat.unsigned_abs() <= 900_000_000, "location.latitude", lat);
+        self.check(lon.unsigned_abs() <= 1_800_000_000, "location.longitude", lon);
+        self.check(p[18] >> 4 <= 6, "location.vertical_accuracy", p[18] >> 4);
+        self.check(p[18] & 0x0f <= 12, "location.horizontal_accuracy", p[18] & 0x0f);
+        self.check(p[19] >> 4 <= 6, "location.baro_altitude_accuracy", p[19] >> 4);
+        self.check(p[19] & 0x0f <= 4, "location.speed_accuracy", p[19] & 0x0f);
+        // 整点后的 0.1 秒数，0xFFFF 表示未知
+        let timestamp = u16_at(20);
+        self.check(timestamp <= 36_000 || timestamp == 0xffff, "location.timestamp", timestamp);
+
+        self.sample("location.latitude", lat.unsigned_abs().into());
+        self.sample("location.longitude", lon.unsigned_abs().into());
+        for (field, offset) in [("location.pressure_altitude", 12), ("location.geodetic_altitude", 14), ("location.height", 16)] {
+            self.sample(field, u16_at(offset).into());
+        }
+        if p[0] & 0x01 == 0 && p[2] != 255 {
+            self.sample("location.speed", p[2].into());
+        }
+    }
+
+    fn check(&mut self, ok: bool, field: &'static str, value: impl std::fmt::Display) {
+        if !ok {
+            self.range.entry((Rule::Range, field)).or_insert_with(|| (0, value.to_string())).0 += 1;
+        }
+    }
+
+    /// 文本字段应为可打印 ASCII，以 NUL 补齐；`may_be_empty` 为 false 时不能为空
+    fn check_text(&mut self, may_be_empty: bool, bytes: &[u8], field: &'static str) {
+        let len = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+        let (text, padding) = bytes.split_at(len);
+        let ok = text.iter().all(|b| (0x20..0x7f).contains(b)) && padding.iter().all(|b| *b == 0) && (may_be_empty || len > 0);
+        self.check(ok, field, remote_id::to_hex(bytes));
+    }
+
+    fn sample(&mut self, field: &'static str, value: u64) {
+        self.resolution.entry(field).or_default().add(value);
+    }
+
+    /// 汇总全部不符合项
+    pub fn violations(&self) -> Vec<Violation> {
+        let mut violations: Vec<Violation> = self.range.iter()
+            .map(|((rule, field), (count, example))| Violation {
+                rule: *rule,
+                field: field.to_string(),
+                detail: format!("{} 次，例如 {}", count, example),
+            })
+            .collect();
+        for (field, samples) in &self.resolution {
+            if let Some(step) = samples.coarse() {
+                violations.push(Violation {
+                    rule: Rule::Resolution,
+                    field: field.to_string(),
+                    detail: format!("所有取值均为编码单位的 {} 倍，未使用标准分辨率", step),
+                });
+            }
+        }
+        for (message_type, name, limit) in REQUIRED {
+            match self.gaps.get(&message_type) {
+                None => violations.push(Violation {
+                    rule: Rule::MissingMessage,
+                    field: name.to_string(),
+                    detail: "未收到".into(),
+                }),
+                Some((_, max_gap, over)) if *over > 0 => violations.push(Violation {
+                    rule: Rule::BroadcastRate,
+                    field: name.to_string(),
+                    detail: format!("最大间隔 {:.1} 秒 (应不超过 {} 秒)，超限 {} 次",
+                                    *max_gap as f64 / 1000.0, limit / 1000, over),
+                }),
+                Some(_) => {}
+            }
+        }
+        violations
+    }
+}
+
+/// 一次认证测试的检查器：按 UAS ID 汇总收到的每条原始消息
+///
+/// 检查的内容是 ASTM F3411 / ASD-STAN EN 4709-002 的编码规则：取值范围与保留值、
+/// 编码分辨率、必须广播的消息类型以及广播频率。检查基于原始 25 字节消息，
+/// 解码失败的消息同样计入。
+#[derive(Debug, Default)]
+pub struct ConformanceCheck {
+    /// 只检查该 UAS ID（被测无人机）
+    pub uas_filter: Option<String>,
+    pub drones: BTreeMap<String, DroneConformance>,
+}
+
+impl ConformanceCheck {
+    pub fn new(uas_filter: Option<String>) -> Self {
+        Self { uas_filter, drones: BTreeMap::new() }
+    }
+
+    pub fn observe(&mut self, event: &DecodedEvent) {
+        let record = &event.record;
+        let uas_id = if record.rid.is_empty() { &record.track_id } else { &record.rid };
+        if self.uas_filter.as_ref().is_some_and(|filter| filter != uas_id) {
+            return;
+        }
+        let drone = self.drones.entry(uas_id.clone()).or_insert_with(|| DroneConformance {
+            first_ms: event.received_at_ms,
+            ..Default::default()
+        });
+        drone.last_ms = event.received_at_ms;
+        for raw in raw_messages(record) {
+            drone.observe(event.received_at_ms, &raw);
+        }
+    }
+
+    /// 输出报告，所有无人机均符合时返回 true
+    pub fn write_report<W: Write>(&self, mut out: W) -> io::Result<bool> {
+        if self.drones.is_empty() {
+            writeln!(out, "未收到被测无人机的 Remote ID")?;
+            return Ok(false);
+        }
+        let mut passed = true;
+        for (uas_id, drone) in &self.drones {
+            writeln!(out, "UAS ID: {} ({:.1} 秒)", uas_id, (drone.last_ms - drone.first_ms) as f64 / 1000.0)?;
+            let counts: Vec<String> = drone.messages.iter().map(|(t, n)| format!("类型 {:X}: {}", t, n)).collect();
+            writeln!(out, "  消息: {}", counts.join(", "))?;
+            let violations = drone.violations();
+            for violation in &violations {
+                writeln!(out, "  [{}] {}: {}", violation.rule.label(), violation.field, violation.detail)?;
+            }
+            if violations.is_empty() {
+                writeln!(out, "  结论: 符合")?;
+            } else {
+                writeln!(out, "  结论: {} 项不符合", violations.len())?;
+                passed = false;
+            }
+        }
+        Ok(passed)
+    }
+}
+
+/// 记录中的原始消息；单机消息包取自原始负载，这样解码失败的消息也能检查
+fn raw_messages(record: &crate::upload_data::UploadData) -> Vec<Vec<u8>> {
+    let payload = remote_id::from_hex(&record.raw_payload).unwrap_or_default();
+    if let Ok(pack) = MessagePack::from_payload(&payload) {
+        let basic_ids = pack.raw_messages().filter(|raw| raw[0] >> 4 == BASIC_ID).count();
+        if basic_ids <= 1 {
+            return pack.raw_messages().map(<[u8]>::to_vec).collect();
+        }
+    }
+    // 中继包带有多架无人机的消息，只取归到本记录的部分
+    record.raw_messages.iter()
+        .filter_map(|hex| remote_id::from_hex(hex))
+        .filter(|raw| raw.len() == MESSAGE_LEN)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::upload_data::UploadData;
+
+    fn pack(messages: &[[u8; MESSAGE_LEN]]) -> String {
+        let mut payload = vec![0x00, 0xf2, MESSAGE_LEN as u8, messages.len() as u8];
+        for message in messages {
+            payload.extend_from_slice(message);
+        }
+        remote_id::to_hex(&payload)
+    }
+
+    const UAS_ID: &str = "1581F5FKD229400A";
+
+    fn message(header: u8, body: &[(usize, u8)]) -> [u8; MESSAGE_LEN] {
+        let mut message = [0u8; MESSAGE_LEN];
+        message[0] = header;
+        for (offset, byte) in body {
+            message[*offset] = *byte;
+        }
+        message
+    }
+
+    fn basic_id() -> [u8; MESSAGE_LEN] {
+        let mut basic = message(0x02, &[(1, 0x12)]);
+        basic[2..18].copy_from_slice(UAS_ID.as_bytes());
+        basic
+    }
+
+    /// 每 `interval_ms` 收到一次同样的消息包，共 `count` 次
+    fn observe(check: &mut ConformanceCheck, messages: &[[u8; MESSAGE_LEN]], interval_ms: i64, count: i64) {
+        for i in 0..count {
+            let record = UploadData { rid: UAS_ID.into(), raw_payload: pack(messages), ..Default::default() };
+            check.observe(&DecodedEvent { received_at_ms: i * interval_ms, record });
+        }
+    }
+
+    /// 1 Hz 广播全部必需消息的被测无人机，`location` 为位置向量报文
+    fn drone(location: [u8; MESSAGE_LEN]) -> Vec<Violation> {
+        let mut check = ConformanceCheck::new(None);
+        observe(&mut check, &[basic_id(), location, message(0x42, &[]), message(0x52, &[])], 1_000, 5);
+        check.drones[UAS_ID].violations()
+    }
+
+    fn drone_with_basic(basic: [u8; MESSAGE_LEN]) -> Vec<Violation> {
+        let mut check = ConformanceCheck::new(None);
+        observe(&mut check, &[basic, message(0x12, &[]), message(0x42, &[]), message(0x52, &[])], 1_000, 1);
+        check.drones[UAS_ID].violations()
+    }
+
+    fn fields(violations: &[Violation]) -> Vec<(Rule, &str)> {
+        violations.iter().map(|v| (v.rule, v.field.as_str())).collect()
+    }
+
+    #[test]
+    fn test_conforming_drone() {
+        let mut check = ConformanceCheck::new(None);
+        observe(&mut check, &[basic_id(), message(0x12, &[]), message(0x42, &[]), message(0x52, &[])], 1_000, 5);
+        assert!(check.drones[UAS_ID].violations().is_empty());
+        let mut report = Vec::new();
+        assert!(check.write_report(&mut report).unwrap());
+        let report = String::from_utf8(report).unwrap();
+        assert!(report.contains("UAS ID: 1581F5FKD229400A (4.0 秒)"), "{}", report);
+        assert!(report.contains("类型 0: 5, 类型 1: 5, 类型 4: 5, 类型 5: 5"), "{}", report);
+        assert!(report.contains("结论: 符合"), "{}", report);
+    }
+
+    #[test]
+    fn test_range_violations() {
+        // 垂直速度 63.5 m/s 超出范围，水平精度 13 为保留值
+        let violations = drone(message(0x12, &[(1, 0x20), (4, 0x7f), (19, 0x0d)]));
+        assert_eq!(fields(&violations), [
+            (Rule::Range, "location.horizontal_accuracy"),
+            (Rule::Range, "location.vertical_speed"),
+        ]);
+        assert_eq!(violations[1].detail, "5 次，例如 127");
+    }
+
+    #[test]
+    fn test_unknown_values_are_in_range() {
+        // 垂直速度 63 m/s (126) 与时间戳 0xFFFF 表示未知
+        assert!(drone(message(0x12, &[(4, 126), (21, 0xff), (22, 0xff)])).is_empty());
+        // 航迹角 181 + 180 = 361 表示未知，200 + 180 超出 359
+        assert!(drone(message(0x12, &[(1, 0x02), (2, 181)])).is_empty());
+        let violations = drone(message(0x12, &[(1, 0x02), (2, 200)]));
+        assert_eq!(fields(&violations), [(Rule::Range, "location.track_direction")]);
+    }
+
+    #[test]
+    fn test_text_fields() {
+        let mut check = ConformanceCheck::new(None);
+        let mut basic = basic_id();
+        basic[3] = 0x07;   // 不可打印字符
+        let operator = message(0x52, &[(2, b'O'), (4, b'X')]);   // NUL 之后还有内容
+        observe(&mut check, &[basic, message(0x12, &[]), message(0x42, &[]), operator], 1_000, 1);
+        assert_eq!(fields(&check.drones[UAS_ID].violations()), [
+            (Rule::Range, "basic_id.uas_id"),
+            (Rule::Range, "operator_id.operator_id"),
+        ]);
+        // 未声明 ID 类型时 UAS ID 可以为空，否则不能为空
+        assert!(drone_with_basic(message(0x02, &[])).is_empty());
+        assert_eq!(fields(&drone_with_basic(message(0x02, &[(1, 0x12)]))), [(Rule::Range, "basic_id.uas_id")]);
+    }
+
+    #[test]
+    fn test_broadcast_rate() {
+        let mut check = ConformanceCheck::new(None);
+        // 每 2 秒一次：位置向量超过 1 秒的要求，静态消息在 3 秒以内
+        observe(&mut check, &[basic_id(), message(0x12, &[]), message(0x42, &[]), message(0x52, &[])], 2_000, 5);
+        let violations = check.drones[UAS_ID].violations();
+        assert_eq!(fields(&violations), [(Rule::BroadcastRate, "位置向量")]);
+        assert_eq!(violations[0].detail, "最大间隔 2.0 秒 (应不超过 1 秒)，超限 4 次");
+    }
+
+    #[test]
+    fn test_missing_messages() {
+        let mut check = ConformanceCheck::new(None);
+        observe(&mut check, &[basic_id(), message(0x12, &[])], 1_000, 3);
+        assert_eq!(fields(&check.drones[UAS_ID].violations()), [
+            (Rule::MissingMessage, "System"),
+            (Rule::MissingMessage, "Operator ID"),
+        ]);
+        let mut report = Vec::new();
+        assert!(!check.write_report(&mut report).unwrap());
+        assert!(String::from_utf8(report).unwrap().contains("  [缺少消息] System: 未收到\n"));
+    }
+
+    #[test]
+    fn test_coarse_resolution() {
+        let mut samples = Samples::default();
+        (1..=RESOLUTION_SAMPLES as u64).for_each(|v| samples.add(v * 2));
+        assert_eq!(samples.coarse(), Some(2));
+        samples.add(7);
+        assert_eq!(samples.coarse(), None);
+    }
+
+    #[test]
+    fn test_too_few_samples_for_resolution() {
+        let mut samples = Samples::default();
+        (1..RESOLUTION_SAMPLES as u64).for_each(|v| samples.add(v * 2));
+        samples.add(0);   // 0 不计入
+        assert_eq!(samples.coarse(), None);
+    }
+
+    #[test]
+    fn test_uas_filter() {
+        let mut check = ConformanceCheck::new(Some(UAS_ID.into()));
+        observe(&mut check, &[basic_id()], 1_000, 1);
+        check.observe(&DecodedEvent { received_at_ms: 0, record: UploadData { rid: "OTHER".into(), ..Default::default() } });
+        assert_eq!(check.drones.keys().collect::<Vec<_>>(), [UAS_ID]);
+    }
+
+    #[test]
+    fn test_report_without_drones() {
+        let mut report = Vec::new();
+        assert!(!ConformanceCheck::new(Some(UAS_ID.into())).write_report(&mut report).unwrap());
+        assert_eq!(String::from_utf8(report).unwrap(), "未收到被测无人机的 Remote ID\n");
+    }
+}
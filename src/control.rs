@@ -0,0 +1,315 @@
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use serde_json::{json, Value};
+use tracing::level_filters::LevelFilter;
+use tracing::{error, info, warn};
+
+use crate::capture::Quirks;
+use crate::config::Config;
+use crate::environment::EnvironmentReport;
+use crate::playback::{self, PlaybackControl};
+use crate::telemetry::SystemTelemetry;
+use crate::wifi;
+
+/// 需要在解码线程中执行的输出操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputCommand {
+    /// 写出缓冲中的数据
+    Flush,
+    /// 关闭当前录制文件并开始新文件
+    Rotate,
+}
+
+type LogLevelHook = Box<dyn Fn(LevelFilter) -> Result<(), String> + Send + Sync>;
+
+/// 运行时控制状态，由控制接口线程修改、抓包/解码线程读取
+#[derive(Default)]
+pub struct RuntimeControl {
+    paused: AtomicBool,
+    channel_overrides: Mutex<BTreeMap<String, u8>>,
+    pending: Mutex<Vec<OutputCommand>>,
+    log_level: Mutex<Option<LogLevelHook>>,
+    latency: Mutex<Value>,
+    config: Mutex<Value>,
+    offered_config: Mutex<Option<Config>>,
+    environment: Mutex<Option<EnvironmentReport>>,
+    playback: Mutex<Option<Arc<PlaybackControl>>>,
+    data_dir: PathBuf,
+}
+
+impl RuntimeControl {
+    /// `data_dir` 为录制文件/数据库所在目录，状态中报告其磁盘剩余空间
+    pub fn new(data_dir: PathBuf) -> Arc<Self> {
+        Arc::new(Self { data_dir, ..Default::default() })
+    }
+
+    pub fn set_paused(&self, paused: bool) {
+        self.paused.store(paused, Ordering::Relaxed);
+        info!("capture {}", if paused { "paused" } else { "resumed" });
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+
+    /// 将网卡固定到指定信道，该网卡的信道轮换随之暂停
+    pub fn set_channel(&self, interface: &str, channel: u8) -> std::io::Result<()> {
+        wifi::set_channel(interface, channel)?;
+        self.channel_overrides.lock().unwrap().insert(interface.to_string(), channel);
+        info!("{} fixed to channel {}", interface, channel);
+        Ok(())
+    }
+
+    /// 取消固定信道，恢复配置中的信道轮换
+    pub fn clear_channel(&self, interface: &str) {
+        self.channel_overrides.lock().unwrap().remove(interface);
+    }
+
+    pub fn channel_override(&self, interface: &str) -> Option<u8> {
+        self.channel_overrides.lock().unwrap().get(interface).copied()
+    }
+
+    pub fn request(&self, command: OutputCommand) {
+        self.pending.lock().unwrap().push(command);
+    }
+
+    pub fn take_requests(&self) -> Vec<OutputCommand> {
+        std::mem::take(&mut *self.pending.lock().unwrap())
+    }
+
+    /// 注册调整日志级别的回调（日志初始化时调用）
+    pub fn set_log_level_hook<F>(&self, hook: F)
+    where
+        F: Fn(LevelFilter) -> Result<(), String> + Send + Sync + 'static,
+    {
+        *self.log_level.lock().unwrap() = Some(Box::new(hook));
+    }
+
+    pub fn set_log_level(&self, level: LevelFilter) -> Result<(), String> {
+        match self.log_level.lock().unwrap().as_ref() {
+            Some(hook) => hook(level),
+            None => Err("日志级别不可调整".to_string()),
+        }
+    }
+
+    /// 按配置设置诊断日志级别；未配置时不改动
+    pub fn apply_log_level(&self, level: Option<&str>) {
+        let Some(level) = level else { return };
+        match level.parse::<LevelFilter>() {
+            Ok(filter) => match self.set_log_level(filter) {
+                Ok(()) => info!("log level set to {}", filter),
+                Err(e) => warn!("cannot set log level {}: {}", level, e),
+            },
+            Err(_) => error!("无效的日志级别 {}，应为 trace/debug/info/warn/error/off", level),
+        }
+    }
+
+    /// 更新状态中报告的最近一个周期的延迟统计
+    pub fn set_latency(&self, summary: Value) {
+        *self.latency.lock().unwrap() = summary;
+    }
+
+    /// `GET /api/config` 返回的配置，应已遮蔽机密
+    pub fn set_config(&self, config: Value) {
+        *self.config.lock().unwrap() = config;
+    }
+
+    /// 远程拉取到的新配置，由解码线程取出热更新；未取出前再次提供时以新的为准
+    pub fn offer_config(&self, config: Config) {
+        *self.offered_config.lock().unwrap() = Some(config);
+    }
+
+    pub fn take_config(&self) -> Option<Config> {
+        self.offered_config.lock().unwrap().take()
+    }
+
+    /// 启动时采集的运行环境报告，随状态返回
+    pub fn set_environment(&self, report: EnvironmentReport) {
+        *self.environment.lock().unwrap() = Some(report);
+    }
+
+    /// 抓包线程检测到网卡的 radiotap 字段情况后补入环境报告
+    pub fn set_radiotap(&self, interface: &str, quirks: Quirks) {
+        if let Some(report) = self.environment.lock().unwrap().as_mut() {
+            report.set_radiotap(interface, quirks);
+        }
+    }
+
+    /// 回放录制文件或抓包文件时注册回放控制，之后可经 `/playback/*` 调整
+    pub fn set_playback(&self, playback: Arc<PlaybackControl>) {
+        *self.playback.lock().unwrap() = Some(playback);
+    }
+
+    fn status(&self) -> Value {
+        json!({
+            "paused": self.is_paused(),
+            "channel_overrides": *self.channel_overrides.lock().unwrap(),
+            "latency": *self.latency.lock().unwrap(),
+            "system": SystemTelemetry::collect(&self.data_dir),
+            "environment": *self.environment.lock().unwrap(),
+            "playback": self.playback.lock().unwrap().as_ref().map(|p| p.status()),
+        })
+    }
+
+    /// 启动 HTTP 控制接口
+    ///
+    /// - `GET  /status`
+    /// - `GET  /api/config`（实际生效的配置，机密已遮蔽）
+    /// - `POST /pause`、`POST /resume`
+    /// - `POST /channel?interface=wlan1&channel=6`（不带 channel 时恢复轮换）
+    /// - `POST /flush`、`POST /rotate`
+    /// - `POST /log-level?level=debug`
+    /// - 回放时：`POST /playback/pause`、`POST /playback/resume`、`POST /playback/step`、
+    ///   `POST /playback/speed?speed=2`、`POST /playback/seek?to=<Unix 毫秒或 RFC 3339>`
+    pub fn spawn_http_listener(self: &Arc<Self>, addr: String) {
+        let control = self.clone();
+        thread::spawn(move || {
+            let listener = match TcpListener::bind(&addr) {
+                Ok(listener) => listener,
+                Err(e) => {
+                    error!("无法监听控制接口 {}: {}", addr, e);
+                    return;
+                }
+            };
+            info!("control api on http://{}", addr);
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else { continue };
+                let mut buf = [0u8; 1024];
+                let len = stream.read(&mut buf).unwrap_or(0);
+                let request = String::from_utf8_lossy(&buf[..len]);
+                let (status, body) = control.handle(&request);
+                let _ = write!(stream, "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+                    status, body.len(), body);
+            }
+        });
+    }
+
+    fn handle(&self, request: &str) -> (&'static str, String) {
+        let mut parts = request.lines().next().unwrap_or_default().split_whitespace();
+        let (method, target) = (parts.next().unwrap_or_default(), parts.next().unwrap_or_default());
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        let param = |name: &str| query.split('&')
+            .find_map(|kv| kv.strip_prefix(name)?.strip_prefix('='));
+        let ok = || ("200 OK", self.status().to_string());
+        let bad = |message: String| ("400 Bad Request", json!({ "error": message }).to_string());
+
+        match (method, path) {
+            ("GET", "/status") => ok(),
+            ("GET", "/api/config") => ("200 OK", self.config.lock().unwrap().to_string()),
+            ("POST", "/pause") => {
+                self.set_paused(true);
+                ok()
+            }
+            ("POST", "/resume") => {
+                self.set_paused(false);
+                ok()
+            }
+            ("POST", "/flush") => {
+                self.request(OutputCommand::Flush);
+                ok()
+            }
+            ("POST", "/rotate") => {
+                self.request(OutputCommand::Rotate);
+                ok()
+            }
+            ("POST", "/channel") => {
+                let Some(interface) = param("interface") else {
+                    return bad("缺少 interface 参数".to_string());
+                };
+                match param("channel").map(str::parse::<u8>) {
+                    None => {
+                        self.clear_channel(interface);
+                        ok()
+                    }
+                    Some(Ok(channel)) => match self.set_channel(interface, channel) {
+                        Ok(()) => ok(),
+                        Err(e) => bad(format!("设置信道失败: {}", e)),
+                    },
+                    Some(Err(_)) => bad("无效信道".to_string()),
+                }
+            }
+            ("POST", "/log-level") => {
+                match param("level").and_then(|l| l.parse::<LevelFilter>().ok()) {
+                    Some(level) => match self.set_log_level(level) {
+                        Ok(()) => ok(),
+                        Err(e) => bad(e),
+                    },
+                    None => bad("level 应为 off/error/warn/info/debug/trace".to_string()),
+                }
+            }
+            ("POST", command) if command.starts_with("/playback/") => {
+                let Some(playback) = self.playback.lock().unwrap().clone() else {
+                    return ("409 Conflict", json!({ "error": "未在回放" }).to_string());
+                };
+                match command {
+                    "/playback/pause" => playback.set_paused(true),
+                    "/playback/resume" => playback.set_paused(false),
+                    "/playback/step" => playback.step(),
+                    "/playback/speed" => match param("speed").and_then(|s| s.parse().ok()) {
+                        Some(speed) => playback.set_speed(speed),
+                        None => return bad("无效倍速".to_string()),
+                    },
+                    "/playback/seek" => match param("to").and_then(playback::parse_timestamp_ms) {
+                        Some(ms) => playback.seek(ms),
+                        None => return bad("to 应为 Unix 毫秒或 RFC 3339 时间".to_string()),
+                    },
+                    _ => return ("404 Not Found", json!({ "error": "unknown endpoint" }).to_string()),
+                }
+                ok()
+            }
+            _ => ("404 Not Found", json!({ "error": "unknown endpoint" }).to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_control_requests() {
+        let control = RuntimeControl::new(PathBuf::from("."));
+        let (status, _) = control.handle("POST /pause HTTP/1.1\r\n\r\n");
+        assert_eq!("200 OK", status);
+        assert!(control.is_paused());
+
+        control.handle("POST /rotate HTTP/1.1\r\n\r\n");
+        control.handle("POST /flush HTTP/1.1\r\n\r\n");
+        assert_eq!(vec![OutputCommand::Rotate, OutputCommand::Flush], control.take_requests());
+        assert!(control.take_requests().is_empty());
+
+        let (status, _) = control.handle("POST /log-level?level=loud HTTP/1.1\r\n\r\n");
+        assert_eq!("400 Bad Request", status);
+        let (status, _) = control.handle("GET /nope HTTP/1.1\r\n\r\n");
+        assert_eq!("404 Not Found", status);
+    }
+
+    #[test]
+    fn test_playback_requests() {
+        let control = RuntimeControl::new(PathBuf::from("."));
+        assert_eq!(control.handle("POST /playback/pause HTTP/1.1\r\n\r\n").0, "409 Conflict");
+
+        let playback = PlaybackControl::new(1.0);
+        control.set_playback(playback.clone());
+        let (status, body) = control.handle("POST /playback/pause HTTP/1.1\r\n\r\n");
+        assert_eq!("200 OK", status);
+        assert_eq!(serde_json::from_str::<Value>(&body).unwrap()["playback"]["paused"], true);
+
+        control.handle("POST /playback/speed?speed=8 HTTP/1.1\r\n\r\n");
+        assert_eq!(playback.status()["speed"], 8.0);
+        assert_eq!(control.handle("POST /playback/speed?speed=fast HTTP/1.1\r\n\r\n").0, "400 Bad Request");
+
+        control.handle("POST /playback/seek?to=2024-05-01T08:00:00Z HTTP/1.1\r\n\r\n");
+        assert_eq!(playback.take_seek(), Some(1_714_550_400_000));
+        assert_eq!(control.handle("POST /playback/seek HTTP/1.1\r\n\r\n").0, "400 Bad Request");
+
+        control.handle("POST /playback/resume HTTP/1.1\r\n\r\n");
+        assert_eq!(playback.status()["paused"], false);
+    }
+}
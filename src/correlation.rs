@@ -0,0 +1,180 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use tracing::info;
+
+/// MAC 随机化关联参数
+#[derive(Debug, Clone, Copy)]
+pub struct CorrelationConfig {
+    pub max_gap: Duration,        // 旧 MAC 最后出现到新 MAC 出现的最大间隔
+    pub max_counter_jump: u8,     // 消息计数器允许的最大跳变
+    pub max_rssi_delta: f32,      // 允许的最大信号强度变化 (dB)
+    pub fragment_ttl: Duration,   // 片段过期时间
+}
+
+impl Default for CorrelationConfig {
+    fn default() -> Self {
+        Self {
+            max_gap: Duration::from_secs(10),
+            max_counter_jump: 16,
+            max_rssi_delta: 10.0,
+            fragment_ttl: Duration::from_secs(600),
+        }
+    }
+}
+
+/// 单个源 MAC 对应的轨迹片段
+struct Fragment {
+    uas_id: String,
+    track_id: String,
+    last_seen: Instant,
+    last_counter: u8,
+    last_rssi: Option<f32>,
+    has_successor: bool,
+}
+
+/// MAC 随机化关联
+///
+/// 信标 MAC 随机化的无人机会被拆成许多短轨迹。新 MAC 出现时，若存在
+/// UAS ID 相同、刚刚停止发送、消息计数器连续且信号强度相近的旧片段，
+/// 则沿用其逻辑轨迹 ID，把片段拼接回同一条轨迹。属于启发式判断。
+pub struct MacCorrelator {
+    config: CorrelationConfig,
+    fragments: HashMap<String, Fragment>,
+}
+
+impl MacCorrelator {
+    pub fn new(config: CorrelationConfig) -> Self {
+        Self { config, fragments: HashMap::new() }
+    }
+
+    /// 返回该记录所属的逻辑轨迹 ID（首个片段的 MAC）
+    pub fn correlate(&mut self, mac: &str, uas_id: &str, counter: u8, rssi: Option<f32>) -> String {
+        let now = Instant::now();
+        let ttl = self.config.fragment_ttl;
+        self.fragments.retain(|_, f| now.duration_since(f.last_seen) < ttl);
+
+        if let Some(fragment) = self.fragments.get_mut(mac) {
+            fragment.last_seen = now;
+            fragment.last_counter = counter;
+            fragment.last_rssi = rssi;
+            return fragment.track_id.clone();
+        }
+
+        let track_id = match self.find_predecessor(mac, uas_id, counter, rssi, now) {
+            Some(previous) => {
+                let fragment = self.fragments.get_mut(&previous).unwrap();
+                fragment.has_successor = true;
+                info!(uas_id = %uas_id, "stitched MAC {} onto track {} (previous MAC {})", mac, fragment.track_id, previous);
+                fragment.track_id.clone()
+            }
+            None => mac.to_string(),
+        };
+        self.fragments.insert(mac.to_string(), Fragment {
+            uas_id: uas_id.to_string(),
+            track_id: track_id.clone(),
+            last_seen: now,
+            last_counter: counter,
+            last_rssi: rssi,
+            has_successor: false,
+        });
+        track_id
+    }
+
+    fn find_predecessor(&self, mac: &str, uas_id: &str, counter: u8, rssi: Option<f32>, now: Instant) -> Option<String> {
+        if uas_id.is_empty() {
+            return None;
+        }
+        self.fragments.iter()
+            .filter(|(other, f)| {
+                other.as_str() != mac
+                    && !f.has_successor
+                    && f.uas_id == uas_id
+                    && now.duration_since(f.last_seen) <= self.config.max_gap
+            })
+            .filter_map(|(other, f)| {
+                let jump = counter.wrapping_sub(f.last_counter);
+                if jump == 0 || jump > self.config.max_counter_jump {
+                    return None;
+                }
+                if let (Some(a), Some(b)) = (rssi, f.last_rssi)
+                    && (a - b).abs() > self.config.max_rssi_delta
+                {
+                    return None;
+                }
+                Some((jump, other))
+            })
+            .min_by_key(|(jump, _)| *jump)
+            .map(|(_, other)| other.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_known_mac_keeps_its_track() {
+        let mut correlator = MacCorrelator::new(CorrelationConfig::default());
+        assert_eq!(correlator.correlate("mac-1", "RID-1", 10, Some(-60.0)), "mac-1");
+        assert_eq!(correlator.correlate("mac-1", "RID-1", 11, Some(-60.0)), "mac-1");
+    }
+
+    #[test]
+    fn test_new_mac_is_stitched_onto_predecessor() {
+        let mut correlator = MacCorrelator::new(CorrelationConfig::default());
+        correlator.correlate("mac-1", "RID-1", 10, Some(-60.0));
+        assert_eq!(correlator.correlate("mac-2", "RID-1", 12, Some(-63.0)), "mac-1");
+        // 第三个 MAC 接在第二个片段之后，仍属于同一轨迹
+        assert_eq!(correlator.correlate("mac-3", "RID-1", 14, Some(-62.0)), "mac-1");
+    }
+
+    #[test]
+    fn test_counter_wraps_around() {
+        let mut correlator = MacCorrelator::new(CorrelationConfig::default());
+        correlator.correlate("mac-1", "RID-1", 254, None);
+        assert_eq!(correlator.correlate("mac-2", "RID-1", 2, None), "mac-1");
+    }
+
+    #[test]
+    fn test_counter_jump_and_repeat_are_rejected() {
+        let mut correlator = MacCorrelator::new(CorrelationConfig::default());
+        correlator.correlate("mac-1", "RID-1", 10, None);
+        assert_eq!(correlator.correlate("mac-2", "RID-1", 10, None), "mac-2");   // 计数器没有前进
+        assert_eq!(correlator.correlate("mac-3", "RID-1", 40, None), "mac-3");   // 跳变超过 16
+    }
+
+    #[test]
+    fn test_rssi_change_is_rejected() {
+        let mut correlator = MacCorrelator::new(CorrelationConfig::default());
+        correlator.correlate("mac-1", "RID-1", 10, Some(-50.0));
+        assert_eq!(correlator.correlate("mac-2", "RID-1", 11, Some(-75.0)), "mac-2");
+    }
+
+    #[test]
+    fn test_requires_matching_uas_id() {
+        let mut correlator = MacCorrelator::new(CorrelationConfig::default());
+        correlator.correlate("mac-1", "RID-1", 10, None);
+        assert_eq!(correlator.correlate("mac-2", "RID-2", 11, None), "mac-2");
+        correlator.correlate("mac-3", "", 10, None);
+        assert_eq!(correlator.correlate("mac-4", "", 11, None), "mac-4");
+    }
+
+    #[test]
+    fn test_predecessor_is_used_once() {
+        let mut correlator = MacCorrelator::new(CorrelationConfig::default());
+        correlator.correlate("mac-1", "RID-1", 10, None);
+        assert_eq!(correlator.correlate("mac-2", "RID-1", 11, None), "mac-1");
+        // mac-1 已有后继，mac-3 只能接在 mac-2 之后；计数器相对 mac-2 没有前进时另起轨迹
+        assert_eq!(correlator.correlate("mac-3", "RID-1", 11, None), "mac-3");
+    }
+
+    #[test]
+    fn test_gap_too_long_starts_new_track() {
+        let config = CorrelationConfig { max_gap: Duration::from_millis(5), ..Default::default() };
+        let mut correlator = MacCorrelator::new(config);
+        correlator.correlate("mac-1", "RID-1", 10, None);
+        std::thread::sleep(Duration::from_millis(20));
+        assert_eq!(correlator.correlate("mac-2", "RID-1", 11, None), "mac-2");
+    }
+}
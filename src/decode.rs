@@ -0,0 +1,644 @@
+use libwifi::frame::components::{MacAddress, VendorSpecificInfo};
+#[cfg(not(feature = "builtin-parser"))]
+use libwifi::{parse_frame, Frame};
+use tracing::{debug, info, warn};
+
+use crate::bearing::BearingEstimator;
+use crate::clock::{self, TimeSource};
+use crate::config::{FormatConfig, Oui, PreFilter, Tags};
+use crate::correlation::MacCorrelator;
+use crate::deep_scan;
+use crate::failure_sink::FailureSink;
+use crate::id_collision::IdCollisionDetector;
+use crate::message::{self, AnyMessage, DecodeOptions, DecodedMessage};
+use crate::message::message::MessageError;
+use crate::message::message_pack::MessagePack;
+use crate::mgt_parser;
+use crate::nan;
+use crate::position::{self, PositionStatus};
+use crate::quality::{self, CounterContinuity};
+use crate::radiotap::{self, RadiotapHeader};
+use crate::remote_id;
+use crate::rssi::RssiTracker;
+use crate::schema;
+use crate::shedding::LoadShedder;
+use crate::ssid_check::SsidChecker;
+use crate::stats::{FrameClass, FrameStats};
+use crate::upload_data::{Authentication, OperatorPosition, RecordSource, UploadData};
+use crate::wifi;
+
+/// 逐帧解码时共享的上下文：解码选项、帧统计、失败样本输出
+#[derive(Default)]
+pub struct DecodeContext {
+    pub options: DecodeOptions,
+    pub stats: FrameStats,
+    pub failures: Option<FailureSink>,
+    pub collisions: IdCollisionDetector,
+    pub ssid_check: Option<SsidChecker>,
+    pub correlator: Option<MacCorrelator>,
+    pub rssi: RssiTracker,
+    pub bearing: Option<BearingEstimator>,
+    pub deep_scan: bool,
+    pub ignored_ouis: Vec<Oui>,
+    pub prefilter: PreFilter,
+    pub shedder: Option<LoadShedder>,
+    pub format: FormatConfig,
+    pub counters: CounterContinuity,
+    /// 解码线程中不做轨迹关联、测距等有状态的处理，由主线程按帧顺序调用 [`annotate_track`]
+    pub defer_tracking: bool,
+}
+
+impl DecodeContext {
+    pub fn record_failure(&mut self, kind: &str, error: &dyn std::fmt::Display, bytes: &[u8]) {
+        if let Some(failures) = self.failures.as_mut() {
+            failures.record(kind, error, bytes);
+        }
+    }
+
+    /// 记录处理一帧所用的时间，供过载判断
+    pub fn record_busy(&mut self, elapsed: std::time::Duration) {
+        if let Some(shedder) = self.shedder.as_mut() {
+            shedder.record_busy(elapsed);
+        }
+    }
+}
+
+/// 从一帧的厂商 IE 中重组并解码 Remote ID，帧中可能带有多个负载；没有 Remote ID 时返回空
+fn decode_remote_id(source: MacAddress, vendor_specific: &[VendorSpecificInfo], ssid: &str,
+                    radiotap: &RadiotapHeader, ctx: &mut DecodeContext) -> Vec<UploadData> {
+    remote_id::reassemble_all(vendor_specific).into_iter()
+        .flat_map(|vendor_data| decode_payload(source, vendor_data, ssid, radiotap, ctx))
+        .collect()
+}
+
+/// 启发式扫描明文数据帧中内嵌的 Remote ID 消息包，命中的记录标记为 heuristic
+fn scan_data_frame(data: &[u8], radiotap: &RadiotapHeader, ctx: &mut DecodeContext) -> Vec<UploadData> {
+    let Some((source, body)) = deep_scan::data_frame_body(data) else { return Vec::new() };
+    let Some(payload) = deep_scan::find_message_pack(body) else { return Vec::new() };
+    info!("heuristic remote id match in data frame from {}", source);
+    let mut records = decode_payload(source, payload, "", radiotap, ctx);
+    for record in &mut records {
+        record.heuristic = true;
+        record.quality = record.quality.map(|q| quality::round(q * quality::HEURISTIC_FACTOR));
+    }
+    records
+}
+
+/// 解码以消息计数器开头的 Remote ID 负载，包中每架无人机一条记录
+pub fn decode_payload(source: MacAddress, vendor_data: Vec<u8>, ssid: &str,
+                      radiotap: &RadiotapHeader, ctx: &mut DecodeContext) -> Vec<UploadData> {
+    let rssi = radiotap.signal_dbm.map(f32::from);
+    let options = DecodeOptions { profile: ctx.format.profile_for(&source.0), ..ctx.options };
+    let upload_data = UploadData {format_version: schema::FORMAT_VERSION,
+            record_id: String::new(),
+            tags: Tags::default(),
+            annotation: None,
+            authorization: None,
+            auth_verification: None,
+            time_source: TimeSource::System,
+            rid: String::from(""),
+            source_mac: remote_id::format_mac(&source.0),
+            id_collision: false,
+            track_id: remote_id::format_mac(&source.0),
+            message_counter: vendor_data.first().copied().unwrap_or_default(),
+            rssi,
+            channel: radiotap.channel_freq.map(wifi::frequency_to_channel).filter(|c| *c != 0),
+            noise_dbm: radiotap.noise_dbm.map(f32::from),
+            mac_timestamp_us: radiotap.tsft_us,
+            received_at_ms: None,
+            rssi_trend: None,
+            estimated_range_m: None,
+            range_bin: None,
+            bearing_deg: None,
+            bearing_confidence: None,
+            rid_lossy: false,
+            rid_raw: None,
+            ua_type: None,
+            self_id_type: None,
+            self_id: None,
+            operator_id_type: None,
+            operator_id: None,
+            auth: None,
+            heuristic: false,
+            source: RecordSource::Broadcast,
+            // 位置字段只由位置向量消息填充，包中没有位置消息时保持 0（未知）
+            run_status: 0,
+            reserved_flag: false,
+            height_type: 0,
+            track_direction: false,
+            speed_multiplier: false,
+            track_angle: 0,
+            ground_speed: 0,
+            vertical_speed: 0,
+            latitude: 0,
+            longitude: 0,
+            pressure_altitude: 0,
+            geometric_altitude: 0,
+            ground_altitude: 0,
+            vertical_accuracy: 0,
+            horizontal_accuracy: 0,
+            speed_accuracy: 0,
+            timestamp: 0,
+            timestamp_accuracy: 0,
+            reserved: 0,
+            operator: None,
+            classification: None,
+            accuracy_bounds: Default::default(),
+            position_status: PositionStatus::NoFix,
+            ssid_match: None,
+            format_profile: options.profile,
+            quality: None,
+            operator_distance_m: None,
+            interface_rssi: Vec::new(),
+            receiver: None,
+            receiver_distance_m: None,
+            receiver_bearing_deg: None,
+            inside_geofence: None,
+            geofence_zones: Vec::new(),
+            operator_inside_geofence: None,
+            operator_geofence_zones: Vec::new(),
+            kinematics: None,
+            localized: None,
+            spoof_score: None,
+            ua_type_name: None,
+            uas_id_type: None,
+            raw_payload: remote_id::to_hex(&vendor_data),
+            raw_messages: Vec::new(),
+        };
+    let pack = match MessagePack::from_payload(&vendor_data) {
+        Ok(pack) => pack,
+        Err(err) => {
+            let err = remote_id::PayloadError::MalformedPack(err.to_string(), vendor_data.clone());
+            ctx.stats.record(FrameClass::MalformedPack);
+            warn!(ssid = %ssid, "{}", err);
+            ctx.record_failure("malformed_pack", &err, &vendor_data);
+            return Vec::new();
+        }
+    };
+    debug!("this is the openid element, ssid: {:?}, counter: {}, pack count: {}", ssid, vendor_data[0], pack.len());
+    let mut messages = Vec::with_capacity(pack.len());
+    let mut parse_warnings = 0;
+    for (i, raw) in pack.raw_messages().enumerate() {
+        match DecodedMessage::decode(raw, &options) {
+            Ok(decoded) => messages.push(decoded),
+            Err(err) => {
+                warn!("message {} decode failed: {}", i, err);
+                parse_warnings += 1;
+                ctx.record_failure("message", &err, raw);
+            }
+        }
+    }
+    ctx.stats.record(FrameClass::RidBeacon);
+    // 中继包可能带有多架无人机的消息，每架各出一条记录
+    let groups = message::group_by_uas(messages);
+    if groups.len() > 1 {
+        debug!("pack from {} carries {} drones", source, groups.len());
+    }
+    groups.into_iter().map(|group| {
+        let mut upload_data = upload_data.clone();
+        let mut located = false;
+        for decoded in group {
+            upload_data.raw_messages.push(remote_id::to_hex(&decoded.raw));
+            match decoded.message {
+                AnyMessage::Base(bm) => {
+                    debug!("{:?}", bm);
+                    if bm.uas_id_lossy {
+                        upload_data.rid_lossy = true;
+                        upload_data.rid_raw = Some(remote_id::to_hex(&bm.uas_id_raw));
+                    }
+                    upload_data.ua_type = Some(bm.ua_type);
+                    upload_data.uas_id_type = Some(bm.uas_id_type());
+                    upload_data.rid = bm.uas_id;
+                },
+                AnyMessage::PositionVector(pvm) => {
+                    debug!("{:?}", pvm);
+                    upload_data.apply_position(&pvm);
+                    located = true;
+                },
+                AnyMessage::Auth(am) => {
+                    debug!("{:?}", am);
+                    match upload_data.auth.as_mut() {
+                        Some(auth) => auth.add_page(&am),
+                        None => upload_data.auth = Some(Authentication::from(&am)),
+                    }
+                },
+                AnyMessage::SelfId(sid) => {
+                    debug!("{:?}", sid);
+                    upload_data.self_id_type = Some(sid.description_type);
+                    upload_data.self_id = Some(sid.description);
+                },
+                AnyMessage::OperatorId(oid) => {
+                    debug!("{:?}", oid);
+                    upload_data.operator_id_type = Some(oid.operator_id_type);
+                    upload_data.operator_id = Some(oid.operator_id);
+                },
+                AnyMessage::System(sm) => {
+                    debug!("{:?}", sm);
+                    upload_data.operator = Some(OperatorPosition::from(&sm));
+                    upload_data.classification = Some(sm.classification());
+                    if let Some(timestamp) = sm.timestamp {
+                        clock::observe_remote_id(&upload_data.source_mac, timestamp);
+                    }
+                }
+            }
+        }
+        upload_data.position_status = position::classify_decoded(&upload_data, located);
+        if let Some(checker) = ctx.ssid_check.as_mut() {
+            upload_data.ssid_match = checker.check(&upload_data.source_mac, ssid, &upload_data.rid);
+        }
+        upload_data.quality = Some(quality::round(quality::frame_score(&upload_data, radiotap.bad_fcs(), parse_warnings)));
+        if !ctx.defer_tracking {
+            annotate_track(&mut upload_data, ctx);
+        }
+        upload_data
+    }).collect()
+}
+
+/// 轨迹关联、RSSI 测距/测向、ID 冲突检测与计数器连续性评分
+pub fn annotate_track(upload_data: &mut UploadData, ctx: &mut DecodeContext) {
+    let rssi = upload_data.rssi;
+    if let Some(correlator) = ctx.correlator.as_mut() {
+        upload_data.track_id = correlator.correlate(
+            &upload_data.source_mac, &upload_data.rid, upload_data.message_counter, rssi);
+    }
+    if let Some(rssi) = rssi {
+        let summary = ctx.rssi.observe(&upload_data.track_id, rssi);
+        upload_data.rssi_trend = summary.trend;
+        upload_data.estimated_range_m = Some(summary.estimated_range_m);
+        upload_data.range_bin = Some(summary.range_bin);
+        if let Some(estimate) = ctx.bearing.as_mut().and_then(|b| b.observe(&upload_data.track_id, rssi)) {
+            upload_data.bearing_deg = Some(estimate.bearing_deg);
+            upload_data.bearing_confidence = Some(estimate.confidence);
+        }
+    }
+    if let Some(score) = upload_data.quality.as_mut() {
+        *score = quality::round(*score * ctx.counters.observe(&upload_data.track_id, upload_data.message_counter));
+    }
+    upload_data.id_collision = ctx.collisions
+        .observe(&upload_data.rid, &upload_data.track_id, (upload_data.latitude, upload_data.longitude), rssi)
+        .is_some();
+}
+
+/// 解码不带 radiotap 头的 802.11 帧（如 LINKTYPE_IEEE802_11 抓包文件中的帧），含 NAN 服务发现帧
+pub fn parse_80211_mgt(data: &[u8], radiotap: &RadiotapHeader, ctx: &mut DecodeContext) -> Vec<UploadData> {
+    match decode_nan(data, radiotap, ctx) {
+        Some(records) => records,
+        None => parse_mgt_frame(data, radiotap, ctx),
+    }
+}
+
+/// NAN 服务发现帧和同步信标中的 Remote ID 按信标厂商 IE 的负载格式解码，不是 NAN 帧时返回 None
+fn decode_nan(data: &[u8], radiotap: &RadiotapHeader, ctx: &mut DecodeContext) -> Option<Vec<UploadData>> {
+    let nan = nan::extract_odid(data)?;
+    debug!("nan remote id from {:02x?}, cluster {:02x?}", nan.source, nan.cluster);
+    Some(decode_remote_id(MacAddress(nan.source), &[nan.vendor_element()], "", radiotap, ctx))
+}
+
+/// 用 libwifi 解析管理帧，处理信标帧和探测请求/响应帧
+#[cfg(not(feature = "builtin-parser"))]
+fn parse_mgt_frame(data: &[u8], radiotap: &RadiotapHeader, ctx: &mut DecodeContext) -> Vec<UploadData> {
+    // libwifi 遇到畸形 IE 时可能 panic，捕获后与解析错误一样走最简解析器，不中断抓包循环
+    let parsed = parse_frame_contained(data)
+        .and_then(|result| result.map_err(|err| format!("{err:?}")));
+    match parsed {
+        Ok(frame) => {
+            // 部分发射端把 Remote ID 厂商 IE 放在探测响应（偶尔探测请求）中；探测请求的 SSID 是要找的网络，不参与比对
+            let (source, station_info, ssid) = match &frame {
+                Frame::Beacon(beacon) => (beacon.header.address_2, &beacon.station_info, beacon.station_info.ssid()),
+                Frame::ProbeResponse(response) => (response.header.address_2, &response.station_info, response.station_info.ssid()),
+                Frame::ProbeRequest(request) => (request.header.address_2, &request.station_info, String::new()),
+                _ => {
+                    ctx.stats.record(FrameClass::from_frame_control(data[0]));
+                    return Vec::new();
+                }
+            };
+            let records = decode_remote_id(source, &station_info.vendor_specific, &ssid, radiotap, ctx);
+            if records.is_empty() {
+                ctx.stats.record(if matches!(frame, Frame::Beacon(_)) { FrameClass::OtherBeacon } else { FrameClass::OtherManagement });
+            } else if !matches!(frame, Frame::Beacon(_)) {
+                debug!("remote id in probe frame from {}", source);
+            }
+            return records;
+        }
+        Err(err) => {
+            // libwifi 无法解析时用最简解析器按偏移查找厂商 IE，尽量取回 Remote ID
+            if let Some(frame) = mgt_parser::parse_management(data) {
+                let records = decode_remote_id(frame.source, &frame.vendor_specific, "", radiotap, ctx);
+                if !records.is_empty() {
+                    debug!("recovered remote id via fallback parser (subtype {}, truncated: {}): {err}",
+                        frame.subtype, frame.truncated);
+                    return records;
+                }
+            }
+            ctx.stats.record(FrameClass::Undecodable);
+            debug!("Error during parsing : {err}");
+            ctx.record_failure("frame", &err, data);
+        }
+    }
+    Vec::new()
+}
+
+#[cfg(not(feature = "builtin-parser"))]
+thread_local! {
+    /// 当前线程正在 libwifi 中解析帧
+    static IN_LIBWIFI: std::cell::Cell<bool> = const { std::cell::Cell::new(false) };
+}
+
+/// 调用 libwifi 解析帧并捕获其 panic
+///
+/// 解析期间的 panic 只以 debug 日志记录，不经默认钩子向标准错误打印回溯；其它 panic 仍交给原来的钩子。
+/// 需要展开 (unwind) 才能捕获，`panic = "abort"` 的构建应启用 `builtin-parser`。
+#[cfg(not(feature = "builtin-parser"))]
+fn parse_frame_contained(data: &[u8]) -> Result<Result<Frame, libwifi::error::Error>, String> {
+    static HOOK: std::sync::Once = std::sync::Once::new();
+    HOOK.call_once(|| {
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            if IN_LIBWIFI.with(|flag| flag.get()) {
+                debug!("libwifi panicked: {}", info);
+            } else {
+                previous(info);
+            }
+        }));
+    });
+    IN_LIBWIFI.with(|flag| flag.set(true));
+    let result = std::panic::catch_unwind(|| parse_frame(data, false));
+    IN_LIBWIFI.with(|flag| flag.set(false));
+    result.map_err(|_| "libwifi panicked".to_string())
+}
+
+/// 内置解析器：不经过 libwifi，直接按偏移遍历 IE 提取 Remote ID
+#[cfg(feature = "builtin-parser")]
+fn parse_mgt_frame(data: &[u8], radiotap: &RadiotapHeader, ctx: &mut DecodeContext) -> Vec<UploadData> {
+    let Some(&frame_control) = data.first() else { return Vec::new() };
+    // 只处理信标帧 (类型 0, 子类型 8) 和探测请求/响应帧 (子类型 4/5)
+    if !matches!(frame_control & 0xfc, 0x80 | 0x40 | 0x50) {
+        ctx.stats.record(FrameClass::from_frame_control(frame_control));
+        return Vec::new();
+    }
+    let Some(frame) = mgt_parser::parse_management(data) else {
+        ctx.stats.record(FrameClass::Undecodable);
+        debug!("management frame too short for builtin parser: {} bytes", data.len());
+        ctx.record_failure("frame", &"management frame too short", data);
+        return Vec::new();
+    };
+    // 探测请求的 SSID 是要找的网络，不参与比对
+    let ssid = if frame.subtype == 4 { "" } else { frame.ssid.as_str() };
+    let records = decode_remote_id(frame.source, &frame.vendor_specific, ssid, radiotap, ctx);
+    if records.is_empty() {
+        ctx.stats.record(if frame.subtype == 8 { FrameClass::OtherBeacon } else { FrameClass::OtherManagement });
+    }
+    records
+}
+
+/// 解码一个带 radiotap 头的原始帧，返回其中的 Remote ID 记录（中继包中每架无人机一条）
+///
+/// 帧分类计入 `ctx.stats`，轨迹关联、测距等按 `ctx` 中启用的功能附加到记录上。
+pub fn process_packet(packet: &[u8], ctx: &mut DecodeContext) -> Vec<UploadData> {
+    // 统计所有帧的类型/子类型，radiotap 头长度位于字节 2-3 (小端序)
+    let mut transmitter = None;
+    if packet.len() >= 4 {
+        let radiotap_len = u16::from_le_bytes([packet[2], packet[3]]) as usize;
+        if let Some(frame_control) = packet.get(radiotap_len) {
+            ctx.stats.record_subtype(*frame_control);
+        }
+        // 发送方地址 (addr2) 位于 802.11 头偏移 10，忽略列表中的发送方不再解析
+        transmitter = packet.get(radiotap_len + 10..radiotap_len + 16);
+        if let Some(transmitter) = transmitter
+            && ctx.ignored_ouis.iter().any(|oui| oui.matches(transmitter))
+        {
+            ctx.stats.record(FrameClass::Ignored);
+            return Vec::new();
+        }
+    }
+    let Some(transmitter) = transmitter.filter(|_| ctx.shedder.is_some()) else { return decode_frame(packet, ctx) };
+    // 过载时已跟踪发射端的重复帧按比例丢弃，新发射端照常解析
+    if ctx.shedder.as_mut().is_some_and(|shedder| !shedder.admit(transmitter)) {
+        ctx.stats.record(FrameClass::Shed);
+        return Vec::new();
+    }
+    let records = decode_frame(packet, ctx);
+    if !records.is_empty()
+        && let Some(shedder) = ctx.shedder.as_mut()
+    {
+        shedder.mark_known(transmitter);
+    }
+    records
+}
+
+/// 预过滤后解析 radiotap 帧
+fn decode_frame(packet: &[u8], ctx: &mut DecodeContext) -> Vec<UploadData> {
+    if packet.len() < ctx.prefilter.min_packet_len {
+        ctx.stats.record(FrameClass::Short);
+        return Vec::new();
+    }
+    //let data = packet.data;
+    let (radiotap, remaining) = parse_radiotap(packet);
+    // NAN 帧不带 Remote ID 厂商 IE 签名，先于预过滤识别
+    if let Some(records) = decode_nan(remaining, &radiotap, ctx) {
+        return records;
+    }
+    let records = if ctx.prefilter.require_rid_oui && !remote_id::has_rid_signature(remaining) {
+        // 控制帧/数据帧照常分类，没有签名的管理帧不再交给帧解析器
+        match remaining.first().map(|fc| FrameClass::from_frame_control(*fc)) {
+            Some(FrameClass::OtherManagement) | None => ctx.stats.record(FrameClass::NoRidSignature),
+            Some(class) => ctx.stats.record(class),
+        }
+        Vec::new()
+    } else {
+        parse_mgt_frame(remaining, &radiotap, ctx)
+    };
+    if records.is_empty() && ctx.deep_scan {
+        return scan_data_frame(remaining, &radiotap, ctx);
+    }
+    records
+}
+
+/// 解析 radiotap 头，返回其后的 802.11 帧；驱动附带的 FCS 在此去掉，不参与 IE 解析
+pub fn parse_radiotap(data: &[u8]) -> (RadiotapHeader, &[u8]) {
+    let Some((header, header_len)) = radiotap::parse(data) else {
+        return (RadiotapHeader::default(), data);
+    };
+    let mut frame = &data[header_len..];
+    if header.fcs_included() {
+        frame = &frame[..frame.len().saturating_sub(4)];
+    }
+    (header, frame)
+}
+
+/// [`parse_remote_id_frame`] 的错误
+#[derive(Debug)]
+pub enum FrameError {
+    /// 不是可解析的 802.11 管理帧
+    NotManagement,
+    /// 帧中没有 Remote ID 厂商 IE
+    NoRemoteId,
+    /// Remote ID 负载或其中的消息格式错误
+    Message(MessageError),
+}
+
+impl std::error::Error for FrameError {}
+
+impl std::fmt::Display for FrameError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            FrameError::NotManagement => write!(f, "不是 802.11 管理帧"),
+            FrameError::NoRemoteId => write!(f, "帧中没有 Remote ID"),
+            FrameError::Message(e) => write!(f, "消息包格式错误: {}", e),
+        }
+    }
+}
+
+impl From<MessageError> for FrameError {
+    fn from(e: MessageError) -> Self {
+        FrameError::Message(e)
+    }
+}
+
+/// 从一个 802.11 帧（不含 radiotap 头，可先用 [`parse_radiotap`] 去掉）中解出全部 Remote ID 消息
+///
+/// 支持信标/探测响应等管理帧中的 ODID 厂商 IE（含分片重组）以及 NAN 服务发现帧。
+/// 与 [`process_packet`] 不同，不做统计、轨迹关联等处理，任一消息无法解码即返回错误。
+pub fn parse_remote_id_frame(frame: &[u8]) -> Result<Vec<AnyMessage>, FrameError> {
+    let payloads = match nan::extract_odid(frame) {
+        Some(nan) => remote_id::reassemble_all(&[nan.vendor_element()]),
+        None => {
+            let frame = mgt_parser::parse_management(frame).ok_or(FrameError::NotManagement)?;
+            remote_id::reassemble_all(&frame.vendor_specific)
+        }
+    };
+    if payloads.is_empty() {
+        return Err(FrameError::NoRemoteId);
+    }
+    let mut messages = Vec::new();
+    for payload in payloads {
+        let pack = MessagePack::from_payload(&payload)?;
+        for raw in pack.raw_messages() {
+            messages.push(AnyMessage::from_bytes(raw)?);
+        }
+    }
+    Ok(messages)
+}
+
+#[cfg(test)]
+mod tests {
+    // 注意这个惯用法：在 tests 模块中，从外部作用域导入所有名字。
+    use super::*;
+    use crate::test_support::Fixture;
+
+    #[test]
+    fn test_process_packet() {
+        let packet = Fixture::load("rid_beacon").frame;
+        let records = process_packet(&packet, &mut DecodeContext::default());
+        assert_eq!(records.len(), 1);
+        let r = &records[0];
+        assert_eq!((r.rid.as_str(), r.source_mac.as_str()), ("1581F7FVC251A00CQ25C", "e4:7a:2c:24:3d:26"));
+        assert_eq!((r.latitude, r.longitude, r.channel), (417_144_317, 1_234_844_131, Some(6)));
+
+        // 去掉 radiotap 头后由公开接口解出同一帧中的三条消息
+        let radiotap_len = u16::from_le_bytes([packet[2], packet[3]]) as usize;
+        let messages = parse_remote_id_frame(&packet[radiotap_len..]).unwrap();
+        assert!(matches!(&messages[..],
+            [AnyMessage::Base(bm), AnyMessage::PositionVector(pv), AnyMessage::System(sm)]
+                if bm.uas_id == r.rid && pv.latitude == r.latitude && sm.ua_category == 1));
+    }
+
+    #[test]
+    fn test_parse_remote_id_frame() {
+        let mut frame = vec![0x80, 0x00, 0x00, 0x00];
+        frame.extend_from_slice(&[0xff; 6]);
+        frame.extend_from_slice(&[0x02, 0x11, 0x22, 0x33, 0x44, 0x55]);
+        frame.extend_from_slice(&[0x02, 0x11, 0x22, 0x33, 0x44, 0x55]);
+        frame.extend_from_slice(&[0x10, 0x00]);
+        frame.extend_from_slice(&[0u8; 12]);
+        frame.extend_from_slice(&[0x00, 0x00]);
+        assert!(matches!(parse_remote_id_frame(&frame), Err(FrameError::NoRemoteId)));
+
+        let mut basic_id = [0u8; 25];
+        basic_id[0] = 0x02;
+        basic_id[1] = 0x12;
+        basic_id[2..18].copy_from_slice(b"1581F5FKD229400A");
+        let mut element = vec![0xdd, 8 + 25, 0xfa, 0x0b, 0xbc, 0x0d, 0x01, 0xf2, 25, 1];
+        element.extend_from_slice(&basic_id);
+        frame.extend_from_slice(&element);
+        let messages = parse_remote_id_frame(&frame).unwrap();
+        assert!(matches!(&messages[..], [AnyMessage::Base(bm)] if bm.uas_id == "1581F5FKD229400A"));
+
+        assert!(matches!(parse_remote_id_frame(&frame[..10]), Err(FrameError::NotManagement)));
+    }
+
+    #[test]
+    fn test_parse_80211_mgt_probe() {
+        let mut basic_id = [0u8; 25];
+        basic_id[0] = 0x02;
+        basic_id[1] = 0x12;
+        basic_id[2..18].copy_from_slice(b"1581F5FKD229400A");
+        let mut element = vec![0xdd, 8 + 25, 0xfa, 0x0b, 0xbc, 0x0d, 0x01, 0xf2, 25, 1];
+        element.extend_from_slice(&basic_id);
+        // 探测响应带固定字段，探测请求的 IE 紧跟帧头
+        for (frame_control, fixed) in [(0x50, 12), (0x40, 0)] {
+            let mut frame = vec![frame_control, 0x00, 0x00, 0x00];
+            frame.extend_from_slice(&[0xff; 6]);
+            frame.extend_from_slice(&[0x02, 0x11, 0x22, 0x33, 0x44, 0x55]);
+            frame.extend_from_slice(&[0x02, 0x11, 0x22, 0x33, 0x44, 0x55]);
+            frame.extend_from_slice(&[0x10, 0x00]);
+            frame.extend(std::iter::repeat_n(0u8, fixed));
+            frame.extend_from_slice(&[0x00, 0x00]);
+            frame.extend_from_slice(&element);
+
+            let mut ctx = DecodeContext::default();
+            let records = parse_80211_mgt(&frame, &RadiotapHeader::default(), &mut ctx);
+            assert_eq!(records.len(), 1, "frame control {:#04x}", frame_control);
+            assert_eq!((records[0].rid.as_str(), records[0].source_mac.as_str()), ("1581F5FKD229400A", "02:11:22:33:44:55"));
+            assert_eq!(ctx.stats.count(FrameClass::RidBeacon), 1);
+        }
+    }
+
+    #[test]
+    fn test_basic_id_only_has_no_fix() {
+        let mut basic_id = [0u8; 25];
+        basic_id[0] = 0x02;
+        basic_id[1] = 0x12;
+        basic_id[2..18].copy_from_slice(b"1581F5FKD229400A");
+        let mut frame = vec![0x80, 0x00, 0x00, 0x00];
+        frame.extend_from_slice(&[0xff; 6]);
+        frame.extend_from_slice(&[0x02, 0x11, 0x22, 0x33, 0x44, 0x55]);
+        frame.extend_from_slice(&[0x02, 0x11, 0x22, 0x33, 0x44, 0x55]);
+        frame.extend_from_slice(&[0x10, 0x00]);
+        frame.extend_from_slice(&[0u8; 12]);
+        frame.extend_from_slice(&[0x00, 0x00]);
+        frame.extend_from_slice(&[0xdd, 8 + 25, 0xfa, 0x0b, 0xbc, 0x0d, 0x01, 0xf2, 25, 1]);
+        frame.extend_from_slice(&basic_id);
+
+        let records = parse_80211_mgt(&frame, &RadiotapHeader::default(), &mut DecodeContext::default());
+        assert_eq!(records.len(), 1);
+        let r = &records[0];
+        assert_eq!(r.rid, "1581F5FKD229400A");
+        assert_eq!(r.position_status, PositionStatus::NoFix);
+        assert_eq!((r.latitude, r.longitude, r.run_status, r.geometric_altitude, r.timestamp), (0, 0, 0, 0, 0));
+    }
+
+    #[test]
+    fn test_parse_80211_mgt_nan() {
+        // 不带 radiotap 头的 NAN 服务发现帧
+        let mut frame = vec![0xd0, 0x00, 0x00, 0x00];
+        frame.extend_from_slice(&[0xff; 6]);
+        frame.extend_from_slice(&[0x02, 0x11, 0x22, 0x33, 0x44, 0x55]);
+        frame.extend_from_slice(&[0x50, 0x6f, 0x9a, 0x01, 0x12, 0x34]);
+        frame.extend_from_slice(&[0x10, 0x00]);
+        frame.extend_from_slice(&[4, 9, 0x50, 0x6f, 0x9a, 0x13]);
+        let mut service_info = vec![0x01, 0xf2, 25, 1, 0x02, 0x12];
+        service_info.extend_from_slice(b"1581F5FKD229400A");
+        service_info.resize(4 + 25, 0);
+        let mut sda = nan::ODID_SERVICE_ID.to_vec();
+        sda.extend_from_slice(&[0x01, 0x00, 0x10, service_info.len() as u8]);
+        sda.extend_from_slice(&service_info);
+        frame.push(0x03);
+        frame.extend_from_slice(&(sda.len() as u16).to_le_bytes());
+        frame.extend_from_slice(&sda);
+
+        let records = parse_80211_mgt(&frame, &RadiotapHeader::default(), &mut DecodeContext::default());
+        assert_eq!(records.len(), 1);
+        assert_eq!((records[0].rid.as_str(), records[0].source_mac.as_str()), ("1581F5FKD229400A", "02:11:22:33:44:55"));
+    }
+}
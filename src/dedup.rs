@@ -0,0 +1,235 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::VecDeque;
+use std::hash::{Hash, Hasher};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::pipeline::CapturedFrame;
+use crate::radiotap;
+use crate::upload_data::{InterfaceRssi, UploadData};
+
+/// 多网卡去重：信道重叠的网卡会收到同一帧，按帧内容在短时间窗口内只输出一次
+///
+/// ```toml
+/// [dedup]
+/// window_ms = 100     # 0 为不去重
+/// ```
+///
+/// 重复帧不再计入统计和上传，各网卡的信号强度记在保留记录的 `interface_rssi` 中。
+/// 只有一个网卡时不去重，也不增加输出延迟。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DedupConfig {
+    #[serde(default = "default_window_ms")]
+    pub window_ms: u64,
+}
+
+fn default_window_ms() -> u64 {
+    100
+}
+
+impl Default for DedupConfig {
+    fn default() -> Self {
+        Self { window_ms: default_window_ms() }
+    }
+}
+
+/// 去重窗口结束后输出的一帧及其记录
+pub struct UniqueFrame {
+    pub frame: CapturedFrame,
+    pub records: Vec<UploadData>,
+}
+
+struct Pending {
+    hash: Option<u64>,
+    received: Instant,
+    unique: UniqueFrame,
+}
+
+/// 帧在窗口内等待其他网卡的重复，窗口结束后按收到顺序输出
+pub struct DuplicateFilter {
+    window: Duration,
+    interfaces: Vec<String>,
+    pending: VecDeque<Pending>,
+    duplicates: u64,
+}
+
+impl DuplicateFilter {
+    /// `interfaces[i]` 为序号 i 的网卡名，记入 `interface_rssi`
+    pub fn new(config: DedupConfig, interfaces: Vec<String>) -> Self {
+        let window = if interfaces.len() > 1 { Duration::from_millis(config.window_ms) } else { Duration::ZERO };
+        Self { window, interfaces, pending: VecDeque::new(), duplicates: 0 }
+    }
+
+    /// 放入一帧；另一网卡已在窗口内收到相同内容的帧时丢弃，只把信号强度合并到先收到的记录
+    pub fn push(&mut self, frame: CapturedFrame, records: Vec<UploadData>, now: Instant) {
+        // 没有记录的帧不参与去重
+        let hash = (!self.window.is_zero() && !records.is_empty()).then(|| frame_hash(&frame.data)).flatten();
+        let window = self.window;
+        let original = hash.and_then(|hash| self.pending.iter_mut().find(|p| {
+            p.hash == Some(hash) && p.unique.frame.interface != frame.interface && now.duration_since(p.received) <= window
+        }));
+        let Some(original) = original else {
+            self.pending.push_back(Pending { hash, received: now, unique: UniqueFrame { frame, records } });
+            return;
+        };
+        self.duplicates += 1;
+        let primary = self.interfaces.get(original.unique.frame.interface).cloned().unwrap_or_default();
+        let duplicate = self.interfaces.get(frame.interface).cloned().unwrap_or_default();
+        for (kept, record) in original.unique.records.iter_mut().zip(&records) {
+            if let Some(rssi) = kept.rssi.filter(|_| kept.interface_rssi.is_empty()) {
+                kept.interface_rssi.push(InterfaceRssi { interface: primary.clone(), rssi });
+            }
+            if let Some(rssi) = record.rssi {
+                match kept.interface_rssi.iter_mut().find(|r| r.interface == duplicate) {
+                    Some(existing) => existing.rssi = rssi,
+                    None => kept.interface_rssi.push(InterfaceRssi { interface: duplicate.clone(), rssi }),
+                }
+            }
+        }
+    }
+
+    /// 取出窗口已结束的帧
+    pub fn take_due(&mut self, now: Instant) -> Vec<UniqueFrame> {
+        let mut due = Vec::new();
+        while self.pending.front().is_some_and(|p| now.duration_since(p.received) >= self.window) {
+            due.extend(self.pending.pop_front().map(|p| p.unique));
+        }
+        due
+    }
+
+    /// 退出时取出全部等待中的帧
+    pub fn flush(&mut self) -> Vec<UniqueFrame> {
+        self.pending.drain(..).map(|p| p.unique).collect()
+    }
+
+    /// 丢弃的重复帧数
+    pub fn duplicates(&self) -> u64 {
+        self.duplicates
+    }
+}
+
+/// 802.11 帧内容的哈希：去掉各网卡不同的 radiotap 头和可能附带的 FCS
+fn frame_hash(packet: &[u8]) -> Option<u64> {
+    let (header, len) = radiotap::parse(packet)?;
+    let end = if header.fcs_included() { packet.len().checked_sub(4)? } else { packet.len() };
+    let mut hasher = DefaultHasher::new();
+    packet.get(len..end)?.hash(&mut hasher);
+    Some(hasher.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(interface: usize, radiotap_pad: u8, body: u8) -> CapturedFrame {
+        let mut data = vec![0, 0, 9, 0, 0, 0, 0, 0, radiotap_pad];
+        data.extend_from_slice(&[0x80, 0, 0, 0, body, 1, 2, 3]);
+        CapturedFrame { interface, channel: Some(6), data }
+    }
+
+    fn records(rssi: f32) -> Vec<UploadData> {
+        vec![UploadData { rid: "RID-1".into(), rssi: Some(rssi), ..Default::default() }]
+    }
+
+    const MS: Duration = Duration::from_millis(1);
+
+    fn filter() -> DuplicateFilter {
+        DuplicateFilter::new(DedupConfig::default(), vec!["wlan0".to_string(), "wlan1".to_string(), "wlan2".to_string()])
+    }
+
+    fn interface_rssi(unique: &UniqueFrame) -> Vec<(&str, f32)> {
+        unique.records[0].interface_rssi.iter().map(|r| (r.interface.as_str(), r.rssi)).collect()
+    }
+
+    #[test]
+    fn test_frame_hash_ignores_radiotap_and_fcs() {
+        assert_eq!(frame_hash(&frame(0, 1, 7).data), frame_hash(&frame(1, 2, 7).data));
+        assert_ne!(frame_hash(&frame(0, 1, 7).data), frame_hash(&frame(0, 1, 8).data));
+        // flags 中 FCS 在尾部
+        let mut with_fcs = vec![0, 0, 9, 0, 0x02, 0, 0, 0, 0x10];
+        with_fcs.extend_from_slice(&[0x80, 0, 0, 0, 7, 1, 2, 3, 0xde, 0xad, 0xbe, 0xef]);
+        assert_eq!(frame_hash(&with_fcs), frame_hash(&frame(0, 1, 7).data));
+        assert_eq!(frame_hash(&[0x80, 0]), None);
+    }
+
+    #[test]
+    fn test_duplicate_from_other_interface() {
+        let mut filter = filter();
+        let start = Instant::now();
+        filter.push(frame(0, 1, 7), records(-60.0), start);
+        // 另一网卡收到同一帧，radiotap 头不同
+        filter.push(frame(1, 2, 7), records(-72.0), start + 5 * MS);
+        filter.push(frame(2, 3, 7), records(-80.0), start + 6 * MS);
+        let due = filter.take_due(start + 100 * MS);
+        assert_eq!(due.len(), 1);
+        assert_eq!(interface_rssi(&due[0]), [("wlan0", -60.0), ("wlan1", -72.0), ("wlan2", -80.0)]);
+        assert_eq!(due[0].records[0].rssi, Some(-60.0));
+        assert_eq!(filter.duplicates(), 2);
+    }
+
+    #[test]
+    fn test_same_interface_is_not_duplicate() {
+        let mut filter = filter();
+        let start = Instant::now();
+        filter.push(frame(0, 1, 7), records(-60.0), start);
+        filter.push(frame(0, 1, 7), records(-61.0), start + 10 * MS);
+        assert_eq!((filter.flush().len(), filter.duplicates()), (2, 0));
+    }
+
+    #[test]
+    fn test_different_content_is_not_duplicate() {
+        let mut filter = filter();
+        let start = Instant::now();
+        filter.push(frame(0, 1, 7), records(-60.0), start);
+        filter.push(frame(1, 2, 8), records(-70.0), start + 10 * MS);
+        assert_eq!((filter.flush().len(), filter.duplicates()), (2, 0));
+    }
+
+    #[test]
+    fn test_duplicate_after_window() {
+        let mut filter = filter();
+        let start = Instant::now();
+        filter.push(frame(0, 1, 7), records(-60.0), start);
+        filter.push(frame(1, 2, 7), records(-72.0), start + 101 * MS);
+        assert_eq!((filter.flush().len(), filter.duplicates()), (2, 0));
+    }
+
+    #[test]
+    fn test_frames_without_records_are_not_deduplicated() {
+        let mut filter = filter();
+        let start = Instant::now();
+        filter.push(frame(0, 1, 7), Vec::new(), start);
+        filter.push(frame(1, 2, 7), Vec::new(), start);
+        assert_eq!((filter.flush().len(), filter.duplicates()), (2, 0));
+    }
+
+    #[test]
+    fn test_frames_wait_for_window_in_order() {
+        let mut filter = filter();
+        let start = Instant::now();
+        filter.push(frame(0, 1, 7), records(-60.0), start);
+        filter.push(frame(1, 2, 8), records(-70.0), start + 20 * MS);
+        assert!(filter.take_due(start + 50 * MS).is_empty());
+        let due = filter.take_due(start + 100 * MS);
+        assert_eq!(due.iter().map(|u| u.frame.interface).collect::<Vec<_>>(), [0]);
+        assert_eq!(filter.take_due(start + 120 * MS)[0].frame.interface, 1);
+    }
+
+    #[test]
+    fn test_single_interface_has_no_delay() {
+        let mut single = DuplicateFilter::new(DedupConfig::default(), vec!["wlan0".to_string()]);
+        let start = Instant::now();
+        single.push(frame(0, 1, 7), records(-60.0), start);
+        assert_eq!(single.take_due(start).len(), 1);
+    }
+
+    #[test]
+    fn test_zero_window_disables_dedup() {
+        let mut filter = DuplicateFilter::new(DedupConfig { window_ms: 0 }, vec!["wlan0".to_string(), "wlan1".to_string()]);
+        let start = Instant::now();
+        filter.push(frame(0, 1, 7), records(-60.0), start);
+        filter.push(frame(1, 2, 7), records(-72.0), start);
+        assert_eq!((filter.take_due(start).len(), filter.duplicates()), (2, 0));
+    }
+}
@@ -0,0 +1,167 @@
+use libwifi::frame::components::MacAddress;
+
+use crate::remote_id::{MESSAGE_SIZE, PAYLOAD_HEADER_LEN};
+
+/// 消息包中最多的消息数
+const MAX_PACK_MESSAGES: usize = 9;
+
+/// 数据帧的源地址与帧体
+///
+/// 跳过受保护（加密）帧和不携带数据的空帧；四地址帧和 QoS 数据帧的头部长度按标志位计算。
+pub fn data_frame_body(frame: &[u8]) -> Option<(MacAddress, &[u8])> {
+    let (fc0, fc1) = (*frame.first()?, *frame.get(1)?);
+    let frame_type = (fc0 >> 2) & 0x03;
+    let subtype = fc0 >> 4;
+    if frame_type != 2 || subtype & 0x04 != 0 || fc1 & 0x40 != 0 {
+        return None;
+    }
+    let to_ds_from_ds = fc1 & 0x03 == 0x03;
+    let mut header_len = 24;
+    if to_ds_from_ds {
+        header_len += 6;
+    }
+    if subtype & 0x08 != 0 {
+        header_len += 2;
+    }
+    // 源地址：FromDS 时为地址 3，四地址帧为地址 4，否则为地址 2
+    let source_offset = match fc1 & 0x03 {
+        0x02 => 16,
+        0x03 => 24,
+        _ => 10,
+    };
+    let mut source = [0u8; 6];
+    source.copy_from_slice(frame.get(source_offset..source_offset + 6)?);
+    Some((MacAddress(source), frame.get(header_len..)?))
+}
+
+/// 在任意字节中启发式查找 ODID 消息包
+///
+/// 特征为：消息包头 (0xF?)、消息大小 25、消息数量 1-9，且每条消息头的类型
+/// 不超过 5、协议版本不超过 2。命中时返回以消息计数器开头的完整负载
+/// （即与厂商 IE 中相同的布局），计数器取特征前一字节，不存在时为 0。
+/// 结果可能是误报，调用方应将记录标记为启发式。
+pub fn find_message_pack(bytes: &[u8]) -> Option<Vec<u8>> {
+    (0..bytes.len().saturating_sub(PAYLOAD_HEADER_LEN - 1)).find_map(|i| {
+        let header = &bytes[i..];
+        if header[0] >> 4 != 0x0F || header[1] as usize != MESSAGE_SIZE {
+            return None;
+        }
+        let count = header[2] as usize;
+        let len = PAYLOAD_HEADER_LEN - 1 + MESSAGE_SIZE * count;
+        if !(1..=MAX_PACK_MESSAGES).contains(&count) || header.len() < len {
+            return None;
+        }
+        let plausible = (0..count)
+            .map(|m| header[PAYLOAD_HEADER_LEN - 1 + MESSAGE_SIZE * m])
+            .all(|b| b >> 4 <= 0x05 && b & 0x0F <= 0x02);
+        if !plausible {
+            return None;
+        }
+        let counter = if i > 0 { bytes[i - 1] } else { 0 };
+        let mut payload = vec![counter];
+        payload.extend_from_slice(&header[..len]);
+        Some(payload)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 数据帧头：四个地址依次填 0x01..0x04，地址 4 仅在四地址帧中写入
+    fn data_frame(fc0: u8, fc1: u8) -> Vec<u8> {
+        let mut frame = vec![fc0, fc1, 0, 0];
+        for n in 1..=3 {
+            frame.extend_from_slice(&[0x02, 0, 0, 0, 0, n]);
+        }
+        frame.extend_from_slice(&[0, 0]);
+        if fc1 & 0x03 == 0x03 {
+            frame.extend_from_slice(&[0x02, 0, 0, 0, 0, 4]);
+        }
+        if fc0 & 0x80 != 0 {
+            frame.extend_from_slice(&[0, 0]);
+        }
+        frame.extend_from_slice(b"body");
+        frame
+    }
+
+    /// 消息包：计数器 + 包头 + 每条消息 (仅填消息头)
+    fn pack(counter: u8, headers: &[u8]) -> Vec<u8> {
+        let mut bytes = vec![counter, 0xf2, MESSAGE_SIZE as u8, headers.len() as u8];
+        for &header in headers {
+            let mut message = [0u8; MESSAGE_SIZE];
+            message[0] = header;
+            bytes.extend_from_slice(&message);
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_find_pack_in_udp_payload() {
+        // QoS 数据帧 (FromDS)，LLC/SNAP + IPv4 + UDP 头后跟消息包
+        let mut frame = data_frame(0x88, 0x02);
+        frame.truncate(frame.len() - 4);
+        frame.extend_from_slice(&[0xaa, 0xaa, 0x03, 0, 0, 0, 0x08, 0x00]);
+        frame.extend_from_slice(&[0x45; 28]);
+        frame.extend_from_slice(&pack(0x07, &[0x02, 0x12]));
+
+        let (_, body) = data_frame_body(&frame).unwrap();
+        let payload = find_message_pack(body).unwrap();
+        assert_eq!(&payload[..4], &[0x07, 0xf2, 25, 2]);
+        assert_eq!(payload.len(), 4 + 50);
+    }
+
+    #[test]
+    fn test_data_frame_source_address() {
+        let source = |fc1| data_frame_body(&data_frame(0x08, fc1)).unwrap().0.0[5];
+        assert_eq!(source(0x00), 2);
+        assert_eq!(source(0x01), 2);
+        assert_eq!(source(0x02), 3);
+        assert_eq!(source(0x03), 4);
+    }
+
+    #[test]
+    fn test_data_frame_header_length() {
+        assert_eq!(data_frame_body(&data_frame(0x08, 0x01)).unwrap().1, b"body");
+        assert_eq!(data_frame_body(&data_frame(0x88, 0x02)).unwrap().1, b"body");
+        assert_eq!(data_frame_body(&data_frame(0x88, 0x03)).unwrap().1, b"body");
+    }
+
+    #[test]
+    fn test_data_frame_skips_protected_null_and_non_data() {
+        assert!(data_frame_body(&data_frame(0x08, 0x42)).is_none());
+        assert!(data_frame_body(&data_frame(0x48, 0x01)).is_none());
+        assert!(data_frame_body(&data_frame(0xc8, 0x01)).is_none());
+        assert!(data_frame_body(&data_frame(0x80, 0x00)).is_none());
+        assert!(data_frame_body(&[0x08, 0x01, 0, 0]).is_none());
+    }
+
+    #[test]
+    fn test_find_pack_at_start_has_zero_counter() {
+        let bytes = pack(0x07, &[0x02]);
+        let payload = find_message_pack(&bytes[1..]).unwrap();
+        assert_eq!(payload[0], 0);
+        assert_eq!(payload[1..], bytes[1..]);
+    }
+
+    #[test]
+    fn test_find_pack_rejects_bad_count() {
+        let mut bytes = pack(0x07, &[0x02]);
+        bytes[3] = 0;
+        assert!(find_message_pack(&bytes).is_none());
+        let bytes = pack(0x07, &[0x02; MAX_PACK_MESSAGES + 1]);
+        assert!(find_message_pack(&bytes).is_none());
+    }
+
+    #[test]
+    fn test_find_pack_rejects_implausible_messages() {
+        assert!(find_message_pack(&pack(0x07, &[0x02, 0x62])).is_none());
+        assert!(find_message_pack(&pack(0x07, &[0x02, 0x13])).is_none());
+    }
+
+    #[test]
+    fn test_find_pack_rejects_truncated() {
+        let bytes = pack(0x07, &[0x02, 0x12]);
+        assert!(find_message_pack(&bytes[..bytes.len() - 1]).is_none());
+    }
+}
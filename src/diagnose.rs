@@ -0,0 +1,174 @@
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::time::Duration;
+
+use crate::radiotap;
+use crate::wifi::frequency_to_channel;
+
+/// 网卡射频诊断统计
+#[derive(Debug, Default)]
+pub struct Diagnosis {
+    pub duration: Duration,
+    pub frames: u64,
+    pub radiotap: u64,
+    pub with_signal: u64,
+    pub fcs_included: u64,
+    pub bad_fcs: u64,
+    pub beacons: u64,
+    pub rid_beacons: u64,
+    pub channels: BTreeMap<u16, u64>,   // 频率 (MHz) → 帧数
+}
+
+impl Diagnosis {
+    pub fn add_frame(&mut self, packet: &[u8], remote_id: bool) {
+        self.frames += 1;
+        if remote_id {
+            self.rid_beacons += 1;
+        }
+        let Some((info, header_len)) = radiotap::parse(packet) else { return };
+        self.radiotap += 1;
+        if info.signal_dbm.is_some() {
+            self.with_signal += 1;
+        }
+        if info.fcs_included() {
+            self.fcs_included += 1;
+        }
+        if info.bad_fcs() {
+            self.bad_fcs += 1;
+        }
+        if let Some(freq) = info.channel_freq {
+            *self.channels.entry(freq).or_default() += 1;
+        }
+        if packet.get(header_len).is_some_and(|fc| fc & 0xfc == 0x80) {
+            self.beacons += 1;
+        }
+    }
+
+    /// 网卡/驱动是否可用于 Remote ID 监听
+    pub fn usable(&self) -> bool {
+        self.radiotap > 0 && self.beacons > 0
+    }
+
+    pub fn write_report<W: Write>(&self, mut out: W) -> io::Result<()> {
+        let percent = |n: u64| if self.frames > 0 { n as f64 * 100.0 / self.frames as f64 } else { 0.0 };
+        let yes_no = |ok: bool| if ok { "是" } else { "否" };
+        writeln!(out, "抓包时长: {} 秒, 共 {} 帧", self.duration.as_secs(), self.frames)?;
+        writeln!(out, "radiotap 头: {} ({:.0}%)", yes_no(self.radiotap > 0), percent(self.radiotap))?;
+        writeln!(out, "信号强度 (dBm): {} ({:.0}%)", yes_no(self.with_signal > 0), percent(self.with_signal))?;
+        writeln!(out, "帧含 FCS: {} ({:.0}%)", yes_no(self.fcs_included > 0), percent(self.fcs_included))?;
+        writeln!(out, "投递 FCS 错误帧: {} ({} 帧)", yes_no(self.bad_fcs > 0), self.bad_fcs)?;
+        let channels: Vec<String> = self.channels.iter()
+            .map(|(freq, n)| format!("{} ({} MHz, {} 帧)", frequency_to_channel(*freq), freq, n))
+            .collect();
+        writeln!(out, "观察到的信道: {}", if channels.is_empty() { "-".to_string() } else { channels.join(", ") })?;
+        writeln!(out, "信标帧: {}, 其中 Remote ID: {}", self.beacons, self.rid_beacons)?;
+
+        writeln!(out)?;
+        if self.frames == 0 {
+            writeln!(out, "结论: 未收到任何帧，请确认网卡处于监听模式且驱动支持")?;
+        } else if self.radiotap == 0 {
+            writeln!(out, "结论: 帧不含 radiotap 头，网卡可能未处于监听模式")?;
+        } else if !self.usable() {
+            writeln!(out, "结论: 未收到信标帧，请检查信道设置或天线")?;
+        } else {
+            writeln!(out, "结论: 网卡可用于 Remote ID 监听")?;
+            if self.with_signal == 0 {
+                writeln!(out, "注意: 驱动未提供信号强度，测距/测向功能不可用")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// radiotap 头 (flags | rate | channel | dbm_antsignal) + 帧控制字段
+    fn packet(flags: u8, frame_control: u8) -> Vec<u8> {
+        vec![
+            0x00, 0x00, 0x10, 0x00, 0x2e, 0x00, 0x00, 0x00,
+            flags,
+            0x02,                   // rate
+            0x85, 0x09, 0xa0, 0x00, // 2437 MHz
+            0xc4,                   // -60 dBm
+            0x00,                   // 对齐填充
+            frame_control, 0x00,
+        ]
+    }
+
+    /// 只有 rate 字段的 radiotap 头，没有信道和信号强度
+    fn bare_packet(frame_control: u8) -> Vec<u8> {
+        vec![0x00, 0x00, 0x09, 0x00, 0x04, 0x00, 0x00, 0x00, 0x02, frame_control, 0x00]
+    }
+
+    fn report(diagnosis: &Diagnosis) -> String {
+        let mut out = Vec::new();
+        diagnosis.write_report(&mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_counts_radiotap_fields() {
+        let mut diagnosis = Diagnosis::default();
+        diagnosis.add_frame(&packet(0x50, 0x80), true);   // FCS 在尾部 + FCS 错误
+        diagnosis.add_frame(&packet(0x10, 0x80), false);
+        diagnosis.add_frame(&bare_packet(0x80), false);
+        assert_eq!((diagnosis.frames, diagnosis.radiotap, diagnosis.with_signal), (3, 3, 2));
+        assert_eq!((diagnosis.fcs_included, diagnosis.bad_fcs), (2, 1));
+        assert_eq!((diagnosis.beacons, diagnosis.rid_beacons), (3, 1));
+        assert_eq!(diagnosis.channels, BTreeMap::from([(2437, 2)]));
+        assert!(diagnosis.usable());
+    }
+
+    #[test]
+    fn test_only_beacons_are_counted_as_beacons() {
+        let mut diagnosis = Diagnosis::default();
+        diagnosis.add_frame(&packet(0, 0x50), false);   // 探测响应
+        diagnosis.add_frame(&packet(0, 0x08), false);   // 数据帧
+        assert_eq!(diagnosis.beacons, 0);
+        assert!(!diagnosis.usable());
+    }
+
+    #[test]
+    fn test_frames_without_radiotap() {
+        let mut diagnosis = Diagnosis::default();
+        diagnosis.add_frame(&[0x80, 0x00, 0x00], false);
+        assert_eq!((diagnosis.frames, diagnosis.radiotap, diagnosis.beacons), (1, 0, 0));
+        assert!(!diagnosis.usable());
+        assert!(report(&diagnosis).ends_with("结论: 帧不含 radiotap 头，网卡可能未处于监听模式\n"));
+    }
+
+    #[test]
+    fn test_report_without_frames() {
+        let report = report(&Diagnosis { duration: Duration::from_secs(10), ..Default::default() });
+        assert!(report.starts_with("抓包时长: 10 秒, 共 0 帧\nradiotap 头: 否 (0%)\n"), "{}", report);
+        assert!(report.contains("观察到的信道: -\n"), "{}", report);
+        assert!(report.ends_with("结论: 未收到任何帧，请确认网卡处于监听模式且驱动支持\n"), "{}", report);
+    }
+
+    #[test]
+    fn test_report_without_beacons() {
+        let mut diagnosis = Diagnosis::default();
+        diagnosis.add_frame(&packet(0, 0x08), false);
+        assert!(report(&diagnosis).ends_with("结论: 未收到信标帧，请检查信道设置或天线\n"));
+    }
+
+    #[test]
+    fn test_report_usable_card() {
+        let mut diagnosis = Diagnosis::default();
+        diagnosis.add_frame(&packet(0x10, 0x80), true);
+        diagnosis.add_frame(&packet(0, 0x08), false);
+        let report = report(&diagnosis);
+        assert!(report.contains("信号强度 (dBm): 是 (100%)\n帧含 FCS: 是 (50%)\n"), "{}", report);
+        assert!(report.contains("观察到的信道: 6 (2437 MHz, 2 帧)\n信标帧: 1, 其中 Remote ID: 1\n"), "{}", report);
+        assert!(report.ends_with("结论: 网卡可用于 Remote ID 监听\n"), "{}", report);
+    }
+
+    #[test]
+    fn test_report_notes_missing_signal() {
+        let mut diagnosis = Diagnosis::default();
+        diagnosis.add_frame(&bare_packet(0x80), false);
+        assert!(report(&diagnosis).ends_with("结论: 网卡可用于 Remote ID 监听\n注意: 驱动未提供信号强度，测距/测向功能不可用\n"));
+    }
+}
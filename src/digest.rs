@@ -0,0 +1,296 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+use std::sync::mpsc;
+
+use chrono::SecondsFormat;
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use crate::alerts::spawn_webhook;
+use crate::egress::Priority;
+use crate::event_log::DecodedEvent;
+use crate::geo::degrees;
+use crate::time_format;
+
+/// 首次出现汇总的配置
+///
+/// ```toml
+/// [digest]
+/// interval_minutes = 60
+/// webhook = "https://example.com/hooks/new-drones"
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DigestConfig {
+    #[serde(default = "default_interval_minutes")]
+    pub interval_minutes: u64,
+    #[serde(default)]
+    pub webhook: Option<String>,
+}
+
+fn default_interval_minutes() -> u64 {
+    60
+}
+
+/// 汇总周期内首次出现的一架无人机
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NewDrone {
+    pub uas_id: String,
+    pub label: Option<String>,
+    pub first_seen_ms: i64,
+    pub ua_type: Option<u8>,
+    pub operator: Option<(f64, f64)>,     // 控制站位置 (纬度, 经度)
+    pub closest_range_m: Option<f32>,     // 周期内按信号强度估算的最近距离
+    pub max_operator_distance_m: Option<f32>,   // 周期内与控制站的最大距离
+}
+
+/// 一个周期的汇总
+#[derive(Debug, Clone, Serialize)]
+pub struct Digest {
+    pub period_start_ms: i64,
+    pub period_end_ms: i64,
+    pub drones: Vec<NewDrone>,
+    pub table: String,
+}
+
+/// 把"新无人机首次出现"事件按周期合并为一份汇总，代替逐条告警
+///
+/// 24 小时内没有再出现的无人机再次出现时重新视为新无人机。
+pub struct FirstSeenDigest {
+    interval_ms: i64,
+    period_start_ms: Option<i64>,
+    last_seen: HashMap<String, i64>,
+    pending: BTreeMap<String, NewDrone>,
+}
+
+impl FirstSeenDigest {
+    const FORGET_AFTER_MS: i64 = 24 * 3600 * 1000;
+
+    pub fn new(interval_ms: i64) -> Self {
+        Self { interval_ms, period_start_ms: None, last_seen: HashMap::new(), pending: BTreeMap::new() }
+    }
+
+    pub fn observe(&mut self, event: &DecodedEvent) {
+        let r = &event.record;
+        if r.rid.is_empty() {
+            return;
+        }
+        let now = event.received_at_ms;
+        self.period_start_ms.get_or_insert(now);
+        let previous = self.last_seen.insert(r.rid.clone(), now);
+        if previous.is_some_and(|t| now - t < Self::FORGET_AFTER_MS) && !self.pending.contains_key(&r.rid) {
+            return;
+        }
+        let drone = self.pending.entry(r.rid.clone()).or_insert_with(|| NewDrone {
+            uas_id: r.rid.clone(),
+            label: None,
+            first_seen_ms: now,
+            ua_type: None,
+            operator: None,
+            closest_range_m: None,
+            max_operator_distance_m: None,
+        });
+        drone.label = drone.label.take().or_else(|| r.annotation.as_ref().map(|a| a.label()));
+        drone.ua_type = drone.ua_type.or(r.ua_type);
+        if let Some(operator) = &r.operator {
+            drone.operator = Some((degrees(operator.latitude), degrees(operator.longitude)));
+        }
+        if let Some(range) = r.estimated_range_m {
+            drone.closest_range_m = Some(drone.closest_range_m.map_or(range, |closest| closest.min(range)));
+        }
+        if let Some(distance) = r.operator_distance_m {
+            drone.max_operator_distance_m = Some(drone.max_operator_distance_m.map_or(distance, |max| max.max(distance)));
+        }
+    }
+
+    /// 恢复重启前各无人机最近出现的时间，之前一天内出现过的不再当作新无人机
+    pub fn restore_seen(&mut self, seen: impl IntoIterator<Item = (String, i64)>) {
+        for (uas_id, at) in seen {
+            let last = self.last_seen.entry(uas_id).or_insert(at);
+            *last = (*last).max(at);
+        }
+    }
+
+    /// 周期结束时取出汇总（没有新无人机时返回 None 并开始下一个周期）
+    pub fn take_due(&mut self, now_ms: i64) -> Option<Digest> {
+        let start = self.period_start_ms?;
+        if now_ms - start < self.interval_ms {
+            return None;
+        }
+        self.period_start_ms = Some(now_ms);
+        self.last_seen.retain(|_, t| now_ms - *t < Self::FORGET_AFTER_MS);
+        if self.pending.is_empty() {
+            return None;
+        }
+        let drones: Vec<NewDrone> = std::mem::take(&mut self.pending).into_values().collect();
+        let table = format_table(&drones);
+        Some(Digest { period_start_ms: start, period_end_ms: now_ms, drones, table })
+    }
+}
+
+fn format_table(drones: &[NewDrone]) -> String {
+    let mut table = format!("{:<24} {:<20} {:>6} {:<24} {:>10} {:>10}\n", "UAS ID", "首次出现", "类型", "控制站", "最近距离", "控制站距离");
+    for d in drones {
+        let _ = writeln!(table, "{:<24} {:<20} {:>6} {:<24} {:>10} {:>10}",
+            d.label.as_deref().unwrap_or(&d.uas_id),
+            time_format::format_ms(d.first_seen_ms, SecondsFormat::Secs),
+            d.ua_type.map(|t| t.to_string()).unwrap_or("-".into()),
+            d.operator.map(|(lat, lon)| format!("{:.5},{:.5}", lat, lon)).unwrap_or("-".into()),
+            d.closest_range_m.map(|m| format!("{:.0}m", m)).unwrap_or("-".into()),
+            d.max_operator_distance_m.map(|m| format!("{:.0}m", m)).unwrap_or("-".into()));
+    }
+    table
+}
+
+/// 汇总的去向：写入日志，配置了 webhook 时 POST 发送
+pub struct DigestNotifier {
+    digest: FirstSeenDigest,
+    webhook: Option<mpsc::Sender<Digest>>,
+}
+
+impl DigestNotifier {
+    pub fn new(config: &DigestConfig) -> Self {
+        Self {
+            digest: FirstSeenDigest::new(config.interval_minutes as i64 * 60_000),
+            webhook: config.webhook.clone().map(|url| spawn_webhook(url, Priority::Status)),
+        }
+    }
+
+    pub fn observe(&mut self, event: &DecodedEvent) {
+        self.digest.observe(event);
+    }
+
+    pub fn restore_seen(&mut self, seen: impl IntoIterator<Item = (String, i64)>) {
+        self.digest.restore_seen(seen);
+    }
+
+    pub fn poll(&mut self, now_ms: i64) {
+        let Some(digest) = self.digest.take_due(now_ms) else { return };
+        info!("{} new drones since {}:\n{}", digest.drones.len(),
+            time_format::format_ms(digest.period_start_ms, SecondsFormat::Secs), digest.table);
+        if let Some(webhook) = &self.webhook {
+            let _ = webhook.send(digest);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::upload_data::UploadData;
+
+    const HOUR_MS: i64 = 3_600_000;
+
+    fn fix(rid: &str, at: i64) -> DecodedEvent {
+        DecodedEvent { received_at_ms: at, record: UploadData { rid: rid.into(), ..Default::default() } }
+    }
+
+    fn uas_ids(digest: &Digest) -> Vec<&str> {
+        digest.drones.iter().map(|d| d.uas_id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_not_due_before_interval() {
+        let mut digest = FirstSeenDigest::new(HOUR_MS);
+        assert!(digest.take_due(HOUR_MS).is_none());
+        digest.observe(&fix("A", 0));
+        assert!(digest.take_due(HOUR_MS - 1).is_none());
+        let due = digest.take_due(HOUR_MS).unwrap();
+        assert_eq!((due.period_start_ms, due.period_end_ms), (0, HOUR_MS));
+    }
+
+    #[test]
+    fn test_new_drones_in_uas_id_order() {
+        let mut digest = FirstSeenDigest::new(HOUR_MS);
+        for (rid, at) in [("B", 0), ("A", 60_000), ("B", 120_000)] {
+            digest.observe(&fix(rid, at));
+        }
+        let due = digest.take_due(HOUR_MS).unwrap();
+        assert_eq!(uas_ids(&due), ["A", "B"]);
+        assert_eq!(due.drones[1].first_seen_ms, 0);
+    }
+
+    #[test]
+    fn test_records_without_uas_id_are_ignored() {
+        let mut digest = FirstSeenDigest::new(HOUR_MS);
+        digest.observe(&fix("", 0));
+        assert!(digest.take_due(HOUR_MS).is_none());
+    }
+
+    #[test]
+    fn test_closest_range_and_farthest_operator() {
+        let mut digest = FirstSeenDigest::new(HOUR_MS);
+        for (at, range, distance) in [(0, 300.0, None), (60_000, 120.0, Some(650.0)), (120_000, 200.0, Some(400.0))] {
+            let mut event = fix("A", at);
+            event.record.estimated_range_m = Some(range);
+            event.record.operator_distance_m = distance;
+            digest.observe(&event);
+        }
+        digest.observe(&fix("B", 0));
+        let due = digest.take_due(HOUR_MS).unwrap();
+        assert_eq!((due.drones[0].closest_range_m, due.drones[0].max_operator_distance_m), (Some(120.0), Some(650.0)));
+        assert_eq!((due.drones[1].closest_range_m, due.drones[1].max_operator_distance_m), (None, None));
+    }
+
+    #[test]
+    fn test_first_ua_type_is_kept() {
+        let mut digest = FirstSeenDigest::new(HOUR_MS);
+        let mut event = fix("A", 0);
+        digest.observe(&event);
+        event.record.ua_type = Some(2);
+        digest.observe(&event);
+        event.record.ua_type = Some(1);
+        digest.observe(&event);
+        assert_eq!(digest.take_due(HOUR_MS).unwrap().drones[0].ua_type, Some(2));
+    }
+
+    #[test]
+    fn test_reported_drones_are_not_repeated() {
+        let mut digest = FirstSeenDigest::new(HOUR_MS);
+        digest.observe(&fix("A", 0));
+        digest.take_due(HOUR_MS).unwrap();
+        digest.observe(&fix("A", HOUR_MS + 100_000));
+        digest.observe(&fix("C", HOUR_MS + 200_000));
+        assert_eq!(uas_ids(&digest.take_due(2 * HOUR_MS).unwrap()), ["C"]);
+        // 没有新无人机的周期不输出汇总
+        digest.observe(&fix("A", 2 * HOUR_MS + 100_000));
+        assert!(digest.take_due(3 * HOUR_MS).is_none());
+    }
+
+    #[test]
+    fn test_drone_back_after_a_day_is_new() {
+        let mut digest = FirstSeenDigest::new(HOUR_MS);
+        digest.observe(&fix("A", 0));
+        digest.take_due(HOUR_MS).unwrap();
+        digest.observe(&fix("A", 25 * HOUR_MS));
+        assert_eq!(uas_ids(&digest.take_due(26 * HOUR_MS).unwrap()), ["A"]);
+    }
+
+    #[test]
+    fn test_restore_seen() {
+        let mut digest = FirstSeenDigest::new(HOUR_MS);
+        digest.restore_seen([("A".to_string(), -HOUR_MS), ("B".to_string(), -25 * HOUR_MS)]);
+        digest.observe(&fix("A", 0));
+        digest.observe(&fix("B", 0));
+        assert_eq!(uas_ids(&digest.take_due(HOUR_MS).unwrap()), ["B"]);
+    }
+
+    #[test]
+    fn test_table() {
+        let drone = NewDrone {
+            uas_id: "1581F5FKD229400A".into(),
+            label: None,
+            first_seen_ms: 0,
+            ua_type: Some(2),
+            operator: Some((31.2, 121.4)),
+            closest_range_m: Some(120.4),
+            max_operator_distance_m: None,
+        };
+        let labelled = NewDrone { label: Some("巡检机-1".into()), ua_type: None, operator: None, closest_range_m: None, ..drone.clone() };
+        let table = format_table(&[drone, labelled]);
+        let rows: Vec<Vec<&str>> = table.lines().skip(1).map(|line| line.split_whitespace().collect()).collect();
+        assert_eq!(rows, [
+            vec!["1581F5FKD229400A", "1970-01-01T00:00:00Z", "2", "31.20000,121.40000", "120m", "-"],
+            vec!["巡检机-1", "1970-01-01T00:00:00Z", "-", "-", "-", "-"],
+        ]);
+    }
+}
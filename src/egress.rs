@@ -0,0 +1,187 @@
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use tracing::warn;
+
+/// 出站消息的优先级，预算紧张时先丢弃低优先级
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Priority {
+    /// 告警：预算耗尽时可透支至多一分钟的额度
+    Alert,
+    /// 状态/汇总通知
+    Status,
+    /// 批量定位数据：上传、ASTERIX
+    Bulk,
+}
+
+impl Priority {
+    /// 发送后令牌桶至少要剩余的比例，为更高优先级留出余量
+    fn reserve(self) -> f64 {
+        match self {
+            Self::Alert => -1.0,
+            Self::Status => 0.1,
+            Self::Bulk => 0.3,
+        }
+    }
+}
+
+/// 出站流量令牌桶，容量为一分钟的预算，按秒平滑补充
+#[derive(Debug)]
+pub struct TokenBucket {
+    capacity: f64,
+    rate_per_s: f64,
+    tokens: f64,
+    refilled_at: Instant,
+    dropped: [u64; 3],
+    warned_at: Option<Instant>,
+}
+
+impl TokenBucket {
+    pub fn new(kb_per_min: u32, now: Instant) -> Self {
+        let capacity = kb_per_min as f64 * 1024.0;
+        Self { capacity, rate_per_s: capacity / 60.0, tokens: capacity, refilled_at: now, dropped: [0; 3], warned_at: None }
+    }
+
+    /// 该优先级一次最多可发送的字节数
+    pub fn share(&self, priority: Priority) -> usize {
+        (self.capacity * (1.0 - priority.reserve().max(0.0))) as usize
+    }
+
+    /// 预算允许时扣除 `bytes` 并返回 true，否则记为丢弃
+    ///
+    /// 超过该优先级份额、但不超过一分钟预算的消息在令牌桶满时放行（此后透支），否则永远发不出去。
+    pub fn try_take(&mut self, priority: Priority, bytes: usize, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.refilled_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate_per_s).min(self.capacity);
+        self.refilled_at = now;
+        let remaining = self.tokens - bytes as f64;
+        let oversized = bytes > self.share(priority) && bytes as f64 <= self.capacity && self.tokens >= self.capacity;
+        if remaining >= priority.reserve() * self.capacity || oversized {
+            self.tokens = remaining;
+            true
+        } else {
+            self.dropped[priority as usize] += 1;
+            false
+        }
+    }
+
+    /// 每分钟至多返回一次自上次以来的丢弃数 (告警, 状态, 批量)
+    fn take_drop_report(&mut self, now: Instant) -> Option<[u64; 3]> {
+        if self.dropped == [0; 3] || self.warned_at.is_some_and(|t| now - t < Duration::from_secs(60)) {
+            return None;
+        }
+        self.warned_at = Some(now);
+        Some(std::mem::take(&mut self.dropped))
+    }
+}
+
+/// HTTP 请求头、TCP/TLS 帧等在消息体之外的估计字节数
+pub const HTTP_OVERHEAD: usize = 600;
+
+static BUDGET: OnceLock<Mutex<TokenBucket>> = OnceLock::new();
+
+/// 启动时设置一次全局出站预算 (KB/分钟)；未设置时不限流
+pub fn configure(kb_per_min: u32) {
+    let _ = BUDGET.set(Mutex::new(TokenBucket::new(kb_per_min, Instant::now())));
+}
+
+/// 该优先级一次最多可发送的线上字节数；未设置预算时为 None
+pub fn share(priority: Priority) -> Option<usize> {
+    BUDGET.get().map(|budget| budget.lock().unwrap().share(priority))
+}
+
+/// 所有经移动网络出站的发送方在发送前调用，`bytes` 为估计的线上字节数
+pub fn allow(priority: Priority, bytes: usize) -> bool {
+    let Some(budget) = BUDGET.get() else { return true };
+    let mut budget = budget.lock().unwrap();
+    let now = Instant::now();
+    let allowed = budget.try_take(priority, bytes, now);
+    if let Some([alert, status, bulk]) = budget.take_drop_report(now) {
+        warn!("egress budget exhausted, dropped {} alert / {} status / {} bulk messages", alert, status, bulk);
+    }
+    allowed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 10 KB/分钟 (10240 字节) 的满令牌桶
+    fn bucket(start: Instant) -> TokenBucket {
+        TokenBucket::new(10, start)
+    }
+
+    #[test]
+    fn test_bulk_keeps_reserve() {
+        let start = Instant::now();
+        let mut bucket = bucket(start);
+        assert!(bucket.try_take(Priority::Bulk, 7000, start));
+        assert!(!bucket.try_take(Priority::Bulk, 200, start));   // 需为上层保留 30%
+        assert!(bucket.try_take(Priority::Status, 200, start));
+    }
+
+    #[test]
+    fn test_status_keeps_smaller_reserve() {
+        let start = Instant::now();
+        let mut bucket = bucket(start);
+        assert!(bucket.try_take(Priority::Status, 9000, start));
+        assert!(!bucket.try_take(Priority::Status, 500, start));   // 需保留 10%
+        assert!(bucket.try_take(Priority::Alert, 500, start));
+    }
+
+    #[test]
+    fn test_alert_may_overdraw_one_minute() {
+        let start = Instant::now();
+        let mut bucket = bucket(start);
+        assert!(bucket.try_take(Priority::Alert, 15_000, start));
+        assert!(!bucket.try_take(Priority::Alert, 6_000, start));   // 透支不超过一分钟的预算
+        assert!(bucket.try_take(Priority::Alert, 5_000, start));
+    }
+
+    #[test]
+    fn test_refill_repays_overdraft() {
+        let start = Instant::now();
+        let mut bucket = bucket(start);
+        assert!(bucket.try_take(Priority::Alert, 15_000, start));
+        // 一分钟补充 10240 字节，先扣回透支的 4760 字节
+        let later = start + Duration::from_secs(60);
+        assert!(!bucket.try_take(Priority::Bulk, 3000, later));
+        assert!(bucket.try_take(Priority::Status, 3000, later));
+    }
+
+    #[test]
+    fn test_refill_is_capped_at_capacity() {
+        let start = Instant::now();
+        let mut bucket = bucket(start);
+        let later = start + Duration::from_secs(600);
+        assert!(bucket.try_take(Priority::Bulk, 7168, later));
+        assert!(!bucket.try_take(Priority::Bulk, 1, later));
+    }
+
+    #[test]
+    fn test_oversized_message_waits_for_full_bucket() {
+        let start = Instant::now();
+        let mut bucket = bucket(start);
+        assert_eq!(bucket.share(Priority::Bulk), 7168);
+        assert!(bucket.try_take(Priority::Status, 100, start));
+        assert!(!bucket.try_take(Priority::Bulk, 8000, start));
+        let later = start + Duration::from_secs(60);
+        assert!(bucket.try_take(Priority::Bulk, 8000, later));
+        assert!(!bucket.try_take(Priority::Bulk, 100, later));
+    }
+
+    #[test]
+    fn test_drop_report_once_a_minute() {
+        let start = Instant::now();
+        let mut bucket = bucket(start);
+        assert_eq!(bucket.take_drop_report(start), None);
+        assert!(!bucket.try_take(Priority::Bulk, 20_000, start));
+        assert!(!bucket.try_take(Priority::Status, 20_000, start));
+        assert!(!bucket.try_take(Priority::Bulk, 20_000, start));
+        assert_eq!(bucket.take_drop_report(start), Some([0, 1, 2]));
+        assert!(!bucket.try_take(Priority::Alert, 30_000, start));
+        assert_eq!(bucket.take_drop_report(start + Duration::from_secs(30)), None);
+        // 未报告的丢弃数累积到下一次
+        assert_eq!(bucket.take_drop_report(start + Duration::from_secs(60)), Some([1, 0, 0]));
+    }
+}
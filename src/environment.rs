@@ -0,0 +1,206 @@
+use std::fs;
+use std::process::Command;
+
+use serde::Serialize;
+use tracing::info;
+
+use crate::capture::Quirks;
+
+/// `/sys/class/net/<网卡>/type` 中监听模式（radiotap 头）的链路类型
+const ARPHRD_IEEE80211_RADIOTAP: &str = "803";
+
+/// 抓包网卡的驱动与能力
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct AdapterInfo {
+    pub name: String,
+    pub driver: Option<String>,
+    pub phy: Option<String>,
+    /// phy 支持的接口模式中是否有 monitor；读不到 `iw` 输出时为 None
+    pub monitor_supported: Option<bool>,
+    /// 当前是否处于监听模式
+    pub monitor_active: bool,
+    /// 抓到足够样本后观察到的 radiotap 字段情况，见 [`crate::capture::QuirkDetector`]
+    pub radiotap: Option<Quirks>,
+}
+
+impl AdapterInfo {
+    pub fn collect(name: &str) -> Self {
+        let net = format!("/sys/class/net/{}", name);
+        let phy = fs::read_to_string(format!("{}/phy80211/name", net)).ok().map(|p| p.trim().to_string());
+        let monitor_supported = phy.as_deref()
+            .and_then(|phy| Command::new("iw").args(["phy", phy, "info"]).output().ok())
+            .filter(|output| output.status.success())
+            .map(|output| interface_modes(&String::from_utf8_lossy(&output.stdout)).iter().any(|m| m == "monitor"));
+        Self {
+            name: name.to_string(),
+            driver: fs::read_link(format!("{}/device/driver", net)).ok()
+                .and_then(|link| link.file_name().map(|n| n.to_string_lossy().into_owned())),
+            phy,
+            monitor_supported,
+            monitor_active: fs::read_to_string(format!("{}/type", net)).is_ok_and(|t| t.trim() == ARPHRD_IEEE80211_RADIOTAP),
+            radiotap: None,
+        }
+    }
+}
+
+/// 时钟来源与同步状态
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ClockReport {
+    pub mode: &'static str,
+    pub gpsd: Option<String>,
+    /// systemd-timesyncd/chrony 报告的 NTP 同步状态，无法查询时为 None
+    pub ntp_synchronized: Option<bool>,
+}
+
+impl ClockReport {
+    pub fn collect(mode: &'static str, gpsd: Option<String>) -> Self {
+        let ntp_synchronized = Command::new("timedatectl")
+            .args(["show", "--property=NTPSynchronized", "--value"])
+            .output().ok()
+            .filter(|output| output.status.success())
+            .and_then(|output| match String::from_utf8_lossy(&output.stdout).trim() {
+                "yes" => Some(true),
+                "no" => Some(false),
+                _ => None,
+            });
+        Self { mode, gpsd, ntp_synchronized }
+    }
+}
+
+/// 启动时的运行环境与能力报告，写入日志并在控制接口 `/status` 中返回，远程排查时不必登录传感器
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct EnvironmentReport {
+    pub version: &'static str,
+    pub os: Option<String>,
+    pub kernel: Option<String>,
+    pub arch: &'static str,
+    pub adapters: Vec<AdapterInfo>,
+    /// 编译时启用的可选特性
+    pub features: Vec<&'static str>,
+    /// 本次运行启用的输出
+    pub sinks: Vec<&'static str>,
+    pub clock: ClockReport,
+}
+
+impl EnvironmentReport {
+    pub fn collect(adapters: &[String], sinks: Vec<&'static str>, clock: ClockReport) -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION"),
+            os: fs::read_to_string("/etc/os-release").ok().as_deref().and_then(pretty_name),
+            kernel: fs::read_to_string("/proc/sys/kernel/osrelease").ok().map(|k| k.trim().to_string()),
+            arch: std::env::consts::ARCH,
+            adapters: adapters.iter().map(|name| AdapterInfo::collect(name)).collect(),
+            features: compiled_features(),
+            sinks,
+            clock,
+        }
+    }
+
+    /// 一行摘要加完整的 JSON
+    pub fn log(&self) {
+        let adapters: Vec<String> = self.adapters.iter().map(|a| {
+            let monitor = match (a.monitor_active, a.monitor_supported) {
+                (true, _) => "monitor",
+                (false, Some(true)) => "monitor capable",
+                (false, Some(false)) => "no monitor support",
+                (false, None) => "monitor unknown",
+            };
+            format!("{} ({}, {})", a.name, a.driver.as_deref().unwrap_or("unknown driver"), monitor)
+        }).collect();
+        info!("environment: {} / kernel {} / {}, adapters: {}, sinks: {}, clock: {}",
+            self.os.as_deref().unwrap_or("unknown os"), self.kernel.as_deref().unwrap_or("?"), self.arch,
+            adapters.join(", "), self.sinks.join(","), self.clock.mode);
+        info!("environment report: {}", serde_json::to_string(self).unwrap_or_default());
+    }
+
+    /// 记录抓包线程观察到的 radiotap 字段情况
+    pub fn set_radiotap(&mut self, interface: &str, quirks: Quirks) {
+        if let Some(adapter) = self.adapters.iter_mut().find(|a| a.name == interface) {
+            adapter.radiotap = Some(quirks);
+        }
+    }
+}
+
+fn compiled_features() -> Vec<&'static str> {
+    [
+        ("database", cfg!(feature = "database")),
+        ("dashboard", cfg!(feature = "dashboard")),
+        ("mesh", cfg!(feature = "mesh")),
+        ("mqtt", cfg!(feature = "mqtt")),
+        ("libpcap", cfg!(feature = "libpcap")),
+        ("bluetooth", cfg!(feature = "bluetooth")),
+        ("monitor", cfg!(feature = "monitor")),
+        ("builtin-parser", cfg!(feature = "builtin-parser")),
+        ("tui", cfg!(feature = "tui")),
+    ].into_iter().filter(|(_, enabled)| *enabled).map(|(name, _)| name).collect()
+}
+
+/// `/etc/os-release` 中的 PRETTY_NAME
+fn pretty_name(text: &str) -> Option<String> {
+    text.lines()
+        .find_map(|line| line.strip_prefix("PRETTY_NAME="))
+        .map(|value| value.trim().trim_matches('"').to_string())
+}
+
+/// `iw phy <phy> info` 中 "Supported interface modes" 列出的模式
+fn interface_modes(text: &str) -> Vec<String> {
+    text.lines()
+        .skip_while(|line| !line.trim().starts_with("Supported interface modes"))
+        .skip(1)
+        .map_while(|line| line.trim().strip_prefix("* "))
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_os_pretty_name() {
+        let os_release = "NAME=\"Debian GNU/Linux\"\nPRETTY_NAME=\"Debian GNU/Linux 12 (bookworm)\"\nID=debian\n";
+        assert_eq!(pretty_name(os_release).as_deref(), Some("Debian GNU/Linux 12 (bookworm)"));
+        assert_eq!(pretty_name("PRETTY_NAME=OpenWrt 23.05\n").as_deref(), Some("OpenWrt 23.05"));
+        assert_eq!(pretty_name("ID=openwrt\n"), None);
+    }
+
+    #[test]
+    fn test_iw_interface_modes() {
+        let iw = "Wiphy phy1\n\tmax # scan SSIDs: 4\n\tSupported interface modes:\n\t\t * IBSS\n\t\t * managed\n\t\t * monitor\n\
+                  \tBand 1:\n\t\tCapabilities: 0x1862\n";
+        assert_eq!(interface_modes(iw), ["IBSS", "managed", "monitor"]);
+    }
+
+    #[test]
+    fn test_iw_without_interface_modes() {
+        assert!(interface_modes("Wiphy phy0\n").is_empty());
+        assert!(interface_modes("Wiphy phy0\n\tSupported interface modes:\n\tBand 1:\n").is_empty());
+    }
+
+    #[test]
+    fn test_missing_adapter() {
+        let adapter = AdapterInfo::collect("nosuchwlan9");
+        assert_eq!(adapter, AdapterInfo { name: "nosuchwlan9".into(), ..Default::default() });
+    }
+
+    #[test]
+    fn test_set_radiotap() {
+        let mut report = EnvironmentReport {
+            adapters: vec![AdapterInfo { name: "wlan1".into(), ..Default::default() }],
+            ..Default::default()
+        };
+        let quirks = Quirks { missing_signal: false, missing_channel: true, fcs_at_end: false };
+        report.set_radiotap("wlan1", quirks);
+        report.set_radiotap("wlan9", quirks);   // 未知网卡被忽略
+        assert_eq!(report.adapters.len(), 1);
+        assert_eq!(report.adapters[0].radiotap, Some(quirks));
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["adapters"][0]["radiotap"]["missing_channel"], true);
+    }
+
+    #[test]
+    fn test_compiled_features() {
+        assert_eq!(compiled_features().contains(&"mqtt"), cfg!(feature = "mqtt"));
+        assert_eq!(compiled_features().contains(&"tui"), cfg!(feature = "tui"));
+    }
+}
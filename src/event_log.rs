@@ -0,0 +1,275 @@
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::canonical;
+use crate::clock;
+use crate::playback::{Pace, Pacer, PlaybackControl};
+use crate::upload_data::UploadData;
+
+/// 解码后的事件，即录制文件中的一条记录
+#[derive(Clone, Serialize, Deserialize)]
+pub struct DecodedEvent {
+    pub received_at_ms: i64,   // 接收时间 (Unix 毫秒)
+    pub record: UploadData,
+}
+
+impl DecodedEvent {
+    /// 以当前时间创建事件，并在记录中注明时间来源
+    pub fn now(mut record: UploadData) -> Self {
+        let (received_at_ms, time_source) = clock::now_ms();
+        record.time_source = time_source;
+        Self { received_at_ms, record }
+    }
+
+    /// 尚无记录 ID 时按传感器、接收时间、计数器和 UAS ID 生成
+    pub fn assign_id(&mut self, sensor: &str) {
+        if self.record.record_id.is_empty() {
+            let uas_id = if self.record.rid.is_empty() { &self.record.track_id } else { &self.record.rid };
+            self.record.record_id = canonical::record_id(
+                sensor, self.received_at_ms, self.record.message_counter, uas_id);
+        }
+    }
+}
+
+/// 事件录制器，格式为 [4 字节大端长度][CBOR 数据] 的序列
+pub struct EventWriter {
+    out: BufWriter<File>,
+}
+
+impl EventWriter {
+    pub fn create<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Ok(Self { out: BufWriter::new(File::create(path)?) })
+    }
+
+    pub fn write(&mut self, event: &DecodedEvent) -> io::Result<()> {
+        let mut buf = Vec::new();
+        ciborium::into_writer(event, &mut buf)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+        self.out.write_all(&(buf.len() as u32).to_be_bytes())?;
+        self.out.write_all(&buf)?;
+        self.out.flush()
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+/// 顺序读取录制文件中的事件
+pub struct EventReader<R> {
+    input: R,
+}
+
+impl EventReader<BufReader<File>> {
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Ok(Self::new(BufReader::new(File::open(path)?)))
+    }
+}
+
+impl<R: Read> EventReader<R> {
+    pub fn new(input: R) -> Self {
+        Self { input }
+    }
+
+    /// 文件在事件边界处结束时返回 `None`；最后一个事件不完整 (如录制中断) 时返回 `UnexpectedEof` 错误
+    fn read_event(&mut self) -> io::Result<Option<DecodedEvent>> {
+        let mut len = [0u8; 4];
+        let mut filled = 0;
+        while filled < len.len() {
+            match self.input.read(&mut len[filled..]) {
+                Ok(0) if filled == 0 => return Ok(None),
+                Ok(0) => return Err(truncated()),
+                Ok(n) => filled += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        let mut buf = vec![0u8; u32::from_be_bytes(len) as usize];
+        self.input.read_exact(&mut buf).map_err(|e| match e.kind() {
+            io::ErrorKind::UnexpectedEof => truncated(),
+            _ => e,
+        })?;
+        ciborium::from_reader(buf.as_slice())
+            .map(Some)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))
+    }
+}
+
+fn truncated() -> io::Error {
+    io::Error::new(io::ErrorKind::UnexpectedEof, "录制文件末尾的事件不完整")
+}
+
+impl<R: Read> Iterator for EventReader<R> {
+    type Item = io::Result<DecodedEvent>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_event().transpose()
+    }
+}
+
+/// 回放录制文件
+///
+/// 按 `control` 的倍速/暂停/跳转状态放行事件。向前跳转时跳过中间事件，
+/// 向后跳转时从文件头重新读取。
+///
+/// # 参数
+/// - `handler`: 每个事件的处理函数
+pub fn replay<P, F>(path: P, control: &PlaybackControl, mut handler: F) -> io::Result<usize>
+where
+    P: AsRef<Path>,
+    F: FnMut(DecodedEvent),
+{
+    let path = path.as_ref();
+    let mut reader = EventReader::open(path)?;
+    let mut pacer = Pacer::new(control);
+    let mut count = 0;
+    while let Some(event) = reader.next() {
+        let event = event?;
+        match pacer.admit(event.received_at_ms) {
+            Pace::Emit => {}
+            Pace::Skip => continue,
+            Pace::Rewind => {
+                reader = EventReader::open(path)?;
+                continue;
+            }
+        }
+        handler(event);
+        count += 1;
+    }
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(received_at_ms: i64) -> DecodedEvent {
+        DecodedEvent { received_at_ms, record: UploadData { rid: "A".into(), ..Default::default() } }
+    }
+
+    /// 按录制文件格式编码，不经过文件
+    fn encode(events: &[DecodedEvent]) -> Vec<u8> {
+        let mut buf = Vec::new();
+        for event in events {
+            let mut cbor = Vec::new();
+            ciborium::into_writer(event, &mut cbor).unwrap();
+            buf.extend_from_slice(&(cbor.len() as u32).to_be_bytes());
+            buf.extend_from_slice(&cbor);
+        }
+        buf
+    }
+
+    #[test]
+    fn test_round_trip_preserves_records() {
+        let record = UploadData {
+            rid: "1581F5FKD229400".into(),
+            latitude: 312_000_000,
+            longitude: 1_214_000_000,
+            rssi: Some(-61.5),
+            operator_id: Some("CHN-OP-1".into()),
+            geofence_zones: vec!["plant".into()],
+            ua_type: Some(2),
+            ..Default::default()
+        };
+        let events = [DecodedEvent { received_at_ms: 1_700_000_000_123, record }, event(1_700_000_001_000)];
+        let buf = encode(&events);
+
+        let read: Vec<DecodedEvent> = EventReader::new(buf.as_slice()).collect::<io::Result<_>>().unwrap();
+        assert_eq!(read.len(), 2);
+        for (read, written) in read.iter().zip(&events) {
+            assert_eq!(read.received_at_ms, written.received_at_ms);
+            assert_eq!(serde_json::to_value(&read.record).unwrap(), serde_json::to_value(&written.record).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_writer_uses_big_endian_length_prefix() {
+        let path = std::env::temp_dir().join("wifi-capture-event-layout-test.bin");
+        let mut writer = EventWriter::create(&path).unwrap();
+        writer.write(&event(1_000)).unwrap();
+        writer.write(&event(2_000)).unwrap();
+        drop(writer);
+
+        let bytes = std::fs::read(&path).unwrap();
+        let len = u32::from_be_bytes(bytes[..4].try_into().unwrap()) as usize;
+        let first: DecodedEvent = ciborium::from_reader(&bytes[4..4 + len]).unwrap();
+        assert_eq!(first.received_at_ms, 1_000);
+        let second_len = u32::from_be_bytes(bytes[4 + len..8 + len].try_into().unwrap()) as usize;
+        assert_eq!(bytes.len(), 8 + len + second_len);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_empty_file_has_no_events() {
+        assert!(EventReader::new(&[][..]).next().is_none());
+    }
+
+    #[test]
+    fn test_truncated_body_is_an_error() {
+        let mut buf = encode(&[event(1_000), event(2_000)]);
+        buf.truncate(buf.len() - 3);
+
+        let mut reader = EventReader::new(buf.as_slice());
+        assert_eq!(reader.next().unwrap().unwrap().received_at_ms, 1_000);
+        assert_eq!(reader.next().unwrap().err().unwrap().kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn test_truncated_length_prefix_is_an_error() {
+        let mut file = encode(&[event(1_000)]);
+        file.extend_from_slice(&[0, 0]);   // 下一个事件的长度只写了一半
+
+        let mut reader = EventReader::new(file.as_slice());
+        assert!(reader.next().unwrap().is_ok());
+        assert_eq!(reader.next().unwrap().err().unwrap().kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn test_corrupt_body_is_invalid_data() {
+        let file = [0, 0, 0, 2, 0xff, 0xff];
+        let error = EventReader::new(&file[..]).next().unwrap().err().unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_replay_emits_events_before_truncation() {
+        let path = std::env::temp_dir().join("wifi-capture-replay-truncated-test.bin");
+        let mut writer = EventWriter::create(&path).unwrap();
+        writer.write(&event(1_000)).unwrap();
+        writer.write(&event(2_000)).unwrap();
+        drop(writer);
+        let len = std::fs::metadata(&path).unwrap().len();
+        std::fs::OpenOptions::new().write(true).open(&path).unwrap().set_len(len - 1).unwrap();
+
+        let mut seen = Vec::new();
+        let result = replay(&path, &PlaybackControl::new(0.0), |event| seen.push(event.received_at_ms));
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+        assert_eq!(seen, [1_000]);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_replay_rereads_file_on_backward_seek() {
+        let path = std::env::temp_dir().join("wifi-capture-replay-seek-test.bin");
+        let mut writer = EventWriter::create(&path).unwrap();
+        for at in [1_000, 2_000, 3_000, 4_000] {
+            writer.write(&event(at)).unwrap();
+        }
+        drop(writer);
+
+        let control = PlaybackControl::new(0.0);
+        let mut seen = Vec::new();
+        let count = replay(&path, &control, |event| {
+            if event.received_at_ms == 3_000 && !seen.contains(&3_000) {
+                control.seek(2_000);
+            }
+            seen.push(event.received_at_ms);
+        }).unwrap();
+        assert_eq!(seen, [1_000, 2_000, 3_000, 2_000, 3_000, 4_000]);
+        assert_eq!(count, 6);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,115 @@
+use std::fmt::Display;
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+use chrono::{Local, NaiveDate};
+use tracing::warn;
+use tracing_appender::rolling::{self, RollingFileAppender};
+
+use crate::remote_id::hex_dump;
+use crate::time_format;
+
+/// 解码失败样本输出
+///
+/// 将解析失败的帧/IE 以十六进制写入独立的按天滚动文件，
+/// 按分钟限速，并限制每个文件的最大字节数，便于收集真实样本。
+pub struct FailureSink {
+    writer: RollingFileAppender,
+    max_per_minute: u32,
+    max_bytes_per_file: u64,
+    window_start: Instant,
+    window_count: u32,
+    day: NaiveDate,
+    day_bytes: u64,
+    capped: bool,
+}
+
+impl FailureSink {
+    pub const DEFAULT_MAX_PER_MINUTE: u32 = 60;
+    pub const DEFAULT_MAX_BYTES_PER_FILE: u64 = 10 * 1024 * 1024;
+
+    pub fn new(directory: &str, max_per_minute: u32, max_bytes_per_file: u64) -> Self {
+        Self {
+            writer: rolling::daily(directory, "decode-failures.log"),
+            max_per_minute,
+            max_bytes_per_file,
+            window_start: Instant::now(),
+            window_count: 0,
+            day: Local::now().date_naive(),
+            day_bytes: 0,
+            capped: false,
+        }
+    }
+
+    /// 记录一个失败样本，超过限速或容量时丢弃
+    pub fn record(&mut self, kind: &str, error: &dyn Display, bytes: &[u8]) {
+        if self.window_start.elapsed() >= Duration::from_secs(60) {
+            self.window_start = Instant::now();
+            self.window_count = 0;
+        }
+        let today = Local::now().date_naive();
+        if today != self.day {
+            self.day = today;
+            self.day_bytes = 0;
+            self.capped = false;
+        }
+        if self.window_count >= self.max_per_minute || self.capped {
+            return;
+        }
+        let line = format!("{} {} len={} error=\"{}\"\n{}\n",
+            time_format::now(), kind, bytes.len(), error, hex_dump(bytes));
+        if self.day_bytes + line.len() as u64 > self.max_bytes_per_file {
+            warn!("decode failure dump reached {} bytes, suppressed until rotation", self.day_bytes);
+            self.capped = true;
+            return;
+        }
+        if let Err(e) = self.writer.write_all(line.as_bytes()) {
+            warn!("写入解码失败样本失败: {}", e);
+            return;
+        }
+        self.window_count += 1;
+        self.day_bytes += line.len() as u64;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 在独立的临时目录中写入样本，返回写出的全部内容
+    fn dump(name: &str, max_per_minute: u32, max_bytes: u64, samples: &[&[u8]]) -> String {
+        let dir = std::env::temp_dir().join(format!("wifi-capture-failure-sink-{}-test", name));
+        let _ = std::fs::remove_dir_all(&dir);
+        let mut sink = FailureSink::new(dir.to_str().unwrap(), max_per_minute, max_bytes);
+        for bytes in samples {
+            sink.record("ie", &"未知消息类型: 0x0F", bytes);
+        }
+        drop(sink);
+        let content = std::fs::read_dir(&dir).unwrap()
+            .map(|entry| std::fs::read_to_string(entry.unwrap().path()).unwrap())
+            .collect();
+        std::fs::remove_dir_all(&dir).unwrap();
+        content
+    }
+
+    #[test]
+    fn test_sample_includes_kind_error_and_hex() {
+        let content = dump("format", 10, 1024, &[&[0xfa, 0x0b, 0xbc]]);
+        assert!(content.contains("ie len=3 error=\"未知消息类型: 0x0F\""));
+        assert!(content.contains(&hex_dump(&[0xfa, 0x0b, 0xbc])));
+    }
+
+    #[test]
+    fn test_samples_are_rate_limited_per_minute() {
+        let content = dump("rate", 2, 1024 * 1024, &[&[1], &[2], &[3]]);
+        assert_eq!(content.matches("len=1").count(), 2);
+    }
+
+    #[test]
+    fn test_file_size_is_capped() {
+        let one = dump("single", 10, 1024 * 1024, &[&[0; 16]]).len() as u64;
+        // 容量只够一条样本时后续样本全部丢弃
+        let content = dump("cap", 10, one + one / 2, &[&[0; 16], &[0; 16], &[0; 4]]);
+        assert_eq!(content.matches("len=").count(), 1);
+    }
+}
@@ -0,0 +1,137 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// 已知无人机的标注信息，附加到记录上供界面显示
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Annotation {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,   // 显示颜色，如 #1f77b4
+}
+
+impl Annotation {
+    /// 显示名称，如 `Inspection-Drone-3 (Facilities)`
+    pub fn label(&self) -> String {
+        match &self.owner {
+            Some(owner) => format!("{} ({})", self.name, owner),
+            None => self.name.clone(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct FleetRow {
+    uas_id: String,
+    #[serde(flatten)]
+    annotation: Annotation,
+}
+
+/// 机队标注表：UAS ID 到名称、所属单位和颜色的映射
+///
+/// 从带表头的 CSV 加载，空白单元格视为未填写：
+///
+/// ```csv
+/// uas_id,name,owner,color
+/// 1581F5FKD229400A,Inspection-Drone-3,Facilities,#2ca02c
+/// ```
+#[derive(Debug, Default)]
+pub struct Fleet {
+    annotations: HashMap<String, Annotation>,
+}
+
+impl Fleet {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let reader = csv::ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .from_path(path)
+            .map_err(|e| e.to_string())?;
+        Self::from_reader(reader)
+    }
+
+    fn from_reader<R: std::io::Read>(mut reader: csv::Reader<R>) -> Result<Self, String> {
+        let mut annotations = HashMap::new();
+        for (line, row) in reader.deserialize::<FleetRow>().enumerate() {
+            let mut row = row.map_err(|e| format!("第 {} 行: {}", line + 2, e))?;
+            row.annotation.owner = row.annotation.owner.filter(|s| !s.is_empty());
+            row.annotation.color = row.annotation.color.filter(|s| !s.is_empty());
+            annotations.insert(row.uas_id, row.annotation);
+        }
+        Ok(Self { annotations })
+    }
+
+    pub fn len(&self) -> usize {
+        self.annotations.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.annotations.is_empty()
+    }
+
+    pub fn lookup(&self, uas_id: &str) -> Option<&Annotation> {
+        self.annotations.get(uas_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(text: &str) -> Result<Fleet, String> {
+        Fleet::from_reader(csv::ReaderBuilder::new().trim(csv::Trim::All).from_reader(text.as_bytes()))
+    }
+
+    #[test]
+    fn test_full_row() {
+        let fleet = parse("uas_id,name,owner,color\n1581F5FKD229400A, Inspection-Drone-3 ,Facilities,#2ca02c\n").unwrap();
+        assert_eq!(fleet.lookup("1581F5FKD229400A"), Some(&Annotation {
+            name: "Inspection-Drone-3".into(),
+            owner: Some("Facilities".into()),
+            color: Some("#2ca02c".into()),
+        }));
+    }
+
+    #[test]
+    fn test_blank_cells_are_unset() {
+        let fleet = parse("uas_id,name,owner,color\nABC123,Survey-1,,\n").unwrap();
+        let survey = fleet.lookup("ABC123").unwrap();
+        assert_eq!((survey.owner.as_ref(), survey.color.as_ref()), (None, None));
+    }
+
+    #[test]
+    fn test_optional_columns() {
+        let fleet = parse("uas_id,name\nABC123,Survey-1\n").unwrap();
+        assert_eq!(fleet.lookup("ABC123").unwrap().name, "Survey-1");
+    }
+
+    #[test]
+    fn test_unknown_uas_id() {
+        let fleet = parse("uas_id,name\nABC123,Survey-1\n").unwrap();
+        assert_eq!(fleet.len(), 1);
+        assert!(fleet.lookup("unknown").is_none());
+        assert!(parse("uas_id,name\n").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_invalid_row_reports_line() {
+        let error = parse("uas_id,name\nABC123,Survey-1\nDEF456\n").err().unwrap();
+        assert!(error.starts_with("第 3 行: "), "{}", error);
+        assert!(parse("uas_id,owner\nABC123,Facilities\n").is_err());   // 缺少 name 列
+    }
+
+    #[test]
+    fn test_label() {
+        let annotation = Annotation { name: "Inspection-Drone-3".into(), owner: Some("Facilities".into()), color: None };
+        assert_eq!(annotation.label(), "Inspection-Drone-3 (Facilities)");
+        assert_eq!(Annotation { owner: None, ..annotation }.label(), "Inspection-Drone-3");
+    }
+
+    #[test]
+    fn test_load_missing_file() {
+        assert!(Fleet::load(std::env::temp_dir().join("wifi-capture-no-such-fleet.csv")).is_err());
+    }
+}
@@ -0,0 +1,534 @@
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use chrono::SecondsFormat;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tracing::{error, info};
+
+use crate::event_log::DecodedEvent;
+use crate::geo::degrees;
+use crate::position;
+use crate::time_format;
+use crate::units;
+
+/// 航迹导出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlightFormat {
+    GeoJson,
+    Czml,
+    Kml,
+}
+
+impl FlightFormat {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "geojson" => Some(Self::GeoJson),
+            "czml" => Some(Self::Czml),
+            "kml" => Some(Self::Kml),
+            _ => None,
+        }
+    }
+}
+
+/// 多架无人机航迹的导出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TrackFormat {
+    /// 每段航迹一个 LineString 要素，QGIS 等直接打开
+    #[default]
+    GeoJson,
+    /// 每段航迹一个 Placemark，Google Earth 直接打开
+    Kml,
+}
+
+impl TrackFormat {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "geojson" => Some(Self::GeoJson),
+            "kml" => Some(Self::Kml),
+            _ => None,
+        }
+    }
+}
+
+/// 带时间戳的航迹点
+#[derive(Debug, Clone, Copy)]
+pub struct FlightPoint {
+    pub time_ms: i64,
+    pub lat: f64,
+    pub lon: f64,
+    pub altitude_m: f64,
+}
+
+/// 单架无人机的一段航迹
+pub struct Flight {
+    pub id: String,
+    pub points: Vec<FlightPoint>,
+}
+
+impl Flight {
+    /// 从事件流中选出 UAS ID 或轨迹 ID 等于 `id` 的记录，跳过 (0, 0) 无效位置
+    pub fn collect<I: IntoIterator<Item = DecodedEvent>>(id: &str, events: I) -> Self {
+        let mut points: Vec<FlightPoint> = events.into_iter()
+            .filter(|e| e.record.rid == id || e.record.track_id == id)
+            .filter(|e| e.record.latitude != 0 || e.record.longitude != 0)
+            .map(|e| FlightPoint {
+                time_ms: e.received_at_ms,
+                lat: degrees(e.record.latitude),
+                lon: degrees(e.record.longitude),
+                altitude_m: e.record.geometric_altitude as f64,
+            })
+            .collect();
+        points.sort_by_key(|p| p.time_ms);
+        Self { id: id.to_string(), points }
+    }
+
+    /// 按格式输出的文件内容
+    pub fn render(&self, format: FlightFormat) -> String {
+        match format {
+            FlightFormat::GeoJson => serde_json::to_string_pretty(&self.to_geojson()).unwrap(),
+            FlightFormat::Czml => serde_json::to_string_pretty(&self.to_czml()).unwrap(),
+            FlightFormat::Kml => tracks_kml(std::slice::from_ref(self)),
+        }
+    }
+
+    /// GeoJSON: 一条带 `coordTimes` 的 LineString，外加每个点一个带 `time` 的 Point
+    ///
+    /// 几何坐标按 GeoJSON 规范始终为度/米；点属性中的 `altitude` 使用配置的 JSON 高度单位。
+    pub fn to_geojson(&self) -> Value {
+        let units = units::json();
+        let coordinates: Vec<Value> = self.points.iter()
+            .map(|p| json!([p.lon, p.lat, p.altitude_m]))
+            .collect();
+        let times: Vec<String> = self.points.iter().map(|p| iso(p.time_ms)).collect();
+        let mut features = vec![json!({
+            "type": "Feature",
+            "geometry": { "type": "LineString", "coordinates": coordinates },
+            "properties": { "id": self.id, "coordTimes": times },
+        })];
+        features.extend(self.points.iter().map(|p| json!({
+            "type": "Feature",
+            "geometry": { "type": "Point", "coordinates": [p.lon, p.lat, p.altitude_m] },
+            "properties": {
+                "id": self.id,
+                "time": iso(p.time_ms),
+                "altitude": units.altitude(p.altitude_m),
+                "altitude_unit": units.altitude_suffix(),
+            },
+        })));
+        json!({ "type": "FeatureCollection", "features": features })
+    }
+
+    /// CZML (Cesium): 以首个点为 epoch 的时间采样位置与航迹线
+    pub fn to_czml(&self) -> Value {
+        let (Some(first), Some(last)) = (self.points.first(), self.points.last()) else {
+            return json!([{ "id": "document", "version": "1.0" }]);
+        };
+        let interval = format!("{}/{}", iso(first.time_ms), iso(last.time_ms));
+        let samples: Vec<f64> = self.points.iter()
+            .flat_map(|p| [(p.time_ms - first.time_ms) as f64 / 1000.0, p.lon, p.lat, p.altitude_m])
+            .collect();
+        json!([
+            {
+                "id": "document",
+                "name": self.id,
+                "version": "1.0",
+                "clock": {
+                    "interval": interval,
+                    "currentTime": iso(first.time_ms),
+                    "multiplier": 1,
+                },
+            },
+            {
+                "id": self.id,
+                "availability": interval,
+                "position": {
+                    "epoch": iso(first.time_ms),
+                    "cartographicDegrees": samples,
+                },
+                "point": { "pixelSize": 10, "color": { "rgba": [255, 80, 0, 255] } },
+                "path": { "width": 2, "leadTime": 0, "trailTime": 600, "resolution": 1 },
+                "label": { "text": self.id, "pixelOffset": { "cartesian2": [0, -20] } },
+            }
+        ])
+    }
+}
+
+/// 相邻定位间隔超过该时长时另起一段航迹，与数据库航段一致
+pub const TRACK_GAP_MS: i64 = 5 * 60 * 1000;
+
+/// 按无人机 (UAS ID，没有时为轨迹 ID) 聚合的航迹，间隔 [`TRACK_GAP_MS`] 未再出现时分段
+#[derive(Default)]
+pub struct TrackCollector {
+    open: HashMap<String, Flight>,
+    closed: Vec<Flight>,
+}
+
+impl TrackCollector {
+    /// 加入一条记录，没有有效位置的记录跳过；记录应大致按时间顺序加入
+    pub fn add(&mut self, event: &DecodedEvent) {
+        let r = &event.record;
+        if !position::has_coordinates(r) {
+            return;
+        }
+        let uas_id = if r.rid.is_empty() { &r.track_id } else { &r.rid };
+        let point = FlightPoint {
+            time_ms: event.received_at_ms,
+            lat: degrees(r.latitude),
+            lon: degrees(r.longitude),
+            altitude_m: r.geometric_altitude as f64,
+        };
+        let flight = self.open.entry(uas_id.clone()).or_insert_with(|| Flight { id: uas_id.clone(), points: Vec::new() });
+        if flight.points.last().is_some_and(|last| (point.time_ms - last.time_ms).abs() > TRACK_GAP_MS) {
+            let points = std::mem::take(&mut flight.points);
+            self.closed.push(Flight { id: uas_id.clone(), points });
+        }
+        flight.points.push(point);
+    }
+
+    /// 丢弃最后定位早于 `before_ms` 的航迹，返回丢弃的段数
+    pub fn prune(&mut self, before_ms: i64) -> usize {
+        let ended = |flight: &Flight| flight.points.last().is_none_or(|p| p.time_ms < before_ms);
+        let count = self.closed.len() + self.open.len();
+        self.closed.retain(|flight| !ended(flight));
+        self.open.retain(|_, flight| !ended(flight));
+        count - self.closed.len() - self.open.len()
+    }
+
+    /// 全部航迹，按 UAS ID 和开始时间排列，各段内的点按时间排列
+    pub fn flights(&self) -> Vec<Flight> {
+        let mut flights: Vec<Flight> = self.closed.iter().chain(self.open.values())
+            .map(|flight| {
+                let mut points = flight.points.clone();
+                points.sort_by_key(|p| p.time_ms);
+                Flight { id: flight.id.clone(), points }
+            })
+            .collect();
+        flights.sort_by(|a, b| a.id.cmp(&b.id).then(a.points[0].time_ms.cmp(&b.points[0].time_ms)));
+        flights
+    }
+}
+
+/// 按格式输出多段航迹
+pub fn render_tracks(flights: &[Flight], format: TrackFormat) -> String {
+    match format {
+        TrackFormat::GeoJson => serde_json::to_string_pretty(&tracks_geojson(flights)).unwrap(),
+        TrackFormat::Kml => tracks_kml(flights),
+    }
+}
+
+/// GeoJSON: 每段航迹一个 LineString 要素，属性带开始/结束时间、点数和各点时间 `coordTimes`
+pub fn tracks_geojson(flights: &[Flight]) -> Value {
+    let features: Vec<Value> = flights.iter()
+        .filter(|flight| !flight.points.is_empty())
+        .map(|flight| {
+            let coordinates: Vec<Value> = flight.points.iter().map(|p| json!([p.lon, p.lat, p.altitude_m])).collect();
+            let times: Vec<String> = flight.points.iter().map(|p| iso(p.time_ms)).collect();
+            json!({
+                "type": "Feature",
+                "geometry": { "type": "LineString", "coordinates": coordinates },
+                "properties": {
+                    "id": flight.id,
+                    "start": times.first(),
+                    "end": times.last(),
+                    "points": flight.points.len(),
+                    "coordTimes": times,
+                },
+            })
+        })
+        .collect();
+    json!({ "type": "FeatureCollection", "features": features })
+}
+
+/// KML: 每段航迹一个带时间范围的 Placemark，高度按海拔绘制
+pub fn tracks_kml(flights: &[Flight]) -> String {
+    let mut kml = String::from(concat!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+        "<kml xmlns=\"http://www.opengis.net/kml/2.2\">\n<Document>\n<name>Remote ID tracks</name>\n",
+        "<Style id=\"track\"><LineStyle><color>ff0050ff</color><width>2</width></LineStyle></Style>\n",
+    ));
+    for flight in flights {
+        let (Some(first), Some(last)) = (flight.points.first(), flight.points.last()) else { continue };
+        let coordinates: Vec<String> = flight.points.iter()
+            .map(|p| format!("{:.7},{:.7},{:.1}", p.lon, p.lat, p.altitude_m))
+            .collect();
+        let _ = write!(
+            kml,
+            "<Placemark>\n<name>{}</name>\n<TimeSpan><begin>{}</begin><end>{}</end></TimeSpan>\n\
+             <styleUrl>#track</styleUrl>\n<LineString><altitudeMode>absolute</altitudeMode>\
+             <coordinates>{}</coordinates></LineString>\n</Placemark>\n",
+            xml_escape(&flight.id), iso(first.time_ms), iso(last.time_ms), coordinates.join(" "),
+        );
+    }
+    kml.push_str("</Document>\n</kml>\n");
+    kml
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// 实时抓包时定期写出航迹文件
+///
+/// ```toml
+/// [track_export]
+/// path = "/var/lib/wifi-capture/tracks.geojson"
+/// format = "geojson"      # geojson / kml
+/// interval_s = 60         # 写出间隔
+/// retain_hours = 24       # 最后定位早于该时长的航迹不再写出
+/// ```
+///
+/// 每次先写临时文件再改名，QGIS 等定时刷新时不会读到写了一半的文件。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TrackExportConfig {
+    pub path: PathBuf,
+    #[serde(default)]
+    pub format: TrackFormat,
+    #[serde(default = "default_interval_s")]
+    pub interval_s: u64,
+    #[serde(default = "default_retain_hours")]
+    pub retain_hours: u64,
+}
+
+fn default_interval_s() -> u64 {
+    60
+}
+
+fn default_retain_hours() -> u64 {
+    24
+}
+
+pub struct TrackExporter {
+    config: TrackExportConfig,
+    tracks: TrackCollector,
+    written_at_ms: Option<i64>,
+}
+
+impl TrackExporter {
+    pub fn new(config: TrackExportConfig) -> Self {
+        Self { config, tracks: TrackCollector::default(), written_at_ms: None }
+    }
+
+    pub fn observe(&mut self, event: &DecodedEvent) {
+        self.tracks.add(event);
+    }
+
+    /// 到达写出间隔时写出航迹文件
+    pub fn poll(&mut self, now_ms: i64) {
+        if self.written_at_ms.is_some_and(|at| now_ms - at < self.config.interval_s as i64 * 1000) {
+            return;
+        }
+        self.written_at_ms = Some(now_ms);
+        self.tracks.prune(now_ms - self.config.retain_hours as i64 * 3600 * 1000);
+        if let Err(e) = self.write() {
+            error!("写出航迹文件 {} 失败: {}", self.config.path.display(), e);
+        }
+    }
+
+    /// 立即写出，退出时调用
+    pub fn write(&self) -> io::Result<()> {
+        let flights = self.tracks.flights();
+        let mut temp = self.config.path.clone().into_os_string();
+        temp.push(".tmp");
+        fs::write(&temp, render_tracks(&flights, self.config.format))?;
+        fs::rename(&temp, &self.config.path)?;
+        info!("{} tracks written to {}", flights.len(), self.config.path.display());
+        Ok(())
+    }
+}
+
+fn iso(ms: i64) -> String {
+    time_format::format_ms(ms, SecondsFormat::Millis)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::upload_data::UploadData;
+
+    fn fix(rid: &str, at: i64, latitude: i32) -> DecodedEvent {
+        DecodedEvent {
+            received_at_ms: at,
+            record: UploadData { rid: rid.into(), latitude, longitude: 1_214_000_000, geometric_altitude: 120, ..Default::default() },
+        }
+    }
+
+    #[test]
+    fn test_flight_format_names() {
+        assert_eq!(FlightFormat::parse("czml"), Some(FlightFormat::Czml));
+        assert_eq!(FlightFormat::parse("kml"), Some(FlightFormat::Kml));
+        assert_eq!(FlightFormat::parse("gpx"), None);
+    }
+
+    #[test]
+    fn test_collect_selects_drone_and_sorts() {
+        let mut by_track = fix("", 500, 312_003_000);
+        by_track.record.track_id = "RID-A".into();
+        let events = vec![
+            fix("RID-A", 2_000, 312_001_000),
+            fix("RID-B", 1_500, 312_000_000),
+            fix("RID-A", 1_000, 312_000_000),
+            fix("RID-A", 1_200, 0),
+            by_track,
+        ];
+        let mut no_fix = fix("RID-A", 3_000, 0);
+        no_fix.record.longitude = 0;
+        let flight = Flight::collect("RID-A", events.into_iter().chain([no_fix]));
+        assert_eq!(flight.points.iter().map(|p| p.time_ms).collect::<Vec<_>>(), [500, 1_000, 1_200, 2_000]);
+        assert_eq!((flight.points[1].lat, flight.points[1].altitude_m), (31.2, 120.0));
+    }
+
+    #[test]
+    fn test_flight_geojson() {
+        let flight = Flight::collect("RID-A", [fix("RID-A", 0, 312_000_000), fix("RID-A", 1_000, 312_001_000)]);
+        let geojson = flight.to_geojson();
+        let features = geojson["features"].as_array().unwrap();
+        assert_eq!(features.len(), 3);
+        assert_eq!(features[0]["geometry"]["type"], "LineString");
+        assert_eq!(features[0]["properties"]["coordTimes"][1], "1970-01-01T00:00:01.000Z");
+        assert_eq!(features[2]["geometry"]["coordinates"], json!([degrees(1_214_000_000), 31.2001, 120.0]));
+        assert_eq!(features[2]["properties"]["time"], "1970-01-01T00:00:01.000Z");
+    }
+
+    #[test]
+    fn test_flight_czml() {
+        let flight = Flight::collect("RID-A", [fix("RID-A", 0, 312_000_000), fix("RID-A", 2_500, 312_001_000)]);
+        let czml = flight.to_czml();
+        assert_eq!(czml[0]["clock"]["interval"], "1970-01-01T00:00:00.000Z/1970-01-01T00:00:02.500Z");
+        assert_eq!(czml[1]["position"]["epoch"], "1970-01-01T00:00:00.000Z");
+        let lon = degrees(1_214_000_000);
+        assert_eq!(czml[1]["position"]["cartographicDegrees"], json!([0.0, lon, 31.2, 120.0, 2.5, lon, 31.2001, 120.0]));
+    }
+
+    #[test]
+    fn test_empty_flight_czml_has_only_document() {
+        let flight = Flight::collect("RID-A", Vec::new());
+        assert_eq!(flight.to_czml(), json!([{ "id": "document", "version": "1.0" }]));
+    }
+
+    fn collector(events: &[DecodedEvent]) -> TrackCollector {
+        let mut tracks = TrackCollector::default();
+        events.iter().for_each(|e| tracks.add(e));
+        tracks
+    }
+
+    fn summary(flights: &[Flight]) -> Vec<(&str, usize)> {
+        flights.iter().map(|f| (f.id.as_str(), f.points.len())).collect()
+    }
+
+    #[test]
+    fn test_track_format_names() {
+        assert_eq!(TrackFormat::parse("geojson"), Some(TrackFormat::GeoJson));
+        assert_eq!(TrackFormat::parse("kml"), Some(TrackFormat::Kml));
+        assert_eq!(TrackFormat::parse("czml"), None);
+    }
+
+    #[test]
+    fn test_tracks_split_on_gap() {
+        let tracks = collector(&[
+            fix("RID-A", 1_000, 312_000_000),
+            fix("RID-A", 2_000, 312_001_000),
+            fix("RID-A", 3_000 + TRACK_GAP_MS, 312_002_000),
+        ]);
+        assert_eq!(summary(&tracks.flights()), [("RID-A", 2), ("RID-A", 1)]);
+    }
+
+    #[test]
+    fn test_tracks_skip_records_without_fix() {
+        let tracks = collector(&[
+            fix("RID-A", 1_000, 312_000_000),
+            DecodedEvent { received_at_ms: 2_500, record: UploadData { rid: "RID-A".into(), ..Default::default() } },
+        ]);
+        assert_eq!(summary(&tracks.flights()), [("RID-A", 1)]);
+    }
+
+    #[test]
+    fn test_tracks_are_ordered_by_id_then_start() {
+        let mut anonymous = fix("", 0, 312_000_000);
+        anonymous.record.track_id = "aa:bb".into();
+        let tracks = collector(&[
+            fix("RID-B", 0, 312_000_000),
+            fix("RID-A", 10_000 + TRACK_GAP_MS, 312_000_000),
+            fix("RID-A", 9_000 + TRACK_GAP_MS * 3, 312_000_000),
+            anonymous,
+        ]);
+        let flights = tracks.flights();
+        assert_eq!(summary(&flights), [("RID-A", 1), ("RID-A", 1), ("RID-B", 1), ("aa:bb", 1)]);
+        assert!(flights[0].points[0].time_ms < flights[1].points[0].time_ms);
+    }
+
+    #[test]
+    fn test_prune_drops_ended_tracks() {
+        let mut tracks = collector(&[
+            fix("RID-A", 1_000, 312_000_000),
+            fix("RID-A", 3_000 + TRACK_GAP_MS, 312_002_000),
+            fix("RID-B", 0, 312_000_000),
+        ]);
+        assert_eq!(tracks.prune(2_000), 2);
+        assert_eq!(summary(&tracks.flights()), [("RID-A", 1)]);
+    }
+
+    #[test]
+    fn test_tracks_geojson() {
+        let tracks = collector(&[fix("RID-A", 1_000, 312_000_000), fix("RID-A", 2_000, 312_001_000)]);
+        let geojson = tracks_geojson(&tracks.flights());
+        let feature = &geojson["features"][0];
+        assert_eq!(feature["geometry"]["type"], "LineString");
+        let second = &feature["geometry"]["coordinates"][1];
+        assert_eq!((second[1].as_f64(), second[2].as_f64()), (Some(31.2001), Some(120.0)));
+        assert_eq!(feature["properties"]["points"], 2);
+        assert_eq!((&feature["properties"]["start"], &feature["properties"]["end"]),
+            (&json!("1970-01-01T00:00:01.000Z"), &json!("1970-01-01T00:00:02.000Z")));
+        // 空航迹不输出要素
+        let empty = Flight { id: "X".into(), points: Vec::new() };
+        assert_eq!(tracks_geojson(&[empty])["features"], json!([]));
+    }
+
+    #[test]
+    fn test_tracks_kml() {
+        let tracks = collector(&[fix("RID-A", 1_000, 312_000_000), fix("RID-A", 2_000, 312_001_000), fix("RID-B", 0, 312_000_000)]);
+        let kml = tracks_kml(&tracks.flights());
+        assert_eq!(kml.matches("<Placemark>").count(), 2);
+        assert!(kml.contains("<coordinates>121.4000000,31.2000000,120.0 121.4000000,31.2001000,120.0</coordinates>"));
+        assert!(kml.contains("<TimeSpan><begin>1970-01-01T00:00:01.000Z</begin><end>1970-01-01T00:00:02.000Z</end></TimeSpan>"));
+        assert!(kml.ends_with("</Document>\n</kml>\n"));
+    }
+
+    #[test]
+    fn test_kml_escapes_names() {
+        let flight = Flight::collect("<A&B>", [fix("<A&B>", 0, 312_000_000)]);
+        assert!(tracks_kml(&[flight]).contains("<name>&lt;A&amp;B&gt;</name>"));
+    }
+
+    #[test]
+    fn test_export_config_defaults() {
+        let config: TrackExportConfig = toml::from_str(r#"path = "tracks.kml""#).unwrap();
+        assert_eq!((config.format, config.interval_s, config.retain_hours), (TrackFormat::GeoJson, 60, 24));
+        assert!(toml::from_str::<TrackExportConfig>("path = \"t\"\nintervl_s = 5").is_err());
+    }
+
+    #[test]
+    fn test_exporter_writes_on_interval() {
+        let dir = std::env::temp_dir().join("wifi-capture-track-export-test");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("tracks.geojson");
+        std::fs::remove_file(&path).ok();
+        let mut exporter = TrackExporter::new(TrackExportConfig { path: path.clone(), format: TrackFormat::GeoJson, interval_s: 60, retain_hours: 24 });
+        exporter.poll(0);
+        assert_eq!(serde_json::from_str::<Value>(&fs::read_to_string(&path).unwrap()).unwrap()["features"], json!([]));
+
+        exporter.observe(&fix("RID-A", 1_000, 312_000_000));
+        exporter.poll(30_000);   // 未到写出间隔
+        assert!(fs::read_to_string(&path).unwrap().contains("\"features\": []"));
+        exporter.poll(60_000);
+        let written: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(written["features"][0]["properties"]["id"], "RID-A");
+        assert!(!dir.join("tracks.geojson.tmp").exists());
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
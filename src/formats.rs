@@ -0,0 +1,338 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::schema::{self, ProtoField};
+use crate::upload_data::UploadData;
+
+/// 输出记录的序列化格式，字段定义统一来自 [`schema`] 中的 JSON Schema 和 proto
+///
+/// 一批记录编码为一个整体：
+/// - `json`：JSON 数组
+/// - `ndjson`：每行一条 JSON
+/// - `cbor`：CBOR 数组
+/// - `protobuf`：依次排列的 `UploadData` 消息，每条前加 varint 长度
+/// - `csv`：表头为 [`schema::record_fields`]，嵌套字段以 JSON 文本填入单元格
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    #[default]
+    Json,
+    Ndjson,
+    Cbor,
+    Protobuf,
+    Csv,
+}
+
+impl Format {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "json" => Some(Self::Json),
+            "ndjson" => Some(Self::Ndjson),
+            "cbor" => Some(Self::Cbor),
+            "protobuf" | "proto" => Some(Self::Protobuf),
+            "csv" => Some(Self::Csv),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Ndjson => "ndjson",
+            Self::Cbor => "cbor",
+            Self::Protobuf => "protobuf",
+            Self::Csv => "csv",
+        }
+    }
+
+    /// HTTP `Content-Type`
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Json => "application/json",
+            Self::Ndjson => "application/x-ndjson",
+            Self::Cbor => "application/cbor",
+            Self::Protobuf => "application/x-protobuf",
+            Self::Csv => "text/csv",
+        }
+    }
+
+    /// 把一批记录编码为本格式
+    pub fn encode<'a, I>(self, records: I) -> Result<Vec<u8>, String>
+    where
+        I: IntoIterator<Item = &'a UploadData>,
+    {
+        let values = records.into_iter()
+            .map(serde_json::to_value)
+            .collect::<Result<Vec<Value>, _>>()
+            .map_err(|e| e.to_string())?;
+        let mut out = Vec::new();
+        match self {
+            Self::Json => serde_json::to_writer(&mut out, &values).map_err(|e| e.to_string())?,
+            Self::Ndjson => {
+                for value in &values {
+                    serde_json::to_writer(&mut out, value).map_err(|e| e.to_string())?;
+                    out.push(b'\n');
+                }
+            }
+            Self::Cbor => ciborium::into_writer(&values, &mut out).map_err(|e| e.to_string())?,
+            Self::Protobuf => {
+                for value in &values {
+                    let mut message = Vec::new();
+                    encode_message("UploadData", value, &mut message)?;
+                    put_varint(&mut out, message.len() as u64);
+                    out.extend_from_slice(&message);
+                }
+            }
+            Self::Csv => encode_csv(&values, &mut out)?,
+        }
+        Ok(out)
+    }
+}
+
+fn encode_csv(values: &[Value], out: &mut Vec<u8>) -> Result<(), String> {
+    let columns = schema::record_fields();
+    let mut writer = csv::Writer::from_writer(out);
+    writer.write_record(&columns).map_err(|e| e.to_string())?;
+    for value in values {
+        let row = columns.iter().map(|column| match &value[*column] {
+            Value::Null => String::new(),
+            Value::String(text) => text.clone(),
+            other => other.to_string(),
+        });
+        writer.write_record(row).map_err(|e| e.to_string())?;
+    }
+    writer.flush().map_err(|e| e.to_string())
+}
+
+fn put_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn put_key(out: &mut Vec<u8>, number: u32, wire_type: u8) {
+    put_varint(out, (u64::from(number) << 3) | u64::from(wire_type));
+}
+
+/// 按 proto 中的字段编号编码一个 JSON 对象；JSON 中有而 proto 中没有的字段报错
+fn encode_message(message: &str, value: &Value, out: &mut Vec<u8>) -> Result<(), String> {
+    let fields = schema::registry().message(message).ok_or_else(|| format!("remote_id.proto 中没有消息 {}", message))?;
+    let Value::Object(map) = value else {
+        return Err(format!("{} 应为对象，实际为 {}", message, value));
+    };
+    for (name, value) in map {
+        let field = fields.iter().find(|field| field.name == *name)
+            .ok_or_else(|| format!("remote_id.proto 的 {} 缺少字段 {}", message, name))?;
+        match value {
+            Value::Null => {}
+            Value::Array(items) if field.repeated => {
+                for item in items {
+                    encode_field(field, item, out)?;
+                }
+            }
+            _ => encode_field(field, value, out)?,
+        }
+    }
+    Ok(())
+}
+
+fn encode_field(field: &ProtoField, value: &Value, out: &mut Vec<u8>) -> Result<(), String> {
+    let number = field.number;
+    let integer = || value.as_i64().or_else(|| value.as_f64().map(|v| v as i64))
+        .ok_or_else(|| format!("字段 {} 应为整数，实际为 {}", field.name, value));
+    match field.kind.as_str() {
+        "bool" => {
+            put_key(out, number, 0);
+            put_varint(out, u64::from(value.as_bool().unwrap_or_default()));
+        }
+        "int32" | "int64" | "uint32" | "uint64" => {
+            put_key(out, number, 0);
+            put_varint(out, integer()? as u64);
+        }
+        "sint32" | "sint64" => {
+            let v = integer()?;
+            put_key(out, number, 0);
+            put_varint(out, ((v << 1) ^ (v >> 63)) as u64);
+        }
+        "float" => {
+            put_key(out, number, 5);
+            out.extend_from_slice(&(value.as_f64().unwrap_or_default() as f32).to_le_bytes());
+        }
+        "double" => {
+            put_key(out, number, 1);
+            out.extend_from_slice(&value.as_f64().unwrap_or_default().to_le_bytes());
+        }
+        "string" => {
+            // 保留值等非字符串取值以 JSON 文本表示
+            let text = match value {
+                Value::String(text) => text.clone(),
+                other => other.to_string(),
+            };
+            put_key(out, number, 2);
+            put_varint(out, text.len() as u64);
+            out.extend_from_slice(text.as_bytes());
+        }
+        kind if schema::registry().is_enum(kind) => {
+            let name = value.as_str().unwrap_or_default();
+            let v = schema::registry().enum_value(kind, name)
+                .ok_or_else(|| format!("枚举 {} 中没有取值 {}", kind, name))?;
+            put_key(out, number, 0);
+            put_varint(out, v as u64);
+        }
+        kind => {
+            let mut nested = Vec::new();
+            encode_message(kind, value, &mut nested)?;
+            put_key(out, number, 2);
+            put_varint(out, nested.len() as u64);
+            out.extend_from_slice(&nested);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::authorization::AuthorizationStatus;
+    use crate::message::accuracy::AccuracyBounds;
+    use crate::upload_data::{AuthPage, Authentication, OperatorPosition};
+
+    /// 尽量填充各类字段（嵌套消息、可选值、重复字段、枚举）的记录
+    fn record() -> UploadData {
+        UploadData {
+            format_version: schema::FORMAT_VERSION,
+            rid: "1581F5FKD229400A".into(),
+            latitude: -1,
+            rssi: Some(-62.0),
+            operator: Some(OperatorPosition { location_type: 1, latitude: 1, longitude: 2, altitude: 3 }),
+            accuracy_bounds: AccuracyBounds::from_codes(10, 4, 3, 5),
+            raw_messages: vec!["00".into(), "01".into()],
+            authorization: Some(AuthorizationStatus::Unknown),
+            auth: Some(Authentication {
+                auth_type: 1, last_page_index: Some(0), length: Some(3), timestamp: Some(4),
+                pages: vec![AuthPage { page: 0, data: "abc".into() }],
+            }),
+            ..Default::default()
+        }
+    }
+
+    /// 按 varint 长度前缀拆分 protobuf 输出
+    fn split_messages(mut bytes: &[u8]) -> Vec<&[u8]> {
+        let mut messages = Vec::new();
+        while !bytes.is_empty() {
+            let (mut len, mut prefix) = (0, 0);
+            loop {
+                let byte = bytes[prefix];
+                len |= ((byte & 0x7f) as usize) << (7 * prefix);
+                prefix += 1;
+                if byte & 0x80 == 0 {
+                    break;
+                }
+            }
+            messages.push(&bytes[prefix..prefix + len]);
+            bytes = &bytes[prefix + len..];
+        }
+        messages
+    }
+
+    #[test]
+    fn test_format_names() {
+        assert_eq!(Format::parse("PROTO"), Some(Format::Protobuf));
+        assert_eq!(Format::parse("xml"), None);
+        for format in [Format::Json, Format::Ndjson, Format::Cbor, Format::Protobuf, Format::Csv] {
+            assert_eq!(Format::parse(format.name()), Some(format));
+        }
+        assert_eq!(Format::Ndjson.content_type(), "application/x-ndjson");
+    }
+
+    #[test]
+    fn test_json_and_ndjson() {
+        let records = [record(), record()];
+        let expected = serde_json::to_value(&records[0]).unwrap();
+        let json: Vec<Value> = serde_json::from_slice(&Format::Json.encode(&records).unwrap()).unwrap();
+        assert_eq!(json, [expected.clone(), expected]);
+        let ndjson = String::from_utf8(Format::Ndjson.encode(&records).unwrap()).unwrap();
+        assert!(ndjson.ends_with('\n'));
+        assert_eq!(ndjson.lines().map(|line| serde_json::from_str(line).unwrap()).collect::<Vec<Value>>(), json);
+    }
+
+    #[test]
+    fn test_cbor_round_trip() {
+        let records = [record()];
+        let cbor: Vec<UploadData> = ciborium::from_reader(Format::Cbor.encode(&records).unwrap().as_slice()).unwrap();
+        assert_eq!(serde_json::to_value(&cbor).unwrap(), serde_json::to_value(&records).unwrap());
+    }
+
+    /// 每个 JSON 字段都必须有 CSV 列：新增字段若未登记到 schema，此测试失败
+    #[test]
+    fn test_csv_has_column_for_every_field() {
+        let csv = String::from_utf8(Format::Csv.encode(&[record(), record()]).unwrap()).unwrap();
+        let mut lines = csv.lines();
+        let header: Vec<&str> = lines.next().unwrap().split(',').collect();
+        for field in serde_json::to_value(record()).unwrap().as_object().unwrap().keys() {
+            assert!(header.contains(&field.as_str()), "CSV 缺少列 {}", field);
+        }
+        assert_eq!(lines.count(), 2);
+    }
+
+    #[test]
+    fn test_csv_cells() {
+        let csv = Format::Csv.encode(&[record()]).unwrap();
+        let mut reader = csv::Reader::from_reader(csv.as_slice());
+        let header = reader.headers().unwrap().clone();
+        let row = reader.records().next().unwrap().unwrap();
+        let cell = |name: &str| row.get(header.iter().position(|h| h == name).unwrap()).unwrap().to_string();
+        assert_eq!(cell("rid"), "1581F5FKD229400A");
+        assert_eq!(cell("latitude"), "-1");
+        assert_eq!(cell("raw_messages"), r#"["00","01"]"#);
+        assert_eq!(serde_json::from_str::<Value>(&cell("operator")).unwrap()["longitude"], 2);
+    }
+
+    /// 每个 JSON 字段都必须能编码为 protobuf：新增字段若未同步到 proto，此测试失败
+    #[test]
+    fn test_protobuf_is_length_prefixed() {
+        let protobuf = Format::Protobuf.encode(&[record(), record()]).unwrap();
+        let messages = split_messages(&protobuf);
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0], messages[1]);
+    }
+
+    #[test]
+    fn test_protobuf_field_encoding() {
+        let protobuf = Format::Protobuf.encode(&[record()]).unwrap();
+        let message = split_messages(&protobuf)[0];
+        // rid = 5 (字符串), latitude = 29 (zigzag -1 = 1), authorization = 47 (unknown = 2)
+        assert!(message.windows(18).any(|w| w == b"\x2a\x101581F5FKD229400A"));
+        assert!(message.windows(3).any(|w| w == [0xe8, 0x01, 0x01]));
+        assert!(message.windows(3).any(|w| w == [0xf8, 0x02, 0x02]));
+    }
+
+    #[test]
+    fn test_varint() {
+        let mut out = Vec::new();
+        put_varint(&mut out, 1);
+        put_varint(&mut out, 300);
+        put_varint(&mut out, u64::MAX);
+        assert_eq!(out[..3], [0x01, 0xac, 0x02]);
+        assert_eq!(out.len(), 3 + 10);
+    }
+
+    #[test]
+    fn test_protobuf_rejects_unknown_field() {
+        let mut out = Vec::new();
+        let error = encode_message("UploadData", &serde_json::json!({ "no_such_field": 1 }), &mut out).unwrap_err();
+        assert!(error.contains("no_such_field"), "{}", error);
+        assert!(encode_message("NoSuchMessage", &serde_json::json!({}), &mut out).is_err());
+    }
+
+    #[test]
+    fn test_empty_batch() {
+        assert_eq!(Format::Json.encode(&[]).unwrap(), b"[]");
+        assert!(Format::Ndjson.encode(&[]).unwrap().is_empty());
+        assert!(Format::Protobuf.encode(&[]).unwrap().is_empty());
+        // CSV 仍输出表头
+        assert_eq!(String::from_utf8(Format::Csv.encode(&[]).unwrap()).unwrap().lines().count(), 1);
+    }
+}
@@ -0,0 +1,197 @@
+use std::collections::VecDeque;
+use std::fs::{self, File};
+use std::io::{self, BufWriter};
+use std::path::PathBuf;
+
+use chrono::DateTime;
+use serde::{Deserialize, Serialize};
+
+use crate::alerts::{Alert, Severity};
+use crate::pcap_writer::PcapWriter;
+
+/// 告警取证：内存中保留最近一段时间的原始帧，高级别告警触发时写出为 pcap
+///
+/// ```toml
+/// [frame_ring]
+/// seconds = 30                # 保留最近多少秒的帧
+/// max_mb = 16                 # 内存上限，超出时丢弃最旧的帧
+/// dir = "alert-pcaps"         # 写出目录
+/// min_severity = "critical"   # 达到该级别的告警才写出
+/// ```
+///
+/// 文件名为 `alert-<规则名>-<告警时间>.pcap`，时间为 UTC。不必一直写完整的存证 pcap
+/// 也能保留告警前后的现场。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FrameRingConfig {
+    #[serde(default = "default_seconds")]
+    pub seconds: u64,
+    #[serde(default = "default_max_mb")]
+    pub max_mb: u64,
+    #[serde(default = "default_dir")]
+    pub dir: PathBuf,
+    #[serde(default = "default_min_severity")]
+    pub min_severity: Severity,
+}
+
+fn default_seconds() -> u64 {
+    30
+}
+
+fn default_max_mb() -> u64 {
+    16
+}
+
+fn default_dir() -> PathBuf {
+    PathBuf::from("alert-pcaps")
+}
+
+fn default_min_severity() -> Severity {
+    Severity::Critical
+}
+
+/// 最近原始帧的环形缓冲
+pub struct FrameRing {
+    config: FrameRingConfig,
+    /// (Unix 微秒, 帧) 由旧到新
+    frames: VecDeque<(i64, Vec<u8>)>,
+    bytes: usize,
+}
+
+impl FrameRing {
+    pub fn new(config: FrameRingConfig) -> Self {
+        Self { config, frames: VecDeque::new(), bytes: 0 }
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// 加入一帧，丢弃超出时长或内存上限的旧帧
+    pub fn push(&mut self, timestamp_us: i64, frame: &[u8]) {
+        self.frames.push_back((timestamp_us, frame.to_vec()));
+        self.bytes += frame.len();
+        let oldest_us = timestamp_us - self.config.seconds as i64 * 1_000_000;
+        let max_bytes = self.config.max_mb as usize * 1024 * 1024;
+        while let Some((at, frame)) = self.frames.front() {
+            if *at >= oldest_us && self.bytes <= max_bytes {
+                break;
+            }
+            self.bytes -= frame.len();
+            self.frames.pop_front();
+        }
+    }
+
+    /// 告警级别达到设置时，把缓冲中的帧写出，返回文件路径；缓冲为空时不写
+    pub fn dump(&self, alert: &Alert) -> io::Result<Option<PathBuf>> {
+        if alert.severity < self.config.min_severity || self.frames.is_empty() {
+            return Ok(None);
+        }
+        fs::create_dir_all(&self.config.dir)?;
+        let time = DateTime::from_timestamp_millis(alert.at_ms).unwrap_or_default();
+        let rule: String = alert.rule.chars().map(|c| if c.is_alphanumeric() || c == '-' { c } else { '_' }).collect();
+        let path = self.config.dir.join(format!("alert-{}-{}.pcap", rule, time.format("%Y%m%dT%H%M%S%.3f")));
+        let mut writer = PcapWriter::new(BufWriter::new(File::create(&path)?))?;
+        for (timestamp_us, frame) in &self.frames {
+            writer.write(*timestamp_us, frame)?;
+        }
+        writer.flush()?;
+        Ok(Some(path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    use crate::mapped_pcap::MappedPcap;
+
+    const START_US: i64 = 1_748_764_800_000_000;
+
+    fn config(dir: &std::path::Path, extra: &str) -> FrameRingConfig {
+        toml::from_str(&format!("dir = {:?}\n{}", dir, extra)).unwrap()
+    }
+
+    fn alert(severity: Severity) -> Alert {
+        Alert {
+            rule: "too high".into(),
+            severity,
+            subject: "RID-1".into(),
+            message: String::new(),
+            at_ms: START_US / 1000 + 14_000,
+            authorization: None,
+            channels: Vec::new(),
+        }
+    }
+
+    /// 每秒一帧，内容为秒数
+    fn ring(config: FrameRingConfig, seconds: i64) -> FrameRing {
+        let mut ring = FrameRing::new(config);
+        for second in 0..seconds {
+            ring.push(START_US + second * 1_000_000, &[second as u8; 50]);
+        }
+        ring
+    }
+
+    #[test]
+    fn test_config_defaults() {
+        let config: FrameRingConfig = toml::from_str("").unwrap();
+        assert_eq!((config.seconds, config.max_mb, config.min_severity), (30, 16, Severity::Critical));
+        assert_eq!(config.dir, PathBuf::from("alert-pcaps"));
+    }
+
+    #[test]
+    fn test_keeps_recent_seconds() {
+        let ring = ring(config(Path::new("unused"), "seconds = 10"), 15);
+        // 最近 10 秒 (含边界) 的帧
+        assert_eq!(ring.len(), 11);
+        assert_eq!(ring.frames.front().unwrap().1[0], 4);
+    }
+
+    #[test]
+    fn test_memory_limit_drops_oldest() {
+        let mut ring = FrameRing::new(config(Path::new("unused"), "max_mb = 1"));
+        for i in 0..5 {
+            ring.push(START_US + i, &[i as u8; 300 * 1024]);
+        }
+        assert_eq!(ring.len(), 3);
+        assert_eq!(ring.frames.front().unwrap().1[0], 2);
+        assert_eq!(ring.bytes, 3 * 300 * 1024);
+    }
+
+    #[test]
+    fn test_low_severity_is_not_dumped() {
+        let dir = std::env::temp_dir().join("wifi-capture-frame-ring-severity-test");
+        let ring = ring(config(&dir, ""), 3);
+        assert_eq!(ring.dump(&alert(Severity::Warning)).unwrap(), None);
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    fn test_empty_ring_is_not_dumped() {
+        let dir = std::env::temp_dir().join("wifi-capture-frame-ring-empty-test");
+        let ring = FrameRing::new(config(&dir, ""));
+        assert!(ring.is_empty());
+        assert_eq!(ring.dump(&alert(Severity::Critical)).unwrap(), None);
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    fn test_dump_writes_pcap() {
+        let dir = std::env::temp_dir().join("wifi-capture-frame-ring-test");
+        fs::remove_dir_all(&dir).ok();
+        let ring = ring(config(&dir, "seconds = 10"), 15);
+        let path = ring.dump(&alert(Severity::Critical)).unwrap().unwrap();
+        assert_eq!(path.file_name().unwrap(), "alert-too_high-20250601T080014.000.pcap");
+        let mapped = MappedPcap::open(&path).unwrap();
+        let frames: Vec<_> = mapped.frames().unwrap().map(|f| f.unwrap()).collect();
+        fs::remove_dir_all(&dir).ok();
+        assert_eq!(frames.len(), 11);
+        assert_eq!(frames[0].data, [4u8; 50]);
+        assert_eq!(frames[0].timestamp_ms, START_US / 1000 + 4_000);
+    }
+}
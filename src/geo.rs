@@ -0,0 +1,110 @@
+/// 地球平均半径 (米)
+pub const EARTH_RADIUS_M: f64 = 6_371_000.0;
+
+/// Remote ID 经纬度编码 (1e-7 度) 转换为度
+pub fn degrees(e7: i32) -> f64 {
+    e7 as f64 * 1e-7
+}
+
+/// 两点间大圆距离 (米)，输入为度
+pub fn distance_m(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
+    let (phi1, phi2) = (lat1.to_radians(), lat2.to_radians());
+    let d_phi = (lat2 - lat1).to_radians();
+    let d_lambda = (lon2 - lon1).to_radians();
+    let a = (d_phi / 2.0).sin().powi(2) + phi1.cos() * phi2.cos() * (d_lambda / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_M * a.sqrt().asin()
+}
+
+/// 解析 `纬度,经度`（度），如 `31.2304,121.4737`
+pub fn parse_lat_lon(s: &str) -> Option<(f64, f64)> {
+    let (lat, lon) = s.split_once(',')?;
+    let (lat, lon): (f64, f64) = (lat.trim().parse().ok()?, lon.trim().parse().ok()?);
+    ((-90.0..=90.0).contains(&lat) && (-180.0..=180.0).contains(&lon)).then_some((lat, lon))
+}
+
+/// 从点 1 指向点 2 的初始方位角 (度, 正北顺时针 0-360)
+pub fn bearing_deg(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
+    let (phi1, phi2) = (lat1.to_radians(), lat2.to_radians());
+    let d_lambda = (lon2 - lon1).to_radians();
+    let y = d_lambda.sin() * phi2.cos();
+    let x = phi1.cos() * phi2.sin() - phi1.sin() * phi2.cos() * d_lambda.cos();
+    y.atan2(x).to_degrees().rem_euclid(360.0)
+}
+
+/// 以某点为原点的局部平面投影（等距圆柱），适用于数公里范围
+#[derive(Debug, Clone, Copy)]
+pub struct LocalProjection {
+    origin_lat: f64,
+    origin_lon: f64,
+}
+
+impl LocalProjection {
+    pub fn new(origin_lat: f64, origin_lon: f64) -> Self {
+        Self { origin_lat, origin_lon }
+    }
+
+    /// 经纬度 -> (东向米, 北向米)
+    pub fn to_local(&self, lat: f64, lon: f64) -> (f64, f64) {
+        let x = (lon - self.origin_lon).to_radians() * EARTH_RADIUS_M * self.origin_lat.to_radians().cos();
+        let y = (lat - self.origin_lat).to_radians() * EARTH_RADIUS_M;
+        (x, y)
+    }
+
+    /// (东向米, 北向米) -> 经纬度
+    pub fn to_geo(&self, x: f64, y: f64) -> (f64, f64) {
+        let lat = self.origin_lat + (y / EARTH_RADIUS_M).to_degrees();
+        let lon = self.origin_lon + (x / (EARTH_RADIUS_M * self.origin_lat.to_radians().cos())).to_degrees();
+        (lat, lon)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_degrees_from_e7() {
+        assert_eq!(degrees(312_304_000), 31.2304);
+        assert_eq!(degrees(-1_800_000_000), -180.0);
+    }
+
+    #[test]
+    fn test_distance_m() {
+        assert_eq!(distance_m(31.0, 121.0, 31.0, 121.0), 0.0);
+        // 经线上 1 度约 111.2 公里
+        assert!((distance_m(0.0, 0.0, 1.0, 0.0) - 111_195.0).abs() < 1.0);
+        let d = distance_m(31.2304, 121.4737, 39.9042, 116.4074);   // 上海 - 北京
+        assert!((d - 1_067_000.0).abs() < 5_000.0, "{}", d);
+        assert_eq!(distance_m(31.2, 121.4, 39.9, 116.4), distance_m(39.9, 116.4, 31.2, 121.4));
+    }
+
+    #[test]
+    fn test_parse_lat_lon() {
+        assert_eq!(parse_lat_lon("31.2304,121.4737"), Some((31.2304, 121.4737)));
+        assert_eq!(parse_lat_lon(" -33.9 , 151.2 "), Some((-33.9, 151.2)));
+        assert_eq!(parse_lat_lon("91,0"), None);
+        assert_eq!(parse_lat_lon("0,181"), None);
+        assert_eq!(parse_lat_lon("31.2304"), None);
+        assert_eq!(parse_lat_lon("north,east"), None);
+    }
+
+    #[test]
+    fn test_bearing_deg() {
+        assert!((bearing_deg(0.0, 0.0, 1.0, 0.0) - 0.0).abs() < 1e-9);
+        assert!((bearing_deg(0.0, 0.0, 0.0, 1.0) - 90.0).abs() < 1e-9);
+        assert!((bearing_deg(0.0, 0.0, -1.0, 0.0) - 180.0).abs() < 1e-9);
+        assert!((bearing_deg(0.0, 0.0, 0.0, -1.0) - 270.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_local_projection_round_trip() {
+        let projection = LocalProjection::new(31.2, 121.4);
+        assert_eq!(projection.to_local(31.2, 121.4), (0.0, 0.0));
+        let (x, y) = projection.to_local(31.21, 121.41);
+        assert!(x > 0.0 && y > 0.0);
+        // 局部平面距离与大圆距离在数公里内一致
+        assert!((x.hypot(y) - distance_m(31.2, 121.4, 31.21, 121.41)).abs() < 1.0);
+        let (lat, lon) = projection.to_geo(x, y);
+        assert!((lat - 31.21).abs() < 1e-9 && (lon - 121.41).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,303 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::geo::{degrees, distance_m, LocalProjection};
+use crate::position;
+use crate::upload_data::UploadData;
+
+/// 区域形状，坐标单位为度
+#[derive(Debug, Clone, PartialEq)]
+pub enum Shape {
+    Circle { lat: f64, lon: f64, radius_m: f64 },
+    Polygon(Vec<(f64, f64)>),   // (纬度, 经度) 顶点，首尾不必重复
+}
+
+/// 电子围栏区域
+#[derive(Debug, Clone, PartialEq)]
+pub struct Zone {
+    pub name: String,
+    pub shape: Shape,
+}
+
+impl Zone {
+    pub fn contains(&self, lat: f64, lon: f64) -> bool {
+        match &self.shape {
+            Shape::Circle { lat: clat, lon: clon, radius_m } => distance_m(*clat, *clon, lat, lon) <= *radius_m,
+            Shape::Polygon(vertices) => point_in_polygon(vertices, lat, lon),
+        }
+    }
+
+    /// 到区域边界的距离 (米)，位于区域内时为 0
+    pub fn distance_m(&self, lat: f64, lon: f64) -> f64 {
+        if self.contains(lat, lon) {
+            return 0.0;
+        }
+        match &self.shape {
+            Shape::Circle { lat: clat, lon: clon, radius_m } => distance_m(*clat, *clon, lat, lon) - radius_m,
+            Shape::Polygon(vertices) => {
+                let projection = LocalProjection::new(lat, lon);
+                let points: Vec<(f64, f64)> = vertices.iter()
+                    .map(|(vlat, vlon)| projection.to_local(*vlat, *vlon))
+                    .collect();
+                (0..points.len())
+                    .map(|i| segment_distance(points[i], points[(i + 1) % points.len()]))
+                    .fold(f64::INFINITY, f64::min)
+            }
+        }
+    }
+}
+
+/// 射线法判断点是否在多边形内
+fn point_in_polygon(vertices: &[(f64, f64)], lat: f64, lon: f64) -> bool {
+    let mut inside = false;
+    let mut j = vertices.len().wrapping_sub(1);
+    for i in 0..vertices.len() {
+        let (yi, xi) = vertices[i];
+        let (yj, xj) = vertices[j];
+        if (yi > lat) != (yj > lat) && lon < (xj - xi) * (lat - yi) / (yj - yi) + xi {
+            inside = !inside;
+        }
+        j = i;
+    }
+    inside
+}
+
+/// 原点到线段 ab 的距离（局部平面坐标）
+fn segment_distance(a: (f64, f64), b: (f64, f64)) -> f64 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let len2 = dx * dx + dy * dy;
+    let t = if len2 == 0.0 { 0.0 } else { (-(a.0 * dx + a.1 * dy) / len2).clamp(0.0, 1.0) };
+    let (px, py) = (a.0 + t * dx, a.1 + t * dy);
+    (px * px + py * py).sqrt()
+}
+
+/// 从 GeoJSON 加载区域
+///
+/// 支持 Polygon（取外环）和带 `radius_m` 属性的 Point（圆形区域），
+/// 区域名称取 `name` 属性，缺省时按序号命名。
+pub fn load_geojson<P: AsRef<Path>>(path: P) -> Result<Vec<Zone>, String> {
+    let text = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let value: Value = serde_json::from_str(&text).map_err(|e| e.to_string())?;
+    let features = match value["type"].as_str() {
+        Some("FeatureCollection") => value["features"].as_array().cloned().unwrap_or_default(),
+        Some("Feature") => vec![value],
+        _ => return Err("GeoJSON 必须是 Feature 或 FeatureCollection".to_string()),
+    };
+    features.iter()
+        .enumerate()
+        .map(|(i, feature)| {
+            let name = feature["properties"]["name"].as_str()
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("zone-{}", i + 1));
+            let geometry = &feature["geometry"];
+            let shape = match geometry["type"].as_str() {
+                Some("Polygon") => {
+                    let ring = geometry["coordinates"][0].as_array()
+                        .ok_or(format!("{}: 缺少多边形坐标", name))?;
+                    let mut vertices: Vec<(f64, f64)> = ring.iter()
+                        .filter_map(|p| Some((p[1].as_f64()?, p[0].as_f64()?)))
+                        .collect();
+                    if vertices.len() > 1 && vertices.first() == vertices.last() {
+                        vertices.pop();
+                    }
+                    if vertices.len() < 3 {
+                        return Err(format!("{}: 多边形顶点不足", name));
+                    }
+                    Shape::Polygon(vertices)
+                }
+                Some("Point") => {
+                    let radius_m = feature["properties"]["radius_m"].as_f64()
+                        .ok_or(format!("{}: 圆形区域缺少 radius_m", name))?;
+                    let c = &geometry["coordinates"];
+                    let (Some(lon), Some(lat)) = (c[0].as_f64(), c[1].as_f64()) else {
+                        return Err(format!("{}: 缺少圆心坐标", name));
+                    };
+                    Shape::Circle { lat, lon, radius_m }
+                }
+                other => return Err(format!("{}: 不支持的几何类型 {:?}", name, other)),
+            };
+            Ok(Zone { name, shape })
+        })
+        .collect()
+}
+
+/// 围栏内或围栏外
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FenceSide {
+    Inside,
+    Outside,
+}
+
+/// 配置文件中的内联区域：`polygon` 顶点或 `center` 加 `radius_m`，坐标为 [纬度, 经度]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ZoneConfig {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub polygon: Vec<[f64; 2]>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub center: Option<[f64; 2]>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub radius_m: Option<f64>,
+}
+
+impl ZoneConfig {
+    fn to_zone(&self) -> Result<Zone, String> {
+        let shape = match (self.polygon.len(), self.center, self.radius_m) {
+            (0, Some([lat, lon]), Some(radius_m)) => Shape::Circle { lat, lon, radius_m },
+            (n, None, None) if n >= 3 => Shape::Polygon(self.polygon.iter().map(|[lat, lon]| (*lat, *lon)).collect()),
+            _ => return Err(format!("{}: 区域应为至少 3 个顶点的 polygon，或 center 加 radius_m", self.name)),
+        };
+        Ok(Zone { name: self.name.clone(), shape })
+    }
+}
+
+/// 电子围栏：只关心设施周界内（或外）的无人机时使用
+///
+/// ```toml
+/// [geofence]
+/// geojson = "perimeter.geojson"   # 可选，格式见 load_geojson，与下面的内联区域合并
+/// drop = "outside"                # inside/outside：不输出围栏内/外的记录，默认都输出
+///
+/// [[geofence.zone]]
+/// name = "plant"
+/// polygon = [[31.000, 121.000], [31.000, 121.010], [31.010, 121.010], [31.010, 121.000]]
+///
+/// [[geofence.zone]]
+/// name = "gate"
+/// center = [31.005, 121.012]
+/// radius_m = 200
+/// ```
+///
+/// 有位置的记录在 `inside_geofence` 和 `geofence_zones` 中标明是否在围栏内及所在区域；
+/// 没有可用位置的记录无法判断，不标注也不丢弃。系统消息中的控制站位置同样标注在
+/// `operator_inside_geofence` 和 `operator_geofence_zones` 中，但不影响是否丢弃。进出围栏的告警见 `alerts::Condition::Geofence`。
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GeofenceConfig {
+    #[serde(default)]
+    pub geojson: Option<PathBuf>,
+    #[serde(default, rename = "zone")]
+    pub zones: Vec<ZoneConfig>,
+    #[serde(default)]
+    pub drop: Option<FenceSide>,
+}
+
+/// 按围栏标注和过滤记录
+#[derive(Debug, Clone, Default)]
+pub struct Geofence {
+    zones: Vec<Zone>,
+    drop: Option<FenceSide>,
+}
+
+impl Geofence {
+    pub fn load(config: &GeofenceConfig) -> Result<Self, String> {
+        let mut zones = match &config.geojson {
+            Some(path) => load_geojson(path).map_err(|e| format!("{}: {}", path.display(), e))?,
+            None => Vec::new(),
+        };
+        for zone in &config.zones {
+            zones.push(zone.to_zone()?);
+        }
+        Ok(Self { zones, drop: config.drop })
+    }
+
+    pub fn len(&self) -> usize {
+        self.zones.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.zones.is_empty()
+    }
+
+    /// 包含该点的区域名称
+    fn zones_containing(&self, lat: f64, lon: f64) -> Vec<String> {
+        self.zones.iter().filter(|z| z.contains(lat, lon)).map(|z| z.name.clone()).collect()
+    }
+
+    /// 标注无人机和控制站所在的区域，返回是否保留该记录；是否丢弃只按无人机位置判断
+    pub fn apply(&self, record: &mut UploadData) -> bool {
+        if self.zones.is_empty() {
+            return true;
+        }
+        if let Some((lat, lon)) = position::operator_coordinates(record) {
+            record.operator_geofence_zones = self.zones_containing(lat, lon);
+            record.operator_inside_geofence = Some(!record.operator_geofence_zones.is_empty());
+        }
+        if !position::has_coordinates(record) {
+            return true;
+        }
+        let (lat, lon) = (degrees(record.latitude), degrees(record.longitude));
+        record.geofence_zones = self.zones_containing(lat, lon);
+        let side = if record.geofence_zones.is_empty() { FenceSide::Outside } else { FenceSide::Inside };
+        record.inside_geofence = Some(side == FenceSide::Inside);
+        self.drop != Some(side)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::upload_data::OperatorPosition;
+
+    #[test]
+    fn test_zone_distance() {
+        let square = Zone {
+            name: "square".to_string(),
+            shape: Shape::Polygon(vec![(31.0, 121.0), (31.0, 121.01), (31.01, 121.01), (31.01, 121.0)]),
+        };
+        assert!(square.contains(31.005, 121.005));
+        assert_eq!(square.distance_m(31.005, 121.005), 0.0);
+        // 正南方约 0.001° 纬度 ≈ 111 米
+        let d = square.distance_m(30.999, 121.005);
+        assert!((d - 111.2).abs() < 1.0, "distance {}", d);
+
+        let circle = Zone { name: "c".to_string(), shape: Shape::Circle { lat: 31.0, lon: 121.0, radius_m: 100.0 } };
+        assert!(circle.contains(31.0005, 121.0));
+        assert!((circle.distance_m(31.002, 121.0) - 122.4).abs() < 1.0);
+    }
+
+    #[test]
+    fn test_geofence_filter() {
+        let config: GeofenceConfig = toml::from_str(r#"
+            drop = "outside"
+
+            [[zone]]
+            name = "plant"
+            polygon = [[31.0, 121.0], [31.0, 121.01], [31.01, 121.01], [31.01, 121.0]]
+
+            [[zone]]
+            name = "gate"
+            center = [31.005, 121.005]
+            radius_m = 200
+        "#).unwrap();
+        let fence = Geofence::load(&config).unwrap();
+        assert_eq!(fence.len(), 2);
+
+        let mut inside = UploadData { latitude: 310_050_000, longitude: 1_210_050_000, ..Default::default() };
+        assert!(fence.apply(&mut inside));
+        assert_eq!(inside.inside_geofence, Some(true));
+        assert_eq!(inside.geofence_zones, ["plant", "gate"]);
+        let mut outside = UploadData { latitude: 310_200_000, longitude: 1_210_050_000, ..Default::default() };
+        assert!(!fence.apply(&mut outside));
+        assert_eq!(outside.inside_geofence, Some(false));
+        // 没有位置时无法判断，保留且不标注
+        let mut no_fix = UploadData::default();
+        assert!(fence.apply(&mut no_fix));
+        assert_eq!(no_fix.inside_geofence, None);
+
+        // 控制站在围栏内，无人机尚无位置
+        let operator = OperatorPosition { location_type: 1, latitude: 310_010_000, longitude: 1_210_010_000, altitude: 0 };
+        let mut pilot = UploadData { operator: Some(operator), ..Default::default() };
+        assert!(fence.apply(&mut pilot));
+        assert_eq!((pilot.operator_inside_geofence, pilot.operator_geofence_zones.as_slice()), (Some(true), ["plant".to_string()].as_slice()));
+        assert_eq!(pilot.inside_geofence, None);
+
+        let bad = GeofenceConfig { zones: vec![ZoneConfig { name: "x".into(), polygon: vec![[31.0, 121.0]], center: None, radius_m: None }], ..Default::default() };
+        assert!(Geofence::load(&bad).is_err());
+    }
+}
@@ -0,0 +1,487 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tracing::{error, info, warn};
+
+use crate::clock;
+use crate::egress::{self, Priority};
+use crate::watchdog::{InterfaceActivity, Watchdog};
+
+/// 心跳与健康检查设置
+///
+/// 传感器无人值守运行时，周围没有无人机也定期报告自身状态：运行时长、最近收到帧的时间、
+/// 网卡状态和各队列积压。心跳以 JSON POST 到 `webhook`，不设置时发往上传地址（带上传令牌）；
+/// 配置了 `mqtt.heartbeat_topic` 时同时发布到 MQTT。
+///
+/// ```toml
+/// [health]
+/// interval_s = 60
+/// webhook = "https://example.com/api/heartbeat"
+/// listen = "127.0.0.1:8081"    # 本地 GET /healthz，健康时返回 200，否则 503
+/// max_idle_s = 300             # 所有网卡超过该时长未收到帧即为不健康
+/// ```
+///
+/// 以 systemd `Type=notify` 服务运行时（不需要 `[health]`）启动完成后通知就绪；
+/// 设置了 `WatchdogSec=` 时只在健康期间喂狗，抓包停滞或解码线程卡住时由 systemd 重启：
+///
+/// ```ini
+/// [Service]
+/// Type=notify
+/// WatchdogSec=600
+/// Restart=on-failure
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HealthConfig {
+    #[serde(default = "default_interval_s")]
+    pub interval_s: u64,
+    #[serde(default)]
+    pub webhook: Option<String>,
+    #[serde(default)]
+    pub listen: Option<String>,
+    #[serde(default = "default_max_idle_s")]
+    pub max_idle_s: u64,
+}
+
+fn default_interval_s() -> u64 {
+    60
+}
+
+fn default_max_idle_s() -> u64 {
+    300
+}
+
+impl Default for HealthConfig {
+    fn default() -> Self {
+        Self { interval_s: default_interval_s(), webhook: None, listen: None, max_idle_s: default_max_idle_s() }
+    }
+}
+
+/// 一个抓包网卡的状态
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct InterfaceHealth {
+    pub name: String,
+    pub up: Option<bool>,       // 网卡是否启用，无法读取时为空
+    pub idle_s: u64,            // 距最近一次收到帧（尚未收到时为开始抓包）的秒数
+    pub restarts: u32,          // 看门狗连续重新初始化的次数
+}
+
+/// 心跳内容，也是 `/healthz` 的响应
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthReport {
+    pub sensor_id: String,
+    pub version: &'static str,
+    pub timestamp_ms: i64,
+    pub uptime_s: u64,
+    pub last_frame_ms: Option<i64>,     // 所有网卡中最近一次收到帧的时间
+    pub interfaces: Vec<InterfaceHealth>,
+    pub queues: BTreeMap<&'static str, usize>,
+    pub healthy: bool,
+    pub problems: Vec<String>,
+}
+
+/// 按各网卡的收帧情况判断是否健康，返回是否健康和发现的问题
+///
+/// 只要有一个网卡在 `max_idle` 内收到过帧就认为节点仍在抓包；启动后的 `max_idle` 内、
+/// 以及没有 Wi-Fi 网卡（只扫描蓝牙）时总是健康。单个网卡停滞或未启用只作为问题报告。
+fn evaluate(interfaces: &[InterfaceHealth], uptime: Duration, max_idle: Duration) -> (bool, Vec<String>) {
+    let mut problems = Vec::new();
+    for interface in interfaces {
+        if interface.up == Some(false) {
+            problems.push(format!("网卡 {} 未启用", interface.name));
+        } else if interface.idle_s >= max_idle.as_secs() {
+            problems.push(format!("网卡 {} 已 {} 秒未收到帧", interface.name, interface.idle_s));
+        }
+    }
+    let capturing = interfaces.is_empty()
+        || uptime < max_idle
+        || interfaces.iter().any(|i| i.up != Some(false) && i.idle_s < max_idle.as_secs());
+    (capturing, problems)
+}
+
+/// 网卡是否处于启用状态 (IFF_UP)，无法读取时返回 None
+fn interface_up(name: &str) -> Option<bool> {
+    let flags = fs::read_to_string(format!("/sys/class/net/{}/flags", name)).ok()?;
+    let flags = u32::from_str_radix(flags.trim().trim_start_matches("0x"), 16).ok()?;
+    Some(flags & 0x1 != 0)
+}
+
+fn interface_health(activity: InterfaceActivity) -> InterfaceHealth {
+    InterfaceHealth {
+        up: interface_up(&activity.interface),
+        name: activity.interface,
+        idle_s: activity.idle.as_secs(),
+        restarts: activity.restarts,
+    }
+}
+
+/// 节点健康监测
+///
+/// 由解码线程定期调用 [`HealthMonitor::poll`]：按间隔生成报告、发送心跳并通知 systemd。
+/// 解码线程卡住时报告不再更新，`/healthz` 随之返回 503，systemd 看门狗也得不到喂狗。
+pub struct HealthMonitor {
+    config: HealthConfig,
+    sensor_id: String,
+    started: Instant,
+    watchdog: Arc<Watchdog>,
+    queues: BTreeMap<&'static str, usize>,
+    latest: Arc<Mutex<Option<(Instant, HealthReport)>>>,
+    heartbeat: Option<mpsc::Sender<HealthReport>>,
+    systemd: Option<SystemdNotifier>,
+    next_report: Instant,
+    next_ping: Instant,
+}
+
+impl HealthMonitor {
+    /// `watchdog` 为抓包线程登记网卡的看门狗，网卡的收帧时间从中读取
+    pub fn new(config: HealthConfig, sensor_id: &str, watchdog: Arc<Watchdog>) -> Self {
+        let now = Instant::now();
+        Self {
+            config,
+            sensor_id: sensor_id.to_string(),
+            started: now,
+            watchdog,
+            queues: BTreeMap::new(),
+            latest: Arc::new(Mutex::new(None)),
+            heartbeat: None,
+            systemd: SystemdNotifier::from_env(),
+            next_report: now,
+            next_ping: now,
+        }
+    }
+
+    /// 此后每份报告都以 JSON POST 到 `url`
+    pub fn send_heartbeats(&mut self, url: String, token: Option<String>) {
+        self.heartbeat = Some(spawn_sender(url, token));
+    }
+
+    /// 更新报告中一个队列的积压数
+    pub fn set_queue(&mut self, name: &'static str, depth: usize) {
+        self.queues.insert(name, depth);
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(self.config.interval_s.max(1))
+    }
+
+    pub fn report(&self) -> HealthReport {
+        let interfaces: Vec<InterfaceHealth> = self.watchdog.activity().into_iter().map(interface_health).collect();
+        let uptime = self.started.elapsed();
+        let (healthy, problems) = evaluate(&interfaces, uptime, Duration::from_secs(self.config.max_idle_s));
+        let timestamp_ms = clock::now_ms().0;
+        HealthReport {
+            sensor_id: self.sensor_id.clone(),
+            version: env!("CARGO_PKG_VERSION"),
+            timestamp_ms,
+            uptime_s: uptime.as_secs(),
+            last_frame_ms: interfaces.iter().map(|i| i.idle_s).min().map(|idle| timestamp_ms - idle as i64 * 1000),
+            interfaces,
+            queues: self.queues.clone(),
+            healthy,
+            problems,
+        }
+    }
+
+    /// 通知 systemd 启动完成
+    pub fn ready(&self) {
+        if let Some(systemd) = &self.systemd {
+            systemd.notify("READY=1");
+            info!("notified systemd ready (watchdog {:?})", systemd.watchdog);
+        }
+    }
+
+    /// 通知 systemd 正在退出，退出期间不再要求喂狗
+    pub fn stopping(&self) {
+        if let Some(systemd) = &self.systemd {
+            systemd.notify("STOPPING=1");
+        }
+    }
+
+    /// 到了间隔时生成报告并发送心跳，返回报告供其它通道发布
+    pub fn poll(&mut self) -> Option<HealthReport> {
+        let now = Instant::now();
+        if let Some(systemd) = &self.systemd
+            && let Some(watchdog) = systemd.watchdog
+            && now >= self.next_ping
+        {
+            self.next_ping = now + watchdog / 2;
+            if self.report().healthy {
+                systemd.notify("WATCHDOG=1");
+            }
+        }
+        if now < self.next_report {
+            return None;
+        }
+        self.next_report = now + self.interval();
+        let report = self.report();
+        if !report.healthy {
+            warn!("sensor unhealthy: {}", report.problems.join("; "));
+        }
+        if let Some(systemd) = &self.systemd {
+            systemd.notify(&format!("STATUS={}", status_line(&report)));
+        }
+        if let Some(heartbeat) = &self.heartbeat {
+            let _ = heartbeat.send(report.clone());
+        }
+        *self.latest.lock().unwrap() = Some((now, report.clone()));
+        Some(report)
+    }
+
+    /// 启动本地健康检查接口 `GET /healthz`
+    ///
+    /// 返回最近一份报告；不健康或报告超过三个间隔未更新（解码线程卡住）时状态码为 503。
+    pub fn spawn_http_listener(&self, addr: String) {
+        let latest = self.latest.clone();
+        let stale_after = self.interval() * 3;
+        thread::spawn(move || {
+            let listener = match TcpListener::bind(&addr) {
+                Ok(listener) => listener,
+                Err(e) => {
+                    error!("无法监听健康检查接口 {}: {}", addr, e);
+                    return;
+                }
+            };
+            info!("health check on http://{}/healthz", addr);
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else { continue };
+                let mut buf = [0u8; 1024];
+                let len = stream.read(&mut buf).unwrap_or(0);
+                let request = String::from_utf8_lossy(&buf[..len]);
+                let path = request.lines().next().and_then(|line| line.split_whitespace().nth(1)).unwrap_or_default();
+                let (status, body) = if path == "/healthz" {
+                    healthz(latest.lock().unwrap().as_ref(), stale_after)
+                } else {
+                    ("404 Not Found", r#"{"error":"unknown endpoint"}"#.to_string())
+                };
+                let _ = write!(stream, "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+                    status, body.len(), body);
+            }
+        });
+    }
+}
+
+fn healthz(latest: Option<&(Instant, HealthReport)>, stale_after: Duration) -> (&'static str, String) {
+    let Some((at, report)) = latest else {
+        return ("503 Service Unavailable", r#"{"healthy":false,"problems":["尚未生成健康报告"]}"#.to_string());
+    };
+    let mut report = report.clone();
+    if at.elapsed() > stale_after {
+        report.healthy = false;
+        report.problems.push(format!("健康报告已 {} 秒未更新", at.elapsed().as_secs()));
+    }
+    let status = if report.healthy { "200 OK" } else { "503 Service Unavailable" };
+    (status, serde_json::to_string(&report).unwrap_or_default())
+}
+
+/// systemd 状态栏显示的一行摘要
+fn status_line(report: &HealthReport) -> String {
+    let receiving = report.interfaces.iter().filter(|i| i.idle_s < 60).count();
+    let queued: usize = report.queues.values().sum();
+    format!("{} {}/{} interfaces receiving, {} queued",
+        if report.healthy { "healthy" } else { "unhealthy" }, receiving, report.interfaces.len(), queued)
+}
+
+/// 后台线程依次 POST 心跳，按状态通知优先级计入出站预算；送不出的心跳直接丢弃
+fn spawn_sender(url: String, token: Option<String>) -> mpsc::Sender<HealthReport> {
+    let (sender, receiver) = mpsc::channel::<HealthReport>();
+    thread::spawn(move || {
+        let client = reqwest::blocking::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .unwrap();
+        for report in receiver {
+            let size = serde_json::to_vec(&report).map_or(0, |body| body.len());
+            if !egress::allow(Priority::Status, size + egress::HTTP_OVERHEAD) {
+                continue;
+            }
+            let mut request = client.post(&url).json(&report);
+            if let Some(token) = &token {
+                request = request.bearer_auth(token);
+            }
+            if let Err(e) = request.send().and_then(|r| r.error_for_status()) {
+                warn!("heartbeat to {} failed: {}", url, e);
+            }
+        }
+    });
+    sender
+}
+
+/// systemd 通知 (sd_notify)：向 `NOTIFY_SOCKET` 指向的 Unix 数据报套接字发送状态
+#[cfg(unix)]
+pub struct SystemdNotifier {
+    socket: std::os::unix::net::UnixDatagram,
+    addr: std::os::unix::net::SocketAddr,
+    /// `WatchdogSec=` 设置的看门狗超时
+    pub watchdog: Option<Duration>,
+}
+
+#[cfg(unix)]
+impl SystemdNotifier {
+    /// 不是由 systemd 以 `Type=notify` 启动时返回 None
+    pub fn from_env() -> Option<Self> {
+        let path = std::env::var_os("NOTIFY_SOCKET")?;
+        let addr = match notify_addr(&path) {
+            Ok(addr) => addr,
+            Err(e) => {
+                warn!("invalid NOTIFY_SOCKET {:?}: {}", path, e);
+                return None;
+            }
+        };
+        let socket = std::os::unix::net::UnixDatagram::unbound().ok()?;
+        // WATCHDOG_PID 不是本进程时看门狗属于启动脚本等父进程
+        let own_watchdog = std::env::var("WATCHDOG_PID").map_or(true, |pid| pid.parse() == Ok(std::process::id()));
+        let watchdog = std::env::var("WATCHDOG_USEC").ok()
+            .and_then(|usec| usec.parse().ok())
+            .filter(|usec| *usec > 0 && own_watchdog)
+            .map(Duration::from_micros);
+        Some(Self { socket, addr, watchdog })
+    }
+
+    pub fn notify(&self, state: &str) {
+        if let Err(e) = self.socket.send_to_addr(state.as_bytes(), &self.addr) {
+            warn!("systemd notify failed: {}", e);
+        }
+    }
+}
+
+/// `@` 开头的为抽象命名空间中的套接字
+#[cfg(unix)]
+fn notify_addr(path: &std::ffi::OsStr) -> std::io::Result<std::os::unix::net::SocketAddr> {
+    #[cfg(target_os = "linux")]
+    {
+        use std::os::linux::net::SocketAddrExt;
+        use std::os::unix::ffi::OsStrExt;
+        if let Some(name) = path.as_bytes().strip_prefix(b"@") {
+            return std::os::unix::net::SocketAddr::from_abstract_name(name);
+        }
+    }
+    std::os::unix::net::SocketAddr::from_pathname(path)
+}
+
+/// 非 Unix 平台没有 systemd
+#[cfg(not(unix))]
+pub struct SystemdNotifier {
+    pub watchdog: Option<Duration>,
+}
+
+#[cfg(not(unix))]
+impl SystemdNotifier {
+    pub fn from_env() -> Option<Self> {
+        None
+    }
+
+    pub fn notify(&self, _state: &str) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAX_IDLE: Duration = Duration::from_secs(300);
+    const UPTIME: Duration = Duration::from_secs(3600);
+    const STALE_AFTER: Duration = Duration::from_secs(180);
+
+    fn interface(name: &str, up: Option<bool>, idle_s: u64) -> InterfaceHealth {
+        InterfaceHealth { name: name.into(), up, idle_s, restarts: 0 }
+    }
+
+    fn report(healthy: bool) -> HealthReport {
+        HealthReport {
+            sensor_id: "roof-2".into(),
+            version: "0.1.0",
+            timestamp_ms: 1_700_000_000_000,
+            uptime_s: 3600,
+            last_frame_ms: None,
+            interfaces: vec![interface("wlan1", Some(true), 5), interface("wlan2", Some(true), 120)],
+            queues: BTreeMap::from([("upload_batches", 3), ("mqtt", 2)]),
+            healthy,
+            problems: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_one_receiving_interface_is_healthy() {
+        let (healthy, problems) = evaluate(&[interface("wlan1", Some(true), 5), interface("wlan2", None, 900)], UPTIME, MAX_IDLE);
+        assert!(healthy);
+        assert_eq!(problems, ["网卡 wlan2 已 900 秒未收到帧"]);
+    }
+
+    #[test]
+    fn test_down_and_stalled_interfaces() {
+        let (healthy, problems) = evaluate(&[interface("wlan1", Some(false), 5), interface("wlan2", Some(true), 900)], UPTIME, MAX_IDLE);
+        assert!(!healthy);
+        assert_eq!(problems, ["网卡 wlan1 未启用", "网卡 wlan2 已 900 秒未收到帧"]);
+    }
+
+    #[test]
+    fn test_grace_period_after_start() {
+        assert!(evaluate(&[interface("wlan1", Some(true), 100)], Duration::from_secs(100), MAX_IDLE).0);
+        assert!(!evaluate(&[interface("wlan1", Some(true), 300)], Duration::from_secs(300), MAX_IDLE).0);
+    }
+
+    #[test]
+    fn test_bluetooth_only_is_healthy() {
+        assert_eq!(evaluate(&[], UPTIME, MAX_IDLE), (true, Vec::new()));
+    }
+
+    #[test]
+    fn test_healthz_before_first_report() {
+        let (status, body) = healthz(None, STALE_AFTER);
+        assert_eq!(status, "503 Service Unavailable");
+        assert!(body.contains("尚未生成健康报告"));
+    }
+
+    #[test]
+    fn test_healthz_serves_latest_report() {
+        let (status, body) = healthz(Some(&(Instant::now(), report(true))), STALE_AFTER);
+        assert_eq!(status, "200 OK");
+        assert!(body.contains(r#""upload_batches":3"#), "{}", body);
+        assert_eq!(healthz(Some(&(Instant::now(), report(false))), STALE_AFTER).0, "503 Service Unavailable");
+    }
+
+    #[test]
+    fn test_healthz_stale_report() {
+        let Some(stale) = Instant::now().checked_sub(Duration::from_secs(200)) else { return };
+        let (status, body) = healthz(Some(&(stale, report(true))), STALE_AFTER);
+        assert_eq!(status, "503 Service Unavailable");
+        assert!(body.contains(r#""healthy":false"#) && body.contains("健康报告已 200 秒未更新"), "{}", body);
+    }
+
+    #[test]
+    fn test_status_line() {
+        assert_eq!(status_line(&report(true)), "healthy 1/2 interfaces receiving, 5 queued");
+        assert_eq!(status_line(&HealthReport { interfaces: Vec::new(), ..report(false) }), "unhealthy 0/0 interfaces receiving, 5 queued");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_systemd_notify() {
+        use std::os::unix::net::UnixDatagram;
+        let path = std::env::temp_dir().join("wifi-capture-notify-test.sock");
+        fs::remove_file(&path).ok();
+        let systemd = UnixDatagram::bind(&path).unwrap();
+        let notifier = SystemdNotifier {
+            socket: UnixDatagram::unbound().unwrap(),
+            addr: notify_addr(path.as_os_str()).unwrap(),
+            watchdog: None,
+        };
+        notifier.notify("READY=1");
+        let mut buf = [0u8; 64];
+        let len = systemd.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..len], b"READY=1");
+        fs::remove_file(&path).ok();
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_abstract_notify_socket() {
+        use std::os::linux::net::SocketAddrExt;
+        let addr = notify_addr(std::ffi::OsStr::new("@/org/freedesktop/systemd1/notify")).unwrap();
+        assert_eq!(addr.as_abstract_name(), Some(&b"/org/freedesktop/systemd1/notify"[..]));
+    }
+}
@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use tracing::warn;
+
+/// 某个源 MAC 以同一 UAS ID 发送的记录概况
+#[derive(Debug, Clone)]
+pub struct SourceEvidence {
+    pub mac: String,
+    pub frames: u64,
+    pub first_seen: Instant,
+    pub last_seen: Instant,
+    pub last_position: (i32, i32),   // 最近一次位置 (纬度, 经度)
+    pub last_rssi: Option<f32>,
+}
+
+/// 同一 UAS ID 出现在多个源 MAC 上的告警
+#[derive(Debug, Clone)]
+pub struct IdCollision {
+    pub uas_id: String,
+    pub sources: Vec<SourceEvidence>,
+}
+
+/// UAS ID 冲突检测
+///
+/// 两个发射端宣称相同的 UAS ID 是仿冒的强烈信号。检测器按 (UAS ID, 源 MAC)
+/// 分别记录，在窗口期内同一 ID 出现第二个 MAC 时产生告警；
+/// 同一 ID 的告警在冷却期内只产生一次。
+pub struct IdCollisionDetector {
+    window: Duration,
+    cooldown: Duration,
+    seen: HashMap<String, Vec<SourceEvidence>>,
+    last_alert: HashMap<String, Instant>,
+}
+
+impl Default for IdCollisionDetector {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(300), Duration::from_secs(600))
+    }
+}
+
+impl IdCollisionDetector {
+    pub fn new(window: Duration, cooldown: Duration) -> Self {
+        Self { window, cooldown, seen: HashMap::new(), last_alert: HashMap::new() }
+    }
+
+    /// 记录一次观测，同一 ID 在窗口期内有多个源 MAC 时返回冲突
+    pub fn observe(&mut self, uas_id: &str, mac: &str, position: (i32, i32), rssi: Option<f32>) -> Option<IdCollision> {
+        if uas_id.is_empty() {
+            return None;
+        }
+        let now = Instant::now();
+        let window = self.window;
+        let sources = self.seen.entry(uas_id.to_string()).or_default();
+        sources.retain(|s| now.duration_since(s.last_seen) < window);
+        match sources.iter_mut().find(|s| s.mac == mac) {
+            Some(source) => {
+                source.frames += 1;
+                source.last_seen = now;
+                source.last_position = position;
+                source.last_rssi = rssi;
+            }
+            None => sources.push(SourceEvidence {
+                mac: mac.to_string(),
+                frames: 1,
+                first_seen: now,
+                last_seen: now,
+                last_position: position,
+                last_rssi: rssi,
+            }),
+        }
+        if sources.len() < 2 {
+            return None;
+        }
+        let collision = IdCollision { uas_id: uas_id.to_string(), sources: sources.clone() };
+        let alerted = self.last_alert.get(uas_id)
+            .is_some_and(|t| now.duration_since(*t) < self.cooldown);
+        if !alerted {
+            self.last_alert.insert(uas_id.to_string(), now);
+            warn!(uas_id = %collision.uas_id, "UAS ID claimed by {} transmitters: {}",
+                collision.sources.len(), describe(&collision.sources));
+        }
+        Some(collision)
+    }
+}
+
+fn describe(sources: &[SourceEvidence]) -> String {
+    sources.iter()
+        .map(|s| format!("{} (frames={}, last=({}, {}), rssi={:?}, active {}s)",
+            s.mac, s.frames, s.last_position.0, s.last_position.1, s.last_rssi,
+            s.last_seen.duration_since(s.first_seen).as_secs()))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POSITION: (i32, i32) = (312_000_000, 1_214_000_000);
+
+    #[test]
+    fn test_single_transmitter_is_not_a_collision() {
+        let mut detector = IdCollisionDetector::default();
+        assert!(detector.observe("RID-1", "aa:aa:aa:aa:aa:01", POSITION, Some(-60.0)).is_none());
+        assert!(detector.observe("RID-1", "aa:aa:aa:aa:aa:01", POSITION, Some(-61.0)).is_none());
+    }
+
+    #[test]
+    fn test_second_transmitter_collides() {
+        let mut detector = IdCollisionDetector::default();
+        detector.observe("RID-1", "aa:aa:aa:aa:aa:01", POSITION, Some(-60.0));
+        detector.observe("RID-1", "aa:aa:aa:aa:aa:01", POSITION, Some(-60.0));
+        let collision = detector.observe("RID-1", "bb:bb:bb:bb:bb:02", (0, 0), None).unwrap();
+        assert_eq!(collision.uas_id, "RID-1");
+        let sources: Vec<_> = collision.sources.iter().map(|s| (s.mac.as_str(), s.frames, s.last_position)).collect();
+        assert_eq!(sources, [("aa:aa:aa:aa:aa:01", 2, POSITION), ("bb:bb:bb:bb:bb:02", 1, (0, 0))]);
+        // 冷却期内仍返回冲突，只是不再重复记录告警
+        assert!(detector.observe("RID-1", "aa:aa:aa:aa:aa:01", POSITION, None).is_some());
+    }
+
+    #[test]
+    fn test_different_ids_do_not_collide() {
+        let mut detector = IdCollisionDetector::default();
+        detector.observe("RID-1", "aa:aa:aa:aa:aa:01", POSITION, None);
+        assert!(detector.observe("RID-2", "bb:bb:bb:bb:bb:02", POSITION, None).is_none());
+        // 没有 UAS ID 的记录不参与检测
+        detector.observe("", "aa:aa:aa:aa:aa:01", POSITION, None);
+        assert!(detector.observe("", "bb:bb:bb:bb:bb:02", POSITION, None).is_none());
+    }
+
+    #[test]
+    fn test_sources_outside_window_are_forgotten() {
+        let mut detector = IdCollisionDetector::new(Duration::ZERO, Duration::from_secs(600));
+        detector.observe("RID-1", "aa:aa:aa:aa:aa:01", POSITION, None);
+        assert!(detector.observe("RID-1", "bb:bb:bb:bb:bb:02", POSITION, None).is_none());
+    }
+}
@@ -0,0 +1,154 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use crate::event_log::DecodedEvent;
+use crate::fleet::Annotation;
+use crate::remote_id;
+
+/// 一架无人机跨重启保留的身份摘要
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DroneIdentity {
+    pub uas_id: String,
+    pub first_seen_ms: i64,
+    pub last_seen_ms: i64,
+    /// 曾用来广播该 UAS ID 的轨迹 ID（发射端 MAC 或关联后的轨迹）
+    pub aliases: BTreeSet<String>,
+    pub annotation: Option<Annotation>,
+}
+
+/// 按 UAS ID 记录首次出现时间、轨迹别名和标注，保存在数据库中，重启后恢复
+///
+/// 恢复后，重启前见过的无人机不会再被当作今天首次出现；只有位置向量的报文
+/// 也能按重启前记录的别名归到对应的 UAS ID。
+#[derive(Default)]
+pub struct IdentityCache {
+    drones: HashMap<String, DroneIdentity>,
+    /// 轨迹 ID -> UAS ID
+    aliases: HashMap<String, String>,
+    /// 上次保存后有变化的 UAS ID
+    dirty: HashSet<String>,
+}
+
+impl IdentityCache {
+    pub fn len(&self) -> usize {
+        self.drones.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.drones.is_empty()
+    }
+
+    pub fn get(&self, uas_id: &str) -> Option<&DroneIdentity> {
+        self.drones.get(uas_id)
+    }
+
+    /// 载入数据库中保存的身份
+    ///
+    /// 旧版本以不带分隔符的十六进制保存 MAC 别名，载入时改写为冒号分隔形式，
+    /// 并标记为有变化以便下次保存时写回。
+    pub fn restore(&mut self, identities: Vec<DroneIdentity>) {
+        for mut identity in identities {
+            if identity.aliases.iter().any(|a| legacy_mac(a).is_some()) {
+                identity.aliases = identity.aliases.iter()
+                    .map(|a| legacy_mac(a).unwrap_or_else(|| a.clone()))
+                    .collect();
+                self.dirty.insert(identity.uas_id.clone());
+            }
+            for alias in &identity.aliases {
+                self.aliases.insert(alias.clone(), identity.uas_id.clone());
+            }
+            self.drones.insert(identity.uas_id.clone(), identity);
+        }
+    }
+
+    /// 轨迹 ID 对应的 UAS ID
+    pub fn resolve(&self, track_id: &str) -> Option<&str> {
+        self.aliases.get(track_id).map(String::as_str)
+    }
+
+    /// 记录一条带 UAS ID 的事件
+    pub fn observe(&mut self, event: &DecodedEvent) {
+        let r = &event.record;
+        if r.rid.is_empty() {
+            return;
+        }
+        let now = event.received_at_ms;
+        let identity = self.drones.entry(r.rid.clone()).or_insert_with(|| DroneIdentity {
+            uas_id: r.rid.clone(),
+            first_seen_ms: now,
+            last_seen_ms: now,
+            ..Default::default()
+        });
+        identity.first_seen_ms = identity.first_seen_ms.min(now);
+        identity.last_seen_ms = identity.last_seen_ms.max(now);
+        if r.track_id != r.rid && identity.aliases.insert(r.track_id.clone()) {
+            self.aliases.insert(r.track_id.clone(), r.rid.clone());
+        }
+        if r.annotation.is_some() {
+            identity.annotation.clone_from(&r.annotation);
+        }
+        self.dirty.insert(r.rid.clone());
+    }
+
+    /// 取出上次保存后有变化的身份
+    pub fn take_dirty(&mut self) -> Vec<DroneIdentity> {
+        self.dirty.drain()
+            .filter_map(|uas_id| self.drones.get(&uas_id).cloned())
+            .collect()
+    }
+}
+
+/// 旧版本记录的 12 位十六进制 MAC 转为冒号分隔形式
+fn legacy_mac(alias: &str) -> Option<String> {
+    if alias.len() != 12 {
+        return None;
+    }
+    let bytes: [u8; 6] = remote_id::from_hex(alias)?.try_into().ok()?;
+    Some(remote_id::format_mac(&bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::upload_data::UploadData;
+
+    fn event(at: i64, rid: &str, track_id: &str) -> DecodedEvent {
+        let record = UploadData { rid: rid.into(), track_id: track_id.into(), ..Default::default() };
+        DecodedEvent { received_at_ms: at, record }
+    }
+
+    #[test]
+    fn test_identity_cache_restore() {
+        let mut cache = IdentityCache::default();
+        cache.observe(&event(5_000, "RID-1", "02:11:22:33:44:55"));
+        cache.observe(&event(9_000, "RID-1", "02:11:22:33:44:66"));
+        cache.observe(&event(9_500, "", "02:11:22:33:44:77"));
+        let saved = cache.take_dirty();
+        assert_eq!(saved.len(), 1);
+        assert_eq!((saved[0].first_seen_ms, saved[0].last_seen_ms, saved[0].aliases.len()), (5_000, 9_000, 2));
+        assert!(cache.take_dirty().is_empty());
+
+        // 重启后恢复：首次出现时间沿用，MAC 别名可解析
+        let mut restored = IdentityCache::default();
+        restored.restore(saved);
+        assert_eq!(restored.resolve("02:11:22:33:44:66"), Some("RID-1"));
+        restored.observe(&event(20_000, "RID-1", "02:11:22:33:44:55"));
+        assert_eq!(restored.get("RID-1").map(|i| (i.first_seen_ms, i.last_seen_ms)), Some((5_000, 20_000)));
+    }
+
+    #[test]
+    fn test_restore_rewrites_bare_hex_aliases() {
+        let saved = DroneIdentity {
+            uas_id: "RID-1".into(),
+            aliases: ["021122334455".to_string(), "RID-1-b".to_string()].into(),
+            ..Default::default()
+        };
+        let mut cache = IdentityCache::default();
+        cache.restore(vec![saved]);
+        assert_eq!(cache.resolve("02:11:22:33:44:55"), Some("RID-1"));
+        assert_eq!(cache.resolve("021122334455"), None);
+        assert_eq!(cache.resolve("RID-1-b"), Some("RID-1"));
+        let rewritten = cache.take_dirty();
+        assert_eq!(rewritten.len(), 1);
+        assert!(rewritten[0].aliases.contains("02:11:22:33:44:55"));
+    }
+}
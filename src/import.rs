@@ -0,0 +1,270 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use chrono::{DateTime, NaiveDateTime};
+
+#[cfg(feature = "database")]
+use crate::decode::DecodeContext;
+use crate::event_log::DecodedEvent;
+#[cfg(feature = "database")]
+use crate::mapped_pcap::decode_pcap;
+use crate::schema::FORMAT_VERSION;
+#[cfg(feature = "database")]
+use crate::storage::Store;
+use crate::upload_data::UploadData;
+
+/// pcap 链路类型：裸 802.11 帧
+pub const LINKTYPE_IEEE802_11: u32 = 105;
+/// pcap 链路类型：带 radiotap 头的 802.11 帧
+pub const LINKTYPE_RADIOTAP: u32 = 127;
+
+/// 可导入的外部日志格式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImportFormat {
+    /// 带表头的 CSV（OpenDroneID 接收器日志、Drone Scanner 导出等）
+    Csv,
+    /// 其它抓包工具保存的 pcap/pcapng (radiotap 或裸 802.11)
+    Pcap,
+}
+
+impl ImportFormat {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "csv" | "odid" | "drone-scanner" => Some(Self::Csv),
+            "pcap" | "pcapng" => Some(Self::Pcap),
+            _ => None,
+        }
+    }
+
+    /// 按扩展名推断格式
+    pub fn detect(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "csv" | "txt" => Some(Self::Csv),
+            "pcap" | "pcapng" | "cap" => Some(Self::Pcap),
+            _ => None,
+        }
+    }
+}
+
+/// 各工具对同一字段使用的列名（比较时忽略大小写、空格、下划线）
+const TIME_COLUMNS: &[&str] = &["timestamp", "time", "receivedat", "datetime", "date"];
+const ID_COLUMNS: &[&str] = &["uasid", "basicid", "serialnumber", "serial", "droneid", "id"];
+const MAC_COLUMNS: &[&str] = &["macaddress", "mac", "address"];
+const LAT_COLUMNS: &[&str] = &["latitude", "lat", "dronelat", "dronelatitude"];
+const LON_COLUMNS: &[&str] = &["longitude", "lon", "lng", "dronelon", "dronelongitude"];
+const ALT_COLUMNS: &[&str] = &["altitudegeodetic", "geodeticaltitude", "altitude", "alt", "height"];
+const RSSI_COLUMNS: &[&str] = &["rssi", "signal"];
+
+fn normalize(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+fn find_column(headers: &[String], aliases: &[&str]) -> Option<usize> {
+    aliases.iter().find_map(|alias| headers.iter().position(|h| h == alias))
+}
+
+/// 解析 Unix 秒/毫秒、RFC 3339 或 "YYYY-MM-DD HH:MM:SS[.fff]" (按 UTC)
+fn parse_time_ms(s: &str) -> Option<i64> {
+    let s = s.trim();
+    if let Ok(n) = s.parse::<f64>() {
+        // 小于 1e11 视为秒
+        return Some(if n.abs() < 1e11 { (n * 1000.0) as i64 } else { n as i64 });
+    }
+    if let Ok(t) = DateTime::parse_from_rfc3339(s) {
+        return Some(t.timestamp_millis());
+    }
+    ["%Y-%m-%d %H:%M:%S%.f", "%Y/%m/%d %H:%M:%S%.f"].iter()
+        .find_map(|f| NaiveDateTime::parse_from_str(s, f).ok())
+        .map(|t| t.and_utc().timestamp_millis())
+}
+
+/// 导入记录以文件名作为传感器标识，重复导入同一文件得到相同的记录 ID
+pub fn file_sensor(input: &Path) -> String {
+    input.file_name().map_or_else(String::new, |n| n.to_string_lossy().into_owned())
+}
+
+/// 导入外部日志：CSV 按列映射，pcap 逐帧走与实时抓包相同的解码流程
+#[cfg(feature = "database")]
+pub fn import_into(store: &mut Store, input: &Path, format: ImportFormat) -> Result<(usize, usize), String> {
+    let mut imported = 0;
+    let skipped = match format {
+        ImportFormat::Csv => {
+            let (events, bad_rows) = read_csv(input)?;
+            let sensor = file_sensor(input);
+            for mut event in events {
+                event.assign_id(&sensor);
+                store.insert(&event, event.record.operator_id.as_deref()).map_err(|e| e.to_string())?;
+                imported += 1;
+            }
+            bad_rows
+        }
+        ImportFormat::Pcap => {
+            let mut ctx = DecodeContext::default();
+            let frames = decode_pcap(input, &mut ctx, |event| {
+                store.insert(&event, event.record.operator_id.as_deref()).map_err(|e| e.to_string())?;
+                imported += 1;
+                Ok(())
+            })?;
+            frames - imported
+        }
+    };
+    Ok((imported, skipped))
+}
+
+
+/// 读取带表头的 CSV 日志
+///
+/// 按列名识别时间、UAS ID、MAC、经纬度、高度、RSSI，缺少时间或 UAS ID 列时报错；
+/// 无法解析的行被跳过，返回 (事件, 跳过行数)。
+pub fn read_csv<P: AsRef<Path>>(path: P) -> Result<(Vec<DecodedEvent>, usize), String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_path(path)
+        .map_err(|e| e.to_string())?;
+    let headers: Vec<String> = reader.headers().map_err(|e| e.to_string())?
+        .iter().map(normalize).collect();
+    let time = find_column(&headers, TIME_COLUMNS).ok_or("缺少时间列")?;
+    let id = find_column(&headers, ID_COLUMNS).ok_or("缺少 UAS ID 列")?;
+    let mac = find_column(&headers, MAC_COLUMNS);
+    let lat = find_column(&headers, LAT_COLUMNS);
+    let lon = find_column(&headers, LON_COLUMNS);
+    let alt = find_column(&headers, ALT_COLUMNS);
+    let rssi = find_column(&headers, RSSI_COLUMNS);
+
+    let mut events = Vec::new();
+    let mut skipped = 0;
+    for row in reader.records() {
+        let Ok(row) = row else {
+            skipped += 1;
+            continue;
+        };
+        let field = |i: Option<usize>| i.and_then(|i| row.get(i)).filter(|s| !s.is_empty());
+        let number = |i: Option<usize>| field(i).and_then(|s| s.parse::<f64>().ok());
+        let (Some(received_at_ms), Some(rid)) = (field(Some(time)).and_then(parse_time_ms), field(Some(id))) else {
+            skipped += 1;
+            continue;
+        };
+        let source_mac = field(mac).unwrap_or_default().to_ascii_lowercase();
+        let record = UploadData {
+            format_version: FORMAT_VERSION,
+            rid: rid.to_string(),
+            track_id: if source_mac.is_empty() { rid.to_string() } else { source_mac.clone() },
+            source_mac,
+            rssi: number(rssi).map(|v| v as f32),
+            latitude: number(lat).map_or(0, |v| (v * 1e7).round() as i32),
+            longitude: number(lon).map_or(0, |v| (v * 1e7).round() as i32),
+            geometric_altitude: number(alt).map_or(0, |v| v.round() as i16),
+            ..Default::default()
+        };
+        events.push(DecodedEvent { received_at_ms, record });
+    }
+    Ok((events, skipped))
+}
+
+/// 读取本程序 `[output] ndjson` 写出的检测记录
+///
+/// 每行一条记录，接收时间取 `received_at_ms`；无法解析或缺少接收时间的行被跳过，返回 (事件, 跳过行数)。
+pub fn read_ndjson<P: AsRef<Path>>(path: P) -> Result<(Vec<DecodedEvent>, usize), String> {
+    let file = File::open(path).map_err(|e| e.to_string())?;
+    let mut events = Vec::new();
+    let mut skipped = 0;
+    for line in BufReader::new(file).lines() {
+        let line = line.map_err(|e| e.to_string())?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let record = serde_json::from_str::<UploadData>(&line).ok();
+        match record.and_then(|record| Some((record.received_at_ms?, record))) {
+            Some((received_at_ms, record)) => events.push(DecodedEvent { received_at_ms, record }),
+            None => skipped += 1,
+        }
+    }
+    Ok((events, skipped))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_file(name: &str, content: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("wifi-capture-import-test-{}", name));
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_format_names_and_extensions() {
+        assert_eq!(ImportFormat::parse("drone-scanner"), Some(ImportFormat::Csv));
+        assert_eq!(ImportFormat::parse("pcapng"), Some(ImportFormat::Pcap));
+        assert_eq!(ImportFormat::parse("kml"), None);
+        assert_eq!(ImportFormat::detect(Path::new("log.TXT")), Some(ImportFormat::Csv));
+        assert_eq!(ImportFormat::detect(Path::new("air.cap")), Some(ImportFormat::Pcap));
+        assert_eq!(ImportFormat::detect(Path::new("air")), None);
+    }
+
+    #[test]
+    fn test_parse_time_forms() {
+        assert_eq!(parse_time_ms("1748764800"), Some(1_748_764_800_000));
+        assert_eq!(parse_time_ms("1748764800.25"), Some(1_748_764_800_250));
+        assert_eq!(parse_time_ms("1748764800500"), Some(1_748_764_800_500));
+        assert_eq!(parse_time_ms("2025-06-01T16:00:00+08:00"), Some(1_748_764_800_000));
+        assert_eq!(parse_time_ms("2025/06/01 08:00:00"), Some(1_748_764_800_000));
+        assert_eq!(parse_time_ms("yesterday"), None);
+    }
+
+    #[test]
+    fn test_read_csv_aliases() {
+        let path = temp_file("aliases.csv", "Time,MAC Address,UAS ID,Drone Lat,Drone Lon,Altitude Geodetic,RSSI\n\
+            2025-06-01 08:00:00.500,AA:BB:CC:00:11:22,1581F5FKD229400A,31.2304,121.4737,120.5,-67\n");
+        let (events, skipped) = read_csv(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!((events.len(), skipped), (1, 0));
+        let e = &events[0];
+        assert_eq!(e.received_at_ms, 1_748_764_800_500);
+        assert_eq!(e.record.rid, "1581F5FKD229400A");
+        assert_eq!(e.record.source_mac, "aa:bb:cc:00:11:22");
+        assert_eq!(e.record.track_id, "aa:bb:cc:00:11:22");
+        assert_eq!(e.record.latitude, 312_304_000);
+        assert_eq!(e.record.geometric_altitude, 121);
+        assert_eq!(e.record.rssi, Some(-67.0));
+    }
+
+    #[test]
+    fn test_read_csv_skips_bad_rows() {
+        let path = temp_file("skip.csv", "timestamp,serial_number\n\
+            1748764800,1581F5FKD229400A\n\
+            garbage,1581F5FKD229400A\n\
+            1748764801,\n");
+        let (events, skipped) = read_csv(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!((events.len(), skipped), (1, 2));
+        // 没有 MAC 列时以 UAS ID 作为轨迹
+        assert_eq!((events[0].record.track_id.as_str(), events[0].record.latitude), ("1581F5FKD229400A", 0));
+    }
+
+    #[test]
+    fn test_read_csv_requires_time_and_id() {
+        let path = temp_file("columns.csv", "lat,lon\n31.2,121.4\n");
+        assert_eq!(read_csv(&path).err().unwrap(), "缺少时间列");
+        let path = temp_file("columns.csv", "time,lat\n1748764800,31.2\n");
+        assert_eq!(read_csv(&path).err().unwrap(), "缺少 UAS ID 列");
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_read_ndjson() {
+        let record = UploadData { rid: "A".into(), received_at_ms: Some(1_000), ..Default::default() };
+        let untimed = UploadData { rid: "B".into(), ..Default::default() };
+        let content = format!("{}\n\n{}\nnot json\n", serde_json::to_string(&record).unwrap(), serde_json::to_string(&untimed).unwrap());
+        let path = temp_file("records.ndjson", &content);
+        let (events, skipped) = read_ndjson(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!((events.len(), skipped), (1, 2));
+        assert_eq!((events[0].received_at_ms, events[0].record.rid.as_str()), (1_000, "A"));
+    }
+}
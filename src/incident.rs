@@ -0,0 +1,230 @@
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::Path;
+
+use chrono::{SecondsFormat, Utc};
+use serde_json::json;
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+use crate::event_log::DecodedEvent;
+use crate::geo::degrees;
+use crate::storage::{FixQuery, Store};
+use crate::time_format;
+use crate::units;
+
+/// 事件包格式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BundleFormat {
+    /// 独立的 SQLite 数据库，表结构与本地存储相同
+    Sqlite,
+    /// 含 fixes.csv / drones.csv / manifest.json 的 zip 包
+    CsvZip,
+}
+
+impl BundleFormat {
+    /// 按输出文件扩展名推断格式
+    pub fn detect(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "db" | "sqlite" | "sqlite3" => Some(Self::Sqlite),
+            "zip" => Some(Self::CsvZip),
+            _ => None,
+        }
+    }
+}
+
+/// 单架无人机在事件包中的汇总
+#[derive(Default)]
+struct DroneRow {
+    first_seen_ms: i64,
+    last_seen_ms: i64,
+    fixes: usize,
+    macs: Vec<String>,
+}
+
+/// 将满足条件的所有记录导出为可独立分享的事件包，返回导出的记录数
+///
+/// 输出文件已存在时报错，不覆盖。
+pub fn export_bundle(store: &Store, query: &FixQuery, output: &Path, format: BundleFormat) -> Result<usize, String> {
+    if output.exists() {
+        return Err(format!("{} 已存在", output.display()));
+    }
+    let events = store.query(query).map_err(|e| e.to_string())?;
+    match format {
+        BundleFormat::Sqlite => {
+            let mut bundle = Store::open(output).map_err(|e| e.to_string())?;
+            for event in &events {
+                bundle.insert(event, None).map_err(|e| e.to_string())?;
+            }
+        }
+        BundleFormat::CsvZip => write_zip(&events, query, output).map_err(|e| e.to_string())?,
+    }
+    Ok(events.len())
+}
+
+fn write_zip(events: &[DecodedEvent], query: &FixQuery, output: &Path) -> io::Result<()> {
+    let mut zip = ZipWriter::new(File::create(output)?);
+    let options = SimpleFileOptions::default();
+
+    let mut drones: BTreeMap<&str, DroneRow> = BTreeMap::new();
+    zip.start_file("fixes.csv", options)?;
+    let units = units::csv();
+    writeln!(zip, "time,uas_id,track_id,source_mac,latitude,longitude,altitude_{},ground_speed_{},rssi_dbm,channel",
+        units.altitude_suffix(), units.speed_suffix())?;
+    for event in events {
+        let r = &event.record;
+        let uas_id = if r.rid.is_empty() { r.track_id.as_str() } else { r.rid.as_str() };
+        writeln!(zip, "{},{},{},{},{},{},{:.1},{:.1},{},{}",
+            iso(event.received_at_ms), uas_id, r.track_id, r.source_mac,
+            units.coordinate(degrees(r.latitude), true), units.coordinate(degrees(r.longitude), false),
+            units.altitude(r.geometric_altitude as f64), units.speed_from_knots(r.ground_speed_knots()),
+            r.rssi.map(|v| v.to_string()).unwrap_or_default(),
+            r.channel.map(|v| v.to_string()).unwrap_or_default())?;
+
+        let drone = drones.entry(uas_id).or_insert_with(|| DroneRow {
+            first_seen_ms: event.received_at_ms,
+            ..Default::default()
+        });
+        drone.first_seen_ms = drone.first_seen_ms.min(event.received_at_ms);
+        drone.last_seen_ms = drone.last_seen_ms.max(event.received_at_ms);
+        drone.fixes += 1;
+        if !r.source_mac.is_empty() && !drone.macs.contains(&r.source_mac) {
+            drone.macs.push(r.source_mac.clone());
+        }
+    }
+
+    zip.start_file("drones.csv", options)?;
+    writeln!(zip, "uas_id,first_seen,last_seen,fixes,source_macs")?;
+    for (uas_id, d) in &drones {
+        writeln!(zip, "{},{},{},{},{}",
+            uas_id, iso(d.first_seen_ms), iso(d.last_seen_ms), d.fixes, d.macs.join(" "))?;
+    }
+
+    zip.start_file("manifest.json", options)?;
+    let manifest = json!({
+        "generated_at": time_format::format_ms(Utc::now().timestamp_millis(), SecondsFormat::Secs),
+        "criteria": {
+            "from": query.from_ms.map(iso),
+            "to": query.to_ms.map(iso),
+            "bbox": query.bbox.map(|b| [b.min_lat, b.min_lon, b.max_lat, b.max_lon]),
+        },
+        "fixes": events.len(),
+        "drones": drones.len(),
+    });
+    zip.write_all(serde_json::to_string_pretty(&manifest)?.as_bytes())?;
+    zip.finish()?;
+    Ok(())
+}
+
+fn iso(ms: i64) -> String {
+    time_format::format_ms(ms, SecondsFormat::Millis)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use super::*;
+    use crate::upload_data::UploadData;
+
+    fn fix(rid: &str, mac: &str, at: i64) -> DecodedEvent {
+        let record = UploadData {
+            rid: rid.to_string(),
+            track_id: mac.to_string(),
+            source_mac: mac.to_string(),
+            latitude: 312_000_000,
+            longitude: 1_214_000_000,
+            channel: Some(6),
+            ..Default::default()
+        };
+        DecodedEvent { received_at_ms: at, record }
+    }
+
+    fn store() -> Store {
+        let mut store = Store::open(":memory:").unwrap();
+        store.insert(&fix("RID-1", "aa:aa", 1_000), None).unwrap();
+        store.insert(&fix("RID-1", "bb:bb", 2_000), None).unwrap();
+        store.insert(&fix("RID-2", "cc:cc", 3_000), None).unwrap();
+        store
+    }
+
+    fn output(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join("wifi-capture-incident-test");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    fn read_entry(path: &Path, name: &str) -> String {
+        let mut archive = zip::ZipArchive::new(File::open(path).unwrap()).unwrap();
+        let mut text = String::new();
+        archive.by_name(name).unwrap().read_to_string(&mut text).unwrap();
+        text
+    }
+
+    #[test]
+    fn test_detect_format() {
+        assert_eq!(BundleFormat::detect(Path::new("a.db")), Some(BundleFormat::Sqlite));
+        assert_eq!(BundleFormat::detect(Path::new("a.SQLite3")), Some(BundleFormat::Sqlite));
+        assert_eq!(BundleFormat::detect(Path::new("a.zip")), Some(BundleFormat::CsvZip));
+        assert_eq!(BundleFormat::detect(Path::new("a.csv")), None);
+        assert_eq!(BundleFormat::detect(Path::new("bundle")), None);
+    }
+
+    #[test]
+    fn test_existing_output_is_not_overwritten() {
+        let path = output("existing.zip");
+        std::fs::write(&path, b"keep").unwrap();
+        assert!(export_bundle(&store(), &FixQuery::default(), &path, BundleFormat::CsvZip).is_err());
+        assert_eq!(std::fs::read(&path).unwrap(), b"keep");
+    }
+
+    #[test]
+    fn test_sqlite_bundle_contains_matching_fixes() {
+        let path = output("bundle.db");
+        let query = FixQuery { from_ms: Some(1_500), ..Default::default() };
+        assert_eq!(export_bundle(&store(), &query, &path, BundleFormat::Sqlite).unwrap(), 2);
+        let bundle = Store::open(&path).unwrap();
+        let events = bundle.query(&FixQuery::default()).unwrap();
+        assert_eq!(events.iter().map(|e| e.received_at_ms).collect::<Vec<_>>(), vec![2_000, 3_000]);
+    }
+
+    #[test]
+    fn test_zip_fixes_csv() {
+        let path = output("fixes.zip");
+        export_bundle(&store(), &FixQuery::default(), &path, BundleFormat::CsvZip).unwrap();
+        let fixes = read_entry(&path, "fixes.csv");
+        let lines: Vec<_> = fixes.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].starts_with("time,uas_id,track_id,source_mac,latitude,longitude,altitude_"));
+        assert!(lines[1].starts_with("1970-01-01T00:00:01.000Z,RID-1,aa:aa,aa:aa,31.2"), "{}", lines[1]);
+        assert!(lines[1].ends_with(",6"));
+    }
+
+    #[test]
+    fn test_zip_drones_csv_merges_macs() {
+        let path = output("drones.zip");
+        export_bundle(&store(), &FixQuery::default(), &path, BundleFormat::CsvZip).unwrap();
+        let drones = read_entry(&path, "drones.csv");
+        let lines: Vec<_> = drones.lines().collect();
+        assert_eq!(lines, vec![
+            "uas_id,first_seen,last_seen,fixes,source_macs",
+            "RID-1,1970-01-01T00:00:01.000Z,1970-01-01T00:00:02.000Z,2,aa:aa bb:bb",
+            "RID-2,1970-01-01T00:00:03.000Z,1970-01-01T00:00:03.000Z,1,cc:cc",
+        ]);
+    }
+
+    #[test]
+    fn test_zip_manifest_records_criteria() {
+        let path = output("manifest.zip");
+        let query = FixQuery { to_ms: Some(2_500), ..Default::default() };
+        export_bundle(&store(), &query, &path, BundleFormat::CsvZip).unwrap();
+        let manifest: serde_json::Value = serde_json::from_str(&read_entry(&path, "manifest.json")).unwrap();
+        assert_eq!(manifest["fixes"], 2);
+        assert_eq!(manifest["drones"], 1);
+        assert_eq!(manifest["criteria"]["to"], "1970-01-01T00:00:02.500Z");
+        assert!(manifest["criteria"]["from"].is_null());
+    }
+}
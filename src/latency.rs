@@ -0,0 +1,188 @@
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Value};
+use tracing::info;
+
+use crate::clock;
+use crate::event_log::DecodedEvent;
+use crate::position;
+
+const HOUR_MS: i64 = 3_600_000;
+
+/// 位置向量报文时间戳（整点后的 0.1 秒数）到主机接收时间的延迟 (毫秒)
+///
+/// 时间戳只在一小时内有意义，延迟取 ±30 分钟内的值；机载时钟超前时为负。
+/// 0xFFFF 等超出一小时的值视为无效。
+pub fn broadcast_delay_ms(received_at_ms: i64, timestamp: u16) -> Option<i64> {
+    if timestamp as i64 * 100 >= HOUR_MS {
+        return None;
+    }
+    let delay = (received_at_ms.rem_euclid(HOUR_MS) - timestamp as i64 * 100).rem_euclid(HOUR_MS);
+    Some(if delay > HOUR_MS / 2 { delay - HOUR_MS } else { delay })
+}
+
+/// 已排序样本的百分位数（最近秩法）
+fn percentile(sorted: &[i64], p: f64) -> i64 {
+    let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// 端到端延迟统计
+///
+/// - `airframe`：报文时间戳到主机接收
+/// - 各输出 (`record`、`store`、`sbs`、`asterix`、`live_layer`、`upload`)：主机接收到该输出完成
+///
+/// 按周期输出每个阶段的 p50/p99 并清零；默认不启用（回放时接收时间是历史时间，没有意义）。
+#[derive(Default)]
+pub struct LatencyMetrics {
+    samples: BTreeMap<&'static str, Vec<i64>>,
+    interval: Option<Duration>,
+    last_report: Option<Instant>,
+}
+
+impl LatencyMetrics {
+    pub fn new(interval: Duration) -> Self {
+        Self { samples: BTreeMap::new(), interval: Some(interval), last_report: Some(Instant::now()) }
+    }
+
+    pub fn observe(&mut self, stage: &'static str, delay_ms: i64) {
+        if self.interval.is_some() {
+            self.samples.entry(stage).or_default().push(delay_ms);
+        }
+    }
+
+    /// 记录报文时间戳到接收的延迟（记录中没有有效时间戳时跳过）
+    pub fn observe_broadcast(&mut self, event: &DecodedEvent) {
+        if !position::has_coordinates(&event.record) {
+            return;
+        }
+        if let Some(delay) = broadcast_delay_ms(event.received_at_ms, event.record.timestamp) {
+            self.observe("airframe", delay);
+        }
+    }
+
+    /// 记录接收到某个输出完成的延迟
+    pub fn sink_done(&mut self, sink: &'static str, received_at_ms: i64) {
+        if self.interval.is_some() {
+            self.observe(sink, clock::now_ms().0 - received_at_ms);
+        }
+    }
+
+    /// 各阶段的样本数与 p50/p99
+    pub fn summary(&self) -> Value {
+        let stages: serde_json::Map<String, Value> = self.samples.iter()
+            .filter(|(_, samples)| !samples.is_empty())
+            .map(|(stage, samples)| {
+                let mut sorted = samples.clone();
+                sorted.sort_unstable();
+                (stage.to_string(), json!({
+                    "count": sorted.len(),
+                    "p50_ms": percentile(&sorted, 50.0),
+                    "p99_ms": percentile(&sorted, 99.0),
+                }))
+            })
+            .collect();
+        Value::Object(stages)
+    }
+
+    /// 到达汇总周期时输出一次统计并清零，返回本周期的汇总
+    pub fn maybe_report(&mut self) -> Option<Value> {
+        let (interval, last_report) = (self.interval?, self.last_report?);
+        if last_report.elapsed() < interval {
+            return None;
+        }
+        let summary = self.summary();
+        for (stage, stats) in summary.as_object().into_iter().flatten() {
+            info!("latency {}: n={} p50={}ms p99={}ms", stage, stats["count"], stats["p50_ms"], stats["p99_ms"]);
+        }
+        self.samples.clear();
+        self.last_report = Some(Instant::now());
+        Some(summary)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::upload_data::UploadData;
+
+    /// 某个整点时刻
+    const TOP_OF_HOUR_MS: i64 = 1_700_000_000_000 - 1_700_000_000_000 % HOUR_MS;
+
+    #[test]
+    fn test_broadcast_delay() {
+        // 接收于整点后 10.250 秒，报文时间戳为整点后 10.0 秒
+        assert_eq!(broadcast_delay_ms(TOP_OF_HOUR_MS + 10_250, 100), Some(250));
+    }
+
+    #[test]
+    fn test_broadcast_delay_across_hour() {
+        // 报文时间戳为上一小时的 59:59.0，接收时已是下一小时的 0.5 秒
+        assert_eq!(broadcast_delay_ms(TOP_OF_HOUR_MS + 500, 35_990), Some(1_500));
+    }
+
+    #[test]
+    fn test_airframe_clock_ahead() {
+        assert_eq!(broadcast_delay_ms(TOP_OF_HOUR_MS + 10_000, 105), Some(-500));
+        assert_eq!(broadcast_delay_ms(TOP_OF_HOUR_MS + 35_999_900, 5), Some(-600));
+    }
+
+    #[test]
+    fn test_invalid_timestamp() {
+        assert_eq!(broadcast_delay_ms(TOP_OF_HOUR_MS, 0xFFFF), None);
+        assert_eq!(broadcast_delay_ms(TOP_OF_HOUR_MS, 36_000), None);
+    }
+
+    #[test]
+    fn test_percentiles() {
+        let sorted: Vec<i64> = (1..=100).collect();
+        assert_eq!((percentile(&sorted, 50.0), percentile(&sorted, 99.0)), (50, 99));
+        assert_eq!((percentile(&[7], 50.0), percentile(&[7], 99.0)), (7, 7));
+        assert_eq!(percentile(&[1, 2, 3], 0.0), 1);
+    }
+
+    #[test]
+    fn test_report_summarizes_and_clears() {
+        let mut metrics = LatencyMetrics::new(Duration::ZERO);
+        for delay in (1..=100).rev() {
+            metrics.observe("upload", delay);
+        }
+        metrics.observe("sbs", 3);
+        let summary = metrics.maybe_report().unwrap();
+        assert_eq!(summary, json!({
+            "sbs": { "count": 1, "p50_ms": 3, "p99_ms": 3 },
+            "upload": { "count": 100, "p50_ms": 50, "p99_ms": 99 },
+        }));
+        assert_eq!(metrics.summary(), json!({}));
+    }
+
+    #[test]
+    fn test_report_waits_for_interval() {
+        let mut metrics = LatencyMetrics::new(Duration::from_secs(3600));
+        metrics.observe("upload", 1);
+        assert!(metrics.maybe_report().is_none());
+        assert_eq!(metrics.summary()["upload"]["count"], 1);
+    }
+
+    #[test]
+    fn test_disabled_by_default() {
+        let mut metrics = LatencyMetrics::default();
+        metrics.observe("upload", 1);
+        metrics.sink_done("upload", 0);
+        assert_eq!(metrics.summary(), json!({}));
+        assert!(metrics.maybe_report().is_none());
+    }
+
+    #[test]
+    fn test_broadcast_needs_position() {
+        let mut metrics = LatencyMetrics::new(Duration::ZERO);
+        let record = UploadData { timestamp: 100, ..Default::default() };
+        metrics.observe_broadcast(&DecodedEvent { received_at_ms: TOP_OF_HOUR_MS + 10_250, record: record.clone() });
+        assert_eq!(metrics.summary(), json!({}));
+        let record = UploadData { latitude: 312_304_000, longitude: 1_214_737_000, ..record };
+        metrics.observe_broadcast(&DecodedEvent { received_at_ms: TOP_OF_HOUR_MS + 10_250, record });
+        assert_eq!(metrics.summary()["airframe"]["p50_ms"], 250);
+    }
+}
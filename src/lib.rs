@@ -0,0 +1,130 @@
+//! Wi-Fi 广播 Remote ID (ASTM F3411 / ASD-STAN) 抓包与解码
+//!
+//! 命令行程序 `wifi-capture` 建立在本库之上；其他服务可以直接嵌入解码流程：
+//!
+//! - [`parse_remote_id_frame`]：从单个 802.11 帧中解出 Remote ID 消息，不保留任何状态
+//! - [`process_packet`]：带 radiotap 头的原始帧到输出记录 [`UploadData`]，附带帧统计、轨迹关联、测距等
+//! - [`Capture`]：在监听模式网卡上抓包、轮换信道并逐条回调解码后的 [`DecodedEvent`]
+//! - [`output::Output`]：命令行程序的输出流程（轨迹合并、告警、存储、上传等），可接收 [`Capture`] 回调的事件
+//! - [`mapped_pcap::decode_pcap`]：离线解码 pcap/pcapng 文件，[`batch::decode_files`] 并行解码一组文件，
+//!   [`compare::compare_pcap`] 比较两组解码选项的结果
+//!
+//! ```no_run
+//! let frame: &[u8] = &[];
+//! for message in wifi_capture::parse_remote_id_frame(frame).unwrap() {
+//!     println!("{:?}", message);
+//! }
+//! ```
+//!
+//! Windows 和 macOS 上通过 libpcap (Windows 为 Npcap) 以 rfmon 方式抓包，使用 `desktop` 特性构建：
+//! `cargo build --release --no-default-features --features desktop`。
+
+#[cfg(all(not(target_os = "linux"), not(feature = "libpcap")))]
+compile_error!("Windows/macOS 上需要通过 libpcap (Npcap) 抓包，请使用 --no-default-features --features desktop 构建");
+#[cfg(all(not(target_os = "linux"), any(feature = "monitor", feature = "bpf", feature = "bluetooth")))]
+compile_error!("monitor、bpf、bluetooth 特性只支持 Linux，请使用 --no-default-features --features desktop 构建");
+
+pub mod wifi;
+pub mod message;
+pub mod upload_data;
+pub mod schema;
+pub mod event_log;
+pub mod cli;
+pub mod playback;
+pub mod stats;
+pub mod remote_id;
+pub mod failure_sink;
+pub mod id_collision;
+pub mod ssid_check;
+pub mod correlation;
+pub mod rssi;
+pub mod bearing;
+pub mod geo;
+pub mod position;
+pub mod receiver;
+pub mod quality;
+pub mod tracker;
+pub mod throttle;
+pub mod identity;
+pub mod localization;
+pub mod traffic_stats;
+pub mod flight_export;
+pub mod geofence;
+pub mod zone_report;
+#[cfg(feature = "database")]
+pub mod storage;
+pub mod import;
+#[cfg(feature = "database")]
+pub mod incident;
+#[cfg(feature = "database")]
+pub mod aggregate;
+pub mod time_format;
+pub mod mgt_parser;
+pub mod decode;
+pub mod deep_scan;
+pub mod shedding;
+pub mod shutdown;
+pub mod pipeline;
+pub mod output;
+pub mod dedup;
+pub mod config;
+pub mod regdomain;
+pub mod survey;
+pub mod control;
+pub mod client;
+pub mod clock;
+pub mod telemetry;
+pub mod environment;
+pub mod watchdog;
+pub mod health;
+pub mod diagnose;
+pub mod conformance;
+pub mod loopback;
+pub mod batch;
+pub mod canonical;
+pub mod units;
+pub mod sbs;
+pub mod asterix;
+#[cfg(feature = "dashboard")]
+pub mod live_layer;
+#[cfg(feature = "tui")]
+pub mod tui;
+pub mod live_feed;
+pub mod fleet;
+pub mod latency;
+pub mod privacy;
+pub mod alerts;
+pub mod digest;
+pub mod upload;
+pub mod sink;
+pub mod mqtt;
+pub mod logging;
+pub mod capture;
+pub mod bpf;
+#[cfg(feature = "libpcap")]
+pub mod libpcap;
+#[cfg(feature = "mesh")]
+pub mod mesh;
+pub mod egress;
+pub mod pcapng;
+pub mod pcap_writer;
+pub mod frame_ring;
+pub mod nan;
+pub mod bluetooth;
+pub mod radiotap;
+pub mod remote_config;
+pub mod signing;
+pub mod network_rid;
+pub mod authorization;
+pub mod auth_verify;
+pub mod compare;
+pub mod mapped_pcap;
+pub mod formats;
+#[cfg(any(test, feature = "test-support"))]
+pub mod test_support;
+
+pub use capture::{Capture, CaptureError};
+pub use decode::{parse_radiotap, parse_remote_id_frame, process_packet, DecodeContext, FrameError};
+pub use event_log::DecodedEvent;
+pub use message::{AnyMessage, DecodeOptions};
+pub use upload_data::UploadData;
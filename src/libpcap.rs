@@ -0,0 +1,147 @@
+use std::ffi::{c_char, c_int, c_uint, CStr, CString};
+use std::io;
+use std::time::Duration;
+
+use crate::bpf::Instruction;
+use crate::capture::{CaptureError, FrameSource};
+use crate::import::LINKTYPE_RADIOTAP;
+
+/// libpcap 的最小 FFI 绑定，只包含实时抓包需要的函数
+///
+/// OpenWrt 的 mips/arm 目标上 pnet 的原始套接字方式不够可靠，改为链接系统的 libpcap
+/// (`opkg install libpcap`)，交叉编译时把 SDK 中的库目录加入链接路径即可。
+/// Windows 上链接 Npcap 的 wpcap（安装 Npcap 时勾选 802.11 原始帧支持，构建需要 Npcap SDK），
+/// macOS 上链接系统自带的 libpcap。
+#[allow(non_camel_case_types)]
+mod ffi {
+    use std::ffi::{c_char, c_int, c_uint};
+
+    pub enum pcap_t {}
+
+    #[repr(C)]
+    pub struct bpf_program {
+        pub bf_len: c_uint,
+        pub bf_insns: *const crate::bpf::Instruction,
+    }
+
+    #[repr(C)]
+    pub struct pcap_pkthdr {
+        pub ts: libc::timeval,
+        pub caplen: c_uint,
+        pub len: c_uint,
+    }
+
+    pub const PCAP_ERRBUF_SIZE: usize = 256;
+    pub const PCAP_ERROR_NO_SUCH_DEVICE: c_int = -5;
+    pub const PCAP_ERROR_RFMON_NOTSUP: c_int = -6;
+    pub const PCAP_ERROR_PERM_DENIED: c_int = -8;
+    pub const PCAP_ERROR_IFACE_NOT_UP: c_int = -9;
+
+    #[cfg_attr(windows, link(name = "wpcap"))]
+    #[cfg_attr(not(windows), link(name = "pcap"))]
+    unsafe extern "C" {
+        pub fn pcap_create(source: *const c_char, errbuf: *mut c_char) -> *mut pcap_t;
+        pub fn pcap_set_snaplen(p: *mut pcap_t, snaplen: c_int) -> c_int;
+        pub fn pcap_set_timeout(p: *mut pcap_t, to_ms: c_int) -> c_int;
+        pub fn pcap_set_immediate_mode(p: *mut pcap_t, immediate: c_int) -> c_int;
+        pub fn pcap_set_rfmon(p: *mut pcap_t, rfmon: c_int) -> c_int;
+        pub fn pcap_activate(p: *mut pcap_t) -> c_int;
+        pub fn pcap_datalink(p: *mut pcap_t) -> c_int;
+        pub fn pcap_set_datalink(p: *mut pcap_t, dlt: c_int) -> c_int;
+        pub fn pcap_setfilter(p: *mut pcap_t, fp: *const bpf_program) -> c_int;
+        pub fn pcap_next_ex(p: *mut pcap_t, header: *mut *mut pcap_pkthdr, data: *mut *const u8) -> c_int;
+        pub fn pcap_geterr(p: *mut pcap_t) -> *mut c_char;
+        pub fn pcap_close(p: *mut pcap_t);
+    }
+}
+
+const SNAPLEN: c_int = 65535;
+/// 未设置读超时时 libpcap 的轮询间隔，超时后继续等待
+const POLL_MS: c_int = 1000;
+/// 是否在打开时请求监听模式 (rfmon)
+const RFMON: bool = cfg!(not(target_os = "linux"));
+
+/// 基于 libpcap 的监听网卡抓包
+pub struct PcapCapture {
+    handle: *mut ffi::pcap_t,
+    read_timeout: Option<Duration>,
+}
+
+impl PcapCapture {
+    /// 打开监听网卡，要求链路类型为 radiotap；`filter` 为挂载到抓包句柄上的 BPF 过滤程序
+    pub fn open(interface: &str, read_timeout: Option<Duration>, filter: Option<&[Instruction]>) -> Result<Self, CaptureError> {
+        let name = CString::new(interface)
+            .map_err(|e| CaptureError::from_io(interface, io::Error::new(io::ErrorKind::InvalidInput, e)))?;
+        let mut errbuf = [0 as c_char; ffi::PCAP_ERRBUF_SIZE];
+        let handle = unsafe { ffi::pcap_create(name.as_ptr(), errbuf.as_mut_ptr()) };
+        if handle.is_null() {
+            let message = unsafe { CStr::from_ptr(errbuf.as_ptr()) }.to_string_lossy().into_owned();
+            return Err(CaptureError::from_io(interface, io::Error::other(message)));
+        }
+        // 先交给 PcapCapture，出错返回时由 Drop 关闭句柄
+        let capture = Self { handle, read_timeout };
+        let timeout_ms = read_timeout.map_or(POLL_MS, |t| t.as_millis().clamp(1, c_int::MAX as u128) as c_int);
+        let status = unsafe {
+            ffi::pcap_set_snaplen(handle, SNAPLEN);
+            ffi::pcap_set_timeout(handle, timeout_ms);
+            ffi::pcap_set_immediate_mode(handle, 1);
+            // Linux 上由 --monitor 或 iw 预先设好监听模式；Windows/macOS 由 libpcap 打开 rfmon
+            if RFMON {
+                ffi::pcap_set_rfmon(handle, 1);
+            }
+            ffi::pcap_activate(handle)
+        };
+        if status < 0 {
+            let source = io::Error::other(capture.last_error());
+            return Err(match status {
+                ffi::PCAP_ERROR_PERM_DENIED => CaptureError::PermissionDenied { interface: interface.to_string() },
+                ffi::PCAP_ERROR_RFMON_NOTSUP => CaptureError::UnsupportedChannelType { interface: interface.to_string() },
+                ffi::PCAP_ERROR_NO_SUCH_DEVICE | ffi::PCAP_ERROR_IFACE_NOT_UP =>
+                    CaptureError::DeviceGone { interface: interface.to_string(), source },
+                _ => CaptureError::IoError { interface: interface.to_string(), source },
+            });
+        }
+        // macOS 与 Npcap 在监听模式下可能默认给出不带 radiotap 的 802.11 链路类型
+        let radiotap = LINKTYPE_RADIOTAP as c_int;
+        if unsafe { ffi::pcap_datalink(handle) != radiotap && ffi::pcap_set_datalink(handle, radiotap) < 0 } {
+            return Err(CaptureError::UnsupportedChannelType { interface: interface.to_string() });
+        }
+        if let Some(filter) = filter {
+            let program = ffi::bpf_program { bf_len: filter.len() as c_uint, bf_insns: filter.as_ptr() };
+            // libpcap 复制程序后交给内核，返回后无需保留
+            if unsafe { ffi::pcap_setfilter(handle, &program) } < 0 {
+                return Err(CaptureError::IoError { interface: interface.to_string(), source: io::Error::other(capture.last_error()) });
+            }
+        }
+        Ok(capture)
+    }
+
+    fn last_error(&self) -> String {
+        unsafe { CStr::from_ptr(ffi::pcap_geterr(self.handle)) }.to_string_lossy().into_owned()
+    }
+}
+
+impl FrameSource for PcapCapture {
+    fn next_frame(&mut self) -> io::Result<&[u8]> {
+        loop {
+            let mut header = std::ptr::null_mut();
+            let mut data = std::ptr::null();
+            match unsafe { ffi::pcap_next_ex(self.handle, &mut header, &mut data) } {
+                1 => {
+                    // 数据在下一次调用 pcap_next_ex 之前有效，借用期限与 &mut self 一致
+                    let caplen = unsafe { (*header).caplen } as usize;
+                    return Ok(unsafe { std::slice::from_raw_parts(data, caplen) });
+                }
+                0 if self.read_timeout.is_some() => return Err(io::ErrorKind::TimedOut.into()),
+                0 => continue,
+                _ => return Err(io::Error::other(self.last_error())),
+            }
+        }
+    }
+}
+
+impl Drop for PcapCapture {
+    fn drop(&mut self) {
+        unsafe { ffi::pcap_close(self.handle) }
+    }
+}
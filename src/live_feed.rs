@@ -0,0 +1,273 @@
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use chrono::SecondsFormat;
+use serde_json::{json, Value};
+use tracing::{error, info};
+
+use crate::event_log::DecodedEvent;
+use crate::geo::degrees;
+use crate::position;
+use crate::time_format;
+
+/// 连接时快照中保留的最长未更新时间，与实时图层一致
+const MAX_AGE_MS: i64 = 5 * 60 * 1000;
+
+/// 推送给地图页面的一条轨迹
+fn track_json(uas_id: &str, event: &DecodedEvent) -> Value {
+    let r = &event.record;
+    json!({
+        "uas_id": uas_id,
+        "track_id": r.track_id,
+        "lat": degrees(r.latitude),
+        "lon": degrees(r.longitude),
+        "altitude_m": r.geometric_altitude,
+        "ground_speed_kt": r.ground_speed_knots(),
+        "track_angle": r.track_angle as u16 + if r.track_direction { 180 } else { 0 },
+        "ua_type": r.ua_type,
+        "rssi": r.rssi,
+        "position_status": position::classify(r),
+        "operator_distance_m": r.operator_distance_m,
+        "label": r.annotation.as_ref().map(|a| a.label()),
+        "last_seen": time_format::format_ms(event.received_at_ms, SecondsFormat::Millis),
+        "received_at_ms": event.received_at_ms,
+    })
+}
+
+/// 一条 Server-Sent Events 消息
+fn sse_message(kind: &str, data: &Value) -> String {
+    format!("event: {}\ndata: {}\n\n", kind, data)
+}
+
+#[derive(Default)]
+struct FeedState {
+    /// UAS ID → (接收时间, 轨迹 JSON)
+    tracks: HashMap<String, (i64, Value)>,
+    clients: Vec<TcpStream>,
+}
+
+impl FeedState {
+    /// 新连接先收到的快照：当前跟踪中的全部无人机，按 UAS ID 排列
+    fn snapshot(&mut self, now_ms: i64) -> String {
+        self.tracks.retain(|_, (received_at_ms, _)| now_ms - *received_at_ms <= MAX_AGE_MS);
+        let mut ids: Vec<&String> = self.tracks.keys().collect();
+        ids.sort();
+        let tracks: Vec<&Value> = ids.into_iter().map(|id| &self.tracks[id].1).collect();
+        sse_message("snapshot", &json!(tracks))
+    }
+
+    fn broadcast(&mut self, message: &str) {
+        // 写失败的客户端视为已断开
+        self.clients.retain_mut(|client| client.write_all(message.as_bytes()).is_ok());
+    }
+}
+
+/// 实时检测推送：地图页面通过 `GET /events` (Server-Sent Events) 订阅
+///
+/// 连接后先收到 `snapshot` 事件（当前跟踪中的无人机数组），之后每条新的或更新的轨迹推送一条 `track` 事件，
+/// 无人机离开时推送 `expired` 事件。浏览器端直接用 `EventSource` 接收，断线后自动重连并重新取得快照。
+#[derive(Clone)]
+pub struct LiveFeed {
+    state: Arc<Mutex<FeedState>>,
+}
+
+impl LiveFeed {
+    pub fn listen(addr: &str) -> std::io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        info!("live detection feed on http://{}/events", addr);
+        let feed = Self { state: Arc::new(Mutex::new(FeedState::default())) };
+        let accepted = feed.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                match stream {
+                    Ok(stream) => accepted.accept(stream),
+                    Err(e) => error!("实时推送连接失败: {}", e),
+                }
+            }
+        });
+        Ok(feed)
+    }
+
+    fn accept(&self, mut stream: TcpStream) {
+        let mut buf = [0u8; 1024];
+        let len = stream.read(&mut buf).unwrap_or(0);
+        let request = String::from_utf8_lossy(&buf[..len]);
+        let target = request.lines().next().and_then(|l| l.split_whitespace().nth(1)).unwrap_or_default();
+        if target.split('?').next() != Some("/events") {
+            let _ = write!(stream, "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n");
+            return;
+        }
+        // 慢客户端不能阻塞解码线程
+        let _ = stream.set_write_timeout(Some(Duration::from_millis(200)));
+        let mut state = self.state.lock().unwrap();
+        let snapshot = state.snapshot(crate::clock::now_ms().0);
+        let header = "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\n\
+            Access-Control-Allow-Origin: *\r\nConnection: keep-alive\r\n\r\n";
+        if stream.write_all(header.as_bytes()).and_then(|_| stream.write_all(snapshot.as_bytes())).is_ok() {
+            info!("live feed client connected: {:?}", stream.peer_addr().ok());
+            state.clients.push(stream);
+        }
+    }
+
+    /// 推送一条新的或更新的轨迹
+    pub fn publish(&self, event: &DecodedEvent) {
+        let r = &event.record;
+        if position::classify(r) == position::PositionStatus::NoFix {
+            return;
+        }
+        let uas_id = if r.rid.is_empty() { &r.track_id } else { &r.rid };
+        let track = track_json(uas_id, event);
+        let message = sse_message("track", &track);
+        let mut state = self.state.lock().unwrap();
+        state.tracks.insert(uas_id.clone(), (event.received_at_ms, track));
+        state.broadcast(&message);
+    }
+
+    /// 无人机离开跟踪
+    pub fn expire(&self, uas_id: &str) {
+        let mut state = self.state.lock().unwrap();
+        if state.tracks.remove(uas_id).is_some() {
+            state.broadcast(&sse_message("expired", &json!({ "uas_id": uas_id })));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::upload_data::UploadData;
+
+    use std::io::{BufRead, BufReader};
+
+    fn feed() -> LiveFeed {
+        LiveFeed { state: Arc::new(Mutex::new(FeedState::default())) }
+    }
+
+    fn event(rid: &str, received_at_ms: i64) -> DecodedEvent {
+        DecodedEvent {
+            received_at_ms,
+            record: UploadData { rid: rid.into(), latitude: 312_000_000, longitude: 1_214_000_000, ..Default::default() },
+        }
+    }
+
+    fn snapshot(feed: &LiveFeed, now_ms: i64) -> Value {
+        let snapshot = feed.state.lock().unwrap().snapshot(now_ms);
+        serde_json::from_str(snapshot.strip_prefix("event: snapshot\ndata: ").unwrap().trim_end()).unwrap()
+    }
+
+    fn uas_ids(snapshot: &Value) -> Vec<&str> {
+        snapshot.as_array().unwrap().iter().map(|t| t["uas_id"].as_str().unwrap()).collect()
+    }
+
+    /// 建立一个已被 [`LiveFeed::accept`] 处理的连接，返回客户端一侧
+    fn connect(feed: &LiveFeed, request: &str) -> BufReader<TcpStream> {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        client.write_all(request.as_bytes()).unwrap();
+        client.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+        feed.accept(listener.accept().unwrap().0);
+        BufReader::new(client)
+    }
+
+    /// 读到空行为止
+    fn read_block(client: &mut BufReader<TcpStream>) -> Vec<String> {
+        let mut lines = Vec::new();
+        loop {
+            let mut line = String::new();
+            client.read_line(&mut line).unwrap();
+            let line = line.trim_end().to_string();
+            if line.is_empty() {
+                return lines;
+            }
+            lines.push(line);
+        }
+    }
+
+    #[test]
+    fn test_track_json() {
+        let mut event = event("RID-A", 1_000);
+        event.record.track_direction = true;
+        event.record.track_angle = 90;
+        event.record.geometric_altitude = 120;
+        let track = track_json("RID-A", &event);
+        assert_eq!((track["lat"].as_f64(), track["lon"].as_f64()), (Some(31.2), Some(degrees(1_214_000_000))));
+        assert_eq!((track["track_angle"].as_u64(), track["altitude_m"].as_i64()), (Some(270), Some(120)));
+        assert_eq!(track["last_seen"], "1970-01-01T00:00:01.000Z");
+        assert_eq!(track["received_at_ms"], 1_000);
+    }
+
+    #[test]
+    fn test_snapshot_sorted_by_uas_id() {
+        let feed = feed();
+        feed.publish(&event("RID-B", 1_000));
+        feed.publish(&event("RID-A", 2_000));
+        feed.publish(&event("RID-B", 2_500));   // 同一架无人机只保留最新一条
+        let snapshot = snapshot(&feed, 3_000);
+        assert_eq!(uas_ids(&snapshot), ["RID-A", "RID-B"]);
+        assert_eq!(snapshot[1]["received_at_ms"], 2_500);
+    }
+
+    #[test]
+    fn test_track_id_when_no_uas_id() {
+        let feed = feed();
+        let mut event = event("", 1_000);
+        event.record.track_id = "aa:bb:cc:dd:ee:ff".into();
+        feed.publish(&event);
+        assert_eq!(uas_ids(&snapshot(&feed, 1_000)), ["aa:bb:cc:dd:ee:ff"]);
+    }
+
+    #[test]
+    fn test_records_without_fix_are_not_published() {
+        let feed = feed();
+        feed.publish(&DecodedEvent { received_at_ms: 2_000, record: UploadData { rid: "RID-C".into(), ..Default::default() } });
+        assert_eq!(snapshot(&feed, 2_000), json!([]));
+    }
+
+    #[test]
+    fn test_expired_tracks_leave_snapshot() {
+        let feed = feed();
+        feed.publish(&event("RID-A", 1_000));
+        feed.publish(&event("RID-B", 1_000));
+        feed.expire("RID-B");
+        feed.expire("RID-Z");
+        assert_eq!(uas_ids(&snapshot(&feed, 1_000)), ["RID-A"]);
+    }
+
+    #[test]
+    fn test_stale_tracks_leave_snapshot() {
+        let feed = feed();
+        feed.publish(&event("RID-A", 2_000));
+        assert_eq!(uas_ids(&snapshot(&feed, 2_000 + MAX_AGE_MS)), ["RID-A"]);
+        assert_eq!(feed.state.lock().unwrap().snapshot(2_000 + MAX_AGE_MS + 1), "event: snapshot\ndata: []\n\n");
+    }
+
+    #[test]
+    fn test_subscriber_gets_snapshot_then_updates() {
+        let feed = feed();
+        feed.publish(&event("RID-A", crate::clock::now_ms().0));
+        let mut client = connect(&feed, "GET /events?since=0 HTTP/1.1\r\n\r\n");
+        let header = read_block(&mut client);
+        assert_eq!(header[0], "HTTP/1.1 200 OK");
+        assert!(header.contains(&"Content-Type: text/event-stream".to_string()));
+        let snapshot = read_block(&mut client);
+        assert_eq!(snapshot[0], "event: snapshot");
+        assert!(snapshot[1].contains(r#""uas_id":"RID-A""#), "{:?}", snapshot);
+
+        feed.publish(&event("RID-B", 1_000));
+        assert_eq!(read_block(&mut client)[0], "event: track");
+        feed.expire("RID-A");
+        assert_eq!(read_block(&mut client), ["event: expired", r#"data: {"uas_id":"RID-A"}"#]);
+    }
+
+    #[test]
+    fn test_unknown_path() {
+        let feed = feed();
+        let mut client = connect(&feed, "GET / HTTP/1.1\r\n\r\n");
+        assert_eq!(read_block(&mut client), ["HTTP/1.1 404 Not Found", "Content-Length: 0"]);
+        assert!(feed.state.lock().unwrap().clients.is_empty());
+    }
+}
@@ -0,0 +1,386 @@
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use chrono::{SecondsFormat, Utc};
+use serde_json::{json, Value};
+use tracing::{error, info};
+
+use crate::aggregate::{self, AggregateCache, AggregateQuery};
+use crate::event_log::DecodedEvent;
+use crate::fleet::Annotation;
+use crate::geo::{degrees, distance_m};
+use crate::geofence::Zone;
+use crate::playback::parse_timestamp_ms;
+use crate::position::{self, PositionStatus};
+use crate::remote_id::ua_type_style;
+use crate::storage::{FixQuery, Store};
+use crate::time_format;
+
+/// 每架无人机的最新位置
+struct LivePoint {
+    received_at_ms: i64,
+    lat: f64,
+    lon: f64,
+    altitude_m: f64,
+    ua_type: Option<u8>,
+    track_id: String,
+    annotation: Option<Annotation>,
+    position_status: PositionStatus,
+    /// 系统消息中最近一次有效的控制站位置 (纬度, 经度)
+    operator: Option<(f64, f64)>,
+}
+
+/// 实时无人机图层，以 GeoJSON 提供给 QGIS 等 GIS 软件定时刷新
+///
+/// 每架无人机一个 Point 要素，属性中带样式提示：`color` 按 UA 类型着色（机队标注表中指定了颜色时优先），
+/// `opacity` 随最后一次更新的时间由 1.0 线性降到 0.2。超过 `max_age_ms` 未更新的无人机不再输出。
+/// 已知控制站位置时另输出一个 `kind = "operator"` 的 Point 要素，颜色与无人机相同，
+/// 无人机要素的 `kind` 为 `"drone"`。
+#[derive(Clone)]
+pub struct LiveLayer {
+    points: Arc<Mutex<HashMap<String, LivePoint>>>,
+    max_age_ms: i64,
+    basemap: Option<Arc<String>>,
+    history: Option<PathBuf>,
+    site: Option<(f64, f64)>,
+    zones: Arc<Vec<Zone>>,
+    aggregates: Arc<Mutex<AggregateCache>>,
+}
+
+/// 内置的态势页面，不依赖在线瓦片
+const DASHBOARD: &str = include_str!("../assets/dashboard.html");
+
+impl LiveLayer {
+    pub fn new(max_age_ms: i64) -> Self {
+        Self {
+            points: Arc::new(Mutex::new(HashMap::new())),
+            max_age_ms,
+            basemap: None,
+            history: None,
+            site: None,
+            zones: Arc::new(Vec::new()),
+            aggregates: Arc::new(Mutex::new(AggregateCache::new(Self::AGGREGATE_TTL_MS))),
+        }
+    }
+
+    /// 态势页面回放历史时查询的数据库
+    pub fn with_history(mut self, store: PathBuf) -> Self {
+        self.history = Some(store);
+        self
+    }
+
+    /// 接收站位置（度），看板按到该点的距离由近到远排列
+    pub fn with_site(mut self, lat: f64, lon: f64) -> Self {
+        self.site = Some((lat, lon));
+        self
+    }
+
+    /// `GET /api/aggregate?group_by=zone` 统计使用的区域
+    pub fn with_zones(mut self, zones: Vec<Zone>) -> Self {
+        self.zones = Arc::new(zones);
+        self
+    }
+
+    /// 态势页面使用的离线底图：国界/行政区划等轮廓的 GeoJSON（Polygon/LineString 要素）
+    pub fn with_basemap(mut self, geojson: String) -> Self {
+        self.basemap = Some(Arc::new(geojson));
+        self
+    }
+
+    pub fn update(&self, event: &DecodedEvent) {
+        let r = &event.record;
+        let position_status = position::classify(r);
+        if position_status == PositionStatus::NoFix {
+            return;
+        }
+        let id = if r.rid.is_empty() { r.track_id.clone() } else { r.rid.clone() };
+        let mut points = self.points.lock().unwrap();
+        let ua_type = r.ua_type.or_else(|| points.get(&id).and_then(|p| p.ua_type));
+        let operator = position::operator_coordinates(r).or_else(|| points.get(&id).and_then(|p| p.operator));
+        points.insert(id, LivePoint {
+            received_at_ms: event.received_at_ms,
+            lat: degrees(r.latitude),
+            lon: degrees(r.longitude),
+            altitude_m: r.geometric_altitude as f64,
+            ua_type,
+            track_id: r.track_id.clone(),
+            annotation: r.annotation.clone(),
+            position_status,
+            operator,
+        });
+    }
+
+    pub fn to_geojson(&self, now_ms: i64) -> Value {
+        let mut points = self.points.lock().unwrap();
+        points.retain(|_, p| now_ms - p.received_at_ms <= self.max_age_ms);
+        let mut ids: Vec<&String> = points.keys().collect();
+        ids.sort();
+        let features: Vec<Value> = ids.into_iter().flat_map(|id| {
+            let p = &points[id];
+            let age_ms = (now_ms - p.received_at_ms).max(0);
+            let (ua_type, color) = ua_type_style(p.ua_type);
+            let color = p.annotation.as_ref().and_then(|a| a.color.as_deref()).unwrap_or(color);
+            let opacity = 1.0 - 0.8 * (age_ms as f64 / self.max_age_ms as f64).min(1.0);
+            let opacity = (opacity * 100.0).round() / 100.0;
+            let label = p.annotation.as_ref().map_or(id.clone(), |a| a.label());
+            let operator = p.operator.map(|(lat, lon)| json!({
+                "type": "Feature",
+                "id": format!("{}/operator", id),
+                "geometry": { "type": "Point", "coordinates": [lon, lat] },
+                "properties": {
+                    "kind": "operator",
+                    "uas_id": id,
+                    "label": format!("{} 控制站", label),
+                    "color": color,
+                    "opacity": opacity,
+                },
+            }));
+            let drone = json!({
+                "type": "Feature",
+                "id": id,
+                "geometry": { "type": "Point", "coordinates": [p.lon, p.lat, p.altitude_m] },
+                "properties": {
+                    "kind": "drone",
+                    "uas_id": id,
+                    "label": label,
+                    "track_id": p.track_id,
+                    "ua_type": ua_type,
+                    "altitude_m": p.altitude_m,
+                    "last_seen": time_format::format_ms(p.received_at_ms, SecondsFormat::Secs),
+                    "age_s": age_ms / 1000,
+                    "color": color,
+                    "opacity": opacity,
+                    "position_status": p.position_status,
+                },
+            });
+            std::iter::once(drone).chain(operator)
+        }).collect();
+        json!({ "type": "FeatureCollection", "features": features })
+    }
+
+    /// 入口看板用的精简列表，只含显示字段，直接取内存中的最新位置，不查询数据库
+    ///
+    /// 配置了接收站位置时按距离由近到远排列，否则按最后更新时间由新到旧排列。
+    pub fn board(&self, now_ms: i64) -> Value {
+        let points = self.points.lock().unwrap();
+        let mut rows: Vec<(Option<f64>, i64, Value)> = points.iter()
+            .filter(|(_, p)| now_ms - p.received_at_ms <= self.max_age_ms)
+            .map(|(id, p)| {
+                let distance = self.site.map(|(lat, lon)| distance_m(lat, lon, p.lat, p.lon));
+                let age_ms = (now_ms - p.received_at_ms).max(0);
+                let (ua_type, color) = ua_type_style(p.ua_type);
+                let row = json!({
+                    "label": p.annotation.as_ref().map_or(id.clone(), |a| a.label()),
+                    "type": ua_type,
+                    "dist_m": distance.map(|d| d.round()),
+                    "alt_m": p.altitude_m.round(),
+                    "age_s": age_ms / 1000,
+                    "color": p.annotation.as_ref().and_then(|a| a.color.as_deref()).unwrap_or(color),
+                });
+                (distance, age_ms, row)
+            })
+            .collect();
+        rows.sort_by(|(da, aa, _), (db, ab, _)| match (da, db) {
+            (Some(da), Some(db)) => da.total_cmp(db),
+            _ => aa.cmp(ab),
+        });
+        json!({ "drones": rows.into_iter().map(|(_, _, row)| row).collect::<Vec<_>>() })
+    }
+
+    /// 按请求路径返回 (状态, Content-Type, 内容)
+    fn respond(&self, target: &str, now_ms: i64) -> (&'static str, &'static str, String) {
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        match (path, &self.basemap) {
+            ("/", _) => ("200 OK", "text/html; charset=utf-8", DASHBOARD.to_string()),
+            ("/drones.geojson", _) => ("200 OK", "application/geo+json", self.to_geojson(now_ms).to_string()),
+            ("/api/board", _) => ("200 OK", "application/json", self.board(now_ms).to_string()),
+            ("/basemap.geojson", Some(basemap)) => ("200 OK", "application/geo+json", basemap.to_string()),
+            ("/history", _) if self.history.is_some() => self.history(query),
+            ("/api/aggregate", _) if self.history.is_some() => self.aggregate(query, now_ms),
+            _ => ("404 Not Found", "text/plain", String::new()),
+        }
+    }
+
+    /// `GET /history?from=<毫秒或 RFC 3339>&to=...`：时间段内的全部定位，供页面按时间滑块回放
+    fn history(&self, query: &str) -> (&'static str, &'static str, String) {
+        let param = |name: &str| query.split('&')
+            .find_map(|kv| kv.strip_prefix(name)?.strip_prefix('='))
+            .and_then(parse_timestamp_ms);
+        let (Some(from_ms), Some(to_ms)) = (param("from"), param("to")) else {
+            return ("400 Bad Request", "application/json", json!({ "error": "需要 from 和 to 参数" }).to_string());
+        };
+        let fixes = FixQuery { from_ms: Some(from_ms), to_ms: Some(to_ms), limit: Some(Self::HISTORY_LIMIT), ..Default::default() };
+        let events = self.history.as_ref()
+            .ok_or_else(|| "未配置数据库".to_string())
+            .and_then(|path| Store::open(path).map_err(|e| e.to_string()))
+            .and_then(|store| store.query(&fixes).map_err(|e| e.to_string()));
+        match events {
+            Ok(events) => ("200 OK", "application/json", history_frames(&events).to_string()),
+            Err(e) => {
+                error!("查询历史记录失败: {}", e);
+                ("500 Internal Server Error", "application/json", json!({ "error": e }).to_string())
+            }
+        }
+    }
+
+    const HISTORY_LIMIT: usize = 100_000;
+
+    /// `GET /api/aggregate?from=&to=&bucket=hour|day&group_by=ua_type|operator|zone`：
+    /// 按时段统计无人机数和定位数，外部看板画简单图表时不必拉取原始定位
+    fn aggregate(&self, query: &str, now_ms: i64) -> (&'static str, &'static str, String) {
+        let query = match AggregateQuery::parse(query, now_ms) {
+            Ok(query) => query,
+            Err(e) => return ("400 Bad Request", "application/json", json!({ "error": e }).to_string()),
+        };
+        let result: Result<_, String> = self.aggregates.lock().unwrap().get_or_compute(&query, now_ms, || {
+            let path = self.history.as_ref().ok_or_else(|| "未配置数据库".to_string())?;
+            let store = Store::open(path).map_err(|e| e.to_string())?;
+            let samples = store.samples(query.from_ms, query.to_ms).map_err(|e| e.to_string())?;
+            Ok(aggregate::aggregate(&samples, &query, &self.zones))
+        });
+        match result {
+            Ok(result) => ("200 OK", "application/json", result.to_string()),
+            Err(e) => {
+                error!("聚合统计失败: {}", e);
+                ("500 Internal Server Error", "application/json", json!({ "error": e }).to_string())
+            }
+        }
+    }
+
+    /// 聚合结果的缓存时间
+    const AGGREGATE_TTL_MS: i64 = 60_000;
+
+    /// 通过 HTTP 提供 `GET /drones.geojson`、态势页面 `GET /`、离线底图 `GET /basemap.geojson`、
+    /// 历史回放数据 `GET /history`、入口看板 `GET /api/board` 及聚合统计 `GET /api/aggregate`
+    pub fn spawn_http_listener(&self, addr: String) {
+        let layer = self.clone();
+        thread::spawn(move || {
+            let listener = match TcpListener::bind(&addr) {
+                Ok(listener) => listener,
+                Err(e) => {
+                    error!("无法监听 GeoJSON 图层 {}: {}", addr, e);
+                    return;
+                }
+            };
+            info!("serving live layer on http://{}/drones.geojson (dashboard at /)", addr);
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else { continue };
+                let mut buf = [0u8; 1024];
+                let len = stream.read(&mut buf).unwrap_or(0);
+                let request = String::from_utf8_lossy(&buf[..len]);
+                let target = request.lines().next().and_then(|l| l.split_whitespace().nth(1)).unwrap_or_default();
+                let (status, content_type, body) = layer.respond(target, Utc::now().timestamp_millis());
+                let _ = write!(stream, "HTTP/1.1 {}\r\nContent-Type: {}\r\n\
+                    Access-Control-Allow-Origin: *\r\nCache-Control: no-cache\r\nContent-Length: {}\r\n\r\n{}",
+                    status, content_type, body.len(), body);
+            }
+        });
+    }
+}
+
+/// 历史定位按时间排列的帧：`{"frames": [{"t", "id", "lon", "lat", "alt", "color", "label"}]}`
+///
+/// 与实时图层一样，没有 Basic ID 的报文沿用该无人机此前的 UA 类型着色。
+pub fn history_frames(events: &[DecodedEvent]) -> Value {
+    let mut ua_types: HashMap<String, u8> = HashMap::new();
+    let frames: Vec<Value> = events.iter()
+        .filter(|e| e.record.latitude != 0 || e.record.longitude != 0)
+        .map(|event| {
+            let r = &event.record;
+            let id = if r.rid.is_empty() { r.track_id.clone() } else { r.rid.clone() };
+            if let Some(ua_type) = r.ua_type {
+                ua_types.insert(id.clone(), ua_type);
+            }
+            let (_, color) = ua_type_style(ua_types.get(&id).copied());
+            let color = r.annotation.as_ref().and_then(|a| a.color.as_deref()).unwrap_or(color);
+            json!({
+                "t": event.received_at_ms,
+                "label": r.annotation.as_ref().map_or(id.clone(), |a| a.label()),
+                "id": id,
+                "lon": degrees(r.longitude),
+                "lat": degrees(r.latitude),
+                "alt": r.geometric_altitude,
+                "color": color,
+            })
+        })
+        .collect();
+    json!({ "frames": frames })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::upload_data::{OperatorPosition, UploadData};
+
+    #[test]
+    fn test_live_layer_styles() {
+        let layer = LiveLayer::new(300_000);
+        let fix = |rid: &str, at, ua_type| DecodedEvent {
+            received_at_ms: at,
+            record: UploadData { rid: rid.into(), latitude: 312_000_000, longitude: 1_214_000_000, ua_type, ..Default::default() },
+        };
+        layer.update(&fix("A", 0, Some(2)));
+        layer.update(&fix("B", 150_000, None));
+        layer.update(&fix("A", 100_000, None));   // 后续报文没有 Basic ID 时保留已知类型
+
+        let geojson = layer.to_geojson(250_000);
+        let features = geojson["features"].as_array().unwrap();
+        assert_eq!(features.len(), 2);
+        assert_eq!(features[0]["properties"]["ua_type"], "multirotor");
+        assert_eq!(features[0]["properties"]["opacity"], 0.6);
+        assert_eq!(features[1]["properties"]["color"], "#ff00ff");
+
+        let board = layer.board(250_000);
+        assert_eq!(board["drones"][0]["label"], "B");   // 未配置位置时最近更新的排在前面
+        assert!(board["drones"][0]["dist_m"].is_null());
+        let layer = layer.with_site(31.3, 121.4);
+        layer.update(&DecodedEvent {
+            received_at_ms: 0,
+            record: UploadData { rid: "C".into(), latitude: 313_000_000, longitude: 1_214_000_000, ..Default::default() },
+        });
+        let board = layer.board(250_000);
+        assert_eq!(board["drones"][0]["label"], "C");
+        assert_eq!(board["drones"][0]["dist_m"], 0.0);
+        assert_eq!(layer.respond("/api/board", 250_000).0, "200 OK");
+
+        assert_eq!(layer.to_geojson(500_000)["features"].as_array().unwrap().len(), 0);
+
+        assert_eq!(layer.respond("/", 0).1, "text/html; charset=utf-8");
+        assert_eq!(layer.respond("/basemap.geojson", 0).0, "404 Not Found");
+        let layer = layer.with_basemap(r#"{"type":"FeatureCollection","features":[]}"#.into());
+        assert_eq!(layer.respond("/basemap.geojson", 0).0, "200 OK");
+        assert_eq!(layer.respond("/history?from=0&to=1", 0).0, "404 Not Found");
+        assert_eq!(layer.respond("/api/aggregate", 0).0, "404 Not Found");
+
+        let frames = history_frames(&[fix("A", 0, Some(2)), fix("A", 1_000, None)]);
+        assert_eq!(frames["frames"][1]["color"], "#d62728");
+        assert_eq!(frames["frames"][1]["t"], 1_000);
+    }
+
+    #[test]
+    fn test_operator_feature() {
+        let layer = LiveLayer::new(300_000);
+        let operator = OperatorPosition { location_type: 1, latitude: 312_010_000, longitude: 1_214_010_000, altitude: 0 };
+        let fix = |at, operator| DecodedEvent {
+            received_at_ms: at,
+            record: UploadData { rid: "A".into(), latitude: 312_000_000, longitude: 1_214_000_000, operator, ..Default::default() },
+        };
+        layer.update(&fix(0, None));
+        assert_eq!(layer.to_geojson(0)["features"].as_array().unwrap().len(), 1);
+
+        layer.update(&fix(1_000, Some(operator)));
+        layer.update(&fix(2_000, None));   // 没有系统消息的报文保留已知的控制站位置
+        let geojson = layer.to_geojson(2_000);
+        let features = geojson["features"].as_array().unwrap();
+        assert_eq!(features.len(), 2);
+        assert_eq!(features[0]["properties"]["kind"], "drone");
+        assert_eq!(features[1]["id"], "A/operator");
+        assert_eq!(features[1]["properties"]["kind"], "operator");
+        assert_eq!(features[1]["properties"]["color"], features[0]["properties"]["color"]);
+        assert_eq!(features[1]["geometry"]["coordinates"], json!([degrees(1_214_010_000), degrees(312_010_000)]));
+    }
+}
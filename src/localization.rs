@@ -0,0 +1,204 @@
+use std::collections::HashMap;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::geo::{distance_m, LocalProjection};
+use crate::rssi::PathLossModel;
+use crate::upload_data::UploadData;
+
+/// 某个位置已知的传感器对同一架无人机的观测
+#[derive(Debug, Clone, Copy)]
+pub struct SensorObservation {
+    pub sensor_lat: f64,
+    pub sensor_lon: f64,
+    pub rssi: Option<f32>,
+    pub bearing_deg: Option<f32>,
+}
+
+/// 定位方法
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum LocalizationMethod {
+    BearingIntersection,   // 两个及以上方位线交会
+    RssiCentroid,          // 按 RSSI 估算距离加权的质心
+}
+
+/// 多传感器粗定位结果
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct PositionEstimate {
+    pub latitude: f64,               // 纬度 (度)
+    pub longitude: f64,              // 经度 (度)
+    pub method: LocalizationMethod,
+    pub sensors: u32,                // 参与估计的接收站数
+}
+
+impl PositionEstimate {
+    /// 与广播位置比较得到的仿冒评分 (0-1)
+    ///
+    /// 偏差超过该定位方法的典型误差时评分上升，达到两倍误差时为 1。
+    pub fn spoof_score(&self, claimed_lat: f64, claimed_lon: f64) -> f32 {
+        let tolerance = match self.method {
+            LocalizationMethod::BearingIntersection => 300.0,
+            LocalizationMethod::RssiCentroid => 1000.0,
+        };
+        let deviation = distance_m(self.latitude, self.longitude, claimed_lat, claimed_lon);
+        ((deviation - tolerance) / tolerance).clamp(0.0, 1.0) as f32
+    }
+}
+
+/// 由两个及以上传感器的观测估计无人机位置
+///
+/// 有至少两条方位线时做最小二乘交会；否则按路径损耗模型把 RSSI 换算为距离，
+/// 以距离平方的倒数为权重求传感器位置的加权质心。
+pub fn estimate_position(observations: &[SensorObservation], model: &PathLossModel) -> Option<PositionEstimate> {
+    if observations.len() < 2 {
+        return None;
+    }
+    let n = observations.len() as f64;
+    let origin_lat = observations.iter().map(|o| o.sensor_lat).sum::<f64>() / n;
+    let origin_lon = observations.iter().map(|o| o.sensor_lon).sum::<f64>() / n;
+    let projection = LocalProjection::new(origin_lat, origin_lon);
+
+    let bearings: Vec<((f64, f64), f64)> = observations.iter()
+        .filter_map(|o| o.bearing_deg.map(|b| (projection.to_local(o.sensor_lat, o.sensor_lon), b as f64)))
+        .collect();
+    if bearings.len() >= 2
+        && let Some((x, y)) = intersect_bearings(&bearings)
+    {
+        let (latitude, longitude) = projection.to_geo(x, y);
+        let sensors = bearings.len() as u32;
+        return Some(PositionEstimate { latitude, longitude, method: LocalizationMethod::BearingIntersection, sensors });
+    }
+
+    let weighted: Vec<((f64, f64), f64)> = observations.iter()
+        .filter_map(|o| o.rssi.map(|r| {
+            let range = model.estimate_range_m(r).max(1.0) as f64;
+            (projection.to_local(o.sensor_lat, o.sensor_lon), 1.0 / (range * range))
+        }))
+        .collect();
+    if weighted.len() < 2 {
+        return None;
+    }
+    let total: f64 = weighted.iter().map(|(_, w)| w).sum();
+    let x = weighted.iter().map(|((x, _), w)| x * w).sum::<f64>() / total;
+    let y = weighted.iter().map(|((_, y), w)| y * w).sum::<f64>() / total;
+    let (latitude, longitude) = projection.to_geo(x, y);
+    Some(PositionEstimate { latitude, longitude, method: LocalizationMethod::RssiCentroid, sensors: weighted.len() as u32 })
+}
+
+/// 一个接收站最近一次收到某架无人机
+#[derive(Debug, Clone, Copy)]
+struct Sighting {
+    observation: SensorObservation,
+    at_ms: i64,
+}
+
+/// 汇聚节点上按无人机收集各接收站的最近观测，两个及以上接收站同时收到时估计其位置
+///
+/// 接收站以记录中的接收站位置区分，相距不到 [`Self::MIN_BASELINE_M`] 的视为同一个。
+pub struct MultiSensorLocator {
+    model: PathLossModel,
+    recent: HashMap<String, Vec<Sighting>>,
+}
+
+impl MultiSensorLocator {
+    /// 不同接收站的观测在该时间窗 (毫秒) 内才视为同时
+    const WINDOW_MS: i64 = 3_000;
+    /// 接收站之间的最小距离 (米)
+    const MIN_BASELINE_M: f64 = 20.0;
+    /// 跟踪的无人机数超过该值时清理已过时间窗的
+    const MAX_TRACKED: usize = 1024;
+
+    pub fn new(model: PathLossModel) -> Self {
+        Self { model, recent: HashMap::new() }
+    }
+
+    /// 记录一条带接收站位置的记录，返回由各接收站最近观测得到的位置估计
+    pub fn observe(&mut self, record: &UploadData, at_ms: i64) -> Option<PositionEstimate> {
+        let receiver = record.receiver?;
+        if record.rssi.is_none() && record.bearing_deg.is_none() {
+            return None;
+        }
+        let key = if record.rid.is_empty() { &record.track_id } else { &record.rid };
+        if self.recent.len() >= Self::MAX_TRACKED && !self.recent.contains_key(key) {
+            self.recent.retain(|_, s| s.iter().any(|s| at_ms - s.at_ms <= Self::WINDOW_MS));
+        }
+        let observation = SensorObservation {
+            sensor_lat: receiver.latitude,
+            sensor_lon: receiver.longitude,
+            rssi: record.rssi,
+            bearing_deg: record.bearing_deg,
+        };
+        let sightings = self.recent.entry(key.clone()).or_default();
+        sightings.retain(|s| at_ms - s.at_ms <= Self::WINDOW_MS
+            && distance_m(s.observation.sensor_lat, s.observation.sensor_lon, receiver.latitude, receiver.longitude) >= Self::MIN_BASELINE_M);
+        sightings.push(Sighting { observation, at_ms });
+        let observations: Vec<SensorObservation> = sightings.iter().map(|s| s.observation).collect();
+        estimate_position(&observations, &self.model)
+    }
+}
+
+/// 方位线最小二乘交会，方位为正北顺时针角度；近似平行时返回 None
+fn intersect_bearings(lines: &[((f64, f64), f64)]) -> Option<(f64, f64)> {
+    // 每条线的法向量 n = (cosθ, -sinθ)，约束 n·p = n·s
+    let (mut a11, mut a12, mut a22, mut b1, mut b2) = (0.0, 0.0, 0.0, 0.0, 0.0);
+    for ((sx, sy), bearing) in lines {
+        let theta = bearing.to_radians();
+        let (nx, ny) = (theta.cos(), -theta.sin());
+        let c = nx * sx + ny * sy;
+        a11 += nx * nx;
+        a12 += nx * ny;
+        a22 += ny * ny;
+        b1 += nx * c;
+        b2 += ny * c;
+    }
+    let det = a11 * a22 - a12 * a12;
+    if det.abs() < 1e-6 {
+        return None;
+    }
+    Some(((a22 * b1 - a12 * b2) / det, (a11 * b2 - a12 * b1) / det))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bearing_intersection() {
+        // 两个传感器分别位于目标正南与正西约 1 公里处
+        let target = (40.0, 116.0);
+        let south = SensorObservation { sensor_lat: 39.991, sensor_lon: 116.0, rssi: None, bearing_deg: Some(0.0) };
+        let west = SensorObservation { sensor_lat: 40.0, sensor_lon: 115.988, rssi: None, bearing_deg: Some(90.0) };
+        let estimate = estimate_position(&[south, west], &PathLossModel::default()).unwrap();
+        assert_eq!(estimate.method, LocalizationMethod::BearingIntersection);
+        assert!(distance_m(estimate.latitude, estimate.longitude, target.0, target.1) < 50.0);
+        assert_eq!(estimate.spoof_score(target.0, target.1), 0.0);
+        assert_eq!(estimate.spoof_score(40.02, 116.0), 1.0);
+    }
+
+    #[test]
+    fn test_locator_needs_two_sensors() {
+        use crate::receiver::ReceiverPosition;
+
+        let record = |lat: f64, lon: f64, rssi: f32| UploadData {
+            rid: "RID-1".into(),
+            rssi: Some(rssi),
+            receiver: Some(ReceiverPosition { latitude: lat, longitude: lon, altitude_m: None }),
+            ..Default::default()
+        };
+        let mut locator = MultiSensorLocator::new(PathLossModel::default());
+        assert!(locator.observe(&record(40.0, 116.0, -70.0), 0).is_none());
+        // 同一接收站再次收到不算第二个
+        assert!(locator.observe(&record(40.0, 116.00001, -71.0), 500).is_none());
+        // 时间窗外的观测已过时
+        assert!(locator.observe(&record(40.01, 116.0, -60.0), 10_000).is_none());
+
+        let estimate = locator.observe(&record(40.0, 116.0, -80.0), 11_000).unwrap();
+        assert_eq!((estimate.method, estimate.sensors), (LocalizationMethod::RssiCentroid, 2));
+        // 信号更强的接收站附近
+        assert!(estimate.latitude > 40.005);
+        assert_eq!(estimate.spoof_score(estimate.latitude, estimate.longitude), 0.0);
+        assert_eq!(estimate.spoof_score(40.5, 116.0), 1.0);
+    }
+}
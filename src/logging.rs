@@ -0,0 +1,193 @@
+use std::io::IsTerminal;
+use std::thread;
+use std::time::Duration;
+
+use chrono::Utc;
+use tracing::level_filters::LevelFilter;
+use tracing::{warn, Event, Level, Subscriber};
+use tracing_appender::non_blocking::{ErrorCounter, NonBlockingBuilder, WorkerGuard};
+use tracing_appender::rolling;
+use tracing_subscriber::fmt::format::Writer;
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::{filter, fmt, layer::SubscriberExt, reload, util::SubscriberInitExt, Layer, Registry};
+
+use crate::time_format;
+
+/// 逐条检测记录使用的日志 target，写入独立的数据日志而不是诊断日志
+///
+/// ```ignore
+/// info!(target: DATA_TARGET, "json: {}", json);
+/// ```
+pub const DATA_TARGET: &str = "data";
+/// 每条解码记录的一行摘要，控制台上以检测样式突出显示
+pub const DETECTION_TARGET: &str = "detection";
+/// 触发的告警，控制台上以告警样式突出显示
+pub const ALERT_TARGET: &str = "alert";
+
+/// 诊断日志 (`logs/capture.log`) 的缓冲行数
+const DIAGNOSTIC_BUFFER_LINES: usize = 128_000;
+/// 数据日志 (`logs/data.log`) 的缓冲行数；检测突发时宁可丢数据行，也不阻塞解码线程
+const DATA_BUFFER_LINES: usize = 32_768;
+
+/// 调整日志级别的句柄
+pub type LevelHandle = reload::Handle<LevelFilter, Registry>;
+
+/// 日志写入线程的守护对象，drop 时写出剩余缓冲
+pub struct Logging {
+    _guards: [WorkerGuard; 2],
+    pub level: LevelHandle,
+}
+
+/// 控制台输出设置
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Console {
+    /// 按严重程度着色
+    pub color: bool,
+    /// 显示 debug 日志
+    pub verbose: bool,
+    /// 写到标准错误而不是标准输出（标准输出留给检测数据时）
+    pub stderr: bool,
+    /// 不输出到控制台（终端界面占用屏幕时）
+    pub quiet: bool,
+}
+
+/// 初始化日志：控制台、诊断日志、数据日志
+///
+/// 诊断和数据各自使用独立的非阻塞写入线程和缓冲区，一方突发不会挤掉另一方；
+/// 缓冲满时丢弃的行数每分钟检查一次并告警。
+///
+/// 控制台只显示 info 及以上（`verbose` 时为 debug 及以上）的诊断日志、检测摘要和告警，
+/// 逐条 JSON 只写入数据日志。
+/// `color` 为 false、设置了 `NO_COLOR` 或控制台不是终端时不输出颜色。
+///
+/// 可重复调用：已有全局 subscriber（此前调用过，或嵌入本程序的应用自行设置了）时
+/// 不做任何改动并返回 None，日志继续交给已有的 subscriber。
+pub fn init_logging(dir: &str, console: Console) -> Option<Logging> {
+    let (diagnostic_writer, diagnostic_guard) = NonBlockingBuilder::default()
+        .buffered_lines_limit(DIAGNOSTIC_BUFFER_LINES)
+        .thread_name("diagnostic-log")
+        .finish(rolling::daily(dir, "capture.log"));
+    let (data_writer, data_guard) = NonBlockingBuilder::default()
+        .buffered_lines_limit(DATA_BUFFER_LINES)
+        .thread_name("data-log")
+        .finish(rolling::daily(dir, "data.log"));
+    let counters = (diagnostic_writer.error_counter(), data_writer.error_counter());
+
+    let diagnostic_layer = fmt::layer()
+        .with_ansi(false)
+        .with_timer(time_format::LogTimer)
+        .with_writer(diagnostic_writer)
+        .with_filter(filter::filter_fn(|meta| meta.target() != DATA_TARGET));
+    let data_layer = fmt::layer()
+        .with_ansi(false)
+        .with_target(false)
+        .with_timer(time_format::LogTimer)
+        .with_writer(data_writer)
+        .with_filter(filter::filter_fn(|meta| meta.target() == DATA_TARGET));
+    let console_level = if console.verbose { Level::DEBUG } else { Level::INFO };
+    let terminal = if console.stderr { std::io::stderr().is_terminal() } else { std::io::stdout().is_terminal() };
+    let color = console.color && std::env::var_os("NO_COLOR").is_none() && terminal;
+    let console_writer = if console.stderr { BoxMakeWriter::new(std::io::stderr) } else { BoxMakeWriter::new(std::io::stdout) };
+    let console_subscriber = fmt::layer()
+        .event_format(ConsoleFormat { color })
+        .with_writer(console_writer)
+        .with_filter(filter::filter_fn(move |meta| !console.quiet && meta.target() != DATA_TARGET && *meta.level() <= console_level));
+
+    let (level_filter, level) = reload::Layer::new(LevelFilter::TRACE);
+    tracing_subscriber::registry()
+        .with(level_filter)
+        .with(console_subscriber)
+        .with(diagnostic_layer)
+        .with(data_layer)
+        .try_init()
+        .ok()?;
+    spawn_overflow_monitor(counters.0, counters.1);
+    Some(Logging { _guards: [diagnostic_guard, data_guard], level })
+}
+
+/// 控制台输出格式：按严重程度着色并加图标，检测和告警与普通日志区分开
+struct ConsoleFormat {
+    color: bool,
+}
+
+impl<S, N> FormatEvent<S, N> for ConsoleFormat
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(&self, ctx: &FmtContext<'_, S, N>, mut writer: Writer<'_>, event: &Event<'_>) -> std::fmt::Result {
+        let meta = event.metadata();
+        let (icon, label, style) = console_style(meta.target(), *meta.level());
+        let styled = self.color && !style.is_empty();
+        if styled {
+            write!(writer, "\x1b[{}m", style)?;
+        }
+        write!(writer, "{} {} {:<5} ", time_format::display_ms(Utc::now().timestamp_millis()), icon, label)?;
+        ctx.field_format().format_fields(writer.by_ref(), event)?;
+        if styled {
+            write!(writer, "\x1b[0m")?;
+        }
+        writeln!(writer)
+    }
+}
+
+/// 控制台上各类事件的 (图标, 标签, ANSI 样式)
+fn console_style(target: &str, level: Level) -> (&'static str, &'static str, &'static str) {
+    match (target, level) {
+        (ALERT_TARGET, _) => ("🔔", "ALERT", "1;35"),
+        (DETECTION_TARGET, _) => ("✈", "UAS", "1;32"),
+        (_, Level::ERROR) => ("✖", "ERROR", "1;31"),
+        (_, Level::WARN) => ("⚠", "WARN", "33"),
+        (_, Level::INFO) => ("·", "INFO", ""),
+        _ => (" ", "DEBUG", "2"),
+    }
+}
+
+fn spawn_overflow_monitor(diagnostic: ErrorCounter, data: ErrorCounter) {
+    thread::spawn(move || {
+        let (mut diagnostic_dropped, mut data_dropped) = (0, 0);
+        loop {
+            thread::sleep(Duration::from_secs(60));
+            let (diagnostic_now, data_now) = (diagnostic.dropped_lines(), data.dropped_lines());
+            if diagnostic_now > diagnostic_dropped {
+                warn!("diagnostic log dropped {} lines (buffer full)", diagnostic_now - diagnostic_dropped);
+            }
+            if data_now > data_dropped {
+                warn!("data log dropped {} lines (buffer full)", data_now - data_dropped);
+            }
+            (diagnostic_dropped, data_dropped) = (diagnostic_now, data_now);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_init_logging_twice() {
+        let dir = std::env::temp_dir().join("wifi-capture-logging-test");
+        let dir = dir.to_str().unwrap();
+        let first = init_logging(dir, Console { color: true, ..Default::default() });
+        let second = init_logging(dir, Console { verbose: true, stderr: true, ..Default::default() });
+        // 其它测试可能已先初始化；无论如何第二次调用都不应 panic，也不应再次生效
+        assert!(second.is_none());
+        drop(first);
+    }
+
+    #[test]
+    fn test_console_style_by_target() {
+        assert_eq!(console_style(ALERT_TARGET, Level::INFO).1, "ALERT");
+        assert_eq!(console_style(DETECTION_TARGET, Level::DEBUG).1, "UAS");
+    }
+
+    #[test]
+    fn test_console_style_by_level() {
+        assert_eq!(console_style("wifi_capture", Level::ERROR), ("✖", "ERROR", "1;31"));
+        assert_eq!(console_style("wifi_capture", Level::WARN).1, "WARN");
+        assert_eq!(console_style("wifi_capture", Level::INFO), ("·", "INFO", ""));
+        assert_eq!(console_style("wifi_capture", Level::TRACE).1, "DEBUG");
+    }
+}
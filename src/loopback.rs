@@ -0,0 +1,269 @@
+use std::collections::BTreeSet;
+use std::io::{self, Write};
+
+use crate::remote_id::{ODID_OUI, ODID_OUI_TYPE, VENDOR_ELEMENT_ID, MESSAGE_SIZE};
+use crate::upload_data::UploadData;
+
+/// 注入用的最简 radiotap 头：版本 0、长度 8、不带任何字段
+const RADIOTAP_HEADER: [u8; 8] = [0x00, 0x00, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00];
+
+/// 位置向量时间戳的取值上限 (整点后的 0.1 秒数)，序号按此取模写入时间戳
+const SEQUENCE_MODULO: u32 = 36_000;
+
+/// 自测用的模拟发射端：广播 Basic ID + 位置向量的信标
+///
+/// 每帧的序号写入位置向量的时间戳字段，接收端据此统计丢帧，并逐字段核对解码结果。
+#[derive(Debug, Clone)]
+pub struct TestTransmitter {
+    pub uas_id: String,
+    pub mac: [u8; 6],
+    pub latitude: i32,
+    pub longitude: i32,
+    /// 几何高度编码值 (0.5 m 分辨率，偏移 -1000 m)
+    pub altitude: u16,
+}
+
+impl Default for TestTransmitter {
+    fn default() -> Self {
+        Self {
+            uas_id: "LOOPBACK-SELFTEST".into(),
+            // 本地管理地址，不会与真实设备冲突
+            mac: [0x02, 0x4c, 0x4f, 0x4f, 0x50, 0x42],
+            latitude: 399_042_000,
+            longitude: 1_164_074_000,
+            altitude: 2_200,
+        }
+    }
+}
+
+impl TestTransmitter {
+    fn basic_id(&self) -> [u8; MESSAGE_SIZE] {
+        let mut message = [0u8; MESSAGE_SIZE];
+        message[0] = 0x02;
+        message[1] = 0x12;  // 序列号 (CTA-2063-A)，多旋翼
+        let id = self.uas_id.as_bytes();
+        let len = id.len().min(20);
+        message[2..2 + len].copy_from_slice(&id[..len]);
+        message
+    }
+
+    fn location(&self, sequence: u32) -> [u8; MESSAGE_SIZE] {
+        let mut message = [0u8; MESSAGE_SIZE];
+        message[0] = 0x12;
+        message[1] = 0x20;  // 空中
+        message[5..9].copy_from_slice(&self.latitude.to_le_bytes());
+        message[9..13].copy_from_slice(&self.longitude.to_le_bytes());
+        message[15..17].copy_from_slice(&self.altitude.to_le_bytes());
+        message[19] = 0x3b; // 垂直精度 < 25 m，水平精度 < 3 m
+        message[21..23].copy_from_slice(&((sequence % SEQUENCE_MODULO) as u16).to_le_bytes());
+        message
+    }
+
+    /// 第 `sequence` 帧：radiotap 头 + 信标帧 (SSID + Remote ID 厂商 IE)
+    pub fn beacon(&self, sequence: u32) -> Vec<u8> {
+        let mut frame = RADIOTAP_HEADER.to_vec();
+        frame.extend_from_slice(&[0x80, 0x00, 0x00, 0x00]);
+        frame.extend_from_slice(&[0xff; 6]);
+        frame.extend_from_slice(&self.mac);
+        frame.extend_from_slice(&self.mac);
+        frame.extend_from_slice(&((sequence as u16 & 0x0fff) << 4).to_le_bytes());
+        frame.extend_from_slice(&[0u8; 8]);         // 时间戳
+        frame.extend_from_slice(&[0x64, 0x00]);     // 信标间隔 100 TU
+        frame.extend_from_slice(&[0x01, 0x00]);     // 能力信息: ESS
+        let ssid = b"RID-LOOPBACK";
+        frame.extend_from_slice(&[0x00, ssid.len() as u8]);
+        frame.extend_from_slice(ssid);
+
+        let messages = [self.basic_id(), self.location(sequence)];
+        let body_len = 4 + 4 + messages.len() * MESSAGE_SIZE;
+        frame.extend_from_slice(&[VENDOR_ELEMENT_ID, body_len as u8]);
+        frame.extend_from_slice(&ODID_OUI);
+        frame.push(ODID_OUI_TYPE);
+        frame.extend_from_slice(&[sequence as u8, 0xf2, MESSAGE_SIZE as u8, messages.len() as u8]);
+        for message in &messages {
+            frame.extend_from_slice(message);
+        }
+        frame
+    }
+}
+
+/// 环回自测结果：按序号统计收到的帧，核对解码出的位置与发送值一致
+#[derive(Debug)]
+pub struct LoopbackCheck {
+    transmitter: TestTransmitter,
+    pub sent: u32,
+    pub received: BTreeSet<u16>,
+    /// 解码结果与发送内容不一致的帧数
+    pub mismatched: u32,
+}
+
+impl LoopbackCheck {
+    pub fn new(transmitter: TestTransmitter) -> Self {
+        Self { transmitter, sent: 0, received: BTreeSet::new(), mismatched: 0 }
+    }
+
+    /// 检查一条解码记录，其他发射端的记录被忽略
+    pub fn observe(&mut self, record: &UploadData) {
+        let expected = &self.transmitter;
+        if record.rid != expected.uas_id {
+            return;
+        }
+        if (record.latitude, record.longitude, record.geometric_altitude as u16)
+            != (expected.latitude, expected.longitude, expected.altitude)
+        {
+            self.mismatched += 1;
+        }
+        self.received.insert(record.timestamp);
+    }
+
+    /// 收到的比例
+    pub fn delivery(&self) -> f64 {
+        let expected = self.sent.min(SEQUENCE_MODULO);
+        if expected == 0 { 0.0 } else { self.received.len() as f64 / expected as f64 }
+    }
+
+    /// 输出报告；至少收到 `min_delivery` 比例的帧且全部解码正确时返回 true
+    pub fn write_report<W: Write>(&self, mut out: W, min_delivery: f64) -> io::Result<bool> {
+        writeln!(out, "发送: {} 帧", self.sent)?;
+        writeln!(out, "收到并解码: {} 帧 ({:.1}%)", self.received.len(), self.delivery() * 100.0)?;
+        writeln!(out, "解码内容不一致: {} 帧", self.mismatched)?;
+        let passed = self.delivery() >= min_delivery && self.mismatched == 0;
+        if passed {
+            writeln!(out, "结论: 通过")?;
+        } else if self.received.is_empty() {
+            writeln!(out, "结论: 未通过，未收到任何自测帧，请检查两块网卡的信道和注入支持")?;
+        } else {
+            writeln!(out, "结论: 未通过 (收到比例应不低于 {:.0}%)", min_delivery * 100.0)?;
+        }
+        Ok(passed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decode::parse_remote_id_frame;
+    use crate::message::AnyMessage;
+
+    fn record(transmitter: &TestTransmitter, sequence: u16) -> UploadData {
+        UploadData {
+            rid: transmitter.uas_id.clone(),
+            latitude: transmitter.latitude,
+            longitude: transmitter.longitude,
+            geometric_altitude: transmitter.altitude as i16,
+            timestamp: sequence,
+            ..Default::default()
+        }
+    }
+
+    fn report(check: &LoopbackCheck, min_delivery: f64) -> (bool, String) {
+        let mut out = Vec::new();
+        let passed = check.write_report(&mut out, min_delivery).unwrap();
+        (passed, String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn test_beacon_decodes_to_basic_id_and_location() {
+        let transmitter = TestTransmitter::default();
+        let beacon = transmitter.beacon(7);
+        let messages = parse_remote_id_frame(&beacon[RADIOTAP_HEADER.len()..]).unwrap();
+        let [AnyMessage::Base(basic), AnyMessage::PositionVector(location)] = &messages[..] else {
+            panic!("unexpected messages: {:?}", messages);
+        };
+        assert_eq!(basic.uas_id, transmitter.uas_id);
+        assert_eq!((location.latitude, location.longitude, location.timestamp), (transmitter.latitude, transmitter.longitude, 7));
+    }
+
+    #[test]
+    fn test_beacon_sequence_wraps_at_modulo() {
+        let transmitter = TestTransmitter::default();
+        let beacon = transmitter.beacon(SEQUENCE_MODULO + 7);
+        let messages = parse_remote_id_frame(&beacon[RADIOTAP_HEADER.len()..]).unwrap();
+        let Some(AnyMessage::PositionVector(location)) = messages.get(1) else {
+            panic!("unexpected messages: {:?}", messages);
+        };
+        assert_eq!(location.timestamp, 7);
+    }
+
+    #[test]
+    fn test_basic_id_truncates_long_uas_id() {
+        let transmitter = TestTransmitter { uas_id: "A".repeat(30), ..Default::default() };
+        let message = transmitter.basic_id();
+        assert_eq!(&message[2..22], "A".repeat(20).as_bytes());
+        assert_eq!(message[22..], [0u8; MESSAGE_SIZE - 22]);
+    }
+
+    #[test]
+    fn test_observe_counts_distinct_sequences() {
+        let transmitter = TestTransmitter::default();
+        let mut check = LoopbackCheck::new(transmitter.clone());
+        check.sent = 4;
+        for sequence in [0, 1, 1, 3] {
+            check.observe(&record(&transmitter, sequence));
+        }
+        assert_eq!(check.received, BTreeSet::from([0, 1, 3]));
+        assert_eq!(check.delivery(), 0.75);
+        assert_eq!(check.mismatched, 0);
+    }
+
+    #[test]
+    fn test_observe_ignores_other_transmitters() {
+        let mut check = LoopbackCheck::new(TestTransmitter::default());
+        check.observe(&UploadData { rid: "OTHER".into(), ..Default::default() });
+        assert!(check.received.is_empty());
+        assert_eq!(check.mismatched, 0);
+    }
+
+    #[test]
+    fn test_observe_counts_mismatched_position() {
+        let transmitter = TestTransmitter::default();
+        let mut check = LoopbackCheck::new(transmitter.clone());
+        check.observe(&UploadData { latitude: 0, ..record(&transmitter, 0) });
+        assert_eq!(check.mismatched, 1);
+        assert_eq!(check.received.len(), 1);
+    }
+
+    #[test]
+    fn test_delivery_without_sent_frames_is_zero() {
+        let check = LoopbackCheck::new(TestTransmitter::default());
+        assert_eq!(check.delivery(), 0.0);
+    }
+
+    #[test]
+    fn test_report_passes_above_min_delivery() {
+        let transmitter = TestTransmitter::default();
+        let mut check = LoopbackCheck::new(transmitter.clone());
+        check.sent = 4;
+        for sequence in 0..3 {
+            check.observe(&record(&transmitter, sequence));
+        }
+        let (passed, text) = report(&check, 0.5);
+        assert!(passed);
+        assert!(text.contains("收到并解码: 3 帧 (75.0%)"));
+        assert!(text.ends_with("结论: 通过\n"));
+
+        let (passed, text) = report(&check, 0.9);
+        assert!(!passed);
+        assert!(text.contains("收到比例应不低于 90%"));
+    }
+
+    #[test]
+    fn test_report_fails_on_mismatch() {
+        let transmitter = TestTransmitter::default();
+        let mut check = LoopbackCheck::new(transmitter.clone());
+        check.sent = 1;
+        check.observe(&UploadData { longitude: 0, ..record(&transmitter, 0) });
+        let (passed, text) = report(&check, 0.5);
+        assert!(!passed);
+        assert!(text.contains("解码内容不一致: 1 帧"));
+    }
+
+    #[test]
+    fn test_report_hints_when_nothing_received() {
+        let mut check = LoopbackCheck::new(TestTransmitter::default());
+        check.sent = 10;
+        let (passed, text) = report(&check, 0.5);
+        assert!(!passed);
+        assert!(text.contains("未收到任何自测帧"));
+    }
+}
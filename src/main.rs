@@ -1,281 +1,922 @@
-use message::{message::{Message, MessageError}, AnyMessage};
 use tracing::{info, error};
-use tracing_subscriber::{fmt, layer::SubscriberExt, util::SubscriberInitExt};
-use tracing_appender::{non_blocking, rolling::{self}};
-use pnet::datalink::{self, interfaces, Channel, NetworkInterface};
-use libwifi::{parse_frame, Frame};
-use chrono::Local;
-use reqwest::blocking::Client;
-use std::time::Duration;
-use std::ops::Range;
+use pnet::datalink::{interfaces, NetworkInterface};
+use std::time::{Duration, Instant};
+use std::path::PathBuf;
+use std::thread;
 
-pub mod wifi;
-pub mod message;
-pub mod upload_data;
+use wifi_capture::{canonical, capture, clock, compare, conformance, config, diagnose, egress, event_log, geofence, import,
+                   logging, loopback, playback, receiver, remote_config, remote_id, schema, survey, time_format, units, upload, wifi};
+#[cfg(feature = "database")]
+use wifi_capture::{batch, incident, storage};
+use wifi_capture::decode::{process_packet, DecodeContext};
+use wifi_capture::event_log::DecodedEvent;
+use wifi_capture::cli::{Command, Options};
+use wifi_capture::config::{Config, InterfaceProfile};
+use wifi_capture::control::RuntimeControl;
+use wifi_capture::telemetry::SystemTelemetry;
+use wifi_capture::watchdog::Watchdog;
+use wifi_capture::health::HealthMonitor;
+use wifi_capture::sbs::SbsServer;
+use wifi_capture::live_feed::LiveFeed;
+use wifi_capture::asterix::AsterixSender;
+#[cfg(feature = "dashboard")]
+use wifi_capture::live_layer::LiveLayer;
+#[cfg(feature = "tui")]
+use wifi_capture::tui::Tui;
+use wifi_capture::latency::LatencyMetrics;
+use wifi_capture::environment::{ClockReport, EnvironmentReport};
+#[cfg(feature = "monitor")]
+use wifi_capture::wifi::monitor::{self, MonitorGuard};
+#[cfg(feature = "mesh")]
+use wifi_capture::localization::MultiSensorLocator;
+#[cfg(feature = "mesh")]
+use wifi_capture::mesh::Mesh;
+use wifi_capture::network_rid::NetworkIngest;
+#[cfg(feature = "bluetooth")]
+use wifi_capture::bluetooth::BleIngest;
+use wifi_capture::pcapng::PcapngWriter;
+use wifi_capture::frame_ring::FrameRing;
+use wifi_capture::pcap_writer::RotatingPcap;
+use wifi_capture::event_log::EventReader;
+use wifi_capture::traffic_stats::TrafficStats;
+use wifi_capture::flight_export::{self, Flight, TrackCollector};
+use wifi_capture::zone_report::ZoneReport;
+#[cfg(feature = "database")]
+use wifi_capture::storage::Store;
+use wifi_capture::mapped_pcap::decode_pcap;
+use wifi_capture::playback::{Pace, Pacer, PlaybackControl};
+use wifi_capture::output::{capture_profiles, decode_context, Output};
+use wifi_capture::shutdown;
+use wifi_capture::client::RemoteSensor;
 
+/// 选择单网卡模式的抓包网卡：指定了名称时按名称查找，否则只在恰好有一个无线网卡时自动选用
+fn select_interface(name: Option<&str>) -> Result<NetworkInterface, String> {
+    let available = interfaces();
+    if let Some(name) = name {
+        return available.into_iter().find(|i| i.name == name)
+            .ok_or_else(|| format!("未找到网卡 {}，可用 list-interfaces 查看", name));
+    }
+    let mut wireless: Vec<NetworkInterface> = available.into_iter().filter(|i| wifi::is_wireless(&i.name)).collect();
+    match wireless.len() {
+        0 => Err("未找到无线网卡，可用 list-interfaces 查看".into()),
+        1 => Ok(wireless.remove(0)),
+        _ => {
+            let names: Vec<&str> = wireless.iter().map(|i| i.name.as_str()).collect();
+            Err(format!("找到多个无线网卡 ({})，请用 --interface 指定", names.join(", ")))
+        }
+    }
+}
 
-use crate::message::base_message::BaseMessage;
-use crate::message::position_vector_message::PositionVectorMessage;
-use crate::upload_data::UploadData;
-
-fn get_wifi_devices() -> Vec<NetworkInterface> {
- let interfaces = interfaces();
-    let mut wifi_devices = Vec::new();
+/// `--monitor`：把要抓包的网卡设为监听模式，返回退出时还原网卡的守卫
+///
+/// 驱动只能新建监听接口时，改在新接口上抓包（单网卡和配置的多网卡都相应改名）。
+#[cfg(feature = "monitor")]
+fn setup_monitors(interface: Option<NetworkInterface>, config: &mut Config)
+                  -> Result<(Option<NetworkInterface>, Vec<MonitorGuard>), String> {
+    if let Some(interface) = interface {
+        let guard = monitor::setup(&interface.name).map_err(|e| e.to_string())?;
+        let interface = if guard.interface() == interface.name {
+            interface
+        } else {
+            select_interface(Some(guard.interface()))?
+        };
+        return Ok((Some(interface), vec![guard]));
+    }
+    let mut guards = Vec::new();
+    for profile in &mut config.interfaces {
+        let guard = monitor::setup(&profile.name).map_err(|e| e.to_string())?;
+        profile.name = guard.interface().to_string();
+        guards.push(guard);
+    }
+    Ok((None, guards))
+}
 
-    info!("Available WiFi network devices:");
-    for interface in interfaces {
-        // 根据操作系统调整过滤条件
-        if interface.name.contains("wlx") || interface.name.contains("wlan1") {
-            info!("Name: {}, MAC: {:?}", interface.name, interface.mac);
-            wifi_devices.push(interface);
-        }
+/// 列出网卡及其 MAC，无线网卡标注 `wifi`
+fn list_interfaces() {
+    for interface in interfaces() {
+        let mac = interface.mac.map(|mac| mac.to_string()).unwrap_or_else(|| "-".into());
+        let kind = if wifi::is_wireless(&interface.name) { "wifi" } else { "" };
+        // Windows 上网卡名是 Npcap 设备路径，附带驱动给出的描述便于辨认
+        let line = format!("{:<16} {:<17} {:<4} {}", interface.name, mac, kind, interface.description);
+        println!("{}", line.trim_end());
     }
-    wifi_devices
 }
 
-fn capture_wifi_channel(interface: NetworkInterface)  {
-let (mut tx, mut rx) = match datalink::channel(&interface, Default::default()) {
-        Ok(Channel::Ethernet(tx, rx)) => (tx, rx),
-        Ok(_) => {
-            error!("Unsupported channel type");
+/// 信道勘测：依次停留在网卡支持的每个信道上，统计各信道的 Remote ID 检测
+fn run_survey(interface: Option<&str>, minutes: u64, dwell: Duration) {
+    let interface = match select_interface(interface) {
+        Ok(interface) => interface,
+        Err(e) => {
+            eprintln!("{}", e);
             return;
         }
+    };
+    let channels = wifi::supported_channels(&interface.name).unwrap_or_else(|e| {
+        eprintln!("无法读取 {} 支持的信道 ({}), 使用默认信道", interface.name, e);
+        [config::Band::Ghz2_4, config::Band::Ghz5].iter().flat_map(|b| b.default_channels()).collect()
+    });
+    let mut rx = match capture::open(&interface, Some(Duration::from_millis(100))) {
+        Ok(rx) => rx,
         Err(e) => {
-            error!("Failed to create channel: {}", e);
+            eprintln!("{}", e);
             return;
         }
     };
+    let deadline = Instant::now() + Duration::from_secs(minutes * 60);
+    eprintln!("勘测 {} 上的 {} 个信道，共 {} 分钟", interface.name, channels.len(), minutes);
+
+    let mut ctx = DecodeContext::default();
+    let mut survey = survey::Survey::default();
+    'survey: for &channel in channels.iter().cycle() {
+        if let Err(e) = wifi::set_channel(&interface.name, channel) {
+            eprintln!("设置信道 {} 失败: {}", channel, e);
+            continue;
+        }
+        let dwell_start = Instant::now();
+        while dwell_start.elapsed() < dwell {
+            if Instant::now() >= deadline {
+                survey.add_dwell(channel, dwell_start.elapsed());
+                break 'survey;
+            }
+            match rx.next_frame() {
+                Ok(packet) => {
+                    let records = process_packet(packet, &mut ctx);
+                    survey.add_frame(channel, &records);
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::TimedOut => {}
+                Err(e) => {
+                    eprintln!("读取数据包失败: {}", e);
+                    break 'survey;
+                }
+            }
+        }
+        survey.add_dwell(channel, dwell_start.elapsed());
+    }
+    if let Err(e) = survey.write_report(std::io::stdout().lock()) {
+        eprintln!("输出失败: {}", e);
+    }
+}
 
-    info!("Capturing on {}", interface.name);
-    
-    loop {
-        match rx.next() {
+/// 在当前信道上抓包一段时间，报告网卡/驱动的 radiotap、FCS、信道与信标情况
+fn run_diagnose(interface: Option<&str>, duration: Duration) -> bool {
+    let interface = match select_interface(interface) {
+        Ok(interface) => interface,
+        Err(e) => {
+            eprintln!("{}", e);
+            return false;
+        }
+    };
+    let mut rx = match capture::open(&interface, Some(Duration::from_millis(100))) {
+        Ok(rx) => rx,
+        Err(e) => {
+            eprintln!("{}", e);
+            return false;
+        }
+    };
+    eprintln!("在 {} 上诊断 {} 秒", interface.name, duration.as_secs());
+
+    let mut ctx = DecodeContext::default();
+    let mut diagnosis = diagnose::Diagnosis::default();
+    let start = Instant::now();
+    while start.elapsed() < duration {
+        match rx.next_frame() {
             Ok(packet) => {
-                process_packet(packet);
-                //let current_time = Local::now().format("%H:%M:%S").to_string();
-                //info!("当前时间: {}", current_time);
+                let remote_id = !process_packet(packet, &mut ctx).is_empty();
+                diagnosis.add_frame(packet, remote_id);
             }
+            Err(e) if e.kind() == std::io::ErrorKind::TimedOut => {}
             Err(e) => {
-                error!("Error reading packet: {}", e);
+                eprintln!("读取数据包失败: {}", e);
                 break;
             }
         }
     }
+    diagnosis.duration = start.elapsed();
+    if let Err(e) = diagnosis.write_report(std::io::stdout().lock()) {
+        eprintln!("输出失败: {}", e);
+    }
+    diagnosis.usable()
 }
 
-struct RadiotapHeader {
-    signal: f32,
-    rate: f32,
-    channel_freq: u16,
-}
-
-fn parse_80211_mgt(data: &[u8]) {
-    match parse_frame(data, false) {
-        Ok(frame) => {
-            //info!("Got frame: {frame:?}");
-            if let Frame::Beacon(beacon) = frame {
-                //info!("this is the beacon frame: {:?}", beacon);
-                //info!("vendor info: {:?}", beacon.station_info.vendor_specific);
-                if (beacon.station_info.vendor_specific[0].element_id == 221) && (beacon.station_info.vendor_specific[0].oui_type == 13) {
-                    let mut upload_data = UploadData {rid: String::from(""),
-                            run_status: 10,
-                            reserved_flag: true,
-                            height_type: 2,
-                            track_direction: false,
-                            speed_multiplier: true,
-                            track_angle: 45,
-                            ground_speed: 30,
-                            vertical_speed: -5,
-                            latitude: 34789012,
-                            longitude: 11567890,
-                            pressure_altitude: 1500,
-                            geometric_altitude: 1520,
-                            ground_altitude: 1485,
-                            vertical_accuracy: 3,
-                            horizontal_accuracy: 2,
-                            speed_accuracy: 1,
-                            timestamp: 12345,
-                            timestamp_accuracy: 0,
-                            reserved: 0,
-                        };
-                    let ssid = beacon.station_info.ssid();
-                    let vendor_data = &beacon.station_info.vendor_specific[0].data;
-                    info!("this is the openid element, ssid: {:?}, total len: {}, pack count: {}, pack size: {}", ssid, vendor_data[0], vendor_data[3], vendor_data[2]);
-                    let count = vendor_data[3];
-                    for i in 0..count {
-                        
-                        let range: Range<usize> = ((25*i+4) as usize)..((25*i+29) as usize);
-                        info!("i = {}, range:{:?}", i, range);
-                        let pack = &vendor_data[range];
-                        let message = AnyMessage::from_bytes(pack).unwrap();
-                        match message {
-                            AnyMessage::Base(bm) => {
-                                bm.print();
-                                upload_data.rid = bm.uas_id;
-                            }, 
-                            AnyMessage::PositionVector(pvm) => {
-                                pvm.print();
-                                upload_data.longitude = pvm.longitude;
-                                upload_data.latitude = pvm.latitude;
-                            },
-                            AnyMessage::System(sm) => {
-                                sm.print();
-                            }
-                        }
+/// 认证测试：抓包一段时间或读取抓包文件，检查被测无人机的广播编码
+fn run_conformance(interface: Option<&str>, duration: Duration, input: Option<&std::path::Path>, uas: Option<&str>) -> bool {
+    let mut check = conformance::ConformanceCheck::new(uas.map(String::from));
+    let mut ctx = DecodeContext::default();
+    if let Some(input) = input {
+        let result = decode_pcap(input, &mut ctx, |event| {
+            check.observe(&event);
+            Ok(())
+        });
+        if let Err(e) = result {
+            eprintln!("读取 {} 失败: {}", input.display(), e);
+            return false;
+        }
+    } else {
+        let interface = match select_interface(interface) {
+            Ok(interface) => interface,
+            Err(e) => {
+                eprintln!("{}", e);
+                return false;
+            }
+        };
+        let mut rx = match capture::open(&interface, Some(Duration::from_millis(100))) {
+            Ok(rx) => rx,
+            Err(e) => {
+                eprintln!("{}", e);
+                return false;
+            }
+        };
+        eprintln!("在 {} 上检查 {} 秒", interface.name, duration.as_secs());
+        let start = Instant::now();
+        while start.elapsed() < duration {
+            match rx.next_frame() {
+                Ok(packet) => {
+                    for record in process_packet(packet, &mut ctx) {
+                        check.observe(&DecodedEvent::now(record));
                     }
-                    let json = serde_json::to_string_pretty(&upload_data).unwrap();
-                    info!("json: {}", json);
-                    let client = Client::builder()
-                        .timeout(Duration::from_secs(10)) // 设置超时
-                        .build().unwrap();
-                    let response = client
-                        //.post("http://182.92.155.88:8111/position") // 替换your_endpoint
-                        .post("https://mx-lasm-lafs-dev.mxnavi.com/collect/api/v1/data/collect/rid")
-                        .json(&json)
-                        .send()
-                        .map_err(|err| {
-                            error!("发送失败: {}", err);
-                            err
-                        });
-                    info!("status: {}, text: {}", response.status(), response.text().unwrap());  
-
-                } else {
-                    print!("#");
                 }
-            } else {
-                print!(".");
+                Err(e) if e.kind() == std::io::ErrorKind::TimedOut => {}
+                Err(e) => {
+                    eprintln!("读取数据包失败: {}", e);
+                    break;
+                }
             }
         }
-        Err(err) => {
-            error!("Error during parsing : {err:?}");
-        }
     }
+    check.write_report(std::io::stdout().lock()).unwrap_or_else(|e| {
+        eprintln!("输出失败: {}", e);
+        false
+    })
 }
 
-fn create_special_message(data: &[u8]) -> Result<Box<dyn Message>, MessageError> {
-    let message_type = (data[0] >> 4) & 0x0f;
-    let content = &data[1..];
-    match message_type {
-        BaseMessage::MESSAGE_TYPE => {
-            let message = BaseMessage::from_bytes(content);
-            match message {
-                Ok(message) => {
-                    return Ok(Box::new(message));
-                },
-                Err(err) => {
-                    error!("base error: {}", err);
-                    return  Err(err);
-                }
+/// 环回自测：`tx` 注入模拟信标，`rx` 抓包解码，发送结束后再等待 1 秒收尾
+fn run_loopback(tx: &str, rx: &str, count: u32, interval: Duration, channel: Option<u8>) -> bool {
+    let (tx, rx) = match (select_interface(Some(tx)), select_interface(Some(rx))) {
+        (Ok(tx), Ok(rx)) => (tx, rx),
+        (Err(e), _) | (_, Err(e)) => {
+            eprintln!("{}", e);
+            return false;
+        }
+    };
+    if let Some(channel) = channel {
+        for name in [&tx.name, &rx.name] {
+            if let Err(e) = wifi::set_channel(name, channel) {
+                eprintln!("无法将 {} 设置到信道 {}: {}", name, channel, e);
+                return false;
             }
-        },
-        PositionVectorMessage::MESSAGE_TYPE =>{
-            let message = PositionVectorMessage::from_bytes(content);
-            match message {
-                Ok(message) => {
-                    return Ok(Box::new(message));
-                },
-                Err(err) => {
-                    error!("base error: {}", err);
-                    return  Err(err);
-                }
+        }
+    }
+    let mut capture = match capture::open(&rx, Some(Duration::from_millis(100))) {
+        Ok(capture) => capture,
+        Err(e) => {
+            eprintln!("{}", e);
+            return false;
+        }
+    };
+    let mut injector = match capture::open_injector(&tx) {
+        Ok(injector) => injector,
+        Err(e) => {
+            eprintln!("{}", e);
+            return false;
+        }
+    };
+    eprintln!("从 {} 注入 {} 帧，在 {} 上接收", tx.name, count, rx.name);
+
+    let transmitter = loopback::TestTransmitter::default();
+    let mut check = loopback::LoopbackCheck::new(transmitter.clone());
+    let sender = thread::spawn(move || {
+        let mut sent = 0;
+        for sequence in 0..count {
+            match injector.send_to(&transmitter.beacon(sequence), None) {
+                Some(Ok(())) => sent += 1,
+                Some(Err(e)) => eprintln!("注入失败: {}", e),
+                None => eprintln!("注入失败: 缓冲区不足"),
             }
+            thread::sleep(interval);
         }
-        _ => {
-            return Err(MessageError::UnknownMessageType(0));
+        sent
+    });
+    let mut ctx = DecodeContext::default();
+    let mut finished_at = None;
+    while finished_at.is_none_or(|at: Instant| at.elapsed() < Duration::from_secs(1)) {
+        if finished_at.is_none() && sender.is_finished() {
+            finished_at = Some(Instant::now());
+        }
+        match capture.next_frame() {
+            Ok(packet) => process_packet(packet, &mut ctx).iter().for_each(|record| check.observe(record)),
+            Err(e) if e.kind() == std::io::ErrorKind::TimedOut => {}
+            Err(e) => {
+                eprintln!("读取数据包失败: {}", e);
+                break;
+            }
         }
     }
+    check.sent = sender.join().unwrap_or_default();
+    check.write_report(std::io::stdout().lock(), 0.9).unwrap_or_else(|e| {
+        eprintln!("输出失败: {}", e);
+        false
+    })
 }
 
+fn open_events(path: &std::path::Path) -> Option<EventReader<std::io::BufReader<std::fs::File>>> {
+    EventReader::open(path)
+        .map_err(|e| eprintln!("无法打开 {}: {}", path.display(), e))
+        .ok()
+}
 
-fn process_packet(packet: &[u8]) {
-    if packet.len() < 100 {
-        return;
+/// 读取录制文件、NDJSON 检测输出 (.ndjson/.jsonl) 或数据库 (.db/.sqlite) 中的全部记录
+fn read_archive(path: &std::path::Path) -> Option<Vec<DecodedEvent>> {
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or_default().to_ascii_lowercase();
+    match extension.as_str() {
+        "ndjson" | "jsonl" | "json" => {
+            let (events, skipped) = import::read_ndjson(path)
+                .map_err(|e| eprintln!("无法读取 {}: {}", path.display(), e))
+                .ok()?;
+            if skipped > 0 {
+                eprintln!("跳过 {} 行无法解析的记录", skipped);
+            }
+            Some(events)
+        }
+        #[cfg(feature = "database")]
+        "db" | "sqlite" | "sqlite3" => {
+            open_store(path)?.query(&Default::default())
+                .map_err(|e| eprintln!("查询失败: {}", e))
+                .ok()
+        }
+        _ => Some(open_events(path)?.map_while(Result::ok).collect()),
     }
-    //let data = packet.data;
-    let (radiotap, remaining) = parse_radiotap(packet);
-    parse_80211_mgt(remaining);
 }
 
-fn parse_radiotap(data: &[u8]) -> (RadiotapHeader, &[u8]) {
-    let mut offset = 0;
-    let header_len = data[2] as usize;
-    
-    let mut signal = 0.0;
-    let mut rate = 0.0;
-    let mut channel_freq = 0;
+#[cfg(feature = "database")]
+fn open_store(path: &std::path::Path) -> Option<Store> {
+    Store::open(path)
+        .map_err(|e| eprintln!("无法打开数据库 {}: {}", path.display(), e))
+        .ok()
+}
 
-    while offset < header_len {
-        let field_type = data[offset];
-        offset += 1;
-        
-        match field_type {
-            0x03 => { // Signal
-                signal = data[offset] as i8 as f32;
-                offset += 1;
+/// 执行离线子命令
+fn run_command(command: &Command, config: &Config) {
+    match command {
+        Command::StatsExport { input, bucket } => {
+            let mut stats = TrafficStats::new(*bucket);
+            let Some(reader) = open_events(input) else { return };
+            for event in reader {
+                match event {
+                    Ok(event) => stats.add(&event),
+                    Err(e) => {
+                        eprintln!("读取事件失败: {}", e);
+                        break;
+                    }
+                }
+            }
+            if let Err(e) = stats.write_csv(std::io::stdout().lock()) {
+                eprintln!("输出失败: {}", e);
+            }
+        }
+        Command::ExportFlight { input, id, format } => {
+            let Some(reader) = open_events(input) else { return };
+            let flight = Flight::collect(id, reader.map_while(Result::ok));
+            eprintln!("{} points for {}", flight.points.len(), id);
+            println!("{}", flight.render(*format));
+        }
+        Command::ExportTracks { input, format, from_ms, to_ms } => {
+            let Some(events) = read_archive(input) else { return };
+            let mut tracks = TrackCollector::default();
+            let in_range = |at: i64| from_ms.is_none_or(|t| at >= t) && to_ms.is_none_or(|t| at < t);
+            for event in events.iter().filter(|e| in_range(e.received_at_ms)) {
+                tracks.add(event);
+            }
+            let flights = tracks.flights();
+            eprintln!("{} tracks from {} records", flights.len(), events.len());
+            print!("{}", flight_export::render_tracks(&flights, *format));
+        }
+        Command::Replay { input, from_ms, to_ms, uas, speed } => {
+            let Some(events) = read_archive(input) else { return };
+            let signing = match config.upload.load_signing_key() {
+                Ok(signing) => signing,
+                Err(e) => {
+                    eprintln!("无法读取上传签名密钥: {}", e);
+                    return;
+                }
+            };
+            let total = events.len();
+            let events = upload::replay_selection(events, *from_ms, *to_ms, uas.as_deref());
+            eprintln!("重新上传 {} 条记录 (共 {} 条) 到 {}", events.len(), total, config.upload.url);
+            if !upload::replay(&events, *speed, config, signing) {
+                eprintln!("{:?} 内未能送出全部批次，未送出的批次留在上传队列中", upload::REPLAY_DRAIN_TIMEOUT);
+            }
+        }
+        Command::ZoneReport { input, zones } => {
+            let zones = match geofence::load_geojson(zones) {
+                Ok(zones) => zones,
+                Err(e) => {
+                    eprintln!("无法加载区域 {}: {}", zones.display(), e);
+                    return;
+                }
+            };
+            let Some(reader) = open_events(input) else { return };
+            let mut report = ZoneReport::new(zones);
+            for event in reader.map_while(Result::ok) {
+                report.add(&event);
+            }
+            if let Err(e) = report.write_csv(std::io::stdout().lock()) {
+                eprintln!("输出失败: {}", e);
+            }
+        }
+        #[cfg(feature = "database")]
+        Command::Query { db, query, search, tracks } => {
+            let Some(store) = open_store(db) else { return };
+            let result = match search {
+                Some(text) => store.search_drones(text).map(|drones| {
+                    for d in drones {
+                        println!("{}\t{}\t{}\t{}", d.uas_id, d.operator_id.unwrap_or_default(),
+                            time_format::display_ms(d.first_seen_ms), time_format::display_ms(d.last_seen_ms));
+                    }
+                }),
+                None if *tracks => store.tracks(query).map(|segments| {
+                    for t in segments {
+                        println!("{}\t{}\t{}\t{}\t{:.0} m\t{}", t.uas_id,
+                            time_format::display_ms(t.start_ms), time_format::display_ms(t.end_ms), t.fixes, t.distance_m,
+                            t.max_altitude_m.map_or("-".to_string(), |m| format!("{:.0} m", m)));
+                    }
+                }),
+                None => store.query(query).map(|events| {
+                    for event in events {
+                        println!("{}", canonical::to_json(&event).unwrap());
+                    }
+                }),
+            };
+            if let Err(e) = result {
+                eprintln!("查询失败: {}", e);
+            }
+        }
+        #[cfg(feature = "database")]
+        Command::ExportIncident { db, output, format, query } => {
+            let Some(store) = open_store(db) else { return };
+            match incident::export_bundle(&store, query, output, *format) {
+                Ok(count) => println!("导出 {} 条记录到 {}", count, output.display()),
+                Err(e) => eprintln!("导出失败: {}", e),
+            }
+        }
+        #[cfg(feature = "database")]
+        Command::Import { db, input, format } => {
+            let Some(mut store) = open_store(db) else { return };
+            match import::import_into(&mut store, input, *format) {
+                Ok((imported, skipped)) => println!("导入 {} 条，跳过 {} 条", imported, skipped),
+                Err(e) => eprintln!("导入 {} 失败: {}", input.display(), e),
+            }
+            if let Err(e) = store.close() {
+                eprintln!("关闭数据库失败: {}", e);
+            }
+        }
+        #[cfg(feature = "database")]
+        Command::Batch { db, dir, jobs } => {
+            let Some(mut store) = open_store(db) else { return };
+            match batch::list_pcaps(dir) {
+                Ok(files) => {
+                    eprintln!("使用 {} 个线程处理 {} 个 pcap 文件", batch::thread_count(*jobs, files.len()), files.len());
+                    let insert = |event: DecodedEvent| {
+                        if let Err(e) = store.insert(&event, event.record.operator_id.as_deref()) {
+                            error!("写入数据库失败: {}", e);
+                        }
+                    };
+                    let reports = batch::decode_files(files, *jobs, insert, |report| {
+                        eprintln!("完成 {}: {} 条记录", report.path.display(), report.records);
+                    });
+                    if let Err(e) = batch::write_report(&reports, std::io::stdout().lock()) {
+                        eprintln!("输出失败: {}", e);
+                    }
+                }
+                Err(e) => eprintln!("读取目录 {} 失败: {}", dir.display(), e),
+            }
+            if let Err(e) = store.close() {
+                eprintln!("关闭数据库失败: {}", e);
+            }
+        }
+        Command::Survey { interface, minutes, dwell_ms } => {
+            run_survey(interface.as_deref(), *minutes, Duration::from_millis(*dwell_ms));
+        }
+        Command::Diagnose { interface, seconds } => {
+            if !run_diagnose(interface.as_deref(), Duration::from_secs(*seconds)) {
+                std::process::exit(1);
+            }
+        }
+        Command::Conformance { interface, seconds, input, uas } => {
+            if !run_conformance(interface.as_deref(), Duration::from_secs(*seconds), input.as_deref(), uas.as_deref()) {
+                std::process::exit(1);
+            }
+        }
+        #[cfg(feature = "database")]
+        Command::DbCheck { db } => match storage::check(db) {
+            Ok(status) => {
+                println!("表结构版本: {} (最新 {})", status.version, status.latest);
+                println!("完整性检查: {}", status.integrity);
+                if !status.is_ok() {
+                    std::process::exit(1);
+                }
+            }
+            Err(e) => {
+                eprintln!("检查失败: {}", e);
+                std::process::exit(1);
+            }
+        },
+        #[cfg(feature = "database")]
+        Command::DbMigrate { db } => match Store::open(db) {
+            Ok(_) => println!("已升级到表结构版本 {}", storage::SCHEMA_VERSION),
+            Err(e) => eprintln!("升级失败: {}", e),
+        },
+        Command::Compare { input, baseline, save, a, b, ignore } => {
+            match compare::compare_pcap(input, baseline.as_deref(), save.as_deref(), a, b, ignore, config) {
+                Ok(report) => {
+                    if let Some(path) = save {
+                        eprintln!("已保存 {} 条事件到 {}", report.total_b(), path.display());
+                    }
+                    if let Err(e) = report.write(std::io::stdout().lock()) {
+                        eprintln!("输出失败: {}", e);
+                    }
+                    if !report.is_identical() {
+                        std::process::exit(1);
+                    }
+                }
+                Err(e) => {
+                    eprintln!("比较失败: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Command::ConfigDump { redacted } => {
+            let sensor_id = canonical::sensor_id(config.sensor_id.as_deref());
+            let mut effective = config.effective(&sensor_id, units::output_units());
+            if *redacted {
+                config::redact(&mut effective);
             }
-            0x02 => { // Rate
-                rate = (data[offset] as f32) * 0.5;
-                offset += 1;
+            println!("{}", serde_json::to_string_pretty(&effective).unwrap_or_default());
+        }
+        Command::LoopbackTest { tx, rx, count, interval_ms, channel } => {
+            if !run_loopback(tx, rx, *count, Duration::from_millis(*interval_ms), *channel) {
+                std::process::exit(1);
             }
-            0x12 => { // Channel
-                channel_freq = u16::from_le_bytes([data[offset], data[offset+1]]);
-                offset += 4;
+        }
+        Command::ListInterfaces => list_interfaces(),
+        Command::Client { dashboard, control, action } => {
+            if let Err(e) = RemoteSensor::new(dashboard.as_deref(), control.as_deref()).run(action) {
+                eprintln!("{}", e);
+                std::process::exit(1);
             }
-            _ => break,
         }
     }
+}
 
-    (RadiotapHeader { signal, rate, channel_freq }, &data[header_len..])
+/// 录制文件/数据库所在目录，默认当前目录
+fn data_dir(options: &Options) -> PathBuf {
+    options.store.as_ref().or(options.record.as_ref())
+        .and_then(|path| path.parent())
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .map_or_else(|| PathBuf::from("."), |dir| dir.to_path_buf())
 }
 
 fn main() {
-    let file_appender = rolling::daily("logs", "capture.log");
-    let (non_blocking_appender, _guard) = non_blocking(file_appender);
-    let file_layer = fmt::layer()
-        .with_ansi(false)
-        .with_writer(non_blocking_appender);
-
-    let console_subscriber = fmt::layer().with_writer(std::io::stdout);
-
-    tracing_subscriber::registry().with(console_subscriber).with(file_layer).init();
-    let wifi_devices = get_wifi_devices();
-    if !wifi_devices.is_empty() {
-        capture_wifi_channel(wifi_devices.first().unwrap().clone());
+    let options = Options::parse();
+    time_format::configure(
+        options.timezone.unwrap_or(time_format::OutputTimeZone::Utc),
+        options.display_timezone.unwrap_or(time_format::OutputTimeZone::Local),
+    );
+    clock::configure(options.clock);
+    let mut config = match &options.config {
+        Some(path) => match Config::load(path) {
+            Ok(config) => config,
+            Err(e) => {
+                eprintln!("无法加载配置 {}: {}", path.display(), e);
+                return;
+            }
+        },
+        None => Config::default(),
+    };
+    let env_overrides = match config.apply_env(std::env::vars()) {
+        Ok(applied) => applied,
+        Err(e) => {
+            eprintln!("{}", e);
+            return;
+        }
+    };
+    if let Some(url) = &options.upload_url {
+        config.upload.url.clone_from(url);
+    }
+    config.ignore_ouis.extend(options.filter_ouis.iter().copied());
+    if !options.interfaces.is_empty() {
+        config.interfaces.clone_from(&options.interfaces);
+    }
+    if let Some(path) = &options.output {
+        config.output.ndjson = Some(path.clone());
+    }
+    remote_id::configure_ouis(config.rid_ouis.iter().map(|oui| oui.0).collect());
+    units::configure(match options.units {
+        Some(all) => units::OutputUnits { csv: all, json: all },
+        None => config.units,
+    });
+    if options.print_schema {
+        schema::print_schema();
+        return;
+    }
+    if let Some(command) = &options.command {
+        run_command(command, &config);
+        return;
     }
-}
-
 
-#[cfg(test)]
-mod tests {
-    // 注意这个惯用法：在 tests 模块中，从外部作用域导入所有名字。
-    use super::*;
+    // 终端界面与输出到标准输出的检测记录争用屏幕
+    let tui = cfg!(feature = "tui") && options.tui && !config.output.to_stdout();
+    let logging = logging::init_logging("logs", logging::Console {
+        color: !options.no_color,
+        verbose: options.verbose,
+        stderr: config.output.to_stdout(),
+        quiet: tui,
+    });
+    if options.tui && !tui {
+        if cfg!(feature = "tui") {
+            error!("检测记录输出到标准输出时不能使用 --tui");
+        } else {
+            error!("此构建未启用 tui 特性，忽略 --tui");
+        }
+    }
 
-    #[test]
-    fn test_process_packet() {
-        let file_appender = rolling::daily("logs", "capture.log");
-    let (non_blocking_appender, _guard) = non_blocking(file_appender);
-    let file_layer = fmt::layer()
-        .with_ansi(false)
-        .with_writer(non_blocking_appender);
+    let control = RuntimeControl::new(data_dir(&options));
+    if let Some(logging) = &logging {
+        let level_handle = logging.level.clone();
+        control.set_log_level_hook(move |level| level_handle.reload(level).map_err(|e| e.to_string()));
+    }
+    if !env_overrides.is_empty() {
+        info!("configuration overridden by environment: {}", env_overrides.join(", "));
+    }
+    control.apply_log_level(config.log_level.as_deref());
+    if let Some(addr) = &options.control_listen {
+        control.spawn_http_listener(addr.clone());
+    }
+    if let Some(addr) = &options.gpsd {
+        clock::spawn_gpsd_reader(addr.clone());
+    }
+    receiver::configure(options.site);
+    if let Some(path) = &options.gps_serial {
+        receiver::spawn_nmea_reader(path.clone());
+    }
+    if let Some(kb_per_min) = config.egress_kb_per_min {
+        egress::configure(kb_per_min);
+    }
+    let sensor_id = canonical::sensor_id(config.sensor_id.as_deref());
+    let mut effective = config.effective(&sensor_id, units::output_units());
+    config::redact(&mut effective);
+    control.set_config(effective);
+    let signing = match config.upload.load_signing_key() {
+        Ok(signing) => signing,
+        Err(e) => {
+            error!("无法读取上传签名密钥: {}", e);
+            return;
+        }
+    };
+    if let Some(key) = &signing {
+        info!("signing upload batches with key {} (public key {})", key.key_id(), key.public_key_hex());
+    }
+    let mut output = Output::new(options.record.clone(), config.tags.clone(), sensor_id, config.upload.clone(), signing);
+    output.record_sink = config.output.open()
+        .map_err(|e| error!("无法打开检测输出 {:?}: {}", config.output.ndjson, e))
+        .ok()
+        .flatten();
+    #[cfg(feature = "database")]
+    if let Some(path) = &options.store {
+        output.store = Store::open(path)
+            .map_err(|e| error!("无法打开数据库 {}: {}", path.display(), e))
+            .ok();
+    }
+    #[cfg(not(feature = "database"))]
+    if options.store.is_some() {
+        error!("此构建未启用 database 特性，忽略 --store");
+    }
+    output.reload(config.clone());
+    #[cfg(feature = "database")]
+    output.restore_identities();
+    output.frame_ring = config.frame_ring.clone().map(FrameRing::new);
+    if let Some(remote) = &config.remote_config {
+        remote_config::spawn_puller(remote.clone(), control.clone());
+    }
+    if let Some(path) = &options.pcapng {
+        output.pcapng = PcapngWriter::create(path)
+            .map_err(|e| error!("无法创建 pcapng 文件 {}: {}", path.display(), e))
+            .ok();
+    }
+    if let Some(dir) = &options.pcap_dir {
+        let max_bytes = options.pcap_max_mb.map(|mb| mb * 1024 * 1024);
+        let max_age = options.pcap_max_minutes.map(|minutes| Duration::from_secs(minutes * 60));
+        output.raw_capture = RotatingPcap::new(dir, max_bytes, max_age, options.pcap_rid_only)
+            .map_err(|e| error!("无法创建原始帧存证目录 {}: {}", dir.display(), e))
+            .ok();
+    }
+    #[cfg(feature = "mesh")]
+    if let Some(port) = options.mesh {
+        output.mesh = Mesh::start(&output.sensor_id, port)
+            .map_err(|e| error!("无法启动组网发现: {}", e))
+            .ok();
+        output.locator = Some(MultiSensorLocator::new(options.path_loss.unwrap_or(config.path_loss)));
+    }
+    #[cfg(not(feature = "mesh"))]
+    if options.mesh.is_some() {
+        error!("此构建未启用 mesh 特性，忽略 --mesh");
+    }
+    if options.netrid_listen.is_some() || options.netrid_poll.is_some() {
+        let network = NetworkIngest::default();
+        if let Some(addr) = &options.netrid_listen {
+            network.spawn_http_listener(addr.clone());
+        }
+        if let Some(url) = &options.netrid_poll {
+            network.spawn_poller(url.clone(), Duration::from_secs(1));
+        }
+        output.network = Some(network);
+    }
+    #[cfg(feature = "bluetooth")]
+    if let Some(device) = &options.ble {
+        output.ble = Some(BleIngest::spawn(device.clone(), decode_context(&options, &config)));
+    }
+    #[cfg(not(feature = "bluetooth"))]
+    if options.ble.is_some() {
+        error!("此构建未启用 bluetooth 特性，忽略 --ble");
+    }
+    if let Some(addr) = &options.sbs_listen {
+        output.sbs = SbsServer::listen(addr)
+            .map_err(|e| error!("无法监听 SBS-1 输出 {}: {}", addr, e))
+            .ok();
+    }
+    if let Some(target) = &options.asterix {
+        let (sac, sic) = options.asterix_sac_sic;
+        output.asterix = AsterixSender::new(target, sac, sic)
+            .map_err(|e| error!("无法创建 ASTERIX 输出 {}: {}", target, e))
+            .ok();
+    }
+    if let Some(addr) = &options.live_listen {
+        output.live_feed = LiveFeed::listen(addr)
+            .map_err(|e| error!("无法监听实时检测推送 {}: {}", addr, e))
+            .ok();
+    }
+    // 回放录制文件或抓包文件时的倍速/暂停/跳转控制：终端界面运行时由界面按键控制，
+    // 否则从标准输入读取命令；配置了控制接口时也可经 /playback/* 调整
+    let playback = (options.replay.is_some() || options.read_file.is_some())
+        .then(|| PlaybackControl::new(options.replay_speed));
+    if let Some(playback) = &playback {
+        control.set_playback(playback.clone());
+        if !tui {
+            playback::spawn_stdin_controller(playback.clone());
+        }
+    }
+    #[cfg(feature = "tui")]
+    if tui {
+        output.tui = Some(Tui::start(playback.clone()));
+    }
+    #[cfg(feature = "dashboard")]
+    if let Some(addr) = &options.geojson_listen {
+        let mut layer = LiveLayer::new(5 * 60 * 1000);
+        if let Some(path) = &options.basemap {
+            match std::fs::read_to_string(path) {
+                Ok(geojson) => layer = layer.with_basemap(geojson),
+                Err(e) => error!("无法读取底图 {}: {}", path.display(), e),
+            }
+        }
+        if let Some(path) = &options.store {
+            layer = layer.with_history(path.clone());
+        }
+        if let Some(path) = &options.zones {
+            match geofence::load_geojson(path) {
+                Ok(zones) => layer = layer.with_zones(zones),
+                Err(e) => error!("无法加载区域 {}: {}", path.display(), e),
+            }
+        }
+        if let Some((lat, lon)) = options.site {
+            layer = layer.with_site(lat, lon);
+        }
+        layer.spawn_http_listener(addr.clone());
+        output.live_layer = Some(layer);
+    }
+    #[cfg(not(feature = "dashboard"))]
+    if options.geojson_listen.is_some() {
+        error!("此构建未启用 dashboard 特性，忽略 --geojson-listen");
+    }
 
-    let console_subscriber = fmt::layer().with_writer(std::io::stdout);
+    if let (Some(path), Some(playback)) = (&options.replay, &playback) {
+        info!("replaying {} at {}x", path.display(), options.replay_speed);
+        match event_log::replay(path, playback, |event| output.emit(event)) {
+            Ok(count) => info!("replayed {} events", count),
+            Err(e) => error!("回放失败: {}", e),
+        }
+        output.shutdown(&control, config.shutdown);
+        return;
+    }
+    if let (Some(path), Some(playback)) = (&options.read_file, &playback) {
+        info!("reading {} at {}x", path.display(), options.replay_speed);
+        let mut ctx = decode_context(&options, &config);
+        // 按抓包时间戳的间隔推进，跳转与回放录制文件相同：向后跳转时从文件头重新读取
+        let mut pacer = Pacer::new(playback);
+        let mut records = 0;
+        let result = loop {
+            let mut rewind = false;
+            let result = decode_pcap(path, &mut ctx, |event| {
+                match pacer.admit(event.received_at_ms) {
+                    Pace::Emit => {}
+                    Pace::Skip => return Ok(()),
+                    Pace::Rewind => {
+                        rewind = true;
+                        return Err("rewind".into());
+                    }
+                }
+                output.emit(event);
+                output.poll(&control);
+                records += 1;
+                Ok(())
+            });
+            if !rewind {
+                break result;
+            }
+        };
+        match result {
+            Ok(frames) => info!("read {} frames, {} remote id records from {}", frames, records, path.display()),
+            Err(e) => error!("读取 {} 失败: {}", path.display(), e),
+        }
+        output.shutdown(&control, config.shutdown);
+        return;
+    }
 
-    tracing_subscriber::registry().with(console_subscriber).with(file_layer).init();
-        let packet = vec![0x00, 0x00, 0x26, 0x00, 0x2f, 0x40, 0x00, 0xa0,  0x20, 0x08, 0x00, 0xa0, 0x20, 0x08, 0x00, 0x00,
-                                   0x74, 0x71, 0xf3, 0x0b, 0x00, 0x00, 0x00, 0x00,  0x10, 0x0c, 0x85, 0x09, 0xc0, 0x00, 0x10, 0x00,
-                                   0x00, 0x00, 0xc4, 0x00, 0x10, 0x01, 0x80, 0x00,  0x00, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
-                                   0xe4, 0x7a, 0x2c, 0x24, 0x3d, 0x26, 0xe4, 0x7a,  0x2c, 0x24, 0x3d, 0x26, 0x00, 0x00, 0x80, 0x84,
-                                   0x00, 0x05, 0x00, 0x00, 0x00, 0x00, 0xa0, 0x00,  0x20, 0x04, 0x00, 0x18, 0x52, 0x49, 0x44, 0x2d,
-                                   0x31, 0x35, 0x38, 0x31, 0x46, 0x37, 0x46, 0x56,  0x43, 0x32, 0x35, 0x31, 0x41, 0x30, 0x30, 0x43,
-                                   0x51, 0x32, 0x35, 0x43, 0xdd, 0x53, 0xfa, 0x0b,  0xbc, 0x0d, 0x75, 0xf1, 0x19, 0x03, 0x01, 0x12,
-                                   0x31, 0x35, 0x38, 0x31, 0x46, 0x37, 0x46, 0x56,  0x43, 0x32, 0x35, 0x31, 0x41, 0x30, 0x30, 0x43,
-                                   0x51, 0x32, 0x35, 0x43, 0x00, 0x00, 0x00, 0x11,  0x22, 0xb5, 0x00, 0x00, 0xfd, 0x1d, 0xdd, 0x18,
-                                   0xe3, 0x39, 0x9a, 0x49, 0xf2, 0x08, 0x48, 0x08,  0xd2, 0x07, 0x3b, 0x04, 0xee, 0x13, 0x0a, 0x00,
-                                   0x41, 0x08, 0x00, 0x1e, 0xdd, 0x18, 0x00, 0x3a,  0x9a, 0x49, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00,
-                                   0x00, 0x01, 0x46, 0x08, 0xae, 0xce, 0xd1, 0x0b,  0x00, 0xb6, 0xba, 0x45, 0xe7];
-        info!("start process packet.");
-        process_packet(&packet);
-        assert_eq!(4, 3);
+    // 配置了多网卡且未用 --interface 指定时按配置抓包，否则单网卡抓包；
+    // 没有可用的无线网卡但指定了 --ble 时只扫描蓝牙
+    let mut ble_only = false;
+    let interface = if config.interfaces.is_empty() || options.interface.is_some() {
+        match select_interface(options.interface.as_deref()) {
+            Ok(interface) => Some(interface),
+            Err(e) if cfg!(feature = "bluetooth") && options.ble.is_some() && options.interface.is_none() => {
+                info!("no WiFi capture interface ({}), capturing Bluetooth LE only", e);
+                ble_only = true;
+                None
+            }
+            Err(e) => {
+                error!("{}", e);
+                return;
+            }
+        }
+    } else {
+        None
+    };
+    #[cfg(feature = "monitor")]
+    let (interface, _monitors) = if options.monitor && !ble_only {
+        match setup_monitors(interface, &mut config) {
+            Ok(ready) => ready,
+            Err(e) => {
+                error!("无法设置监听模式: {}", e);
+                return;
+            }
+        }
+    } else {
+        (interface, Vec::new())
+    };
+    #[cfg(not(feature = "monitor"))]
+    if options.monitor {
+        error!("此构建未启用 monitor 特性，忽略 --monitor");
+    }
+    let adapters: Vec<String> = match &interface {
+        Some(interface) => vec![interface.name.clone()],
+        None => config.interfaces.iter().map(|p| p.name.clone()).collect(),
+    };
+    let environment = EnvironmentReport::collect(&adapters, output.sinks(),
+        ClockReport::collect(options.clock.name(), options.gpsd.clone()));
+    environment.log();
+    control.set_environment(environment);
+    SystemTelemetry::spawn_reporter(data_dir(&options), Duration::from_secs(300));
+    output.latency = LatencyMetrics::new(Duration::from_secs(60));
+    // 健康报告读取看门狗登记的各网卡收帧时间，因此总是登记，只在设置了超时时重新初始化网卡
+    let watchdog = Watchdog::new(Duration::from_secs(options.watchdog_secs));
+    if options.watchdog_secs > 0 {
+        watchdog.spawn();
+    }
+    let mut health = HealthMonitor::new(config.health.clone().unwrap_or_default(), &output.sensor_id, watchdog.clone());
+    if let Some(health_config) = &config.health {
+        match &health_config.webhook {
+            Some(url) => health.send_heartbeats(url.clone(), None),
+            None => health.send_heartbeats(config.upload.url.clone(), config.upload.token.clone()),
+        }
+        if let Some(addr) = &health_config.listen {
+            health.spawn_http_listener(addr.clone());
+        }
+    }
+    health.ready();
+    output.health = Some(health);
+    let watchdog = Some(watchdog);
+    let mut ctx = decode_context(&options, &config);
+    shutdown::install(config.shutdown);
+    if let Some(interface) = interface {
+        let profile = InterfaceProfile { dwell_ms: options.dwell_ms, ..InterfaceProfile::new(&interface.name, options.hop.clone()) };
+        capture_profiles(&[profile], &options, &config, &mut ctx, &mut output, &control, watchdog.as_ref());
+    } else if ble_only {
+        while !shutdown::requested() {
+            output.poll(&control);
+            thread::sleep(Duration::from_millis(100));
+        }
+    } else {
+        capture_profiles(&config.interfaces, &options, &config, &mut ctx, &mut output, &control, watchdog.as_ref());
     }
+    output.shutdown(&control, config.shutdown);
+}
+
 
-}
\ No newline at end of file
@@ -0,0 +1,547 @@
+use std::fs::File;
+use std::io;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use memmap2::Mmap;
+use tracing::info;
+
+use crate::decode::{parse_80211_mgt, process_packet, DecodeContext};
+use crate::event_log::DecodedEvent;
+use crate::import::{self, LINKTYPE_IEEE802_11, LINKTYPE_RADIOTAP};
+use crate::pcapng::{BLOCK_EPB, BLOCK_IDB, BLOCK_SHB};
+use crate::radiotap::RadiotapHeader;
+
+/// 映射文件中的一帧，数据直接引用文件内容，不复制
+pub struct PcapFrame<'a> {
+    pub timestamp_ms: i64,
+    pub link_type: u32,
+    pub data: &'a [u8],
+}
+
+/// 内存映射的 pcap/pcapng 文件，供多 GB 抓包的离线分析使用
+///
+/// 文件内容由内核按需换页，不整体读入内存；映射期间文件不得被截断。
+pub struct MappedPcap {
+    map: Mmap,
+}
+
+impl MappedPcap {
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let file = File::open(path)?;
+        // SAFETY: 只读映射；抓包文件在分析期间不应被其他进程截断或改写
+        let map = unsafe { Mmap::map(&file)? };
+        #[cfg(unix)]
+        let _ = map.advise(memmap2::Advice::Sequential);
+        Ok(Self { map })
+    }
+
+    /// 文件字节数
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    pub fn frames(&self) -> io::Result<PcapFrames<'_>> {
+        PcapFrames::new(&self.map)
+    }
+}
+
+/// 逐帧遍历 pcap/pcapng 字节序列，支持大小端及纳秒时间戳格式
+///
+/// pcapng 只读取增强分组块 (EPB)，按所属接口的链路类型和时间戳精度解释；
+/// 多个节的文件在每个节头处重新确定字节序并重置接口列表。
+pub struct PcapFrames<'a> {
+    data: &'a [u8],
+    offset: usize,
+    big_endian: bool,
+    layout: Layout,
+    /// pcap 文件头中的链路类型；pcapng 为第一个接口的链路类型
+    pub link_type: u32,
+}
+
+impl<'a> PcapFrames<'a> {
+    pub fn new(data: &'a [u8]) -> io::Result<Self> {
+        let magic: [u8; 4] = data.get(..4).ok_or_else(|| invalid("文件过短"))?.try_into().unwrap();
+        if u32::from_le_bytes(magic) == BLOCK_SHB {
+            let mut frames = Self { data, offset: 0, big_endian: false, layout: Layout::Pcapng { interfaces: Vec::new() }, link_type: 0 };
+            // 接口描述块必须位于引用它的分组之前，读到第一个为止以确定链路类型
+            while frames.link_type == 0 {
+                match frames.next_block()? {
+                    Some((BLOCK_IDB, body)) => frames.link_type = frames.add_interface(body)?,
+                    Some(_) => {}
+                    None => return Err(invalid("pcapng 文件没有接口描述块")),
+                }
+            }
+            return Ok(frames);
+        }
+        let header = data.get(..24).ok_or_else(|| invalid("pcap 文件头不完整"))?;
+        let (big_endian, nanos) = pcap_magic(magic)?;
+        let link_type = u32_at(header, 20, big_endian);
+        Ok(Self { data, offset: 24, big_endian, layout: Layout::Pcap { nanos }, link_type })
+    }
+
+    /// 已遍历的字节数，用于进度报告
+    pub fn position(&self) -> usize {
+        self.offset
+    }
+
+    fn take(&mut self, len: usize) -> io::Result<&'a [u8]> {
+        let bytes = self.data.get(self.offset..self.offset + len).ok_or_else(|| invalid("文件在记录中间截断"))?;
+        self.offset += len;
+        Ok(bytes)
+    }
+
+    /// 下一个 pcapng 块的类型及块体（不含首尾长度）；节头块在此处理
+    fn next_block(&mut self) -> io::Result<Option<(u32, &'a [u8])>> {
+        while self.offset < self.data.len() {
+            let header = self.take(8)?;
+            // 节头块类型是回文，与字节序无关
+            let is_section = u32::from_le_bytes(header[..4].try_into().unwrap()) == BLOCK_SHB;
+            if is_section {
+                self.big_endian = section_big_endian(self.data.get(self.offset..self.offset + 4).unwrap_or_default())?;
+                self.layout = Layout::Pcapng { interfaces: Vec::new() };
+            }
+            let block_type = u32_at(header, 0, self.big_endian);
+            let total_len = u32_at(header, 4, self.big_endian) as usize;
+            if total_len < 12 || !total_len.is_multiple_of(4) {
+                return Err(invalid("pcapng 块长度无效"));
+            }
+            let body = self.take(total_len - 8)?;
+            if !is_section {
+                return Ok(Some((block_type, &body[..total_len - 12])));
+            }
+        }
+        Ok(None)
+    }
+
+    fn add_interface(&mut self, body: &[u8]) -> io::Result<u32> {
+        let interface = parse_interface(body, self.big_endian)?;
+        if let Layout::Pcapng { interfaces } = &mut self.layout {
+            interfaces.push(interface);
+        }
+        Ok(interface.0)
+    }
+
+    fn next_frame(&mut self) -> io::Result<Option<PcapFrame<'a>>> {
+        if let Layout::Pcap { nanos } = self.layout {
+            if self.offset >= self.data.len() {
+                return Ok(None);
+            }
+            let header = self.take(16)?;
+            let timestamp_ms = pcap_timestamp_ms(u32_at(header, 0, self.big_endian), u32_at(header, 4, self.big_endian), nanos);
+            let captured = u32_at(header, 8, self.big_endian) as usize;
+            let data = self.take(captured)?;
+            return Ok(Some(PcapFrame { timestamp_ms, link_type: self.link_type, data }));
+        }
+        while let Some((block_type, body)) = self.next_block()? {
+            match block_type {
+                BLOCK_IDB => {
+                    self.add_interface(body)?;
+                }
+                BLOCK_EPB if body.len() >= 20 => {
+                    let Layout::Pcapng { interfaces } = &self.layout else { unreachable!() };
+                    let (timestamp_ms, link_type, range) = parse_epb(body, self.big_endian, interfaces)?;
+                    return Ok(Some(PcapFrame { timestamp_ms, link_type, data: &body[range] }));
+                }
+                _ => {}
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl<'a> Iterator for PcapFrames<'a> {
+    type Item = io::Result<PcapFrame<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let frame = self.next_frame().transpose();
+        if matches!(frame, Some(Err(_))) {
+            // 出错后停止，避免在损坏处反复报错
+            self.offset = self.data.len();
+        }
+        frame
+    }
+}
+
+/// 接口描述块中的时间戳精度选项
+const OPTION_IF_TSRESOL: u16 = 9;
+
+enum Layout {
+    Pcap { nanos: bool },
+    /// 当前节中各接口的 (链路类型, 每秒时间戳单位数)
+    Pcapng { interfaces: Vec<(u32, u64)> },
+}
+
+fn u32_at(bytes: &[u8], offset: usize, big_endian: bool) -> u32 {
+    let b: [u8; 4] = bytes[offset..offset + 4].try_into().unwrap();
+    if big_endian { u32::from_be_bytes(b) } else { u32::from_le_bytes(b) }
+}
+
+fn u16_at(bytes: &[u8], offset: usize, big_endian: bool) -> u16 {
+    let b: [u8; 2] = bytes[offset..offset + 2].try_into().unwrap();
+    if big_endian { u16::from_be_bytes(b) } else { u16::from_le_bytes(b) }
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+/// 由 pcap 文件头魔数确定 (大端序, 纳秒时间戳)
+fn pcap_magic(magic: [u8; 4]) -> io::Result<(bool, bool)> {
+    match magic {
+        [0xd4, 0xc3, 0xb2, 0xa1] => Ok((false, false)),
+        [0xa1, 0xb2, 0xc3, 0xd4] => Ok((true, false)),
+        [0x4d, 0x3c, 0xb2, 0xa1] => Ok((false, true)),
+        [0xa1, 0xb2, 0x3c, 0x4d] => Ok((true, true)),
+        _ => Err(invalid("不是 pcap/pcapng 文件")),
+    }
+}
+
+/// 由节头块的字节序标记确定本节是否为大端序
+fn section_big_endian(byte_order_magic: &[u8]) -> io::Result<bool> {
+    match byte_order_magic {
+        [0x4d, 0x3c, 0x2b, 0x1a] => Ok(false),
+        [0x1a, 0x2b, 0x3c, 0x4d] => Ok(true),
+        _ => Err(invalid("pcapng 字节序标记无效")),
+    }
+}
+
+fn pcap_timestamp_ms(seconds: u32, fraction: u32, nanos: bool) -> i64 {
+    let fraction_ms = if nanos { fraction / 1_000_000 } else { fraction / 1_000 };
+    seconds as i64 * 1000 + fraction_ms as i64
+}
+
+/// 解析接口描述块体，返回 (链路类型, 每秒时间戳单位数)
+fn parse_interface(body: &[u8], big_endian: bool) -> io::Result<(u32, u64)> {
+    if body.len() < 8 {
+        return Err(invalid("pcapng 接口描述块过短"));
+    }
+    let link_type = u16_at(body, 0, big_endian) as u32;
+    let mut units_per_sec = 1_000_000;
+    let mut options = &body[8..];
+    while options.len() >= 4 {
+        let (code, len) = (u16_at(options, 0, big_endian), u16_at(options, 2, big_endian) as usize);
+        if code == 0 {
+            break;
+        }
+        if code == OPTION_IF_TSRESOL && len == 1 && options.len() > 4 {
+            let resolution = options[4];
+            let exponent = (resolution & 0x7f) as u32;
+            units_per_sec = if resolution & 0x80 != 0 { 2u64.checked_pow(exponent) } else { 10u64.checked_pow(exponent) }
+                .unwrap_or(units_per_sec);
+        }
+        options = options.get(4 + len.next_multiple_of(4)..).unwrap_or_default();
+    }
+    Ok((link_type, units_per_sec))
+}
+
+/// 解析增强分组块体，返回 (时间戳毫秒, 链路类型, 帧数据在块体中的范围)
+fn parse_epb(body: &[u8], big_endian: bool, interfaces: &[(u32, u64)]) -> io::Result<(i64, u32, Range<usize>)> {
+    let interface = u32_at(body, 0, big_endian) as usize;
+    let &(link_type, units_per_sec) = interfaces.get(interface)
+        .ok_or_else(|| invalid("分组引用了未声明的接口"))?;
+    let units = (u32_at(body, 4, big_endian) as u64) << 32 | u32_at(body, 8, big_endian) as u64;
+    let captured = u32_at(body, 12, big_endian) as usize;
+    if body.len() < 20 + captured {
+        return Err(invalid("pcapng 分组长度超出块"));
+    }
+    let timestamp_ms = (units as u128 * 1000 / units_per_sec as u128) as i64;
+    Ok((timestamp_ms, link_type, 20..20 + captured))
+}
+
+/// 大文件读取进度，最多每隔 `interval` 输出一次已处理比例和吞吐
+pub struct Progress {
+    path: PathBuf,
+    total: usize,
+    started: Instant,
+    last_report: Instant,
+    interval: Duration,
+}
+
+impl Progress {
+    pub fn new(path: &Path, total: usize, interval: Duration) -> Self {
+        let now = Instant::now();
+        Self { path: path.to_path_buf(), total, started: now, last_report: now, interval }
+    }
+
+    pub fn update(&mut self, position: usize) {
+        if self.last_report.elapsed() < self.interval || self.total == 0 {
+            return;
+        }
+        self.last_report = Instant::now();
+        let mb = position as f64 / 1e6;
+        info!("{}: {:.1}% ({:.0} / {:.0} MB, {:.1} MB/s)", self.path.display(),
+            position as f64 * 100.0 / self.total as f64, mb, self.total as f64 / 1e6,
+            mb / self.started.elapsed().as_secs_f64());
+    }
+}
+
+/// 解码抓包文件时输出进度的间隔
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(10);
+
+/// 逐帧解码 pcap/pcapng 文件，每条记录以抓包时间戳交给 `on_event`，返回总帧数
+///
+/// 文件以内存映射方式读取，帧数据不复制直接交给解码器；大文件每 10 秒输出一次进度。
+/// `on_event` 返回错误时停止读取并返回该错误。
+pub fn decode_pcap<F>(input: &Path, ctx: &mut DecodeContext, mut on_event: F) -> Result<usize, String>
+where
+    F: FnMut(DecodedEvent) -> Result<(), String>,
+{
+    let mapped = MappedPcap::open(input).map_err(|e| e.to_string())?;
+    let mut reader = mapped.frames().map_err(|e| e.to_string())?;
+    let link_type = reader.link_type;
+    if link_type != LINKTYPE_RADIOTAP && link_type != LINKTYPE_IEEE802_11 {
+        return Err(format!("不支持的链路类型 {}", link_type));
+    }
+    let sensor = import::file_sensor(input);
+    let mut progress = Progress::new(input, mapped.len(), PROGRESS_INTERVAL);
+    let mut frames = 0;
+    while let Some(frame) = reader.next() {
+        let frame = frame.map_err(|e| e.to_string())?;
+        frames += 1;
+        let records = match frame.link_type {
+            LINKTYPE_RADIOTAP => process_packet(frame.data, ctx),
+            LINKTYPE_IEEE802_11 => parse_80211_mgt(frame.data, &RadiotapHeader::default(), ctx),
+            _ => Vec::new(),
+        };
+        for record in records {
+            let mut event = DecodedEvent { received_at_ms: frame.timestamp_ms, record };
+            event.assign_id(&sensor);
+            on_event(event)?;
+        }
+        progress.update(reader.position());
+    }
+    Ok(frames)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pcap() -> Vec<u8> {
+        let mut pcap = Vec::new();
+        let mut writer = crate::pcap_writer::PcapWriter::new(&mut pcap).unwrap();
+        writer.write(1_748_764_800_500_000, &[0x00, 0x00, 0x08, 0x00, 0x80]).unwrap();
+        writer.write(1_748_764_801_250_000, &[0xab; 40]).unwrap();
+        pcap
+    }
+
+    fn pcapng() -> Vec<u8> {
+        let mut pcapng = Vec::new();
+        let mut writer = crate::pcapng::PcapngWriter::new(&mut pcapng).unwrap();
+        writer.write(1_748_764_800_500_250, &[0x00, 0x00, 0x08, 0x00, 0x80], Some("Remote ID")).unwrap();
+        writer.write(1_748_764_801_000_000, &[0x00; 3], None).unwrap();
+        pcapng
+    }
+
+    /// 经典 pcap：文件头 + 每帧 (秒, 秒内小数, 长度)
+    fn raw_pcap(magic: [u8; 4], big_endian: bool, frames: &[(u32, u32, &[u8])]) -> Vec<u8> {
+        let word = |v: u32| if big_endian { v.to_be_bytes() } else { v.to_le_bytes() };
+        let mut bytes = magic.to_vec();
+        bytes.extend_from_slice(&if big_endian { [0, 2, 0, 4] } else { [2, 0, 4, 0] });
+        bytes.extend_from_slice(&[0; 8]);
+        bytes.extend_from_slice(&word(65535));
+        bytes.extend_from_slice(&word(LINKTYPE_IEEE802_11));
+        for (seconds, fraction, data) in frames {
+            for v in [*seconds, *fraction, data.len() as u32, data.len() as u32] {
+                bytes.extend_from_slice(&word(v));
+            }
+            bytes.extend_from_slice(data);
+        }
+        bytes
+    }
+
+    /// pcapng 块，块体需已按 4 字节对齐
+    fn block(block_type: u32, body: &[u8], big_endian: bool) -> Vec<u8> {
+        let word = |v: u32| if big_endian { v.to_be_bytes() } else { v.to_le_bytes() };
+        let total = body.len() as u32 + 12;
+        [&word(block_type)[..], &word(total), body, &word(total)].concat()
+    }
+
+    fn section(big_endian: bool) -> Vec<u8> {
+        let magic: u32 = 0x1A2B_3C4D;
+        let body = [if big_endian { magic.to_be_bytes() } else { magic.to_le_bytes() }, [0; 4], [0xff; 4], [0xff; 4]].concat();
+        block(BLOCK_SHB, &body, big_endian)
+    }
+
+    fn interface(link_type: u16, tsresol: Option<u8>, big_endian: bool) -> Vec<u8> {
+        let half = |v: u16| if big_endian { v.to_be_bytes() } else { v.to_le_bytes() };
+        let mut body = [&half(link_type)[..], &[0, 0], &[0, 0, 0xff, 0xff]].concat();
+        if let Some(resolution) = tsresol {
+            body.extend_from_slice(&[&half(OPTION_IF_TSRESOL)[..], &half(1), &[resolution, 0, 0, 0], &[0; 4]].concat());
+        }
+        block(BLOCK_IDB, &body, big_endian)
+    }
+
+    fn packet(interface: u32, units: u64, data: &[u8], big_endian: bool) -> Vec<u8> {
+        let word = |v: u32| if big_endian { v.to_be_bytes() } else { v.to_le_bytes() };
+        let mut body = [word(interface), word((units >> 32) as u32), word(units as u32), word(data.len() as u32), word(data.len() as u32)].concat();
+        body.extend_from_slice(data);
+        body.resize(body.len().next_multiple_of(4), 0);
+        block(BLOCK_EPB, &body, big_endian)
+    }
+
+    fn read_all(bytes: &[u8]) -> io::Result<Vec<PcapFrame<'_>>> {
+        PcapFrames::new(bytes)?.collect()
+    }
+
+    #[test]
+    fn test_written_pcap() {
+        let bytes = pcap();
+        let frames = read_all(&bytes).unwrap();
+        assert_eq!(PcapFrames::new(&bytes).unwrap().link_type, LINKTYPE_RADIOTAP);
+        assert_eq!(frames.iter().map(|f| (f.timestamp_ms, f.data.len())).collect::<Vec<_>>(),
+            [(1_748_764_800_500, 5), (1_748_764_801_250, 40)]);
+    }
+
+    #[test]
+    fn test_pcapng_sections_are_concatenated() {
+        let bytes = [pcapng(), pcapng()].concat();
+        let frames = read_all(&bytes).unwrap();
+        assert_eq!(frames.iter().map(|f| f.timestamp_ms).collect::<Vec<_>>(),
+            [1_748_764_800_500, 1_748_764_801_000, 1_748_764_800_500, 1_748_764_801_000]);
+    }
+
+    #[test]
+    fn test_read_pcap_little_endian_micros() {
+        let bytes = raw_pcap([0xd4, 0xc3, 0xb2, 0xa1], false, &[(1_748_764_800, 500_250, &[1, 2, 3])]);
+        let reader = PcapFrames::new(&bytes).unwrap();
+        assert_eq!(reader.link_type, LINKTYPE_IEEE802_11);
+        let records = read_all(&bytes).unwrap();
+        assert_eq!((records[0].timestamp_ms, records[0].link_type), (1_748_764_800_500, LINKTYPE_IEEE802_11));
+        assert_eq!(records[0].data, [1, 2, 3]);
+    }
+
+    #[test]
+    fn test_read_pcap_big_endian_nanos() {
+        let bytes = raw_pcap([0xa1, 0xb2, 0x3c, 0x4d], true, &[(10, 250_000_000, &[7]), (11, 0, &[8, 9])]);
+        let records = read_all(&bytes).unwrap();
+        assert_eq!(records.iter().map(|r| r.timestamp_ms).collect::<Vec<_>>(), [10_250, 11_000]);
+        assert_eq!(records[1].data, [8, 9]);
+    }
+
+    #[test]
+    fn test_read_pcap_truncated_frame() {
+        let mut bytes = raw_pcap([0xd4, 0xc3, 0xb2, 0xa1], false, &[(1, 0, &[1, 2, 3, 4])]);
+        bytes.truncate(bytes.len() - 2);
+        assert_eq!(read_all(&bytes).err().unwrap().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_unknown_magic_is_rejected() {
+        assert_eq!(PcapFrames::new(&[0u8; 24]).err().unwrap().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_read_pcapng() {
+        let mut bytes = Vec::new();
+        {
+            let mut writer = crate::pcapng::PcapngWriter::new(&mut bytes).unwrap();
+            writer.write(1_748_764_800_500_250, &[0x00, 0x00, 0x08, 0x00, 0x80], Some("Remote ID")).unwrap();
+            writer.write(1_748_764_801_000_000, &[0x00; 3], None).unwrap();
+        }
+
+        let reader = PcapFrames::new(&bytes).unwrap();
+        assert_eq!(reader.link_type, LINKTYPE_RADIOTAP);
+        let records: Vec<PcapFrame> = reader.collect::<io::Result<_>>().unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].timestamp_ms, 1_748_764_800_500);
+        assert_eq!(records[0].data, [0x00, 0x00, 0x08, 0x00, 0x80]);
+        assert_eq!(records[1].data.len(), 3);
+    }
+
+    #[test]
+    fn test_read_pcapng_big_endian() {
+        let bytes = [section(true), interface(105, None, true), packet(0, 2_000_000, &[1, 2, 3, 4, 5], true)].concat();
+        let records = read_all(&bytes).unwrap();
+        assert_eq!((records[0].timestamp_ms, records[0].link_type), (2_000, LINKTYPE_IEEE802_11));
+        assert_eq!(records[0].data, [1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn test_read_pcapng_timestamp_resolution() {
+        // if_tsresol = 9：纳秒；0x80 | 10：2^-10 秒
+        let bytes = [
+            section(false),
+            interface(127, Some(9), false),
+            interface(105, Some(0x80 | 10), false),
+            packet(0, 1_500_000_000, &[1], false),
+            packet(1, 2048, &[2], false),
+        ].concat();
+        let records = read_all(&bytes).unwrap();
+        assert_eq!(records.iter().map(|r| (r.timestamp_ms, r.link_type)).collect::<Vec<_>>(),
+            [(1_500, LINKTYPE_RADIOTAP), (2_000, LINKTYPE_IEEE802_11)]);
+    }
+
+    #[test]
+    fn test_read_pcapng_new_section_resets_interfaces() {
+        let bytes = [
+            section(false), interface(127, None, false), packet(0, 1_000_000, &[1], false),
+            section(true), interface(105, None, true), packet(0, 2_000_000, &[2], true),
+        ].concat();
+        let records = read_all(&bytes).unwrap();
+        assert_eq!(records.iter().map(|r| (r.link_type, r.data[0])).collect::<Vec<_>>(),
+            [(LINKTYPE_RADIOTAP, 1), (LINKTYPE_IEEE802_11, 2)]);
+    }
+
+    #[test]
+    fn test_read_pcapng_errors() {
+        // 只有节头，没有接口描述块
+        assert_eq!(PcapFrames::new(&section(false)).err().unwrap().kind(), io::ErrorKind::InvalidData);
+        // 分组引用未声明的接口
+        let bytes = [section(false), interface(127, None, false), packet(1, 0, &[1], false)].concat();
+        assert_eq!(read_all(&bytes).err().unwrap().kind(), io::ErrorKind::InvalidData);
+        // 块长度不是 4 的倍数
+        let last = packet(0, 0, &[1], false);
+        let mut bytes = [section(false), interface(127, None, false), last.clone()].concat();
+        let offset = bytes.len() - last.len();
+        bytes[offset + 4] -= 1;
+        assert_eq!(read_all(&bytes).err().unwrap().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_mapped_file() {
+        let path = std::env::temp_dir().join("wifi-capture-mapped-pcap-test");
+        let bytes = pcapng();
+        std::fs::write(&path, &bytes).unwrap();
+        let mapped = MappedPcap::open(&path).unwrap();
+        assert_eq!((mapped.len(), mapped.is_empty()), (bytes.len(), false));
+        let frames: Vec<_> = mapped.frames().unwrap().map(|f| f.unwrap().data.len()).collect();
+        assert_eq!(frames, [5, 3]);
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_position_tracks_progress() {
+        let bytes = pcap();
+        let mut frames = PcapFrames::new(&bytes).unwrap();
+        assert_eq!(frames.position(), 24);
+        frames.next().unwrap().unwrap();
+        assert_eq!(frames.position(), 24 + 16 + 5);
+        frames.next().unwrap().unwrap();
+        assert_eq!(frames.position(), bytes.len());
+    }
+
+    #[test]
+    fn test_truncated_record_fails_once() {
+        let bytes = pcap();
+        let mut frames = PcapFrames::new(&bytes[..bytes.len() - 1]).unwrap();
+        assert!(frames.next().unwrap().is_ok());
+        assert!(frames.next().unwrap().is_err());
+        assert!(frames.next().is_none());
+    }
+
+    #[test]
+    fn test_invalid_headers() {
+        assert!(PcapFrames::new(&[0xd4, 0xc3]).is_err());
+        assert!(PcapFrames::new(&pcap()[..20]).is_err());
+        assert!(PcapFrames::new(&[0u8; 24]).is_err());
+        // 只有节头块
+        assert!(PcapFrames::new(&pcapng()[..28]).is_err());
+    }
+}
@@ -0,0 +1,214 @@
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use mdns_sd::{ServiceDaemon, ServiceEvent, ServiceInfo};
+use tracing::{error, info, warn};
+
+use crate::event_log::DecodedEvent;
+
+/// 局域网内接收站互相发现使用的 DNS-SD 服务类型
+pub const SERVICE_TYPE: &str = "_wifi-capture._tcp.local.";
+
+/// 选举汇聚节点：传感器 ID 最小者当选，各节点无需协商即可得出相同结果
+pub fn elect<'a>(candidates: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    candidates.into_iter().min()
+}
+
+/// 多接收站组网：通过 mDNS/DNS-SD 发现同一局域网内的其他接收站，选出一个汇聚节点，
+/// 其余节点把解码事件自动转发给它，现场临时部署无需手工配置地址。
+///
+/// 转发格式为每行一个 JSON 编码的 [`DecodedEvent`]，经 TCP 发送到汇聚节点的组网端口。
+/// 转发的事件保留来源节点生成的记录 ID 和标签。
+pub struct Mesh {
+    sensor_id: String,
+    peers: Arc<Mutex<HashMap<String, SocketAddr>>>,
+    forward: mpsc::Sender<DecodedEvent>,
+    ingest: mpsc::Receiver<DecodedEvent>,
+    _daemon: ServiceDaemon,
+}
+
+impl Mesh {
+    /// 在 `port` 上接收其他节点转发的事件，并在局域网内广播本节点
+    pub fn start(sensor_id: &str, port: u16) -> Result<Self, mdns_sd::Error> {
+        let daemon = ServiceDaemon::new()?;
+        let host = format!("{}.local.", sensor_id.replace(|c: char| !c.is_ascii_alphanumeric() && c != '-', "-"));
+        let properties = [("id", sensor_id)];
+        let service = ServiceInfo::new(SERVICE_TYPE, sensor_id, &host, "", port, &properties[..])?
+            .enable_addr_auto();
+        daemon.register(service)?;
+        let browser = daemon.browse(SERVICE_TYPE)?;
+
+        let peers = Arc::new(Mutex::new(HashMap::new()));
+        let discovered = peers.clone();
+        let own_id = sensor_id.to_string();
+        thread::spawn(move || {
+            while let Ok(event) = browser.recv() {
+                match event {
+                    ServiceEvent::ServiceResolved(info) => {
+                        let Some(id) = info.get_property_val_str("id").map(str::to_string) else { continue };
+                        let Some(ip) = info.get_addresses_v4().into_iter().next().copied() else { continue };
+                        if id == own_id {
+                            continue;
+                        }
+                        let addr = SocketAddr::from((ip, info.get_port()));
+                        if discovered.lock().unwrap().insert(id.clone(), addr) != Some(addr) {
+                            info!("mesh peer {} discovered at {}", id, addr);
+                        }
+                    }
+                    ServiceEvent::ServiceRemoved(_, fullname) => {
+                        let id = fullname.strip_suffix(SERVICE_TYPE).and_then(|n| n.strip_suffix('.'));
+                        if let Some(id) = id
+                            && discovered.lock().unwrap().remove(id).is_some()
+                        {
+                            info!("mesh peer {} left", id);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        });
+
+        let (ingest_tx, ingest) = mpsc::channel();
+        spawn_ingest_listener(port, ingest_tx);
+        let (forward, forward_rx) = mpsc::channel();
+        spawn_forwarder(sensor_id.to_string(), peers.clone(), forward_rx);
+        info!("mesh discovery started as {} on port {}", sensor_id, port);
+        Ok(Self { sensor_id: sensor_id.to_string(), peers, forward, ingest, _daemon: daemon })
+    }
+
+    /// 当前选出的汇聚节点地址，本节点当选时为 None
+    pub fn aggregator(&self) -> Option<SocketAddr> {
+        aggregator(&self.sensor_id, &self.peers.lock().unwrap())
+    }
+
+    pub fn is_aggregator(&self) -> bool {
+        self.aggregator().is_none()
+    }
+
+    /// 本节点不是汇聚节点时把事件转发出去
+    pub fn forward(&self, event: &DecodedEvent) {
+        if !self.is_aggregator() {
+            let _ = self.forward.send(event.clone());
+        }
+    }
+
+    /// 取出其他节点转发来的事件
+    pub fn take_ingested(&self) -> Vec<DecodedEvent> {
+        self.ingest.try_iter().collect()
+    }
+}
+
+fn aggregator(own_id: &str, peers: &HashMap<String, SocketAddr>) -> Option<SocketAddr> {
+    let elected = elect(peers.keys().map(String::as_str).chain([own_id]))?;
+    peers.get(elected).copied()
+}
+
+fn spawn_ingest_listener(port: u16, ingest: mpsc::Sender<DecodedEvent>) {
+    thread::spawn(move || {
+        let listener = match TcpListener::bind(("0.0.0.0", port)) {
+            Ok(listener) => listener,
+            Err(e) => {
+                error!("无法监听组网端口 {}: {}", port, e);
+                return;
+            }
+        };
+        for stream in listener.incoming() {
+            let Ok(stream) = stream else { continue };
+            let ingest = ingest.clone();
+            thread::spawn(move || {
+                let peer = stream.peer_addr().ok();
+                for line in BufReader::new(stream).lines() {
+                    let Ok(line) = line else { break };
+                    match serde_json::from_str::<DecodedEvent>(&line) {
+                        Ok(event) => {
+                            if ingest.send(event).is_err() {
+                                return;
+                            }
+                        }
+                        Err(e) => warn!("invalid event from mesh peer {:?}: {}", peer, e),
+                    }
+                }
+            });
+        }
+    });
+}
+
+/// 按当前选举结果把事件写到汇聚节点，连接断开时丢弃该事件并在下一条重新连接
+fn spawn_forwarder(sensor_id: String, peers: Arc<Mutex<HashMap<String, SocketAddr>>>,
+                   events: mpsc::Receiver<DecodedEvent>) {
+    thread::spawn(move || {
+        let mut connection: Option<(SocketAddr, TcpStream)> = None;
+        for event in events {
+            let Some(target) = aggregator(&sensor_id, &peers.lock().unwrap()) else { continue };
+            if connection.as_ref().is_none_or(|(addr, _)| *addr != target) {
+                connection = TcpStream::connect_timeout(&target, Duration::from_secs(3))
+                    .map_err(|e| warn!("cannot connect to mesh aggregator {}: {}", target, e))
+                    .ok()
+                    .map(|stream| {
+                        info!("feeding mesh aggregator {}", target);
+                        (target, stream)
+                    });
+            }
+            let Some((_, stream)) = connection.as_mut() else { continue };
+            let Ok(line) = serde_json::to_string(&event) else { continue };
+            if let Err(e) = writeln!(stream, "{}", line) {
+                warn!("mesh forward to {} failed: {}", target, e);
+                connection = None;
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::upload_data::UploadData;
+
+    fn peer(last_octet: u8) -> SocketAddr {
+        SocketAddr::from(([10, 0, 0, last_octet], 30010))
+    }
+
+    #[test]
+    fn test_elect_lowest_sensor_id() {
+        assert_eq!(elect(["sensor-c", "sensor-a", "sensor-b"]), Some("sensor-a"));
+        assert_eq!(elect([]), None);
+    }
+
+    #[test]
+    fn test_alone_is_aggregator() {
+        assert_eq!(aggregator("sensor-b", &HashMap::new()), None);
+    }
+
+    #[test]
+    fn test_higher_peers_feed_this_node() {
+        let peers = HashMap::from([("sensor-c".to_string(), peer(3))]);
+        assert_eq!(aggregator("sensor-b", &peers), None);
+    }
+
+    #[test]
+    fn test_lower_peer_is_aggregator() {
+        let peers = HashMap::from([("sensor-c".to_string(), peer(3)), ("sensor-a".to_string(), peer(1))]);
+        assert_eq!(aggregator("sensor-b", &peers), Some(peer(1)));
+    }
+
+    #[test]
+    fn test_forwarder_writes_json_lines() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let peers = Arc::new(Mutex::new(HashMap::from([("sensor-a".to_string(), listener.local_addr().unwrap())])));
+        let (forward, events) = mpsc::channel();
+        spawn_forwarder("sensor-b".into(), peers, events);
+        for rid in ["RID-1", "RID-2"] {
+            forward.send(DecodedEvent::now(UploadData { rid: rid.into(), ..Default::default() })).unwrap();
+        }
+        let (stream, _) = listener.accept().unwrap();
+        let rids: Vec<String> = BufReader::new(stream).lines().take(2)
+            .map(|line| serde_json::from_str::<DecodedEvent>(&line.unwrap()).unwrap().record.rid)
+            .collect();
+        assert_eq!(rids, ["RID-1", "RID-2"]);
+    }
+}
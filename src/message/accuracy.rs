@@ -0,0 +1,118 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// 水平精度编码 -> 误差上限 (米)，0 及预留值返回 None
+pub fn horizontal_accuracy_m(code: u8) -> Option<f32> {
+    match code {
+        1 => Some(18520.0),  // < 10 NM
+        2 => Some(7408.0),   // < 4 NM
+        3 => Some(3704.0),   // < 2 NM
+        4 => Some(1852.0),   // < 1 NM
+        5 => Some(926.0),    // < 0.5 NM
+        6 => Some(555.6),    // < 0.3 NM
+        7 => Some(185.2),    // < 0.1 NM
+        8 => Some(92.6),     // < 0.05 NM
+        9 => Some(30.0),
+        10 => Some(10.0),
+        11 => Some(3.0),
+        12 => Some(1.0),
+        _ => None,
+    }
+}
+
+/// 垂直精度编码 -> 误差上限 (米)
+pub fn vertical_accuracy_m(code: u8) -> Option<f32> {
+    match code {
+        1 => Some(150.0),
+        2 => Some(45.0),
+        3 => Some(25.0),
+        4 => Some(10.0),
+        5 => Some(3.0),
+        6 => Some(1.0),
+        _ => None,
+    }
+}
+
+/// 速度精度编码 -> 误差上限 (米/秒)
+pub fn speed_accuracy_mps(code: u8) -> Option<f32> {
+    match code {
+        1 => Some(10.0),
+        2 => Some(3.0),
+        3 => Some(1.0),
+        4 => Some(0.3),
+        _ => None,
+    }
+}
+
+/// 时间戳精度编码 -> 误差上限 (秒)，每级 0.1 秒
+pub fn timestamp_accuracy_s(code: u8) -> Option<f32> {
+    match code {
+        1..=15 => Some(code as f32 * 0.1),
+        _ => None,
+    }
+}
+
+/// 精度编码解码后的物理上限，None 表示未知或预留
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Default)]
+pub struct AccuracyBounds {
+    pub horizontal_m: Option<f32>,
+    pub vertical_m: Option<f32>,
+    pub speed_mps: Option<f32>,
+    pub timestamp_s: Option<f32>,
+}
+
+impl AccuracyBounds {
+    pub fn from_codes(horizontal: u8, vertical: u8, speed: u8, timestamp: u8) -> Self {
+        Self {
+            horizontal_m: horizontal_accuracy_m(horizontal),
+            vertical_m: vertical_accuracy_m(vertical),
+            speed_mps: speed_accuracy_mps(speed),
+            timestamp_s: timestamp_accuracy_s(timestamp),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_horizontal_accuracy_table() {
+        assert_eq!(horizontal_accuracy_m(1), Some(18520.0));
+        assert_eq!(horizontal_accuracy_m(7), Some(185.2));
+        assert_eq!(horizontal_accuracy_m(12), Some(1.0));
+        // 编码越大精度越高
+        let bounds: Vec<f32> = (1..=12).filter_map(horizontal_accuracy_m).collect();
+        assert!(bounds.windows(2).all(|w| w[0] > w[1]));
+    }
+
+    #[test]
+    fn test_unknown_and_reserved_codes_are_none() {
+        assert_eq!(horizontal_accuracy_m(0), None);
+        assert_eq!(horizontal_accuracy_m(13), None);
+        assert_eq!(vertical_accuracy_m(0), None);
+        assert_eq!(vertical_accuracy_m(7), None);
+        assert_eq!(speed_accuracy_mps(5), None);
+        assert_eq!(timestamp_accuracy_s(0), None);
+    }
+
+    #[test]
+    fn test_vertical_and_speed_accuracy_tables() {
+        assert_eq!((1..=6).map(vertical_accuracy_m).collect::<Vec<_>>(),
+                   [Some(150.0), Some(45.0), Some(25.0), Some(10.0), Some(3.0), Some(1.0)]);
+        assert_eq!((1..=4).map(speed_accuracy_mps).collect::<Vec<_>>(), [Some(10.0), Some(3.0), Some(1.0), Some(0.3)]);
+    }
+
+    #[test]
+    fn test_timestamp_accuracy_is_tenths_of_a_second() {
+        assert_eq!(timestamp_accuracy_s(1), Some(0.1));
+        assert!((timestamp_accuracy_s(15).unwrap() - 1.5).abs() < 1e-6);
+        assert_eq!(timestamp_accuracy_s(16), None);
+    }
+
+    #[test]
+    fn test_bounds_from_codes() {
+        let bounds = AccuracyBounds::from_codes(10, 4, 3, 0);
+        assert_eq!(bounds, AccuracyBounds { horizontal_m: Some(10.0), vertical_m: Some(10.0), speed_mps: Some(1.0), timestamp_s: None });
+    }
+}
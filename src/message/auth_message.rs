@@ -0,0 +1,127 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::message::{Message, MessageError};
+use super::profile::ASTM_EPOCH;
+
+/// 认证消息的一页
+///
+/// 认证数据分多页发送：第 0 页带有末页序号、数据总长度和时间戳，携带 17 字节数据；
+/// 其余页各携带 23 字节数据。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuthMessage {
+    pub auth_type: u8,                // 认证类型 (7-4 位)
+    pub page_number: u8,              // 页号 (3-0 位)
+    pub last_page_index: Option<u8>,  // 末页序号，仅第 0 页
+    pub length: Option<u8>,           // 认证数据总长度 (字节)，仅第 0 页
+    pub timestamp: Option<u32>,       // 2019-01-01 00:00:00 UTC 起的秒数，仅第 0 页
+    pub data: Vec<u8>,                // 本页的认证数据
+}
+
+impl AuthMessage {
+    pub const MESSAGE_TYPE: u8 = 0x02;
+    const EXPECTED_LENGTH: usize = 24;
+
+    /// 第 0 页的时间戳 (UTC)
+    pub fn timestamp(&self) -> Option<DateTime<Utc>> {
+        self.timestamp.and_then(|t| DateTime::from_timestamp(ASTM_EPOCH as i64 + t as i64, 0))
+    }
+}
+
+impl Message for AuthMessage {
+    fn from_bytes(data: &[u8]) -> Result<Self, MessageError> {
+        if data.len() < Self::EXPECTED_LENGTH {
+            return Err(MessageError::InsufficientLength(Self::EXPECTED_LENGTH, data.len()));
+        }
+        let auth_type = (data[0] >> 4) & 0x0F;
+        let page_number = data[0] & 0x0F;
+        if page_number == 0 {
+            Ok(Self {
+                auth_type,
+                page_number,
+                last_page_index: Some(data[1]),
+                length: Some(data[2]),
+                timestamp: Some(u32::from_le_bytes([data[3], data[4], data[5], data[6]])),
+                data: data[7..24].to_vec(),
+            })
+        } else {
+            Ok(Self { auth_type, page_number, last_page_index: None, length: None, timestamp: None, data: data[1..24].to_vec() })
+        }
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut data = vec![0u8; Self::EXPECTED_LENGTH];
+        data[0] = self.auth_type << 4 | self.page_number & 0x0F;
+        let start = if self.page_number == 0 {
+            data[1] = self.last_page_index.unwrap_or_default();
+            data[2] = self.length.unwrap_or_default();
+            data[3..7].copy_from_slice(&self.timestamp.unwrap_or_default().to_le_bytes());
+            7
+        } else {
+            1
+        };
+        let len = self.data.len().min(Self::EXPECTED_LENGTH - start);
+        data[start..start + len].copy_from_slice(&self.data[..len]);
+        data
+    }
+
+    fn print(&self) {
+        println!("=== 认证消息 (AuthMessage) ===");
+        println!("认证类型: {}", self.auth_type);
+        println!("页号: {}", self.page_number);
+        if let (Some(last), Some(length)) = (self.last_page_index, self.length) {
+            println!("末页序号: {}, 数据长度: {}", last, length);
+        }
+        if let Some(ts) = self.timestamp {
+            println!("时间戳: {}", ts);
+        }
+        println!("认证数据: {:02X?}", self.data);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 第 0 页：类型 1，末页 1，长度 30，时间戳 10000 秒，17 字节数据
+    fn first_page() -> [u8; 24] {
+        let mut data = [0xaa; 24];
+        data[..7].copy_from_slice(&[0x10, 0x01, 30, 0x10, 0x27, 0x00, 0x00]);
+        data
+    }
+
+    #[test]
+    fn test_first_page_header() {
+        let auth = AuthMessage::from_bytes(&first_page()).unwrap();
+        assert_eq!((auth.auth_type, auth.page_number, auth.last_page_index, auth.length), (1, 0, Some(1), Some(30)));
+        assert_eq!(auth.timestamp, Some(10_000));
+        assert_eq!(auth.data, vec![0xaa; 17]);
+    }
+
+    #[test]
+    fn test_later_page_carries_only_data() {
+        let mut data = [0xbb; 24];
+        data[0] = 0x11;
+        let auth = AuthMessage::from_bytes(&data).unwrap();
+        assert_eq!((auth.auth_type, auth.page_number), (1, 1));
+        assert_eq!((auth.last_page_index, auth.length, auth.timestamp), (None, None, None));
+        assert_eq!(auth.data, vec![0xbb; 23]);
+    }
+
+    #[test]
+    fn test_timestamp_uses_astm_epoch() {
+        let auth = AuthMessage::from_bytes(&first_page()).unwrap();
+        assert_eq!(auth.timestamp().map(|t| t.timestamp()), Some(ASTM_EPOCH as i64 + 10_000));
+    }
+
+    #[test]
+    fn test_short_message_is_rejected() {
+        assert_eq!(AuthMessage::from_bytes(&[0u8; 20]), Err(MessageError::InsufficientLength(24, 20)));
+    }
+
+    #[test]
+    fn test_encode_round_trip() {
+        let data = first_page();
+        assert_eq!(AuthMessage::from_bytes(&data).unwrap().to_bytes(), data);
+    }
+}
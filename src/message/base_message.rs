@@ -1,33 +1,50 @@
 use std::convert::TryInto;
 use std::str;
 
+use serde::{Deserialize, Serialize};
 use tracing::info;
 
-use super::message::{Message, MessageError};
+use super::codes::{IdType, UaType};
+use super::message::{decode_text, encode_text, Message, MessageError};
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct BaseMessage {
     pub id_type: u8,          // 高位 4 位 (7-4 位)
     pub ua_type: u8,          // 低位 4 位 (3-0 位)
     pub uas_id: String,       // UAS 识别身份信息（字符串类型）
+    pub uas_id_raw: [u8; 20], // UAS ID 原始 20 字节
+    pub uas_id_lossy: bool,   // UAS ID 含非法 UTF-8，已按宽松模式替换
     pub reserved: [u8; 3],    // 3 字节预留空间
 }
 
 impl BaseMessage {
     pub const MESSAGE_TYPE: u8 = 0x00;
     const EXPECTED_LENGTH: usize = 24;
-}
 
-impl Message for BaseMessage {
-    /// 从 u8 数组解析为结构化数据
+    pub fn uas_id_type(&self) -> IdType {
+        IdType::from_code(self.id_type)
+    }
+
+    pub fn aircraft_type(&self) -> UaType {
+        UaType::from_code(self.ua_type)
+    }
+
+    /// 宽松解析：UAS ID 含非法 UTF-8 时不丢弃整条消息
     ///
+    /// 尾部的 0x00/0xFF 填充被去除，其余非法字节替换为 U+FFFD，
+    /// 并置位 `uas_id_lossy`。
+    pub fn from_bytes_lossy(data: &[u8]) -> Result<Self, MessageError> {
+        Self::parse(data, true)
+    }
+
     /// # 参数
     /// - `data`: 至少包含 24 字节的输入数据
+    /// - `lossy`: UAS ID 是否使用宽松 UTF-8 解码
     ///
     /// # 错误
     /// - 当输入数据长度不足时返回 ParseError::InsufficientLength
-    /// - 当 UAS ID 不是有效的 UTF-8 时返回 ParseError::InvalidUtf8
-    fn from_bytes(data: &[u8]) -> Result<Self, MessageError> {
+    /// - 非宽松模式下，当 UAS ID 不是有效的 UTF-8 时返回 ParseError::InvalidUtf8
+    fn parse(data: &[u8], lossy: bool) -> Result<Self, MessageError> {
         if data.len() < Self::EXPECTED_LENGTH{
             return Err(MessageError::InsufficientLength(
                 Self::EXPECTED_LENGTH, 
@@ -43,8 +60,11 @@ impl Message for BaseMessage {
         // 解析 UAS ID (起始字节 2，长度 20)
         let uas_id_start = 1;
         let uas_id_bytes = &data[uas_id_start..uas_id_start + 20];
+        let uas_id_raw: [u8; 20] = uas_id_bytes.try_into()
+            .map_err(|_| MessageError::InsufficientLength(21, data.len()))?;
         
         // 转换为 String，移除尾部的空字符(\0)和空白字符
+        let mut uas_id_lossy = false;
         let uas_id = match str::from_utf8(uas_id_bytes) {
             Ok(s) => {
                 // 移除尾部的空字符和空白字符
@@ -52,6 +72,16 @@ impl Message for BaseMessage {
                  .trim_end()
                  .to_string()
             },
+            Err(_) if lossy => {
+                info!("base message utf8 error, decoding lossy.");
+                uas_id_lossy = true;
+                let end = uas_id_bytes.iter()
+                    .rposition(|b| *b != 0x00 && *b != 0xFF)
+                    .map_or(0, |i| i + 1);
+                String::from_utf8_lossy(&uas_id_bytes[..end])
+                    .trim_end()
+                    .to_string()
+            },
             Err(e) => {
                 info!("base message utf8 error.");
                 return Err(MessageError::InvalidUtf8(e))
@@ -68,16 +98,40 @@ impl Message for BaseMessage {
             id_type,
             ua_type,
             uas_id,
+            uas_id_raw,
+            uas_id_lossy,
             reserved,
         })
     }
+}
 
+impl Message for BaseMessage {
+    /// 从 u8 数组解析为结构化数据，UAS ID 必须是有效的 UTF-8
+    fn from_bytes(data: &[u8]) -> Result<Self, MessageError> {
+        Self::parse(data, false)
+    }
+
+    /// 原始字节与 UAS ID 一致时按原始字节编码（保留填充和宽松解码前的内容），否则按文本编码
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut data = vec![0u8; Self::EXPECTED_LENGTH];
+        data[0] = self.id_type << 4 | self.ua_type & 0x0F;
+        if decode_text(&self.uas_id_raw, true).is_ok_and(|(text, _)| text == self.uas_id) {
+            data[1..21].copy_from_slice(&self.uas_id_raw);
+        } else {
+            encode_text(&self.uas_id, &mut data[1..21]);
+        }
+        data[21..24].copy_from_slice(&self.reserved);
+        data
+    }
 
     fn print(&self) {
         println!("=== BaseMessage ===");
-        println!("ID 类型: 0x{:X}", self.id_type);
-        println!("UA 类型: 0x{:X}", self.ua_type);
+        println!("ID 类型: 0x{:X} ({})", self.id_type, self.uas_id_type());
+        println!("UA 类型: 0x{:X} ({})", self.ua_type, self.aircraft_type());
         println!("UAS ID: '{}'", self.uas_id);
+        if self.uas_id_lossy {
+            println!("UAS ID 原始字节: {:02X?}", self.uas_id_raw);
+        }
         println!("预留字段: {:02X?}", self.reserved);
     }
 }
@@ -0,0 +1,182 @@
+use std::fmt;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// 等级分类归属区域 (SystemMessage 起始字节1, bit4-2)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ClassificationRegion {
+    Undeclared,     // 0: 未声明
+    Eu,             // 1: 欧盟
+    China,          // 2: 中国 (GB 42590)
+    Reserved(u8),   // 3-7: 预留
+}
+
+impl ClassificationRegion {
+    pub fn from_code(code: u8) -> Self {
+        match code {
+            0 => Self::Undeclared,
+            1 => Self::Eu,
+            2 => Self::China,
+            t => Self::Reserved(t),
+        }
+    }
+}
+
+impl fmt::Display for ClassificationRegion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Undeclared => write!(f, "未声明"),
+            Self::Eu => write!(f, "欧盟"),
+            Self::China => write!(f, "中国"),
+            Self::Reserved(t) => write!(f, "预留({})", t),
+        }
+    }
+}
+
+/// UA 运行类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum UaCategory {
+    Undefined,      // 0: 未定义
+    Open,           // 1: 开放类
+    Specific,       // 2: 特定类
+    Certified,      // 3: 审定类
+    Reserved(u8),   // 其它: 预留
+}
+
+impl UaCategory {
+    pub fn from_code(code: u8) -> Self {
+        match code {
+            0 => Self::Undefined,
+            1 => Self::Open,
+            2 => Self::Specific,
+            3 => Self::Certified,
+            t => Self::Reserved(t),
+        }
+    }
+}
+
+impl fmt::Display for UaCategory {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Undefined => write!(f, "未定义"),
+            Self::Open => write!(f, "开放类"),
+            Self::Specific => write!(f, "特定类"),
+            Self::Certified => write!(f, "审定类"),
+            Self::Reserved(t) => write!(f, "预留({})", t),
+        }
+    }
+}
+
+/// UA 等级，含义取决于分类归属区域
+///
+/// - 欧盟: 1-7 对应 C0-C6 级标识
+/// - 中国 (GB 42590): 1-5 对应 微型/轻型/小型/中型/大型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum UaLevel {
+    Undefined,
+    EuClass(u8),    // C0-C6
+    Micro,
+    Light,
+    Small,
+    Medium,
+    Large,
+    Reserved(u8),
+}
+
+impl UaLevel {
+    pub fn from_code(region: ClassificationRegion, code: u8) -> Self {
+        match (region, code) {
+            (_, 0) => Self::Undefined,
+            (ClassificationRegion::Eu, 1..=7) => Self::EuClass(code - 1),
+            (ClassificationRegion::China, 1) => Self::Micro,
+            (ClassificationRegion::China, 2) => Self::Light,
+            (ClassificationRegion::China, 3) => Self::Small,
+            (ClassificationRegion::China, 4) => Self::Medium,
+            (ClassificationRegion::China, 5) => Self::Large,
+            (_, t) => Self::Reserved(t),
+        }
+    }
+}
+
+impl fmt::Display for UaLevel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Undefined => write!(f, "未定义"),
+            Self::EuClass(c) => write!(f, "C{}", c),
+            Self::Micro => write!(f, "微型"),
+            Self::Light => write!(f, "轻型"),
+            Self::Small => write!(f, "小型"),
+            Self::Medium => write!(f, "中型"),
+            Self::Large => write!(f, "大型"),
+            Self::Reserved(t) => write!(f, "预留({})", t),
+        }
+    }
+}
+
+/// 解码后的分类信息，用于输出
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct Classification {
+    pub region: ClassificationRegion,
+    pub category: UaCategory,
+    pub level: UaLevel,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_region_codes() {
+        assert_eq!(ClassificationRegion::from_code(0), ClassificationRegion::Undeclared);
+        assert_eq!(ClassificationRegion::from_code(1), ClassificationRegion::Eu);
+        assert_eq!(ClassificationRegion::from_code(2), ClassificationRegion::China);
+        assert_eq!(ClassificationRegion::from_code(5), ClassificationRegion::Reserved(5));
+        assert_eq!(ClassificationRegion::from_code(5).to_string(), "预留(5)");
+    }
+
+    #[test]
+    fn test_category_codes() {
+        let categories: Vec<_> = (0..5).map(UaCategory::from_code).collect();
+        assert_eq!(categories, [UaCategory::Undefined, UaCategory::Open, UaCategory::Specific,
+                                UaCategory::Certified, UaCategory::Reserved(4)]);
+        assert_eq!(UaCategory::Specific.to_string(), "特定类");
+    }
+
+    #[test]
+    fn test_eu_levels_are_class_marks() {
+        assert_eq!(UaLevel::from_code(ClassificationRegion::Eu, 1), UaLevel::EuClass(0));
+        assert_eq!(UaLevel::from_code(ClassificationRegion::Eu, 7).to_string(), "C6");
+        assert_eq!(UaLevel::from_code(ClassificationRegion::Eu, 8), UaLevel::Reserved(8));
+    }
+
+    #[test]
+    fn test_china_levels_are_weight_classes() {
+        let levels: Vec<_> = (1..=6).map(|code| UaLevel::from_code(ClassificationRegion::China, code)).collect();
+        assert_eq!(levels, [UaLevel::Micro, UaLevel::Light, UaLevel::Small, UaLevel::Medium, UaLevel::Large, UaLevel::Reserved(6)]);
+        assert_eq!(UaLevel::Light.to_string(), "轻型");
+    }
+
+    #[test]
+    fn test_level_depends_on_region() {
+        // 未声明区域时等级无法解释，0 在任何区域都是未定义
+        assert_eq!(UaLevel::from_code(ClassificationRegion::Undeclared, 2), UaLevel::Reserved(2));
+        assert_eq!(UaLevel::from_code(ClassificationRegion::China, 0), UaLevel::Undefined);
+        assert_eq!(UaLevel::from_code(ClassificationRegion::Eu, 0), UaLevel::Undefined);
+    }
+
+    #[test]
+    fn test_serialized_names() {
+        let classification = Classification {
+            region: ClassificationRegion::China,
+            category: UaCategory::Open,
+            level: UaLevel::Micro,
+        };
+        assert_eq!(serde_json::to_value(classification).unwrap(),
+                   serde_json::json!({ "region": "china", "category": "open", "level": "micro" }));
+        assert_eq!(serde_json::to_value(UaLevel::EuClass(2)).unwrap(), serde_json::json!({ "eu_class": 2 }));
+    }
+}
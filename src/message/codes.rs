@@ -0,0 +1,221 @@
+use std::fmt;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// 运行状态 (PositionVectorMessage 第1字节, bit7-4)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum OperationalStatus {
+    Undeclared,     // 0: 未声明
+    Ground,         // 1: 地面
+    Airborne,       // 2: 空中
+    Emergency,      // 3: 紧急
+    RemoteIdFailure, // 4: 远程识别系统故障
+    Reserved,       // 5-15: 预留
+}
+
+impl OperationalStatus {
+    pub fn from_code(code: u8) -> Self {
+        match code {
+            0 => Self::Undeclared,
+            1 => Self::Ground,
+            2 => Self::Airborne,
+            3 => Self::Emergency,
+            4 => Self::RemoteIdFailure,
+            _ => Self::Reserved,
+        }
+    }
+}
+
+impl fmt::Display for OperationalStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Self::Undeclared => "未声明",
+            Self::Ground => "地面",
+            Self::Airborne => "空中",
+            Self::Emergency => "紧急",
+            Self::RemoteIdFailure => "识别系统故障",
+            Self::Reserved => "预留",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// 距地高度的基准 (PositionVectorMessage 第1字节, bit2)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum HeightReference {
+    Takeoff,        // 0: 相对起飞点
+    Ground,         // 1: 相对地面
+}
+
+impl HeightReference {
+    pub fn from_code(code: u8) -> Self {
+        if code & 0x01 == 0 { Self::Takeoff } else { Self::Ground }
+    }
+}
+
+impl fmt::Display for HeightReference {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Takeoff => write!(f, "相对起飞点"),
+            Self::Ground => write!(f, "相对地面"),
+        }
+    }
+}
+
+/// UAS ID 类型 (BaseMessage 第1字节, bit7-4)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum IdType {
+    None,               // 0: 无
+    SerialNumber,       // 1: 产品序列号
+    CaaRegistration,    // 2: 民航局登记号
+    UtmAssigned,        // 3: UTM 分配的 ID
+    SpecificSession,    // 4: 特定会话 ID
+    Reserved,           // 5-15: 预留
+}
+
+impl IdType {
+    pub fn from_code(code: u8) -> Self {
+        match code {
+            0 => Self::None,
+            1 => Self::SerialNumber,
+            2 => Self::CaaRegistration,
+            3 => Self::UtmAssigned,
+            4 => Self::SpecificSession,
+            _ => Self::Reserved,
+        }
+    }
+}
+
+impl fmt::Display for IdType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Self::None => "无",
+            Self::SerialNumber => "产品序列号",
+            Self::CaaRegistration => "登记号",
+            Self::UtmAssigned => "UTM 分配",
+            Self::SpecificSession => "特定会话",
+            Self::Reserved => "预留",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// UA 类型 (BaseMessage 第1字节, bit3-0)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum UaType {
+    None,               // 0: 未声明
+    Aeroplane,          // 1: 固定翼
+    Multirotor,         // 2: 多旋翼
+    Gyroplane,          // 3: 旋翼机
+    HybridVtol,         // 4: 垂直起降固定翼
+    Ornithopter,        // 5: 扑翼机
+    Glider,             // 6: 滑翔机
+    Kite,               // 7: 风筝
+    FreeBalloon,        // 8: 自由气球
+    CaptiveBalloon,     // 9: 系留气球
+    Airship,            // 10: 飞艇
+    Parachute,          // 11: 无动力降落伞
+    Rocket,             // 12: 火箭
+    Tethered,           // 13: 系留动力飞行器
+    GroundObstacle,     // 14: 地面障碍物
+    Other,              // 15: 其它
+}
+
+impl UaType {
+    pub fn from_code(code: u8) -> Self {
+        match code & 0x0F {
+            0 => Self::None,
+            1 => Self::Aeroplane,
+            2 => Self::Multirotor,
+            3 => Self::Gyroplane,
+            4 => Self::HybridVtol,
+            5 => Self::Ornithopter,
+            6 => Self::Glider,
+            7 => Self::Kite,
+            8 => Self::FreeBalloon,
+            9 => Self::CaptiveBalloon,
+            10 => Self::Airship,
+            11 => Self::Parachute,
+            12 => Self::Rocket,
+            13 => Self::Tethered,
+            14 => Self::GroundObstacle,
+            _ => Self::Other,
+        }
+    }
+}
+
+impl fmt::Display for UaType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Self::None => "未声明",
+            Self::Aeroplane => "固定翼",
+            Self::Multirotor => "多旋翼",
+            Self::Gyroplane => "旋翼机",
+            Self::HybridVtol => "垂直起降固定翼",
+            Self::Ornithopter => "扑翼机",
+            Self::Glider => "滑翔机",
+            Self::Kite => "风筝",
+            Self::FreeBalloon => "自由气球",
+            Self::CaptiveBalloon => "系留气球",
+            Self::Airship => "飞艇",
+            Self::Parachute => "降落伞",
+            Self::Rocket => "火箭",
+            Self::Tethered => "系留飞行器",
+            Self::GroundObstacle => "地面障碍物",
+            Self::Other => "其它",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_operational_status_codes() {
+        let statuses: Vec<_> = (0..6).map(OperationalStatus::from_code).collect();
+        assert_eq!(statuses, [OperationalStatus::Undeclared, OperationalStatus::Ground, OperationalStatus::Airborne,
+                              OperationalStatus::Emergency, OperationalStatus::RemoteIdFailure, OperationalStatus::Reserved]);
+        assert_eq!(OperationalStatus::from_code(15), OperationalStatus::Reserved);
+        assert_eq!(OperationalStatus::Airborne.to_string(), "空中");
+    }
+
+    #[test]
+    fn test_height_reference_uses_low_bit() {
+        assert_eq!(HeightReference::from_code(0), HeightReference::Takeoff);
+        assert_eq!(HeightReference::from_code(1), HeightReference::Ground);
+        assert_eq!(HeightReference::from_code(0b10), HeightReference::Takeoff);
+        assert_eq!(HeightReference::Ground.to_string(), "相对地面");
+    }
+
+    #[test]
+    fn test_id_type_codes() {
+        let types: Vec<_> = (0..6).map(IdType::from_code).collect();
+        assert_eq!(types, [IdType::None, IdType::SerialNumber, IdType::CaaRegistration,
+                           IdType::UtmAssigned, IdType::SpecificSession, IdType::Reserved]);
+        assert_eq!(IdType::SerialNumber.to_string(), "产品序列号");
+    }
+
+    #[test]
+    fn test_ua_type_codes() {
+        assert_eq!(UaType::from_code(2), UaType::Multirotor);
+        assert_eq!(UaType::from_code(14), UaType::GroundObstacle);
+        assert_eq!(UaType::from_code(15), UaType::Other);
+        // 只取低 4 位
+        assert_eq!(UaType::from_code(0x12), UaType::Multirotor);
+        assert_eq!(UaType::HybridVtol.to_string(), "垂直起降固定翼");
+    }
+
+    #[test]
+    fn test_serde_names() {
+        assert_eq!(serde_json::to_value(OperationalStatus::RemoteIdFailure).unwrap(), "remote_id_failure");
+        assert_eq!(serde_json::to_value(UaType::FreeBalloon).unwrap(), "free_balloon");
+        assert_eq!(serde_json::from_value::<IdType>("caa_registration".into()).unwrap(), IdType::CaaRegistration);
+    }
+}
@@ -7,6 +7,8 @@ pub enum MessageError {
     InsufficientLength(usize, usize),  // 期望长度, 实际长度
     InvalidUtf8(str::Utf8Error),        // UTF-8 格式错误
     UnknownMessageType(u8),             // 未知消息类型
+    NotMessagePack(u8),                 // 消息包头不是消息包类型
+    InvalidMessageSize(usize),          // 消息包声明的消息大小不是 25
 }
 
 // 公共消息错误类型
@@ -14,7 +16,10 @@ pub enum MessageError {
 pub enum MessageType {
     BaseMessageType = 0,
     PositionVectorMessageType = 1,
+    AuthMessageType = 2,
+    SelfIdMessageType = 3,
     SystemMessageType = 4,
+    OperatorIdMessageType = 5,
 }
 
 impl std::error::Error for MessageError {}
@@ -27,15 +32,47 @@ impl fmt::Display for MessageError {
                 write!(f, "文本格式错误: {}", e),
             MessageError::UnknownMessageType(t) => 
                 write!(f, "未知消息类型: 0x{:02X}", t),
+            MessageError::NotMessagePack(header) =>
+                write!(f, "消息包头 0x{:02X} 不是消息包类型", header),
+            MessageError::InvalidMessageSize(size) =>
+                write!(f, "消息大小 {} 不等于 25", size),
         }
     }
 }
 
+/// 解码定长文本字段，去除尾部的 0x00 填充和空白
+///
+/// 宽松模式下同时去除 0xFF 填充，其余非法字节替换为 U+FFFD，返回值第二项表示是否发生了替换。
+pub fn decode_text(bytes: &[u8], lossy: bool) -> Result<(String, bool), MessageError> {
+    match str::from_utf8(bytes) {
+        Ok(s) => Ok((s.trim_end_matches('\0').trim_end().to_string(), false)),
+        Err(_) if lossy => {
+            let end = bytes.iter()
+                .rposition(|b| *b != 0x00 && *b != 0xFF)
+                .map_or(0, |i| i + 1);
+            Ok((String::from_utf8_lossy(&bytes[..end]).trim_end().to_string(), true))
+        }
+        Err(e) => Err(MessageError::InvalidUtf8(e)),
+    }
+}
+
+/// 把文本写入定长字段，超长截断，不足部分以 0x00 填充
+pub fn encode_text(text: &str, field: &mut [u8]) {
+    let len = text.len().min(field.len());
+    field[..len].copy_from_slice(&text.as_bytes()[..len]);
+    field[len..].fill(0);
+}
+
 /// 所有消息类型必须实现的 trait
 pub trait Message {
     /// 从字节数组解析消息
     fn from_bytes(data: &[u8]) -> Result<Self, MessageError> where Self: Sized;
-    
+
+    /// 编码为 `from_bytes` 接受的 24 字节消息体（不含消息头）
+    ///
+    /// 解码得到的消息满足 `from_bytes(&m.to_bytes()) == Ok(m)`；宽松解码替换过的文本按替换后的内容编码。
+    fn to_bytes(&self) -> Vec<u8>;
+
     /// 打印消息内容
     fn print(&self);
 }
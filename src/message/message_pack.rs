@@ -0,0 +1,133 @@
+use super::message::MessageError;
+use super::{AnyMessage, MESSAGE_LEN};
+
+/// 消息包 (类型 0xF)：在一个厂商 IE 中打包多条定长消息
+///
+/// 格式为 消息包头(1) + 消息大小(1) + 消息数量(1) + 消息数量 × 25 字节。
+#[derive(Debug, Clone, Copy)]
+pub struct MessagePack<'a> {
+    messages: &'a [u8],
+}
+
+impl<'a> MessagePack<'a> {
+    pub const MESSAGE_TYPE: u8 = 0x0F;
+    const HEADER_LEN: usize = 3;
+
+    /// 从消息包头开始解析，校验声明的消息大小和数量；声明长度之后的多余字节被忽略
+    pub fn parse(data: &'a [u8]) -> Result<Self, MessageError> {
+        if data.len() < Self::HEADER_LEN {
+            return Err(MessageError::InsufficientLength(Self::HEADER_LEN, data.len()));
+        }
+        if data[0] >> 4 != Self::MESSAGE_TYPE {
+            return Err(MessageError::NotMessagePack(data[0]));
+        }
+        let (size, count) = (data[1] as usize, data[2] as usize);
+        if size != MESSAGE_LEN {
+            return Err(MessageError::InvalidMessageSize(size));
+        }
+        let len = Self::HEADER_LEN + size * count;
+        let messages = data.get(Self::HEADER_LEN..len).ok_or(MessageError::InsufficientLength(len, data.len()))?;
+        Ok(Self { messages })
+    }
+
+    /// 从 Remote ID 负载解析：负载以 1 字节消息计数器开头，其后为消息包
+    pub fn from_payload(payload: &'a [u8]) -> Result<Self, MessageError> {
+        match payload.split_first() {
+            Some((_counter, pack)) => Self::parse(pack),
+            None => Err(MessageError::InsufficientLength(1 + Self::HEADER_LEN, 0)),
+        }
+    }
+
+    /// 包内消息数量
+    pub fn len(&self) -> usize {
+        self.messages.len() / MESSAGE_LEN
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+
+    /// 依次取出每条消息的原始 25 字节
+    pub fn raw_messages(&self) -> impl Iterator<Item = &'a [u8]> + use<'a> {
+        self.messages.chunks_exact(MESSAGE_LEN)
+    }
+
+    /// 解码包内消息，无法解码的消息被跳过
+    pub fn messages(&self) -> Vec<AnyMessage> {
+        self.raw_messages().filter_map(|raw| AnyMessage::from_bytes(raw).ok()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(header: u8, body: &[u8]) -> [u8; MESSAGE_LEN] {
+        let mut raw = [0u8; MESSAGE_LEN];
+        raw[0] = header;
+        raw[1..1 + body.len()].copy_from_slice(body);
+        raw
+    }
+
+    /// 含 Basic ID 与 Self-ID 两条消息的消息包，末尾带 1 字节填充
+    fn pack() -> Vec<u8> {
+        let mut pack = vec![0xf2, MESSAGE_LEN as u8, 2];
+        pack.extend_from_slice(&message(0x02, b"\x121581F5FKD229400A"));
+        pack.extend_from_slice(&message(0x32, b"\x00Hello"));
+        pack.push(0x00);
+        pack
+    }
+
+    #[test]
+    fn test_raw_messages_are_split_by_declared_count() {
+        let data = pack();
+        let parsed = MessagePack::parse(&data).unwrap();
+        assert_eq!((parsed.len(), parsed.is_empty()), (2, false));
+        assert_eq!(parsed.raw_messages().nth(1), Some(&message(0x32, b"\x00Hello")[..]));
+    }
+
+    #[test]
+    fn test_messages_are_decoded() {
+        let data = pack();
+        let messages = MessagePack::parse(&data).unwrap().messages();
+        assert!(matches!(&messages[0], AnyMessage::Base(b) if b.uas_id == "1581F5FKD229400A"));
+        assert!(matches!(&messages[1], AnyMessage::SelfId(s) if s.description == "Hello"));
+    }
+
+    #[test]
+    fn test_undecodable_messages_are_skipped() {
+        let mut data = vec![0xf2, MESSAGE_LEN as u8, 2];
+        data.extend_from_slice(&message(0x32, b"\x00Hello"));
+        data.extend_from_slice(&message(0x92, &[]));   // 未知类型
+        let parsed = MessagePack::parse(&data).unwrap();
+        assert_eq!((parsed.len(), parsed.messages().len()), (2, 1));
+    }
+
+    #[test]
+    fn test_empty_pack() {
+        let parsed = MessagePack::parse(&[0xf2, MESSAGE_LEN as u8, 0]).unwrap();
+        assert!(parsed.is_empty());
+        assert!(parsed.messages().is_empty());
+    }
+
+    #[test]
+    fn test_header_errors() {
+        assert_eq!(MessagePack::parse(&[0xf2, 25]).unwrap_err(), MessageError::InsufficientLength(3, 2));
+        assert_eq!(MessagePack::parse(&[0x02, 25, 0]).unwrap_err(), MessageError::NotMessagePack(0x02));
+        assert_eq!(MessagePack::parse(&[0xf2, 24, 0]).unwrap_err(), MessageError::InvalidMessageSize(24));
+    }
+
+    #[test]
+    fn test_truncated_pack_is_rejected() {
+        let data = pack();
+        assert_eq!(MessagePack::parse(&data[..40]).unwrap_err(), MessageError::InsufficientLength(53, 40));
+    }
+
+    #[test]
+    fn test_from_payload_skips_counter() {
+        let mut payload = vec![0x07];
+        payload.extend_from_slice(&pack());
+        assert_eq!(MessagePack::from_payload(&payload).unwrap().len(), 2);
+        assert_eq!(MessagePack::from_payload(&[]).unwrap_err(), MessageError::InsufficientLength(4, 0));
+    }
+}
@@ -1,21 +1,75 @@
 
+#[allow(clippy::module_inception)]
 pub mod message;
 pub mod base_message;
 pub mod position_vector_message;
 pub mod system_message;
+pub mod auth_message;
+pub mod self_id_message;
+pub mod operator_id_message;
+pub mod message_pack;
+pub mod classification;
+pub mod codes;
+pub mod accuracy;
+pub mod profile;
+use serde::{Deserialize, Serialize};
 use tracing::info;
 
 use crate::message::message::Message;
 
+/// 任一类型的 Remote ID 消息
+///
+/// 序列化为带 `type` 字段的 JSON 对象（如 `{"type":"position_vector",...}`），
+/// 便于将解码后的原始消息写入 NDJSON 或存储后重新读取。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
 pub enum AnyMessage {
     Base(base_message::BaseMessage),
     PositionVector(position_vector_message::PositionVectorMessage),
-    System(system_message::SystemMessage)
+    Auth(auth_message::AuthMessage),
+    SelfId(self_id_message::SelfIdMessage),
+    System(system_message::SystemMessage),
+    OperatorId(operator_id_message::OperatorIdMessage),
+}
+
+/// 单条消息的字节长度（含 1 字节消息头）
+pub const MESSAGE_LEN: usize = 25;
+
+/// 编码时写入消息头低 4 位的协议版本
+pub const PROTOCOL_VERSION: u8 = 2;
+
+/// 解码后的消息及其原始 25 字节，便于下游转发原始数据重新解码
+#[derive(Debug, Clone)]
+pub struct DecodedMessage {
+    pub message: AnyMessage,
+    pub raw: [u8; MESSAGE_LEN],
+}
+
+impl DecodedMessage {
+    pub fn decode(data: &[u8], options: &DecodeOptions) -> Result<Self, message::MessageError> {
+        let raw: [u8; MESSAGE_LEN] = data.get(..MESSAGE_LEN)
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or(message::MessageError::InsufficientLength(MESSAGE_LEN, data.len()))?;
+        let message = AnyMessage::from_bytes_with(&raw, options)?;
+        Ok(Self { message, raw })
+    }
+}
+
+/// 消息解码选项
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DecodeOptions {
+    pub lossy_utf8: bool,   // UAS ID 等文本字段使用宽松 UTF-8 解码
+    pub profile: profile::FormatProfile,    // 位置向量和系统消息的字段布局
 }
 
 impl AnyMessage {
     /// 工厂方法 - 根据首字节的消息类型创建具体的消息实例
     pub fn from_bytes(data: &[u8]) -> Result<Self, message::MessageError> {
+        Self::from_bytes_with(data, &DecodeOptions::default())
+    }
+
+    /// 按指定解码选项创建消息实例
+    pub fn from_bytes_with(data: &[u8], options: &DecodeOptions) -> Result<Self, message::MessageError> {
 
         if data.is_empty() {
             return Err(message::MessageError::InsufficientLength(1, 0));
@@ -25,23 +79,370 @@ impl AnyMessage {
         info!("message type = {}", message_type);
         match message_type {
             base_message::BaseMessage::MESSAGE_TYPE => {
-                base_message::BaseMessage::from_bytes(content).map(AnyMessage::Base)
+                if options.lossy_utf8 {
+                    base_message::BaseMessage::from_bytes_lossy(content).map(AnyMessage::Base)
+                } else {
+                    base_message::BaseMessage::from_bytes(content).map(AnyMessage::Base)
+                }
             },
             position_vector_message::PositionVectorMessage::MESSAGE_TYPE => {
-                position_vector_message::PositionVectorMessage::from_bytes(content).map(AnyMessage::PositionVector)
+                match options.profile {
+                    profile::FormatProfile::National => position_vector_message::PositionVectorMessage::from_bytes(content),
+                    profile::FormatProfile::Astm => position_vector_message::PositionVectorMessage::from_astm_bytes(content),
+                }.map(AnyMessage::PositionVector)
+            },
+            auth_message::AuthMessage::MESSAGE_TYPE => {
+                auth_message::AuthMessage::from_bytes(content).map(AnyMessage::Auth)
+            },
+            self_id_message::SelfIdMessage::MESSAGE_TYPE => {
+                if options.lossy_utf8 {
+                    self_id_message::SelfIdMessage::from_bytes_lossy(content).map(AnyMessage::SelfId)
+                } else {
+                    self_id_message::SelfIdMessage::from_bytes(content).map(AnyMessage::SelfId)
+                }
             },
             system_message::SystemMessage::MESSAGE_TYPE => {
-                system_message::SystemMessage::from_bytes(content).map(AnyMessage::System)
+                match options.profile {
+                    profile::FormatProfile::National => system_message::SystemMessage::from_bytes(content),
+                    profile::FormatProfile::Astm => system_message::SystemMessage::from_astm_bytes(content),
+                }.map(AnyMessage::System)
+            },
+            operator_id_message::OperatorIdMessage::MESSAGE_TYPE => {
+                if options.lossy_utf8 {
+                    operator_id_message::OperatorIdMessage::from_bytes_lossy(content).map(AnyMessage::OperatorId)
+                } else {
+                    operator_id_message::OperatorIdMessage::from_bytes(content).map(AnyMessage::OperatorId)
+                }
             },
             t => Err(message::MessageError::UnknownMessageType(t)),
         }
     }
     
+    /// 编码为带消息头的 25 字节消息
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let (message_type, body) = match self {
+            AnyMessage::Base(msg) => (base_message::BaseMessage::MESSAGE_TYPE, msg.to_bytes()),
+            AnyMessage::PositionVector(msg) => (position_vector_message::PositionVectorMessage::MESSAGE_TYPE, msg.to_bytes()),
+            AnyMessage::Auth(msg) => (auth_message::AuthMessage::MESSAGE_TYPE, msg.to_bytes()),
+            AnyMessage::SelfId(msg) => (self_id_message::SelfIdMessage::MESSAGE_TYPE, msg.to_bytes()),
+            AnyMessage::System(msg) => (system_message::SystemMessage::MESSAGE_TYPE, msg.to_bytes()),
+            AnyMessage::OperatorId(msg) => (operator_id_message::OperatorIdMessage::MESSAGE_TYPE, msg.to_bytes()),
+        };
+        let mut data = Vec::with_capacity(MESSAGE_LEN);
+        data.push(message_type << 4 | PROTOCOL_VERSION);
+        data.extend_from_slice(&body);
+        data.truncate(MESSAGE_LEN);
+        data
+    }
+
     pub fn print(&self) {
         match self {
             AnyMessage::Base(msg) => msg.print(),
             AnyMessage::PositionVector(msg) => msg.print(),
+            AnyMessage::Auth(msg) => msg.print(),
+            AnyMessage::SelfId(msg) => msg.print(),
             AnyMessage::System(msg) => msg.print(),
+            AnyMessage::OperatorId(msg) => msg.print(),
+        }
+    }
+}
+/// 按来源无人机拆分一个消息包中的消息
+///
+/// 中继/汇聚广播会把多架无人机的消息放进同一个包，按包内顺序分组：
+/// - Basic ID 消息开始一架无人机的上下文；UAS ID 与已有分组相同时归入该分组。
+///   紧接在 Basic ID 之后、ID 类型不同的 Basic ID 视为同一架机的第二个身份。
+/// - 其它消息属于最近的 Basic ID；当前分组已有同类消息时视为下一架无人机的开始。
+///   认证消息按页区分，同一分组可有多页，只有页号重复时才视为下一架。
+pub fn group_by_uas(messages: Vec<DecodedMessage>) -> Vec<Vec<DecodedMessage>> {
+    let mut groups: Vec<Vec<DecodedMessage>> = Vec::new();
+    let mut current = 0;
+    for decoded in messages {
+        let target = match &decoded.message {
+            AnyMessage::Base(base) => {
+                let group = groups.get(current);
+                if let Some(existing) = groups.iter().position(|g| g.iter().any(|m| uas_id(m) == Some(&base.uas_id))) {
+                    existing
+                } else if group.is_some_and(|g| g.iter().all(|m| uas_id(m).is_none())) {
+                    current
+                } else if let Some(g) = group
+                    && g.last().is_some_and(|m| matches!(m.message, AnyMessage::Base(_)))
+                    && !g.iter().any(|m| matches!(&m.message, AnyMessage::Base(b) if b.id_type == base.id_type))
+                {
+                    current
+                } else {
+                    groups.len()
+                }
+            }
+            message => match groups.get(current) {
+                Some(g) if g.iter().any(|m| same_slot(&m.message, message)) => groups.len(),
+                _ => current,
+            },
+        };
+        if target == groups.len() {
+            groups.push(Vec::new());
+        }
+        groups[target].push(decoded);
+        current = target;
+    }
+    groups
+}
+
+/// 同一架无人机的一组消息中是否只能出现其一
+fn same_slot(a: &AnyMessage, b: &AnyMessage) -> bool {
+    match (a, b) {
+        (AnyMessage::Auth(a), AnyMessage::Auth(b)) => a.page_number == b.page_number,
+        _ => std::mem::discriminant(a) == std::mem::discriminant(b),
+    }
+}
+
+fn uas_id(decoded: &DecodedMessage) -> Option<&String> {
+    match &decoded.message {
+        AnyMessage::Base(base) => Some(&base.uas_id),
+        _ => None,
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::base_message::BaseMessage;
+    use super::position_vector_message::PositionVectorMessage;
+    use super::system_message::SystemMessage;
+    use super::auth_message::AuthMessage;
+    use super::self_id_message::SelfIdMessage;
+    use super::operator_id_message::OperatorIdMessage;
+
+    #[test]
+    fn test_serde_round_trip() {
+        for data in payloads(64) {
+            let mut message = [0u8; MESSAGE_LEN];
+            message[1..].copy_from_slice(&data);
+            for message_type in [0x0, 0x1, 0x2] {
+                message[0] = message_type << 4 | PROTOCOL_VERSION;
+                let decoded = AnyMessage::from_bytes_with(&message, &DecodeOptions { lossy_utf8: true, ..Default::default() }).unwrap();
+                let json = serde_json::to_value(&decoded).unwrap();
+                assert_eq!(json["type"], ["base", "position_vector", "auth"][message_type as usize]);
+                assert_eq!(serde_json::from_value::<AnyMessage>(json).unwrap(), decoded);
+            }
+        }
+        let json = r#"{"type":"self_id","description_type":0,"description":"survey","description_lossy":false}"#;
+        let AnyMessage::SelfId(sid) = serde_json::from_str(json).unwrap() else { panic!() };
+        assert_eq!(sid.description, "survey");
+    }
+
+    #[test]
+    fn test_position_vector_accessors() {
+        use chrono::DateTime;
+        use super::codes::{HeightReference, OperationalStatus};
+
+        let mut data = [0u8; 24];
+        data[0] = 0x20 | 0x04 | 0x02 | 0x01;                // 空中, 相对地面, 西向, 速度乘数
+        data[1] = 10;
+        data[2] = 20;
+        data[3] = 63;                                       // 垂直速度未知
+        data[4..8].copy_from_slice(&312_345_678i32.to_le_bytes());
+        data[8..12].copy_from_slice(&1_214_000_000i32.to_le_bytes());
+        data[12..14].copy_from_slice(&(-1000i16).to_le_bytes());
+        data[14..16].copy_from_slice(&120i16.to_le_bytes());
+        data[20..22].copy_from_slice(&35_995u16.to_le_bytes());
+        let pvm = PositionVectorMessage::from_bytes(&data).unwrap();
+        assert_eq!((pvm.operational_status(), pvm.height_reference()), (OperationalStatus::Airborne, HeightReference::Ground));
+        assert_eq!((pvm.latitude_deg(), pvm.track_angle_deg()), (31.234_567_8, 190));
+        assert!((pvm.ground_speed_mps() - 102.89).abs() < 0.01);
+        assert_eq!((pvm.vertical_speed_mps(), pvm.pressure_altitude_m(), pvm.geometric_altitude_m()), (None, None, Some(120.0)));
+        // 59:59.5，在整点后 2 秒收到时属于上一小时
+        let received = DateTime::from_timestamp(1_700_006_402, 0).unwrap();
+        assert_eq!(pvm.timestamp(received).map(|t| t.timestamp_millis()), Some(1_700_006_399_500));
+    }
+
+    #[test]
+    fn test_base_message_accessors() {
+        use super::codes::{IdType, UaType};
+
+        let base = BaseMessage::from_bytes(&[0x12; 24]).unwrap();
+        assert_eq!((base.uas_id_type(), base.aircraft_type()), (IdType::SerialNumber, UaType::Multirotor));
+    }
+
+    #[test]
+    fn test_system_message_accessors() {
+        let mut data = [0u8; 24];
+        data[0] = 2 << 2;
+        data[18..20].copy_from_slice(&525u16.to_le_bytes());
+        data[20..24].copy_from_slice(&1_700_000_000u32.to_le_bytes());
+        let sm = SystemMessage::from_bytes(&data).unwrap();
+        assert!((sm.station_altitude_m() - 52.5).abs() < 1e-4);
+        assert_eq!(sm.timestamp().map(|t| t.timestamp()), Some(1_700_000_000));
+    }
+
+    fn round_trip<M: Message + PartialEq + std::fmt::Debug>(message: M, decode: fn(&[u8]) -> Result<M, message::MessageError>) {
+        assert_eq!(decode(&message.to_bytes()), Ok(message));
+    }
+
+    /// 任意解码得到的消息编码后再解码不变：`from_bytes(to_bytes(m)) == m`
+    #[test]
+    fn test_to_bytes_round_trip() {
+        for mut data in payloads(512) {
+            round_trip(BaseMessage::from_bytes_lossy(&data).unwrap(), BaseMessage::from_bytes_lossy);
+            round_trip(PositionVectorMessage::from_bytes(&data).unwrap(), PositionVectorMessage::from_bytes);
+            round_trip(AuthMessage::from_bytes(&data).unwrap(), AuthMessage::from_bytes);
+
+            let mut text = data;
+            text[1..].iter_mut().for_each(|b| *b = 0x20 + *b % 0x5F);
+            round_trip(SelfIdMessage::from_bytes(&text).unwrap(), SelfIdMessage::from_bytes);
+            round_trip(OperatorIdMessage::from_bytes(&text).unwrap(), OperatorIdMessage::from_bytes);
+
+            data[0] = (data[0] & !0x1C) | (1 + data[0] % 3) << 2;
+            let sm = SystemMessage::from_bytes(&data).unwrap();
+            round_trip(sm.clone(), SystemMessage::from_bytes);
+
+            // 带消息头的完整消息
+            let AnyMessage::System(decoded) = AnyMessage::from_bytes(&AnyMessage::System(sm.clone()).to_bytes()).unwrap() else { panic!() };
+            assert_eq!(decoded, sm);
         }
+
+        // 直接构造的消息按文本编码 UAS ID
+        let base = BaseMessage {
+            id_type: 1,
+            ua_type: 2,
+            uas_id: "1581F5FKD229400A".into(),
+            uas_id_raw: [0; 20],
+            uas_id_lossy: false,
+            reserved: [0; 3],
+        };
+        let decoded = BaseMessage::from_bytes(&base.to_bytes()).unwrap();
+        assert_eq!((decoded.id_type, decoded.ua_type, decoded.uas_id.as_str()), (1, 2, "1581F5FKD229400A"));
+    }
+
+    /// 固定种子的伪随机消息体，保证测试可复现
+    fn payloads(count: usize) -> impl Iterator<Item = [u8; 24]> {
+        let mut state: u64 = 0x9E37_79B9_7F4A_7C15;
+        (0..count).map(move |_| {
+            let mut payload = [0u8; 24];
+            for byte in payload.iter_mut() {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                *byte = state as u8;
+            }
+            payload
+        })
     }
-}
\ No newline at end of file
+
+    /// 各字段按规定的偏移和字节序解出
+    #[test]
+    fn test_decoded_field_values() {
+        let mut data = [0u8; 24];
+        data[0] = 0x12;
+        data[1..17].copy_from_slice(b"1581F5FKD229400A");
+        data[21..24].copy_from_slice(&[7, 8, 9]);
+        let base = BaseMessage::from_bytes(&data).unwrap();
+        assert_eq!((base.id_type, base.ua_type, base.uas_id.as_str()), (1, 2, "1581F5FKD229400A"));
+        assert_eq!(base.reserved, [7, 8, 9]);
+
+        let mut data = [0u8; 24];
+        data[0] = 0x20;
+        data[1..4].copy_from_slice(&[90, 12, (-3i8) as u8]);
+        data[4..8].copy_from_slice(&399_123_456i32.to_le_bytes());
+        data[8..12].copy_from_slice(&(-1_164_000_001i32).to_le_bytes());
+        data[12..14].copy_from_slice(&150i16.to_le_bytes());
+        data[14..16].copy_from_slice(&152i16.to_le_bytes());
+        data[16..18].copy_from_slice(&(-20i16).to_le_bytes());
+        data[18] = 0x3B;
+        data[19] = 0xF4;
+        data[20..22].copy_from_slice(&12_345u16.to_le_bytes());
+        data[22] = 0xF5;
+        data[23] = 0xAA;
+        let pv = PositionVectorMessage::from_bytes(&data).unwrap();
+        assert_eq!((pv.run_status, pv.track_angle, pv.ground_speed, pv.vertical_speed), (2, 90, 12, -3));
+        assert_eq!((pv.latitude, pv.longitude), (399_123_456, -1_164_000_001));
+        assert_eq!((pv.pressure_altitude, pv.geometric_altitude, pv.ground_altitude), (150, 152, -20));
+        assert_eq!((pv.vertical_accuracy, pv.horizontal_accuracy, pv.speed_accuracy), (3, 11, 4));
+        assert_eq!((pv.timestamp, pv.timestamp_accuracy, pv.reserved), (12_345, 5, 0xAA));
+
+        let mut data = [0u8; 24];
+        data[0] = 1 << 2 | 0x01;
+        data[1..5].copy_from_slice(&399_000_000i32.to_le_bytes());
+        data[5..9].copy_from_slice(&1_164_000_000i32.to_le_bytes());
+        data[9..11].copy_from_slice(&3u16.to_le_bytes());
+        data[11] = 25;
+        data[12..14].copy_from_slice(&1200u16.to_le_bytes());
+        data[14..16].copy_from_slice(&1000u16.to_le_bytes());
+        data[16..18].copy_from_slice(&[2, 5]);
+        data[18..20].copy_from_slice(&525u16.to_le_bytes());
+        data[20..24].copy_from_slice(&1_700_000_000u32.to_le_bytes());
+        let sm = SystemMessage::from_bytes(&data).unwrap();
+        assert_eq!((sm.classification_region, sm.station_type), (1, 1));
+        assert_eq!((sm.latitude, sm.longitude), (399_000_000, 1_164_000_000));
+        assert_eq!((sm.operation_count, sm.operation_radius), (Some(3), Some(25)));
+        assert_eq!((sm.altitude_upper, sm.altitude_lower), (Some(1200), Some(1000)));
+        assert_eq!((sm.ua_category, sm.ua_level, sm.station_altitude), (2, 5, 525));
+        assert_eq!(sm.timestamp, Some(1_700_000_000));
+    }
+
+    #[test]
+    fn test_group_by_uas() {
+        let message = |message_type: u8, id_type: u8, id: &str| {
+            let mut raw = [0u8; MESSAGE_LEN];
+            raw[0] = message_type << 4;
+            raw[1] = id_type << 4 | 2;
+            raw[2..2 + id.len()].copy_from_slice(id.as_bytes());
+            DecodedMessage::decode(&raw, &DecodeOptions::default()).unwrap()
+        };
+        let base = |id_type, id| message(BaseMessage::MESSAGE_TYPE, id_type, id);
+        let location = || message(PositionVectorMessage::MESSAGE_TYPE, 0, "");
+        let ids = |groups: &[Vec<DecodedMessage>]| groups.iter()
+            .map(|g| g.iter().filter_map(uas_id).cloned().collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+
+        // 单架无人机：两个身份 + 位置
+        let groups = group_by_uas(vec![base(1, "SERIAL-A"), base(2, "CAA-A"), location()]);
+        assert_eq!(ids(&groups), [vec!["SERIAL-A", "CAA-A"]]);
+
+        // 中继包中的两架无人机
+        let groups = group_by_uas(vec![base(1, "A"), location(), base(1, "B"), location(), base(1, "A")]);
+        assert_eq!(ids(&groups), [vec!["A", "A"], vec!["B"]]);
+        assert_eq!(groups.iter().map(Vec::len).collect::<Vec<_>>(), [3, 2]);
+
+        // 没有 Basic ID 时重复的位置消息分属不同上下文
+        assert_eq!(group_by_uas(vec![location(), location()]).len(), 2);
+    }
+
+    #[test]
+    fn test_header_dispatches_self_id_and_operator_id() {
+        let mut raw = [0u8; MESSAGE_LEN];
+        raw[0] = 0x32;
+        raw[2..16].copy_from_slice(b"Bridge survey ");
+        let AnyMessage::SelfId(self_id) = AnyMessage::from_bytes(&raw).unwrap() else { panic!() };
+        assert_eq!(self_id.description, "Bridge survey");
+
+        let mut raw = [0u8; MESSAGE_LEN];
+        raw[0] = 0x52;
+        raw[2..18].copy_from_slice(b"CHN87astrdge12k8");
+        let AnyMessage::OperatorId(operator) = AnyMessage::from_bytes(&raw).unwrap() else { panic!() };
+        assert_eq!(operator.operator_id, "CHN87astrdge12k8");
+        assert!(AnyMessage::from_bytes(&raw[..10]).is_err());
+    }
+
+    #[test]
+    fn test_lossy_option_reaches_operator_id() {
+        let mut raw = [0u8; MESSAGE_LEN];
+        raw[0] = 0x52;
+        raw[20] = 0xfe;
+        assert!(AnyMessage::from_bytes(&raw).is_err());
+        let options = DecodeOptions { lossy_utf8: true, ..Default::default() };
+        let AnyMessage::OperatorId(operator) = AnyMessage::from_bytes_with(&raw, &options).unwrap() else { panic!() };
+        assert!(operator.operator_id_lossy);
+    }
+
+    /// 同一架无人机的多页认证归入同一分组，重复的页另起一组
+    #[test]
+    fn test_auth_pages_group_together() {
+        let mut page0 = [0u8; MESSAGE_LEN];
+        page0[..8].copy_from_slice(&[0x22, 0x10, 0x01, 30, 0x10, 0x27, 0x00, 0x00]);
+        let mut page1 = [0xbb; MESSAGE_LEN];
+        page1[..2].copy_from_slice(&[0x22, 0x11]);
+        let decode = |raw: &[u8]| DecodedMessage::decode(raw, &DecodeOptions::default()).unwrap();
+        assert!(matches!(decode(&page1).message, AnyMessage::Auth(ref auth) if auth.page_number == 1));
+        assert_eq!(group_by_uas(vec![decode(&page0), decode(&page1)]).len(), 1);
+        assert_eq!(group_by_uas(vec![decode(&page0), decode(&page0)]).len(), 2);
+    }
+}
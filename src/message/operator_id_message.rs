@@ -0,0 +1,97 @@
+use serde::{Deserialize, Serialize};
+
+use super::message::{decode_text, encode_text, Message, MessageError};
+
+/// 运营人 ID 消息：民航主管部门登记的运营人编号
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OperatorIdMessage {
+    pub operator_id_type: u8,     // 运营人 ID 类型 (0 为 CAA 登记号)
+    pub operator_id: String,      // 运营人 ID (20 字节 ASCII)
+    pub operator_id_lossy: bool,  // 运营人 ID 含非法 UTF-8，已按宽松模式替换
+    pub reserved: [u8; 3],        // 3 字节预留空间
+}
+
+impl OperatorIdMessage {
+    pub const MESSAGE_TYPE: u8 = 0x05;
+    const EXPECTED_LENGTH: usize = 24;
+
+    /// 宽松解析：运营人 ID 含非法 UTF-8 时替换为 U+FFFD
+    pub fn from_bytes_lossy(data: &[u8]) -> Result<Self, MessageError> {
+        Self::parse(data, true)
+    }
+
+    fn parse(data: &[u8], lossy: bool) -> Result<Self, MessageError> {
+        if data.len() < Self::EXPECTED_LENGTH {
+            return Err(MessageError::InsufficientLength(Self::EXPECTED_LENGTH, data.len()));
+        }
+        let (operator_id, operator_id_lossy) = decode_text(&data[1..21], lossy)?;
+        Ok(Self {
+            operator_id_type: data[0],
+            operator_id,
+            operator_id_lossy,
+            reserved: [data[21], data[22], data[23]],
+        })
+    }
+}
+
+impl Message for OperatorIdMessage {
+    fn from_bytes(data: &[u8]) -> Result<Self, MessageError> {
+        Self::parse(data, false)
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut data = vec![0u8; Self::EXPECTED_LENGTH];
+        data[0] = self.operator_id_type;
+        encode_text(&self.operator_id, &mut data[1..21]);
+        data[21..24].copy_from_slice(&self.reserved);
+        data
+    }
+
+    fn print(&self) {
+        println!("=== 运营人 ID 消息 (OperatorIdMessage) ===");
+        println!("运营人 ID 类型: {}", self.operator_id_type);
+        println!("运营人 ID: '{}'", self.operator_id);
+        println!("预留字段: {:02X?}", self.reserved);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn body(operator_id: &[u8]) -> [u8; 24] {
+        let mut data = [0u8; 24];
+        data[1..1 + operator_id.len()].copy_from_slice(operator_id);
+        data[21..24].copy_from_slice(&[1, 2, 3]);
+        data
+    }
+
+    #[test]
+    fn test_operator_id_and_reserved_bytes() {
+        let operator = OperatorIdMessage::from_bytes(&body(b"CHN87astrdge12k8")).unwrap();
+        assert_eq!((operator.operator_id_type, operator.operator_id.as_str()), (0, "CHN87astrdge12k8"));
+        assert_eq!(operator.reserved, [1, 2, 3]);
+        assert!(!operator.operator_id_lossy);
+    }
+
+    #[test]
+    fn test_short_message_is_rejected() {
+        assert_eq!(OperatorIdMessage::from_bytes(&[0u8; 10]), Err(MessageError::InsufficientLength(24, 10)));
+    }
+
+    #[test]
+    fn test_invalid_utf8_needs_lossy_mode() {
+        let mut data = body(b"CHN87astrdge12k8");
+        data[19] = 0xfe;
+        assert!(matches!(OperatorIdMessage::from_bytes(&data), Err(MessageError::InvalidUtf8(_))));
+        let operator = OperatorIdMessage::from_bytes_lossy(&data).unwrap();
+        assert!(operator.operator_id_lossy);
+        assert!(operator.operator_id.starts_with("CHN87astrdge12k8"));
+    }
+
+    #[test]
+    fn test_encode_keeps_reserved_bytes() {
+        let data = body(b"CHN-OP-77");
+        assert_eq!(OperatorIdMessage::from_bytes(&data).unwrap().to_bytes(), data);
+    }
+}
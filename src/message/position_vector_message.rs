@@ -1,12 +1,18 @@
 
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::accuracy::AccuracyBounds;
+use super::codes::{HeightReference, OperationalStatus};
 use super::message::{Message, MessageError};
+use super::profile::astm_altitude_m;
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct PositionVectorMessage {
     // 第1字节 (运行状态和标志位)
     pub run_status: u8,         // 运行状态 (7-4位)
     pub reserved_flag: bool,     // 预留标志位 (3位)
-    pub height_type: u8,        // 高度类型位 (2位) - 0-3
+    pub height_type: u8,        // 高度类型 (2位, 单个标志位) - 0 相对起飞点 / 1 相对地面
     pub track_direction: bool,   // 航迹角 E/W 方向标志 (1位)
     pub speed_multiplier: bool,  // 速度乘数 (0位)
 
@@ -37,14 +43,144 @@ impl PositionVectorMessage {
     pub const MESSAGE_TYPE: u8 = 0x01;
     const EXPECTED_LENGTH: usize = 24;
 
-    fn calculate_full_track_angle(&self) -> u16 {
+    /// 高度字段表示未知的值 (米)
+    const UNKNOWN_ALTITUDE_M: i16 = -1000;
+    /// 垂直速度字段表示未知的值 (m/s)
+    const UNKNOWN_VERTICAL_SPEED: i8 = 63;
+    /// 时间戳的最大有效值 (0.1 秒)，即一小时
+    const MAX_TIMESTAMP: u16 = 36_000;
+
+    /// 航迹角 (度, 正北顺时针 0-359)，已按 E/W 方向标志换算
+    pub fn track_angle_deg(&self) -> u16 {
         if self.track_direction {
             self.track_angle as u16 + 180
         } else {
             self.track_angle as u16
         }
     }
+
+    pub fn operational_status(&self) -> OperationalStatus {
+        OperationalStatus::from_code(self.run_status)
+    }
+
+    pub fn height_reference(&self) -> HeightReference {
+        HeightReference::from_code(self.height_type)
+    }
+
+    /// 纬度 (度)
+    pub fn latitude_deg(&self) -> f64 {
+        self.latitude as f64 / 1e7
+    }
+
+    /// 经度 (度)
+    pub fn longitude_deg(&self) -> f64 {
+        self.longitude as f64 / 1e7
+    }
+
+    /// 地速 (m/s)，已按速度乘数换算
+    pub fn ground_speed_mps(&self) -> f32 {
+        self.calculate_ground_speed_knots() * 0.514_444
+    }
+
+    /// 垂直速度 (m/s, 向上为正)，未知时为 None
+    pub fn vertical_speed_mps(&self) -> Option<f32> {
+        (self.vertical_speed != Self::UNKNOWN_VERTICAL_SPEED).then_some(self.vertical_speed as f32)
+    }
+
+    /// 气压高度 (米)，未知时为 None
+    pub fn pressure_altitude_m(&self) -> Option<f32> {
+        altitude_m(self.pressure_altitude)
+    }
+
+    /// 几何高度 (米)，未知时为 None
+    pub fn geometric_altitude_m(&self) -> Option<f32> {
+        altitude_m(self.geometric_altitude)
+    }
+
+    /// 距地高度 (米)，基准见 [`Self::height_reference`]，未知时为 None
+    pub fn height_m(&self) -> Option<f32> {
+        altitude_m(self.ground_altitude)
+    }
+
+    /// 整点后的秒数，未知或越界时为 None
+    pub fn timestamp_s(&self) -> Option<f32> {
+        (self.timestamp <= Self::MAX_TIMESTAMP).then_some(self.timestamp as f32 / 10.0)
+    }
+
+    /// 报文时间戳只有整点后的 0.1 秒数，取离参考时间（通常为接收时间）最近的一个时刻
+    pub fn timestamp(&self, reference: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.timestamp_s()?;
+        let offset = TimeDelta::milliseconds(self.timestamp as i64 * 100);
+        let hour = TimeDelta::hours(1);
+        let start = reference.timestamp() - reference.timestamp().rem_euclid(3600);
+        let mut time = DateTime::from_timestamp(start, 0)? + offset;
+        if time - reference > hour / 2 {
+            time -= hour;
+        } else if reference - time > hour / 2 {
+            time += hour;
+        }
+        Some(time)
+    }
     
+    /// 各精度编码对应的物理误差上限
+    pub fn accuracy_bounds(&self) -> AccuracyBounds {
+        AccuracyBounds::from_codes(
+            self.horizontal_accuracy,
+            self.vertical_accuracy,
+            self.speed_accuracy,
+            self.timestamp_accuracy,
+        )
+    }
+
+    /// 按 ASTM F3411 布局解析，数值换算为国标格式的单位
+    ///
+    /// ASTM 的速度为 0.25 m/s（速度乘数时 0.75 m/s 加 63.75 m/s），垂直速度为 0.5 m/s，
+    /// 高度为 0.5 米分辨率加 -1000 米偏移；未知地速 (255) 记为 0，未知垂直速度记为 63 m/s。
+    pub fn from_astm_bytes(data: &[u8]) -> Result<Self, MessageError> {
+        if data.len() < Self::EXPECTED_LENGTH {
+            return Err(MessageError::InsufficientLength(Self::EXPECTED_LENGTH, data.len()));
+        }
+
+        let byte0 = data[0];
+        let speed_mps = match (data[2], byte0 & 0x01 != 0) {
+            (255, _) => 0.0,
+            (code, false) => code as f32 * 0.25,
+            (code, true) => code as f32 * 0.75 + 63.75,
+        };
+        let knots = speed_mps / 0.514_444;
+        let speed_multiplier = knots > i8::MAX as f32;
+        let ground_speed = if speed_multiplier { knots / 10.0 } else { knots }.round().min(i8::MAX as f32) as i8;
+        let vertical_speed = match data[3] as i8 {
+            126 => 63,
+            code => (code as f32 * 0.5).round() as i8,
+        };
+        let altitude = |offset: usize| {
+            astm_altitude_m(u16::from_le_bytes([data[offset], data[offset + 1]])).round() as i16
+        };
+
+        Ok(Self {
+            run_status: byte0 >> 4,
+            reserved_flag: byte0 & 0x08 != 0,
+            height_type: (byte0 >> 2) & 0x01,
+            track_direction: byte0 & 0x02 != 0,
+            speed_multiplier,
+            track_angle: data[1],
+            ground_speed,
+            vertical_speed,
+            latitude: i32::from_le_bytes([data[4], data[5], data[6], data[7]]),
+            longitude: i32::from_le_bytes([data[8], data[9], data[10], data[11]]),
+            pressure_altitude: altitude(12),
+            geometric_altitude: altitude(14),
+            ground_altitude: altitude(16),
+            vertical_accuracy: data[18] >> 4,
+            horizontal_accuracy: data[18] & 0x0F,
+            speed_accuracy: data[19] & 0x0F,
+            timestamp: u16::from_le_bytes([data[20], data[21]]),
+            timestamp_accuracy: data[22] & 0x0F,
+            reserved: data[23],
+        })
+    }
+
     fn calculate_ground_speed_knots(&self) -> f32 {
         if self.speed_multiplier {
             self.ground_speed as f32 * 10.0
@@ -54,6 +190,10 @@ impl PositionVectorMessage {
     }
 }
 
+/// 高度字段 (米) 换算，-1000 表示未知
+fn altitude_m(value: i16) -> Option<f32> {
+    (value != PositionVectorMessage::UNKNOWN_ALTITUDE_M).then_some(value as f32)
+}
 
 impl Message for PositionVectorMessage {
     /// 从u8数组解析为PositionVectorMessage
@@ -75,8 +215,8 @@ impl Message for PositionVectorMessage {
         let byte0 = data[0];
         let run_status = (byte0 >> 4) & 0x0F; // 7-4位: 运行状态
         let reserved_flag = (byte0 & 0x08) != 0; // 3位: 预留标志位
-        let height_type = (byte0 & 0x06) >> 1; // 2位: 高度类型位 (00, 01, 10, 11)
-        let track_direction = (byte0 & 0x01) != 0; // 1位: 航迹角方向标志
+        let height_type = (byte0 >> 2) & 0x01; // 2位: 高度类型 (单个标志位)
+        let track_direction = (byte0 & 0x02) != 0; // 1位: 航迹角方向标志
         let speed_multiplier = (byte0 & 0x01) != 0; // 0位: 速度乘数
 
         // 解析后续字节
@@ -137,24 +277,82 @@ impl Message for PositionVectorMessage {
     }
 
     
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut data = vec![0u8; Self::EXPECTED_LENGTH];
+        data[0] = self.run_status << 4
+            | (self.reserved_flag as u8) << 3
+            | (self.height_type & 0x01) << 2
+            | (self.track_direction as u8) << 1
+            | self.speed_multiplier as u8;
+        data[1] = self.track_angle;
+        data[2] = self.ground_speed as u8;
+        data[3] = self.vertical_speed as u8;
+        data[4..8].copy_from_slice(&self.latitude.to_le_bytes());
+        data[8..12].copy_from_slice(&self.longitude.to_le_bytes());
+        data[12..14].copy_from_slice(&self.pressure_altitude.to_le_bytes());
+        data[14..16].copy_from_slice(&self.geometric_altitude.to_le_bytes());
+        data[16..18].copy_from_slice(&self.ground_altitude.to_le_bytes());
+        data[18] = self.vertical_accuracy << 4 | self.horizontal_accuracy & 0x0F;
+        data[19] = self.speed_accuracy & 0x0F;
+        data[20..22].copy_from_slice(&self.timestamp.to_le_bytes());
+        data[22] = self.timestamp_accuracy & 0x0F;
+        data[23] = self.reserved;
+        data
+    }
+
     fn print(&self) {
         println!("=== PositionVectorMessage ===");
-        println!("运行状态: 0x{:X}", self.run_status);
-        println!("高度类型: {}", self.height_type);
+        println!("运行状态: 0x{:X} ({})", self.run_status, self.operational_status());
+        println!("高度类型: {} ({})", self.height_type, self.height_reference());
         println!("航迹方向: {}", if self.track_direction { "西" } else { "东" });
-        println!("航迹角: {}° (完整: {}°)", self.track_angle, self.calculate_full_track_angle());
+        println!("航迹角: {}° (完整: {}°)", self.track_angle, self.track_angle_deg());
         println!("地速: {}节 (×{})", self.calculate_ground_speed_knots(), 
                  if self.speed_multiplier { 10 } else { 1 });
         println!("垂直速度: {} m/s", self.vertical_speed);
-        println!("位置: ({}, {})", 
-                 self.latitude , 
-                 self.longitude);
+        println!("位置: ({}, {}) = ({:.7}°, {:.7}°)",
+                 self.latitude, self.longitude, self.latitude_deg(), self.longitude_deg());
         println!("高度: 气压={}m, 几何={}m, 距地={}m", 
                  self.pressure_altitude, self.geometric_altitude, self.ground_altitude);
-        println!("精度: 垂直={}, 水平={}, 速度={}", 
-                 self.vertical_accuracy, self.horizontal_accuracy, self.speed_accuracy);
+        let bounds = self.accuracy_bounds();
+        println!("精度: 垂直={} (<{:?}m), 水平={} (<{:?}m), 速度={} (<{:?}m/s)", 
+                 self.vertical_accuracy, bounds.vertical_m,
+                 self.horizontal_accuracy, bounds.horizontal_m,
+                 self.speed_accuracy, bounds.speed_mps);
         println!("时间戳: {} (0.1秒)", self.timestamp);
-        println!("时间精度: {}", self.timestamp_accuracy);
+        println!("时间精度: {} (<{:?}s)", self.timestamp_accuracy, bounds.timestamp_s);
         println!("预留: {:02X}", self.reserved);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn body(byte0: u8) -> [u8; 24] {
+        let mut data = [0u8; 24];
+        data[0] = byte0;
+        data
+    }
+
+    #[test]
+    fn test_flag_bits_follow_spec_layout() {
+        // 第1字节: 7-4 运行状态, 3 预留, 2 高度类型, 1 E/W 方向, 0 速度乘数，每个标志各占 1 位
+        let pvm = PositionVectorMessage::from_bytes(&body(0b0010_0110)).unwrap();
+        assert_eq!((pvm.run_status, pvm.reserved_flag, pvm.height_type), (2, false, 1));
+        assert_eq!((pvm.track_direction, pvm.speed_multiplier), (true, false));
+
+        let pvm = PositionVectorMessage::from_bytes(&body(0b0001_1001)).unwrap();
+        assert_eq!((pvm.run_status, pvm.reserved_flag, pvm.height_type), (1, true, 0));
+        assert_eq!((pvm.track_direction, pvm.speed_multiplier), (false, true));
+    }
+
+    #[test]
+    fn test_direction_bit_is_not_speed_multiplier() {
+        // 只置速度乘数位时航迹角不加 180°
+        let pvm = PositionVectorMessage::from_bytes(&body(0b0000_0001)).unwrap();
+        assert!(!pvm.track_direction && pvm.speed_multiplier);
+        let pvm = PositionVectorMessage::from_bytes(&body(0b0000_0010)).unwrap();
+        assert!(pvm.track_direction && !pvm.speed_multiplier);
+        assert_eq!(pvm.height_type, 0);
+    }
+}
@@ -0,0 +1,154 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// 报文字段布局与缩放规则
+///
+/// 两种格式的消息类型和 Basic ID 布局相同，位置向量和系统消息的字段编码不同。
+/// ASTM 格式解出的值换算为国标格式的单位，下游不必区分来源：
+/// - 高度为米，速度为节（超过 127 节时 ×10 并置速度乘数），垂直速度为 m/s；
+/// - 系统消息的时间戳换算为 Unix 秒，控制站高度换算为 0.1 米。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FormatProfile {
+    /// GB 42590 国标格式
+    #[default]
+    National,
+    /// ASTM F3411 / ASD-STAN 4709-002 (OpenDroneID)
+    Astm,
+}
+
+/// ASTM 时间戳的起点 2019-01-01 00:00:00 UTC 对应的 Unix 秒
+pub const ASTM_EPOCH: u32 = 1_546_300_800;
+
+/// ASTM 高度编码：0.5 米分辨率，偏移 -1000 米
+pub fn astm_altitude_m(code: u16) -> f32 {
+    code as f32 * 0.5 - 1000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message::{AnyMessage, DecodeOptions};
+    use crate::message::position_vector_message::PositionVectorMessage;
+    use crate::message::system_message::SystemMessage;
+
+    fn astm() -> DecodeOptions {
+        DecodeOptions { profile: FormatProfile::Astm, ..Default::default() }
+    }
+
+    /// ASTM 位置向量：`flags` 为状态字节低 4 位，航迹 10°
+    fn astm_position(flags: u8, speed: u8, vertical_speed: i8) -> [u8; 25] {
+        let mut raw = [0u8; 25];
+        raw[0] = 0x12;
+        raw[1] = 0x20 | flags;
+        raw[2] = 10;
+        raw[3] = speed;
+        raw[4] = vertical_speed as u8;
+        raw[5..9].copy_from_slice(&312_345_678i32.to_le_bytes());
+        raw[9..13].copy_from_slice(&1_214_567_890i32.to_le_bytes());
+        raw[13..15].copy_from_slice(&2200u16.to_le_bytes()); // 100 m
+        raw[15..17].copy_from_slice(&2240u16.to_le_bytes()); // 120 m
+        raw[17..19].copy_from_slice(&0u16.to_le_bytes());    // 未知 -1000 m
+        raw[19] = 0x4b;
+        raw[20] = 0x23;
+        raw[21..23].copy_from_slice(&1234u16.to_le_bytes());
+        raw[23] = 0x05;
+        raw
+    }
+
+    fn decode_position(raw: &[u8]) -> PositionVectorMessage {
+        let AnyMessage::PositionVector(pvm) = AnyMessage::from_bytes_with(raw, &astm()).unwrap() else { panic!() };
+        pvm
+    }
+
+    /// ASTM 系统消息：分类区域/类别字节、控制站高度 50 m、时间戳 100000 秒
+    fn astm_system(flags: u8, classification: u8) -> [u8; 25] {
+        let mut raw = [0u8; 25];
+        raw[0] = 0x42;
+        raw[1] = flags;
+        raw[17] = classification;
+        raw[18..20].copy_from_slice(&2100u16.to_le_bytes()); // 50 m
+        raw[20..24].copy_from_slice(&100_000u32.to_le_bytes());
+        raw
+    }
+
+    fn decode_system(raw: &[u8]) -> SystemMessage {
+        let AnyMessage::System(sm) = AnyMessage::from_bytes_with(raw, &astm()).unwrap() else { panic!() };
+        sm
+    }
+
+    #[test]
+    fn test_astm_altitude_encoding() {
+        assert_eq!(astm_altitude_m(0), -1000.0);
+        assert_eq!(astm_altitude_m(2000), 0.0);
+        assert_eq!(astm_altitude_m(2201), 100.5);
+    }
+
+    #[test]
+    fn test_profile_defaults_to_national() {
+        assert_eq!(FormatProfile::default(), FormatProfile::National);
+        assert_eq!(serde_json::to_string(&FormatProfile::Astm).unwrap(), "\"astm\"");
+    }
+
+    #[test]
+    fn test_astm_position_flags_and_direction() {
+        // 空中、几何高度、西向：航迹 10+180°
+        let pvm = decode_position(&astm_position(0x04 | 0x02, 40, 7));
+        assert_eq!((pvm.run_status, pvm.height_type, pvm.track_direction, pvm.track_angle), (2, 1, true, 10));
+        assert_eq!((pvm.latitude, pvm.longitude), (312_345_678, 1_214_567_890));
+    }
+
+    #[test]
+    fn test_astm_speed_in_knots() {
+        // 40 × 0.25 = 10 m/s ≈ 19.4 节
+        let pvm = decode_position(&astm_position(0, 40, 7));
+        assert_eq!((pvm.speed_multiplier, pvm.ground_speed), (false, 19));
+        // 未知速度
+        assert_eq!(decode_position(&astm_position(0, 255, 7)).ground_speed, 0);
+    }
+
+    #[test]
+    fn test_astm_high_speed_sets_multiplier() {
+        // 地速 100 × 0.75 + 63.75 = 138.75 m/s ≈ 269.7 节，超过 127 节按 ×10 编码
+        let pvm = decode_position(&astm_position(0x01, 100, 7));
+        assert_eq!((pvm.speed_multiplier, pvm.ground_speed), (true, 27));
+    }
+
+    #[test]
+    fn test_astm_vertical_speed() {
+        assert_eq!(decode_position(&astm_position(0, 40, 7)).vertical_speed, 4);
+        assert_eq!(decode_position(&astm_position(0, 40, -7)).vertical_speed, -4);
+        assert_eq!(decode_position(&astm_position(0, 40, 126)).vertical_speed, 63);
+    }
+
+    #[test]
+    fn test_astm_altitudes_and_accuracy() {
+        let pvm = decode_position(&astm_position(0, 40, 7));
+        assert_eq!((pvm.pressure_altitude, pvm.geometric_altitude, pvm.ground_altitude), (100, 120, -1000));
+        assert_eq!((pvm.vertical_accuracy, pvm.horizontal_accuracy, pvm.speed_accuracy), (4, 11, 3));
+        assert_eq!((pvm.timestamp, pvm.timestamp_accuracy), (1234, 5));
+    }
+
+    #[test]
+    fn test_astm_system_undeclared_region() {
+        // 未声明分类区域 (0) 在国标格式下是非法值
+        let raw = astm_system(0x01, 0x00);
+        assert!(AnyMessage::from_bytes(&raw).is_err());
+        let sm = decode_system(&raw);
+        assert_eq!((sm.classification_region, sm.station_type), (0, 1));
+    }
+
+    #[test]
+    fn test_astm_system_timestamp_and_altitude() {
+        let sm = decode_system(&astm_system(0x01, 0x00));
+        assert_eq!(sm.station_altitude, 500);
+        assert_eq!(sm.timestamp, Some(ASTM_EPOCH + 100_000));
+    }
+
+    #[test]
+    fn test_astm_system_eu_classification() {
+        // 欧盟分类：类别与等级在同一字节
+        let sm = decode_system(&astm_system(0x01 << 2, 0x23));
+        assert_eq!((sm.classification_region, sm.ua_category, sm.ua_level), (1, 2, 3));
+    }
+}
@@ -0,0 +1,92 @@
+use serde::{Deserialize, Serialize};
+
+use super::message::{decode_text, encode_text, Message, MessageError};
+
+/// 自我描述消息：操作员填写的飞行目的等文本
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SelfIdMessage {
+    pub description_type: u8,     // 描述类型 (0 为文本，1 紧急，2 扩展状态)
+    pub description: String,      // 描述文本 (23 字节 ASCII)
+    pub description_lossy: bool,  // 描述含非法 UTF-8，已按宽松模式替换
+}
+
+impl SelfIdMessage {
+    pub const MESSAGE_TYPE: u8 = 0x03;
+    const EXPECTED_LENGTH: usize = 24;
+
+    /// 宽松解析：描述含非法 UTF-8 时替换为 U+FFFD
+    pub fn from_bytes_lossy(data: &[u8]) -> Result<Self, MessageError> {
+        Self::parse(data, true)
+    }
+
+    fn parse(data: &[u8], lossy: bool) -> Result<Self, MessageError> {
+        if data.len() < Self::EXPECTED_LENGTH {
+            return Err(MessageError::InsufficientLength(Self::EXPECTED_LENGTH, data.len()));
+        }
+        let (description, description_lossy) = decode_text(&data[1..24], lossy)?;
+        Ok(Self { description_type: data[0], description, description_lossy })
+    }
+}
+
+impl Message for SelfIdMessage {
+    fn from_bytes(data: &[u8]) -> Result<Self, MessageError> {
+        Self::parse(data, false)
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut data = vec![0u8; Self::EXPECTED_LENGTH];
+        data[0] = self.description_type;
+        encode_text(&self.description, &mut data[1..24]);
+        data
+    }
+
+    fn print(&self) {
+        println!("=== 自我描述消息 (SelfIdMessage) ===");
+        println!("描述类型: {}", self.description_type);
+        println!("描述: '{}'", self.description);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn body(description_type: u8, text: &[u8]) -> [u8; 24] {
+        let mut data = [0u8; 24];
+        data[0] = description_type;
+        data[1..1 + text.len()].copy_from_slice(text);
+        data
+    }
+
+    #[test]
+    fn test_description_padding_is_trimmed() {
+        let sid = SelfIdMessage::from_bytes(&body(0, b"Bridge survey ")).unwrap();
+        assert_eq!((sid.description_type, sid.description.as_str(), sid.description_lossy), (0, "Bridge survey", false));
+    }
+
+    #[test]
+    fn test_emergency_description_type() {
+        assert_eq!(SelfIdMessage::from_bytes(&body(1, b"LOST LINK")).unwrap().description_type, 1);
+    }
+
+    #[test]
+    fn test_short_message_is_rejected() {
+        assert_eq!(SelfIdMessage::from_bytes(&[0u8; 23]), Err(MessageError::InsufficientLength(24, 23)));
+    }
+
+    #[test]
+    fn test_invalid_utf8_needs_lossy_mode() {
+        let data = body(0, b"survey\xfe");
+        assert!(matches!(SelfIdMessage::from_bytes(&data), Err(MessageError::InvalidUtf8(_))));
+        let sid = SelfIdMessage::from_bytes_lossy(&data).unwrap();
+        assert_eq!((sid.description.as_str(), sid.description_lossy), ("survey\u{FFFD}", true));
+    }
+
+    #[test]
+    fn test_long_description_is_truncated_on_encode() {
+        let sid = SelfIdMessage { description_type: 0, description: "x".repeat(30), description_lossy: false };
+        let data = sid.to_bytes();
+        assert_eq!(data.len(), 24);
+        assert_eq!(SelfIdMessage::from_bytes(&data).unwrap().description, "x".repeat(23));
+    }
+}
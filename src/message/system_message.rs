@@ -1,10 +1,15 @@
 use std::convert::TryInto;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 use tracing::info;
 
+use super::classification::{Classification, ClassificationRegion, UaCategory, UaLevel};
 use super::message::{Message, MessageError};
+use super::profile::{astm_altitude_m, ASTM_EPOCH};
 
 // SystemMessage 结构体
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SystemMessage {
     // 起始字节1 (1字节)
     pub coordinate_system: u8,     // 坐标系类型 (7位)
@@ -41,6 +46,93 @@ pub struct SystemMessage {
 impl SystemMessage {
     pub const MESSAGE_TYPE: u8 = 0x04;
     const EXPECTED_LENGTH: usize = 24;
+
+    pub fn region(&self) -> ClassificationRegion {
+        ClassificationRegion::from_code(self.classification_region)
+    }
+
+    pub fn category(&self) -> UaCategory {
+        UaCategory::from_code(self.ua_category)
+    }
+
+    pub fn level(&self) -> UaLevel {
+        UaLevel::from_code(self.region(), self.ua_level)
+    }
+
+    /// 按 ASTM F3411 布局解析
+    ///
+    /// 与国标格式的区别：允许未声明分类区域 (0)，类别与等级共用一个字节（高/低 4 位），
+    /// 控制站高度为 0.5 米分辨率加 -1000 米偏移（换算为 0.1 米），时间戳自 2019-01-01 起（换算为 Unix 秒）。
+    pub fn from_astm_bytes(data: &[u8]) -> Result<Self, MessageError> {
+        if data.len() < Self::EXPECTED_LENGTH {
+            return Err(MessageError::InsufficientLength(Self::EXPECTED_LENGTH, data.len()));
+        }
+
+        let byte0 = data[0];
+        let u16_at = |offset: usize| u16::from_le_bytes([data[offset], data[offset + 1]]);
+        let station_altitude = (astm_altitude_m(u16_at(17)) * 10.0).round().clamp(0.0, u16::MAX as f32) as u16;
+        let timestamp = u32::from_le_bytes([data[19], data[20], data[21], data[22]]);
+
+        Ok(Self {
+            coordinate_system: (byte0 >> 5) & 0x07,
+            reserved_bits: 0,
+            classification_region: (byte0 >> 2) & 0x07,
+            station_type: byte0 & 0x03,
+            latitude: i32::from_le_bytes([data[1], data[2], data[3], data[4]]),
+            longitude: i32::from_le_bytes([data[5], data[6], data[7], data[8]]),
+            operation_count: Some(u16_at(9)),
+            operation_radius: Some(data[11]),
+            altitude_upper: Some(u16_at(12)),
+            altitude_lower: Some(u16_at(14)),
+            ua_category: data[16] >> 4,
+            ua_level: data[16] & 0x0F,
+            station_altitude,
+            timestamp: Some(if timestamp == 0 { 0 } else { timestamp.saturating_add(ASTM_EPOCH) }),
+            reserved: Some(data[23]),
+        })
+    }
+
+    /// 控制站纬度 (度)
+    pub fn latitude_deg(&self) -> f64 {
+        self.latitude as f64 / 1e7
+    }
+
+    /// 控制站经度 (度)
+    pub fn longitude_deg(&self) -> f64 {
+        self.longitude as f64 / 1e7
+    }
+
+    /// 控制站高度 (米)
+    pub fn station_altitude_m(&self) -> f32 {
+        self.station_altitude as f32 * 0.1
+    }
+
+    /// 运行区域半径 (米)
+    pub fn operation_radius_m(&self) -> Option<f32> {
+        self.operation_radius.map(|radius| radius as f32 * 10.0)
+    }
+
+    /// 运行区域高度上限和下限 (米)
+    pub fn operation_ceiling_m(&self) -> Option<f32> {
+        self.altitude_upper.map(|altitude| altitude as f32 * 0.1)
+    }
+
+    pub fn operation_floor_m(&self) -> Option<f32> {
+        self.altitude_lower.map(|altitude| altitude as f32 * 0.1)
+    }
+
+    /// 报文时间戳 (UTC)，未填写 (0) 时为 None
+    pub fn timestamp(&self) -> Option<DateTime<Utc>> {
+        self.timestamp.filter(|t| *t != 0).and_then(|t| DateTime::from_timestamp(t as i64, 0))
+    }
+
+    pub fn classification(&self) -> Classification {
+        Classification {
+            region: self.region(),
+            category: self.category(),
+            level: self.level(),
+        }
+    }
 }
 
 
@@ -162,18 +254,34 @@ impl Message for SystemMessage {
         })
     }
 
+    /// 预留位与分类区域共用字节，由 `classification_region` 决定
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut data = vec![0u8; Self::EXPECTED_LENGTH];
+        data[0] = self.coordinate_system << 5 | (self.classification_region & 0x07) << 2 | self.station_type & 0x03;
+        data[1..5].copy_from_slice(&self.latitude.to_le_bytes());
+        data[5..9].copy_from_slice(&self.longitude.to_le_bytes());
+        data[9..11].copy_from_slice(&self.operation_count.unwrap_or_default().to_le_bytes());
+        data[11] = self.operation_radius.unwrap_or_default();
+        data[12..14].copy_from_slice(&self.altitude_upper.unwrap_or_default().to_le_bytes());
+        data[14..16].copy_from_slice(&self.altitude_lower.unwrap_or_default().to_le_bytes());
+        data[16] = self.ua_category;
+        data[17] = self.ua_level;
+        data[18..20].copy_from_slice(&self.station_altitude.to_le_bytes());
+        data[20..24].copy_from_slice(&self.timestamp.unwrap_or_default().to_le_bytes());
+        if let Some(reserved) = self.reserved {
+            data.push(reserved);
+        }
+        data
+    }
+
     fn print(&self) {
         println!("=== 系统消息 (SystemMessage) ===");
         println!("坐标系类型: {}", self.coordinate_system);
         println!("预留位: {:02b}", self.reserved_bits);
-        println!("等级分类归属区域: {}", match self.classification_region {
-            2 => "中国",
-            3..=7 => "预留",
-            _ => "未定义或无效",
-        });
+        println!("等级分类归属区域: {}", self.region());
         println!("控制站位置类型: {}", self.station_type);
-        println!("控制站纬度: {:.6}°", self.latitude as f64 * 1e-7);
-        println!("控制站经度: {:.6}°", self.longitude as f64 * 1e-7);
+        println!("控制站纬度: {:.6}°", self.latitude_deg());
+        println!("控制站经度: {:.6}°", self.longitude_deg());
         
         if let Some(count) = self.operation_count {
             println!("运行区域计数: {}", count);
@@ -188,9 +296,9 @@ impl Message for SystemMessage {
             println!("运行区域高度下限: {} (实际: {:.1} 米)", alt_lower, alt_lower as f32 * 0.1);
         }
         
-        println!("UA运行类别: {}", self.ua_category);
-        println!("UA等级: {}", self.ua_level);
-        println!("控制站高度: {} (实际: {:.1} 米)", self.station_altitude, self.station_altitude as f32 * 0.1);
+        println!("UA运行类别: {} ({})", self.category(), self.ua_category);
+        println!("UA等级: {} ({})", self.level(), self.ua_level);
+        println!("控制站高度: {} (实际: {:.1} 米)", self.station_altitude, self.station_altitude_m());
         
         if let Some(ts) = self.timestamp {
             // 实际应用中可将时间戳转换为可读时间
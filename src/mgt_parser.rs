@@ -0,0 +1,137 @@
+use libwifi::frame::components::{MacAddress, VendorSpecificInfo};
+
+use crate::remote_id::VENDOR_ELEMENT_ID;
+
+/// 管理帧头长度
+const MGT_HEADER_LEN: usize = 24;
+
+/// 最简 802.11 管理帧解析结果
+#[derive(Debug, Clone)]
+pub struct MinimalFrame {
+    pub subtype: u8,
+    pub source: MacAddress,
+    pub ssid: String,
+    pub vendor_specific: Vec<VendorSpecificInfo>,
+    pub truncated: bool,    // IE 列表在末尾不完整（截断或带 FCS）
+}
+
+/// 专用于 Remote ID 提取的最简管理帧解析器
+///
+/// 只读取帧控制字段的子类型、地址 2（源 MAC），并按偏移逐个遍历 IE，收集
+/// SSID 与厂商 IE (221)，不构建完整的帧模型。遇到长度越界的 IE 即停止，
+/// 之前的 IE 仍然保留，因此尾部损坏或带 FCS 的帧也能取出 Remote ID。
+/// 仅处理信标、探测请求/响应。
+///
+/// 默认作为 libwifi 解析失败时的降级路径；启用 `builtin-parser` 特性后取代 libwifi。
+pub fn parse_management(data: &[u8]) -> Option<MinimalFrame> {
+    let frame_control = *data.first()?;
+    let frame_type = (frame_control >> 2) & 0x03;
+    let subtype = frame_control >> 4;
+    if frame_type != 0 || data.len() < MGT_HEADER_LEN {
+        return None;
+    }
+    let ies_start = match subtype {
+        8 | 5 => MGT_HEADER_LEN + 12, // 时间戳(8) + 信标间隔(2) + 能力信息(2)
+        4 => MGT_HEADER_LEN,
+        _ => return None,
+    };
+    let mut source = [0u8; 6];
+    source.copy_from_slice(&data[10..16]);
+
+    let mut ssid = String::new();
+    let mut vendor_specific = Vec::new();
+    let mut ies = data.get(ies_start..)?;
+    let mut truncated = false;
+    while ies.len() >= 2 {
+        let (id, len) = (ies[0], ies[1] as usize);
+        let Some(body) = ies.get(2..2 + len) else {
+            truncated = true;
+            break;
+        };
+        if id == 0 {
+            ssid = String::from_utf8_lossy(body).into_owned();
+        } else if id == VENDOR_ELEMENT_ID && len >= 4 {
+            vendor_specific.push(VendorSpecificInfo {
+                element_id: id,
+                length: len as u8,
+                oui: [body[0], body[1], body[2]],
+                oui_type: body[3],
+                data: body[4..].to_vec(),
+            });
+        }
+        ies = &ies[2 + len..];
+    }
+    Some(MinimalFrame { subtype, source: MacAddress(source), ssid, vendor_specific, truncated })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::remote_id::{reassemble, ODID_OUI_TYPE};
+
+    const SOURCE: [u8; 6] = [0x02, 0x11, 0x22, 0x33, 0x44, 0x55];
+
+    /// 管理帧头 + 固定字段 + IE 列表
+    fn frame(frame_control: u8, fixed_len: usize, ies: &[u8]) -> Vec<u8> {
+        let mut frame = vec![frame_control, 0x00, 0, 0];
+        frame.extend_from_slice(&[0xff; 6]);
+        frame.extend_from_slice(&SOURCE);
+        frame.extend_from_slice(&SOURCE);
+        frame.extend_from_slice(&[0x00, 0x00]);
+        frame.extend_from_slice(&vec![0u8; fixed_len]);
+        frame.extend_from_slice(ies);
+        frame
+    }
+
+    const SSID_RID: [u8; 5] = [0x00, 0x03, b'R', b'I', b'D'];
+    const ODID_IE: [u8; 10] = [221, 8, 0xfa, 0x0b, 0xbc, ODID_OUI_TYPE, 0x01, 0xf1, 0x19, 0x00];
+
+    #[test]
+    fn test_beacon() {
+        let parsed = parse_management(&frame(0x80, 12, &[&SSID_RID[..], &ODID_IE].concat())).unwrap();
+        assert_eq!((parsed.subtype, parsed.source.0, parsed.ssid.as_str()), (8, SOURCE, "RID"));
+        assert!(!parsed.truncated);
+        assert_eq!(reassemble(&parsed.vendor_specific), Some(vec![0x01, 0xf1, 0x19, 0x00]));
+    }
+
+    #[test]
+    fn test_probe_response_and_request() {
+        assert_eq!(parse_management(&frame(0x50, 12, &SSID_RID)).unwrap().ssid, "RID");
+        // 探测请求没有固定字段
+        assert_eq!(parse_management(&frame(0x40, 0, &SSID_RID)).unwrap().ssid, "RID");
+    }
+
+    #[test]
+    fn test_other_frames_are_rejected() {
+        assert!(parse_management(&frame(0xd0, 12, &SSID_RID)).is_none());   // 动作帧
+        assert!(parse_management(&frame(0x88, 12, &SSID_RID)).is_none());   // 数据帧
+        assert!(parse_management(&frame(0x80, 0, &[])[..20]).is_none());    // 帧头不完整
+        assert!(parse_management(&frame(0x80, 4, &[])).is_none());          // 固定字段不完整
+        assert!(parse_management(&[]).is_none());
+    }
+
+    #[test]
+    fn test_vendor_ie_fields() {
+        let parsed = parse_management(&frame(0x80, 12, &ODID_IE)).unwrap();
+        let ie = &parsed.vendor_specific[0];
+        assert_eq!((ie.element_id, ie.length, ie.oui, ie.oui_type), (221, 8, [0xfa, 0x0b, 0xbc], ODID_OUI_TYPE));
+        assert_eq!(ie.data, [0x01, 0xf1, 0x19, 0x00]);
+        // 不足 OUI + 类型的厂商 IE 被忽略
+        assert!(parse_management(&frame(0x80, 12, &[221, 3, 0xfa, 0x0b, 0xbc])).unwrap().vendor_specific.is_empty());
+    }
+
+    #[test]
+    fn test_truncated_beacon_keeps_vendor_ie() {
+        let ies = [&SSID_RID[..], &ODID_IE, &[0x30, 0x40, 0x01]].concat();   // 最后一个 IE 被截断
+        let parsed = parse_management(&frame(0x80, 12, &ies)).unwrap();
+        assert!(parsed.truncated);
+        assert_eq!(parsed.ssid, "RID");
+        assert_eq!(reassemble(&parsed.vendor_specific), Some(vec![0x01, 0xf1, 0x19, 0x00]));
+    }
+
+    #[test]
+    fn test_non_utf8_ssid_is_replaced() {
+        let parsed = parse_management(&frame(0x80, 12, &[0x00, 0x02, b'A', 0xff])).unwrap();
+        assert_eq!(parsed.ssid, "A\u{FFFD}");
+    }
+}
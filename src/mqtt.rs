@@ -0,0 +1,293 @@
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::alerts::Alert;
+use crate::upload_data::UploadData;
+
+/// MQTT 发布设置，需要启用 `mqtt` 特性
+///
+/// 每条检测以与 HTTP 上传相同的 JSON 结构发布到 `topic`，主题中的 `{uas_id}`、`{sensor_id}`
+/// 替换为记录的 UAS ID（没有时为轨迹 ID）和传感器 ID。与上传一样经过 `privacy.upload` 模糊化，
+/// 组网时只由汇聚节点发布。
+///
+/// ```toml
+/// [mqtt]
+/// host = "broker.example.com"
+/// port = 8883
+/// topic = "remoteid/{uas_id}/position"
+/// alert_topic = "remoteid/{sensor_id}/alerts"   # 可选，告警以 JSON 发布到该主题
+/// heartbeat_topic = "remoteid/{sensor_id}/health"   # 可选，心跳发布到该主题，见 health::HealthMonitor
+/// qos = 1                     # 0/1/2
+/// retain = false
+/// tls = true
+/// ca_file = "/etc/mqtt/ca.pem"        # 不指定时使用系统根证书
+/// client_cert = "/etc/mqtt/sensor.pem"  # 可选，双向 TLS
+/// client_key = "/etc/mqtt/sensor.key"
+/// username = "sensor"
+/// password = "..."
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MqttConfig {
+    pub host: String,
+    #[serde(default = "default_port")]
+    pub port: u16,
+    /// 默认使用传感器 ID
+    #[serde(default)]
+    pub client_id: Option<String>,
+    #[serde(default = "default_topic")]
+    pub topic: String,
+    #[serde(default)]
+    pub alert_topic: Option<String>,
+    #[serde(default)]
+    pub heartbeat_topic: Option<String>,
+    #[serde(default)]
+    pub qos: u8,
+    #[serde(default)]
+    pub retain: bool,
+    #[serde(default)]
+    pub tls: bool,
+    #[serde(default)]
+    pub ca_file: Option<PathBuf>,
+    #[serde(default)]
+    pub client_cert: Option<PathBuf>,
+    #[serde(default)]
+    pub client_key: Option<PathBuf>,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+    #[serde(default = "default_keep_alive_s")]
+    pub keep_alive_s: u64,
+}
+
+fn default_port() -> u16 {
+    1883
+}
+
+fn default_topic() -> String {
+    "remoteid/{uas_id}/position".into()
+}
+
+fn default_keep_alive_s() -> u64 {
+    30
+}
+
+/// 按主题模板生成记录的发布主题；ID 中的 `/`、`+`、`#` 替换为 `_`，避免产生多级主题或通配符
+pub fn topic(template: &str, sensor_id: &str, record: &UploadData) -> String {
+    let uas_id = if record.rid.is_empty() { &record.track_id } else { &record.rid };
+    template.replace("{uas_id}", &escape(uas_id)).replace("{sensor_id}", &escape(sensor_id))
+}
+
+/// 告警的发布主题，`{uas_id}` 替换为告警对象（聚合条件为 `*`）
+pub fn alert_topic(template: &str, sensor_id: &str, alert: &Alert) -> String {
+    template.replace("{uas_id}", &escape(&alert.subject)).replace("{sensor_id}", &escape(sensor_id))
+}
+
+fn escape(id: &str) -> String {
+    id.replace(['/', '+', '#'], "_")
+}
+
+#[cfg(feature = "mqtt")]
+pub use client::MqttPublisher;
+
+#[cfg(feature = "mqtt")]
+mod client {
+    use std::thread::{self, JoinHandle};
+    use std::time::Duration;
+
+    use rumqttc::{Client, MqttOptions, Outgoing, QoS, Transport};
+    use tracing::{info, warn};
+
+    use serde::Serialize;
+
+    use super::{alert_topic, escape, topic, Alert, MqttConfig};
+    use crate::canonical;
+    use crate::egress::{self, Priority};
+    use crate::event_log::DecodedEvent;
+    use crate::shutdown;
+
+    /// 发布队列长度，代理不可达时超出的检测被丢弃
+    const QUEUE_LEN: usize = 1024;
+    /// 每条 MQTT 发布估计的协议开销 (字节)
+    const PUBLISH_OVERHEAD: usize = 16;
+
+    /// MQTT 发布端：后台线程维持连接，断开后自动重连
+    pub struct MqttPublisher {
+        client: Client,
+        topic: String,
+        alert_topic: Option<String>,
+        heartbeat_topic: Option<String>,
+        qos: QoS,
+        retain: bool,
+        sensor_id: String,
+        handle: JoinHandle<()>,
+    }
+
+    impl MqttPublisher {
+        pub fn start(config: &MqttConfig, sensor_id: &str) -> Result<Self, String> {
+            let qos = match config.qos {
+                0 => QoS::AtMostOnce,
+                1 => QoS::AtLeastOnce,
+                2 => QoS::ExactlyOnce,
+                other => return Err(format!("qos 应为 0、1 或 2，实际为 {}", other)),
+            };
+            let client_id = config.client_id.clone().unwrap_or_else(|| sensor_id.to_string());
+            let mut options = MqttOptions::new(client_id, &config.host, config.port);
+            options.set_keep_alive(Duration::from_secs(config.keep_alive_s.max(5)));
+            if let Some(username) = &config.username {
+                options.set_credentials(username, config.password.clone().unwrap_or_default());
+            }
+            if config.tls {
+                options.set_transport(tls_transport(config)?);
+            }
+            let (client, mut connection) = Client::new(options, QUEUE_LEN);
+            let broker = format!("{}:{}", config.host, config.port);
+            let handle = thread::spawn(move || {
+                let mut connected = false;
+                for notification in connection.iter() {
+                    match notification {
+                        // 断开请求排在已提交的发布之后，送出即表示队列已清空
+                        Ok(rumqttc::Event::Outgoing(Outgoing::Disconnect)) => break,
+                        Ok(rumqttc::Event::Incoming(rumqttc::Packet::ConnAck(_))) => {
+                            info!("connected to mqtt broker {}", broker);
+                            connected = true;
+                        }
+                        Ok(_) => {}
+                        Err(e) => {
+                            if connected {
+                                warn!("mqtt connection to {} lost: {}", broker, e);
+                            }
+                            connected = false;
+                            thread::sleep(Duration::from_secs(5));
+                        }
+                    }
+                }
+            });
+            Ok(Self {
+                client,
+                topic: config.topic.clone(),
+                alert_topic: config.alert_topic.clone(),
+                heartbeat_topic: config.heartbeat_topic.clone(),
+                qos,
+                retain: config.retain,
+                sensor_id: sensor_id.to_string(),
+                handle,
+            })
+        }
+
+        /// 送出队列中的发布后断开连接；代理不可达或超时返回 false
+        pub fn close(self, timeout: Duration) -> bool {
+            if self.client.try_disconnect().is_err() {
+                return false;
+            }
+            shutdown::join_timeout(self.handle, timeout)
+        }
+
+        /// 发布一条检测；队列已满或超出出站预算时丢弃
+        pub fn publish(&self, event: &DecodedEvent) {
+            let Ok(payload) = canonical::to_json(&event.record) else { return };
+            if !egress::allow(Priority::Bulk, payload.len() + PUBLISH_OVERHEAD) {
+                return;
+            }
+            let topic = topic(&self.topic, &self.sensor_id, &event.record);
+            if let Err(e) = self.client.try_publish(topic, self.qos, self.retain, payload) {
+                warn!("mqtt publish dropped: {}", e);
+            }
+        }
+
+        /// 配置了 `alert_topic` 时发布一条告警，按告警优先级计入出站预算，不保留
+        pub fn publish_alert(&self, alert: &Alert) {
+            let Some(template) = &self.alert_topic else { return };
+            let Ok(payload) = serde_json::to_vec(alert) else { return };
+            if !egress::allow(Priority::Alert, payload.len() + PUBLISH_OVERHEAD) {
+                return;
+            }
+            let topic = alert_topic(template, &self.sensor_id, alert);
+            if let Err(e) = self.client.try_publish(topic, self.qos, false, payload) {
+                warn!("mqtt alert publish dropped: {}", e);
+            }
+        }
+
+        /// 配置了 `heartbeat_topic` 时发布一次心跳，`{sensor_id}` 替换为传感器 ID；保留最后一条，
+        /// 新订阅者立即得到节点的最近状态
+        pub fn publish_heartbeat<T: Serialize>(&self, report: &T) {
+            let Some(template) = &self.heartbeat_topic else { return };
+            let Ok(payload) = serde_json::to_vec(report) else { return };
+            if !egress::allow(Priority::Status, payload.len() + PUBLISH_OVERHEAD) {
+                return;
+            }
+            let topic = template.replace("{sensor_id}", &escape(&self.sensor_id));
+            if let Err(e) = self.client.try_publish(topic, self.qos, true, payload) {
+                warn!("mqtt heartbeat publish dropped: {}", e);
+            }
+        }
+    }
+
+    fn tls_transport(config: &MqttConfig) -> Result<Transport, String> {
+        let Some(ca_file) = &config.ca_file else {
+            return Ok(Transport::tls_with_default_config());
+        };
+        let read = |path: &std::path::PathBuf| std::fs::read(path).map_err(|e| format!("无法读取 {}: {}", path.display(), e));
+        let ca = read(ca_file)?;
+        let client_auth = match (&config.client_cert, &config.client_key) {
+            (Some(cert), Some(key)) => Some((read(cert)?, read(key)?)),
+            (None, None) => None,
+            _ => return Err("client_cert 与 client_key 需同时设置".into()),
+        };
+        Ok(Transport::tls(ca, client_auth, None))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::alerts::Severity;
+
+    fn alert(subject: &str) -> Alert {
+        Alert {
+            rule: "restricted".into(),
+            severity: Severity::Warning,
+            subject: subject.into(),
+            message: String::new(),
+            at_ms: 0,
+            authorization: None,
+            channels: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_config_defaults() {
+        let config: MqttConfig = toml::from_str(r#"host = "127.0.0.1""#).unwrap();
+        assert_eq!((config.port, config.qos, config.retain, config.tls), (1883, 0, false, false));
+        assert_eq!((config.topic.as_str(), config.keep_alive_s), ("remoteid/{uas_id}/position", 30));
+        assert!(config.client_id.is_none() && config.alert_topic.is_none() && config.heartbeat_topic.is_none());
+    }
+
+    #[test]
+    fn test_topic_escapes_wildcards() {
+        let record = UploadData { rid: "1581F5FK/D22+#".into(), ..Default::default() };
+        assert_eq!(topic(&default_topic(), "s1", &record), "remoteid/1581F5FK_D22__/position");
+    }
+
+    #[test]
+    fn test_topic_falls_back_to_track_id() {
+        let record = UploadData { track_id: "02:11:22:33:44:55".into(), ..Default::default() };
+        assert_eq!(topic("rid/{sensor_id}/{uas_id}", "s1", &record), "rid/s1/02:11:22:33:44:55");
+    }
+
+    #[test]
+    fn test_topic_escapes_sensor_id() {
+        let record = UploadData { rid: "RID-1".into(), ..Default::default() };
+        assert_eq!(topic("rid/{sensor_id}/{uas_id}", "roof/2", &record), "rid/roof_2/RID-1");
+    }
+
+    #[test]
+    fn test_alert_topic() {
+        assert_eq!(alert_topic("rid/{sensor_id}/alerts/{uas_id}", "s1", &alert("RID-1")), "rid/s1/alerts/RID-1");
+        // 聚合条件的对象 `*` 不是 MQTT 通配符，原样保留
+        assert_eq!(alert_topic("rid/{uas_id}", "s1", &alert("*")), "rid/*");
+        assert_eq!(alert_topic("rid/{uas_id}", "s1", &alert("a/#")), "rid/a__");
+    }
+}
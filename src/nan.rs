@@ -0,0 +1,239 @@
+use libwifi::frame::components::VendorSpecificInfo;
+
+use crate::remote_id::{ODID_OUI, ODID_OUI_TYPE, VENDOR_ELEMENT_ID};
+
+/// Wi-Fi Alliance OUI 及 NAN 的 OUI 类型
+const WFA_OUI: [u8; 3] = [0x50, 0x6f, 0x9a];
+const NAN_OUI_TYPE: u8 = 0x13;
+/// NAN 服务 ID：SHA-256("org.opendroneid.remoteid") 的前 6 字节
+pub const ODID_SERVICE_ID: [u8; 6] = [0x88, 0x69, 0x19, 0x9d, 0x92, 0x09];
+/// NAN 集群 ID 的前缀 (50:6F:9A:01:xx:xx)，同步信标的地址 3 为集群 ID
+const CLUSTER_ID_PREFIX: [u8; 4] = [0x50, 0x6f, 0x9a, 0x01];
+
+const ATTR_SERVICE_DESCRIPTOR: u8 = 0x03;
+const CATEGORY_PUBLIC: u8 = 4;
+const ACTION_VENDOR_SPECIFIC: u8 = 9;
+const MGT_HEADER_LEN: usize = 24;
+
+/// 从 NAN 帧中取出的 Remote ID 负载
+#[derive(Debug, PartialEq)]
+pub struct NanOdid {
+    pub source: [u8; 6],
+    pub cluster: Option<[u8; 6]>,
+    /// 消息计数器 + 消息包，与信标厂商 IE 中的负载格式相同
+    pub payload: Vec<u8>,
+}
+
+impl NanOdid {
+    /// 包装成 Remote ID 厂商 IE，沿用信标的解码流程
+    pub fn vendor_element(&self) -> VendorSpecificInfo {
+        VendorSpecificInfo {
+            element_id: VENDOR_ELEMENT_ID,
+            length: (self.payload.len() + 4).min(255) as u8,
+            oui: ODID_OUI,
+            oui_type: ODID_OUI_TYPE,
+            data: self.payload.clone(),
+        }
+    }
+}
+
+/// 识别通过 Wi-Fi NAN 广播的 Remote ID
+///
+/// - 服务发现帧 (SDF)：公共 Action 帧 (类别 4，厂商专用动作 9，OUI 50-6F-9A 类型 0x13)
+/// - NAN 同步信标：地址 3 为 NAN 集群 ID 的信标帧，NAN 属性位于 OUI 50-6F-9A 类型 0x13 的厂商 IE 中
+///
+/// 两者都在服务 ID 为 ODID 的服务描述属性 (SDA) 的服务信息中携带消息包。
+/// 不是 NAN 帧或没有 ODID 服务时返回 None。
+pub fn extract_odid(frame: &[u8]) -> Option<NanOdid> {
+    let frame_control = *frame.first()?;
+    if (frame_control >> 2) & 0x03 != 0 || frame.len() < MGT_HEADER_LEN {
+        return None;
+    }
+    let source: [u8; 6] = frame[10..16].try_into().ok()?;
+    let bssid: [u8; 6] = frame[16..22].try_into().ok()?;
+    let cluster = bssid.starts_with(&CLUSTER_ID_PREFIX).then_some(bssid);
+    let body = &frame[MGT_HEADER_LEN..];
+    let payload = match frame_control >> 4 {
+        13 => {
+            let (header, attributes) = body.split_at_checked(6)?;
+            if header != [CATEGORY_PUBLIC, ACTION_VENDOR_SPECIFIC, WFA_OUI[0], WFA_OUI[1], WFA_OUI[2], NAN_OUI_TYPE] {
+                return None;
+            }
+            odid_service_info(attributes)?
+        }
+        8 if cluster.is_some() => {
+            let mut ies = body.get(12..)?;   // 时间戳(8) + 信标间隔(2) + 能力信息(2)
+            let mut found = None;
+            while let [id, len, rest @ ..] = ies {
+                let Some(ie) = rest.get(..*len as usize) else { break };
+                if *id == VENDOR_ELEMENT_ID && ie.len() >= 4 && ie[..3] == WFA_OUI && ie[3] == NAN_OUI_TYPE {
+                    found = odid_service_info(&ie[4..]);
+                    if found.is_some() {
+                        break;
+                    }
+                }
+                ies = &rest[*len as usize..];
+            }
+            found?
+        }
+        _ => return None,
+    };
+    Some(NanOdid { source, cluster, payload })
+}
+
+/// 遍历 NAN 属性 ([ID][长度 (2 字节小端)][内容])，返回 ODID 服务描述属性的服务信息
+fn odid_service_info(mut attributes: &[u8]) -> Option<Vec<u8>> {
+    while let [id, len_lo, len_hi, rest @ ..] = attributes {
+        let len = u16::from_le_bytes([*len_lo, *len_hi]) as usize;
+        let body = rest.get(..len)?;
+        if *id == ATTR_SERVICE_DESCRIPTOR
+            && body.starts_with(&ODID_SERVICE_ID)
+            && let Some(info) = sda_service_info(body)
+        {
+            return Some(info.to_vec());
+        }
+        attributes = &rest[len..];
+    }
+    None
+}
+
+/// 服务描述属性：服务 ID(6) 实例 ID(1) 请求方实例 ID(1) 服务控制(1)，
+/// 之后按服务控制位依次为可选的绑定位图、匹配过滤器、服务响应过滤器和服务信息
+fn sda_service_info(sda: &[u8]) -> Option<&[u8]> {
+    let control = *sda.get(8)?;
+    let mut rest = &sda[9..];
+    if control & 0x40 != 0 {
+        rest = rest.get(2..)?;
+    }
+    for present in [control & 0x04 != 0, control & 0x08 != 0] {
+        if present {
+            let len = *rest.first()? as usize;
+            rest = rest.get(1 + len..)?;
+        }
+    }
+    if control & 0x10 == 0 {
+        return None;
+    }
+    let len = *rest.first()? as usize;
+    rest.get(1..1 + len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAYLOAD: [u8; 4] = [0x07, 0xf1, 0x19, 0x01];
+    const CLUSTER: [u8; 6] = [0x50, 0x6f, 0x9a, 0x01, 0x12, 0x34];
+
+    fn attribute(id: u8, body: &[u8]) -> Vec<u8> {
+        let mut attribute = vec![id];
+        attribute.extend_from_slice(&(body.len() as u16).to_le_bytes());
+        attribute.extend_from_slice(body);
+        attribute
+    }
+
+    /// 服务描述属性：`optional` 为服务控制位之后、服务信息之前的可选字段
+    fn sda_with(service_id: [u8; 6], control: u8, optional: &[u8], service_info: &[u8]) -> Vec<u8> {
+        let mut body = service_id.to_vec();
+        body.extend_from_slice(&[0x01, 0x00, control]);
+        body.extend_from_slice(optional);
+        if control & 0x10 != 0 {
+            body.push(service_info.len() as u8);
+            body.extend_from_slice(service_info);
+        }
+        attribute(ATTR_SERVICE_DESCRIPTOR, &body)
+    }
+
+    /// 带匹配过滤器和服务信息的 ODID 服务描述属性
+    fn sda(service_info: &[u8]) -> Vec<u8> {
+        sda_with(ODID_SERVICE_ID, 0x10 | 0x04, &[2, 0xaa, 0xbb], service_info)
+    }
+
+    fn header(subtype: u8, bssid: [u8; 6]) -> Vec<u8> {
+        let mut frame = vec![subtype << 4, 0, 0, 0];
+        frame.extend_from_slice(&[0xff; 6]);
+        frame.extend_from_slice(&[0x02, 0x11, 0x22, 0x33, 0x44, 0x55]);
+        frame.extend_from_slice(&bssid);
+        frame.extend_from_slice(&[0, 0]);
+        frame
+    }
+
+    fn sdf(attributes: &[u8]) -> Vec<u8> {
+        let mut sdf = header(13, CLUSTER);
+        sdf.extend_from_slice(&[CATEGORY_PUBLIC, ACTION_VENDOR_SPECIFIC, 0x50, 0x6f, 0x9a, NAN_OUI_TYPE]);
+        sdf.extend_from_slice(attributes);
+        sdf
+    }
+
+    fn sync_beacon(bssid: [u8; 6], attributes: &[u8]) -> Vec<u8> {
+        let mut beacon = header(8, bssid);
+        beacon.extend_from_slice(&[0; 12]);
+        beacon.extend_from_slice(&[0, 0]);   // 空 SSID
+        let mut nan_ie = vec![0x50, 0x6f, 0x9a, NAN_OUI_TYPE];
+        nan_ie.extend_from_slice(attributes);
+        beacon.extend_from_slice(&[VENDOR_ELEMENT_ID, nan_ie.len() as u8]);
+        beacon.extend(nan_ie);
+        beacon
+    }
+
+    #[test]
+    fn test_service_discovery_frame() {
+        let frame = sdf(&[attribute(0x02, &[0]), sda(&PAYLOAD)].concat());   // ODID 之前有其它属性
+        let odid = extract_odid(&frame).unwrap();
+        assert_eq!(odid, NanOdid { source: [0x02, 0x11, 0x22, 0x33, 0x44, 0x55], cluster: Some(CLUSTER), payload: PAYLOAD.to_vec() });
+    }
+
+    #[test]
+    fn test_sync_beacon() {
+        assert_eq!(extract_odid(&sync_beacon(CLUSTER, &sda(&PAYLOAD))).unwrap().payload, PAYLOAD);
+    }
+
+    #[test]
+    fn test_beacon_outside_cluster_is_ignored() {
+        assert_eq!(extract_odid(&sync_beacon([0x02; 6], &sda(&PAYLOAD))), None);
+    }
+
+    #[test]
+    fn test_other_action_frames_are_ignored() {
+        let mut frame = sdf(&sda(&PAYLOAD));
+        frame[MGT_HEADER_LEN + 5] = 0x09;   // 其它 WFA OUI 类型
+        assert_eq!(extract_odid(&frame), None);
+        assert_eq!(extract_odid(&header(13, CLUSTER)), None);
+        assert_eq!(extract_odid(&header(4, CLUSTER)), None);   // 探测请求
+    }
+
+    #[test]
+    fn test_other_services_are_skipped() {
+        let other = sda_with([1, 2, 3, 4, 5, 6], 0x10, &[], &[0xee]);
+        assert_eq!(extract_odid(&sdf(&[other, sda(&PAYLOAD)].concat())).unwrap().payload, PAYLOAD);
+        let other = sda_with([1, 2, 3, 4, 5, 6], 0x10, &[], &[0xee]);
+        assert_eq!(extract_odid(&sdf(&other)), None);
+    }
+
+    #[test]
+    fn test_optional_sda_fields_are_skipped() {
+        // 绑定位图 + 匹配过滤器 + 服务响应过滤器
+        let attribute = sda_with(ODID_SERVICE_ID, 0x40 | 0x04 | 0x08 | 0x10, &[0x01, 0x00, 1, 0xaa, 2, 0xbb, 0xcc], &PAYLOAD);
+        assert_eq!(extract_odid(&sdf(&attribute)).unwrap().payload, PAYLOAD);
+    }
+
+    #[test]
+    fn test_sda_without_service_info() {
+        assert_eq!(extract_odid(&sdf(&sda_with(ODID_SERVICE_ID, 0x04, &[1, 0xaa], &[]))), None);
+    }
+
+    #[test]
+    fn test_truncated_attribute() {
+        let mut attributes = sda(&PAYLOAD);
+        attributes.truncate(attributes.len() - 1);
+        assert_eq!(extract_odid(&sdf(&attributes)), None);
+    }
+
+    #[test]
+    fn test_vendor_element_wraps_payload() {
+        let odid = extract_odid(&sdf(&sda(&PAYLOAD))).unwrap();
+        let element = odid.vendor_element();
+        assert!(crate::remote_id::is_remote_id_element(&element));
+        assert_eq!((element.length, element.data), (8, PAYLOAD.to_vec()));
+    }
+}
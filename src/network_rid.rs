@@ -0,0 +1,324 @@
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use chrono::{DateTime, Timelike};
+use reqwest::blocking::Client;
+use serde::Deserialize;
+use tracing::{error, info, warn};
+
+use crate::event_log::DecodedEvent;
+use crate::schema::FORMAT_VERSION;
+use crate::upload_data::{RecordSource, UploadData};
+
+const MPS_PER_KNOT: f64 = 0.514_444;
+/// 推送请求正文上限
+const MAX_BODY: usize = 1 << 20;
+
+/// F3411 aircraft_type 枚举，顺序与 Basic ID 报文中的 UA 类型编码一致
+const AIRCRAFT_TYPES: [&str; 16] = [
+    "NotDeclared", "Aeroplane", "Helicopter", "Gyroplane", "HybridLift", "Ornithopter", "Glider", "Kite",
+    "FreeBalloon", "CaptiveBalloon", "Airship", "FreeFallOrParachute", "Rocket", "TetheredPoweredAircraft",
+    "GroundObstacle", "Other",
+];
+
+/// ASTM F3411 网络 Remote ID 显示接口 `GET /flights` 的响应格式
+#[derive(Debug, Deserialize)]
+struct FlightsResponse {
+    #[serde(default)]
+    flights: Vec<Flight>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Flight {
+    id: String,
+    #[serde(default)]
+    aircraft_type: Option<String>,
+    current_state: Option<FlightState>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FlightState {
+    timestamp: Option<StateTimestamp>,
+    position: Position,
+    track: Option<f64>,            // 航迹角 (度)，361 为未知
+    speed: Option<f64>,            // 地速 (米/秒)，255 为未知
+    vertical_speed: Option<f64>,   // 垂直速度 (米/秒)，63 为未知
+}
+
+#[derive(Debug, Deserialize)]
+struct StateTimestamp {
+    value: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Position {
+    lat: f64,
+    lng: f64,
+    alt: Option<f64>,   // WGS84 几何高度 (米)
+}
+
+/// 把 F3411 `/flights` 响应转换为记录，来源标注为网络 Remote ID
+///
+/// 网络 Remote ID 不含 UAS ID 报文，以飞行 ID 作为 UAS ID，轨迹 ID 加 `netrid:` 前缀与广播轨迹区分。
+/// 没有当前状态的飞行被忽略。
+pub fn parse_flights(json: &str) -> Result<Vec<UploadData>, serde_json::Error> {
+    let response: FlightsResponse = serde_json::from_str(json)?;
+    Ok(response.flights.iter().filter_map(to_record).collect())
+}
+
+fn to_record(flight: &Flight) -> Option<UploadData> {
+    let state = flight.current_state.as_ref()?;
+    let mut record = UploadData {
+        format_version: FORMAT_VERSION,
+        source: RecordSource::Network,
+        rid: flight.id.clone(),
+        track_id: format!("netrid:{}", flight.id),
+        ua_type: flight.aircraft_type.as_deref()
+            .and_then(|t| AIRCRAFT_TYPES.iter().position(|name| *name == t))
+            .map(|code| code as u8),
+        latitude: (state.position.lat * 1e7).round() as i32,
+        longitude: (state.position.lng * 1e7).round() as i32,
+        geometric_altitude: state.position.alt.map_or(0, |alt| alt.round() as i16),
+        ..Default::default()
+    };
+    if let Some(track) = state.track.filter(|t| (0.0..360.0).contains(t)) {
+        let track = track.round() as u16 % 360;
+        record.track_direction = track >= 180;
+        record.track_angle = (track % 180) as u8;
+    }
+    if let Some(speed) = state.speed.filter(|s| (0.0..255.0).contains(s)) {
+        let knots = speed / MPS_PER_KNOT;
+        record.speed_multiplier = knots > i8::MAX as f64;
+        let scaled = if record.speed_multiplier { knots / 10.0 } else { knots };
+        record.ground_speed = scaled.round().min(i8::MAX as f64) as i8;
+    }
+    if let Some(vertical) = state.vertical_speed.filter(|v| *v != 63.0) {
+        record.vertical_speed = vertical.round().clamp(-(i8::MAX as f64), i8::MAX as f64) as i8;
+    }
+    // 与位置向量报文相同，时间戳为整点后的 0.1 秒数
+    if let Some(time) = state.timestamp.as_ref().and_then(|t| DateTime::parse_from_rfc3339(&t.value).ok()) {
+        record.timestamp = ((time.minute() * 60 + time.second()) * 10 + time.nanosecond() / 100_000_000) as u16;
+    }
+    Some(record)
+}
+
+/// 网络 Remote ID 接入：接收 USS 或其他传感器网络推送的航迹，或定期拉取 USS 的显示接口，
+/// 与本机接收的广播记录一起进入输出
+///
+/// 只支持 HTTP；WebSocket 推送需经外部桥接转为 HTTP POST。
+pub struct NetworkIngest {
+    sender: mpsc::Sender<DecodedEvent>,
+    ingest: mpsc::Receiver<DecodedEvent>,
+}
+
+impl Default for NetworkIngest {
+    fn default() -> Self {
+        let (sender, ingest) = mpsc::channel();
+        Self { sender, ingest }
+    }
+}
+
+impl NetworkIngest {
+    /// 接收推送：`POST /flights`，正文为 `/flights` 响应格式的 JSON
+    pub fn spawn_http_listener(&self, addr: String) {
+        let sender = self.sender.clone();
+        thread::spawn(move || {
+            let listener = match TcpListener::bind(&addr) {
+                Ok(listener) => listener,
+                Err(e) => {
+                    error!("无法监听网络 Remote ID 接入 {}: {}", addr, e);
+                    return;
+                }
+            };
+            info!("accepting network remote id on http://{}/flights", addr);
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else { continue };
+                let (status, body) = match read_request(&mut stream) {
+                    Some((request_line, body)) if request_line.starts_with("POST /flights ") => {
+                        match parse_flights(&body) {
+                            Ok(records) => {
+                                let count = records.len();
+                                for record in records {
+                                    let _ = sender.send(DecodedEvent::now(record));
+                                }
+                                ("200 OK", format!("{{\"accepted\":{}}}", count))
+                            }
+                            Err(e) => ("400 Bad Request", serde_json::json!({ "error": e.to_string() }).to_string()),
+                        }
+                    }
+                    Some(_) => ("404 Not Found", r#"{"error":"unknown endpoint"}"#.to_string()),
+                    None => continue,
+                };
+                let _ = write!(stream, "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+                    status, body.len(), body);
+            }
+        });
+    }
+
+    /// 按间隔拉取 USS 显示接口 (`.../flights?view=...`)，同一飞行的状态未更新时不重复记录
+    pub fn spawn_poller(&self, url: String, interval: Duration) {
+        let sender = self.sender.clone();
+        thread::spawn(move || {
+            let client = Client::builder()
+                .timeout(Duration::from_secs(10))
+                .build()
+                .unwrap();
+            info!("polling network remote id from {} every {:?}", url, interval);
+            let mut last_seen: HashMap<String, (u16, i32, i32)> = HashMap::new();
+            loop {
+                let body = client.get(&url).send()
+                    .and_then(|r| r.error_for_status())
+                    .and_then(|r| r.text());
+                match body.as_deref().map(parse_flights) {
+                    Ok(Ok(records)) => {
+                        for record in records {
+                            let state = (record.timestamp, record.latitude, record.longitude);
+                            if last_seen.insert(record.rid.clone(), state) != Some(state) {
+                                let _ = sender.send(DecodedEvent::now(record));
+                            }
+                        }
+                    }
+                    Ok(Err(e)) => warn!("invalid flights response from {}: {}", url, e),
+                    Err(e) => warn!("failed to poll {}: {}", url, e),
+                }
+                thread::sleep(interval);
+            }
+        });
+    }
+
+    /// 取出接入的网络 Remote ID 事件
+    pub fn take_ingested(&self) -> Vec<DecodedEvent> {
+        self.ingest.try_iter().collect()
+    }
+}
+
+/// 读取一个 HTTP 请求，返回请求行和按 Content-Length 读取的正文
+fn read_request(stream: &mut TcpStream) -> Option<(String, String)> {
+    stream.set_read_timeout(Some(Duration::from_secs(5))).ok()?;
+    let mut data = Vec::new();
+    let mut buf = [0u8; 4096];
+    let header_end = loop {
+        let len = stream.read(&mut buf).ok().filter(|len| *len > 0)?;
+        data.extend_from_slice(&buf[..len]);
+        if let Some(end) = data.windows(4).position(|w| w == b"\r\n\r\n") {
+            break end + 4;
+        }
+        if data.len() > MAX_BODY {
+            return None;
+        }
+    };
+    let head = String::from_utf8_lossy(&data[..header_end]).into_owned();
+    let content_length = head.lines()
+        .filter_map(|line| line.split_once(':'))
+        .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, value)| value.trim().parse::<usize>().ok())
+        .unwrap_or(0)
+        .min(MAX_BODY);
+    while data.len() < header_end + content_length {
+        let len = stream.read(&mut buf).ok().filter(|len| *len > 0)?;
+        data.extend_from_slice(&buf[..len]);
+    }
+    let body = String::from_utf8_lossy(&data[header_end..(header_end + content_length).min(data.len())]).into_owned();
+    Some((head.lines().next().unwrap_or_default().to_string(), body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 只有一个飞行的 `/flights` 响应，`state` 为 current_state 中位置以外的字段
+    fn flight(aircraft_type: &str, state: &str) -> UploadData {
+        let json = format!(r#"{{"flights": [{{"id": "uss1-7f3a", "aircraft_type": "{}", "current_state": {{
+            "position": {{"lat": 31.2304, "lng": 121.4737, "alt": 120.4}}{}}}}}]}}"#, aircraft_type, state);
+        parse_flights(&json).unwrap().remove(0)
+    }
+
+    #[test]
+    fn test_flight_identity() {
+        let r = flight("Helicopter", "");
+        assert_eq!(r.source, RecordSource::Network);
+        assert_eq!((r.rid.as_str(), r.track_id.as_str()), ("uss1-7f3a", "netrid:uss1-7f3a"));
+        assert_eq!(r.ua_type, Some(2));
+        assert_eq!(flight("Spaceship", "").ua_type, None);
+    }
+
+    #[test]
+    fn test_position() {
+        let r = flight("Other", "");
+        assert_eq!((r.latitude, r.longitude, r.geometric_altitude), (312_304_000, 1_214_737_000, 120));
+    }
+
+    #[test]
+    fn test_track() {
+        let r = flight("Other", r#", "track": 270.0"#);
+        assert_eq!((r.track_direction, r.track_angle), (true, 90));
+        let r = flight("Other", r#", "track": 45.4"#);
+        assert_eq!((r.track_direction, r.track_angle), (false, 45));
+        // 361 表示未知
+        let r = flight("Other", r#", "track": 361.0"#);
+        assert_eq!((r.track_direction, r.track_angle), (false, 0));
+    }
+
+    #[test]
+    fn test_speed() {
+        // 10 m/s ≈ 19.4 节
+        let r = flight("Other", r#", "speed": 10.0"#);
+        assert_eq!((r.speed_multiplier, r.ground_speed), (false, 19));
+        // 80 m/s ≈ 155.5 节，超出 127 时使用速度乘数
+        let r = flight("Other", r#", "speed": 80.0"#);
+        assert_eq!((r.speed_multiplier, r.ground_speed), (true, 16));
+        // 255 表示未知
+        let r = flight("Other", r#", "speed": 255.0"#);
+        assert_eq!((r.speed_multiplier, r.ground_speed), (false, 0));
+    }
+
+    #[test]
+    fn test_vertical_speed() {
+        assert_eq!(flight("Other", r#", "vertical_speed": -1.5"#).vertical_speed, -2);
+        assert_eq!(flight("Other", r#", "vertical_speed": -200.0"#).vertical_speed, -127);
+        // 63 表示未知
+        assert_eq!(flight("Other", r#", "vertical_speed": 63.0"#).vertical_speed, 0);
+    }
+
+    #[test]
+    fn test_timestamp_is_tenths_past_the_hour() {
+        let r = flight("Other", r#", "timestamp": {"value": "2026-05-01T12:34:56.78Z", "format": "RFC3339"}"#);
+        assert_eq!(r.timestamp, (34 * 60 + 56) * 10 + 7);
+        let r = flight("Other", r#", "timestamp": {"value": "yesterday", "format": "RFC3339"}"#);
+        assert_eq!(r.timestamp, 0);
+    }
+
+    #[test]
+    fn test_flights_without_state_are_skipped() {
+        assert!(parse_flights(r#"{"flights": [{"id": "uss1-no-state"}]}"#).unwrap().is_empty());
+        assert!(parse_flights("{}").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_invalid_json() {
+        assert!(parse_flights("not json").is_err());
+        assert!(parse_flights(r#"{"flights": [{"current_state": null}]}"#).is_err());   // 缺少 id
+    }
+
+    #[test]
+    fn test_read_request_waits_for_body() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let client = thread::spawn(move || {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            stream.write_all(b"POST /flights HTTP/1.1\r\ncontent-length: 14\r\n\r\n{\"flig").unwrap();
+            thread::sleep(Duration::from_millis(50));
+            stream.write_all(b"hts\"").unwrap();
+            stream.write_all(b":[]}").unwrap();
+        });
+        let (mut stream, _) = listener.accept().unwrap();
+        let (request_line, body) = read_request(&mut stream).unwrap();
+        client.join().unwrap();
+        assert_eq!(request_line, "POST /flights HTTP/1.1");
+        assert_eq!(body, r#"{"flights":[]}"#);
+    }
+}
@@ -0,0 +1,965 @@
+use std::path::PathBuf;
+use std::sync::atomic::Ordering;
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use pnet::datalink::interfaces;
+use tracing::{debug, error, info, warn};
+
+#[cfg(feature = "mqtt")]
+use crate::alerts::Channel;
+use crate::alerts::{AlertEngine, AlertRouter};
+use crate::asterix::AsterixSender;
+use crate::auth_verify::AuthVerifier;
+use crate::authorization::AuthorizationClient;
+use crate::bearing::{AntennaBearing, BearingEstimator};
+use crate::bpf;
+#[cfg(feature = "bluetooth")]
+use crate::bluetooth::BleIngest;
+use crate::capture::{self, CaptureError, QuirkDetector};
+use crate::clock;
+use crate::cli::Options;
+use crate::config::{self, Config, InterfaceProfile, Tags};
+use crate::control::{OutputCommand, RuntimeControl};
+use crate::correlation::{CorrelationConfig, MacCorrelator};
+use crate::decode::{annotate_track, DecodeContext};
+use crate::dedup::{DuplicateFilter, UniqueFrame};
+use crate::digest::DigestNotifier;
+use crate::event_log::{DecodedEvent, EventWriter};
+use crate::failure_sink::FailureSink;
+use crate::fleet::Fleet;
+use crate::flight_export::TrackExporter;
+use crate::frame_ring::FrameRing;
+#[cfg(feature = "mesh")]
+use crate::geo::degrees;
+use crate::geofence::Geofence;
+use crate::health::HealthMonitor;
+use crate::id_collision::IdCollisionDetector;
+use crate::identity::IdentityCache;
+use crate::latency::LatencyMetrics;
+use crate::live_feed::LiveFeed;
+#[cfg(feature = "dashboard")]
+use crate::live_layer::LiveLayer;
+#[cfg(feature = "mesh")]
+use crate::localization::MultiSensorLocator;
+use crate::logging::DETECTION_TARGET;
+#[cfg(feature = "mesh")]
+use crate::mesh::Mesh;
+use crate::message::codes::UaType;
+use crate::message::DecodeOptions;
+#[cfg(feature = "mqtt")]
+use crate::mqtt::MqttPublisher;
+use crate::network_rid::NetworkIngest;
+use crate::pcap_writer::RotatingPcap;
+use crate::pcapng::{self, PcapngWriter};
+use crate::pipeline::{CapturedFrame, Decoded, Pipeline};
+use crate::position;
+use crate::privacy::{Feed, PrivacyConfig};
+use crate::quality::CounterContinuity;
+use crate::receiver;
+use crate::regdomain;
+use crate::rssi::RssiTracker;
+use crate::sbs::SbsServer;
+use crate::shedding::LoadShedder;
+use crate::shutdown::{self, ShutdownConfig};
+use crate::signing::SigningKey;
+use crate::sink::RecordSink;
+use crate::ssid_check::SsidChecker;
+use crate::stats::{FrameClass, FrameStats, SessionSummary};
+#[cfg(feature = "database")]
+use crate::storage::Store;
+use crate::throttle::UploadThrottle;
+use crate::tracker::{TrackEvent, Tracker};
+#[cfg(feature = "tui")]
+use crate::tui::Tui;
+use crate::units;
+use crate::upload::{UploadConfig, Uploader};
+use crate::upload_data::{Kinematics, UploadData};
+use crate::watchdog::Watchdog;
+use crate::wifi;
+use crate::wifi::hopper::Hopper;
+
+/// 解码后记录的去向：上传，以及可选的事件录制和数据库存储
+///
+/// 按需设置各输出字段后，把事件交给 [`emit`](Output::emit)，并定期调用 [`poll`](Output::poll)
+/// 处理控制请求、延迟发布和轨迹过期；退出前调用 [`shutdown`](Output::shutdown)。
+pub struct Output {
+    pub uploader: Uploader,
+    recorder: Option<EventWriter>,
+    record_path: Option<PathBuf>,
+    /// 机器可读的检测输出，见 `sink::OutputConfig`
+    pub record_sink: Option<Box<dyn RecordSink>>,
+    #[cfg(feature = "database")]
+    pub store: Option<Store>,
+    /// 跨重启保留的无人机身份摘要，打开数据库时启用
+    identities: Option<IdentityCache>,
+    #[cfg(feature = "database")]
+    identities_saved_at: Instant,
+    tags: Tags,
+    pub sensor_id: String,
+    pub sbs: Option<SbsServer>,
+    pub asterix: Option<AsterixSender>,
+    pub live_feed: Option<LiveFeed>,
+    #[cfg(feature = "dashboard")]
+    pub live_layer: Option<LiveLayer>,
+    #[cfg(feature = "tui")]
+    pub tui: Option<Tui>,
+    fleet: Fleet,
+    tracker: Tracker,
+    pub latency: LatencyMetrics,
+    privacy: PublicFeeds,
+    geofence: Option<Geofence>,
+    throttle: Option<UploadThrottle>,
+    alerts: AlertEngine,
+    alert_router: AlertRouter,
+    digest: Option<DigestNotifier>,
+    track_export: Option<TrackExporter>,
+    #[cfg(feature = "mesh")]
+    pub mesh: Option<Mesh>,
+    /// 汇聚节点上由多个接收站的观测估计无人机位置
+    #[cfg(feature = "mesh")]
+    pub locator: Option<MultiSensorLocator>,
+    #[cfg(feature = "mqtt")]
+    mqtt: Option<MqttPublisher>,
+    pub network: Option<NetworkIngest>,
+    #[cfg(feature = "bluetooth")]
+    pub ble: Option<BleIngest>,
+    authorization: Option<AuthorizationClient>,
+    auth_verifier: Option<AuthVerifier>,
+    pub pcapng: Option<PcapngWriter<std::io::BufWriter<std::fs::File>>>,
+    pub raw_capture: Option<RotatingPcap>,
+    pub frame_ring: Option<FrameRing>,
+    /// 心跳上报、`/healthz` 和 systemd 通知，抓包时启用
+    pub health: Option<HealthMonitor>,
+    config: Config,
+    summary: SessionSummary,
+}
+
+/// 各公开输出的模糊化队列
+#[derive(Default)]
+struct PublicFeeds {
+    upload: Feed,
+    sbs: Feed,
+    asterix: Feed,
+    live_feed: Feed,
+    #[cfg(feature = "dashboard")]
+    live_layer: Feed,
+}
+
+impl PublicFeeds {
+    pub fn new(config: &PrivacyConfig) -> Self {
+        Self {
+            upload: Feed::new(config.upload.clone()),
+            sbs: Feed::new(config.sbs.clone()),
+            asterix: Feed::new(config.asterix.clone()),
+            live_feed: Feed::new(config.live_feed.clone()),
+            #[cfg(feature = "dashboard")]
+            live_layer: Feed::new(config.live_layer.clone()),
+        }
+    }
+}
+
+impl Output {
+    pub fn new(record_path: Option<PathBuf>, tags: Tags, sensor_id: String, upload: UploadConfig,
+           signing: Option<SigningKey>) -> Self {
+        let uploader = Uploader::start(upload, sensor_id.clone(), signing);
+        let recorder = record_path.as_ref().and_then(|path| {
+            EventWriter::create(path)
+                .map_err(|e| error!("无法创建录制文件 {}: {}", path.display(), e))
+                .ok()
+        });
+        Self {
+            uploader, recorder, record_path, tags, sensor_id,
+            record_sink: None,
+            #[cfg(feature = "database")]
+            store: None,
+            identities: None,
+            #[cfg(feature = "database")]
+            identities_saved_at: Instant::now(),
+            sbs: None,
+            asterix: None,
+            live_feed: None,
+            #[cfg(feature = "dashboard")]
+            live_layer: None,
+            #[cfg(feature = "tui")]
+            tui: None,
+            fleet: Fleet::default(),
+            tracker: Tracker::default(),
+            latency: LatencyMetrics::default(),
+            privacy: PublicFeeds::default(),
+            geofence: None,
+            throttle: None,
+            alerts: AlertEngine::default(),
+            alert_router: AlertRouter::default(),
+            digest: None,
+            track_export: None,
+            #[cfg(feature = "mesh")]
+            mesh: None,
+            #[cfg(feature = "mesh")]
+            locator: None,
+            #[cfg(feature = "mqtt")]
+            mqtt: None,
+            network: None,
+            #[cfg(feature = "bluetooth")]
+            ble: None,
+            authorization: None,
+            auth_verifier: None,
+            pcapng: None,
+            raw_capture: None,
+            frame_ring: None,
+            health: None,
+            config: Config::default(),
+            summary: SessionSummary::default(),
+        }
+    }
+
+    /// 本次运行启用的输出，写入环境报告
+    pub fn sinks(&self) -> Vec<&'static str> {
+        let mut sinks = vec!["upload"];
+        if self.recorder.is_some() {
+            sinks.push("record");
+        }
+        if self.record_sink.is_some() {
+            sinks.push("ndjson");
+        }
+        #[cfg(feature = "database")]
+        if self.store.is_some() {
+            sinks.push("database");
+        }
+        #[cfg(feature = "mqtt")]
+        if self.mqtt.is_some() {
+            sinks.push("mqtt");
+        }
+        #[cfg(feature = "mesh")]
+        if self.mesh.is_some() {
+            sinks.push("mesh");
+        }
+        #[cfg(feature = "dashboard")]
+        if self.live_layer.is_some() {
+            sinks.push("dashboard");
+        }
+        for (name, enabled) in [
+            ("sbs", self.sbs.is_some()),
+            ("asterix", self.asterix.is_some()),
+            ("live_feed", self.live_feed.is_some()),
+            ("track_export", self.track_export.is_some()),
+            ("pcapng", self.pcapng.is_some()),
+            ("pcap", self.raw_capture.is_some()),
+            ("alert_webhook", self.config.alert_webhook.is_some()),
+            ("alert_command", self.config.alert_command.is_some()),
+        ] {
+            if enabled {
+                sinks.push(name);
+            }
+        }
+        sinks
+    }
+
+    /// 执行控制接口请求的输出操作
+    pub fn apply(&mut self, command: OutputCommand) {
+        match command {
+            OutputCommand::Flush => {
+                if let Some(recorder) = self.recorder.as_mut()
+                    && let Err(e) = recorder.flush()
+                {
+                    error!("写出录制文件失败: {}", e);
+                }
+                if let Some(pcapng) = self.pcapng.as_mut()
+                    && let Err(e) = pcapng.flush()
+                {
+                    error!("写出 pcapng 文件失败: {}", e);
+                }
+                if let Some(raw) = self.raw_capture.as_mut()
+                    && let Err(e) = raw.flush()
+                {
+                    error!("写出原始帧存证失败: {}", e);
+                }
+                if let Some(sink) = self.record_sink.as_mut()
+                    && let Err(e) = sink.flush()
+                {
+                    error!("写出检测记录失败: {}", e);
+                }
+            }
+            OutputCommand::Rotate => {
+                if let Some(raw) = self.raw_capture.as_mut()
+                    && let Err(e) = raw.rotate()
+                {
+                    error!("轮转原始帧存证失败: {}", e);
+                }
+                let Some(path) = self.record_path.clone() else { return };
+                self.recorder = None;
+                let rotated = path.with_extension(format!("{}.cbor", Utc::now().format("%Y%m%dT%H%M%S")));
+                if let Err(e) = std::fs::rename(&path, &rotated) {
+                    error!("轮转录制文件失败: {}", e);
+                }
+                self.recorder = EventWriter::create(&path)
+                    .map_err(|e| error!("无法创建录制文件 {}: {}", path.display(), e))
+                    .ok();
+                info!("recording rotated to {}", rotated.display());
+            }
+        }
+    }
+
+    /// 应用配置中可热更新的部分，未变化的项保留现有状态（告警冷却、延迟队列等）
+    pub fn reload(&mut self, config: Config) {
+        if config.tags != self.config.tags {
+            self.tags = config.tags.clone();
+        }
+        if config.fleet != self.config.fleet {
+            self.fleet = match &config.fleet {
+                Some(path) => Fleet::load(path)
+                    .inspect(|fleet| info!("loaded {} fleet annotations from {}", fleet.len(), path.display()))
+                    .map_err(|e| error!("无法加载机队标注表 {}: {}", path.display(), e))
+                    .unwrap_or_default(),
+                None => Fleet::default(),
+            };
+        }
+        if config.tracker != self.config.tracker {
+            self.tracker.set_config(config.tracker);
+        }
+        if config.throttle != self.config.throttle {
+            self.throttle = config.throttle.map(UploadThrottle::new);
+        }
+        if config.privacy != self.config.privacy {
+            self.privacy = PublicFeeds::new(&config.privacy);
+        }
+        if config.geofence != self.config.geofence {
+            self.geofence = match config.geofence.as_ref().map(Geofence::load).transpose() {
+                Ok(geofence) => {
+                    if let Some(geofence) = &geofence {
+                        info!("geofence with {} zones", geofence.len());
+                    }
+                    geofence
+                }
+                Err(e) => {
+                    error!("无法加载电子围栏: {}", e);
+                    None
+                }
+            };
+        }
+        if config.alerts != self.config.alerts {
+            let cooldowns = self.alerts.cooldowns();
+            self.alerts = AlertEngine::new(config.alerts.clone());
+            self.alerts.restore_cooldowns(cooldowns);
+        }
+        if config.alert_webhook != self.config.alert_webhook || config.alert_command != self.config.alert_command {
+            self.alert_router = AlertRouter::new(config.alert_webhook.clone(), config.alert_command.clone());
+        }
+        if config.digest != self.config.digest {
+            self.digest = config.digest.as_ref().map(DigestNotifier::new);
+        }
+        if config.track_export != self.config.track_export {
+            self.track_export = config.track_export.clone().map(TrackExporter::new);
+        }
+        if config.authorization != self.config.authorization {
+            self.authorization = config.authorization.clone().map(AuthorizationClient::new);
+        }
+        if config.auth_trust_store != self.config.auth_trust_store {
+            self.auth_verifier = config.auth_trust_store.as_ref().and_then(|path| {
+                AuthVerifier::load(path)
+                    .inspect(|verifier| info!("loaded {} trusted operator keys from {}", verifier.len(), path.display()))
+                    .map_err(|e| error!("无法加载认证信任库 {}: {}", path.display(), e))
+                    .ok()
+            });
+        }
+        if config.mqtt != self.config.mqtt {
+            #[cfg(feature = "mqtt")]
+            {
+                self.mqtt = config.mqtt.as_ref().and_then(|mqtt| {
+                    MqttPublisher::start(mqtt, &self.sensor_id)
+                        .map_err(|e| error!("无法启动 MQTT 发布: {}", e))
+                        .ok()
+                });
+            }
+            #[cfg(not(feature = "mqtt"))]
+            if config.mqtt.is_some() {
+                error!("此构建未启用 mqtt 特性，忽略 [mqtt] 配置");
+            }
+        }
+        self.config = config;
+    }
+
+    /// 处理控制接口积压的请求
+    pub fn poll(&mut self, control: &RuntimeControl) {
+        for command in control.take_requests() {
+            self.apply(command);
+        }
+        if let Some(config) = control.take_config() {
+            for field in self.config.restart_required(&config) {
+                warn!("configuration field {} changed, restart required to apply it", field);
+            }
+            let mut effective = config.effective(&self.sensor_id, units::output_units());
+            config::redact(&mut effective);
+            control.set_config(effective);
+            if config.log_level != self.config.log_level {
+                control.apply_log_level(config.log_level.as_deref());
+            }
+            self.reload(config);
+            info!("configuration reloaded");
+        }
+        #[cfg(feature = "mesh")]
+        for event in self.mesh.as_ref().map(Mesh::take_ingested).unwrap_or_default() {
+            self.process(event);
+        }
+        for event in self.network.as_ref().map(NetworkIngest::take_ingested).unwrap_or_default() {
+            self.emit(event);
+        }
+        #[cfg(feature = "bluetooth")]
+        for event in self.ble.as_ref().map(BleIngest::take_ingested).unwrap_or_default() {
+            self.emit(event);
+        }
+        for expired in self.tracker.expire(clock::now_ms().0) {
+            if let TrackEvent::Expired { uas_id, .. } = expired {
+                info!("drone {} left, {} still tracked", uas_id, self.tracker.len());
+                if let Some(throttle) = self.throttle.as_mut() {
+                    throttle.forget(&uas_id);
+                }
+                if let Some(feed) = &self.live_feed {
+                    feed.expire(&uas_id);
+                }
+            }
+        }
+        #[cfg(feature = "tui")]
+        if let Some(tui) = self.tui.as_mut() {
+            tui.update(self.tracker.drones());
+        }
+        self.publish(None, true);
+        if let Some(digest) = self.digest.as_mut() {
+            digest.poll(clock::now_ms().0);
+        }
+        if let Some(exporter) = self.track_export.as_mut() {
+            exporter.poll(clock::now_ms().0);
+        }
+        #[cfg(feature = "database")]
+        if self.identities_saved_at.elapsed() >= IDENTITY_SAVE_INTERVAL {
+            self.save_identities();
+        }
+        if let Some(summary) = self.latency.maybe_report() {
+            control.set_latency(summary);
+        }
+        if let Some(health) = self.health.as_mut() {
+            health.set_queue("upload_batches", self.uploader.queued());
+            #[cfg_attr(not(feature = "mqtt"), expect(unused_variables))]
+            let report = health.poll();
+            #[cfg(feature = "mqtt")]
+            if let (Some(report), Some(mqtt)) = (&report, &self.mqtt) {
+                mqtt.publish_heartbeat(report);
+            }
+        }
+    }
+
+    /// 载入数据库中保存的无人机身份摘要和告警冷却状态
+    #[cfg(feature = "database")]
+    pub fn restore_identities(&mut self) {
+        let Some(store) = &self.store else { return };
+        let mut identities = IdentityCache::default();
+        match store.load_identities() {
+            Ok(saved) => {
+                if let Some(digest) = self.digest.as_mut() {
+                    digest.restore_seen(saved.iter().map(|i| (i.uas_id.clone(), i.last_seen_ms)));
+                }
+                identities.restore(saved);
+                info!("restored {} drone identities from the database", identities.len());
+            }
+            Err(e) => error!("读取无人机身份失败: {}", e),
+        }
+        match store.load_cooldowns() {
+            Ok(cooldowns) => self.alerts.restore_cooldowns(cooldowns),
+            Err(e) => error!("读取告警冷却状态失败: {}", e),
+        }
+        self.identities = Some(identities);
+    }
+
+    /// 把已结束的航段、有变化的身份摘要和告警冷却状态写入数据库
+    #[cfg(feature = "database")]
+    fn save_identities(&mut self) {
+        self.identities_saved_at = Instant::now();
+        if let Some(store) = self.store.as_mut()
+            && let Err(e) = store.finish_segments(clock::now_ms().0)
+        {
+            error!("写入航段失败: {}", e);
+        }
+        let (Some(store), Some(identities)) = (self.store.as_mut(), self.identities.as_mut()) else { return };
+        if let Err(e) = store.save_identities(&identities.take_dirty()) {
+            error!("保存无人机身份失败: {}", e);
+        }
+        if let Err(e) = store.save_cooldowns(&self.alerts.cooldowns()) {
+            error!("保存告警冷却状态失败: {}", e);
+        }
+    }
+
+    /// 把收到的原始帧写入 pcapng（解出 Remote ID 的帧附带解码摘要）、轮转的 pcap 存证和告警取证缓冲
+    fn capture_frame(&mut self, frame: &[u8], records: &[UploadData]) {
+        let timestamp_us = clock::now_ms().0 * 1000;
+        if let Some(ring) = self.frame_ring.as_mut() {
+            ring.push(timestamp_us, frame);
+        }
+        if let Some(raw) = self.raw_capture.as_mut()
+            && let Err(e) = raw.write(timestamp_us, frame, !records.is_empty())
+        {
+            error!("写入原始帧存证失败: {}", e);
+        }
+        let Some(pcapng) = self.pcapng.as_mut() else { return };
+        let comment = (!records.is_empty())
+            .then(|| records.iter().map(pcapng::summary).collect::<Vec<_>>().join("; "));
+        if let Err(e) = pcapng.write(timestamp_us, frame, comment.as_deref()) {
+            error!("写入 pcapng 失败: {}", e);
+        }
+    }
+
+    /// 本机解码的事件：组网时先转发给汇聚节点，再进入本地输出
+    pub fn emit(&mut self, mut event: DecodedEvent) {
+        // 回放的录制文件保留原有标签
+        if event.record.tags.is_empty() {
+            event.record.tags = self.tags.clone();
+        }
+        event.assign_id(&self.sensor_id);
+        // 回放的录制文件保留录制时的接收站位置
+        if event.record.receiver.is_none() {
+            event.record.receiver = receiver::current();
+        }
+        self.summary.observe(&event.record);
+        #[cfg(feature = "mesh")]
+        if let Some(mesh) = &self.mesh {
+            mesh.forward(&event);
+        }
+        self.process(event);
+    }
+
+    /// 本机或其他接收站的事件进入本地输出
+    fn process(&mut self, mut event: DecodedEvent) {
+        // 只有位置向量的报文按重启前记录的别名归到对应的 UAS ID
+        if event.record.rid.is_empty()
+            && let Some(uas_id) = self.identities.as_ref().and_then(|i| i.resolve(&event.record.track_id))
+        {
+            event.record.rid = uas_id.to_string();
+        }
+        let change = self.tracker.observe(&mut event);
+        match &change {
+            Some(TrackEvent::New { uas_id }) => {
+                if let Some(identity) = self.identities.as_ref().and_then(|i| i.get(uas_id)) {
+                    self.tracker.backdate(uas_id, identity.first_seen_ms);
+                }
+                info!("new drone {}, {} tracked", uas_id, self.tracker.len());
+            }
+            Some(TrackEvent::Changed { uas_id, fields }) => debug!("drone {} changed: {:?}", uas_id, fields),
+            _ => {}
+        }
+        // 网络 Remote ID、其他接收站和回放的记录同样按当前规则判断位置状态
+        event.record.position_status = position::classify(&event.record);
+        // 位置和控制站位置常在不同报文中，按合并后的状态计算
+        event.record.operator_distance_m = position::operator_distance_m(&event.record);
+        // 上传、MQTT 等输出只带记录本身，接收时间随记录发出
+        event.record.received_at_ms = Some(event.received_at_ms);
+        // 按物理单位换算的值随合并后的字段一起输出
+        event.record.kinematics = position::has_coordinates(&event.record)
+            .then(|| Kinematics::new(&event.record.position_vector(), event.received_at_ms));
+        event.record.ua_type_name = event.record.ua_type.map(UaType::from_code);
+        // 组网时为转发该记录的接收站的位置
+        if let Some((distance, bearing)) = receiver::relative_position(&event.record) {
+            event.record.receiver_distance_m = Some(distance);
+            event.record.receiver_bearing_deg = Some(bearing);
+        }
+        // 汇聚节点上两个及以上接收站同时收到时粗略定位，与广播位置比较得到仿冒评分
+        #[cfg(feature = "mesh")]
+        if self.mesh.as_ref().is_some_and(Mesh::is_aggregator)
+            && let Some(estimate) = self.locator.as_mut().and_then(|l| l.observe(&event.record, event.received_at_ms))
+        {
+            event.record.spoof_score = position::has_coordinates(&event.record)
+                .then(|| estimate.spoof_score(degrees(event.record.latitude), degrees(event.record.longitude)));
+            event.record.localized = Some(estimate);
+        }
+        if !self.config.position.keeps(event.record.position_status) {
+            debug!("dropping record from {} with position status {:?}", event.record.track_id, event.record.position_status);
+            return;
+        }
+        if let Some(geofence) = &self.geofence
+            && !geofence.apply(&mut event.record)
+        {
+            debug!("dropping record from {} by geofence", event.record.track_id);
+            return;
+        }
+        if event.record.annotation.is_none() {
+            event.record.annotation = self.fleet.lookup(&event.record.rid).cloned();
+        }
+        if let Some(authorization) = &self.authorization
+            && event.record.authorization.is_none()
+            && !event.record.rid.is_empty()
+        {
+            event.record.authorization = Some(authorization.status(&event.record.rid));
+        }
+        if let Some(verifier) = self.auth_verifier.as_mut() {
+            event.record.auth_verification = Some(verifier.observe(&event.record, event.received_at_ms));
+        }
+        if let Some(identities) = self.identities.as_mut() {
+            identities.observe(&event);
+        }
+        info!(target: DETECTION_TARGET, "{}", pcapng::summary(&event.record));
+        event.assign_id(&self.sensor_id);
+        for alert in self.alerts.observe(&event) {
+            if let Some(ring) = &self.frame_ring {
+                match ring.dump(&alert) {
+                    Ok(Some(path)) => info!("alert {}: {} recent frames written to {}", alert.rule, ring.len(), path.display()),
+                    Ok(None) => {}
+                    Err(e) => error!("写出告警取证帧失败: {}", e),
+                }
+            }
+            #[cfg(feature = "mqtt")]
+            if let Some(mqtt) = self.mqtt.as_ref().filter(|_| alert.notifies(Channel::Mqtt)) {
+                mqtt.publish_alert(&alert);
+            }
+            self.alert_router.route(alert);
+        }
+        if let Some(digest) = self.digest.as_mut() {
+            digest.observe(&event);
+        }
+        if let Some(exporter) = self.track_export.as_mut() {
+            exporter.observe(&event);
+        }
+        let received = event.received_at_ms;
+        self.latency.observe_broadcast(&event);
+        if let Some(recorder) = self.recorder.as_mut() {
+            if let Err(e) = recorder.write(&event) {
+                error!("录制事件失败: {}", e);
+            }
+            self.latency.sink_done("record", received);
+        }
+        if let Some(sink) = self.record_sink.as_mut() {
+            if let Err(e) = sink.write(&event) {
+                error!("写出检测记录失败: {}", e);
+            }
+            self.latency.sink_done("output", received);
+        }
+        #[cfg(feature = "database")]
+        if let Some(store) = self.store.as_mut() {
+            if let Err(e) = store.insert(&event, event.record.operator_id.as_deref()) {
+                error!("写入数据库失败: {}", e);
+            }
+            self.latency.sink_done("store", received);
+        }
+        let upload = (change.is_some() || !self.tracker.config().upload_changes_only)
+            && self.throttle.as_mut().is_none_or(|throttle| throttle.admit(&event));
+        self.publish(Some(&event), upload);
+    }
+
+    /// 经各自的模糊化设置发布到公开输出；`event` 为空时只发布延迟到期的事件，
+    /// `upload` 为 false 时该事件不上传（状态未变化的重复记录）
+    fn publish(&mut self, event: Option<&DecodedEvent>, upload: bool) {
+        let now = clock::now_ms().0;
+        if let Some(sbs) = self.sbs.as_mut() {
+            for event in self.privacy.sbs.release(event, now) {
+                sbs.publish(&event);
+                self.latency.sink_done("sbs", event.received_at_ms);
+            }
+        }
+        if let Some(asterix) = self.asterix.as_mut() {
+            for event in self.privacy.asterix.release(event, now) {
+                asterix.publish(&event);
+                self.latency.sink_done("asterix", event.received_at_ms);
+            }
+        }
+        if let Some(feed) = &self.live_feed {
+            for event in self.privacy.live_feed.release(event, now) {
+                feed.publish(&event);
+                self.latency.sink_done("live_feed", event.received_at_ms);
+            }
+        }
+        #[cfg(feature = "dashboard")]
+        if let Some(layer) = &self.live_layer {
+            for event in self.privacy.live_layer.release(event, now) {
+                layer.update(&event);
+                self.latency.sink_done("live_layer", event.received_at_ms);
+            }
+        }
+        // 组网时只由汇聚节点上传
+        #[cfg(feature = "mesh")]
+        let uploads = self.mesh.as_ref().is_none_or(Mesh::is_aggregator);
+        #[cfg(not(feature = "mesh"))]
+        let uploads = true;
+        for event in self.privacy.upload.release(event.filter(|_| upload), now) {
+            if !uploads {
+                continue;
+            }
+            #[cfg(feature = "mqtt")]
+            if let Some(mqtt) = &self.mqtt {
+                mqtt.publish(&event);
+                self.latency.sink_done("mqtt", event.received_at_ms);
+            }
+            self.latency.sink_done("upload", event.received_at_ms);
+            self.uploader.send(event.into_owned());
+        }
+    }
+
+    /// 有序关闭：处理已收到的事件，结束跟踪中的无人机，写出文件输出，
+    /// 按超时等待网络输出送出剩余数据，最后关闭数据库
+    pub fn shutdown(mut self, control: &RuntimeControl, config: ShutdownConfig) {
+        #[cfg(feature = "tui")]
+        if let Some(tui) = self.tui.take() {
+            tui.close();
+        }
+        if let Some(health) = &self.health {
+            health.stopping();
+        }
+        self.poll(control);
+        let finished = self.tracker.finish();
+        for event in &finished {
+            if let TrackEvent::Expired { uas_id, last_seen_ms } = event {
+                debug!("drone {} tracking ended at shutdown, last seen at {}", uas_id, last_seen_ms);
+            }
+        }
+        info!("shutdown: {} tracked drones finished", finished.len());
+        self.apply(OutputCommand::Flush);
+        #[cfg(feature = "database")]
+        self.save_identities();
+        let timeout = config.sink_timeout();
+        if !self.uploader.close(timeout) {
+            warn!("uploads not finished within {:?} at shutdown", timeout);
+        }
+        #[cfg(feature = "mqtt")]
+        if let Some(mqtt) = self.mqtt.take()
+            && !mqtt.close(timeout)
+        {
+            warn!("mqtt publishes not finished within {:?} at shutdown", timeout);
+        }
+        if let Some(throttle) = self.throttle.as_ref().filter(|t| t.suppressed() > 0) {
+            info!("{} near-identical records not uploaded", throttle.suppressed());
+        }
+        if let Some(exporter) = &self.track_export
+            && let Err(e) = exporter.write()
+        {
+            error!("写出航迹文件失败: {}", e);
+        }
+        self.summary.report();
+        #[cfg(feature = "database")]
+        if let Some(store) = self.store.take()
+            && let Err(e) = store.close()
+        {
+            error!("关闭数据库失败: {}", e);
+        }
+        info!("shutdown complete");
+    }
+}
+
+/// 身份摘要和告警冷却状态写入数据库的间隔
+#[cfg(feature = "database")]
+const IDENTITY_SAVE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// 读取网卡支持的信道，按管制域检查信道计划，返回可用的信道
+fn channel_plan(interface: &str, plan: &[u8], config: &Config) -> Vec<u8> {
+    let supported = match wifi::supported_channels(interface) {
+        Ok(channels) => Some(channels),
+        // 只有 Linux 能读取网卡的可用信道，其它系统只按管制域检查
+        Err(e) if e.kind() == std::io::ErrorKind::Unsupported => None,
+        Err(e) => {
+            warn!("cannot read channel capabilities of {}: {}", interface, e);
+            None
+        }
+    };
+    let (plan, warnings) = regdomain::validate_plan(plan, config.regulatory_domain, supported.as_deref());
+    for warning in warnings {
+        warn!("{}: {}", interface, warning);
+    }
+    plan
+}
+
+/// 抓包读超时：没有帧时也按此间隔处理控制请求和退出信号
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// 按命令行选项和配置创建实时抓包与离线读取共用的解码上下文
+pub fn decode_context(options: &Options, config: &Config) -> DecodeContext {
+    DecodeContext {
+        options: DecodeOptions { lossy_utf8: options.lossy_uas_id, profile: config.format.profile },
+        stats: FrameStats::default().with_labels(config.tags.labels()),
+        failures: options.dump_failures.then(|| FailureSink::new(
+            "logs",
+            FailureSink::DEFAULT_MAX_PER_MINUTE,
+            FailureSink::DEFAULT_MAX_BYTES_PER_FILE,
+        )),
+        collisions: IdCollisionDetector::default(),
+        ssid_check: config.ssid_check.map(SsidChecker::new),
+        correlator: options.correlate_macs.then(|| MacCorrelator::new(CorrelationConfig::default())),
+        rssi: RssiTracker::new(options.path_loss.unwrap_or(config.path_loss), Duration::from_secs(30)),
+        bearing: antenna_bearing(options).map(BearingEstimator::new),
+        deep_scan: options.deep_scan,
+        ignored_ouis: config.ignore_ouis.clone(),
+        prefilter: config.prefilter,
+        shedder: config.shedding.map(LoadShedder::new),
+        format: config.format.clone(),
+        counters: CounterContinuity::default(),
+        defer_tracking: false,
+    }
+}
+
+/// 启用定向天线测向时启动方位输入
+fn antenna_bearing(options: &Options) -> Option<AntennaBearing> {
+    if options.bearing_input.is_none() && options.bearing_listen.is_none() {
+        return None;
+    }
+    let antenna = AntennaBearing::default();
+    if let Some(path) = &options.bearing_input {
+        antenna.spawn_line_reader(path.clone());
+    }
+    if let Some(addr) = &options.bearing_listen {
+        antenna.spawn_http_listener(addr.clone());
+    }
+    Some(antenna)
+}
+
+/// 解码线程使用的上下文：只做无状态的解析，轨迹关联、测距等由主线程的上下文按帧顺序处理
+fn worker_context(options: &Options, config: &Config, workers: usize) -> DecodeContext {
+    DecodeContext {
+        options: DecodeOptions { lossy_utf8: options.lossy_uas_id, profile: config.format.profile },
+        // 各解码线程写同一个样本文件，限速按线程数均分
+        failures: options.dump_failures.then(|| FailureSink::new(
+            "logs",
+            (FailureSink::DEFAULT_MAX_PER_MINUTE / workers as u32).max(1),
+            FailureSink::DEFAULT_MAX_BYTES_PER_FILE / workers as u64,
+        )),
+        ssid_check: config.ssid_check.map(SsidChecker::new),
+        deep_scan: options.deep_scan,
+        ignored_ouis: config.ignore_ouis.clone(),
+        prefilter: config.prefilter,
+        shedder: config.shedding.map(LoadShedder::new),
+        format: config.format.clone(),
+        defer_tracking: true,
+        ..Default::default()
+    }
+}
+
+/// 在一个或多个网卡上同时抓包，所有网卡的帧进入同一解码流程
+///
+/// 每个网卡一个抓包线程，只把帧复制进 [`Pipeline`] 的有界队列，由解码线程解析；
+/// 配置了多个信道的网卡由 [`Hopper`] 按停留时间轮换信道。
+pub fn capture_profiles(profiles: &[InterfaceProfile], options: &Options, config: &Config, ctx: &mut DecodeContext,
+                    output: &mut Output, control: &Arc<RuntimeControl>, watchdog: Option<&Arc<Watchdog>>) {
+    let drain = config.shutdown.drain();
+    let mut interface_stats: Vec<FrameStats> = profiles.iter().map(|p| ctx.stats.for_interface(&p.name)).collect();
+    let workers = config.pipeline.worker_count();
+    let contexts = (0..workers).map(|_| worker_context(options, config, workers)).collect();
+    let pipeline = Pipeline::start(config.pipeline, contexts, profiles.iter().map(|p| p.deep_scan).collect());
+    info!("decoding with {} worker threads", workers);
+    if config.prefilter.kernel_filter && !capture::KERNEL_FILTER {
+        warn!("kernel packet filter not supported by this build, filtering in user space");
+    }
+    let available = interfaces();
+    for (index, profile) in profiles.iter().enumerate() {
+        let Some(interface) = available.iter().find(|i| i.name == profile.name).cloned() else {
+            warn!("interface {} not found, profile skipped", profile.name);
+            continue;
+        };
+        let plan = channel_plan(&profile.name, &profile.channel_plan(), config);
+        let fixed_channel = (plan.len() == 1).then(|| plan[0]);
+        let hopper = Hopper::start(&profile.name, plan, Duration::from_millis(profile.dwell_ms), control.clone());
+        let queue = pipeline.queue.clone();
+        let control = control.clone();
+        let heartbeat = watchdog.map(|w| w.register(&interface.name, fixed_channel));
+        let retry = config.capture;
+        // 深度扫描需要数据帧，这类网卡不挂内核过滤
+        let filter = (config.prefilter.kernel_filter && !profile.deep_scan)
+            .then(|| bpf::remote_id_filter(config.prefilter.min_packet_len));
+        thread::spawn(move || {
+            let result = capture::supervise(&interface.name, &retry, || {
+                // 网卡重新枚举后序号会变化，每次重新打开前按名称查找
+                let interface = capture::find_interface(&interface.name)?;
+                let mut rx = capture::open_filtered(&interface, Some(POLL_INTERVAL), filter.as_deref())?;
+                let mut quirks = QuirkDetector::default();
+                info!("Capturing on {}", interface.name);
+                while !shutdown::requested() {
+                    let packet = match rx.next_frame() {
+                        Ok(packet) => packet,
+                        Err(e) if e.kind() == std::io::ErrorKind::TimedOut => continue,
+                        Err(e) => return Err(CaptureError::from_io(&interface.name, e)),
+                    };
+                    if let Some(heartbeat) = &heartbeat {
+                        heartbeat.beat();
+                    }
+                    if let Some(found) = quirks.check(&interface.name, packet) {
+                        control.set_radiotap(&interface.name, found);
+                    }
+                    let frame = CapturedFrame { interface: index, channel: hopper.current(), data: packet.to_vec() };
+                    if !queue.push(frame) {
+                        return Ok(());
+                    }
+                }
+                Ok(())
+            });
+            if let Err(e) = result {
+                error!("{} 停止抓包: {}", interface.name, e);
+            }
+        });
+    }
+    let Pipeline { queue, decoded, dropped, backlog } = pipeline;
+    drop(queue);
+    let mut dedup = DuplicateFilter::new(config.dedup, profiles.iter().map(|p| p.name.clone()).collect());
+
+    // 收到退出信号后抓包线程停止，已排队的帧在 drain 时间内继续解码
+    let mut drain_until = None;
+    loop {
+        if let Some(health) = output.health.as_mut() {
+            health.set_queue("decode_backlog", backlog.load(Ordering::Relaxed));
+        }
+        if shutdown::requested() && Instant::now() >= *drain_until.get_or_insert_with(|| Instant::now() + drain) {
+            let pending = decoded.try_iter().filter(|d| matches!(d, Decoded::Frame { .. })).count();
+            if pending > 0 {
+                warn!("{} captured frames not processed before the drain timeout", pending);
+            }
+            break;
+        }
+        let (frame, mut records) = match decoded.recv_timeout(POLL_INTERVAL) {
+            Ok(Decoded::Frame { frame, records }) => (frame, records),
+            Ok(Decoded::Stats { interface, stats }) => {
+                // 帧分类计数按网卡分开汇总
+                let Some(totals) = interface_stats.get_mut(interface) else { continue };
+                let mut stats = *stats;
+                stats.record_n(FrameClass::QueueFull, dropped[interface].swap(0, Ordering::Relaxed));
+                output.summary.add_frames(&stats);
+                totals.merge(&stats);
+                totals.maybe_report();
+                continue;
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) => {
+                for unique in dedup.take_due(Instant::now()) {
+                    output_frame(unique, profiles, ctx, output);
+                }
+                output.poll(control);
+                continue;
+            }
+            Err(mpsc::RecvTimeoutError::Disconnected) => break,
+        };
+        output.poll(control);
+        if control.is_paused() {
+            continue;
+        }
+        for record in &mut records {
+            record.channel = record.channel.or(frame.channel);
+        }
+        dedup.push(frame, records, Instant::now());
+        for unique in dedup.take_due(Instant::now()) {
+            output_frame(unique, profiles, ctx, output);
+        }
+    }
+    for unique in dedup.flush() {
+        output_frame(unique, profiles, ctx, output);
+    }
+    if dedup.duplicates() > 0 {
+        info!("{} duplicate frames from overlapping interfaces suppressed", dedup.duplicates());
+    }
+    output.summary.add_duplicates(dedup.duplicates());
+}
+
+/// 去重后的一帧：轨迹关联后写入抓包文件，按所在网卡的信号门限输出记录
+fn output_frame(unique: UniqueFrame, profiles: &[InterfaceProfile], ctx: &mut DecodeContext, output: &mut Output) {
+    let UniqueFrame { frame, mut records } = unique;
+    for record in &mut records {
+        annotate_track(record, ctx);
+    }
+    output.capture_frame(&frame.data, &records);
+    let profile = &profiles[frame.interface];
+    for record in records {
+        if profile.accepts(record.rssi) {
+            output.emit(DecodedEvent::now(record));
+        }
+    }
+}
@@ -0,0 +1,243 @@
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::DateTime;
+use tracing::info;
+
+use crate::import::LINKTYPE_RADIOTAP;
+
+const SNAPLEN: u32 = 65535;
+const FILE_HEADER_LEN: u64 = 24;
+const RECORD_HEADER_LEN: u64 = 16;
+
+/// 经典 pcap 文件写入器（radiotap 链路，微秒时间戳，小端序）
+pub struct PcapWriter<W: Write> {
+    out: W,
+    written: u64,
+}
+
+impl<W: Write> PcapWriter<W> {
+    pub fn new(mut out: W) -> io::Result<Self> {
+        out.write_all(&0xa1b2_c3d4u32.to_le_bytes())?;
+        out.write_all(&2u16.to_le_bytes())?;
+        out.write_all(&4u16.to_le_bytes())?;
+        out.write_all(&0i32.to_le_bytes())?;      // 时区修正
+        out.write_all(&0u32.to_le_bytes())?;      // 时间戳精度
+        out.write_all(&SNAPLEN.to_le_bytes())?;
+        out.write_all(&LINKTYPE_RADIOTAP.to_le_bytes())?;
+        Ok(Self { out, written: FILE_HEADER_LEN })
+    }
+
+    /// 写入一帧，`timestamp_us` 为 Unix 微秒
+    pub fn write(&mut self, timestamp_us: i64, frame: &[u8]) -> io::Result<()> {
+        let timestamp = timestamp_us.max(0);
+        let captured = &frame[..frame.len().min(SNAPLEN as usize)];
+        self.out.write_all(&((timestamp / 1_000_000) as u32).to_le_bytes())?;
+        self.out.write_all(&((timestamp % 1_000_000) as u32).to_le_bytes())?;
+        self.out.write_all(&(captured.len() as u32).to_le_bytes())?;
+        self.out.write_all(&(frame.len() as u32).to_le_bytes())?;
+        self.out.write_all(captured)?;
+        self.written += RECORD_HEADER_LEN + captured.len() as u64;
+        Ok(())
+    }
+
+    /// 已写入的字节数（含文件头）
+    pub fn written(&self) -> u64 {
+        self.written
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+/// 原始帧存证：写入目录下按时间命名的 pcap 文件，超过大小或时长后轮转到新文件
+///
+/// 文件名为 `capture-<首帧时间>-<序号>.pcap`，首帧时间为 UTC。
+/// `rid_only` 时只保存解出 Remote ID 的帧。
+pub struct RotatingPcap {
+    dir: PathBuf,
+    max_bytes: Option<u64>,
+    max_age: Option<Duration>,
+    rid_only: bool,
+    sequence: u32,
+    /// 当前文件及其首帧时间 (Unix 微秒)
+    current: Option<(PcapWriter<BufWriter<File>>, i64)>,
+}
+
+impl RotatingPcap {
+    pub fn new<P: AsRef<Path>>(dir: P, max_bytes: Option<u64>, max_age: Option<Duration>, rid_only: bool)
+                               -> io::Result<Self> {
+        fs::create_dir_all(dir.as_ref())?;
+        Ok(Self { dir: dir.as_ref().to_path_buf(), max_bytes, max_age, rid_only, sequence: 0, current: None })
+    }
+
+    /// 写入一帧；`has_remote_id` 表示该帧是否解出了 Remote ID
+    pub fn write(&mut self, timestamp_us: i64, frame: &[u8], has_remote_id: bool) -> io::Result<()> {
+        if self.rid_only && !has_remote_id {
+            return Ok(());
+        }
+        if let Some((writer, opened_us)) = &self.current {
+            let full = self.max_bytes.is_some_and(|max| writer.written() + RECORD_HEADER_LEN + frame.len() as u64 > max);
+            let expired = self.max_age.is_some_and(|age| timestamp_us - opened_us >= age.as_micros() as i64);
+            if full || expired {
+                self.rotate()?;
+            }
+        }
+        if self.current.is_none() {
+            self.current = Some((self.open(timestamp_us)?, timestamp_us));
+        }
+        match self.current.as_mut() {
+            Some((writer, _)) => writer.write(timestamp_us, frame),
+            None => Ok(()),
+        }
+    }
+
+    /// 关闭当前文件，下一帧写入新文件
+    pub fn rotate(&mut self) -> io::Result<()> {
+        match self.current.take() {
+            Some((mut writer, _)) => writer.flush(),
+            None => Ok(()),
+        }
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        match self.current.as_mut() {
+            Some((writer, _)) => writer.flush(),
+            None => Ok(()),
+        }
+    }
+
+    fn open(&mut self, timestamp_us: i64) -> io::Result<PcapWriter<BufWriter<File>>> {
+        let time = DateTime::from_timestamp_micros(timestamp_us).unwrap_or_default();
+        let path = self.dir.join(format!("capture-{}-{:04}.pcap", time.format("%Y%m%dT%H%M%S"), self.sequence));
+        self.sequence += 1;
+        info!("writing raw frames to {}", path.display());
+        PcapWriter::new(BufWriter::new(File::create(&path)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mapped_pcap::MappedPcap;
+
+    const START_US: i64 = 1_748_764_800_000_000;
+
+    /// 每个测试使用独立的空目录
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("wifi-capture-pcap-rotation-test-{}", name));
+        fs::remove_dir_all(&dir).ok();
+        dir
+    }
+
+    /// 目录中按名称排序的文件
+    fn files(dir: &Path) -> Vec<PathBuf> {
+        let mut files: Vec<PathBuf> = fs::read_dir(dir).unwrap().map(|e| e.unwrap().path()).collect();
+        files.sort();
+        files
+    }
+
+    fn frame_counts(dir: &Path) -> Vec<usize> {
+        files(dir).iter().map(|f| MappedPcap::open(f).unwrap().frames().unwrap().count()).collect()
+    }
+
+    #[test]
+    fn test_file_header() {
+        let writer = PcapWriter::new(Vec::new()).unwrap();
+        assert_eq!(writer.written(), FILE_HEADER_LEN);
+        let header = writer.out;
+        assert_eq!(header.len() as u64, FILE_HEADER_LEN);
+        assert_eq!(&header[..8], [0xd4, 0xc3, 0xb2, 0xa1, 2, 0, 4, 0]);
+        assert_eq!(&header[16..], [&SNAPLEN.to_le_bytes()[..], &LINKTYPE_RADIOTAP.to_le_bytes()].concat());
+    }
+
+    #[test]
+    fn test_record_header() {
+        let mut writer = PcapWriter::new(Vec::new()).unwrap();
+        writer.write(START_US + 250, &[1, 2, 3]).unwrap();
+        assert_eq!(writer.written(), FILE_HEADER_LEN + RECORD_HEADER_LEN + 3);
+        let record = &writer.out[FILE_HEADER_LEN as usize..];
+        let u32_at = |offset: usize| u32::from_le_bytes(record[offset..offset + 4].try_into().unwrap());
+        assert_eq!([u32_at(0), u32_at(4), u32_at(8), u32_at(12)], [1_748_764_800, 250, 3, 3]);
+        assert_eq!(&record[16..], [1, 2, 3]);
+    }
+
+    #[test]
+    fn test_oversized_frame_is_truncated_to_snaplen() {
+        let mut writer = PcapWriter::new(Vec::new()).unwrap();
+        writer.write(0, &vec![0; SNAPLEN as usize + 10]).unwrap();
+        let record = &writer.out[FILE_HEADER_LEN as usize..];
+        assert_eq!(u32::from_le_bytes(record[8..12].try_into().unwrap()), SNAPLEN);
+        assert_eq!(u32::from_le_bytes(record[12..16].try_into().unwrap()), SNAPLEN + 10);
+        assert_eq!(record.len(), 16 + SNAPLEN as usize);
+    }
+
+    #[test]
+    fn test_written_frames_read_back() {
+        let dir = temp_dir("read-back");
+        let mut capture = RotatingPcap::new(&dir, None, None, false).unwrap();
+        capture.write(START_US, &[0xab; 100], true).unwrap();
+        capture.flush().unwrap();
+        let files = files(&dir);
+        assert!(files[0].ends_with("capture-20250601T080000-0000.pcap"));
+        let mapped = MappedPcap::open(&files[0]).unwrap();
+        let first = mapped.frames().unwrap().next().unwrap().unwrap();
+        assert_eq!((first.timestamp_ms, first.link_type, first.data), (1_748_764_800_000, LINKTYPE_RADIOTAP, &[0xab; 100][..]));
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_rid_only_drops_other_frames() {
+        let dir = temp_dir("rid-only");
+        let mut capture = RotatingPcap::new(&dir, None, None, true).unwrap();
+        capture.write(START_US, &[0; 10], false).unwrap();
+        assert!(files(&dir).is_empty());   // 没有可保存的帧时不创建文件
+        capture.write(START_US + 1, &[0; 10], true).unwrap();
+        capture.flush().unwrap();
+        assert_eq!(frame_counts(&dir), [1]);
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_rotates_by_size() {
+        let dir = temp_dir("size");
+        // 文件头 24 字节 + 每帧 16 + 100 字节：每个文件放两帧
+        let mut capture = RotatingPcap::new(&dir, Some(300), None, false).unwrap();
+        for i in 0..5 {
+            capture.write(START_US + i, &[0; 100], false).unwrap();
+        }
+        capture.flush().unwrap();
+        assert_eq!(frame_counts(&dir), [2, 2, 1]);
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_rotates_by_age() {
+        let dir = temp_dir("age");
+        let mut capture = RotatingPcap::new(&dir, None, Some(Duration::from_secs(60)), false).unwrap();
+        capture.write(START_US, &[0; 10], false).unwrap();
+        capture.write(START_US + 59_000_000, &[0; 10], false).unwrap();
+        capture.write(START_US + 60_000_000, &[0; 10], false).unwrap();
+        capture.flush().unwrap();
+        let files = files(&dir);
+        assert_eq!(frame_counts(&dir), [2, 1]);
+        assert!(files[1].ends_with("capture-20250601T080100-0001.pcap"));
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_explicit_rotate() {
+        let dir = temp_dir("explicit");
+        let mut capture = RotatingPcap::new(&dir, None, None, false).unwrap();
+        capture.rotate().unwrap();   // 尚未打开文件时无操作
+        capture.write(START_US, &[0; 10], false).unwrap();
+        capture.rotate().unwrap();
+        capture.write(START_US + 1, &[0; 10], false).unwrap();
+        capture.flush().unwrap();
+        assert_eq!(frame_counts(&dir), [1, 1]);
+        fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,193 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use crate::geo::degrees;
+use crate::import::LINKTYPE_RADIOTAP;
+use crate::upload_data::UploadData;
+
+pub const BLOCK_SHB: u32 = 0x0A0D_0D0A;
+pub const BLOCK_IDB: u32 = 0x0000_0001;
+pub const BLOCK_EPB: u32 = 0x0000_0006;
+const BYTE_ORDER_MAGIC: u32 = 0x1A2B_3C4D;
+const OPT_ENDOFOPT: u16 = 0;
+const OPT_COMMENT: u16 = 1;
+const SNAPLEN: u32 = 65535;
+
+/// 帧注释：携带 Remote ID 的帧在 Wireshark 的 `frame.comment` 中显示解码摘要
+pub fn summary(record: &UploadData) -> String {
+    let uas_id = if record.rid.is_empty() { &record.track_id } else { &record.rid };
+    let mut comment = format!("Remote ID {}", uas_id);
+    if record.latitude != 0 || record.longitude != 0 {
+        comment += &format!(" lat={:.6} lon={:.6} alt={}m",
+            degrees(record.latitude), degrees(record.longitude), record.geometric_altitude);
+    }
+    comment
+}
+
+/// pcapng 抓包文件写入器（radiotap 链路，微秒时间戳）
+///
+/// 所有帧都写入；解出 Remote ID 的帧附带 `opt_comment` 注释，在 Wireshark 中可用
+/// `frame.comment contains "Remote ID"` 过滤。
+pub struct PcapngWriter<W: Write> {
+    out: W,
+}
+
+impl PcapngWriter<BufWriter<File>> {
+    pub fn create<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::new(BufWriter::new(File::create(path)?))
+    }
+}
+
+impl<W: Write> PcapngWriter<W> {
+    /// 写入节头块和唯一的接口描述块
+    pub fn new(mut out: W) -> io::Result<Self> {
+        let mut shb = Vec::new();
+        shb.extend_from_slice(&BYTE_ORDER_MAGIC.to_le_bytes());
+        shb.extend_from_slice(&1u16.to_le_bytes());
+        shb.extend_from_slice(&0u16.to_le_bytes());
+        shb.extend_from_slice(&(-1i64).to_le_bytes());   // 节长度未知
+        write_block(&mut out, BLOCK_SHB, &shb)?;
+
+        let mut idb = Vec::new();
+        idb.extend_from_slice(&(LINKTYPE_RADIOTAP as u16).to_le_bytes());
+        idb.extend_from_slice(&0u16.to_le_bytes());
+        idb.extend_from_slice(&SNAPLEN.to_le_bytes());
+        write_block(&mut out, BLOCK_IDB, &idb)?;
+        Ok(Self { out })
+    }
+
+    /// 写入一帧，`timestamp_us` 为 Unix 微秒
+    pub fn write(&mut self, timestamp_us: i64, frame: &[u8], comment: Option<&str>) -> io::Result<()> {
+        let timestamp = timestamp_us.max(0) as u64;
+        let mut epb = Vec::with_capacity(frame.len() + 64);
+        epb.extend_from_slice(&0u32.to_le_bytes());   // 接口 ID
+        epb.extend_from_slice(&((timestamp >> 32) as u32).to_le_bytes());
+        epb.extend_from_slice(&(timestamp as u32).to_le_bytes());
+        epb.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+        epb.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+        epb.extend_from_slice(frame);
+        pad(&mut epb);
+        if let Some(comment) = comment {
+            epb.extend_from_slice(&OPT_COMMENT.to_le_bytes());
+            epb.extend_from_slice(&(comment.len() as u16).to_le_bytes());
+            epb.extend_from_slice(comment.as_bytes());
+            pad(&mut epb);
+            epb.extend_from_slice(&OPT_ENDOFOPT.to_le_bytes());
+            epb.extend_from_slice(&0u16.to_le_bytes());
+        }
+        write_block(&mut self.out, BLOCK_EPB, &epb)
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+fn pad(bytes: &mut Vec<u8>) {
+    bytes.resize(bytes.len().next_multiple_of(4), 0);
+}
+
+/// 块结构：[类型][总长度][内容][总长度]
+fn write_block<W: Write>(out: &mut W, block_type: u32, body: &[u8]) -> io::Result<()> {
+    let total = (body.len() + 12) as u32;
+    out.write_all(&block_type.to_le_bytes())?;
+    out.write_all(&total.to_le_bytes())?;
+    out.write_all(body)?;
+    out.write_all(&total.to_le_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn u32_at(bytes: &[u8], offset: usize) -> u32 {
+        u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+    }
+
+    /// 依次遍历各块，校验长度首尾一致且 4 字节对齐，返回 (类型, 块体)
+    fn blocks(bytes: &[u8]) -> Vec<(u32, &[u8])> {
+        let mut blocks = Vec::new();
+        let mut offset = 0;
+        while offset < bytes.len() {
+            let len = u32_at(bytes, offset + 4) as usize;
+            assert_eq!(len % 4, 0);
+            assert_eq!(u32_at(bytes, offset + len - 4) as usize, len);
+            blocks.push((u32_at(bytes, offset), &bytes[offset + 8..offset + len - 4]));
+            offset += len;
+        }
+        blocks
+    }
+
+    fn written(frames: &[(i64, &[u8], Option<&str>)]) -> Vec<u8> {
+        let mut writer = PcapngWriter::new(Vec::new()).unwrap();
+        for (timestamp_us, frame, comment) in frames {
+            writer.write(*timestamp_us, frame, *comment).unwrap();
+        }
+        writer.out
+    }
+
+    #[test]
+    fn test_summary_includes_position_when_known() {
+        let record = UploadData { rid: "1581F5FKD229400A".into(), latitude: 312_000_000, longitude: 1_214_000_000, ..Default::default() };
+        assert_eq!(summary(&record), "Remote ID 1581F5FKD229400A lat=31.200000 lon=121.400000 alt=0m");
+    }
+
+    #[test]
+    fn test_summary_falls_back_to_track_id() {
+        let record = UploadData { track_id: "aa:bb:cc:dd:ee:ff".into(), ..Default::default() };
+        assert_eq!(summary(&record), "Remote ID aa:bb:cc:dd:ee:ff");
+    }
+
+    #[test]
+    fn test_header_blocks() {
+        let bytes = written(&[]);
+        let blocks = blocks(&bytes);
+        assert_eq!(blocks.iter().map(|b| b.0).collect::<Vec<_>>(), [BLOCK_SHB, BLOCK_IDB]);
+        let shb = blocks[0].1;
+        assert_eq!(u32_at(shb, 0), BYTE_ORDER_MAGIC);
+        assert_eq!(&shb[4..8], [1, 0, 0, 0]);   // 版本 1.0
+        assert_eq!(&shb[8..16], [0xff; 8]);
+        let idb = blocks[1].1;
+        assert_eq!(u16::from_le_bytes([idb[0], idb[1]]) as u32, LINKTYPE_RADIOTAP);
+        assert_eq!(u32_at(idb, 4), SNAPLEN);
+    }
+
+    #[test]
+    fn test_packet_block_layout() {
+        let bytes = written(&[(0x1_0000_0002, &[1, 2, 3, 4, 5], None)]);
+        let (block_type, epb) = blocks(&bytes)[2];
+        assert_eq!(block_type, BLOCK_EPB);
+        assert_eq!([u32_at(epb, 0), u32_at(epb, 4), u32_at(epb, 8)], [0, 1, 2]);   // 接口 0，时间戳高/低 32 位
+        assert_eq!([u32_at(epb, 12), u32_at(epb, 16)], [5, 5]);
+        // 帧数据补齐到 4 字节
+        assert_eq!(&epb[20..], [1, 2, 3, 4, 5, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_comment_option() {
+        let bytes = written(&[(0, &[0; 4], Some("Remote ID X"))]);
+        let epb = blocks(&bytes)[2].1;
+        let options = &epb[24..];
+        assert_eq!(u16::from_le_bytes([options[0], options[1]]), OPT_COMMENT);
+        assert_eq!(u16::from_le_bytes([options[2], options[3]]), 11);
+        assert_eq!(&options[4..15], b"Remote ID X");
+        assert_eq!(&options[15..], [0, 0, 0, 0, 0]);   // 补齐 1 字节 + opt_endofopt
+    }
+
+    #[test]
+    fn test_negative_timestamp_is_clamped() {
+        let bytes = written(&[(-5, &[0; 4], None)]);
+        let epb = blocks(&bytes)[2].1;
+        assert_eq!([u32_at(epb, 4), u32_at(epb, 8)], [0, 0]);
+    }
+
+    #[test]
+    fn test_written_file_reads_back() {
+        let bytes = written(&[(1_700_000_000_123_456, &[9; 7], Some("Remote ID X")), (1_700_000_001_000_000, &[8; 2], None)]);
+        let records: Vec<_> = crate::mapped_pcap::PcapFrames::new(&bytes).unwrap()
+            .collect::<io::Result<_>>().unwrap();
+        assert_eq!(records.iter().map(|r| r.timestamp_ms).collect::<Vec<_>>(), [1_700_000_000_123, 1_700_000_001_000]);
+        assert_eq!((records[0].data, records[1].data), (&[9u8; 7][..], &[8u8; 2][..]));
+    }
+}
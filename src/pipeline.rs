@@ -0,0 +1,269 @@
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, SyncSender, TrySendError};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::decode::{self, DecodeContext};
+use crate::stats::FrameStats;
+use crate::upload_data::UploadData;
+
+/// 抓包与解码分离：抓包线程只把帧复制进有界队列，由一组解码线程解析
+///
+/// ```toml
+/// [pipeline]
+/// workers = 0         # 解码线程数，0 为按 CPU 核数自动选择（1 到 4 个）
+/// queue_len = 4096    # 每个解码线程的待解码帧队列长度，队满时丢帧并计入 queue_full
+/// ```
+///
+/// 同一发射端的帧总是交给同一个解码线程，保持先后顺序。轨迹关联、测距等有状态的处理
+/// 仍在主线程按帧顺序进行。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PipelineConfig {
+    #[serde(default)]
+    pub workers: usize,
+    #[serde(default = "default_queue_len")]
+    pub queue_len: usize,
+}
+
+fn default_queue_len() -> usize {
+    4096
+}
+
+impl Default for PipelineConfig {
+    fn default() -> Self {
+        Self { workers: 0, queue_len: default_queue_len() }
+    }
+}
+
+impl PipelineConfig {
+    pub fn worker_count(&self) -> usize {
+        if self.workers > 0 {
+            return self.workers;
+        }
+        thread::available_parallelism().map_or(1, |n| n.get().saturating_sub(1)).clamp(1, 4)
+    }
+}
+
+/// 解码线程向主线程发送计数的间隔
+const STATS_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 抓包线程收到的一帧
+pub struct CapturedFrame {
+    /// 网卡序号
+    pub interface: usize,
+    /// 收到该帧时网卡所在的信道
+    pub channel: Option<u8>,
+    pub data: Vec<u8>,
+}
+
+/// 解码线程的输出
+pub enum Decoded {
+    /// 一帧及从中解出的记录，尚未做轨迹关联等有状态的处理
+    Frame { frame: CapturedFrame, records: Vec<UploadData> },
+    /// 某个网卡自上次发送以来的帧分类计数
+    Stats { interface: usize, stats: Box<FrameStats> },
+}
+
+/// 抓包线程投递帧的入口，每个抓包线程持有一个克隆
+#[derive(Clone)]
+pub struct FrameQueue {
+    senders: Arc<Vec<SyncSender<CapturedFrame>>>,
+    dropped: Arc<Vec<AtomicU64>>,
+    backlog: Arc<AtomicUsize>,
+}
+
+impl FrameQueue {
+    /// 按发射端分配解码线程；队列已满时丢弃并计数，解码线程都已退出时返回 false
+    pub fn push(&self, frame: CapturedFrame) -> bool {
+        let interface = frame.interface;
+        // 先计入积压，避免解码线程取出后先减
+        self.backlog.fetch_add(1, Ordering::Relaxed);
+        let result = self.senders[shard(&frame.data, self.senders.len())].try_send(frame);
+        if result.is_err() {
+            self.backlog.fetch_sub(1, Ordering::Relaxed);
+        }
+        match result {
+            Ok(()) => true,
+            Err(TrySendError::Full(_)) => {
+                if let Some(dropped) = self.dropped.get(interface) {
+                    dropped.fetch_add(1, Ordering::Relaxed);
+                }
+                true
+            }
+            Err(TrySendError::Disconnected(_)) => false,
+        }
+    }
+}
+
+/// 运行中的解码流水线
+pub struct Pipeline {
+    pub queue: FrameQueue,
+    pub decoded: Receiver<Decoded>,
+    /// 各网卡因队列已满丢弃的帧数
+    pub dropped: Arc<Vec<AtomicU64>>,
+    /// 各解码线程队列中待解码的帧数合计
+    pub backlog: Arc<AtomicUsize>,
+}
+
+impl Pipeline {
+    /// 每个解码线程使用 `contexts` 中的一个上下文；`deep_scan[i]` 为第 i 个网卡是否启用深度扫描
+    ///
+    /// 上下文应设置 `defer_tracking`，由主线程对收到的记录调用 [`decode::annotate_track`]。
+    pub fn start(config: PipelineConfig, contexts: Vec<DecodeContext>, deep_scan: Vec<bool>) -> Self {
+        let interfaces = deep_scan.len();
+        let deep_scan = Arc::new(deep_scan);
+        let (output, decoded) = mpsc::sync_channel(config.queue_len.max(1));
+        let backlog = Arc::new(AtomicUsize::new(0));
+        let senders = contexts.into_iter().map(|ctx| {
+            let (sender, frames) = mpsc::sync_channel(config.queue_len.max(1));
+            let (output, deep_scan, backlog) = (output.clone(), deep_scan.clone(), backlog.clone());
+            thread::spawn(move || run_worker(ctx, frames, output, &deep_scan, &backlog));
+            sender
+        }).collect();
+        let dropped = Arc::new((0..interfaces).map(|_| AtomicU64::new(0)).collect::<Vec<_>>());
+        let queue = FrameQueue { senders: Arc::new(senders), dropped: dropped.clone(), backlog: backlog.clone() };
+        Self { queue, decoded, dropped, backlog }
+    }
+}
+
+fn run_worker(mut ctx: DecodeContext, frames: Receiver<CapturedFrame>, output: SyncSender<Decoded>, deep_scan: &[bool],
+              backlog: &AtomicUsize) {
+    let base_deep_scan = ctx.deep_scan;
+    let mut stats: Vec<FrameStats> = deep_scan.iter().map(|_| FrameStats::default()).collect();
+    let mut last_sent = Instant::now();
+    loop {
+        let done = match frames.recv_timeout(STATS_INTERVAL) {
+            Ok(frame) => {
+                backlog.fetch_sub(1, Ordering::Relaxed);
+                let started = Instant::now();
+                let index = frame.interface.min(stats.len().saturating_sub(1));
+                ctx.deep_scan = base_deep_scan || deep_scan.get(frame.interface).copied().unwrap_or_default();
+                // 帧分类计数按网卡分开
+                std::mem::swap(&mut ctx.stats, &mut stats[index]);
+                let records = decode::process_packet(&frame.data, &mut ctx);
+                std::mem::swap(&mut ctx.stats, &mut stats[index]);
+                ctx.record_busy(started.elapsed());
+                output.send(Decoded::Frame { frame, records }).is_err()
+            }
+            Err(RecvTimeoutError::Timeout) => false,
+            Err(RecvTimeoutError::Disconnected) => true,
+        };
+        if done || last_sent.elapsed() >= STATS_INTERVAL {
+            for (interface, stats) in stats.iter_mut().enumerate() {
+                let _ = output.send(Decoded::Stats { interface, stats: Box::new(stats.take()) });
+            }
+            last_sent = Instant::now();
+        }
+        if done {
+            return;
+        }
+    }
+}
+
+/// 按发射端地址 (addr2) 选择解码线程，无法取得地址的帧交给第一个线程
+fn shard(packet: &[u8], workers: usize) -> usize {
+    let transmitter = packet.get(2..4)
+        .map(|len| u16::from_le_bytes([len[0], len[1]]) as usize)
+        .and_then(|len| packet.get(len + 10..len + 16));
+    let hash = transmitter.map_or(0, |mac| mac.iter().fold(0usize, |h, b| h.wrapping_mul(31).wrapping_add(*b as usize)));
+    hash % workers.max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stats::FrameClass;
+
+    fn frame(interface: usize, transmitter: u8) -> CapturedFrame {
+        // 8 字节 radiotap 头 + 控制帧，短于预过滤的最短帧长
+        let mut data = vec![0, 0, 8, 0, 0, 0, 0, 0, 0xd4, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        data.extend_from_slice(&[0x02, 0, 0, 0, 0, transmitter]);
+        CapturedFrame { interface, channel: Some(6), data }
+    }
+
+    fn start(workers: usize, queue_len: usize, interfaces: usize) -> Pipeline {
+        let config = PipelineConfig { workers, queue_len };
+        let contexts = (0..config.worker_count()).map(|_| DecodeContext::default()).collect();
+        Pipeline::start(config, contexts, vec![false; interfaces])
+    }
+
+    /// 读完解码输出，返回帧数和按网卡合并的计数
+    fn drain(decoded: Receiver<Decoded>, interfaces: usize) -> (u64, Vec<FrameStats>) {
+        let mut stats: Vec<FrameStats> = (0..interfaces).map(|_| FrameStats::default()).collect();
+        let mut frames = 0;
+        for decoded in decoded {
+            match decoded {
+                Decoded::Frame { frame, records } => {
+                    assert!(records.is_empty() && frame.channel == Some(6));
+                    frames += 1;
+                }
+                Decoded::Stats { interface, stats: counts } => stats[interface].merge(&counts),
+            }
+        }
+        (frames, stats)
+    }
+
+    #[test]
+    fn test_config_defaults() {
+        let config: PipelineConfig = toml::from_str("").unwrap();
+        assert_eq!(config, PipelineConfig::default());
+        assert_eq!(PipelineConfig { workers: 3, ..config }.worker_count(), 3);
+        assert!((1..=4).contains(&config.worker_count()));
+    }
+
+    #[test]
+    fn test_shard_by_transmitter() {
+        // 同一发射端的帧无论来自哪个网卡都交给同一个解码线程
+        assert_eq!(shard(&frame(0, 1).data, 3), shard(&frame(1, 1).data, 3));
+        let shards: std::collections::HashSet<usize> = (0..=255).map(|t| shard(&frame(0, t).data, 3)).collect();
+        assert_eq!(shards.len(), 3);
+    }
+
+    #[test]
+    fn test_shard_without_transmitter() {
+        assert_eq!(shard(&[], 3), 0);
+        assert_eq!(shard(&frame(0, 1).data[..20], 3), 0);
+        assert_eq!(shard(&frame(0, 1).data, 0), 0);
+    }
+
+    #[test]
+    fn test_every_frame_decoded_or_dropped() {
+        let Pipeline { queue, decoded, dropped, .. } = start(2, 1, 2);
+        for i in 0..20 {
+            assert!(queue.push(frame(i % 2, i as u8)));
+        }
+        drop(queue);
+        let (frames, stats) = drain(decoded, 2);
+        let dropped: u64 = dropped.iter().map(|d| d.load(Ordering::Relaxed)).sum();
+        assert_eq!(frames + dropped, 20);
+        assert_eq!(stats[0].count(FrameClass::Short) + stats[1].count(FrameClass::Short), frames);
+    }
+
+    #[test]
+    fn test_stats_per_interface() {
+        let Pipeline { queue, decoded, dropped, backlog, .. } = start(1, 64, 2);
+        for i in 0..10 {
+            assert!(queue.push(frame(1, i)));
+        }
+        drop(queue);
+        let (frames, stats) = drain(decoded, 2);
+        assert_eq!((frames, dropped[1].load(Ordering::Relaxed)), (10, 0));
+        assert_eq!((stats[0].total(), stats[1].count(FrameClass::Short)), (0, 10));
+        assert_eq!(backlog.load(Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn test_push_after_workers_exit() {
+        let Pipeline { queue, decoded, .. } = start(1, 1, 1);
+        // 主线程不再接收时解码线程退出，之后投递返回 false
+        drop(decoded);
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while queue.push(frame(0, 1)) {
+            assert!(Instant::now() < deadline, "worker did not exit");
+            thread::sleep(Duration::from_millis(5));
+        }
+    }
+}
@@ -0,0 +1,323 @@
+use std::io::{self, BufRead};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use chrono::DateTime;
+use serde_json::{json, Value};
+use tracing::info;
+
+struct PlaybackState {
+    speed: f64,               // 回放倍速，<= 0 表示尽快回放
+    paused: bool,
+    steps: usize,             // 暂停状态下允许放行的事件数
+    seek_to_ms: Option<i64>,  // 跳转目标 (Unix 毫秒)
+    position_ms: Option<i64>, // 最近放行的事件时间 (Unix 毫秒)
+}
+
+/// 回放控制：倍速、暂停/单步、跳转到指定时间
+///
+/// 由回放循环与控制端（标准输入、终端界面、HTTP 控制接口）共享。
+pub struct PlaybackControl {
+    state: Mutex<PlaybackState>,
+    changed: Condvar,
+}
+
+impl PlaybackControl {
+    pub fn new(speed: f64) -> Arc<Self> {
+        Arc::new(Self {
+            state: Mutex::new(PlaybackState { speed, paused: false, steps: 0, seek_to_ms: None, position_ms: None }),
+            changed: Condvar::new(),
+        })
+    }
+
+    fn update<F: FnOnce(&mut PlaybackState)>(&self, f: F) {
+        f(&mut self.state.lock().unwrap());
+        self.changed.notify_all();
+    }
+
+    pub fn set_speed(&self, speed: f64) {
+        self.update(|s| s.speed = speed);
+    }
+
+    /// 倍速乘以 `factor`；尽快回放 (倍速 <= 0) 时不变
+    pub fn scale_speed(&self, factor: f64) {
+        self.update(|s| if s.speed > 0.0 { s.speed *= factor });
+    }
+
+    pub fn set_paused(&self, paused: bool) {
+        self.update(|s| s.paused = paused);
+    }
+
+    pub fn toggle_pause(&self) -> bool {
+        let mut paused = false;
+        self.update(|s| {
+            s.paused = !s.paused;
+            paused = s.paused;
+        });
+        paused
+    }
+
+    pub fn step(&self) {
+        self.update(|s| s.steps += 1);
+    }
+
+    pub fn seek(&self, timestamp_ms: i64) {
+        self.update(|s| s.seek_to_ms = Some(timestamp_ms));
+    }
+
+    /// 相对最近放行的事件跳转 `delta_ms`，尚未放行任何事件时忽略
+    pub fn seek_by(&self, delta_ms: i64) {
+        self.update(|s| if let Some(position) = s.position_ms { s.seek_to_ms = Some(position + delta_ms) });
+    }
+
+    pub fn take_seek(&self) -> Option<i64> {
+        self.state.lock().unwrap().seek_to_ms.take()
+    }
+
+    /// 当前倍速、暂停状态与回放位置
+    pub fn status(&self) -> Value {
+        let state = self.state.lock().unwrap();
+        json!({
+            "speed": state.speed,
+            "paused": state.paused,
+            "position_ms": state.position_ms,
+            "seek_to_ms": state.seek_to_ms,
+        })
+    }
+
+    /// 在放行下一个事件前调用
+    ///
+    /// 暂停时阻塞直到恢复或单步；否则按倍速等待 `delta_ms`。
+    /// 等待期间发生跳转时立即返回。
+    pub fn wait(&self, delta_ms: i64) {
+        let mut state = self.state.lock().unwrap();
+        while state.paused && state.seek_to_ms.is_none() {
+            if state.steps > 0 {
+                state.steps -= 1;
+                return;
+            }
+            state = self.changed.wait(state).unwrap();
+        }
+        if state.speed <= 0.0 || delta_ms <= 0 || state.seek_to_ms.is_some() {
+            return;
+        }
+        let deadline = Instant::now() + Duration::from_secs_f64(delta_ms as f64 / 1000.0 / state.speed);
+        loop {
+            let now = Instant::now();
+            if now >= deadline || state.paused || state.seek_to_ms.is_some() {
+                return;
+            }
+            state = self.changed.wait_timeout(state, deadline - now).unwrap().0;
+        }
+    }
+}
+
+/// 回放循环对下一个事件的处理
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pace {
+    /// 放行该事件
+    Emit,
+    /// 向前跳转中，跳过该事件
+    Skip,
+    /// 向后跳转：从头重新读取，早于目标时间的事件会被跳过
+    Rewind,
+}
+
+/// 回放循环中按控制状态决定每个事件的去留：处理跳转，暂停时阻塞，按时间戳间隔和倍速等待
+pub struct Pacer<'a> {
+    control: &'a PlaybackControl,
+    last_ms: Option<i64>,
+    seek_to: Option<i64>,
+}
+
+impl<'a> Pacer<'a> {
+    pub fn new(control: &'a PlaybackControl) -> Self {
+        Self { control, last_ms: None, seek_to: None }
+    }
+
+    /// 在放行时间戳为 `timestamp_ms` 的事件前调用
+    pub fn admit(&mut self, timestamp_ms: i64) -> Pace {
+        if let Some(target) = self.control.take_seek() {
+            self.seek_to = Some(target);
+            if self.last_ms.is_some_and(|last| target < last) {
+                self.last_ms = None;
+                return Pace::Rewind;
+            }
+        }
+        if let Some(target) = self.seek_to {
+            if timestamp_ms < target {
+                return Pace::Skip;
+            }
+            self.seek_to = None;
+            self.last_ms = None;
+        }
+        self.control.wait(self.last_ms.map_or(0, |last| timestamp_ms - last));
+        self.last_ms = Some(timestamp_ms);
+        self.control.state.lock().unwrap().position_ms = Some(timestamp_ms);
+        Pace::Emit
+    }
+}
+
+/// 从标准输入读取回放控制命令
+///
+/// - `p`: 暂停/继续
+/// - `n`: 暂停时单步放行一个事件
+/// - `s <倍速>`: 设置倍速
+/// - `j <时间>`: 跳转，时间为 Unix 毫秒或 RFC 3339
+pub fn spawn_stdin_controller(control: Arc<PlaybackControl>) {
+    thread::spawn(move || {
+        for line in io::stdin().lock().lines() {
+            let Ok(line) = line else { break };
+            let mut parts = line.split_whitespace();
+            match (parts.next(), parts.next()) {
+                (Some("p"), _) => {
+                    let paused = control.toggle_pause();
+                    info!("playback {}", if paused { "paused" } else { "resumed" });
+                }
+                (Some("n"), _) => control.step(),
+                (Some("s"), Some(speed)) => match speed.parse() {
+                    Ok(speed) => control.set_speed(speed),
+                    Err(_) => info!("无效倍速: {}", speed),
+                },
+                (Some("j"), Some(target)) => match parse_timestamp_ms(target) {
+                    Some(ms) => control.seek(ms),
+                    None => info!("无效时间: {}", target),
+                },
+                _ => info!("命令: p | n | s <倍速> | j <时间>"),
+            }
+        }
+    });
+}
+
+/// 解析 Unix 毫秒或 RFC 3339 时间
+pub fn parse_timestamp_ms(s: &str) -> Option<i64> {
+    s.parse::<i64>().ok().or_else(|| {
+        DateTime::parse_from_rfc3339(s).ok().map(|t| t.timestamp_millis())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 按 `Pacer` 的决定模拟回放循环，向后跳转时从头再来，返回放行的时间戳
+    fn run(control: &PlaybackControl, timestamps: &[i64], mut on_emit: impl FnMut(i64)) -> Vec<i64> {
+        let mut pacer = Pacer::new(control);
+        let mut emitted = Vec::new();
+        let mut i = 0;
+        while let Some(&ts) = timestamps.get(i) {
+            i += 1;
+            match pacer.admit(ts) {
+                Pace::Emit => {
+                    emitted.push(ts);
+                    on_emit(ts);
+                }
+                Pace::Skip => {}
+                Pace::Rewind => i = 0,
+            }
+        }
+        emitted
+    }
+
+    #[test]
+    fn test_pacer_seeks_forward() {
+        let control = PlaybackControl::new(0.0);
+        let emitted = run(&control, &[1_000, 2_000, 3_000, 4_000, 5_000], |ts| {
+            if ts == 1_000 {
+                control.seek(3_500);
+            }
+        });
+        assert_eq!(emitted, [1_000, 4_000, 5_000]);
+        // 跳转只生效一次
+        assert_eq!(control.take_seek(), None);
+    }
+
+    #[test]
+    fn test_pacer_rewinds_on_backward_seek() {
+        let control = PlaybackControl::new(0.0);
+        let mut jumped = false;
+        let emitted = run(&control, &[1_000, 2_000, 3_000, 4_000], |ts| {
+            if ts == 3_000 && !jumped {
+                jumped = true;
+                control.seek(2_000);
+            }
+        });
+        assert_eq!(emitted, [1_000, 2_000, 3_000, 2_000, 3_000, 4_000]);
+    }
+
+    /// 在另一线程中调用 `wait`，返回等待结束时的通知
+    fn wait_in_thread(control: &Arc<PlaybackControl>, delta_ms: i64) -> std::sync::mpsc::Receiver<Duration> {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let control = control.clone();
+        thread::spawn(move || {
+            let started = Instant::now();
+            control.wait(delta_ms);
+            let _ = tx.send(started.elapsed());
+        });
+        rx
+    }
+
+    #[test]
+    fn test_wait_scales_by_speed() {
+        let control = PlaybackControl::new(0.0);
+        let started = Instant::now();
+        control.wait(60_000);   // 尽快回放时不等待
+        assert!(started.elapsed() < Duration::from_secs(1));
+
+        control.set_speed(4.0);
+        let started = Instant::now();
+        control.wait(400);
+        assert!(started.elapsed() >= Duration::from_millis(100));
+        control.scale_speed(0.5);
+        assert_eq!(control.status()["speed"], 2.0);
+    }
+
+    #[test]
+    fn test_wait_blocks_while_paused_until_step() {
+        let control = PlaybackControl::new(0.0);
+        assert!(control.toggle_pause());
+        let done = wait_in_thread(&control, 0);
+        assert!(done.recv_timeout(Duration::from_millis(100)).is_err());
+        control.step();
+        done.recv_timeout(Duration::from_secs(5)).unwrap();
+
+        // 单步只放行一个事件
+        let done = wait_in_thread(&control, 0);
+        assert!(done.recv_timeout(Duration::from_millis(100)).is_err());
+        control.set_paused(false);
+        done.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(control.status()["paused"], false);
+    }
+
+    #[test]
+    fn test_seek_interrupts_wait() {
+        let control = PlaybackControl::new(1.0);
+        let done = wait_in_thread(&control, 60_000);
+        assert!(done.recv_timeout(Duration::from_millis(100)).is_err());
+        control.seek(0);
+        assert!(done.recv_timeout(Duration::from_secs(5)).unwrap() < Duration::from_secs(30));
+
+        // 暂停中的跳转同样立即放行
+        control.take_seek();
+        control.set_paused(true);
+        let done = wait_in_thread(&control, 0);
+        assert!(done.recv_timeout(Duration::from_millis(100)).is_err());
+        control.seek(0);
+        done.recv_timeout(Duration::from_secs(5)).unwrap();
+    }
+
+    #[test]
+    fn test_seek_by_is_relative_to_position() {
+        let control = PlaybackControl::new(0.0);
+        control.seek_by(-10_000);   // 尚未放行事件
+        assert_eq!(control.take_seek(), None);
+        let emitted = run(&control, &[1_000, 2_000, 3_000, 4_000], |ts| {
+            if ts == 2_000 {
+                control.seek_by(1_500);
+            }
+        });
+        assert_eq!(emitted, [1_000, 2_000, 4_000]);
+        assert_eq!(control.status()["position_ms"], 4_000);
+    }
+}
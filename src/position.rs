@@ -0,0 +1,175 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::geo::{degrees, distance_m};
+use crate::upload_data::UploadData;
+
+/// 运行状态：地面 (ODID Operational Status 1)
+const STATUS_GROUND: u8 = 1;
+/// 水平精度编码 0 表示未知 (≥ 18.52 km)
+const ACCURACY_UNKNOWN: u8 = 0;
+const MAX_LATITUDE: i32 = 900_000_000;
+const MAX_LONGITUDE: i32 = 1_800_000_000;
+
+/// 记录中位置的可信程度
+///
+/// 很多发射端在 GPS 锁定前广播 0,0（几内亚湾的"零岛"）或越界坐标，锁定后、起飞前又常
+/// 广播精度未知的占位位置。这类位置不应当作真实航迹绘制。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PositionStatus {
+    #[default]
+    Valid,
+    /// 坐标为 0,0 或超出范围，没有可用位置
+    NoFix,
+    /// 处于地面状态且水平精度未知，多为起飞前尚未锁定的占位位置
+    PreTakeoff,
+}
+
+/// 坐标不为 0,0 且在范围内
+fn valid_coordinates(lat: i32, lon: i32) -> bool {
+    (lat != 0 || lon != 0) && (-MAX_LATITUDE..=MAX_LATITUDE).contains(&lat) && (-MAX_LONGITUDE..=MAX_LONGITUDE).contains(&lon)
+}
+
+/// 按坐标、运行状态和水平精度判断位置状态
+pub fn classify(record: &UploadData) -> PositionStatus {
+    let (lat, lon) = (record.latitude, record.longitude);
+    if !valid_coordinates(lat, lon) {
+        return PositionStatus::NoFix;
+    }
+    if record.run_status == STATUS_GROUND && record.horizontal_accuracy == ACCURACY_UNKNOWN {
+        return PositionStatus::PreTakeoff;
+    }
+    PositionStatus::Valid
+}
+
+/// 刚解码的记录的位置状态；包中没有位置向量消息时没有可用位置
+pub fn classify_decoded(record: &UploadData, has_position_message: bool) -> PositionStatus {
+    if !has_position_message {
+        return PositionStatus::NoFix;
+    }
+    classify(record)
+}
+
+/// 记录是否带有可用于定位的坐标（含起飞前的占位位置）
+pub fn has_coordinates(record: &UploadData) -> bool {
+    classify(record) != PositionStatus::NoFix
+}
+
+/// 无人机到控制站的水平距离 (米)；缺少系统消息或任一方位置无效时为 None
+///
+/// 位置向量和系统消息通常在不同的报文中，应在轨迹合并之后计算。
+pub fn operator_distance_m(record: &UploadData) -> Option<f32> {
+    let (lat, lon) = operator_coordinates(record)?;
+    if !has_coordinates(record) {
+        return None;
+    }
+    let distance = distance_m(degrees(record.latitude), degrees(record.longitude), lat, lon);
+    Some(distance.round() as f32)
+}
+
+/// 系统消息中控制站的位置 (度)，缺少系统消息或坐标无效时为 None
+pub fn operator_coordinates(record: &UploadData) -> Option<(f64, f64)> {
+    let operator = record.operator.as_ref()?;
+    valid_coordinates(operator.latitude, operator.longitude)
+        .then(|| (degrees(operator.latitude), degrees(operator.longitude)))
+}
+
+/// 各类位置的处理方式；默认全部输出，由 `position_status` 字段标明
+///
+/// ```toml
+/// [position]
+/// drop_no_fix = true          # 不输出没有可用位置的记录
+/// drop_pre_takeoff = false    # 不输出起飞前的占位位置
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PositionPolicy {
+    #[serde(default)]
+    pub drop_no_fix: bool,
+    #[serde(default)]
+    pub drop_pre_takeoff: bool,
+}
+
+impl PositionPolicy {
+    pub fn keeps(&self, status: PositionStatus) -> bool {
+        match status {
+            PositionStatus::Valid => true,
+            PositionStatus::NoFix => !self.drop_no_fix,
+            PositionStatus::PreTakeoff => !self.drop_pre_takeoff,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::upload_data::OperatorPosition;
+
+    fn record(latitude: i32, longitude: i32, run_status: u8, horizontal_accuracy: u8) -> UploadData {
+        UploadData { latitude, longitude, run_status, horizontal_accuracy, ..Default::default() }
+    }
+
+    fn operator(latitude: i32, longitude: i32) -> Option<OperatorPosition> {
+        Some(OperatorPosition { location_type: 1, latitude, longitude, altitude: 0 })
+    }
+
+    fn drone() -> UploadData {
+        UploadData { operator: operator(399_000_000, 1_164_000_000), ..record(399_090_000, 1_164_000_000, 2, 10) }
+    }
+
+    #[test]
+    fn test_null_island_has_no_fix() {
+        assert_eq!(classify(&record(0, 0, 2, 10)), PositionStatus::NoFix);
+        assert!(!has_coordinates(&record(0, 0, 2, 10)));
+    }
+
+    #[test]
+    fn test_out_of_range_has_no_fix() {
+        assert_eq!(classify(&record(910_000_000, 1, 2, 10)), PositionStatus::NoFix);
+        assert_eq!(classify(&record(1, -1_800_000_001, 2, 10)), PositionStatus::NoFix);
+        assert_eq!(classify(&record(-MAX_LATITUDE, MAX_LONGITUDE, 2, 10)), PositionStatus::Valid);
+    }
+
+    #[test]
+    fn test_grounded_unknown_accuracy_is_pre_takeoff() {
+        assert_eq!(classify(&record(399_000_000, 1_164_000_000, 1, 0)), PositionStatus::PreTakeoff);
+        assert!(has_coordinates(&record(399_000_000, 1_164_000_000, 1, 0)));
+        assert_eq!(classify(&record(399_000_000, 1_164_000_000, 1, 10)), PositionStatus::Valid);
+        assert_eq!(classify(&record(399_000_000, 1_164_000_000, 2, 0)), PositionStatus::Valid);
+    }
+
+    #[test]
+    fn test_without_position_message_has_no_fix() {
+        let located = record(399_000_000, 1_164_000_000, 2, 10);
+        assert_eq!(classify_decoded(&located, false), PositionStatus::NoFix);
+        assert_eq!(classify_decoded(&located, true), PositionStatus::Valid);
+    }
+
+    #[test]
+    fn test_single_zero_coordinate_is_valid() {
+        assert_eq!(classify(&record(399_000_000, 0, 2, 0)), PositionStatus::Valid);
+    }
+
+    #[test]
+    fn test_policy_keeps() {
+        let policy = PositionPolicy { drop_no_fix: true, drop_pre_takeoff: false };
+        assert!(!policy.keeps(PositionStatus::NoFix));
+        assert!(policy.keeps(PositionStatus::PreTakeoff) && policy.keeps(PositionStatus::Valid));
+        assert!(!PositionPolicy { drop_pre_takeoff: true, ..policy }.keeps(PositionStatus::PreTakeoff));
+        assert!(PositionPolicy::default().keeps(PositionStatus::NoFix));
+    }
+
+    #[test]
+    fn test_operator_distance() {
+        assert_eq!(operator_distance_m(&drone()), Some(1001.0));
+        let (lat, lon) = operator_coordinates(&drone()).unwrap();
+        assert!((lat - 39.9).abs() < 1e-9 && (lon - 116.4).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_operator_distance_needs_both_positions() {
+        assert_eq!(operator_distance_m(&UploadData { operator: operator(0, 0), ..drone() }), None);
+        assert_eq!(operator_distance_m(&UploadData { latitude: 0, longitude: 0, ..drone() }), None);
+        assert_eq!(operator_distance_m(&UploadData { operator: None, ..drone() }), None);
+    }
+}
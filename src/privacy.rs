@@ -0,0 +1,215 @@
+use std::borrow::Cow;
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use crate::event_log::DecodedEvent;
+use crate::geo::EARTH_RADIUS_M;
+
+/// 单个输出的位置模糊化设置，用于对外公开的数据源
+///
+/// ```toml
+/// [privacy.live_layer]
+/// round_m = 100          # 坐标取整到约 100 米的网格
+/// delay_minutes = 5      # 延迟 5 分钟后才发布
+/// drop_operator = true   # 不发布控制站位置
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Obfuscation {
+    #[serde(default)]
+    pub round_m: Option<f64>,
+    #[serde(default)]
+    pub delay_minutes: u64,
+    #[serde(default)]
+    pub drop_operator: bool,
+}
+
+impl Obfuscation {
+    fn is_noop(&self) -> bool {
+        self.round_m.is_none() && self.delay_minutes == 0 && !self.drop_operator
+    }
+
+    /// 返回模糊化后的事件副本
+    ///
+    /// 原始负载/消息的十六进制中含精确坐标，模糊化时一并清除。
+    pub fn apply(&self, event: &DecodedEvent) -> DecodedEvent {
+        let mut event = event.clone();
+        let record = &mut event.record;
+        if let Some(grid_m) = self.round_m.filter(|m| *m > 0.0) {
+            (record.latitude, record.longitude) = snap(record.latitude, record.longitude, grid_m);
+            if let Some(operator) = record.operator.as_mut() {
+                (operator.latitude, operator.longitude) = snap(operator.latitude, operator.longitude, grid_m);
+            }
+        }
+        if self.drop_operator {
+            record.operator = None;
+        }
+        if self.round_m.is_some() || self.drop_operator {
+            record.raw_payload.clear();
+            record.raw_messages.clear();
+        }
+        event
+    }
+}
+
+/// 将 1e-7 度编码的坐标对齐到边长约 `grid_m` 米的网格中心
+fn snap(latitude: i32, longitude: i32, grid_m: f64) -> (i32, i32) {
+    if latitude == 0 && longitude == 0 {
+        return (0, 0);
+    }
+    let lat_step = (grid_m / EARTH_RADIUS_M).to_degrees() * 1e7;
+    let lat = ((latitude as f64 / lat_step).floor() + 0.5) * lat_step;
+    let lon_step = lat_step / (lat * 1e-7).to_radians().cos().max(0.01);
+    let lon = ((longitude as f64 / lon_step).floor() + 0.5) * lon_step;
+    (lat.round() as i32, lon.round() as i32)
+}
+
+/// 经过模糊化的输出：按设置处理事件，需要延迟时排队到期后再发布
+#[derive(Default)]
+pub struct Feed {
+    obfuscation: Obfuscation,
+    delayed: VecDeque<DecodedEvent>,
+}
+
+impl Feed {
+    pub fn new(obfuscation: Obfuscation) -> Self {
+        Self { obfuscation, delayed: VecDeque::new() }
+    }
+
+    /// 加入新事件（可为空，仅取出到期事件），返回此刻可以发布的事件
+    pub fn release<'a>(&mut self, event: Option<&'a DecodedEvent>, now_ms: i64) -> Vec<Cow<'a, DecodedEvent>> {
+        if self.obfuscation.is_noop() {
+            return event.map(Cow::Borrowed).into_iter().collect();
+        }
+        let delay_ms = self.obfuscation.delay_minutes as i64 * 60_000;
+        if delay_ms == 0 {
+            return event.map(|e| Cow::Owned(self.obfuscation.apply(e))).into_iter().collect();
+        }
+        if let Some(event) = event {
+            self.delayed.push_back(self.obfuscation.apply(event));
+        }
+        let ready = self.delayed.iter().take_while(|e| now_ms - e.received_at_ms >= delay_ms).count();
+        self.delayed.drain(..ready).map(Cow::Owned).collect()
+    }
+}
+
+/// 各公开输出的模糊化配置 (`[privacy.<输出>]`)，录制文件与数据库保存原始数据
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PrivacyConfig {
+    #[serde(default)]
+    pub upload: Obfuscation,
+    #[serde(default)]
+    pub sbs: Obfuscation,
+    #[serde(default)]
+    pub asterix: Obfuscation,
+    #[serde(default)]
+    pub live_layer: Obfuscation,
+    #[serde(default)]
+    pub live_feed: Obfuscation,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geo::{degrees, distance_m};
+    use crate::upload_data::{OperatorPosition, UploadData};
+
+    fn event(received_at_ms: i64, latitude: i32, longitude: i32) -> DecodedEvent {
+        DecodedEvent {
+            received_at_ms,
+            record: UploadData {
+                latitude,
+                longitude,
+                operator: Some(OperatorPosition { location_type: 1, latitude: 311_230_000, longitude: 1_214_560_000, altitude: 0 }),
+                raw_payload: "f119".into(),
+                raw_messages: vec!["02".into()],
+                ..Default::default()
+            },
+        }
+    }
+
+    fn rounded(round_m: f64) -> Obfuscation {
+        Obfuscation { round_m: Some(round_m), ..Default::default() }
+    }
+
+    #[test]
+    fn test_passthrough_borrows_event() {
+        let event = event(1_000_000, 311_234_567, 1_214_567_891);
+        let mut passthrough = Feed::default();
+        let released = passthrough.release(Some(&event), 0);
+        assert!(matches!(released[..], [Cow::Borrowed(_)]));
+        assert!(passthrough.release(None, 0).is_empty());
+    }
+
+    #[test]
+    fn test_round_snaps_to_grid_cell() {
+        let original = event(1_000_000, 311_234_567, 1_214_567_891);
+        let snapped = rounded(100.0).apply(&original).record;
+        let snapped_nearby = rounded(100.0).apply(&event(1_000_000, 311_234_600, 1_214_567_900)).record;
+        assert_eq!((snapped.latitude, snapped.longitude), (snapped_nearby.latitude, snapped_nearby.longitude));
+        let moved = distance_m(degrees(original.record.latitude), degrees(original.record.longitude),
+            degrees(snapped.latitude), degrees(snapped.longitude));
+        assert!(moved > 0.0 && moved < 100.0);
+    }
+
+    #[test]
+    fn test_round_snaps_operator_and_clears_raw() {
+        let snapped = rounded(100.0).apply(&event(1_000_000, 311_234_567, 1_214_567_891)).record;
+        let operator = snapped.operator.unwrap();
+        assert_ne!((operator.latitude, operator.longitude), (311_230_000, 1_214_560_000));
+        assert!(snapped.raw_payload.is_empty() && snapped.raw_messages.is_empty());
+    }
+
+    #[test]
+    fn test_round_keeps_missing_position() {
+        let snapped = rounded(100.0).apply(&event(1_000_000, 0, 0)).record;
+        assert_eq!((snapped.latitude, snapped.longitude), (0, 0));
+    }
+
+    #[test]
+    fn test_drop_operator() {
+        let obfuscation = Obfuscation { drop_operator: true, ..Default::default() };
+        let record = obfuscation.apply(&event(1_000_000, 311_234_567, 1_214_567_891)).record;
+        assert!(record.operator.is_none() && record.raw_payload.is_empty());
+        assert_eq!((record.latitude, record.longitude), (311_234_567, 1_214_567_891));
+    }
+
+    #[test]
+    fn test_delay_only_keeps_raw() {
+        let obfuscation = Obfuscation { delay_minutes: 1, ..Default::default() };
+        let record = obfuscation.apply(&event(1_000_000, 311_234_567, 1_214_567_891)).record;
+        assert_eq!(record.raw_payload, "f119");
+        assert!(record.operator.is_some());
+    }
+
+    #[test]
+    fn test_feed_without_delay_releases_immediately() {
+        let event = event(1_000_000, 311_234_567, 1_214_567_891);
+        let mut feed = Feed::new(rounded(100.0));
+        let released = feed.release(Some(&event), 0);
+        assert!(matches!(released[..], [Cow::Owned(_)]));
+        assert!(released[0].record.raw_payload.is_empty());
+    }
+
+    #[test]
+    fn test_feed_delays_in_order() {
+        let mut feed = Feed::new(Obfuscation { round_m: Some(100.0), delay_minutes: 5, drop_operator: true });
+        assert!(feed.release(Some(&event(1_000_000, 1, 1)), 1_000_000).is_empty());
+        assert!(feed.release(Some(&event(1_060_000, 1, 1)), 1_060_000).is_empty());
+        assert!(feed.release(None, 1_000_000 + 299_999).is_empty());
+        let released = feed.release(None, 1_060_000 + 300_000);
+        assert_eq!(released.iter().map(|e| e.received_at_ms).collect::<Vec<_>>(), [1_000_000, 1_060_000]);
+        assert!(feed.release(None, 2_000_000).is_empty());
+    }
+
+    #[test]
+    fn test_config_rejects_unknown_fields() {
+        let config: PrivacyConfig = toml::from_str("[live_layer]\nround_m = 100\n").unwrap();
+        assert_eq!(config.live_layer, rounded(100.0));
+        assert_eq!(config.upload, Obfuscation::default());
+        assert!(toml::from_str::<PrivacyConfig>("[live_layer]\nround = 100\n").is_err());
+        assert!(toml::from_str::<PrivacyConfig>("[kafka]\n").is_err());
+    }
+}
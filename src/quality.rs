@@ -0,0 +1,161 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::position::PositionStatus;
+use crate::ssid_check::SsidMatch;
+use crate::upload_data::UploadData;
+
+/// 信号强于该值时不扣分
+const STRONG_RSSI_DBM: f32 = -75.0;
+/// 信号弱于该值时按最低系数计
+const WEAK_RSSI_DBM: f32 = -95.0;
+/// 计数器跟踪表超过该大小时清理长时间未出现的轨迹
+const MAX_TRACKS: usize = 1024;
+const TRACK_EXPIRY: Duration = Duration::from_secs(60);
+
+/// 单帧可得的质量评分 (0-1)：FCS 校验、信号强度、消息解析失败数和字段校验结果相乘
+///
+/// `parse_warnings` 为同一消息包中解码失败的消息数。计数器连续性需要按轨迹保存状态，
+/// 由 [`CounterContinuity`] 在轨迹关联时再乘上。
+pub fn frame_score(record: &UploadData, bad_fcs: bool, parse_warnings: usize) -> f32 {
+    let mut score = 1.0;
+    if bad_fcs {
+        score *= 0.3;
+    }
+    if let Some(rssi) = record.rssi {
+        let t = ((rssi - WEAK_RSSI_DBM) / (STRONG_RSSI_DBM - WEAK_RSSI_DBM)).clamp(0.0, 1.0);
+        score *= 0.5 + 0.5 * t;
+    }
+    score *= 0.7f32.powi(parse_warnings.min(8) as i32);
+    score * field_score(record)
+}
+
+/// 数据帧深度扫描的启发式命中可能是误报，评分乘以该系数
+pub const HEURISTIC_FACTOR: f32 = 0.5;
+
+/// 字段校验：无可用位置、缺少或宽松解码的 UAS ID、SSID 序列号不符各扣一部分
+fn field_score(record: &UploadData) -> f32 {
+    let mut score = 1.0;
+    if record.position_status == PositionStatus::NoFix {
+        score *= 0.7;
+    }
+    if record.rid.is_empty() {
+        score *= 0.9;
+    }
+    if record.rid_lossy {
+        score *= 0.8;
+    }
+    if record.ssid_match == Some(SsidMatch::Mismatch) {
+        score *= 0.6;
+    }
+    score
+}
+
+/// 保留两位小数，输出稳定
+pub fn round(score: f32) -> f32 {
+    (score * 100.0).round() / 100.0
+}
+
+/// 按轨迹检查消息计数器的连续性
+///
+/// 计数器每个消息包加 1 并在 255 后回绕。重复或小幅跳跃（漏收）属正常；大幅跳跃或回退
+/// 多为同一轨迹上混入了另一个发射端（伪造或 ID 冲突）。
+#[derive(Default)]
+pub struct CounterContinuity {
+    last: HashMap<String, (u8, Instant)>,
+}
+
+impl CounterContinuity {
+    /// 记录一个计数器值，返回连续性系数
+    pub fn observe(&mut self, track_id: &str, counter: u8) -> f32 {
+        let now = Instant::now();
+        if self.last.len() > MAX_TRACKS {
+            self.last.retain(|_, (_, seen)| now.duration_since(*seen) < TRACK_EXPIRY);
+        }
+        let previous = self.last.insert(track_id.to_string(), (counter, now));
+        match previous {
+            Some((last, seen)) if now.duration_since(seen) < TRACK_EXPIRY => match counter.wrapping_sub(last) {
+                0..=16 => 1.0,
+                17..=128 => 0.8,
+                _ => 0.5,
+            },
+            _ => 1.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record() -> UploadData {
+        UploadData { rid: "RID-1".into(), rssi: Some(-60.0), ..Default::default() }
+    }
+
+    #[test]
+    fn test_clean_frame_scores_full() {
+        assert_eq!(frame_score(&record(), false, 0), 1.0);
+        assert_eq!(frame_score(&UploadData { rssi: None, ..record() }, false, 0), 1.0);
+    }
+
+    #[test]
+    fn test_rssi_scales_between_weak_and_strong() {
+        let score = |rssi| frame_score(&UploadData { rssi: Some(rssi), ..record() }, false, 0);
+        assert_eq!(score(STRONG_RSSI_DBM), 1.0);
+        assert_eq!(round(score(-85.0)), 0.75);
+        assert_eq!(score(WEAK_RSSI_DBM), 0.5);
+        assert_eq!(score(-110.0), 0.5);
+    }
+
+    #[test]
+    fn test_bad_fcs_and_parse_warnings() {
+        assert_eq!(round(frame_score(&record(), true, 0)), 0.3);
+        assert_eq!(round(frame_score(&record(), false, 2)), 0.49);
+        assert_eq!(round(frame_score(&record(), true, 1)), 0.21);
+        // 解析失败数最多按 8 条计
+        assert_eq!(frame_score(&record(), false, 8), frame_score(&record(), false, 100));
+    }
+
+    #[test]
+    fn test_field_checks() {
+        let score = |record: UploadData| round(frame_score(&record, false, 0));
+        assert_eq!(score(UploadData { position_status: PositionStatus::NoFix, ..record() }), 0.7);
+        assert_eq!(score(UploadData { position_status: PositionStatus::PreTakeoff, ..record() }), 1.0);
+        assert_eq!(score(UploadData { rid: String::new(), ..record() }), 0.9);
+        assert_eq!(score(UploadData { rid_lossy: true, ..record() }), 0.8);
+        assert_eq!(score(UploadData { ssid_match: Some(SsidMatch::Mismatch), ..record() }), 0.6);
+        assert_eq!(score(UploadData { ssid_match: Some(SsidMatch::Match), ..record() }), 1.0);
+    }
+
+    #[test]
+    fn test_factors_multiply() {
+        let weak = UploadData { rssi: Some(-100.0), ssid_match: Some(SsidMatch::Mismatch), ..record() };
+        assert_eq!(frame_score(&weak, false, 0), 0.3);
+    }
+
+    #[test]
+    fn test_counter_small_gaps_and_wraparound() {
+        let mut continuity = CounterContinuity::default();
+        assert_eq!(continuity.observe("a", 250), 1.0);
+        assert_eq!(continuity.observe("a", 250), 1.0);
+        assert_eq!(continuity.observe("a", 2), 1.0);
+        assert_eq!(continuity.observe("a", 18), 1.0);
+    }
+
+    #[test]
+    fn test_counter_jumps_and_rewinds() {
+        let mut continuity = CounterContinuity::default();
+        continuity.observe("a", 10);
+        assert_eq!(continuity.observe("a", 27), 0.8);
+        assert_eq!(continuity.observe("a", 155), 0.8);
+        assert_eq!(continuity.observe("a", 150), 0.5);
+    }
+
+    #[test]
+    fn test_counter_tracks_are_independent() {
+        let mut continuity = CounterContinuity::default();
+        continuity.observe("a", 10);
+        assert_eq!(continuity.observe("b", 200), 1.0);
+        assert_eq!(continuity.observe("a", 11), 1.0);
+    }
+}
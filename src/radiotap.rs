@@ -0,0 +1,276 @@
+/// 按 present 位图解析出的 radiotap 字段
+///
+/// 参见 <https://www.radiotap.org>：头部为版本、长度和一个或多个 present 位图，
+/// 之后的字段按位序排列并按各自大小对齐。
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct RadiotapHeader {
+    /// 网卡收到帧时的 MAC 时间戳 (TSFT，微秒)
+    pub tsft_us: Option<u64>,
+    pub flags: Option<u8>,
+    /// 速率，单位 500 kbps
+    pub rate: Option<u8>,
+    pub channel_freq: Option<u16>,
+    pub channel_flags: Option<u16>,
+    /// 合并后的天线信号强度 (dBm)，即第一个 radiotap 命名空间中的信号字段
+    pub signal_dbm: Option<i8>,
+    pub noise_dbm: Option<i8>,
+    /// 多天线驱动在后续命名空间中逐天线报告的信号 (天线编号, dBm)
+    pub antenna_signals: Vec<(u8, i8)>,
+    pub mcs: Option<Mcs>,
+}
+
+/// 802.11n MCS 字段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mcs {
+    pub known: u8,
+    pub flags: u8,
+    pub index: u8,
+}
+
+impl Mcs {
+    const KNOWN_BANDWIDTH: u8 = 0x01;
+    const KNOWN_GUARD_INTERVAL: u8 = 0x04;
+
+    /// 信道带宽 (MHz)，20L/20U 按 20 计
+    pub fn bandwidth_mhz(&self) -> Option<u16> {
+        (self.known & Self::KNOWN_BANDWIDTH != 0).then_some(if self.flags & 0x03 == 1 { 40 } else { 20 })
+    }
+
+    pub fn short_guard_interval(&self) -> Option<bool> {
+        (self.known & Self::KNOWN_GUARD_INTERVAL != 0).then_some(self.flags & 0x04 != 0)
+    }
+}
+
+impl RadiotapHeader {
+    const FLAG_FCS_AT_END: u8 = 0x10;
+    const FLAG_BAD_FCS: u8 = 0x40;
+    pub const CHANNEL_2GHZ: u16 = 0x0080;
+    pub const CHANNEL_5GHZ: u16 = 0x0100;
+
+    pub fn fcs_included(&self) -> bool {
+        self.flags.is_some_and(|f| f & Self::FLAG_FCS_AT_END != 0)
+    }
+
+    pub fn bad_fcs(&self) -> bool {
+        self.flags.is_some_and(|f| f & Self::FLAG_BAD_FCS != 0)
+    }
+}
+
+const PRESENT_RADIOTAP_NS: u32 = 1 << 29;
+const PRESENT_VENDOR_NS: u32 = 1 << 30;
+const PRESENT_EXT: u32 = 1 << 31;
+
+/// 标准命名空间中各字段的 (对齐, 长度)，按位序号 0..=27；位 28 为 TLV，之后的字段无法确定长度
+const FIELDS: [(usize, usize); 28] = [
+    (8, 8), (1, 1), (1, 1), (2, 4), (1, 2), (1, 1), (1, 1), (2, 2),
+    (2, 2), (2, 2), (1, 1), (1, 1), (1, 1), (1, 1), (2, 2), (2, 2),
+    (1, 1), (1, 1), (4, 8), (1, 3), (4, 8), (2, 12), (8, 12), (2, 12),
+    (2, 12), (2, 6), (1, 1), (2, 4),
+];
+
+/// 解析 radiotap 头，返回字段与头长度
+///
+/// 支持扩展 present 位图、多个 radiotap 命名空间（逐天线字段）并跳过厂商命名空间。
+/// 遇到无法确定长度的字段时停止，已解析的字段仍然返回。
+pub fn parse(data: &[u8]) -> Option<(RadiotapHeader, usize)> {
+    if data.len() < 8 || data[0] != 0 {
+        return None;
+    }
+    let header_len = u16::from_le_bytes([data[2], data[3]]) as usize;
+    let header = data.get(..header_len)?;
+
+    let mut words = Vec::new();
+    let mut offset = 4;
+    loop {
+        let word = u32::from_le_bytes(header.get(offset..offset + 4)?.try_into().ok()?);
+        words.push(word);
+        offset += 4;
+        if word & PRESENT_EXT == 0 {
+            break;
+        }
+    }
+
+    let mut info = RadiotapHeader::default();
+    // 厂商命名空间的字段不解析，进入时按其声明的长度整体跳过
+    let mut in_vendor = false;
+    let mut vendor_skip = 0;
+    let mut namespace = 0;      // 第几个标准命名空间，0 为合并字段，之后为逐天线字段
+    let mut base_bit = 0;       // 同一命名空间内扩展位图的起始位序号
+    let mut antenna = None;
+    let mut pending_signal = None;
+    'words: for word in words {
+        if in_vendor {
+            offset += std::mem::take(&mut vendor_skip);
+        } else {
+            for bit in 0..29 {
+                if word & (1 << bit) == 0 {
+                    continue;
+                }
+                let Some(&(align, len)) = FIELDS.get(base_bit + bit) else { break 'words };
+                offset = offset.next_multiple_of(align);
+                let Some(field) = header.get(offset..offset + len) else { break 'words };
+                match (namespace, base_bit + bit) {
+                    (0, 0) => info.tsft_us = Some(u64::from_le_bytes(field.try_into().ok()?)),
+                    (0, 1) => info.flags = Some(field[0]),
+                    (0, 2) => info.rate = Some(field[0]),
+                    (0, 3) => {
+                        info.channel_freq = Some(u16::from_le_bytes([field[0], field[1]]));
+                        info.channel_flags = Some(u16::from_le_bytes([field[2], field[3]]));
+                    }
+                    (0, 5) => info.signal_dbm = Some(field[0] as i8),
+                    (0, 6) => info.noise_dbm = Some(field[0] as i8),
+                    (0, 19) => info.mcs = Some(Mcs { known: field[0], flags: field[1], index: field[2] }),
+                    (_, 5) => pending_signal = Some(field[0] as i8),
+                    (_, 11) => antenna = Some(field[0]),
+                    _ => {}
+                }
+                offset += len;
+            }
+        }
+        if namespace > 0
+            && let (Some(antenna), Some(signal)) = (antenna.take(), pending_signal.take())
+        {
+            info.antenna_signals.push((antenna, signal));
+        }
+        if word & PRESENT_VENDOR_NS != 0 {
+            // 厂商命名空间头：OUI(3) 子命名空间(1) 跳过长度(2)，2 字节对齐
+            offset = offset.next_multiple_of(2);
+            let Some(vendor) = header.get(offset..offset + 6) else { break };
+            vendor_skip = u16::from_le_bytes([vendor[4], vendor[5]]) as usize;
+            offset += 6;
+            in_vendor = true;
+        } else if word & PRESENT_RADIOTAP_NS != 0 {
+            in_vendor = false;
+            namespace += 1;
+            base_bit = 0;
+        } else if !in_vendor {
+            base_bit += 32;
+        }
+    }
+    Some((info, header_len))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 由 present 位图和字段字节（含对齐填充）拼出 radiotap 头，并填入头长度
+    fn header(present: &[u32], fields: &[u8]) -> Vec<u8> {
+        let mut packet = vec![0x00, 0x00, 0x00, 0x00];
+        for word in present {
+            packet.extend_from_slice(&word.to_le_bytes());
+        }
+        packet.extend_from_slice(fields);
+        let len = packet.len() as u16;
+        packet[2..4].copy_from_slice(&len.to_le_bytes());
+        packet
+    }
+
+    const PRESENT_ANTENNA: u32 = 1 << 5 | 1 << 11;
+
+    #[test]
+    fn test_invalid_headers() {
+        assert!(parse(&[0x00, 0x00, 0x08]).is_none());
+        assert!(parse(&[0x01, 0x00, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00]).is_none());   // 版本不为 0
+        assert!(parse(&[0x00, 0x00, 0x20, 0x00, 0x00, 0x00, 0x00, 0x00]).is_none());   // 声明长度超出数据
+        // 扩展位图超出头长度
+        assert!(parse(&header(&[PRESENT_EXT], &[])).is_none());
+    }
+
+    #[test]
+    fn test_header_length_excludes_frame() {
+        let mut packet = header(&[1 << 1], &[0x00]);
+        packet.extend_from_slice(&[0x80, 0x00]);
+        assert_eq!(parse(&packet).unwrap().1, 9);
+    }
+
+    #[test]
+    fn test_flags() {
+        let (info, _) = parse(&header(&[1 << 1], &[0x50])).unwrap();
+        assert!(info.fcs_included() && info.bad_fcs());
+        let (info, _) = parse(&header(&[1 << 2], &[0x0c])).unwrap();
+        assert_eq!((info.flags, info.rate), (None, Some(12)));
+        assert!(!info.fcs_included() && !info.bad_fcs());
+    }
+
+    #[test]
+    fn test_channel_is_two_byte_aligned() {
+        // flags | channel：flags 之后填充 1 字节
+        let (info, _) = parse(&header(&[1 << 1 | 1 << 3], &[0x00, 0x00, 0x6c, 0x09, 0x80, 0x04])).unwrap();
+        assert_eq!(info.channel_freq, Some(2412));
+        assert_eq!(info.channel_flags.map(|f| f & RadiotapHeader::CHANNEL_2GHZ != 0), Some(true));
+    }
+
+    #[test]
+    fn test_tsft_is_eight_byte_aligned() {
+        // 单个位图后偏移为 8，TSFT 无需填充
+        let mut fields = 0x0123_4567_89abu64.to_le_bytes().to_vec();
+        fields.push(0xc4);
+        let (info, _) = parse(&header(&[1 | 1 << 5], &fields)).unwrap();
+        assert_eq!((info.tsft_us, info.signal_dbm), (Some(0x0123_4567_89ab), Some(-60)));
+        // 两个位图后偏移为 12，TSFT 前填充 4 字节
+        let fields = [&[0u8; 4][..], &7u64.to_le_bytes()].concat();
+        let (info, _) = parse(&header(&[1 | PRESENT_EXT, 0], &fields)).unwrap();
+        assert_eq!(info.tsft_us, Some(7));
+    }
+
+    #[test]
+    fn test_signal_and_noise() {
+        let (info, _) = parse(&header(&[1 << 5 | 1 << 6], &[0xc4, 0xa0])).unwrap();
+        assert_eq!((info.signal_dbm, info.noise_dbm), (Some(-60), Some(-96)));
+    }
+
+    #[test]
+    fn test_mcs_field() {
+        let (info, _) = parse(&header(&[1 << 19], &[0x01, 0x01, 0x07])).unwrap();
+        let mcs = info.mcs.unwrap();
+        assert_eq!((mcs.index, mcs.bandwidth_mhz(), mcs.short_guard_interval()), (7, Some(40), None));
+        let mcs = Mcs { known: 0x05, flags: 0x04, index: 3 };
+        assert_eq!((mcs.bandwidth_mhz(), mcs.short_guard_interval()), (Some(20), Some(true)));
+        assert_eq!(Mcs { known: 0, flags: 0x01, index: 0 }.bandwidth_mhz(), None);
+    }
+
+    #[test]
+    fn test_per_antenna_namespaces() {
+        // 合并字段之后，两个 radiotap 命名空间各报告一个天线的信号
+        let present = [
+            1 << 5 | PRESENT_RADIOTAP_NS | PRESENT_EXT,
+            PRESENT_ANTENNA | PRESENT_RADIOTAP_NS | PRESENT_EXT,
+            PRESENT_ANTENNA,
+        ];
+        let (info, _) = parse(&header(&present, &[0xc4, 0xc2, 0x00, 0xc6, 0x01])).unwrap();
+        assert_eq!(info.signal_dbm, Some(-60));
+        assert_eq!(info.antenna_signals, [(0, -62), (1, -58)]);
+    }
+
+    #[test]
+    fn test_vendor_namespace_is_skipped() {
+        let present = [
+            1 << 1 | PRESENT_VENDOR_NS | PRESENT_EXT,
+            1 | PRESENT_RADIOTAP_NS | PRESENT_EXT,     // 厂商位图，内容不解析
+            PRESENT_ANTENNA,
+        ];
+        let fields = [
+            &[0x00, 0x00][..],                          // flags + 对齐填充
+            &[0x00, 0x11, 0x22, 0x00, 0x03, 0x00],      // OUI, 子命名空间, 跳过 3 字节
+            &[0xaa, 0xbb, 0xcc],                        // 厂商数据
+            &[0xb0, 0x02],                              // 天线 2: -80 dBm
+        ].concat();
+        let (info, _) = parse(&header(&present, &fields)).unwrap();
+        assert_eq!((info.flags, info.signal_dbm), (Some(0), None));
+        assert_eq!(info.antenna_signals, [(2, -80)]);
+    }
+
+    #[test]
+    fn test_unknown_field_stops_parsing() {
+        // 位 28 为 TLV，无法确定长度；之前的字段仍然返回
+        let (info, len) = parse(&header(&[1 << 1 | 1 << 28], &[0x10, 0xff, 0xff])).unwrap();
+        assert_eq!((info.flags, len), (Some(0x10), 11));
+    }
+
+    #[test]
+    fn test_truncated_field_stops_parsing() {
+        let (info, _) = parse(&header(&[1 << 1 | 1 << 3], &[0x10, 0x00, 0x6c])).unwrap();
+        assert_eq!((info.flags, info.channel_freq), (Some(0x10), None));
+    }
+}
@@ -0,0 +1,220 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::geo::{bearing_deg, degrees, distance_m};
+use crate::position;
+use crate::upload_data::UploadData;
+
+/// 接收站位置，车载时随 GPS 更新
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ReceiverPosition {
+    pub latitude: f64,               // 纬度 (度)
+    pub longitude: f64,              // 经度 (度)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub altitude_m: Option<f32>,     // 海拔高度 (米)，GPS 只有二维定位时为空
+}
+
+struct Fix {
+    position: ReceiverPosition,
+    updated: Instant,
+}
+
+#[derive(Default)]
+struct Locator {
+    /// `--site` 指定的固定位置，没有 GPS 定位时使用
+    site: Option<ReceiverPosition>,
+    fix: Option<Fix>,
+}
+
+/// GPS 定位超过该时长未更新视为失锁
+const FIX_TTL: Duration = Duration::from_secs(10);
+
+static LOCATOR: OnceLock<Mutex<Locator>> = OnceLock::new();
+
+fn locator() -> &'static Mutex<Locator> {
+    LOCATOR.get_or_init(|| Mutex::new(Locator::default()))
+}
+
+/// 启动时设置固定的接收站位置 (度)
+pub fn configure(site: Option<(f64, f64)>) {
+    locator().lock().unwrap().site = site.map(|(latitude, longitude)| ReceiverPosition { latitude, longitude, altitude_m: None });
+}
+
+/// GPS 报告的接收站位置
+pub fn observe(position: ReceiverPosition) {
+    locator().lock().unwrap().fix = Some(Fix { position, updated: Instant::now() });
+}
+
+/// 当前接收站位置：有效的 GPS 定位优先，否则为固定位置
+pub fn current() -> Option<ReceiverPosition> {
+    let locator = locator().lock().unwrap();
+    locator.fix.as_ref()
+        .filter(|fix| fix.updated.elapsed() < FIX_TTL)
+        .map(|fix| fix.position)
+        .or(locator.site)
+}
+
+/// 无人机相对记录中接收站位置的水平距离 (米) 和方位 (度, 正北顺时针)
+///
+/// 与控制站距离一样应在轨迹合并之后计算；接收站或无人机没有位置时为 None。
+pub fn relative_position(record: &UploadData) -> Option<(f32, f32)> {
+    let receiver = record.receiver.as_ref()?;
+    if !position::has_coordinates(record) {
+        return None;
+    }
+    let (lat, lon) = (degrees(record.latitude), degrees(record.longitude));
+    let distance = distance_m(receiver.latitude, receiver.longitude, lat, lon);
+    let bearing = bearing_deg(receiver.latitude, receiver.longitude, lat, lon);
+    Some((distance.round() as f32, ((bearing * 10.0).round() / 10.0).rem_euclid(360.0) as f32))
+}
+
+/// gpsd TPV 报告中的位置，至少需要二维定位 (mode ≥ 2)
+pub fn parse_tpv(line: &str) -> Option<ReceiverPosition> {
+    let report: serde_json::Value = serde_json::from_str(line).ok()?;
+    if report["class"] != "TPV" || report["mode"].as_u64().unwrap_or_default() < 2 {
+        return None;
+    }
+    Some(ReceiverPosition {
+        latitude: report["lat"].as_f64()?,
+        longitude: report["lon"].as_f64()?,
+        altitude_m: report["altMSL"].as_f64().or_else(|| report["alt"].as_f64()).map(|alt| alt as f32),
+    })
+}
+
+/// NMEA GGA 语句中的位置，校验和不符或未定位时为 None
+pub fn parse_nmea(line: &str) -> Option<ReceiverPosition> {
+    let (body, checksum) = line.trim().strip_prefix('$')?.split_once('*')?;
+    let expected = u8::from_str_radix(checksum.get(..2)?, 16).ok()?;
+    if body.bytes().fold(0, |sum, b| sum ^ b) != expected {
+        return None;
+    }
+    let fields: Vec<&str> = body.split(',').collect();
+    if fields.len() < 10 || !fields[0].ends_with("GGA") || matches!(fields[6], "" | "0") {
+        return None;
+    }
+    // ddmm.mmmm / dddmm.mmmm
+    let coordinate = |value: &str, hemisphere: &str, degree_digits: usize| -> Option<f64> {
+        let degrees: f64 = value.get(..degree_digits)?.parse().ok()?;
+        let minutes: f64 = value.get(degree_digits..)?.parse().ok()?;
+        let sign = if matches!(hemisphere, "S" | "W") { -1.0 } else { 1.0 };
+        Some(sign * (degrees + minutes / 60.0))
+    };
+    Some(ReceiverPosition {
+        latitude: coordinate(fields[2], fields[3], 2)?,
+        longitude: coordinate(fields[4], fields[5], 3)?,
+        altitude_m: fields[9].parse().ok(),
+    })
+}
+
+/// 从 NMEA 串口（如 /dev/ttyUSB0，波特率需事先用 stty 设置）读取接收站位置，断开后重新打开
+pub fn spawn_nmea_reader(path: PathBuf) {
+    thread::spawn(move || loop {
+        match File::open(&path) {
+            Ok(file) => {
+                info!("reading receiver position from {}", path.display());
+                for line in BufReader::new(file).lines() {
+                    let Ok(line) = line else { break };
+                    if let Some(position) = parse_nmea(&line) {
+                        observe(position);
+                    }
+                }
+                warn!("gps serial {} closed", path.display());
+            }
+            Err(e) => warn!("无法打开 GPS 串口 {}: {}", path.display(), e),
+        }
+        thread::sleep(Duration::from_secs(5));
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GGA: &str = "$GPGGA,123519,4807.038,N,01131.000,E,1,08,0.9,545.4,M,46.9,M,,*47";
+    const TPV: &str = r#"{"class":"TPV","mode":3,"lat":31.2,"lon":121.4,"altMSL":12.5}"#;
+
+    /// 加上校验和的 NMEA 语句
+    fn sentence(body: &str) -> String {
+        format!("${}*{:02X}", body, body.bytes().fold(0, |sum, b| sum ^ b))
+    }
+
+    #[test]
+    fn test_parse_nmea_gga() {
+        let position = parse_nmea(GGA).unwrap();
+        assert!((position.latitude - 48.1173).abs() < 1e-4);
+        assert!((position.longitude - 11.516_667).abs() < 1e-4);
+        assert_eq!(position.altitude_m, Some(545.4));
+    }
+
+    #[test]
+    fn test_parse_nmea_southern_western_hemisphere() {
+        let position = parse_nmea(&sentence("GNGGA,010203,3352.000,S,15112.000,W,2,10,0.8,,M,,M,,")).unwrap();
+        assert!((position.latitude + 33.866_667).abs() < 1e-4);
+        assert!((position.longitude + 151.2).abs() < 1e-4);
+        assert_eq!(position.altitude_m, None);
+    }
+
+    #[test]
+    fn test_parse_nmea_rejects_bad_checksum() {
+        assert_eq!(parse_nmea(&GGA.replace("*47", "*48")), None);
+        assert_eq!(parse_nmea(GGA.trim_end_matches("*47")), None);
+    }
+
+    #[test]
+    fn test_parse_nmea_rejects_no_fix_and_other_sentences() {
+        assert_eq!(parse_nmea("$GPGGA,123519,,,,,0,00,,,M,,M,,*66"), None);
+        assert_eq!(parse_nmea(&sentence("GPRMC,123519,A,4807.038,N,01131.000,E,022.4,084.4,230394,003.1,W")), None);
+    }
+
+    #[test]
+    fn test_parse_tpv() {
+        assert_eq!(parse_tpv(TPV), Some(ReceiverPosition { latitude: 31.2, longitude: 121.4, altitude_m: Some(12.5) }));
+        let hae_only = r#"{"class":"TPV","mode":3,"lat":31.2,"lon":121.4,"alt":20.0}"#;
+        assert_eq!(parse_tpv(hae_only).unwrap().altitude_m, Some(20.0));
+    }
+
+    #[test]
+    fn test_parse_tpv_requires_fix() {
+        assert_eq!(parse_tpv(r#"{"class":"TPV","mode":1}"#), None);
+        assert_eq!(parse_tpv(r#"{"class":"SKY","mode":3,"lat":31.2,"lon":121.4}"#), None);
+        assert_eq!(parse_tpv(r#"{"class":"TPV","mode":2,"lat":31.2}"#), None);
+        assert_eq!(parse_tpv("not json"), None);
+    }
+
+    #[test]
+    fn test_relative_position() {
+        // 无人机在接收站正北约 1.1 公里
+        let record = UploadData {
+            latitude: 312_100_000,
+            longitude: 1_214_000_000,
+            receiver: parse_tpv(TPV),
+            ..Default::default()
+        };
+        assert_eq!(relative_position(&record), Some((1112.0, 0.0)));
+        let west = UploadData { latitude: 312_000_000, longitude: 1_213_000_000, ..record.clone() };
+        assert_eq!(relative_position(&west).map(|(_, bearing)| bearing), Some(270.0));
+    }
+
+    #[test]
+    fn test_relative_position_needs_both_positions() {
+        let record = UploadData { latitude: 312_100_000, longitude: 1_214_000_000, receiver: parse_tpv(TPV), ..Default::default() };
+        assert_eq!(relative_position(&UploadData { receiver: None, ..record.clone() }), None);
+        assert_eq!(relative_position(&UploadData { latitude: 0, longitude: 0, ..record }), None);
+    }
+
+    #[test]
+    fn test_gps_fix_overrides_site() {
+        configure(Some((31.0, 121.0)));
+        assert_eq!(current().map(|p| (p.latitude, p.longitude)), Some((31.0, 121.0)));
+        observe(parse_tpv(TPV).unwrap());
+        assert_eq!(current(), parse_tpv(TPV));
+    }
+}
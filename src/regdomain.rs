@@ -0,0 +1,157 @@
+use serde::{Deserialize, Serialize};
+
+/// 管制域
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum RegulatoryDomain {
+    Cn,
+    Us,
+    Eu,
+    Jp,
+}
+
+const CH_2G_1_11: &[u8] = &[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11];
+const CH_2G_1_13: &[u8] = &[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13];
+const CH_2G_1_14: &[u8] = &[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14];
+const CH_5G_UNII1_2A: &[u8] = &[36, 40, 44, 48, 52, 56, 60, 64];
+const CH_5G_UNII2C_140: &[u8] = &[100, 104, 108, 112, 116, 120, 124, 128, 132, 136, 140];
+const CH_5G_UNII2C_144: &[u8] = &[100, 104, 108, 112, 116, 120, 124, 128, 132, 136, 140, 144];
+const CH_5G_UNII3: &[u8] = &[149, 153, 157, 161, 165];
+
+impl RegulatoryDomain {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_ascii_uppercase().as_str() {
+            "CN" => Some(Self::Cn),
+            "US" => Some(Self::Us),
+            "EU" => Some(Self::Eu),
+            "JP" => Some(Self::Jp),
+            _ => None,
+        }
+    }
+
+    /// 该管制域允许的 2.4 GHz 与 5 GHz 信道（20 MHz）
+    pub fn channels(&self) -> Vec<u8> {
+        let parts: &[&[u8]] = match self {
+            Self::Cn => &[CH_2G_1_13, CH_5G_UNII1_2A, CH_5G_UNII3],
+            Self::Us => &[CH_2G_1_11, CH_5G_UNII1_2A, CH_5G_UNII2C_144, CH_5G_UNII3],
+            Self::Eu => &[CH_2G_1_13, CH_5G_UNII1_2A, CH_5G_UNII2C_140],
+            Self::Jp => &[CH_2G_1_14, CH_5G_UNII1_2A, CH_5G_UNII2C_140],
+        };
+        parts.concat()
+    }
+
+    pub fn allows(&self, channel: u8) -> bool {
+        self.channels().contains(&channel)
+    }
+}
+
+/// 从 `iw phy <phy> info` 输出中解析网卡可用的信道，跳过标记为 disabled 的频点
+///
+/// 频点行形如 `* 2412 MHz [1] (20.0 dBm)`。
+pub fn parse_iw_channels(output: &str) -> Vec<u8> {
+    output.lines()
+        .map(str::trim)
+        .filter(|line| line.starts_with("* ") && line.contains(" MHz [") && !line.contains("(disabled)"))
+        .filter_map(|line| {
+            let start = line.find('[')? + 1;
+            let end = start + line[start..].find(']')?;
+            line[start..end].parse().ok()
+        })
+        .collect()
+}
+
+/// 校验信道列表，返回每个有问题信道的警告以及网卡可用的信道
+///
+/// 网卡不支持的信道无法切换，从结果中去掉；仅管制域不允许的信道只给出警告，
+/// 因为监听不发射，是否使用由用户决定。`supported` 为 None 表示无法获取网卡能力。
+pub fn validate_plan(plan: &[u8], domain: Option<RegulatoryDomain>, supported: Option<&[u8]>)
+    -> (Vec<u8>, Vec<String>)
+{
+    let mut usable = Vec::new();
+    let mut warnings = Vec::new();
+    for &channel in plan {
+        if let Some(domain) = domain
+            && !domain.allows(channel)
+        {
+            warnings.push(format!("信道 {} 不在管制域 {:?} 的信道表中", channel, domain));
+        }
+        if let Some(supported) = supported
+            && !supported.contains(&channel)
+        {
+            warnings.push(format!("网卡不支持信道 {}，已忽略", channel));
+            continue;
+        }
+        usable.push(channel);
+    }
+    (usable, warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IW_OUTPUT: &str = "\
+            Frequencies:
+                    * 2412 MHz [1] (20.0 dBm)
+                    * 2437 MHz [6] (20.0 dBm)
+                    * 2462 MHz [11] (20.0 dBm)
+                    * 2484 MHz [14] (disabled)
+                    * 5745 MHz [149] (30.0 dBm)";
+
+    #[test]
+    fn test_parse_domain_case_insensitive() {
+        assert_eq!(RegulatoryDomain::parse("cn"), Some(RegulatoryDomain::Cn));
+        assert_eq!(RegulatoryDomain::parse("Us"), Some(RegulatoryDomain::Us));
+        assert_eq!(RegulatoryDomain::parse("EU"), Some(RegulatoryDomain::Eu));
+        assert_eq!(RegulatoryDomain::parse("jp"), Some(RegulatoryDomain::Jp));
+        assert_eq!(RegulatoryDomain::parse("DE"), None);
+    }
+
+    #[test]
+    fn test_domain_channel_tables() {
+        assert!(RegulatoryDomain::Jp.allows(14));
+        assert!(!RegulatoryDomain::Cn.allows(14));
+        assert!(!RegulatoryDomain::Us.allows(12));
+        assert!(RegulatoryDomain::Us.allows(144));
+        assert!(!RegulatoryDomain::Eu.allows(144));
+        assert!(!RegulatoryDomain::Cn.allows(100));
+        assert!(RegulatoryDomain::Cn.allows(165));
+        assert!(!RegulatoryDomain::Eu.allows(149));
+    }
+
+    #[test]
+    fn test_parse_iw_channels_skips_disabled() {
+        assert_eq!(parse_iw_channels(IW_OUTPUT), vec![1, 6, 11, 149]);
+    }
+
+    #[test]
+    fn test_parse_iw_channels_ignores_other_lines() {
+        let output = "\
+            * short GI for 40 MHz
+            * 2412 MHz [x] (20.0 dBm)
+            Band 1:";
+        assert!(parse_iw_channels(output).is_empty());
+    }
+
+    #[test]
+    fn test_validate_plan_drops_unsupported_channels() {
+        let supported = parse_iw_channels(IW_OUTPUT);
+        let (usable, warnings) = validate_plan(&[1, 6, 14, 149], Some(RegulatoryDomain::Eu), Some(&supported));
+        assert_eq!(usable, vec![1, 6, 149]);
+        assert_eq!(warnings.len(), 3);  // 14: 管制域 + 网卡; 149: 管制域
+    }
+
+    #[test]
+    fn test_validate_plan_keeps_channels_outside_domain() {
+        let (usable, warnings) = validate_plan(&[1, 149], Some(RegulatoryDomain::Eu), None);
+        assert_eq!(usable, vec![1, 149]);
+        assert_eq!(warnings, vec!["信道 149 不在管制域 Eu 的信道表中".to_string()]);
+    }
+
+    #[test]
+    fn test_validate_plan_without_constraints() {
+        let (usable, warnings) = validate_plan(&[1, 200], None, None);
+        assert_eq!(usable, vec![1, 200]);
+        assert!(warnings.is_empty());
+    }
+}
@@ -0,0 +1,152 @@
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use reqwest::blocking::Client;
+use ring::signature::{UnparsedPublicKey, ED25519};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info};
+
+use crate::config::Config;
+use crate::control::RuntimeControl;
+use crate::remote_id::from_hex;
+
+/// 远程配置拉取：定期从 HTTPS 地址下载配置，验证签名后热更新
+///
+/// 签名为对配置文件原始字节的 Ed25519 签名（十六进制），与配置放在同一地址加 `.sig` 后缀处。
+///
+/// ```toml
+/// [remote_config]
+/// url = "https://fleet.example.com/sensors/roof-2.toml"
+/// public_key = "3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c"
+/// interval_minutes = 15
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RemoteConfig {
+    pub url: String,
+    /// Ed25519 公钥（32 字节，十六进制）
+    pub public_key: String,
+    #[serde(default = "default_interval_minutes")]
+    pub interval_minutes: u64,
+}
+
+fn default_interval_minutes() -> u64 {
+    15
+}
+
+/// 校验配置文件的 Ed25519 签名
+pub fn verify(body: &[u8], signature_hex: &str, public_key_hex: &str) -> Result<(), String> {
+    let public_key = from_hex(public_key_hex.trim()).ok_or("公钥不是有效的十六进制")?;
+    let signature = from_hex(signature_hex.trim()).ok_or("签名不是有效的十六进制")?;
+    UnparsedPublicKey::new(&ED25519, public_key)
+        .verify(body, &signature)
+        .map_err(|_| "签名校验失败".to_string())
+}
+
+/// 下载配置及其签名，校验通过后返回配置文本
+pub fn fetch(client: &Client, remote: &RemoteConfig) -> Result<String, String> {
+    if !remote.url.starts_with("https://") {
+        return Err(format!("远程配置地址必须使用 HTTPS: {}", remote.url));
+    }
+    let get = |url: &str| client.get(url).send()
+        .and_then(|r| r.error_for_status())
+        .and_then(|r| r.text())
+        .map_err(|e| format!("下载 {} 失败: {}", url, e));
+    let body = get(&remote.url)?;
+    let signature = get(&format!("{}.sig", remote.url))?;
+    verify(body.as_bytes(), &signature, &remote.public_key)?;
+    Ok(body)
+}
+
+/// 启动后台线程定期拉取配置，内容变化且签名有效时交给解码线程热更新
+pub fn spawn_puller(remote: RemoteConfig, control: Arc<RuntimeControl>) {
+    thread::spawn(move || {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .unwrap();
+        let mut applied = String::new();
+        loop {
+            match fetch(&client, &remote) {
+                Ok(body) if body != applied => match toml::from_str::<Config>(&body).map_err(|e| e.to_string())
+                    .and_then(|mut config| config.apply_env(std::env::vars()).map(|_| config))
+                {
+                    Ok(config) => {
+                        info!("fetched new configuration from {}", remote.url);
+                        control.offer_config(config);
+                        applied = body;
+                    }
+                    Err(e) => error!("远程配置格式错误: {}", e),
+                },
+                Ok(_) => {}
+                Err(e) => error!("{}", e),
+            }
+            thread::sleep(Duration::from_secs(remote.interval_minutes.max(1) * 60));
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ring::signature::{Ed25519KeyPair, KeyPair};
+
+    use crate::remote_id::to_hex;
+
+    const BODY: &[u8] = b"sensor_id = \"roof-2\"\n";
+
+    fn key(seed: u8) -> Ed25519KeyPair {
+        Ed25519KeyPair::from_seed_unchecked(&[seed; 32]).unwrap()
+    }
+
+    fn public_key(key: &Ed25519KeyPair) -> String {
+        to_hex(key.public_key().as_ref())
+    }
+
+    #[test]
+    fn test_verify_signature() {
+        let key = key(7);
+        let signature = to_hex(key.sign(BODY).as_ref());
+        assert_eq!(verify(BODY, &signature, &public_key(&key)), Ok(()));
+    }
+
+    #[test]
+    fn test_verify_trims_signature_file() {
+        let key = key(7);
+        let signature = format!("{}\n", to_hex(key.sign(BODY).as_ref()));
+        assert_eq!(verify(BODY, &signature, &format!(" {}", public_key(&key))), Ok(()));
+    }
+
+    #[test]
+    fn test_verify_rejects_tampered_body() {
+        let key = key(7);
+        let signature = to_hex(key.sign(BODY).as_ref());
+        assert_eq!(verify(b"sensor_id = \"evil\"\n", &signature, &public_key(&key)), Err("签名校验失败".into()));
+    }
+
+    #[test]
+    fn test_verify_rejects_other_key() {
+        let signature = to_hex(key(7).sign(BODY).as_ref());
+        assert!(verify(BODY, &signature, &public_key(&key(8))).is_err());
+    }
+
+    #[test]
+    fn test_verify_rejects_bad_hex() {
+        let key = key(7);
+        let signature = to_hex(key.sign(BODY).as_ref());
+        assert_eq!(verify(BODY, "zz", &public_key(&key)), Err("签名不是有效的十六进制".into()));
+        assert_eq!(verify(BODY, &signature, "xyz"), Err("公钥不是有效的十六进制".into()));
+    }
+
+    #[test]
+    fn test_fetch_requires_https() {
+        let remote = RemoteConfig { url: "http://example.com/a.toml".into(), public_key: public_key(&key(7)), interval_minutes: 15 };
+        assert!(fetch(&Client::new(), &remote).unwrap_err().contains("HTTPS"));
+    }
+
+    #[test]
+    fn test_config_default_interval() {
+        let remote: RemoteConfig = toml::from_str("url = \"https://a/b.toml\"\npublic_key = \"00\"\n").unwrap();
+        assert_eq!(remote.interval_minutes, 15);
+    }
+}
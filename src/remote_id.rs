@@ -0,0 +1,202 @@
+use std::fmt;
+use std::sync::OnceLock;
+
+use libwifi::frame::components::VendorSpecificInfo;
+
+/// Remote ID 厂商 IE 的元素 ID 与 OUI 类型
+pub const VENDOR_ELEMENT_ID: u8 = 221;
+pub const ODID_OUI_TYPE: u8 = 0x0D;
+/// ASTM F3411 / ASD-STAN Remote ID 厂商 IE 的 OUI
+pub const ODID_OUI: [u8; 3] = [0xfa, 0x0b, 0xbc];
+
+static EXTRA_OUIS: OnceLock<Vec<[u8; 3]>> = OnceLock::new();
+
+/// 启动时设置一次其它 Remote ID 厂商 IE 的 OUI（如国家标准使用的 OUI），OUI 类型同为 0x0D
+pub fn configure_ouis(ouis: Vec<[u8; 3]>) {
+    let _ = EXTRA_OUIS.set(ouis);
+}
+
+/// 识别为 Remote ID 的全部 OUI
+pub fn rid_ouis() -> impl Iterator<Item = [u8; 3]> {
+    std::iter::once(ODID_OUI).chain(EXTRA_OUIS.get().into_iter().flatten().copied())
+}
+
+/// 负载头长度: 消息计数器(1) + 消息包头(1) + 消息大小(1) + 消息数量(1)
+pub const PAYLOAD_HEADER_LEN: usize = 4;
+
+/// 消息包中单条消息的固定长度
+pub const MESSAGE_SIZE: usize = 25;
+
+/// Remote ID 负载错误
+#[derive(Debug, PartialEq)]
+pub enum PayloadError {
+    MalformedPack(String, Vec<u8>),  // 原因, 原始负载
+}
+
+impl std::error::Error for PayloadError {}
+impl fmt::Display for PayloadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PayloadError::MalformedPack(reason, payload) =>
+                write!(f, "消息包格式错误: {}, 数据: {}", reason, hex_dump(payload)),
+        }
+    }
+}
+
+/// 连续的十六进制字符串，用于输出记录中的原始字节
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// 以冒号分隔的小写 MAC 地址，用于输出记录中的发射端地址
+pub fn format_mac(mac: &[u8; 6]) -> String {
+    mac.iter().map(|b| format!("{:02x}", b)).collect::<Vec<_>>().join(":")
+}
+
+/// 解析连续的十六进制字符串，长度为奇数或含非十六进制字符时返回 None
+pub fn from_hex(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..text.len()).step_by(2)
+        .map(|i| text.get(i..i + 2).and_then(|b| u8::from_str_radix(b, 16).ok()))
+        .collect()
+}
+
+/// Remote ID UA 类型名称及图层颜色
+pub fn ua_type_style(ua_type: Option<u8>) -> (&'static str, &'static str) {
+    match ua_type {
+        Some(1) => ("aeroplane", "#1f77b4"),
+        Some(2) => ("multirotor", "#d62728"),
+        Some(3) => ("gyroplane", "#9467bd"),
+        Some(4) => ("hybrid_vtol", "#ff7f0e"),
+        Some(5) => ("ornithopter", "#8c564b"),
+        Some(6) => ("glider", "#2ca02c"),
+        Some(7) => ("kite", "#bcbd22"),
+        Some(8) | Some(9) => ("balloon", "#17becf"),
+        Some(10) => ("airship", "#17becf"),
+        Some(11) => ("parachute", "#e377c2"),
+        Some(12) => ("rocket", "#000000"),
+        Some(13) => ("tethered", "#7f7f7f"),
+        Some(14) => ("ground_obstacle", "#7f7f7f"),
+        _ => ("unknown", "#ff00ff"),
+    }
+}
+
+/// 以空格分隔的十六进制字符串，用于日志
+pub fn hex_dump(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect::<Vec<_>>().join(" ")
+}
+
+pub fn is_remote_id_element(ie: &VendorSpecificInfo) -> bool {
+    ie.element_id == VENDOR_ELEMENT_ID && ie.oui_type == ODID_OUI_TYPE && rid_ouis().any(|oui| oui == ie.oui)
+}
+
+/// 快速判断帧中是否可能含 Remote ID 厂商 IE：只查找 OUI 加 OUI 类型的 4 字节签名，不解析 IE 结构
+///
+/// 返回 false 时帧中一定没有 Remote ID 厂商 IE，可以跳过完整解析。
+pub fn has_rid_signature(frame: &[u8]) -> bool {
+    rid_ouis().any(|oui| {
+        let signature = [oui[0], oui[1], oui[2], ODID_OUI_TYPE];
+        frame.windows(signature.len()).any(|window| window == signature)
+    })
+}
+
+/// 根据负载头计算完整负载应有的长度
+fn declared_len(payload: &[u8]) -> Option<usize> {
+    if payload.len() < PAYLOAD_HEADER_LEN {
+        return None;
+    }
+    Some(PAYLOAD_HEADER_LEN + payload[2] as usize * payload[3] as usize)
+}
+
+/// 重组一帧中的第一个 Remote ID 负载，帧中不含 Remote ID IE 时返回 None，见 [`reassemble_all`]
+pub fn reassemble(vendor_specific: &[VendorSpecificInfo]) -> Option<Vec<u8>> {
+    reassemble_all(vendor_specific).into_iter().next()
+}
+
+/// 重组一帧中的全部 Remote ID 负载
+///
+/// 依次检查每个厂商 IE，不要求 Remote ID IE 排在最前。消息包超过单个 IE 的长度上限时，
+/// 发送端会拆分到多个连续的厂商 IE 中：以负载头计算总长度，拼接后续 IE 的数据直到达到
+/// 声明长度；之后的 Remote ID IE 开始一个新的负载。完全相同的负载只保留一个。
+pub fn reassemble_all(vendor_specific: &[VendorSpecificInfo]) -> Vec<Vec<u8>> {
+    let mut payloads: Vec<Vec<u8>> = Vec::new();
+    let mut expected = None;
+    for ie in vendor_specific.iter().filter(|ie| is_remote_id_element(ie)) {
+        match (payloads.last_mut(), expected) {
+            (Some(payload), Some(len)) if payload.len() < len => payload.extend_from_slice(&ie.data),
+            _ => {
+                expected = declared_len(&ie.data);
+                payloads.push(ie.data.clone());
+            }
+        }
+    }
+    let mut unique: Vec<Vec<u8>> = Vec::with_capacity(payloads.len());
+    for mut payload in payloads {
+        if let Some(len) = declared_len(&payload) {
+            payload.truncate(len);
+        }
+        if !unique.contains(&payload) {
+            unique.push(payload);
+        }
+    }
+    unique
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn element(oui: [u8; 3], data: &[u8]) -> VendorSpecificInfo {
+        VendorSpecificInfo { element_id: VENDOR_ELEMENT_ID, length: data.len() as u8 + 4, oui, oui_type: ODID_OUI_TYPE, data: data.to_vec() }
+    }
+
+    #[test]
+    fn test_other_vendor_elements_are_skipped() {
+        let payload = [0x07, 0xf1, 0x02, 0x01, 0xaa, 0xbb];
+        let elements = [element([0x00, 0x50, 0xf2], &[0x01, 0x02]), element(ODID_OUI, &payload)];
+        assert_eq!(reassemble_all(&elements), vec![payload.to_vec()]);
+        assert_eq!(reassemble(&elements[..1]), None);
+    }
+
+    #[test]
+    fn test_payload_split_across_elements() {
+        let first = [0x07, 0xf1, 0x02, 0x01, 0xaa, 0xbb];
+        let second = [0x08, 0xf1, 0x02, 0x01, 0xcc, 0xdd];
+        // 第一个负载拆分在两个 IE 中，达到声明长度后的 IE 开始新的负载
+        let elements = [element(ODID_OUI, &first[..4]), element(ODID_OUI, &first[4..]), element(ODID_OUI, &second)];
+        assert_eq!(reassemble_all(&elements), vec![first.to_vec(), second.to_vec()]);
+    }
+
+    #[test]
+    fn test_duplicate_payloads_are_dropped() {
+        let payload = [0x07, 0xf1, 0x02, 0x01, 0xaa, 0xbb];
+        let elements = [element(ODID_OUI, &payload), element(ODID_OUI, &payload)];
+        assert_eq!(reassemble_all(&elements), vec![payload.to_vec()]);
+    }
+
+    #[test]
+    fn test_payload_is_cut_to_declared_length() {
+        let payload = [0x07, 0xf1, 0x02, 0x01, 0xaa, 0xbb, 0x00, 0x00];
+        assert_eq!(reassemble(&[element(ODID_OUI, &payload)]), Some(payload[..6].to_vec()));
+        // 负载头不完整时原样返回
+        assert_eq!(reassemble(&[element(ODID_OUI, &[0x07, 0xf1])]), Some(vec![0x07, 0xf1]));
+    }
+
+    #[test]
+    fn test_element_oui_and_type() {
+        assert!(is_remote_id_element(&element(ODID_OUI, &[])));
+        assert!(!is_remote_id_element(&element([0x00, 0x50, 0xf2], &[])));
+        let mut other_type = element(ODID_OUI, &[]);
+        other_type.oui_type = 0x0c;
+        assert!(!is_remote_id_element(&other_type));
+    }
+
+    #[test]
+    fn test_rid_signature() {
+        assert!(!has_rid_signature(&[0x01, 0x02, 0x03, ODID_OUI_TYPE]));
+        assert!(!has_rid_signature(&[0xfa, 0x0b, 0xbc, 0x0c]));
+        assert!(has_rid_signature(&[0x00, 0xfa, 0x0b, 0xbc, ODID_OUI_TYPE]));
+    }
+}
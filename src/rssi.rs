@@ -0,0 +1,245 @@
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// 对数距离路径损耗模型: RSSI = P0 - 10·n·log10(d)
+///
+/// ```toml
+/// [path_loss]
+/// reference_rssi_dbm = -40.0   # 1 米处的参考信号强度
+/// exponent = 2.7               # 开阔地约 2，城区约 3
+/// ```
+///
+/// 命令行 `--path-loss P0:n` 优先于配置文件。
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PathLossModel {
+    pub reference_rssi_dbm: f32,   // 1 米处的参考信号强度 P0
+    pub exponent: f32,             // 路径损耗指数 n (自由空间为 2)
+}
+
+impl Default for PathLossModel {
+    fn default() -> Self {
+        Self { reference_rssi_dbm: -40.0, exponent: 2.7 }
+    }
+}
+
+impl PathLossModel {
+    /// 从 "P0:n" 形式解析，例如 "-40:2.7"
+    pub fn parse(s: &str) -> Option<Self> {
+        let (p0, n) = s.split_once(':')?;
+        Some(Self { reference_rssi_dbm: p0.parse().ok()?, exponent: n.parse().ok()? })
+    }
+
+    pub fn estimate_range_m(&self, rssi_dbm: f32) -> f32 {
+        10f32.powf((self.reference_rssi_dbm - rssi_dbm) / (10.0 * self.exponent))
+    }
+}
+
+/// 粗略距离区间
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RangeBin {
+    Under50m,
+    Under200m,
+    Under500m,
+    Under1km,
+    Over1km,
+}
+
+impl RangeBin {
+    pub fn from_range_m(range: f32) -> Self {
+        match range {
+            r if r < 50.0 => Self::Under50m,
+            r if r < 200.0 => Self::Under200m,
+            r if r < 500.0 => Self::Under500m,
+            r if r < 1000.0 => Self::Under1km,
+            _ => Self::Over1km,
+        }
+    }
+}
+
+/// 信号强度变化趋势
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RssiTrend {
+    Approaching,   // 信号增强
+    Receding,      // 信号减弱
+    Steady,
+}
+
+/// 单架无人机的 RSSI 滚动历史
+pub struct RssiHistory {
+    samples: VecDeque<(Instant, f32)>,
+}
+
+impl RssiHistory {
+    /// 趋势判定的斜率阈值 (dB/s)
+    const TREND_THRESHOLD: f32 = 0.5;
+    const MIN_SAMPLES: usize = 5;
+
+    fn push(&mut self, now: Instant, rssi: f32, window: Duration) {
+        self.samples.push_back((now, rssi));
+        while self.samples.front().is_some_and(|(t, _)| now.duration_since(*t) > window) {
+            self.samples.pop_front();
+        }
+    }
+
+    /// 以最小二乘斜率判断趋势，样本不足时返回 None
+    pub fn trend(&self) -> Option<RssiTrend> {
+        if self.samples.len() < Self::MIN_SAMPLES {
+            return None;
+        }
+        let t0 = self.samples.front()?.0;
+        let points: Vec<(f32, f32)> = self.samples.iter()
+            .map(|(t, rssi)| (t.duration_since(t0).as_secs_f32(), *rssi))
+            .collect();
+        let n = points.len() as f32;
+        let mean_t = points.iter().map(|p| p.0).sum::<f32>() / n;
+        let mean_r = points.iter().map(|p| p.1).sum::<f32>() / n;
+        let var_t: f32 = points.iter().map(|p| (p.0 - mean_t).powi(2)).sum();
+        if var_t == 0.0 {
+            return Some(RssiTrend::Steady);
+        }
+        let slope = points.iter().map(|p| (p.0 - mean_t) * (p.1 - mean_r)).sum::<f32>() / var_t;
+        Some(match slope {
+            s if s > Self::TREND_THRESHOLD => RssiTrend::Approaching,
+            s if s < -Self::TREND_THRESHOLD => RssiTrend::Receding,
+            _ => RssiTrend::Steady,
+        })
+    }
+
+    /// 窗口内的平均 RSSI，用于平滑距离估计
+    pub fn mean(&self) -> Option<f32> {
+        if self.samples.is_empty() {
+            return None;
+        }
+        Some(self.samples.iter().map(|(_, r)| r).sum::<f32>() / self.samples.len() as f32)
+    }
+}
+
+/// 单次观测后的 RSSI 概况
+#[derive(Debug, Clone, Copy)]
+pub struct RssiSummary {
+    pub trend: Option<RssiTrend>,
+    pub estimated_range_m: f32,
+    pub range_bin: RangeBin,
+}
+
+/// 按轨迹维护 RSSI 历史
+pub struct RssiTracker {
+    model: PathLossModel,
+    window: Duration,
+    histories: HashMap<String, RssiHistory>,
+}
+
+impl RssiTracker {
+    pub fn new(model: PathLossModel, window: Duration) -> Self {
+        Self { model, window, histories: HashMap::new() }
+    }
+
+    pub fn observe(&mut self, track_id: &str, rssi: f32) -> RssiSummary {
+        let now = Instant::now();
+        let window = self.window;
+        self.histories.retain(|_, h| h.samples.back().is_some_and(|(t, _)| now.duration_since(*t) <= window));
+        let history = self.histories.entry(track_id.to_string())
+            .or_insert_with(|| RssiHistory { samples: VecDeque::new() });
+        history.push(now, rssi, window);
+        let estimated_range_m = self.model.estimate_range_m(history.mean().unwrap_or(rssi));
+        RssiSummary {
+            trend: history.trend(),
+            estimated_range_m,
+            range_bin: RangeBin::from_range_m(estimated_range_m),
+        }
+    }
+}
+
+impl Default for RssiTracker {
+    fn default() -> Self {
+        Self::new(PathLossModel::default(), Duration::from_secs(30))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 每秒一个样本的历史
+    fn history(rssi: &[f32]) -> RssiHistory {
+        let t0 = Instant::now();
+        let mut history = RssiHistory { samples: VecDeque::new() };
+        for (i, &r) in rssi.iter().enumerate() {
+            history.push(t0 + Duration::from_secs(i as u64), r, Duration::from_secs(30));
+        }
+        history
+    }
+
+    #[test]
+    fn test_path_loss_range() {
+        let model = PathLossModel::parse("-40:2").unwrap();
+        assert!((model.estimate_range_m(-40.0) - 1.0).abs() < 1e-3);
+        assert!((model.estimate_range_m(-80.0) - 100.0).abs() < 1e-1);
+    }
+
+    #[test]
+    fn test_path_loss_parse_rejects_malformed() {
+        assert!(PathLossModel::parse("-40").is_none());
+        assert!(PathLossModel::parse("-40:n").is_none());
+        assert!(PathLossModel::parse("p0:2").is_none());
+    }
+
+    #[test]
+    fn test_path_loss_config() {
+        let model: PathLossModel = toml::from_str("exponent = 3.0").unwrap();
+        assert_eq!(model, PathLossModel { reference_rssi_dbm: -40.0, exponent: 3.0 });
+        assert!(toml::from_str::<PathLossModel>("gain = 1.0").is_err());
+    }
+
+    #[test]
+    fn test_range_bins() {
+        assert_eq!(RangeBin::from_range_m(49.9), RangeBin::Under50m);
+        assert_eq!(RangeBin::from_range_m(50.0), RangeBin::Under200m);
+        assert_eq!(RangeBin::from_range_m(200.0), RangeBin::Under500m);
+        assert_eq!(RangeBin::from_range_m(999.0), RangeBin::Under1km);
+        assert_eq!(RangeBin::from_range_m(1000.0), RangeBin::Over1km);
+    }
+
+    #[test]
+    fn test_trend_needs_min_samples() {
+        assert_eq!(history(&[-80.0, -70.0, -60.0, -50.0]).trend(), None);
+    }
+
+    #[test]
+    fn test_trend_by_slope() {
+        assert_eq!(history(&[-80.0, -78.0, -76.0, -74.0, -72.0]).trend(), Some(RssiTrend::Approaching));
+        assert_eq!(history(&[-60.0, -62.0, -64.0, -66.0, -68.0]).trend(), Some(RssiTrend::Receding));
+        assert_eq!(history(&[-70.0, -71.0, -70.0, -69.0, -70.0]).trend(), Some(RssiTrend::Steady));
+    }
+
+    #[test]
+    fn test_history_drops_samples_outside_window() {
+        let t0 = Instant::now();
+        let mut history = RssiHistory { samples: VecDeque::new() };
+        history.push(t0, -90.0, Duration::from_secs(10));
+        history.push(t0 + Duration::from_secs(5), -70.0, Duration::from_secs(10));
+        history.push(t0 + Duration::from_secs(11), -50.0, Duration::from_secs(10));
+        assert_eq!(history.samples.len(), 2);
+        assert_eq!(history.mean(), Some(-60.0));
+    }
+
+    #[test]
+    fn test_tracker_smooths_range_per_track() {
+        let mut tracker = RssiTracker::new(PathLossModel::parse("-40:2").unwrap(), Duration::from_secs(30));
+        tracker.observe("A", -60.0);
+        let summary = tracker.observe("A", -100.0);
+        // 平均 -80 dBm 对应 100 米
+        assert!((summary.estimated_range_m - 100.0).abs() < 1e-1);
+        assert_eq!(summary.range_bin, RangeBin::Under200m);
+        assert_eq!(summary.trend, None);
+
+        let other = tracker.observe("B", -40.0);
+        assert!((other.estimated_range_m - 1.0).abs() < 1e-3);
+    }
+}
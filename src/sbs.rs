@@ -0,0 +1,185 @@
+use std::collections::HashSet;
+use std::io::Write;
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use tracing::{error, info};
+
+use crate::event_log::DecodedEvent;
+use crate::geo::degrees;
+use crate::time_format;
+
+const FEET_PER_METER: f64 = 3.280_84;
+
+/// 由 UAS ID 派生的 24 位伪 ICAO 地址（6 位十六进制），同一 ID 始终相同
+pub fn hex_ident(uas_id: &str) -> String {
+    let hash = uas_id.bytes().fold(0x811c_9dc5u32, |h, b| (h ^ b as u32).wrapping_mul(0x0100_0193));
+    format!("{:06X}", hash & 0x00ff_ffff)
+}
+
+/// BaseStation 呼号字段最多 8 个字符，取 UAS ID 末尾部分（序列号尾部区分度最高）
+fn callsign(uas_id: &str) -> String {
+    let chars: Vec<char> = uas_id.chars().filter(|c| c.is_ascii_alphanumeric()).collect();
+    chars[chars.len().saturating_sub(8)..].iter().collect()
+}
+
+/// 生成一条事件对应的 SBS-1 (BaseStation) 消息行
+///
+/// 首次出现的无人机先发 MSG,1 (识别)，之后每次更新发 MSG,3 (位置/高度) 与 MSG,4 (速度/航向)。
+/// 高度单位为英尺、速度为节、垂直速度为英尺/分钟，与 ADS-B 显示软件的约定一致。
+pub fn format_messages(event: &DecodedEvent, first_seen: bool) -> Vec<String> {
+    let r = &event.record;
+    let uas_id = if r.rid.is_empty() { &r.track_id } else { &r.rid };
+    let hex = hex_ident(uas_id);
+    let offset = time_format::output_timezone().offset_at(event.received_at_ms);
+    let time = DateTime::<Utc>::from_timestamp_millis(event.received_at_ms).unwrap_or_default().with_timezone(&offset);
+    let (date, clock) = (time.format("%Y/%m/%d"), time.format("%H:%M:%S%.3f"));
+    // MSG,类型,会话,飞行器,HexIdent,航班,生成日期,生成时间,记录日期,记录时间, 后跟 12 个数据字段
+    let header = |kind: u8| format!("MSG,{},1,1,{},1,{},{},{},{}", kind, hex, date, clock, date, clock);
+
+    let mut lines = Vec::new();
+    if first_seen && !uas_id.is_empty() {
+        lines.push(format!("{},{},,,,,,,,,,,", header(1), callsign(uas_id)));
+    }
+    if r.latitude != 0 || r.longitude != 0 {
+        lines.push(format!("{},,{:.0},,,{:.5},{:.5},,,0,0,0,0",
+            header(3),
+            r.geometric_altitude as f64 * FEET_PER_METER,
+            degrees(r.latitude),
+            degrees(r.longitude)));
+        lines.push(format!("{},,,{:.0},{},,,{:.0},,,,,0",
+            header(4),
+            r.ground_speed_knots(),
+            r.track_angle as u16 + if r.track_direction { 180 } else { 0 },
+            r.vertical_speed as f64 * FEET_PER_METER * 60.0));
+    }
+    lines
+}
+
+/// SBS-1 输出：监听 TCP 端口（常用 30003），向所有连接的客户端推送消息
+pub struct SbsServer {
+    clients: Arc<Mutex<Vec<TcpStream>>>,
+    seen: HashSet<String>,
+}
+
+impl SbsServer {
+    pub fn listen(addr: &str) -> std::io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        info!("sbs-1 output on tcp://{}", addr);
+        let clients = Arc::new(Mutex::new(Vec::new()));
+        let accepted = clients.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                match stream {
+                    Ok(stream) => {
+                        // 慢客户端不能阻塞解码线程
+                        let _ = stream.set_write_timeout(Some(Duration::from_millis(200)));
+                        info!("sbs-1 client connected: {:?}", stream.peer_addr().ok());
+                        accepted.lock().unwrap().push(stream);
+                    }
+                    Err(e) => error!("SBS-1 连接失败: {}", e),
+                }
+            }
+        });
+        Ok(Self { clients, seen: HashSet::new() })
+    }
+
+    pub fn publish(&mut self, event: &DecodedEvent) {
+        let r = &event.record;
+        let uas_id = if r.rid.is_empty() { &r.track_id } else { &r.rid };
+        let first_seen = self.seen.insert(uas_id.clone());
+        let text: String = format_messages(event, first_seen).iter().map(|l| format!("{}\r\n", l)).collect();
+        if text.is_empty() {
+            return;
+        }
+        // 写失败的客户端视为已断开
+        self.clients.lock().unwrap().retain_mut(|client| client.write_all(text.as_bytes()).is_ok());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::upload_data::UploadData;
+
+    fn event(rid: &str) -> DecodedEvent {
+        let record = UploadData {
+            rid: rid.into(),
+            latitude: 312_000_000,
+            longitude: 1_214_000_000,
+            geometric_altitude: 100,
+            ground_speed: 12,
+            track_angle: 90,
+            ..Default::default()
+        };
+        DecodedEvent { received_at_ms: 1_748_764_800_250, record }
+    }
+
+    #[test]
+    fn test_hex_ident_is_stable_six_digits() {
+        let hex = hex_ident("1581F5FKD229400A");
+        assert_eq!(hex, hex_ident("1581F5FKD229400A"));
+        assert_eq!(hex.len(), 6);
+        assert!(hex.chars().all(|c| c.is_ascii_hexdigit() && !c.is_ascii_lowercase()));
+        assert_ne!(hex, hex_ident("1581F5FKD229400B"));
+    }
+
+    #[test]
+    fn test_callsign_keeps_last_eight_alphanumerics() {
+        assert_eq!(callsign("1581F5FKD229400A"), "D229400A");
+        assert_eq!(callsign("CHN-OP-7"), "CHNOP7");
+        assert_eq!(callsign(""), "");
+    }
+
+    #[test]
+    fn test_identification_only_when_first_seen() {
+        let lines = format_messages(&event("1581F5FKD229400A"), true);
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("MSG,1,"));
+        assert!(lines[0].ends_with(",D229400A,,,,,,,,,,,"));
+        let lines = format_messages(&event("1581F5FKD229400A"), false);
+        assert!(lines.iter().map(|l| &l[..5]).eq(["MSG,3", "MSG,4"]));
+    }
+
+    #[test]
+    fn test_every_line_has_22_fields() {
+        assert!(format_messages(&event("1581F5FKD229400A"), true).iter().all(|l| l.split(',').count() == 22));
+    }
+
+    #[test]
+    fn test_header_time_fields() {
+        let line = &format_messages(&event("A"), false)[0];
+        let fields: Vec<&str> = line.split(',').collect();
+        assert_eq!(fields[4], hex_ident("A"));
+        assert_eq!(fields[6..10], ["2025/06/01", "08:00:00.250", "2025/06/01", "08:00:00.250"]);
+    }
+
+    #[test]
+    fn test_position_in_feet() {
+        let lines = format_messages(&event("A"), false);
+        assert!(lines[0].contains(",,328,,,31.20000,121.40000,,,0,0,0,0"), "{}", lines[0]);
+    }
+
+    #[test]
+    fn test_velocity_fields() {
+        let mut event = event("A");
+        assert!(format_messages(&event, false)[1].ends_with(",,,12,90,,,0,,,,,0"));
+        // 西向航迹加 180 度，速度乘数放大 10 倍，垂直速度换算为英尺/分钟
+        event.record.track_direction = true;
+        event.record.speed_multiplier = true;
+        event.record.vertical_speed = 2;
+        assert!(format_messages(&event, false)[1].ends_with(",,,120,270,,,394,,,,,0"));
+    }
+
+    #[test]
+    fn test_no_position_without_fix() {
+        let mut event = event("A");
+        event.record.latitude = 0;
+        event.record.longitude = 0;
+        assert_eq!(format_messages(&event, true).len(), 1);
+        assert!(format_messages(&event, false).is_empty());
+    }
+}
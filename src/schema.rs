@@ -0,0 +1,329 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use schemars::schema_for;
+use serde_json::{json, Value};
+
+use crate::upload_data::UploadData;
+
+/// 输出记录格式版本，字段发生不兼容变化时递增
+pub const FORMAT_VERSION: u32 = 1;
+
+/// 随代码发布的 JSON Schema（`schemas/upload_data.schema.json`），须与 [`output_schema`] 一致
+pub const JSON_SCHEMA: &str = include_str!("../schemas/upload_data.schema.json");
+
+/// 随代码发布的 protobuf 描述（`schemas/remote_id.proto`）
+pub const PROTO: &str = include_str!("../schemas/remote_id.proto");
+
+/// 所有输出记录类型的 JSON Schema
+pub fn output_schema() -> Value {
+    json!({
+        "format_version": FORMAT_VERSION,
+        "records": {
+            "upload_data": schema_for!(UploadData),
+        }
+    })
+}
+
+/// protobuf 描述中消息的一个字段
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProtoField {
+    pub name: String,
+    pub number: u32,
+    /// 标量类型、枚举名或消息名
+    pub kind: String,
+    pub repeated: bool,
+}
+
+/// 从 [`PROTO`] 解析出的消息和枚举定义，各输出格式据此得到字段编号和列顺序
+///
+/// 记录新增字段只需修改 `UploadData`、重新生成 JSON Schema 并在 proto 中追加编号，
+/// 各格式的编码自动包含该字段。
+#[derive(Debug, Default)]
+pub struct Registry {
+    messages: HashMap<String, Vec<ProtoField>>,
+    enums: HashMap<String, Vec<(String, i32)>>,
+}
+
+impl Registry {
+    /// 只解析本项目 proto 用到的语法：message、enum 和单行字段定义
+    pub fn parse(proto: &str) -> Self {
+        let mut registry = Self::default();
+        let mut current: Option<(bool, String)> = None;
+        for line in proto.lines() {
+            let line = line.split("//").next().unwrap_or_default().trim();
+            if let Some(header) = line.strip_suffix('{') {
+                current = match header.split_whitespace().collect::<Vec<_>>().as_slice() {
+                    ["message", name] => Some((false, name.to_string())),
+                    ["enum", name] => Some((true, name.to_string())),
+                    _ => None,
+                };
+                continue;
+            }
+            if line == "}" {
+                current = None;
+                continue;
+            }
+            let (Some((is_enum, owner)), Some((left, right))) = (&current, line.split_once('=')) else { continue };
+            let Ok(number) = right.trim().trim_end_matches(';').trim().parse::<i64>() else { continue };
+            let words: Vec<&str> = left.split_whitespace().collect();
+            if *is_enum {
+                if let [name] = words.as_slice() {
+                    registry.enums.entry(owner.clone()).or_default().push((name.to_string(), number as i32));
+                }
+                continue;
+            }
+            let (repeated, kind, name) = match words.as_slice() {
+                ["repeated", kind, name] => (true, kind, name),
+                ["optional", kind, name] | [kind, name] => (false, kind, name),
+                _ => continue,
+            };
+            registry.messages.entry(owner.clone()).or_default().push(ProtoField {
+                name: name.to_string(), number: number as u32, kind: kind.to_string(), repeated,
+            });
+        }
+        registry
+    }
+
+    pub fn message(&self, name: &str) -> Option<&[ProtoField]> {
+        self.messages.get(name).map(Vec::as_slice)
+    }
+
+    pub fn is_enum(&self, name: &str) -> bool {
+        self.enums.contains_key(name)
+    }
+
+    /// JSON 中的枚举值 (如 `remote_id`、`unknown`) 对应的编号；proto 中的值名为其大写形式，
+    /// 可带枚举名前缀 (如 `AUTHORIZATION_UNKNOWN`)
+    pub fn enum_value(&self, name: &str, value: &str) -> Option<i32> {
+        let upper = value.to_ascii_uppercase();
+        self.enums.get(name)?.iter()
+            .find(|(variant, _)| *variant == upper || variant.ends_with(&format!("_{}", upper)))
+            .map(|(_, number)| *number)
+    }
+}
+
+/// 随代码发布的 proto 的解析结果
+pub fn registry() -> &'static Registry {
+    static REGISTRY: OnceLock<Registry> = OnceLock::new();
+    REGISTRY.get_or_init(|| Registry::parse(PROTO))
+}
+
+/// 记录的顶层字段，按 proto 字段编号排序；CSV 等扁平格式以此为列顺序
+pub fn record_fields() -> Vec<&'static str> {
+    let mut fields: Vec<&ProtoField> = registry().message("UploadData").unwrap_or_default().iter().collect();
+    fields.sort_by_key(|field| field.number);
+    fields.into_iter().map(|field| field.name.as_str()).collect()
+}
+
+pub fn print_schema() {
+    println!("{}", serde_json::to_string_pretty(&output_schema()).unwrap());
+}
+
+/// 按 JSON Schema 校验一条记录，返回第一处不符合的位置
+///
+/// 只实现本项目 schema 用到的关键字：type、properties、required、
+/// additionalProperties、enum、oneOf、items、$ref。
+pub fn validate(value: &Value, schema: &Value, defs: &Value, path: &str) -> Result<(), String> {
+    if let Some(reference) = schema["$ref"].as_str() {
+        let name = reference.trim_start_matches("#/$defs/");
+        return validate(value, &defs[name], defs, path);
+    }
+    if let Some(options) = schema["oneOf"].as_array()
+        && !options.iter().any(|s| validate(value, s, defs, path).is_ok())
+    {
+        return Err(format!("{}: 不符合任何 oneOf 分支", path));
+    }
+    if let Some(allowed) = schema["enum"].as_array()
+        && !allowed.contains(value)
+    {
+        return Err(format!("{}: 取值 {} 不在枚举中", path, value));
+    }
+    let types: Vec<&str> = match &schema["type"] {
+        Value::String(t) => vec![t.as_str()],
+        Value::Array(ts) => ts.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    };
+    if !types.is_empty() && !types.iter().any(|t| type_matches(value, t)) {
+        return Err(format!("{}: 类型应为 {:?}，实际为 {}", path, types, value));
+    }
+    if let Value::Object(fields) = value {
+        for required in schema["required"].as_array().into_iter().flatten().filter_map(Value::as_str) {
+            if !fields.contains_key(required) {
+                return Err(format!("{}: 缺少字段 {}", path, required));
+            }
+        }
+        for (name, field) in fields {
+            match schema["properties"].get(name) {
+                Some(field_schema) => validate(field, field_schema, defs, &format!("{}.{}", path, name))?,
+                None if schema["additionalProperties"] == false || schema.get("properties").is_some() => {
+                    return Err(format!("{}: schema 中没有字段 {}", path, name));
+                }
+                None => {}
+            }
+        }
+    }
+    if let (Value::Array(items), Some(item_schema)) = (value, schema.get("items")) {
+        for (i, item) in items.iter().enumerate() {
+            validate(item, item_schema, defs, &format!("{}[{}]", path, i))?;
+        }
+    }
+    Ok(())
+}
+
+fn type_matches(value: &Value, expected: &str) -> bool {
+    match expected {
+        "null" => value.is_null(),
+        "boolean" => value.is_boolean(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "number" => value.is_number(),
+        "string" => value.is_string(),
+        "array" => value.is_array(),
+        "object" => value.is_object(),
+        _ => true,
+    }
+}
+
+/// 按随代码发布的 JSON Schema 校验一条输出记录
+pub fn check_record(record: &UploadData) -> Result<(), String> {
+    let schema: Value = serde_json::from_str(JSON_SCHEMA).map_err(|e| e.to_string())?;
+    let upload_data = &schema["records"]["upload_data"];
+    let value = serde_json::to_value(record).map_err(|e| e.to_string())?;
+    validate(&value, upload_data, &upload_data["$defs"], "upload_data")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message::accuracy::AccuracyBounds;
+    use crate::message::classification::{Classification, ClassificationRegion, UaCategory, UaLevel};
+    use crate::upload_data::OperatorPosition;
+
+    const SAMPLE_PROTO: &str = "\
+syntax = \"proto3\";
+
+enum Mode {
+  MODE_UNKNOWN = 0;
+  MODE_REMOTE_ID = 1;  // 带前缀
+}
+
+message Track {
+  string id = 1;
+  optional double rssi = 3;  // 跳号
+  repeated Point points = 2;
+  Mode mode = 4;
+}
+";
+
+    fn check(value: Value, schema: Value) -> Result<(), String> {
+        validate(&value, &schema, &schema["$defs"], "r")
+    }
+
+    fn sample_record() -> UploadData {
+        UploadData {
+            format_version: FORMAT_VERSION,
+            rid: "1581F5FKD229400A".into(),
+            rssi: Some(-62.0),
+            ua_type: Some(2),
+            operator: Some(OperatorPosition { location_type: 1, latitude: 1, longitude: 2, altitude: 3 }),
+            classification: Some(Classification {
+                region: ClassificationRegion::from_code(2),
+                category: UaCategory::from_code(1),
+                level: UaLevel::from_code(ClassificationRegion::from_code(2), 2),
+            }),
+            accuracy_bounds: AccuracyBounds::from_codes(10, 4, 3, 5),
+            raw_messages: vec!["00".into()],
+            ..Default::default()
+        }
+    }
+
+    /// schema 文件过期时，用 `wifi-capture --print-schema > schemas/upload_data.schema.json` 重新生成，
+    /// 并同步修改 remote_id.proto
+    #[test]
+    fn test_shipped_json_schema_is_current() {
+        let shipped: Value = serde_json::from_str(JSON_SCHEMA).unwrap();
+        assert_eq!(shipped, output_schema(), "schemas/upload_data.schema.json 已过期");
+    }
+
+    #[test]
+    fn test_shipped_proto_covers_record_fields() {
+        let shipped: Value = serde_json::from_str(JSON_SCHEMA).unwrap();
+        let properties = shipped["records"]["upload_data"]["properties"].as_object().unwrap();
+        let fields = record_fields();
+        for field in properties.keys() {
+            assert!(fields.contains(&field.as_str()), "remote_id.proto 缺少字段 {}", field);
+        }
+        assert_eq!(fields[..2], ["format_version", "record_id"]);
+        assert_eq!(registry().enum_value("AuthorizationStatus", "unknown"), Some(2));
+        assert_eq!(registry().enum_value("TimeSource", "remote_id"), Some(2));
+        let pages = &registry().message("Authentication").unwrap()[4];
+        assert_eq!((pages.name.as_str(), pages.kind.as_str(), pages.repeated), ("pages", "AuthPage", true));
+    }
+
+    #[test]
+    fn test_registry_parses_message_fields() {
+        let registry = Registry::parse(SAMPLE_PROTO);
+        let fields = registry.message("Track").unwrap();
+        let summary: Vec<_> = fields.iter().map(|f| (f.name.as_str(), f.number, f.kind.as_str(), f.repeated)).collect();
+        assert_eq!(summary, [
+            ("id", 1, "string", false),
+            ("rssi", 3, "double", false),
+            ("points", 2, "Point", true),
+            ("mode", 4, "Mode", false),
+        ]);
+        assert!(registry.message("Point").is_none());
+    }
+
+    #[test]
+    fn test_registry_enum_values() {
+        let registry = Registry::parse(SAMPLE_PROTO);
+        assert!(registry.is_enum("Mode") && !registry.is_enum("Track"));
+        assert_eq!(registry.enum_value("Mode", "remote_id"), Some(1));
+        assert_eq!(registry.enum_value("Mode", "MODE_UNKNOWN"), Some(0));
+        assert_eq!(registry.enum_value("Mode", "gps"), None);
+        assert_eq!(registry.enum_value("Other", "unknown"), None);
+    }
+
+    #[test]
+    fn test_validate_types() {
+        assert!(check(json!(1), json!({"type": "integer"})).is_ok());
+        assert!(check(json!(1.5), json!({"type": "integer"})).is_err());
+        assert!(check(Value::Null, json!({"type": ["number", "null"]})).is_ok());
+        assert_eq!(check(json!("x"), json!({"type": "number"})), Err("r: 类型应为 [\"number\"]，实际为 \"x\"".into()));
+    }
+
+    #[test]
+    fn test_validate_object_fields() {
+        let schema = json!({"type": "object", "properties": {"a": {"type": "integer"}}, "required": ["a"]});
+        assert!(check(json!({"a": 1}), schema.clone()).is_ok());
+        assert_eq!(check(json!({}), schema.clone()), Err("r: 缺少字段 a".into()));
+        assert_eq!(check(json!({"a": 1, "b": 2}), schema.clone()), Err("r: schema 中没有字段 b".into()));
+        assert_eq!(check(json!({"a": "1"}), schema).unwrap_err().split(':').next(), Some("r.a"));
+    }
+
+    #[test]
+    fn test_validate_enum_one_of_and_ref() {
+        let schema = json!({
+            "items": {"$ref": "#/$defs/Mode"},
+            "$defs": {"Mode": {"oneOf": [{"enum": ["gps"]}, {"type": "null"}]}},
+        });
+        assert!(check(json!(["gps", null]), schema.clone()).is_ok());
+        assert_eq!(check(json!(["gps", "wifi"]), schema), Err("r[1]: 不符合任何 oneOf 分支".into()));
+        assert!(check(json!("b"), json!({"enum": ["a"]})).unwrap_err().contains("不在枚举中"));
+    }
+
+    #[test]
+    fn test_check_record_against_shipped_schema() {
+        check_record(&sample_record()).unwrap();
+        check_record(&UploadData::default()).unwrap();
+    }
+
+    #[test]
+    fn test_shipped_schema_rejects_wrong_type() {
+        let shipped: Value = serde_json::from_str(JSON_SCHEMA).unwrap();
+        let mut broken = serde_json::to_value(sample_record()).unwrap();
+        broken["rssi"] = json!("strong");
+        let schema = &shipped["records"]["upload_data"];
+        assert!(validate(&broken, schema, &schema["$defs"], "upload_data").unwrap_err().starts_with("upload_data.rssi"));
+    }
+}
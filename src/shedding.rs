@@ -0,0 +1,215 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+/// 过载时的丢帧策略
+///
+/// ```toml
+/// [shedding]
+/// busy_ratio = 0.9      # 解码循环忙碌时间占比超过该值视为过载
+/// keep_one_in = 4       # 过载时已跟踪发射端的帧每 N 帧保留 1 帧
+/// known_ttl_s = 60      # 发射端多久未解出 Remote ID 后不再视为已跟踪
+/// ```
+///
+/// 过载时优先处理尚未见过的发射端：没有解出过 Remote ID 的源 MAC 的帧全部照常解析，
+/// 已跟踪无人机的重复帧按比例丢弃，新目标的检测延迟不受积压影响。
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SheddingConfig {
+    #[serde(default = "default_busy_ratio")]
+    pub busy_ratio: f64,
+    #[serde(default = "default_keep_one_in")]
+    pub keep_one_in: u32,
+    #[serde(default = "default_known_ttl_s")]
+    pub known_ttl_s: u64,
+}
+
+fn default_busy_ratio() -> f64 {
+    0.9
+}
+
+fn default_keep_one_in() -> u32 {
+    4
+}
+
+fn default_known_ttl_s() -> u64 {
+    60
+}
+
+impl Default for SheddingConfig {
+    fn default() -> Self {
+        Self { busy_ratio: default_busy_ratio(), keep_one_in: default_keep_one_in(), known_ttl_s: default_known_ttl_s() }
+    }
+}
+
+/// 评估忙碌占比的窗口
+const WINDOW: Duration = Duration::from_secs(1);
+
+/// 已跟踪发射端的最近状态
+struct Known {
+    last_seen: Instant,
+    /// 过载期间收到的帧数，用于按比例保留
+    frames: u32,
+}
+
+/// 按解码循环的忙碌占比判断过载，过载时丢弃已跟踪发射端的部分帧
+pub struct LoadShedder {
+    config: SheddingConfig,
+    window_start: Instant,
+    busy: Duration,
+    overloaded: bool,
+    known: HashMap<[u8; 6], Known>,
+    shed: u64,
+}
+
+impl LoadShedder {
+    pub fn new(config: SheddingConfig) -> Self {
+        Self {
+            config,
+            window_start: Instant::now(),
+            busy: Duration::ZERO,
+            overloaded: false,
+            known: HashMap::new(),
+            shed: 0,
+        }
+    }
+
+    pub fn is_overloaded(&self) -> bool {
+        self.overloaded
+    }
+
+    /// 记录处理一帧（解码及输出）所用的时间，每个窗口结束时重新判断是否过载
+    pub fn record_busy(&mut self, elapsed: Duration) {
+        self.record_busy_at(elapsed, Instant::now());
+    }
+
+    fn record_busy_at(&mut self, elapsed: Duration, now: Instant) {
+        self.busy += elapsed;
+        let window = now.duration_since(self.window_start);
+        if window < WINDOW {
+            return;
+        }
+        let overloaded = self.busy.as_secs_f64() / window.as_secs_f64() > self.config.busy_ratio;
+        if overloaded != self.overloaded {
+            if overloaded {
+                warn!("decoder overloaded, shedding repeat frames of {} tracked transmitters", self.known.len());
+            } else {
+                info!("decoder load back to normal, {} repeat frames shed", self.shed);
+                self.shed = 0;
+            }
+            self.overloaded = overloaded;
+        }
+        let ttl = Duration::from_secs(self.config.known_ttl_s);
+        self.known.retain(|_, known| now.duration_since(known.last_seen) < ttl);
+        self.window_start = now;
+        self.busy = Duration::ZERO;
+    }
+
+    /// 该发射端的帧是否需要解析；未过载或未跟踪的发射端总是返回 true
+    pub fn admit(&mut self, transmitter: &[u8]) -> bool {
+        if !self.overloaded {
+            return true;
+        }
+        let Some(known) = transmitter.try_into().ok().and_then(|mac: [u8; 6]| self.known.get_mut(&mac)) else {
+            return true;
+        };
+        known.frames = known.frames.wrapping_add(1);
+        let keep = known.frames % self.config.keep_one_in.max(1) == 0;
+        if !keep {
+            self.shed += 1;
+        }
+        keep
+    }
+
+    /// 该发射端解出了 Remote ID，之后视为已跟踪
+    pub fn mark_known(&mut self, transmitter: &[u8]) {
+        let Ok(mac) = <[u8; 6]>::try_from(transmitter) else { return };
+        self.known.entry(mac).or_insert(Known { last_seen: Instant::now(), frames: 0 }).last_seen = Instant::now();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TRACKED: [u8; 6] = [0x02, 0, 0, 0, 0, 1];
+    const NEW: [u8; 6] = [0x02, 0, 0, 0, 0, 2];
+
+    /// 已跟踪 TRACKED 并进入过载状态的丢帧器
+    fn overloaded(config: SheddingConfig) -> LoadShedder {
+        let mut shedder = LoadShedder::new(config);
+        shedder.mark_known(&TRACKED);
+        let start = shedder.window_start;
+        shedder.record_busy_at(Duration::from_millis(950), start + Duration::from_secs(1));
+        shedder
+    }
+
+    #[test]
+    fn test_admits_all_when_not_overloaded() {
+        let mut shedder = LoadShedder::new(SheddingConfig::default());
+        shedder.mark_known(&TRACKED);
+        assert!(!shedder.is_overloaded());
+        assert!((0..10).all(|_| shedder.admit(&TRACKED)));
+    }
+
+    #[test]
+    fn test_overload_judged_per_window() {
+        let mut shedder = LoadShedder::new(SheddingConfig::default());
+        let start = shedder.window_start;
+        // 窗口未结束时不判断
+        shedder.record_busy_at(Duration::from_millis(950), start + Duration::from_millis(500));
+        assert!(!shedder.is_overloaded());
+        shedder.record_busy_at(Duration::ZERO, start + Duration::from_secs(1));
+        assert!(shedder.is_overloaded());
+    }
+
+    #[test]
+    fn test_overload_keeps_one_in_n_tracked_frames() {
+        let mut shedder = overloaded(SheddingConfig::default());
+        assert!(shedder.is_overloaded());
+        let kept = (0..8).filter(|_| shedder.admit(&TRACKED)).count();
+        assert_eq!(kept, 2);
+        assert_eq!(shedder.shed, 6);
+    }
+
+    #[test]
+    fn test_overload_admits_new_transmitters() {
+        let mut shedder = overloaded(SheddingConfig::default());
+        assert!((0..8).all(|_| shedder.admit(&NEW)));
+        assert!(shedder.admit(&[0x02, 0, 0]));
+    }
+
+    #[test]
+    fn test_keep_one_in_zero_keeps_all() {
+        let mut shedder = overloaded(SheddingConfig { keep_one_in: 0, ..Default::default() });
+        assert!((0..4).all(|_| shedder.admit(&TRACKED)));
+    }
+
+    #[test]
+    fn test_recovers_when_load_drops() {
+        let mut shedder = overloaded(SheddingConfig::default());
+        let start = shedder.window_start;
+        shedder.admit(&TRACKED);
+        shedder.record_busy_at(Duration::from_millis(100), start + Duration::from_secs(1));
+        assert!(!shedder.is_overloaded());
+        assert_eq!(shedder.shed, 0);
+        assert!(shedder.admit(&TRACKED));
+    }
+
+    #[test]
+    fn test_tracked_transmitters_expire() {
+        let mut shedder = overloaded(SheddingConfig { known_ttl_s: 1, ..Default::default() });
+        let start = shedder.window_start;
+        shedder.record_busy_at(Duration::from_millis(1_900), start + Duration::from_secs(2));
+        assert!(shedder.is_overloaded());
+        assert!((0..4).all(|_| shedder.admit(&TRACKED)));
+    }
+
+    #[test]
+    fn test_config_defaults() {
+        let config: SheddingConfig = toml::from_str("keep_one_in = 8").unwrap();
+        assert_eq!(config, SheddingConfig { keep_one_in: 8, ..Default::default() });
+        assert_eq!((config.busy_ratio, config.known_ttl_s), (0.9, 60));
+    }
+}
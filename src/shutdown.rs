@@ -0,0 +1,131 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tracing::{error, info};
+
+/// 退出流程各阶段的超时
+///
+/// ```toml
+/// [shutdown]
+/// drain_ms = 2000          # 停止抓包后继续解码已收到的帧的最长时间
+/// sink_timeout_ms = 5000   # 每个网络输出（上传、MQTT）送出剩余数据的最长时间
+/// force_exit_s = 30        # 收到退出信号后超过该时间仍未结束时强制退出
+/// ```
+///
+/// 退出顺序：停止抓包 → 解码已排队的帧 → 结束所有跟踪中的无人机 → 依次关闭各输出 →
+/// 关闭数据库。再次收到退出信号时立即退出。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShutdownConfig {
+    #[serde(default = "default_drain_ms")]
+    pub drain_ms: u64,
+    #[serde(default = "default_sink_timeout_ms")]
+    pub sink_timeout_ms: u64,
+    #[serde(default = "default_force_exit_s")]
+    pub force_exit_s: u64,
+}
+
+fn default_drain_ms() -> u64 {
+    2000
+}
+
+fn default_sink_timeout_ms() -> u64 {
+    5000
+}
+
+fn default_force_exit_s() -> u64 {
+    30
+}
+
+impl Default for ShutdownConfig {
+    fn default() -> Self {
+        Self {
+            drain_ms: default_drain_ms(),
+            sink_timeout_ms: default_sink_timeout_ms(),
+            force_exit_s: default_force_exit_s(),
+        }
+    }
+}
+
+impl ShutdownConfig {
+    pub fn drain(&self) -> Duration {
+        Duration::from_millis(self.drain_ms)
+    }
+
+    pub fn sink_timeout(&self) -> Duration {
+        Duration::from_millis(self.sink_timeout_ms)
+    }
+}
+
+static REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// 是否已收到退出信号
+pub fn requested() -> bool {
+    REQUESTED.load(Ordering::Relaxed)
+}
+
+/// 请求退出，与收到 SIGINT/SIGTERM 相同
+pub fn request() {
+    REQUESTED.store(true, Ordering::Relaxed);
+}
+
+/// 安装 SIGINT/SIGTERM 处理：第一次信号开始有序退出并启动强制退出计时，第二次立即退出
+pub fn install(config: ShutdownConfig) {
+    let result = ctrlc::set_handler(move || {
+        if REQUESTED.swap(true, Ordering::Relaxed) {
+            error!("再次收到退出信号，立即退出");
+            std::process::exit(130);
+        }
+        info!("shutdown requested, stopping capture");
+        let force_after = Duration::from_secs(config.force_exit_s);
+        thread::spawn(move || {
+            thread::sleep(force_after);
+            error!("{} 秒内未完成退出，强制退出", force_after.as_secs());
+            std::process::exit(1);
+        });
+    });
+    if let Err(e) = result {
+        error!("无法安装退出信号处理: {}", e);
+    }
+}
+
+/// 等待线程结束，超时返回 false（线程继续运行，随进程退出）
+pub fn join_timeout(handle: JoinHandle<()>, timeout: Duration) -> bool {
+    let deadline = Instant::now() + timeout;
+    while !handle.is_finished() {
+        if Instant::now() >= deadline {
+            return false;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    let _ = handle.join();
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_defaults() {
+        let config: ShutdownConfig = toml::from_str("sink_timeout_ms = 50").unwrap();
+        assert_eq!(config, ShutdownConfig { sink_timeout_ms: 50, ..Default::default() });
+        assert_eq!((config.drain(), config.sink_timeout()), (Duration::from_secs(2), Duration::from_millis(50)));
+        assert_eq!(config.force_exit_s, 30);
+    }
+
+    #[test]
+    fn test_join_finished_thread() {
+        let quick = thread::spawn(|| thread::sleep(Duration::from_millis(5)));
+        assert!(join_timeout(quick, Duration::from_millis(500)));
+    }
+
+    #[test]
+    fn test_join_gives_up_after_timeout() {
+        let slow = thread::spawn(|| thread::sleep(Duration::from_secs(2)));
+        let started = Instant::now();
+        assert!(!join_timeout(slow, Duration::from_millis(50)));
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+}
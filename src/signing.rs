@@ -0,0 +1,123 @@
+use std::fs;
+use std::path::Path;
+
+use ring::digest::{digest, SHA256};
+use ring::signature::{Ed25519KeyPair, KeyPair};
+
+use crate::remote_id::{from_hex, to_hex};
+
+/// 上传批次的签名，随请求以 [`SIGNATURE_HEADER`] 和 [`KEY_ID_HEADER`] 发送
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchSignature {
+    pub key_id: String,
+    /// 对请求正文原始字节的 Ed25519 签名（十六进制）
+    pub signature: String,
+}
+
+/// 批次签名请求头
+pub const SIGNATURE_HEADER: &str = "X-Signature";
+/// 签名所用密钥的 ID 请求头
+pub const KEY_ID_HEADER: &str = "X-Signature-Key-Id";
+
+/// 接收站的 Ed25519 签名密钥，后端按密钥 ID 查到登记的公钥后校验批次确实来自该接收站且未被改动
+///
+/// 密钥文件内容为 32 字节种子的十六进制，例如 `openssl rand -hex 32 > sensor.key`；
+/// 启动时日志会输出对应的公钥，用于在后端登记。
+pub struct SigningKey {
+    key_id: String,
+    pair: Ed25519KeyPair,
+}
+
+impl SigningKey {
+    /// 读取密钥文件；未指定 `key_id` 时使用公钥 SHA-256 的前 8 字节（十六进制）
+    pub fn load(path: &Path, key_id: Option<&str>) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        Self::from_seed_hex(text.trim(), key_id).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    pub fn from_seed_hex(seed: &str, key_id: Option<&str>) -> Result<Self, String> {
+        let seed = from_hex(seed).filter(|s| s.len() == 32).ok_or("密钥应为 32 字节种子的十六进制")?;
+        let pair = Ed25519KeyPair::from_seed_unchecked(&seed).map_err(|e| e.to_string())?;
+        let key_id = match key_id {
+            Some(key_id) => key_id.to_string(),
+            None => to_hex(&digest(&SHA256, pair.public_key().as_ref()).as_ref()[..8]),
+        };
+        Ok(Self { key_id, pair })
+    }
+
+    pub fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    pub fn public_key_hex(&self) -> String {
+        to_hex(self.pair.public_key().as_ref())
+    }
+
+    pub fn sign(&self, body: &[u8]) -> BatchSignature {
+        BatchSignature { key_id: self.key_id.clone(), signature: to_hex(self.pair.sign(body).as_ref()) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::remote_config;
+
+    /// RFC 8032 第 7.1 节 TEST 1
+    const SEED: &str = "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60";
+    const PUBLIC_KEY: &str = "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a";
+
+    #[test]
+    fn test_rfc8032_vector() {
+        let key = SigningKey::from_seed_hex(SEED, None).unwrap();
+        assert_eq!(key.public_key_hex(), PUBLIC_KEY);
+        assert_eq!(key.sign(b"").signature, concat!(
+            "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e065224901555",
+            "fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b"));
+    }
+
+    #[test]
+    fn test_signature_verifies_body() {
+        let key = SigningKey::from_seed_hex(&"07".repeat(32), None).unwrap();
+        let signed = key.sign(b"[{\"rid\":\"A\"}]");
+        assert_eq!(signed.key_id, key.key_id());
+        assert_eq!(remote_config::verify(b"[{\"rid\":\"A\"}]", &signed.signature, &key.public_key_hex()), Ok(()));
+        assert!(remote_config::verify(b"[{\"rid\":\"B\"}]", &signed.signature, &key.public_key_hex()).is_err());
+    }
+
+    #[test]
+    fn test_derived_key_id() {
+        let key = SigningKey::from_seed_hex(SEED, None).unwrap();
+        let public_key = from_hex(PUBLIC_KEY).unwrap();
+        assert_eq!(key.key_id(), to_hex(&digest(&SHA256, &public_key).as_ref()[..8]));
+        assert_eq!(key.key_id().len(), 16);
+    }
+
+    #[test]
+    fn test_configured_key_id() {
+        let key = SigningKey::from_seed_hex(SEED, Some("roof-2")).unwrap();
+        assert_eq!((key.key_id(), key.public_key_hex().as_str()), ("roof-2", PUBLIC_KEY));
+        assert_eq!(key.sign(b"").key_id, "roof-2");
+    }
+
+    #[test]
+    fn test_invalid_seed() {
+        assert!(SigningKey::from_seed_hex("0707", None).is_err());
+        assert!(SigningKey::from_seed_hex(&"zz".repeat(32), None).is_err());
+        assert!(SigningKey::from_seed_hex(&"07".repeat(33), None).is_err());
+    }
+
+    #[test]
+    fn test_load_key_file() {
+        let dir = std::env::temp_dir().join("wifi-capture-signing-test");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("sensor.key");
+        fs::write(&path, format!("{}\n", SEED)).unwrap();
+        assert_eq!(SigningKey::load(&path, None).unwrap().public_key_hex(), PUBLIC_KEY);
+        fs::write(&path, "0707\n").unwrap();
+        let error = SigningKey::load(&path, None).err().unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(error.starts_with(&path.display().to_string()), "{}", error);
+        assert!(SigningKey::load(&path, None).is_err());
+    }
+}
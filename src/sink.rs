@@ -0,0 +1,149 @@
+use std::fs::OpenOptions;
+use std::io::{self, LineWriter, Write};
+use std::path::PathBuf;
+
+use chrono::SecondsFormat;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::event_log::DecodedEvent;
+use crate::time_format;
+
+/// 机器可读的检测输出
+///
+/// ```toml
+/// [output]
+/// ndjson = "-"                  # 每条检测一行 JSON，"-" 为标准输出，否则为文件路径（追加写入）
+/// ```
+///
+/// 输出到标准输出时，控制台日志改写到标准错误，便于直接接 `jq` 或日志采集程序。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OutputConfig {
+    #[serde(default)]
+    pub ndjson: Option<PathBuf>,
+}
+
+impl OutputConfig {
+    /// NDJSON 是否写到标准输出
+    pub fn to_stdout(&self) -> bool {
+        self.ndjson.as_ref().is_some_and(|path| path.as_os_str() == "-")
+    }
+
+    /// 按配置打开输出，未配置时返回 None
+    pub fn open(&self) -> io::Result<Option<Box<dyn RecordSink>>> {
+        let Some(path) = &self.ndjson else { return Ok(None) };
+        let sink: Box<dyn RecordSink> = if self.to_stdout() {
+            Box::new(NdjsonSink::new(io::stdout()))
+        } else {
+            let file = OpenOptions::new().create(true).append(true).open(path)?;
+            Box::new(NdjsonSink::new(LineWriter::new(file)))
+        };
+        Ok(Some(sink))
+    }
+}
+
+/// 解码记录的输出目的地
+pub trait RecordSink {
+    fn write(&mut self, event: &DecodedEvent) -> io::Result<()>;
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// 每条检测一行 JSON：合并后的记录字段（含 RSSI、信道），外加接收时间
+///
+/// `received_at_ms` 为 Unix 毫秒，`received_at` 为按输出时区格式化的 RFC 3339 时间。
+pub struct NdjsonSink<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> NdjsonSink<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write> RecordSink for NdjsonSink<W> {
+    fn write(&mut self, event: &DecodedEvent) -> io::Result<()> {
+        let mut line = serde_json::to_value(&event.record)?;
+        if let Value::Object(fields) = &mut line {
+            fields.insert("received_at_ms".into(), event.received_at_ms.into());
+            fields.insert("received_at".into(), time_format::format_ms(event.received_at_ms, SecondsFormat::Millis).into());
+        }
+        serde_json::to_writer(&mut self.writer, &line)?;
+        self.writer.write_all(b"\n")
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    use crate::upload_data::UploadData;
+
+    fn event(rssi: f32) -> DecodedEvent {
+        let record = UploadData { rid: "1581F5FKD229400A".into(), rssi: Some(rssi), channel: Some(6), ..Default::default() };
+        DecodedEvent { received_at_ms: 1_700_000_000_123, record }
+    }
+
+    fn lines(text: &str) -> Vec<Value> {
+        text.lines().map(|line| serde_json::from_str(line).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_one_line_per_event() {
+        let mut sink = NdjsonSink::new(Vec::new());
+        sink.write(&event(-60.0)).unwrap();
+        sink.write(&event(-58.5)).unwrap();
+        let text = String::from_utf8(sink.into_inner()).unwrap();
+        assert_eq!(text.matches('\n').count(), 2);
+        let lines = lines(&text);
+        assert_eq!(lines[1]["rid"], "1581F5FKD229400A");
+        assert_eq!((lines[1]["rssi"].as_f64(), lines[1]["channel"].as_u64()), (Some(-58.5), Some(6)));
+    }
+
+    #[test]
+    fn test_received_at_fields() {
+        let mut sink = NdjsonSink::new(Vec::new());
+        sink.write(&event(-60.0)).unwrap();
+        let line = &lines(&String::from_utf8(sink.into_inner()).unwrap())[0];
+        assert_eq!(line["received_at_ms"], 1_700_000_000_123i64);
+        assert_eq!(line["received_at"], "2023-11-14T22:13:20.123Z");
+    }
+
+    #[test]
+    fn test_stdout_target() {
+        assert!(OutputConfig { ndjson: Some("-".into()) }.to_stdout());
+        assert!(!OutputConfig { ndjson: Some("detections.ndjson".into()) }.to_stdout());
+        assert!(!OutputConfig::default().to_stdout());
+        assert!(OutputConfig::default().open().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_file_is_appended() {
+        let dir = std::env::temp_dir().join("wifi-capture-sink-test");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("detections.ndjson");
+        fs::write(&path, "{}\n").unwrap();
+        let config = OutputConfig { ndjson: Some(path.clone()) };
+        let mut sink = config.open().unwrap().unwrap();
+        sink.write(&event(-60.0)).unwrap();
+        sink.flush().unwrap();
+        drop(sink);
+        let text = fs::read_to_string(&path).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        let lines = lines(&text);
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[1]["rid"], "1581F5FKD229400A");
+    }
+}
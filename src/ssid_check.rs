@@ -0,0 +1,154 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+/// SSID 与 UAS ID 的一致性检查
+///
+/// 不少机型以序列号命名 SSID（如 `RID-1581F5FKD229400A`、`DJI-Mini-1581F5FK…`、
+/// `无人机_1581F5FK…`）。SSID 中像序列号的片段与解码出的 UAS ID 对不上时，
+/// 多半是克隆的发射端复用了别人的广播内容。
+///
+/// ```toml
+/// [ssid_check]
+/// min_serial_len = 8      # SSID 中至少这么长、含数字的字母数字片段才视为序列号
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SsidCheckConfig {
+    #[serde(default = "default_min_serial_len")]
+    pub min_serial_len: usize,
+}
+
+fn default_min_serial_len() -> usize {
+    8
+}
+
+impl Default for SsidCheckConfig {
+    fn default() -> Self {
+        Self { min_serial_len: default_min_serial_len() }
+    }
+}
+
+/// SSID 中的序列号片段与 UAS ID 的比对结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SsidMatch {
+    /// 片段与 UAS ID 相同，或是其截断的前缀/后缀
+    Match,
+    Mismatch,
+}
+
+/// SSID 中像序列号的片段：按非 ASCII 字母数字切分，前后缀不论哪种语言都被丢掉
+pub fn serial_tokens(ssid: &str, min_len: usize) -> impl Iterator<Item = &str> {
+    ssid.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(move |token| token.len() >= min_len && token.bytes().any(|b| b.is_ascii_digit()))
+}
+
+/// 比对 SSID 与 UAS ID；SSID 中没有序列号片段或 UAS ID 为空时不下结论
+pub fn compare(ssid: &str, uas_id: &str, min_len: usize) -> Option<SsidMatch> {
+    let uas_id = uas_id.trim().to_ascii_uppercase();
+    if uas_id.is_empty() {
+        return None;
+    }
+    let mut tokens = serial_tokens(ssid, min_len).map(str::to_ascii_uppercase).peekable();
+    tokens.peek()?;
+    let matched = tokens.any(|token| {
+        uas_id.starts_with(&token) || uas_id.ends_with(&token)
+            || token.starts_with(&uas_id) || token.ends_with(&uas_id)
+    });
+    Some(if matched { SsidMatch::Match } else { SsidMatch::Mismatch })
+}
+
+/// 按源 MAC 检查 SSID 与 UAS ID，不一致时告警；同一源的告警在冷却期内只产生一次
+pub struct SsidChecker {
+    config: SsidCheckConfig,
+    cooldown: Duration,
+    last_alert: HashMap<String, Instant>,
+}
+
+impl SsidChecker {
+    pub fn new(config: SsidCheckConfig) -> Self {
+        Self { config, cooldown: Duration::from_secs(600), last_alert: HashMap::new() }
+    }
+
+    pub fn check(&mut self, mac: &str, ssid: &str, uas_id: &str) -> Option<SsidMatch> {
+        let verdict = compare(ssid, uas_id, self.config.min_serial_len)?;
+        if verdict == SsidMatch::Mismatch {
+            let now = Instant::now();
+            let alerted = self.last_alert.get(mac).is_some_and(|t| now.duration_since(*t) < self.cooldown);
+            if !alerted {
+                self.last_alert.insert(mac.to_string(), now);
+                warn!(uas_id = %uas_id, "SSID {:?} of {} does not match the broadcast UAS ID, possible cloned transmitter", ssid, mac);
+            }
+        }
+        Some(verdict)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RID: &str = "1581F5FKD229400A";
+
+    #[test]
+    fn test_serial_tokens() {
+        let tokens: Vec<&str> = serial_tokens("DJI-Mini_1581F5FK·Platform2024", 8).collect();
+        assert_eq!(tokens, ["1581F5FK", "Platform2024"]);
+        assert_eq!(serial_tokens("ABCDEFGHIJ-1234567", 8).count(), 0);
+    }
+
+    #[test]
+    fn test_exact_serial_matches() {
+        assert_eq!(compare("RID-1581F5FKD229400A", RID, 8), Some(SsidMatch::Match));
+        assert_eq!(compare("RID-1581F5FKD229400A", " 1581f5fkd229400a ", 8), Some(SsidMatch::Match));
+    }
+
+    #[test]
+    fn test_truncated_serial_matches() {
+        assert_eq!(compare("无人机_1581f5fkd229", RID, 8), Some(SsidMatch::Match));
+        assert_eq!(compare("DJI-Mini-D229400A", RID, 8), Some(SsidMatch::Match));
+    }
+
+    #[test]
+    fn test_serial_containing_short_uas_id_matches() {
+        assert_eq!(compare("RID-SN12345678X", "SN12345678", 8), Some(SsidMatch::Match));
+    }
+
+    #[test]
+    fn test_other_serial_mismatches() {
+        assert_eq!(compare("RID-1581F7ABC0001234", RID, 8), Some(SsidMatch::Mismatch));
+        // 任一片段对上即可
+        assert_eq!(compare("AP0000001-1581F5FK", RID, 8), Some(SsidMatch::Match));
+    }
+
+    #[test]
+    fn test_no_verdict_without_serial_or_uas_id() {
+        // 没有序列号片段时不下结论
+        assert_eq!(compare("Office-WiFi", RID, 8), None);
+        assert_eq!(compare("Cam-1234", RID, 8), None);
+        assert_eq!(compare("", RID, 8), None);
+        assert_eq!(compare("RID-1581F5FKD229400A", "", 8), None);
+    }
+
+    #[test]
+    fn test_checker_alerts_once_per_cooldown() {
+        let mut checker = SsidChecker::new(SsidCheckConfig::default());
+        assert_eq!(checker.check("02:00:00:00:00:01", "RID-1581F5FKD229400A", RID), Some(SsidMatch::Match));
+        assert!(checker.last_alert.is_empty());
+        assert_eq!(checker.check("02:00:00:00:00:01", "RID-1581F7ABC0001234", RID), Some(SsidMatch::Mismatch));
+        let first = checker.last_alert["02:00:00:00:00:01"];
+        assert_eq!(checker.check("02:00:00:00:00:01", "RID-1581F7ABC0001234", RID), Some(SsidMatch::Mismatch));
+        assert_eq!(checker.last_alert["02:00:00:00:00:01"], first);
+    }
+
+    #[test]
+    fn test_config_min_serial_len() {
+        let config: SsidCheckConfig = toml::from_str("").unwrap();
+        assert_eq!(config.min_serial_len, 8);
+        let mut checker = SsidChecker::new(SsidCheckConfig { min_serial_len: 4 });
+        assert_eq!(checker.check("02:00:00:00:00:01", "Cam-1234", RID), Some(SsidMatch::Mismatch));
+    }
+}
@@ -0,0 +1,299 @@
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+use tracing::info;
+
+use crate::upload_data::UploadData;
+
+/// 帧分类，用于统计非 Remote ID 流量而不逐帧打印
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameClass {
+    RidBeacon,        // 携带 Remote ID 的信标帧 (含探测请求/响应)
+    OtherBeacon,      // 其它信标帧
+    OtherManagement,  // 其它管理帧
+    Control,          // 控制帧
+    Data,             // 数据帧
+    Undecodable,      // 无法解析的帧
+    MalformedPack,    // Remote ID 消息包头校验失败
+    Short,            // 长度不足被直接丢弃的帧
+    Ignored,          // 发送方 OUI 在忽略列表中的帧
+    NoRidSignature,   // 预过滤时没有 Remote ID 厂商 IE 签名、未完整解析的帧
+    Shed,             // 过载时丢弃的已跟踪发射端的重复帧
+    QueueFull,        // 解码队列已满、未经解码即丢弃的帧
+}
+
+impl FrameClass {
+    const COUNT: usize = 12;
+
+    /// 根据 802.11 帧控制字段的类型位分类（管理帧需结合内容另行细分）
+    pub fn from_frame_control(byte0: u8) -> Self {
+        match (byte0 >> 2) & 0x03 {
+            0 => Self::OtherManagement,
+            1 => Self::Control,
+            _ => Self::Data,
+        }
+    }
+}
+
+/// 802.11 帧类型/子类型名称，按 (类型 << 4 | 子类型) 索引
+pub fn subtype_name(index: usize) -> &'static str {
+    const MANAGEMENT: [&str; 16] = [
+        "assoc_req", "assoc_resp", "reassoc_req", "reassoc_resp",
+        "probe_req", "probe_resp", "timing_adv", "mgmt_7",
+        "beacon", "atim", "disassoc", "auth",
+        "deauth", "action", "action_no_ack", "mgmt_15",
+    ];
+    const CONTROL: [&str; 16] = [
+        "ctrl_0", "ctrl_1", "trigger", "tack",
+        "beamforming", "vht_ndp", "ctrl_ext", "ctrl_wrapper",
+        "block_ack_req", "block_ack", "ps_poll", "rts",
+        "cts", "ack", "cf_end", "cf_end_ack",
+    ];
+    const DATA: [&str; 16] = [
+        "data", "data_cf_ack", "data_cf_poll", "data_cf_ack_poll",
+        "null", "cf_ack", "cf_poll", "cf_ack_poll",
+        "qos_data", "qos_data_cf_ack", "qos_data_cf_poll", "qos_data_cf_ack_poll",
+        "qos_null", "data_13", "qos_cf_poll", "qos_cf_ack_poll",
+    ];
+    match index >> 4 {
+        0 => MANAGEMENT[index & 0x0f],
+        1 => CONTROL[index & 0x0f],
+        2 => DATA[index & 0x0f],
+        _ => "extension",
+    }
+}
+
+/// 按周期汇总的帧分类计数
+pub struct FrameStats {
+    counts: [u64; FrameClass::COUNT],
+    subtypes: [u64; 64],
+    interval: Duration,
+    last_report: Instant,
+    labels: String,
+}
+
+impl Default for FrameStats {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_INTERVAL)
+    }
+}
+
+impl FrameStats {
+    pub const DEFAULT_INTERVAL: Duration = Duration::from_secs(30);
+
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            counts: [0; FrameClass::COUNT],
+            subtypes: [0; 64],
+            interval,
+            last_report: Instant::now(),
+            labels: String::new(),
+        }
+    }
+
+    /// 附加在每次汇总输出前的标签（如租户/站点）
+    pub fn with_labels(mut self, labels: String) -> Self {
+        self.labels = labels;
+        self
+    }
+
+    /// 某个网卡的计数：汇总周期和标签相同，标签附加 `interface=<网卡>`
+    pub fn for_interface(&self, interface: &str) -> Self {
+        let label = format!("interface={}", interface);
+        let labels = if self.labels.is_empty() { label } else { format!("{} {}", self.labels, label) };
+        Self::new(self.interval).with_labels(labels)
+    }
+
+    pub fn record(&mut self, class: FrameClass) {
+        self.counts[class as usize] += 1;
+    }
+
+    pub fn record_n(&mut self, class: FrameClass, n: u64) {
+        self.counts[class as usize] += n;
+    }
+
+    /// 累加另一份计数，解码线程的计数由此汇总
+    pub fn merge(&mut self, other: &FrameStats) {
+        for (count, other) in self.counts.iter_mut().zip(other.counts) {
+            *count += other;
+        }
+        for (count, other) in self.subtypes.iter_mut().zip(other.subtypes) {
+            *count += other;
+        }
+    }
+
+    /// 取出当前计数并清零
+    pub fn take(&mut self) -> FrameStats {
+        let taken = Self { counts: self.counts, subtypes: self.subtypes, ..Self::new(self.interval) };
+        self.counts = [0; FrameClass::COUNT];
+        self.subtypes = [0; 64];
+        taken
+    }
+
+    pub fn count(&self, class: FrameClass) -> u64 {
+        self.counts[class as usize]
+    }
+
+    /// 各分类的帧数之和
+    pub fn total(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// 按帧控制字段首字节记录类型/子类型
+    pub fn record_subtype(&mut self, frame_control: u8) {
+        self.subtypes[Self::subtype_index(frame_control)] += 1;
+    }
+
+    fn subtype_index(frame_control: u8) -> usize {
+        (((frame_control >> 2) & 0x03) << 4 | (frame_control >> 4)) as usize
+    }
+
+    /// 当前周期内出现过的类型/子类型及计数
+    pub fn subtype_counts(&self) -> Vec<(&'static str, u64)> {
+        self.subtypes.iter()
+            .enumerate()
+            .filter(|(_, count)| **count > 0)
+            .map(|(index, count)| (subtype_name(index), *count))
+            .collect()
+    }
+
+    fn prefix(&self) -> String {
+        if self.labels.is_empty() { String::new() } else { format!("[{}] ", self.labels) }
+    }
+
+    /// 到达汇总周期时输出一次计数并清零
+    pub fn maybe_report(&mut self) {
+        if self.last_report.elapsed() < self.interval {
+            return;
+        }
+        info!(
+            "{}frames in last {}s: rid_beacon={} other_beacon={} other_mgmt={} control={} data={} undecodable={} malformed_pack={} short={} ignored={} no_rid_signature={} shed={} queue_full={}",
+            self.prefix(),
+            self.last_report.elapsed().as_secs(),
+            self.count(FrameClass::RidBeacon),
+            self.count(FrameClass::OtherBeacon),
+            self.count(FrameClass::OtherManagement),
+            self.count(FrameClass::Control),
+            self.count(FrameClass::Data),
+            self.count(FrameClass::Undecodable),
+            self.count(FrameClass::MalformedPack),
+            self.count(FrameClass::Short),
+            self.count(FrameClass::Ignored),
+            self.count(FrameClass::NoRidSignature),
+            self.count(FrameClass::Shed),
+            self.count(FrameClass::QueueFull),
+        );
+        let subtypes: Vec<String> = self.subtype_counts()
+            .iter()
+            .map(|(name, count)| format!("{}={}", name, count))
+            .collect();
+        info!("{}frame subtypes: {}", self.prefix(), subtypes.join(" "));
+        self.counts = [0; FrameClass::COUNT];
+        self.subtypes = [0; 64];
+        self.last_report = Instant::now();
+    }
+}
+
+/// 整个运行期间的累计计数，退出时输出会话摘要
+pub struct SessionSummary {
+    started: Instant,
+    frames: u64,
+    rid_frames: u64,
+    parse_errors: u64,
+    records: u64,
+    duplicates: u64,
+    drones: HashSet<String>,
+}
+
+impl Default for SessionSummary {
+    fn default() -> Self {
+        Self { started: Instant::now(), frames: 0, rid_frames: 0, parse_errors: 0, records: 0, duplicates: 0, drones: HashSet::new() }
+    }
+}
+
+impl SessionSummary {
+    /// 累加一份帧分类计数
+    pub fn add_frames(&mut self, stats: &FrameStats) {
+        self.frames += stats.total();
+        self.rid_frames += stats.count(FrameClass::RidBeacon);
+        self.parse_errors += stats.count(FrameClass::Undecodable) + stats.count(FrameClass::MalformedPack);
+    }
+
+    /// 多网卡去重丢弃的帧，不计入 Remote ID 帧数
+    pub fn add_duplicates(&mut self, duplicates: u64) {
+        self.duplicates += duplicates;
+    }
+
+    /// 记录一条输出记录；没有 UAS ID 的按轨迹 ID 计为一架无人机
+    pub fn observe(&mut self, record: &UploadData) {
+        self.records += 1;
+        let id = if record.rid.is_empty() { &record.track_id } else { &record.rid };
+        if !self.drones.contains(id) {
+            self.drones.insert(id.clone());
+        }
+    }
+
+    pub fn unique_drones(&self) -> usize {
+        self.drones.len()
+    }
+
+    pub fn report(&self) {
+        let uptime = self.started.elapsed().as_secs();
+        info!(
+            "session summary: uptime {}h{:02}m{:02}s, frames captured={} remote_id_frames={} duplicates={} records={} unique_drones={} parse_errors={}",
+            uptime / 3600, uptime / 60 % 60, uptime % 60,
+            self.frames, self.rid_frames.saturating_sub(self.duplicates), self.duplicates, self.records, self.unique_drones(),
+            self.parse_errors,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(stats: &FrameStats) -> SessionSummary {
+        let mut summary = SessionSummary::default();
+        summary.add_frames(stats);
+        summary
+    }
+
+    #[test]
+    fn test_summary_counts_frames() {
+        let mut stats = FrameStats::default();
+        stats.record(FrameClass::RidBeacon);
+        stats.record_n(FrameClass::Control, 5);
+        let summary = summary(&stats);
+        assert_eq!((summary.frames, summary.rid_frames, summary.parse_errors), (6, 1, 0));
+    }
+
+    #[test]
+    fn test_summary_counts_parse_errors() {
+        let mut stats = FrameStats::default();
+        stats.record(FrameClass::Undecodable);
+        stats.record(FrameClass::MalformedPack);
+        stats.record(FrameClass::Short);
+        assert_eq!(summary(&stats).parse_errors, 2);
+    }
+
+    #[test]
+    fn test_summary_accumulates_intervals() {
+        let mut stats = FrameStats::default();
+        stats.record(FrameClass::RidBeacon);
+        let mut summary = summary(&stats.take());
+        // 取出后计数清零，下一周期只累加新的帧
+        stats.record(FrameClass::Data);
+        summary.add_frames(&stats);
+        assert_eq!((summary.frames, summary.rid_frames), (2, 1));
+    }
+
+    #[test]
+    fn test_unique_drones() {
+        let mut summary = SessionSummary::default();
+        for (rid, track_id) in [("RID-1", "a"), ("RID-1", "b"), ("", "c"), ("", "c")] {
+            summary.observe(&UploadData { rid: rid.into(), track_id: track_id.into(), ..Default::default() });
+        }
+        // 同一 UAS ID 的不同轨迹算一架，没有 UAS ID 时按轨迹 ID 计
+        assert_eq!((summary.records, summary.unique_drones()), (4, 2));
+    }
+}
@@ -0,0 +1,692 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use rusqlite::types::Value;
+use rusqlite::{params, params_from_iter, Connection, OpenFlags, OptionalExtension};
+use tracing::info;
+
+use crate::canonical;
+use crate::event_log::DecodedEvent;
+use crate::geo::{degrees, distance_m};
+use crate::identity::DroneIdentity;
+use crate::position;
+
+/// 第 1 版：定位记录、索引、R-tree 与无人机全文索引
+const SCHEMA_V1: &str = "
+CREATE TABLE IF NOT EXISTS fixes (
+    id             INTEGER PRIMARY KEY,
+    received_at_ms INTEGER NOT NULL,
+    uas_id         TEXT NOT NULL,
+    track_id       TEXT NOT NULL,
+    source_mac     TEXT NOT NULL,
+    operator_id    TEXT,
+    latitude       REAL,
+    longitude      REAL,
+    altitude       REAL,
+    record         TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS fixes_uas_time ON fixes (uas_id, received_at_ms);
+CREATE INDEX IF NOT EXISTS fixes_operator_time ON fixes (operator_id, received_at_ms);
+CREATE INDEX IF NOT EXISTS fixes_time ON fixes (received_at_ms);
+CREATE VIRTUAL TABLE IF NOT EXISTS fixes_rtree USING rtree (id, min_lat, max_lat, min_lon, max_lon);
+
+CREATE TABLE IF NOT EXISTS drones (
+    uas_id        TEXT PRIMARY KEY,
+    operator_id   TEXT,
+    first_seen_ms INTEGER NOT NULL,
+    last_seen_ms  INTEGER NOT NULL
+);
+CREATE VIRTUAL TABLE IF NOT EXISTS drones_fts USING fts5 (
+    uas_id, operator_id, content = 'drones', tokenize = 'trigram'
+);
+CREATE TRIGGER IF NOT EXISTS drones_ai AFTER INSERT ON drones BEGIN
+    INSERT INTO drones_fts (rowid, uas_id, operator_id) VALUES (new.rowid, new.uas_id, new.operator_id);
+END;
+CREATE TRIGGER IF NOT EXISTS drones_au AFTER UPDATE OF operator_id ON drones BEGIN
+    INSERT INTO drones_fts (drones_fts, rowid, uas_id, operator_id) VALUES ('delete', old.rowid, old.uas_id, old.operator_id);
+    INSERT INTO drones_fts (rowid, uas_id, operator_id) VALUES (new.rowid, new.uas_id, new.operator_id);
+END;
+";
+
+/// 第 2 版：租户/站点标签列，多租户数据按其分区查询
+const SCHEMA_V2: &str = "
+ALTER TABLE fixes ADD COLUMN tenant TEXT;
+ALTER TABLE fixes ADD COLUMN site TEXT;
+CREATE INDEX IF NOT EXISTS fixes_tenant_site_time ON fixes (tenant, site, received_at_ms);
+";
+
+/// 第 3 版：无人机身份摘要（轨迹别名、标注）与告警冷却状态，重启后恢复
+const SCHEMA_V3: &str = "
+ALTER TABLE drones ADD COLUMN aliases TEXT;
+ALTER TABLE drones ADD COLUMN annotation TEXT;
+CREATE TABLE IF NOT EXISTS alert_cooldowns (
+    rule     TEXT NOT NULL,
+    subject  TEXT NOT NULL,
+    fired_ms INTEGER NOT NULL,
+    PRIMARY KEY (rule, subject)
+);
+";
+
+/// 第 4 版：按航段聚合的轨迹，查询历史航次时不必扫描全部定位记录
+const SCHEMA_V4: &str = "
+CREATE TABLE IF NOT EXISTS tracks (
+    id           INTEGER PRIMARY KEY,
+    uas_id       TEXT NOT NULL,
+    start_ms     INTEGER NOT NULL,
+    end_ms       INTEGER NOT NULL,
+    fixes        INTEGER NOT NULL,
+    min_lat      REAL,
+    max_lat      REAL,
+    min_lon      REAL,
+    max_lon      REAL,
+    max_altitude REAL,
+    distance_m   REAL NOT NULL,
+    tenant       TEXT,
+    site         TEXT
+);
+CREATE INDEX IF NOT EXISTS tracks_uas_time ON tracks (uas_id, start_ms);
+CREATE INDEX IF NOT EXISTS tracks_time ON tracks (start_ms);
+";
+
+/// 按版本顺序排列的迁移脚本，数据库版本保存在 `PRAGMA user_version`
+///
+/// 已发布的脚本不得修改，表结构变更只能追加新版本。
+const MIGRATIONS: &[&str] = &[SCHEMA_V1, SCHEMA_V2, SCHEMA_V3, SCHEMA_V4];
+
+/// 最新的表结构版本
+pub const SCHEMA_VERSION: u32 = MIGRATIONS.len() as u32;
+
+/// `db check` 的检查结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaStatus {
+    pub version: u32,
+    pub latest: u32,
+    pub integrity: String,
+}
+
+impl SchemaStatus {
+    pub fn is_ok(&self) -> bool {
+        self.version == self.latest && self.integrity == "ok"
+    }
+}
+
+fn schema_version(conn: &Connection) -> rusqlite::Result<u32> {
+    conn.pragma_query_value(None, "user_version", |row| row.get(0))
+}
+
+/// 依次执行未应用的迁移，每个版本在单独的事务中完成
+///
+/// 返回 (迁移前版本, 迁移后版本)。数据库版本高于本程序支持的版本时报错，
+/// 避免旧程序写坏新表结构。
+pub fn migrate(conn: &mut Connection) -> rusqlite::Result<(u32, u32)> {
+    let from = schema_version(conn)?;
+    if from > SCHEMA_VERSION {
+        return Err(rusqlite::Error::SqliteFailure(
+            rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_MISMATCH),
+            Some(format!("数据库版本 {} 高于程序支持的版本 {}", from, SCHEMA_VERSION)),
+        ));
+    }
+    for (i, script) in MIGRATIONS.iter().enumerate().skip(from as usize) {
+        let version = i as u32 + 1;
+        let tx = conn.transaction()?;
+        tx.execute_batch(script)?;
+        tx.pragma_update(None, "user_version", version)?;
+        tx.commit()?;
+        info!("database migrated to schema version {}", version);
+    }
+    Ok((from, SCHEMA_VERSION))
+}
+
+/// 检查数据库版本与完整性，不做任何修改
+pub fn check<P: AsRef<Path>>(path: P) -> rusqlite::Result<SchemaStatus> {
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    let integrity = conn.query_row("PRAGMA integrity_check", [], |row| row.get(0))?;
+    Ok(SchemaStatus { version: schema_version(&conn)?, latest: SCHEMA_VERSION, integrity })
+}
+
+/// 经纬度范围 (度)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingBox {
+    pub min_lat: f64,
+    pub min_lon: f64,
+    pub max_lat: f64,
+    pub max_lon: f64,
+}
+
+impl BoundingBox {
+    /// 解析 "min_lat,min_lon,max_lat,max_lon"
+    pub fn parse(s: &str) -> Option<Self> {
+        let v: Vec<f64> = s.split(',').map(|p| p.trim().parse().ok()).collect::<Option<_>>()?;
+        let [min_lat, min_lon, max_lat, max_lon] = v[..] else { return None };
+        Some(Self { min_lat, min_lon, max_lat, max_lon })
+    }
+}
+
+/// 历史查询条件，未设置的条件不参与过滤
+#[derive(Debug, Clone, Default)]
+pub struct FixQuery {
+    pub uas_id_prefix: Option<String>,
+    pub operator_id: Option<String>,
+    pub tenant: Option<String>,
+    pub site: Option<String>,
+    pub from_ms: Option<i64>,
+    pub to_ms: Option<i64>,
+    pub bbox: Option<BoundingBox>,
+    pub limit: Option<usize>,
+}
+
+/// 无人机汇总信息
+#[derive(Debug, Clone, PartialEq)]
+pub struct DroneSummary {
+    pub uas_id: String,
+    pub operator_id: Option<String>,
+    pub first_seen_ms: i64,
+    pub last_seen_ms: i64,
+}
+
+/// 聚合统计用的精简定位样本，不解析完整记录
+#[derive(Debug, Clone, PartialEq)]
+pub struct FixSample {
+    pub received_at_ms: i64,
+    pub uas_id: String,
+    /// 该条记录或该无人机已知的运营人 ID
+    pub operator_id: Option<String>,
+    pub ua_type: Option<u8>,
+    /// (纬度, 经度)，没有定位时为 None
+    pub position: Option<(f64, f64)>,
+}
+
+/// 同一无人机相邻两条记录间隔超过该值时开始新航段，与流量统计的航次划分一致
+pub const SEGMENT_GAP_MS: i64 = 5 * 60 * 1000;
+
+/// 一架无人机连续出现的一段航迹的汇总
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrackSegment {
+    pub uas_id: String,
+    pub start_ms: i64,
+    pub end_ms: i64,
+    pub fixes: u64,
+    /// 航段内定位的经纬度范围，没有定位时为 None
+    pub bounds: Option<BoundingBox>,
+    pub max_altitude_m: Option<f64>,
+    /// 相邻定位间距离之和 (米)
+    pub distance_m: f64,
+    pub tenant: Option<String>,
+    pub site: Option<String>,
+}
+
+impl TrackSegment {
+    fn new(uas_id: &str, event: &DecodedEvent) -> Self {
+        Self {
+            uas_id: uas_id.to_string(),
+            start_ms: event.received_at_ms,
+            end_ms: event.received_at_ms,
+            fixes: 0,
+            bounds: None,
+            max_altitude_m: None,
+            distance_m: 0.0,
+            tenant: event.record.tags.tenant.clone(),
+            site: event.record.tags.site.clone(),
+        }
+    }
+
+    fn add(&mut self, at_ms: i64, position: Option<(f64, f64, f64)>) {
+        self.start_ms = self.start_ms.min(at_ms);
+        self.end_ms = self.end_ms.max(at_ms);
+        self.fixes += 1;
+        let Some((lat, lon, altitude)) = position else { return };
+        let bounds = self.bounds.get_or_insert(BoundingBox { min_lat: lat, min_lon: lon, max_lat: lat, max_lon: lon });
+        bounds.min_lat = bounds.min_lat.min(lat);
+        bounds.max_lat = bounds.max_lat.max(lat);
+        bounds.min_lon = bounds.min_lon.min(lon);
+        bounds.max_lon = bounds.max_lon.max(lon);
+        self.max_altitude_m = Some(self.max_altitude_m.map_or(altitude, |max| max.max(altitude)));
+    }
+}
+
+/// 未结束的航段及其最后一个定位 (纬度, 经度)
+struct OpenSegment {
+    segment: TrackSegment,
+    last: Option<(f64, f64)>,
+}
+
+/// SQLite 存储，打开时自动升级到最新表结构
+///
+/// 每条定位记录写入 `fixes`（完整记录以 JSON 保存），并维护：
+/// - (uas_id, 时间) / (operator_id, 时间) / (租户, 站点, 时间) / 时间 四个 B-tree 索引
+/// - 按经纬度范围查询的 R-tree
+/// - 每架无人机一行的 `drones` 表及其 trigram 全文索引，支持 ID 子串搜索
+/// - 按航段聚合的 `tracks` 表：航段在内存中累积，间隔 [`SEGMENT_GAP_MS`] 未再出现
+///   或关闭数据库时写入
+pub struct Store {
+    conn: Connection,
+    segments: HashMap<String, OpenSegment>,
+}
+
+impl Store {
+    pub fn open<P: AsRef<Path>>(path: P) -> rusqlite::Result<Self> {
+        let mut conn = Connection::open(path)?;
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        conn.pragma_update(None, "synchronous", "NORMAL")?;
+        migrate(&mut conn)?;
+        Ok(Self { conn, segments: HashMap::new() })
+    }
+
+    /// 写出未结束的航段、合并 WAL 后关闭数据库，退出时调用
+    pub fn close(mut self) -> rusqlite::Result<()> {
+        self.finish_segments(i64::MAX)?;
+        self.conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))?;
+        self.conn.close().map_err(|(_, e)| e)
+    }
+
+    pub fn insert(&mut self, event: &DecodedEvent, operator_id: Option<&str>) -> rusqlite::Result<()> {
+        let record = &event.record;
+        let uas_id = if record.rid.is_empty() { &record.track_id } else { &record.rid };
+        let has_position = position::has_coordinates(record);
+        let (lat, lon) = (degrees(record.latitude), degrees(record.longitude));
+        let json = canonical::to_json(record).map_err(|e| rusqlite::Error::ToSqlConversionFailure(e.into()))?;
+
+        let tx = self.conn.transaction()?;
+        tx.execute(
+            "INSERT INTO fixes (received_at_ms, uas_id, track_id, source_mac, operator_id, latitude, longitude, altitude, record, tenant, site)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            params![
+                event.received_at_ms, uas_id, record.track_id, record.source_mac, operator_id,
+                has_position.then_some(lat), has_position.then_some(lon),
+                has_position.then_some(record.geometric_altitude as f64),
+                json, record.tags.tenant, record.tags.site,
+            ],
+        )?;
+        if has_position {
+            tx.execute(
+                "INSERT INTO fixes_rtree (id, min_lat, max_lat, min_lon, max_lon) VALUES (?1, ?2, ?2, ?3, ?3)",
+                params![tx.last_insert_rowid(), lat, lon],
+            )?;
+        }
+        tx.execute(
+            "INSERT INTO drones (uas_id, operator_id, first_seen_ms, last_seen_ms) VALUES (?1, ?2, ?3, ?3)
+             ON CONFLICT (uas_id) DO UPDATE SET
+                 last_seen_ms = max(last_seen_ms, excluded.last_seen_ms),
+                 operator_id = coalesce(excluded.operator_id, operator_id)
+             WHERE excluded.last_seen_ms > last_seen_ms OR excluded.operator_id IS NOT operator_id",
+            params![uas_id, operator_id, event.received_at_ms],
+        )?;
+        tx.commit()?;
+        self.add_to_segment(uas_id, event, has_position.then_some((lat, lon, record.geometric_altitude as f64)))
+    }
+
+    fn add_to_segment(&mut self, uas_id: &str, event: &DecodedEvent, position: Option<(f64, f64, f64)>) -> rusqlite::Result<()> {
+        let at = event.received_at_ms;
+        if let Some(open) = self.segments.get(uas_id)
+            && (at - open.segment.end_ms > SEGMENT_GAP_MS || open.segment.start_ms - at > SEGMENT_GAP_MS)
+            && let Some(finished) = self.segments.remove(uas_id)
+        {
+            self.insert_segment(&finished.segment)?;
+        }
+        let open = self.segments.entry(uas_id.to_string())
+            .or_insert_with(|| OpenSegment { segment: TrackSegment::new(uas_id, event), last: None });
+        if let (Some((lat, lon, _)), Some((last_lat, last_lon))) = (position, open.last) {
+            open.segment.distance_m += distance_m(last_lat, last_lon, lat, lon);
+        }
+        if let Some((lat, lon, _)) = position {
+            open.last = Some((lat, lon));
+        }
+        open.segment.add(at, position);
+        Ok(())
+    }
+
+    fn insert_segment(&self, segment: &TrackSegment) -> rusqlite::Result<()> {
+        let bounds = segment.bounds;
+        self.conn.execute(
+            "INSERT INTO tracks (uas_id, start_ms, end_ms, fixes, min_lat, max_lat, min_lon, max_lon, max_altitude, distance_m, tenant, site)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+            params![
+                segment.uas_id, segment.start_ms, segment.end_ms, segment.fixes as i64,
+                bounds.map(|b| b.min_lat), bounds.map(|b| b.max_lat), bounds.map(|b| b.min_lon), bounds.map(|b| b.max_lon),
+                segment.max_altitude_m, segment.distance_m, segment.tenant, segment.site,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// 写出在 `now_ms` 之前已间隔 [`SEGMENT_GAP_MS`] 未再出现的航段，返回写出的航段数
+    pub fn finish_segments(&mut self, now_ms: i64) -> rusqlite::Result<usize> {
+        let finished: Vec<String> = self.segments.iter()
+            .filter(|(_, open)| now_ms.saturating_sub(open.segment.end_ms) > SEGMENT_GAP_MS)
+            .map(|(uas_id, _)| uas_id.clone())
+            .collect();
+        for uas_id in &finished {
+            if let Some(open) = self.segments.remove(uas_id) {
+                self.insert_segment(&open.segment)?;
+            }
+        }
+        Ok(finished.len())
+    }
+
+    /// 按条件查询已结束的航段，按开始时间升序
+    ///
+    /// 时间条件匹配与该时间段有重叠的航段，经纬度范围匹配与之相交的航段；不支持按运营人 ID 过滤。
+    pub fn tracks(&self, query: &FixQuery) -> rusqlite::Result<Vec<TrackSegment>> {
+        let mut sql = String::from(
+            "SELECT uas_id, start_ms, end_ms, fixes, min_lat, max_lat, min_lon, max_lon, max_altitude, distance_m, tenant, site FROM tracks",
+        );
+        let mut clauses = Vec::new();
+        let mut values: Vec<Value> = Vec::new();
+        if let Some(bbox) = query.bbox {
+            clauses.push("max_lat >= ? AND min_lat <= ? AND max_lon >= ? AND min_lon <= ?");
+            values.extend([bbox.min_lat, bbox.max_lat, bbox.min_lon, bbox.max_lon].map(Value::Real));
+        }
+        if let Some(prefix) = &query.uas_id_prefix {
+            clauses.push("uas_id >= ? AND uas_id < ?");
+            values.push(Value::Text(prefix.clone()));
+            values.push(Value::Text(format!("{}\u{10FFFF}", prefix)));
+        }
+        if let Some(tenant) = &query.tenant {
+            clauses.push("tenant = ?");
+            values.push(Value::Text(tenant.clone()));
+        }
+        if let Some(site) = &query.site {
+            clauses.push("site = ?");
+            values.push(Value::Text(site.clone()));
+        }
+        if let Some(from) = query.from_ms {
+            clauses.push("end_ms >= ?");
+            values.push(Value::Integer(from));
+        }
+        if let Some(to) = query.to_ms {
+            clauses.push("start_ms < ?");
+            values.push(Value::Integer(to));
+        }
+        if !clauses.is_empty() {
+            sql.push_str(" WHERE ");
+            sql.push_str(&clauses.join(" AND "));
+        }
+        sql.push_str(" ORDER BY start_ms");
+        if let Some(limit) = query.limit {
+            sql.push_str(&format!(" LIMIT {}", limit));
+        }
+
+        let mut stmt = self.conn.prepare(&sql)?;
+        let rows = stmt.query_map(params_from_iter(values), |row| {
+            let bounds: [Option<f64>; 4] = [row.get(4)?, row.get(5)?, row.get(6)?, row.get(7)?];
+            let bounds = match bounds {
+                [Some(min_lat), Some(max_lat), Some(min_lon), Some(max_lon)] => Some(BoundingBox { min_lat, min_lon, max_lat, max_lon }),
+                _ => None,
+            };
+            Ok(TrackSegment {
+                uas_id: row.get(0)?,
+                start_ms: row.get(1)?,
+                end_ms: row.get(2)?,
+                fixes: row.get::<_, i64>(3)? as u64,
+                bounds,
+                max_altitude_m: row.get(8)?,
+                distance_m: row.get(9)?,
+                tenant: row.get(10)?,
+                site: row.get(11)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    /// 按条件查询定位记录，按接收时间升序
+    pub fn query(&self, query: &FixQuery) -> rusqlite::Result<Vec<DecodedEvent>> {
+        let mut sql = String::from("SELECT f.received_at_ms, f.record FROM fixes f");
+        let mut clauses = Vec::new();
+        let mut values: Vec<Value> = Vec::new();
+        if let Some(bbox) = query.bbox {
+            sql.push_str(" JOIN fixes_rtree r ON r.id = f.id");
+            clauses.push("r.min_lat >= ? AND r.max_lat <= ? AND r.min_lon >= ? AND r.max_lon <= ?");
+            values.extend([bbox.min_lat, bbox.max_lat, bbox.min_lon, bbox.max_lon].map(Value::Real));
+        }
+        if let Some(prefix) = &query.uas_id_prefix {
+            // 用范围条件代替 LIKE，以便使用 (uas_id, 时间) 索引
+            clauses.push("f.uas_id >= ? AND f.uas_id < ?");
+            values.push(Value::Text(prefix.clone()));
+            values.push(Value::Text(format!("{}\u{10FFFF}", prefix)));
+        }
+        if let Some(operator_id) = &query.operator_id {
+            clauses.push("f.operator_id = ?");
+            values.push(Value::Text(operator_id.clone()));
+        }
+        if let Some(tenant) = &query.tenant {
+            clauses.push("f.tenant = ?");
+            values.push(Value::Text(tenant.clone()));
+        }
+        if let Some(site) = &query.site {
+            clauses.push("f.site = ?");
+            values.push(Value::Text(site.clone()));
+        }
+        if let Some(from) = query.from_ms {
+            clauses.push("f.received_at_ms >= ?");
+            values.push(Value::Integer(from));
+        }
+        if let Some(to) = query.to_ms {
+            clauses.push("f.received_at_ms < ?");
+            values.push(Value::Integer(to));
+        }
+        if !clauses.is_empty() {
+            sql.push_str(" WHERE ");
+            sql.push_str(&clauses.join(" AND "));
+        }
+        sql.push_str(" ORDER BY f.received_at_ms");
+        if let Some(limit) = query.limit {
+            sql.push_str(&format!(" LIMIT {}", limit));
+        }
+
+        let mut stmt = self.conn.prepare(&sql)?;
+        let rows = stmt.query_map(params_from_iter(values), |row| {
+            let received_at_ms: i64 = row.get(0)?;
+            let json: String = row.get(1)?;
+            let record = serde_json::from_str(&json)
+                .map_err(|e| rusqlite::Error::FromSqlConversionFailure(1, rusqlite::types::Type::Text, e.into()))?;
+            Ok(DecodedEvent { received_at_ms, record })
+        })?;
+        rows.collect()
+    }
+
+    /// 时间段内的精简定位样本，按接收时间升序，用于按时间段聚合
+    pub fn samples(&self, from_ms: i64, to_ms: i64) -> rusqlite::Result<Vec<FixSample>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT f.received_at_ms, f.uas_id, coalesce(f.operator_id, d.operator_id),
+                    json_extract(f.record, '$.ua_type'), f.latitude, f.longitude
+             FROM fixes f LEFT JOIN drones d ON d.uas_id = f.uas_id
+             WHERE f.received_at_ms >= ?1 AND f.received_at_ms < ?2 ORDER BY f.received_at_ms",
+        )?;
+        let rows = stmt.query_map(params![from_ms, to_ms], |row| {
+            let lat: Option<f64> = row.get(4)?;
+            let lon: Option<f64> = row.get(5)?;
+            Ok(FixSample {
+                received_at_ms: row.get(0)?,
+                uas_id: row.get(1)?,
+                operator_id: row.get(2)?,
+                ua_type: row.get(3)?,
+                position: lat.zip(lon),
+            })
+        })?;
+        rows.collect()
+    }
+
+    /// 按 UAS ID 或运营人 ID 的子串搜索无人机（至少 3 个字符）
+    pub fn search_drones(&self, text: &str) -> rusqlite::Result<Vec<DroneSummary>> {
+        let pattern = format!("\"{}\"", text.replace('"', "\"\""));
+        let mut stmt = self.conn.prepare(
+            "SELECT d.uas_id, d.operator_id, d.first_seen_ms, d.last_seen_ms
+             FROM drones_fts JOIN drones d ON d.rowid = drones_fts.rowid
+             WHERE drones_fts MATCH ?1 ORDER BY d.last_seen_ms DESC",
+        )?;
+        let rows = stmt.query_map([pattern], |row| {
+            Ok(DroneSummary {
+                uas_id: row.get(0)?,
+                operator_id: row.get(1)?,
+                first_seen_ms: row.get(2)?,
+                last_seen_ms: row.get(3)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    /// 保存身份摘要：首次/最近出现时间取并集，别名和标注以新值为准
+    pub fn save_identities(&mut self, identities: &[DroneIdentity]) -> rusqlite::Result<()> {
+        let tx = self.conn.transaction()?;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT INTO drones (uas_id, first_seen_ms, last_seen_ms, aliases, annotation) VALUES (?1, ?2, ?3, ?4, ?5)
+                 ON CONFLICT (uas_id) DO UPDATE SET
+                     first_seen_ms = min(first_seen_ms, excluded.first_seen_ms),
+                     last_seen_ms = max(last_seen_ms, excluded.last_seen_ms),
+                     aliases = excluded.aliases,
+                     annotation = coalesce(excluded.annotation, annotation)",
+            )?;
+            for identity in identities {
+                let aliases = serde_json::to_string(&identity.aliases).unwrap_or_default();
+                let annotation = identity.annotation.as_ref().and_then(|a| serde_json::to_string(a).ok());
+                stmt.execute(params![identity.uas_id, identity.first_seen_ms, identity.last_seen_ms, aliases, annotation])?;
+            }
+        }
+        tx.commit()
+    }
+
+    /// 载入所有无人机的身份摘要
+    pub fn load_identities(&self) -> rusqlite::Result<Vec<DroneIdentity>> {
+        let mut stmt = self.conn.prepare("SELECT uas_id, first_seen_ms, last_seen_ms, aliases, annotation FROM drones")?;
+        let rows = stmt.query_map([], |row| {
+            let aliases: Option<String> = row.get(3)?;
+            let annotation: Option<String> = row.get(4)?;
+            Ok(DroneIdentity {
+                uas_id: row.get(0)?,
+                first_seen_ms: row.get(1)?,
+                last_seen_ms: row.get(2)?,
+                aliases: aliases.and_then(|json| serde_json::from_str(&json).ok()).unwrap_or_default(),
+                annotation: annotation.and_then(|json| serde_json::from_str(&json).ok()),
+            })
+        })?;
+        rows.collect()
+    }
+
+    /// 以 (规则名, 对象, 触发时间) 替换保存的告警冷却状态
+    pub fn save_cooldowns(&mut self, cooldowns: &[(String, String, i64)]) -> rusqlite::Result<()> {
+        let tx = self.conn.transaction()?;
+        tx.execute("DELETE FROM alert_cooldowns", [])?;
+        {
+            let mut stmt = tx.prepare("INSERT INTO alert_cooldowns (rule, subject, fired_ms) VALUES (?1, ?2, ?3)")?;
+            for (rule, subject, fired_ms) in cooldowns {
+                stmt.execute(params![rule, subject, fired_ms])?;
+            }
+        }
+        tx.commit()
+    }
+
+    pub fn load_cooldowns(&self) -> rusqlite::Result<Vec<(String, String, i64)>> {
+        let mut stmt = self.conn.prepare("SELECT rule, subject, fired_ms FROM alert_cooldowns")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?;
+        rows.collect()
+    }
+
+    pub fn drone(&self, uas_id: &str) -> rusqlite::Result<Option<DroneSummary>> {
+        self.conn.query_row(
+            "SELECT uas_id, operator_id, first_seen_ms, last_seen_ms FROM drones WHERE uas_id = ?1",
+            [uas_id],
+            |row| Ok(DroneSummary {
+                uas_id: row.get(0)?,
+                operator_id: row.get(1)?,
+                first_seen_ms: row.get(2)?,
+                last_seen_ms: row.get(3)?,
+            }),
+        ).optional()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::upload_data::UploadData;
+
+    fn fix(rid: &str, at: i64, lat: f64, lon: f64) -> DecodedEvent {
+        let record = UploadData {
+            rid: rid.to_string(),
+            track_id: "aa:bb:cc:dd:ee:ff".to_string(),
+            latitude: (lat * 1e7) as i32,
+            longitude: (lon * 1e7) as i32,
+            ..Default::default()
+        };
+        DecodedEvent { received_at_ms: at, record }
+    }
+
+    #[test]
+    fn test_store_query() {
+        let mut store = Store::open(":memory:").unwrap();
+        store.insert(&fix("1581F5FKD229400A", 1_000, 31.20, 121.40), None).unwrap();
+        store.insert(&fix("1581F5FKD229400A", 2_000, 31.30, 121.50), Some("CHN-OP-77")).unwrap();
+        let mut tagged = fix("1668B0012345", 3_000, 31.21, 121.41);
+        tagged.record.tags.tenant = Some("acme".into());
+        store.insert(&tagged, None).unwrap();
+
+        let by_prefix = store.query(&FixQuery { uas_id_prefix: Some("1581".into()), ..Default::default() }).unwrap();
+        assert_eq!(by_prefix.len(), 2);
+
+        let bbox = BoundingBox { min_lat: 31.1, min_lon: 121.3, max_lat: 31.25, max_lon: 121.45 };
+        let in_box = store.query(&FixQuery { bbox: Some(bbox), from_ms: Some(1_500), ..Default::default() }).unwrap();
+        assert_eq!(in_box.len(), 1);
+        assert_eq!(in_box[0].record.rid, "1668B0012345");
+
+        let by_tenant = store.query(&FixQuery { tenant: Some("acme".into()), ..Default::default() }).unwrap();
+        assert_eq!(by_tenant.len(), 1);
+        assert_eq!(by_tenant[0].record.tags.tenant.as_deref(), Some("acme"));
+
+        let samples = store.samples(1_500, 3_500).unwrap();
+        assert_eq!(samples.len(), 2);
+        // 运营人 ID 取该无人机已知的值
+        assert_eq!(samples[0].operator_id.as_deref(), Some("CHN-OP-77"));
+        assert_eq!((samples[1].ua_type, samples[1].position.is_some()), (None, true));
+
+        let found = store.search_drones("OP-7").unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].first_seen_ms, 1_000);
+        assert_eq!(found[0].last_seen_ms, 2_000);
+
+        // 身份摘要与已有的无人机行合并
+        let identity = DroneIdentity {
+            uas_id: "1581F5FKD229400A".into(),
+            first_seen_ms: 500,
+            last_seen_ms: 1_500,
+            aliases: ["aa:bb:cc:dd:ee:ff".to_string()].into(),
+            annotation: None,
+        };
+        store.save_identities(&[identity]).unwrap();
+        let restored = store.load_identities().unwrap();
+        let restored = restored.iter().find(|i| i.uas_id == "1581F5FKD229400A").unwrap();
+        assert_eq!((restored.first_seen_ms, restored.last_seen_ms, restored.aliases.len()), (500, 2_000, 1));
+    }
+
+    #[test]
+    fn test_track_segments() {
+        let mut store = Store::open(":memory:").unwrap();
+        store.insert(&fix("RID-1", 0, 31.200, 121.400), None).unwrap();
+        store.insert(&fix("RID-1", 10_000, 31.209, 121.400), None).unwrap();
+        // 间隔超过航段间隔，前一航段写入数据库
+        store.insert(&fix("RID-1", 10_000 + SEGMENT_GAP_MS + 1, 40.0, 116.0), None).unwrap();
+        store.insert(&fix("RID-2", 20_000, 31.0, 121.0), None).unwrap();
+
+        let tracks = store.tracks(&FixQuery::default()).unwrap();
+        assert_eq!(tracks.len(), 1);
+        assert_eq!((tracks[0].start_ms, tracks[0].end_ms, tracks[0].fixes), (0, 10_000, 2));
+        assert!((tracks[0].distance_m - 1001.0).abs() < 1.0);
+        assert_eq!(tracks[0].bounds.map(|b| b.max_lat), Some(31.209));
+
+        assert_eq!(store.finish_segments(20_000 + SEGMENT_GAP_MS + 1).unwrap(), 1);
+        let near = BoundingBox { min_lat: 30.9, min_lon: 120.9, max_lat: 31.1, max_lon: 121.1 };
+        let tracks = store.tracks(&FixQuery { bbox: Some(near), ..Default::default() }).unwrap();
+        assert_eq!(tracks.iter().map(|t| t.uas_id.as_str()).collect::<Vec<_>>(), ["RID-2"]);
+        let tracks = store.tracks(&FixQuery { uas_id_prefix: Some("RID-1".into()), from_ms: Some(5_000), ..Default::default() }).unwrap();
+        assert_eq!(tracks.len(), 1);
+    }
+
+    #[test]
+    fn test_migrate_idempotent() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        assert_eq!(migrate(&mut conn).unwrap(), (0, SCHEMA_VERSION));
+        assert_eq!(migrate(&mut conn).unwrap(), (SCHEMA_VERSION, SCHEMA_VERSION));
+        conn.pragma_update(None, "user_version", SCHEMA_VERSION + 1).unwrap();
+        assert!(migrate(&mut conn).is_err());
+    }
+}
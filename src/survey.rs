@@ -0,0 +1,168 @@
+use std::collections::{BTreeMap, HashSet};
+use std::io::{self, Write};
+use std::time::Duration;
+
+use crate::upload_data::UploadData;
+
+/// 单个信道的勘测结果
+#[derive(Debug, Default)]
+pub struct ChannelSurvey {
+    pub dwell: Duration,
+    pub frames: u64,
+    pub detections: u64,
+    pub drones: HashSet<String>,
+    pub best_rssi: Option<f32>,
+    rssi_sum: f64,
+    rssi_count: u64,
+}
+
+impl ChannelSurvey {
+    pub fn mean_rssi(&self) -> Option<f32> {
+        (self.rssi_count > 0).then(|| (self.rssi_sum / self.rssi_count as f64) as f32)
+    }
+
+    /// 每分钟停留时间内的 Remote ID 检测数
+    pub fn detections_per_minute(&self) -> f64 {
+        let minutes = self.dwell.as_secs_f64() / 60.0;
+        if minutes > 0.0 { self.detections as f64 / minutes } else { 0.0 }
+    }
+}
+
+/// 信道勘测：轮换所有信道，统计各信道的 Remote ID 活动
+#[derive(Debug, Default)]
+pub struct Survey {
+    channels: BTreeMap<u8, ChannelSurvey>,
+}
+
+impl Survey {
+    pub fn add_dwell(&mut self, channel: u8, dwell: Duration) {
+        self.channels.entry(channel).or_default().dwell += dwell;
+    }
+
+    pub fn add_frame(&mut self, channel: u8, records: &[UploadData]) {
+        let entry = self.channels.entry(channel).or_default();
+        entry.frames += 1;
+        for record in records {
+            entry.detections += 1;
+            entry.drones.insert(if record.rid.is_empty() { record.track_id.clone() } else { record.rid.clone() });
+            if let Some(rssi) = record.rssi {
+                entry.rssi_sum += rssi as f64;
+                entry.rssi_count += 1;
+                entry.best_rssi = Some(entry.best_rssi.map_or(rssi, |best| best.max(rssi)));
+            }
+        }
+    }
+
+    /// 按不同无人机数、单位时间检测数排序的信道，没有检测的信道排在最后
+    pub fn ranked(&self) -> Vec<(u8, &ChannelSurvey)> {
+        let mut ranked: Vec<_> = self.channels.iter().map(|(c, s)| (*c, s)).collect();
+        ranked.sort_by(|(_, a), (_, b)| {
+            b.drones.len().cmp(&a.drones.len())
+                .then(b.detections_per_minute().total_cmp(&a.detections_per_minute()))
+        });
+        ranked
+    }
+
+    pub fn write_report<W: Write>(&self, mut out: W) -> io::Result<()> {
+        writeln!(out, "{:>4} {:>8} {:>8} {:>8} {:>8} {:>10} {:>10}",
+            "信道", "停留(s)", "帧数", "检测数", "无人机", "平均RSSI", "最强RSSI")?;
+        let ranked = self.ranked();
+        for (channel, s) in &ranked {
+            writeln!(out, "{:>4} {:>8.0} {:>8} {:>8} {:>8} {:>10} {:>10}",
+                channel, s.dwell.as_secs_f64(), s.frames, s.detections, s.drones.len(),
+                s.mean_rssi().map(|v| format!("{:.1}", v)).unwrap_or("-".into()),
+                s.best_rssi.map(|v| format!("{:.1}", v)).unwrap_or("-".into()))?;
+        }
+        let active: Vec<String> = ranked.iter()
+            .filter(|(_, s)| s.detections > 0)
+            .map(|(c, _)| c.to_string())
+            .collect();
+        if active.is_empty() {
+            writeln!(out, "\n勘测期间未发现 Remote ID 活动")
+        } else {
+            writeln!(out, "\n建议长期监听的信道 (按优先级): {}", active.join(", "))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn drone(rid: &str, rssi: Option<f32>) -> UploadData {
+        UploadData { rid: rid.into(), rssi, ..Default::default() }
+    }
+
+    fn ranking(survey: &Survey) -> Vec<u8> {
+        survey.ranked().iter().map(|(c, _)| *c).collect()
+    }
+
+    fn report(survey: &Survey) -> String {
+        let mut out = Vec::new();
+        survey.write_report(&mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_channel_counters() {
+        let mut survey = Survey::default();
+        survey.add_dwell(6, Duration::from_secs(30));
+        survey.add_dwell(6, Duration::from_secs(30));
+        survey.add_frame(6, &[]);
+        survey.add_frame(6, &[drone("A", Some(-60.0)), drone("A", Some(-80.0)), drone("", None)]);
+        let (_, channel) = survey.ranked()[0];
+        assert_eq!((channel.dwell, channel.frames, channel.detections), (Duration::from_secs(60), 2, 3));
+        assert_eq!(channel.drones.len(), 2);   // 没有 UAS ID 的按轨迹 ID 计
+        assert_eq!((channel.mean_rssi(), channel.best_rssi), (Some(-70.0), Some(-60.0)));
+        assert_eq!(channel.detections_per_minute(), 3.0);
+    }
+
+    #[test]
+    fn test_no_dwell_or_rssi() {
+        let channel = ChannelSurvey::default();
+        assert_eq!((channel.mean_rssi(), channel.detections_per_minute()), (None, 0.0));
+    }
+
+    #[test]
+    fn test_rank_by_drone_count() {
+        let mut survey = Survey::default();
+        for channel in [1, 6, 149] {
+            survey.add_dwell(channel, Duration::from_secs(60));
+        }
+        survey.add_frame(1, &[]);
+        survey.add_frame(6, &[drone("A", Some(-70.0))]);
+        survey.add_frame(6, &[drone("A", Some(-70.0))]);
+        survey.add_frame(149, &[drone("A", Some(-60.0)), drone("B", Some(-80.0))]);
+        assert_eq!(ranking(&survey), [149, 6, 1]);
+    }
+
+    #[test]
+    fn test_rank_by_detection_rate() {
+        let mut survey = Survey::default();
+        survey.add_dwell(1, Duration::from_secs(60));
+        survey.add_dwell(6, Duration::from_secs(30));
+        survey.add_frame(1, &[drone("A", None), drone("A", None)]);
+        // 停留时间短但检测更频繁
+        survey.add_frame(6, &[drone("A", None), drone("A", None)]);
+        assert_eq!(ranking(&survey), [6, 1]);
+    }
+
+    #[test]
+    fn test_report_recommends_active_channels() {
+        let mut survey = Survey::default();
+        survey.add_dwell(1, Duration::from_secs(60));
+        survey.add_dwell(149, Duration::from_secs(60));
+        survey.add_frame(149, &[drone("A", Some(-62.5))]);
+        let report = report(&survey);
+        let rows: Vec<Vec<&str>> = report.lines().skip(1).take(2).map(|line| line.split_whitespace().collect()).collect();
+        assert_eq!(rows, [vec!["149", "60", "1", "1", "1", "-62.5", "-62.5"], vec!["1", "60", "0", "0", "0", "-", "-"]]);
+        assert!(report.ends_with("\n建议长期监听的信道 (按优先级): 149\n"), "{}", report);
+    }
+
+    #[test]
+    fn test_report_without_activity() {
+        let mut survey = Survey::default();
+        survey.add_dwell(1, Duration::from_secs(60));
+        assert!(report(&survey).ends_with("\n勘测期间未发现 Remote ID 活动\n"));
+    }
+}
@@ -0,0 +1,197 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::thread;
+use std::time::Duration;
+
+use serde::Serialize;
+use tracing::{info, warn};
+
+/// 网络接口状态
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct NetworkStatus {
+    pub name: String,
+    pub up: bool,
+    pub rx_bytes: u64,
+    pub tx_bytes: u64,
+}
+
+/// 主机运行状态，供远程运维判断户外传感器是否过热、磁盘将满
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SystemTelemetry {
+    pub cpu_temp_c: Option<f32>,
+    pub load_avg: Option<[f32; 3]>,         // 1/5/15 分钟平均负载
+    pub disk_free_bytes: Option<u64>,
+    pub disk_total_bytes: Option<u64>,
+    pub networks: Vec<NetworkStatus>,
+}
+
+impl SystemTelemetry {
+    /// 超过该温度告警
+    pub const HOT_CPU_C: f32 = 75.0;
+    /// 磁盘剩余低于该比例告警
+    pub const LOW_DISK_RATIO: f64 = 0.05;
+
+    /// 采集当前状态，`data_dir` 为录制文件/数据库所在目录（用于统计磁盘空间）
+    pub fn collect(data_dir: &Path) -> Self {
+        let (disk_free_bytes, disk_total_bytes) = disk_usage(data_dir).unzip();
+        Self {
+            cpu_temp_c: cpu_temperature(),
+            load_avg: fs::read_to_string("/proc/loadavg").ok().as_deref().and_then(parse_loadavg),
+            disk_free_bytes,
+            disk_total_bytes,
+            networks: network_status(),
+        }
+    }
+
+    /// 需要告警的问题
+    pub fn warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if let Some(temp) = self.cpu_temp_c
+            && temp >= Self::HOT_CPU_C
+        {
+            warnings.push(format!("CPU 温度过高: {:.1}°C", temp));
+        }
+        if let (Some(free), Some(total)) = (self.disk_free_bytes, self.disk_total_bytes)
+            && total > 0
+            && (free as f64 / total as f64) < Self::LOW_DISK_RATIO
+        {
+            warnings.push(format!("磁盘剩余空间不足: {} MB / {} MB", free >> 20, total >> 20));
+        }
+        if !self.networks.is_empty() && self.networks.iter().all(|n| !n.up) {
+            warnings.push("所有网络接口均已断开".to_string());
+        }
+        warnings
+    }
+
+    /// 定期输出主机状态，出现问题时告警
+    pub fn spawn_reporter(data_dir: PathBuf, interval: Duration) {
+        thread::spawn(move || loop {
+            let telemetry = Self::collect(&data_dir);
+            info!(
+                "system: cpu_temp={} load={} disk_free_mb={} networks_up={}/{}",
+                telemetry.cpu_temp_c.map(|t| format!("{:.1}", t)).unwrap_or_default(),
+                telemetry.load_avg.map(|l| format!("{:.2}", l[0])).unwrap_or_default(),
+                telemetry.disk_free_bytes.map(|b| (b >> 20).to_string()).unwrap_or_default(),
+                telemetry.networks.iter().filter(|n| n.up).count(),
+                telemetry.networks.len(),
+            );
+            for warning in telemetry.warnings() {
+                warn!("{}", warning);
+            }
+            thread::sleep(interval);
+        });
+    }
+}
+
+/// 各温区中的最高温度（树莓派只有一个 cpu-thermal）
+fn cpu_temperature() -> Option<f32> {
+    fs::read_dir("/sys/class/thermal").ok()?
+        .flatten()
+        .filter(|e| e.file_name().to_string_lossy().starts_with("thermal_zone"))
+        .filter_map(|e| fs::read_to_string(e.path().join("temp")).ok())
+        .filter_map(|text| parse_millidegrees(&text))
+        .reduce(f32::max)
+}
+
+fn parse_millidegrees(text: &str) -> Option<f32> {
+    text.trim().parse::<i64>().ok().map(|m| m as f32 / 1000.0)
+}
+
+fn parse_loadavg(text: &str) -> Option<[f32; 3]> {
+    let mut fields = text.split_whitespace().map(|f| f.parse::<f32>().ok());
+    Some([fields.next()??, fields.next()??, fields.next()??])
+}
+
+/// 通过 `df` 获取 (剩余, 总计) 字节数
+fn disk_usage(path: &Path) -> Option<(u64, u64)> {
+    let output = Command::new("df").arg("-Pk").arg(path).output().ok()?;
+    parse_df(&String::from_utf8_lossy(&output.stdout))
+}
+
+fn parse_df(text: &str) -> Option<(u64, u64)> {
+    // Filesystem 1024-blocks Used Available Capacity Mounted-on
+    let fields: Vec<&str> = text.lines().nth(1)?.split_whitespace().collect();
+    let total: u64 = fields.get(1)?.parse().ok()?;
+    let available: u64 = fields.get(3)?.parse().ok()?;
+    Some((available * 1024, total * 1024))
+}
+
+/// 除回环和无线抓包网卡外的网络接口（回传链路）
+fn network_status() -> Vec<NetworkStatus> {
+    let Ok(entries) = fs::read_dir("/sys/class/net") else { return Vec::new() };
+    let mut networks: Vec<NetworkStatus> = entries.flatten()
+        .filter(|e| e.file_name() != "lo" && !e.path().join("phy80211").exists())
+        .map(|e| {
+            let path = e.path();
+            let read = |name: &str| fs::read_to_string(path.join(name)).unwrap_or_default();
+            NetworkStatus {
+                name: e.file_name().to_string_lossy().into_owned(),
+                up: read("operstate").trim() == "up",
+                rx_bytes: read("statistics/rx_bytes").trim().parse().unwrap_or_default(),
+                tx_bytes: read("statistics/tx_bytes").trim().parse().unwrap_or_default(),
+            }
+        })
+        .collect();
+    networks.sort_by(|a, b| a.name.cmp(&b.name));
+    networks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn network(name: &str, up: bool) -> NetworkStatus {
+        NetworkStatus { name: name.into(), up, rx_bytes: 0, tx_bytes: 0 }
+    }
+
+    #[test]
+    fn test_parse_millidegrees() {
+        assert_eq!(parse_millidegrees("78312\n"), Some(78.312));
+        assert_eq!(parse_millidegrees("-5000"), Some(-5.0));
+        assert_eq!(parse_millidegrees(""), None);
+    }
+
+    #[test]
+    fn test_parse_loadavg() {
+        assert_eq!(parse_loadavg("0.52 0.58 0.59 1/389 12345\n"), Some([0.52, 0.58, 0.59]));
+        assert_eq!(parse_loadavg("0.52 0.58"), None);
+        assert_eq!(parse_loadavg("0.52 high 0.59"), None);
+    }
+
+    #[test]
+    fn test_parse_df() {
+        let df = "Filesystem     1024-blocks     Used Available Capacity Mounted on\n\
+                  /dev/root         30000000 29000000   1000000      97% /\n";
+        assert_eq!(parse_df(df), Some((1_024_000_000, 30_720_000_000)));
+        assert_eq!(parse_df("Filesystem 1024-blocks Used Available Capacity Mounted on\n"), None);
+    }
+
+    #[test]
+    fn test_hot_cpu_warning() {
+        let telemetry = SystemTelemetry { cpu_temp_c: Some(78.3), ..Default::default() };
+        assert_eq!(telemetry.warnings(), ["CPU 温度过高: 78.3°C"]);
+        assert!(SystemTelemetry { cpu_temp_c: Some(60.0), ..Default::default() }.warnings().is_empty());
+    }
+
+    #[test]
+    fn test_low_disk_warning() {
+        let telemetry = SystemTelemetry {
+            disk_free_bytes: Some(1_024_000_000),
+            disk_total_bytes: Some(30_720_000_000),
+            ..Default::default()
+        };
+        assert_eq!(telemetry.warnings(), ["磁盘剩余空间不足: 976 MB / 29296 MB"]);
+        let empty = SystemTelemetry { disk_free_bytes: Some(0), disk_total_bytes: Some(0), ..Default::default() };
+        assert!(empty.warnings().is_empty());
+    }
+
+    #[test]
+    fn test_all_networks_down_warning() {
+        let down = SystemTelemetry { networks: vec![network("eth0", false), network("wwan0", false)], ..Default::default() };
+        assert_eq!(down.warnings(), ["所有网络接口均已断开"]);
+        let partial = SystemTelemetry { networks: vec![network("eth0", false), network("wwan0", true)], ..Default::default() };
+        assert!(partial.warnings().is_empty());
+        assert!(SystemTelemetry::default().warnings().is_empty());
+    }
+}
@@ -0,0 +1,162 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::decode::{self, DecodeContext};
+use crate::upload_data::UploadData;
+
+/// 测试用的抓包帧：`tests/fixtures/<name>.hex`
+///
+/// 文件内容为带 radiotap 头的原始帧的十六进制，字节之间可以有空白和换行；`#` 开头的行为注释，
+/// 其中 `# records: N` 和 `# rid: <UAS ID>` 为期望的解码结果，由 [`Fixture::check`] 校验。
+/// 新厂商的回归样例只需在目录中加一个文件：
+///
+/// ```text
+/// # 某厂商信标，Basic ID 在第二个消息包中
+/// # records: 1
+/// # rid: 1581F7FVC251A00CQ25C
+/// 00 00 26 00 2f 40 00 a0 ...
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Fixture {
+    pub name: String,
+    pub frame: Vec<u8>,
+    pub expected_records: Option<usize>,
+    pub expected_rids: Vec<String>,
+}
+
+/// 仓库中的样例目录
+pub fn fixtures_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("fixtures")
+}
+
+impl Fixture {
+    pub fn parse(name: &str, text: &str) -> Result<Self, String> {
+        let mut fixture = Fixture { name: name.to_string(), ..Default::default() };
+        for (number, line) in text.lines().enumerate() {
+            if let Some(comment) = line.trim().strip_prefix('#') {
+                match comment.split_once(':').map(|(k, v)| (k.trim(), v.trim())) {
+                    Some(("records", n)) => {
+                        fixture.expected_records = Some(n.parse().map_err(|_| format!("{}:{}: records 应为整数", name, number + 1))?);
+                    }
+                    Some(("rid", rid)) => fixture.expected_rids.push(rid.to_string()),
+                    _ => {}
+                }
+                continue;
+            }
+            for byte in line.split_whitespace() {
+                let value = u8::from_str_radix(byte.trim_start_matches("0x"), 16)
+                    .map_err(|_| format!("{}:{}: 无效的十六进制字节 {:?}", name, number + 1, byte))?;
+                fixture.frame.push(value);
+            }
+        }
+        Ok(fixture)
+    }
+
+    /// 读取 `tests/fixtures/<name>.hex`，文件不存在或格式错误时 panic
+    pub fn load(name: &str) -> Self {
+        let path = fixtures_dir().join(format!("{}.hex", name));
+        let text = fs::read_to_string(&path).unwrap_or_else(|e| panic!("无法读取样例 {}: {}", path.display(), e));
+        Self::parse(name, &text).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// 按文件名顺序读取目录中的全部样例
+    pub fn load_all() -> Vec<Self> {
+        let mut names: Vec<String> = fs::read_dir(fixtures_dir())
+            .map(|entries| entries.filter_map(|e| e.ok())
+                .filter_map(|e| e.path().file_name()?.to_str()?.strip_suffix(".hex").map(str::to_string))
+                .collect())
+            .unwrap_or_default();
+        names.sort();
+        names.iter().map(|name| Self::load(name)).collect()
+    }
+
+    /// 用默认解码上下文经公开的解码接口解析该帧
+    pub fn decode(&self) -> Vec<UploadData> {
+        self.decode_with(&mut DecodeContext::default())
+    }
+
+    pub fn decode_with(&self, ctx: &mut DecodeContext) -> Vec<UploadData> {
+        decode::process_packet(&self.frame, ctx)
+    }
+
+    /// 解码并与文件中的期望比较，不符时返回说明
+    pub fn check(&self) -> Result<Vec<UploadData>, String> {
+        let records = self.decode();
+        if let Some(expected) = self.expected_records
+            && records.len() != expected
+        {
+            return Err(format!("{}: 期望 {} 条记录，解出 {} 条", self.name, expected, records.len()));
+        }
+        for rid in &self.expected_rids {
+            if !records.iter().any(|r| &r.rid == rid) {
+                let found: Vec<&str> = records.iter().map(|r| r.rid.as_str()).collect();
+                return Err(format!("{}: 没有解出 UAS ID {}，解出 {:?}", self.name, rid, found));
+            }
+        }
+        Ok(records)
+    }
+
+    /// 由样例派生的畸形帧：每个截断长度，以及逐字节替换为 0x00/0xff/原值取反的变体
+    ///
+    /// 用于验证解析路径对任意输入只返回错误而不 panic；fuzz 目标的初始语料也由此生成。
+    pub fn mutations(&self) -> impl Iterator<Item = Vec<u8>> + '_ {
+        let truncated = (0..self.frame.len()).map(|len| self.frame[..len].to_vec());
+        let flipped = (0..self.frame.len()).flat_map(move |i| {
+            [0x00, 0xff, !self.frame[i]].into_iter().map(move |value| {
+                let mut frame = self.frame.clone();
+                frame[i] = value;
+                frame
+            })
+        });
+        truncated.chain(flipped)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixtures() {
+        let fixture = Fixture::parse("inline", "# records: 0\n# 注释\n00 00 0x08 00\n00 00 00 00\n").unwrap();
+        assert_eq!((fixture.frame.len(), fixture.expected_records), (8, Some(0)));
+        assert!(Fixture::parse("bad", "00 zz").unwrap_err().contains("bad:1"));
+
+        let fixtures = Fixture::load_all();
+        assert!(!fixtures.is_empty());
+        for fixture in fixtures {
+            if let Err(e) = fixture.check() {
+                panic!("{}", e);
+            }
+        }
+    }
+
+    #[test]
+    fn test_malformed_fixtures_do_not_panic() {
+        use crate::message::AnyMessage;
+
+        for fixture in Fixture::load_all() {
+            for frame in fixture.mutations() {
+                decode::process_packet(&frame, &mut DecodeContext::default());
+                let (_, frame) = decode::parse_radiotap(&frame);
+                let _ = decode::parse_remote_id_frame(frame);
+            }
+        }
+        // 各消息类型的任意长度
+        let mut seed = 0x2545_f491u32;
+        for len in 0..=32 {
+            for message_type in 0..16u8 {
+                let mut data: Vec<u8> = (0..len).map(|_| {
+                    seed ^= seed << 13;
+                    seed ^= seed >> 17;
+                    seed ^= seed << 5;
+                    seed as u8
+                }).collect();
+                if let Some(first) = data.first_mut() {
+                    *first = message_type << 4 | *first & 0x0f;
+                }
+                let _ = AnyMessage::from_bytes(&data);
+            }
+        }
+    }
+}
@@ -0,0 +1,185 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::event_log::DecodedEvent;
+use crate::geo::{degrees, distance_m};
+use crate::tracker;
+
+/// 上传限流：悬停的无人机每秒发出多个信标，内容几乎相同，只上传有意义的变化
+///
+/// ```toml
+/// [throttle]
+/// min_distance_m = 10    # 与上次上传的位置相距超过该距离 (含高度) 才上传
+/// max_interval_s = 5     # 距上次上传超过该时间时照常上传，作为心跳
+/// ```
+///
+/// 位置以外的字段（身份、速度、运行状态、控制站等）有变化时立即上传。
+/// 只影响上传和 MQTT，录制文件、NDJSON 输出和数据库仍保存全部记录。
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ThrottleConfig {
+    #[serde(default = "default_min_distance_m")]
+    pub min_distance_m: f64,
+    #[serde(default = "default_max_interval_s")]
+    pub max_interval_s: u64,
+}
+
+fn default_min_distance_m() -> f64 {
+    10.0
+}
+
+fn default_max_interval_s() -> u64 {
+    5
+}
+
+impl Default for ThrottleConfig {
+    fn default() -> Self {
+        Self { min_distance_m: default_min_distance_m(), max_interval_s: default_max_interval_s() }
+    }
+}
+
+/// 每架无人机上次上传的记录
+struct Uploaded {
+    at_ms: i64,
+    event: DecodedEvent,
+}
+
+pub struct UploadThrottle {
+    config: ThrottleConfig,
+    last: HashMap<String, Uploaded>,
+    suppressed: u64,
+}
+
+impl UploadThrottle {
+    pub fn new(config: ThrottleConfig) -> Self {
+        Self { config, last: HashMap::new(), suppressed: 0 }
+    }
+
+    /// 该记录是否上传；上传的记录作为之后比较的基准
+    pub fn admit(&mut self, event: &DecodedEvent) -> bool {
+        let r = &event.record;
+        let uas_id = if r.rid.is_empty() { &r.track_id } else { &r.rid };
+        let admitted = self.last.get(uas_id).is_none_or(|last| {
+            event.received_at_ms - last.at_ms >= self.config.max_interval_s as i64 * 1000
+                || moved_m(&last.event, event) > self.config.min_distance_m
+                || tracker::changed_fields(&last.event.record, r).iter().any(|f| *f != "position")
+        });
+        if admitted {
+            self.last.insert(uas_id.clone(), Uploaded { at_ms: event.received_at_ms, event: event.clone() });
+        } else {
+            self.suppressed += 1;
+        }
+        admitted
+    }
+
+    /// 无人机离开跟踪后不再保留其基准记录
+    pub fn forget(&mut self, uas_id: &str) {
+        self.last.remove(uas_id);
+    }
+
+    /// 未上传的记录数
+    pub fn suppressed(&self) -> u64 {
+        self.suppressed
+    }
+}
+
+/// 两条记录位置间的距离 (米)，含高度差
+fn moved_m(a: &DecodedEvent, b: &DecodedEvent) -> f64 {
+    let (a, b) = (&a.record, &b.record);
+    let horizontal = distance_m(degrees(a.latitude), degrees(a.longitude), degrees(b.latitude), degrees(b.longitude));
+    let vertical = (a.geometric_altitude as f64 - b.geometric_altitude as f64).abs();
+    horizontal.hypot(vertical)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::upload_data::UploadData;
+
+    fn fix(at: i64, latitude: i32, run_status: u8) -> DecodedEvent {
+        DecodedEvent {
+            received_at_ms: at,
+            record: UploadData { rid: "RID-1".into(), latitude, longitude: 1_214_000_000, run_status, ..Default::default() },
+        }
+    }
+
+    fn throttle() -> UploadThrottle {
+        let mut throttle = UploadThrottle::new(ThrottleConfig::default());
+        assert!(throttle.admit(&fix(0, 312_000_000, 2)));
+        throttle
+    }
+
+    #[test]
+    fn test_first_record_admitted() {
+        let mut throttle = UploadThrottle::new(ThrottleConfig::default());
+        assert!(throttle.admit(&fix(0, 312_000_000, 2)));
+        assert_eq!(throttle.suppressed(), 0);
+    }
+
+    #[test]
+    fn test_hover_jitter_suppressed() {
+        let mut throttle = throttle();
+        // 悬停：约 1 米的抖动
+        assert!(!throttle.admit(&fix(200, 312_000_100, 2)));
+        assert!(!throttle.admit(&fix(400, 312_000_050, 2)));
+        assert_eq!(throttle.suppressed(), 2);
+    }
+
+    #[test]
+    fn test_movement_admitted() {
+        let mut throttle = throttle();
+        // 移动约 22 米
+        assert!(throttle.admit(&fix(600, 312_002_000, 2)));
+    }
+
+    #[test]
+    fn test_climb_counts_as_movement() {
+        let mut throttle = throttle();
+        let climbed = DecodedEvent {
+            record: UploadData { geometric_altitude: 15, ..fix(600, 312_000_000, 2).record },
+            ..fix(600, 312_000_000, 2)
+        };
+        assert!(throttle.admit(&climbed));
+    }
+
+    #[test]
+    fn test_other_field_change_admitted() {
+        let mut throttle = throttle();
+        assert!(throttle.admit(&fix(800, 312_000_000, 1)));
+    }
+
+    #[test]
+    fn test_heartbeat_after_max_interval() {
+        let mut throttle = throttle();
+        assert!(!throttle.admit(&fix(4_999, 312_000_000, 2)));
+        assert!(throttle.admit(&fix(5_000, 312_000_000, 2)));
+        // 基准随上传更新
+        assert!(!throttle.admit(&fix(9_999, 312_000_000, 2)));
+    }
+
+    #[test]
+    fn test_drones_throttled_independently() {
+        let mut throttle = throttle();
+        let other = DecodedEvent {
+            record: UploadData { rid: String::new(), track_id: "02:11:22:33:44:55".into(), ..fix(200, 312_000_000, 2).record },
+            ..fix(200, 312_000_000, 2)
+        };
+        assert!(throttle.admit(&other));
+        assert!(!throttle.admit(&other));
+    }
+
+    #[test]
+    fn test_forget_resets_baseline() {
+        let mut throttle = throttle();
+        throttle.forget("RID-1");
+        assert!(throttle.admit(&fix(100, 312_000_000, 2)));
+    }
+
+    #[test]
+    fn test_config_rejects_unknown_fields() {
+        let config: ThrottleConfig = toml::from_str("min_distance_m = 25.0").unwrap();
+        assert_eq!(config, ThrottleConfig { min_distance_m: 25.0, max_interval_s: 5 });
+        assert!(toml::from_str::<ThrottleConfig>("interval = 5").is_err());
+    }
+}
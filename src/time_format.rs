@@ -0,0 +1,148 @@
+use std::sync::OnceLock;
+
+use chrono::{DateTime, FixedOffset, Local, Offset, SecondsFormat, TimeZone, Utc};
+use tracing_subscriber::fmt::format::Writer;
+use tracing_subscriber::fmt::time::FormatTime;
+
+/// 时间输出使用的时区
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputTimeZone {
+    Utc,
+    Local,
+    Fixed(FixedOffset),
+}
+
+impl OutputTimeZone {
+    /// 解析 "utc"、"local" 或 "+08:00" 形式的固定偏移
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "utc" | "z" => Some(Self::Utc),
+            "local" => Some(Self::Local),
+            _ => {
+                let sign = match s.as_bytes().first()? {
+                    b'+' => 1,
+                    b'-' => -1,
+                    _ => return None,
+                };
+                let (h, m) = s[1..].split_once(':').unwrap_or((&s[1..], "0"));
+                let seconds = h.parse::<i32>().ok()? * 3600 + m.parse::<i32>().ok()? * 60;
+                FixedOffset::east_opt(sign * seconds).map(Self::Fixed)
+            }
+        }
+    }
+
+    /// 指定时刻相对 UTC 的偏移（本地时区考虑夏令时）
+    pub fn offset_at(&self, ms: i64) -> FixedOffset {
+        match self {
+            Self::Utc => Utc.fix(),
+            Self::Local => Local.timestamp_millis_opt(ms).single()
+                .map_or(Utc.fix(), |t| t.offset().fix()),
+            Self::Fixed(offset) => *offset,
+        }
+    }
+
+    /// 格式化为 RFC 3339，UTC 以 `Z` 结尾
+    pub fn format_ms(&self, ms: i64, precision: SecondsFormat) -> String {
+        DateTime::<Utc>::from_timestamp_millis(ms)
+            .unwrap_or_default()
+            .with_timezone(&self.offset_at(ms))
+            .to_rfc3339_opts(precision, true)
+    }
+}
+
+static OUTPUT_TIMEZONE: OnceLock<OutputTimeZone> = OnceLock::new();
+static DISPLAY_TIMEZONE: OnceLock<OutputTimeZone> = OnceLock::new();
+
+/// 启动时设置一次时区策略：机器输出（日志、CSV、导出文件、接口）与面向人的显示
+pub fn configure(output: OutputTimeZone, display: OutputTimeZone) {
+    let _ = OUTPUT_TIMEZONE.set(output);
+    let _ = DISPLAY_TIMEZONE.set(display);
+}
+
+/// 机器输出使用的时区，默认 UTC
+pub fn output_timezone() -> OutputTimeZone {
+    *OUTPUT_TIMEZONE.get().unwrap_or(&OutputTimeZone::Utc)
+}
+
+/// 面向人的显示使用的时区，默认本地时区
+pub fn display_timezone() -> OutputTimeZone {
+    *DISPLAY_TIMEZONE.get().unwrap_or(&OutputTimeZone::Local)
+}
+
+/// 按机器输出时区格式化 Unix 毫秒时间
+pub fn format_ms(ms: i64, precision: SecondsFormat) -> String {
+    output_timezone().format_ms(ms, precision)
+}
+
+/// 按显示时区格式化 Unix 毫秒时间
+pub fn display_ms(ms: i64) -> String {
+    display_timezone().format_ms(ms, SecondsFormat::Secs)
+}
+
+/// 当前时间，按机器输出时区格式化
+pub fn now() -> String {
+    format_ms(Utc::now().timestamp_millis(), SecondsFormat::Millis)
+}
+
+/// 日志时间戳，使用机器输出时区
+pub struct LogTimer;
+
+impl FormatTime for LogTimer {
+    fn format_time(&self, w: &mut Writer<'_>) -> std::fmt::Result {
+        write!(w, "{}", format_ms(Utc::now().timestamp_millis(), SecondsFormat::Micros))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MS: i64 = 1_748_764_800_500; // 2025-06-01T08:00:00.500Z
+
+    fn fixed(seconds: i32) -> Option<OutputTimeZone> {
+        FixedOffset::east_opt(seconds).map(OutputTimeZone::Fixed)
+    }
+
+    #[test]
+    fn test_parse_named_zones() {
+        assert_eq!(OutputTimeZone::parse("UTC"), Some(OutputTimeZone::Utc));
+        assert_eq!(OutputTimeZone::parse("z"), Some(OutputTimeZone::Utc));
+        assert_eq!(OutputTimeZone::parse("Local"), Some(OutputTimeZone::Local));
+    }
+
+    #[test]
+    fn test_parse_fixed_offsets() {
+        assert_eq!(OutputTimeZone::parse("+08:00"), fixed(8 * 3600));
+        assert_eq!(OutputTimeZone::parse("-5"), fixed(-5 * 3600));
+        assert_eq!(OutputTimeZone::parse("+05:30"), fixed(5 * 3600 + 30 * 60));
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid() {
+        assert_eq!(OutputTimeZone::parse("mars"), None);
+        assert_eq!(OutputTimeZone::parse(""), None);
+        assert_eq!(OutputTimeZone::parse("+8h"), None);
+        assert_eq!(OutputTimeZone::parse("+25:00"), None);
+    }
+
+    #[test]
+    fn test_utc_format_ends_with_z() {
+        assert_eq!(OutputTimeZone::Utc.format_ms(MS, SecondsFormat::Millis), "2025-06-01T08:00:00.500Z");
+        assert_eq!(OutputTimeZone::Utc.format_ms(MS, SecondsFormat::Secs), "2025-06-01T08:00:00Z");
+    }
+
+    #[test]
+    fn test_fixed_offset_format() {
+        let beijing = OutputTimeZone::parse("+08:00").unwrap();
+        assert_eq!(beijing.format_ms(MS, SecondsFormat::Secs), "2025-06-01T16:00:00+08:00");
+        assert_eq!(beijing.offset_at(MS).local_minus_utc(), 8 * 3600);
+    }
+
+    #[test]
+    fn test_default_output_zone_is_utc() {
+        // 测试中不调用 configure，机器输出使用默认的 UTC
+        assert_eq!(output_timezone(), OutputTimeZone::Utc);
+        assert_eq!(format_ms(MS, SecondsFormat::Millis), "2025-06-01T08:00:00.500Z");
+        assert!(now().ends_with('Z'));
+    }
+}
@@ -0,0 +1,394 @@
+use std::collections::{HashMap, VecDeque};
+
+use serde::{Deserialize, Serialize};
+
+use crate::event_log::DecodedEvent;
+use crate::remote_id;
+use crate::upload_data::UploadData;
+
+/// 按 UAS ID 聚合状态的设置
+///
+/// ```toml
+/// [tracker]
+/// timeout_s = 60                # 超过该时间未收到的无人机视为离开
+/// rssi_history = 32             # 每架无人机保留的 RSSI 样本数
+/// upload_changes_only = false   # 只上传状态有变化的记录
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrackerConfig {
+    #[serde(default = "default_timeout_s")]
+    pub timeout_s: u64,
+    #[serde(default = "default_rssi_history")]
+    pub rssi_history: usize,
+    #[serde(default)]
+    pub upload_changes_only: bool,
+}
+
+fn default_timeout_s() -> u64 {
+    60
+}
+
+fn default_rssi_history() -> usize {
+    32
+}
+
+impl Default for TrackerConfig {
+    fn default() -> Self {
+        Self { timeout_s: default_timeout_s(), rssi_history: default_rssi_history(), upload_changes_only: false }
+    }
+}
+
+/// 一架无人机的最新状态
+#[derive(Clone)]
+pub struct DroneState {
+    pub uas_id: String,
+    pub first_seen_ms: i64,
+    pub last_seen_ms: i64,
+    pub frames: u64,
+    /// 合并了历次报文的记录：本帧没有的消息类型沿用之前收到的内容
+    pub record: UploadData,
+    /// (接收时间, RSSI) 由旧到新
+    pub rssi_history: VecDeque<(i64, f32)>,
+}
+
+/// 状态变化
+#[derive(Debug, Clone, PartialEq)]
+pub enum TrackEvent {
+    New { uas_id: String },
+    /// `fields` 为发生变化的字段组：identity/position/speed/status/operator/self_id
+    Changed { uas_id: String, fields: Vec<&'static str> },
+    Expired { uas_id: String, last_seen_ms: i64 },
+}
+
+/// 消息类型 (报文首字节高 4 位) 对应的位
+fn message_types(record: &UploadData) -> u8 {
+    record.raw_messages.iter()
+        .filter_map(|hex| remote_id::from_hex(hex.get(..2)?))
+        .filter_map(|bytes| bytes.first().map(|b| b >> 4))
+        .filter(|t| *t < 8)
+        .fold(0, |mask, t| mask | 1 << t)
+}
+
+const BASIC_ID: u8 = 1 << 0;
+const LOCATION: u8 = 1 << 1;
+const AUTH: u8 = 1 << 2;
+const SELF_ID: u8 = 1 << 3;
+const SYSTEM: u8 = 1 << 4;
+const OPERATOR_ID: u8 = 1 << 5;
+
+/// 本帧没有的消息类型，其字段取自上一次的状态
+fn merge(previous: &UploadData, record: &mut UploadData) {
+    let present = message_types(record);
+    if present & BASIC_ID == 0 {
+        record.rid.clone_from(&previous.rid);
+        record.rid_lossy = previous.rid_lossy;
+        record.rid_raw.clone_from(&previous.rid_raw);
+        record.ua_type = previous.ua_type;
+        record.uas_id_type = previous.uas_id_type;
+    }
+    if present & LOCATION == 0 {
+        record.run_status = previous.run_status;
+        record.reserved_flag = previous.reserved_flag;
+        record.height_type = previous.height_type;
+        record.track_direction = previous.track_direction;
+        record.speed_multiplier = previous.speed_multiplier;
+        record.track_angle = previous.track_angle;
+        record.ground_speed = previous.ground_speed;
+        record.vertical_speed = previous.vertical_speed;
+        record.latitude = previous.latitude;
+        record.longitude = previous.longitude;
+        record.pressure_altitude = previous.pressure_altitude;
+        record.geometric_altitude = previous.geometric_altitude;
+        record.ground_altitude = previous.ground_altitude;
+        record.vertical_accuracy = previous.vertical_accuracy;
+        record.horizontal_accuracy = previous.horizontal_accuracy;
+        record.speed_accuracy = previous.speed_accuracy;
+        record.timestamp = previous.timestamp;
+        record.timestamp_accuracy = previous.timestamp_accuracy;
+        record.reserved = previous.reserved;
+        record.accuracy_bounds = previous.accuracy_bounds;
+        record.position_status = previous.position_status;
+    }
+    if present & AUTH == 0 {
+        record.auth.clone_from(&previous.auth);
+    }
+    if present & SELF_ID == 0 {
+        record.self_id_type = previous.self_id_type;
+        record.self_id.clone_from(&previous.self_id);
+    }
+    if present & SYSTEM == 0 {
+        record.operator.clone_from(&previous.operator);
+        record.classification.clone_from(&previous.classification);
+    }
+    if present & OPERATOR_ID == 0 {
+        record.operator_id_type = previous.operator_id_type;
+        record.operator_id.clone_from(&previous.operator_id);
+    }
+}
+
+/// 两条记录间发生变化的字段组，见 [`TrackEvent::Changed`]
+pub fn changed_fields(a: &UploadData, b: &UploadData) -> Vec<&'static str> {
+    [
+        ("identity", a.rid != b.rid || a.ua_type != b.ua_type),
+        ("position", (a.latitude, a.longitude, a.geometric_altitude) != (b.latitude, b.longitude, b.geometric_altitude)),
+        ("speed", (a.ground_speed, a.vertical_speed, a.track_angle, a.track_direction)
+            != (b.ground_speed, b.vertical_speed, b.track_angle, b.track_direction)),
+        ("status", a.run_status != b.run_status || a.position_status != b.position_status),
+        ("operator", a.operator != b.operator || a.operator_id != b.operator_id),
+        ("self_id", a.self_id != b.self_id),
+    ].into_iter().filter(|(_, changed)| *changed).map(|(name, _)| name).collect()
+}
+
+/// 按 UAS ID 维护每架无人机的最新状态
+///
+/// 只带部分消息的报文（如只有位置向量）按发射端 MAC 归到之前报过 Basic ID 的无人机，
+/// 合并后的记录替换原记录；状态没有变化的重复报文不产生事件。超过 `timeout_s`
+/// 未收到的无人机由 [`Tracker::expire`] 移除。
+#[derive(Default)]
+pub struct Tracker {
+    config: TrackerConfig,
+    drones: HashMap<String, DroneState>,
+    /// 轨迹 ID (发射端 MAC 或关联后的轨迹) -> UAS ID
+    aliases: HashMap<String, String>,
+}
+
+impl Tracker {
+    pub fn new(config: TrackerConfig) -> Self {
+        Self { config, ..Default::default() }
+    }
+
+    /// 更新设置，已有状态保留
+    pub fn set_config(&mut self, config: TrackerConfig) {
+        self.config = config;
+    }
+
+    pub fn config(&self) -> &TrackerConfig {
+        &self.config
+    }
+
+    pub fn len(&self) -> usize {
+        self.drones.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.drones.is_empty()
+    }
+
+    pub fn get(&self, uas_id: &str) -> Option<&DroneState> {
+        self.drones.get(uas_id)
+    }
+
+    pub fn drones(&self) -> impl Iterator<Item = &DroneState> {
+        self.drones.values()
+    }
+
+    /// 把事件合并进对应无人机的状态，并以合并结果替换事件中的记录
+    pub fn observe(&mut self, event: &mut DecodedEvent) -> Option<TrackEvent> {
+        let record = &mut event.record;
+        let uas_id = if !record.rid.is_empty() {
+            record.rid.clone()
+        } else {
+            self.aliases.get(&record.track_id).cloned().unwrap_or_else(|| record.track_id.clone())
+        };
+        // 先收到位置、后收到 Basic ID 时，把按轨迹 ID 建立的状态移到 UAS ID 下
+        if uas_id != record.track_id
+            && !self.drones.contains_key(&uas_id)
+            && let Some(mut state) = self.drones.remove(&record.track_id)
+        {
+            state.uas_id.clone_from(&uas_id);
+            self.drones.insert(uas_id.clone(), state);
+        }
+        if !record.rid.is_empty() {
+            self.aliases.insert(record.track_id.clone(), uas_id.clone());
+        }
+
+        let history = self.config.rssi_history;
+        let Some(state) = self.drones.get_mut(&uas_id) else {
+            let mut state = DroneState {
+                uas_id: uas_id.clone(),
+                first_seen_ms: event.received_at_ms,
+                last_seen_ms: event.received_at_ms,
+                frames: 1,
+                record: record.clone(),
+                rssi_history: VecDeque::new(),
+            };
+            if let Some(rssi) = record.rssi.filter(|_| history > 0) {
+                state.rssi_history.push_back((event.received_at_ms, rssi));
+            }
+            self.drones.insert(uas_id.clone(), state);
+            return Some(TrackEvent::New { uas_id });
+        };
+        if !record.raw_messages.is_empty() {
+            merge(&state.record, record);
+        }
+        let fields = changed_fields(&state.record, record);
+        state.record = record.clone();
+        state.last_seen_ms = state.last_seen_ms.max(event.received_at_ms);
+        state.frames += 1;
+        if let Some(rssi) = record.rssi {
+            state.rssi_history.push_back((event.received_at_ms, rssi));
+        }
+        while state.rssi_history.len() > history {
+            state.rssi_history.pop_front();
+        }
+        (!fields.is_empty()).then_some(TrackEvent::Changed { uas_id, fields })
+    }
+
+    /// 无人机在本次运行之前已出现过时，沿用更早的首次出现时间
+    pub fn backdate(&mut self, uas_id: &str, first_seen_ms: i64) {
+        if let Some(state) = self.drones.get_mut(uas_id) {
+            state.first_seen_ms = state.first_seen_ms.min(first_seen_ms);
+        }
+    }
+
+    /// 退出时结束所有跟踪中的无人机
+    pub fn finish(&mut self) -> Vec<TrackEvent> {
+        self.aliases.clear();
+        self.drones.drain()
+            .map(|(uas_id, state)| TrackEvent::Expired { uas_id, last_seen_ms: state.last_seen_ms })
+            .collect()
+    }
+
+    /// 移除超时未收到的无人机
+    pub fn expire(&mut self, now_ms: i64) -> Vec<TrackEvent> {
+        let timeout_ms = self.config.timeout_s as i64 * 1000;
+        let mut expired = Vec::new();
+        self.drones.retain(|uas_id, state| {
+            let keep = now_ms - state.last_seen_ms <= timeout_ms;
+            if !keep {
+                expired.push(TrackEvent::Expired { uas_id: uas_id.clone(), last_seen_ms: state.last_seen_ms });
+            }
+            keep
+        });
+        if !expired.is_empty() {
+            self.aliases.retain(|_, uas_id| self.drones.contains_key(uas_id));
+        }
+        expired
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(at: i64, rid: &str, message_type: u8, latitude: i32) -> DecodedEvent {
+        let record = UploadData {
+            rid: rid.into(),
+            track_id: "02:11:22:33:44:55".into(),
+            latitude,
+            rssi: Some(-60.0),
+            raw_messages: vec![format!("{:02x}", message_type << 4 | 2)],
+            ..Default::default()
+        };
+        DecodedEvent { received_at_ms: at, record }
+    }
+
+    fn tracker() -> Tracker {
+        Tracker::new(TrackerConfig { rssi_history: 2, ..Default::default() })
+    }
+
+    #[test]
+    fn test_first_frame_is_new() {
+        let mut tracker = tracker();
+        assert_eq!(tracker.observe(&mut event(1_000, "RID-1", 0, 0)), Some(TrackEvent::New { uas_id: "RID-1".into() }));
+        assert_eq!(tracker.len(), 1);
+    }
+
+    #[test]
+    fn test_position_frame_joins_drone_by_mac() {
+        let mut tracker = tracker();
+        tracker.observe(&mut event(1_000, "RID-1", 0, 0));
+        // 只有位置向量的帧按 MAC 归到 RID-1，并补上 Basic ID
+        let mut position = event(2_000, "", 1, 399_000_000);
+        assert_eq!(tracker.observe(&mut position),
+            Some(TrackEvent::Changed { uas_id: "RID-1".into(), fields: vec!["position"] }));
+        assert_eq!(position.record.rid, "RID-1");
+    }
+
+    #[test]
+    fn test_missing_messages_keep_previous_fields() {
+        let mut tracker = tracker();
+        tracker.observe(&mut event(1_000, "RID-1", 0, 0));
+        tracker.observe(&mut event(2_000, "", 1, 399_000_000));
+        // Basic ID 帧沿用之前的位置，状态不变时不产生事件
+        let mut repeat = event(3_000, "RID-1", 0, 0);
+        assert_eq!(tracker.observe(&mut repeat), None);
+        assert_eq!(repeat.record.latitude, 399_000_000);
+    }
+
+    #[test]
+    fn test_position_before_basic_id_moves_state() {
+        let mut tracker = tracker();
+        assert_eq!(tracker.observe(&mut event(1_000, "", 1, 399_000_000)),
+            Some(TrackEvent::New { uas_id: "02:11:22:33:44:55".into() }));
+        assert_eq!(tracker.observe(&mut event(2_000, "RID-1", 0, 0)),
+            Some(TrackEvent::Changed { uas_id: "RID-1".into(), fields: vec!["identity"] }));
+        assert_eq!(tracker.len(), 1);
+        let state = tracker.get("RID-1").unwrap();
+        assert_eq!((state.uas_id.as_str(), state.first_seen_ms, state.record.latitude), ("RID-1", 1_000, 399_000_000));
+    }
+
+    #[test]
+    fn test_state_counts_frames_and_caps_rssi_history() {
+        let mut tracker = tracker();
+        for at in [1_000, 2_000, 3_000] {
+            tracker.observe(&mut event(at, "RID-1", 0, 0));
+        }
+        let state = tracker.get("RID-1").unwrap();
+        assert_eq!((state.first_seen_ms, state.last_seen_ms, state.frames), (1_000, 3_000, 3));
+        assert_eq!(state.rssi_history, [(2_000, -60.0), (3_000, -60.0)]);
+    }
+
+    #[test]
+    fn test_changed_field_groups() {
+        let a = UploadData::default();
+        assert!(changed_fields(&a, &a).is_empty());
+        let b = UploadData {
+            rid: "RID-1".into(),
+            ground_speed: 3,
+            run_status: 2,
+            operator_id: Some("OP".into()),
+            self_id: Some("survey".into()),
+            ..Default::default()
+        };
+        assert_eq!(changed_fields(&a, &b), ["identity", "speed", "status", "operator", "self_id"]);
+    }
+
+    #[test]
+    fn test_expire_after_timeout() {
+        let mut tracker = tracker();
+        tracker.observe(&mut event(3_000, "RID-1", 0, 0));
+        assert!(tracker.expire(63_000).is_empty());
+        assert_eq!(tracker.expire(63_001), [TrackEvent::Expired { uas_id: "RID-1".into(), last_seen_ms: 3_000 }]);
+        assert!(tracker.is_empty());
+
+        // 别名随之清除，同一 MAC 的位置帧不再归到 RID-1
+        assert_eq!(tracker.observe(&mut event(64_000, "", 1, 1)),
+            Some(TrackEvent::New { uas_id: "02:11:22:33:44:55".into() }));
+    }
+
+    #[test]
+    fn test_backdate_keeps_earliest_first_seen() {
+        let mut tracker = tracker();
+        tracker.observe(&mut event(5_000, "RID-1", 0, 0));
+        tracker.backdate("RID-1", 9_000);
+        assert_eq!(tracker.get("RID-1").unwrap().first_seen_ms, 5_000);
+        tracker.backdate("RID-1", 1_000);
+        assert_eq!(tracker.get("RID-1").unwrap().first_seen_ms, 1_000);
+    }
+
+    #[test]
+    fn test_finish_expires_all() {
+        let mut tracker = tracker();
+        tracker.observe(&mut event(5_000, "RID-1", 0, 0));
+        assert_eq!(tracker.finish(), [TrackEvent::Expired { uas_id: "RID-1".into(), last_seen_ms: 5_000 }]);
+        assert!(tracker.is_empty());
+    }
+
+    #[test]
+    fn test_config_defaults() {
+        let config: TrackerConfig = toml::from_str("upload_changes_only = true").unwrap();
+        assert_eq!(config, TrackerConfig { upload_changes_only: true, ..Default::default() });
+        assert_eq!((config.timeout_s, config.rssi_history), (60, 32));
+    }
+}
@@ -0,0 +1,207 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::{self, Write};
+
+use chrono::{DateTime, SecondsFormat, Timelike, Utc};
+
+use crate::event_log::DecodedEvent;
+use crate::time_format;
+
+/// 统计时间粒度
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bucket {
+    Hour,
+    Day,
+}
+
+impl Bucket {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "hour" => Some(Self::Hour),
+            "day" => Some(Self::Day),
+            _ => None,
+        }
+    }
+
+    /// 时间段起点，按输出时区对齐整点/零点
+    fn start_of(&self, ms: i64) -> i64 {
+        let offset = time_format::output_timezone().offset_at(ms);
+        let t = DateTime::<Utc>::from_timestamp_millis(ms).unwrap_or_default().with_timezone(&offset);
+        let t = match self {
+            Self::Hour => t.date_naive().and_hms_opt(t.hour(), 0, 0),
+            Self::Day => t.date_naive().and_hms_opt(0, 0, 0),
+        };
+        t.and_then(|t| t.and_local_timezone(offset).single())
+            .map_or(ms, |t| t.timestamp_millis())
+    }
+}
+
+/// 单个时间段的流量统计
+#[derive(Debug, Default)]
+pub struct BucketStats {
+    pub records: u64,
+    pub drones: HashSet<String>,
+    pub flights: u64,
+    pub peak_concurrent: usize,
+    pub channels: HashMap<u8, u64>,
+}
+
+impl BucketStats {
+    pub fn busiest_channel(&self) -> Option<u8> {
+        self.channels.iter().max_by_key(|(_, n)| **n).map(|(c, _)| *c)
+    }
+}
+
+/// 按时间段聚合的空域使用统计
+///
+/// - 航次：同一无人机相邻两条记录间隔超过 `flight_gap_ms` 视为新航次
+/// - 并发数：最近 `active_window_ms` 内出现过的无人机数量的峰值
+pub struct TrafficStats {
+    bucket: Bucket,
+    flight_gap_ms: i64,
+    active_window_ms: i64,
+    last_seen: HashMap<String, i64>,
+    buckets: BTreeMap<i64, BucketStats>,
+}
+
+impl TrafficStats {
+    pub fn new(bucket: Bucket) -> Self {
+        Self {
+            bucket,
+            flight_gap_ms: 5 * 60 * 1000,
+            active_window_ms: 60 * 1000,
+            last_seen: HashMap::new(),
+            buckets: BTreeMap::new(),
+        }
+    }
+
+    /// 按时间顺序加入事件
+    pub fn add(&mut self, event: &DecodedEvent) {
+        let record = &event.record;
+        let drone = if record.rid.is_empty() { record.track_id.clone() } else { record.rid.clone() };
+        let now = event.received_at_ms;
+        let new_flight = self.last_seen.get(&drone)
+            .is_none_or(|last| now - last > self.flight_gap_ms);
+        self.last_seen.insert(drone.clone(), now);
+        let concurrent = self.last_seen.values()
+            .filter(|t| now - **t <= self.active_window_ms)
+            .count();
+
+        let stats = self.buckets.entry(self.bucket.start_of(now)).or_default();
+        stats.records += 1;
+        stats.drones.insert(drone);
+        if new_flight {
+            stats.flights += 1;
+        }
+        stats.peak_concurrent = stats.peak_concurrent.max(concurrent);
+        if let Some(channel) = record.channel {
+            *stats.channels.entry(channel).or_default() += 1;
+        }
+    }
+
+    pub fn buckets(&self) -> &BTreeMap<i64, BucketStats> {
+        &self.buckets
+    }
+
+    /// 以 CSV 输出
+    pub fn write_csv<W: Write>(&self, mut out: W) -> io::Result<()> {
+        writeln!(out, "bucket_start,records,unique_drones,flights,peak_concurrent,busiest_channel")?;
+        for (start, stats) in &self.buckets {
+            writeln!(out, "{},{},{},{},{},{}",
+                time_format::format_ms(*start, SecondsFormat::Secs),
+                stats.records,
+                stats.drones.len(),
+                stats.flights,
+                stats.peak_concurrent,
+                stats.busiest_channel().map(|c| c.to_string()).unwrap_or_default())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::upload_data::UploadData;
+
+    const HOUR: i64 = 3_600_000;
+    const MINUTE: i64 = 60_000;
+
+    fn event(rid: &str, at: i64, channel: Option<u8>) -> DecodedEvent {
+        let record = UploadData { rid: rid.to_string(), track_id: format!("track-{}", rid), channel, ..Default::default() };
+        DecodedEvent { received_at_ms: at, record }
+    }
+
+    #[test]
+    fn test_bucket_parse() {
+        assert_eq!(Bucket::parse("hour"), Some(Bucket::Hour));
+        assert_eq!(Bucket::parse("day"), Some(Bucket::Day));
+        assert_eq!(Bucket::parse("week"), None);
+    }
+
+    #[test]
+    fn test_events_are_grouped_by_aligned_bucket() {
+        let mut stats = TrafficStats::new(Bucket::Hour);
+        stats.add(&event("A", 10 * MINUTE, None));
+        stats.add(&event("A", 50 * MINUTE, None));
+        stats.add(&event("A", HOUR + 20 * MINUTE, None));
+        let starts: Vec<_> = stats.buckets().keys().copied().collect();
+        assert_eq!(starts, vec![0, HOUR]);
+        assert_eq!(stats.buckets()[&0].records, 2);
+
+        let mut daily = TrafficStats::new(Bucket::Day);
+        daily.add(&event("A", 25 * HOUR, None));
+        assert_eq!(daily.buckets().keys().copied().collect::<Vec<_>>(), vec![24 * HOUR]);
+    }
+
+    #[test]
+    fn test_gap_starts_new_flight() {
+        let mut stats = TrafficStats::new(Bucket::Day);
+        stats.add(&event("A", 0, None));
+        stats.add(&event("A", 4 * MINUTE, None));
+        stats.add(&event("A", 10 * MINUTE, None));   // 间隔 6 分钟，新航次
+        let day = &stats.buckets()[&0];
+        assert_eq!((day.flights, day.drones.len()), (2, 1));
+    }
+
+    #[test]
+    fn test_drone_falls_back_to_track_id() {
+        let mut stats = TrafficStats::new(Bucket::Day);
+        let mut anonymous = event("", 0, None);
+        anonymous.record.track_id = "aa:bb".into();
+        stats.add(&anonymous);
+        assert!(stats.buckets()[&0].drones.contains("aa:bb"));
+    }
+
+    #[test]
+    fn test_peak_concurrent_uses_active_window() {
+        let mut stats = TrafficStats::new(Bucket::Day);
+        stats.add(&event("A", 0, None));
+        stats.add(&event("B", 30_000, None));
+        stats.add(&event("C", 5 * MINUTE, None));   // A、B 已超出活跃窗口
+        assert_eq!(stats.buckets()[&0].peak_concurrent, 2);
+    }
+
+    #[test]
+    fn test_busiest_channel() {
+        let mut stats = TrafficStats::new(Bucket::Day);
+        stats.add(&event("A", 0, Some(6)));
+        stats.add(&event("A", 1_000, Some(149)));
+        stats.add(&event("B", 2_000, Some(149)));
+        stats.add(&event("B", 3_000, None));
+        assert_eq!(stats.buckets()[&0].busiest_channel(), Some(149));
+        assert_eq!(BucketStats::default().busiest_channel(), None);
+    }
+
+    #[test]
+    fn test_write_csv() {
+        let mut stats = TrafficStats::new(Bucket::Hour);
+        stats.add(&event("A", 0, Some(6)));
+        stats.add(&event("B", HOUR, None));
+        let mut out = Vec::new();
+        stats.write_csv(&mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(),
+            "bucket_start,records,unique_drones,flights,peak_concurrent,busiest_channel\n\
+             1970-01-01T00:00:00Z,1,1,1,1,6\n\
+             1970-01-01T01:00:00Z,1,1,1,1,\n");
+    }
+}
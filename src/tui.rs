@@ -0,0 +1,360 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Paragraph, Row, Table, TableState};
+use ratatui::{DefaultTerminal, Frame};
+use tracing::error;
+
+use crate::geo::degrees;
+use crate::playback::PlaybackControl;
+use crate::position::{self, PositionStatus};
+use crate::remote_id::ua_type_style;
+use crate::shutdown;
+use crate::time_format;
+use crate::tracker::DroneState;
+use crate::units;
+use crate::upload_data::UploadData;
+
+/// 表格的列，可按任一列排序
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Column {
+    UasId,
+    Operator,
+    Latitude,
+    Longitude,
+    Altitude,
+    Speed,
+    Rssi,
+    LastSeen,
+}
+
+const COLUMNS: [Column; 8] = [
+    Column::UasId, Column::Operator, Column::Latitude, Column::Longitude,
+    Column::Altitude, Column::Speed, Column::Rssi, Column::LastSeen,
+];
+
+impl Column {
+    fn title(&self) -> &'static str {
+        match self {
+            Column::UasId => "UAS ID",
+            Column::Operator => "运营人",
+            Column::Latitude => "纬度",
+            Column::Longitude => "经度",
+            Column::Altitude => "高度",
+            Column::Speed => "地速",
+            Column::Rssi => "RSSI",
+            Column::LastSeen => "最后出现",
+        }
+    }
+
+    fn width(&self) -> Constraint {
+        match self {
+            Column::UasId => Constraint::Min(20),
+            Column::Operator => Constraint::Min(16),
+            Column::Latitude | Column::Longitude => Constraint::Length(14),
+            Column::LastSeen => Constraint::Length(10),
+            _ => Constraint::Length(9),
+        }
+    }
+
+    /// 左右方向键切换排序列
+    fn step(self, forward: bool) -> Self {
+        let index = COLUMNS.iter().position(|c| *c == self).unwrap_or_default();
+        let next = if forward { index + 1 } else { index + COLUMNS.len() - 1 };
+        COLUMNS[next % COLUMNS.len()]
+    }
+}
+
+/// 表格中的一行，取自跟踪器中的无人机状态
+#[derive(Clone)]
+pub struct DroneRow {
+    pub uas_id: String,
+    pub first_seen_ms: i64,
+    pub last_seen_ms: i64,
+    pub frames: u64,
+    pub record: UploadData,
+}
+
+impl DroneRow {
+    pub fn from_state(state: &DroneState) -> Self {
+        Self {
+            uas_id: state.uas_id.clone(),
+            first_seen_ms: state.first_seen_ms,
+            last_seen_ms: state.last_seen_ms,
+            frames: state.frames,
+            record: state.record.clone(),
+        }
+    }
+
+    fn has_position(&self) -> bool {
+        position::classify(&self.record) != PositionStatus::NoFix
+    }
+
+    fn cells(&self, now_ms: i64) -> Vec<String> {
+        let units = units::csv();
+        let r = &self.record;
+        let (lat, lon) = if self.has_position() {
+            (units.coordinate(degrees(r.latitude), true), units.coordinate(degrees(r.longitude), false))
+        } else {
+            ("-".to_string(), "-".to_string())
+        };
+        vec![
+            self.uas_id.clone(),
+            r.operator_id.clone().unwrap_or_else(|| "-".to_string()),
+            lat,
+            lon,
+            format!("{:.0}{}", units.altitude(r.geometric_altitude as f64), units.altitude_suffix()),
+            format!("{:.1}{}", units.speed_from_knots(r.ground_speed_knots()), units.speed_suffix()),
+            r.rssi.map_or("-".to_string(), |rssi| format!("{:.0}", rssi)),
+            format!("{}s", (now_ms - self.last_seen_ms).max(0) / 1000),
+        ]
+    }
+}
+
+/// 按列排序，`descending` 为真时由大到小；没有值的行排在最后
+pub fn sort_rows(rows: &mut [DroneRow], column: Column, descending: bool) {
+    rows.sort_by(|a, b| {
+        let (a, b) = if descending { (b, a) } else { (a, b) };
+        let (ra, rb) = (&a.record, &b.record);
+        match column {
+            Column::UasId => a.uas_id.cmp(&b.uas_id),
+            Column::Operator => ra.operator_id.cmp(&rb.operator_id),
+            Column::Latitude => ra.latitude.cmp(&rb.latitude),
+            Column::Longitude => ra.longitude.cmp(&rb.longitude),
+            Column::Altitude => ra.geometric_altitude.cmp(&rb.geometric_altitude),
+            Column::Speed => ra.ground_speed_knots().total_cmp(&rb.ground_speed_knots()),
+            Column::Rssi => ra.rssi.unwrap_or(f32::MIN).total_cmp(&rb.rssi.unwrap_or(f32::MIN)),
+            Column::LastSeen => a.last_seen_ms.cmp(&b.last_seen_ms),
+        }
+    });
+    // 运营人、RSSI 缺失的行无论升降序都放在最后
+    match column {
+        Column::Operator => rows.sort_by_key(|row| row.record.operator_id.is_none()),
+        Column::Rssi => rows.sort_by_key(|row| row.record.rssi.is_none()),
+        _ => {}
+    }
+}
+
+/// 界面刷新与按键检查的间隔
+const FRAME_INTERVAL: Duration = Duration::from_millis(250);
+
+/// 终端界面：实时表格列出跟踪中的无人机，下方为选中无人机的详情
+///
+/// ↑/↓ 选择，←/→ 切换排序列，`r` 反转排序，`q`/Esc/Ctrl-C 退出程序。
+/// 回放时另有空格暂停/继续，`n` 单步，`+`/`-` 加减速，`[`/`]` 后退/前进 [`SEEK_STEP_MS`]。
+/// 界面运行期间控制台不输出日志，诊断日志照常写入日志文件。
+pub struct Tui {
+    rows: Arc<Mutex<Vec<DroneRow>>>,
+    stop: Arc<AtomicBool>,
+    updated: Option<Instant>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Tui {
+    /// `playback` 为回放控制，回放录制文件或抓包文件时在界面中提供暂停、单步、倍速和跳转
+    pub fn start(playback: Option<Arc<PlaybackControl>>) -> Self {
+        let rows = Arc::new(Mutex::new(Vec::new()));
+        let stop = Arc::new(AtomicBool::new(false));
+        let (shared, stopped) = (rows.clone(), stop.clone());
+        let thread = thread::spawn(move || {
+            let terminal = match ratatui::try_init() {
+                Ok(terminal) => terminal,
+                Err(e) => {
+                    error!("无法启动终端界面: {}", e);
+                    return;
+                }
+            };
+            if let Err(e) = run(terminal, &shared, &stopped, playback.as_deref()) {
+                error!("终端界面出错: {}", e);
+            }
+            ratatui::restore();
+        });
+        Self { rows, stop, updated: None, thread: Some(thread) }
+    }
+
+    /// 用跟踪器的当前状态更新表格，两次更新至少间隔一帧
+    pub fn update<'a>(&mut self, drones: impl Iterator<Item = &'a DroneState>) {
+        if self.updated.is_some_and(|at| at.elapsed() < FRAME_INTERVAL) {
+            return;
+        }
+        self.updated = Some(Instant::now());
+        *self.rows.lock().unwrap() = drones.map(DroneRow::from_state).collect();
+    }
+
+    /// 关闭界面并恢复终端
+    pub fn close(mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+struct View {
+    sort: Column,
+    descending: bool,
+    /// 按 UAS ID 记住选中的行，重新排序后仍选中同一架
+    selected: Option<String>,
+}
+
+/// 回放时 `[`/`]` 一次跳转的时长
+pub const SEEK_STEP_MS: i64 = 30_000;
+
+fn run(
+    mut terminal: DefaultTerminal,
+    rows: &Mutex<Vec<DroneRow>>,
+    stop: &AtomicBool,
+    playback: Option<&PlaybackControl>,
+) -> std::io::Result<()> {
+    let mut view = View { sort: Column::LastSeen, descending: true, selected: None };
+    while !stop.load(Ordering::Relaxed) && !shutdown::requested() {
+        let mut rows = rows.lock().unwrap().clone();
+        sort_rows(&mut rows, view.sort, view.descending);
+        let mut index = view.selected.as_ref().and_then(|id| rows.iter().position(|r| &r.uas_id == id));
+        if index.is_none() && !rows.is_empty() {
+            index = Some(0);
+        }
+        terminal.draw(|frame| render(frame, &view, &rows, index, playback))?;
+        if !event::poll(FRAME_INTERVAL)? {
+            view.selected = index.map(|i| rows[i].uas_id.clone());
+            continue;
+        }
+        let Event::Key(key) = event::read()? else { continue };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        let last = rows.len().saturating_sub(1);
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => shutdown::request(),
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => shutdown::request(),
+            KeyCode::Up => index = index.map(|i| i.saturating_sub(1)),
+            KeyCode::Down => index = index.map(|i| (i + 1).min(last)),
+            KeyCode::Left => view.sort = view.sort.step(false),
+            KeyCode::Right => view.sort = view.sort.step(true),
+            KeyCode::Char('r') => view.descending = !view.descending,
+            code => {
+                if let Some(playback) = playback {
+                    playback_key(playback, code);
+                }
+            }
+        }
+        view.selected = index.map(|i| rows[i].uas_id.clone());
+    }
+    Ok(())
+}
+
+/// 回放控制按键
+fn playback_key(playback: &PlaybackControl, code: KeyCode) {
+    match code {
+        KeyCode::Char(' ') => {
+            playback.toggle_pause();
+        }
+        KeyCode::Char('n') => playback.step(),
+        KeyCode::Char('+') | KeyCode::Char('=') => playback.scale_speed(2.0),
+        KeyCode::Char('-') => playback.scale_speed(0.5),
+        KeyCode::Char('[') => playback.seek_by(-SEEK_STEP_MS),
+        KeyCode::Char(']') => playback.seek_by(SEEK_STEP_MS),
+        _ => {}
+    }
+}
+
+/// 底部帮助行，回放时附带回放状态与按键
+fn help_line(playback: Option<&PlaybackControl>) -> String {
+    let help = "↑/↓ 选择  ←/→ 排序列  r 反转排序  q 退出";
+    let Some(playback) = playback else { return help.to_string() };
+    let status = playback.status();
+    let position = status["position_ms"].as_i64().map_or("-".to_string(), time_format::display_ms);
+    let speed = status["speed"].as_f64().unwrap_or_default();
+    format!("{}  │  回放 {} {}  {}  空格 暂停  n 单步  +/- 倍速  [/] 跳转",
+        help, if status["paused"] == true { "已暂停" } else { "播放中" },
+        if speed > 0.0 { format!("{}x", speed) } else { "尽快".to_string() }, position)
+}
+
+fn render(frame: &mut Frame, view: &View, rows: &[DroneRow], selected: Option<usize>, playback: Option<&PlaybackControl>) {
+    let now_ms = Utc::now().timestamp_millis();
+    let [table_area, detail_area, help_area] = Layout::vertical([
+        Constraint::Min(5), Constraint::Length(8), Constraint::Length(1),
+    ]).areas(frame.area());
+
+    let header = Row::new(COLUMNS.iter().map(|column| {
+        let arrow = if view.descending { "▼" } else { "▲" };
+        if *column == view.sort { format!("{}{}", column.title(), arrow) } else { column.title().to_string() }
+    })).style(Style::new().add_modifier(Modifier::BOLD));
+    let table = Table::new(rows.iter().map(|row| Row::new(row.cells(now_ms))), COLUMNS.map(|c| c.width()))
+        .header(header)
+        .block(Block::bordered().title(format!(" 跟踪中的无人机 ({}) ", rows.len())))
+        .row_highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+    let mut state = TableState::default().with_selected(selected);
+    frame.render_stateful_widget(table, table_area, &mut state);
+
+    let detail = selected.map(|i| detail_lines(&rows[i])).unwrap_or_default();
+    frame.render_widget(Paragraph::new(detail).block(Block::bordered().title(" 详情 ")), detail_area);
+    frame.render_widget(Paragraph::new(help_line(playback)), help_area);
+}
+
+/// 选中无人机的详情
+fn detail_lines(row: &DroneRow) -> Vec<Line<'static>> {
+    let r = &row.record;
+    let units = units::csv();
+    let optional = |value: Option<String>| value.unwrap_or_else(|| "-".to_string());
+    vec![
+        Line::from(format!("UAS ID {}  轨迹 {}  发射端 {}  类型 {}  帧数 {}",
+            row.uas_id, r.track_id, r.source_mac, ua_type_style(r.ua_type).0, row.frames)),
+        Line::from(format!("位置状态 {:?}  垂直速度 {}m/s  航向 {}°  距地高度 {}",
+            r.position_status, r.vertical_speed, r.track_angle as u16 + if r.track_direction { 180 } else { 0 },
+            units.altitude(r.ground_altitude as f64).round())),
+        Line::from(format!("运营人 {}  控制站距离 {}  接收站距离 {}",
+            optional(r.operator_id.clone()),
+            optional(r.operator_distance_m.map(|d| format!("{:.0}m", d))),
+            optional(r.receiver_distance_m.map(|d| format!("{:.0}m", d))))),
+        Line::from(format!("信号 {}  估计距离 {}  趋势 {}",
+            optional(r.rssi.map(|rssi| format!("{:.0}dBm", rssi))),
+            optional(r.estimated_range_m.map(|d| format!("{:.0}m", d))),
+            optional(r.rssi_trend.map(|t| format!("{:?}", t))))),
+        Line::from(format!("首次出现 {}  最后出现 {}  Self-ID {}",
+            time_format::display_ms(row.first_seen_ms), time_format::display_ms(row.last_seen_ms),
+            optional(r.self_id.clone()))),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sort_rows() {
+        let row = |uas_id: &str, rssi: Option<f32>, last_seen_ms| DroneRow {
+            uas_id: uas_id.into(),
+            first_seen_ms: 0,
+            last_seen_ms,
+            frames: 1,
+            record: UploadData { rssi, ..Default::default() },
+        };
+        let mut rows = vec![row("B", Some(-70.0), 3_000), row("A", None, 1_000), row("C", Some(-50.0), 2_000)];
+        sort_rows(&mut rows, Column::LastSeen, true);
+        assert_eq!(rows.iter().map(|r| r.uas_id.as_str()).collect::<Vec<_>>(), ["B", "C", "A"]);
+        sort_rows(&mut rows, Column::Rssi, true);
+        assert_eq!(rows.iter().map(|r| r.uas_id.as_str()).collect::<Vec<_>>(), ["C", "B", "A"]);
+        sort_rows(&mut rows, Column::Rssi, false);
+        assert_eq!(rows.iter().map(|r| r.uas_id.as_str()).collect::<Vec<_>>(), ["B", "C", "A"]);
+        assert_eq!(Column::UasId.step(false), Column::LastSeen);
+        assert_eq!(rows[0].cells(10_000)[7], "7s");
+    }
+
+    #[test]
+    fn test_playback_keys() {
+        let playback = PlaybackControl::new(1.0);
+        playback_key(&playback, KeyCode::Char(' '));
+        playback_key(&playback, KeyCode::Char('+'));
+        assert_eq!((playback.status()["paused"].clone(), playback.status()["speed"].clone()), (true.into(), 2.0.into()));
+        assert!(help_line(Some(&playback)).contains("已暂停 2x"));
+        assert!(!help_line(None).contains("回放"));
+    }
+}
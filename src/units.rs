@@ -0,0 +1,231 @@
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+
+/// 高度单位
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AltitudeUnit {
+    #[default]
+    #[serde(rename = "m")]
+    Meters,
+    #[serde(rename = "ft")]
+    Feet,
+}
+
+/// 速度单位
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SpeedUnit {
+    #[default]
+    #[serde(rename = "m/s")]
+    MetersPerSecond,
+    #[serde(rename = "kt")]
+    Knots,
+    #[serde(rename = "km/h")]
+    KilometersPerHour,
+}
+
+/// 经纬度格式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CoordinateFormat {
+    #[default]
+    #[serde(rename = "decimal")]
+    Decimal,
+    #[serde(rename = "dms")]
+    Dms,
+}
+
+/// 一种输出使用的单位
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Units {
+    pub altitude: AltitudeUnit,
+    pub speed: SpeedUnit,
+    pub coordinates: CoordinateFormat,
+}
+
+impl Units {
+    const FEET_PER_METER: f64 = 3.280_84;
+    const MPS_PER_KNOT: f64 = 0.514_444;
+
+    /// 解析 "alt=ft,speed=kt,coords=dms"，未列出的项保持默认
+    pub fn parse(s: &str) -> Option<Self> {
+        let mut units = Self::default();
+        for item in s.split(',').filter(|i| !i.is_empty()) {
+            let (key, value) = item.split_once('=')?;
+            match (key.trim(), value.trim()) {
+                ("alt" | "altitude", "m") => units.altitude = AltitudeUnit::Meters,
+                ("alt" | "altitude", "ft") => units.altitude = AltitudeUnit::Feet,
+                ("speed", "m/s" | "mps") => units.speed = SpeedUnit::MetersPerSecond,
+                ("speed", "kt" | "kn") => units.speed = SpeedUnit::Knots,
+                ("speed", "km/h" | "kmh") => units.speed = SpeedUnit::KilometersPerHour,
+                ("coords" | "coordinates", "decimal") => units.coordinates = CoordinateFormat::Decimal,
+                ("coords" | "coordinates", "dms") => units.coordinates = CoordinateFormat::Dms,
+                _ => return None,
+            }
+        }
+        Some(units)
+    }
+
+    /// 米转换为所选高度单位
+    pub fn altitude(&self, meters: f64) -> f64 {
+        match self.altitude {
+            AltitudeUnit::Meters => meters,
+            AltitudeUnit::Feet => meters * Self::FEET_PER_METER,
+        }
+    }
+
+    pub fn altitude_suffix(&self) -> &'static str {
+        match self.altitude {
+            AltitudeUnit::Meters => "m",
+            AltitudeUnit::Feet => "ft",
+        }
+    }
+
+    /// 节转换为所选速度单位（地速在 Remote ID 记录中以节表示）
+    pub fn speed_from_knots(&self, knots: f64) -> f64 {
+        match self.speed {
+            SpeedUnit::MetersPerSecond => knots * Self::MPS_PER_KNOT,
+            SpeedUnit::Knots => knots,
+            SpeedUnit::KilometersPerHour => knots * Self::MPS_PER_KNOT * 3.6,
+        }
+    }
+
+    /// 用于列名等标识的速度单位后缀
+    pub fn speed_suffix(&self) -> &'static str {
+        match self.speed {
+            SpeedUnit::MetersPerSecond => "mps",
+            SpeedUnit::Knots => "kt",
+            SpeedUnit::KilometersPerHour => "kmh",
+        }
+    }
+
+    /// 格式化纬度/经度；度分秒使用 ′ ″ 符号，避免 CSV 中的引号转义
+    pub fn coordinate(&self, degrees: f64, is_latitude: bool) -> String {
+        match self.coordinates {
+            CoordinateFormat::Decimal => format!("{:.7}", degrees),
+            CoordinateFormat::Dms => {
+                let hemisphere = match (is_latitude, degrees < 0.0) {
+                    (true, false) => 'N',
+                    (true, true) => 'S',
+                    (false, false) => 'E',
+                    (false, true) => 'W',
+                };
+                let total_seconds = (degrees.abs() * 3600.0 * 100.0).round() / 100.0;
+                let d = (total_seconds / 3600.0).floor();
+                let m = ((total_seconds - d * 3600.0) / 60.0).floor();
+                let s = total_seconds - d * 3600.0 - m * 60.0;
+                format!("{}°{:02}′{:05.2}″{}", d, m, s, hemisphere)
+            }
+        }
+    }
+}
+
+/// 各类输出的单位
+///
+/// ```toml
+/// [units.csv]
+/// altitude = "ft"
+/// speed = "kt"
+///
+/// [units.json]
+/// coordinates = "decimal"
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OutputUnits {
+    pub csv: Units,
+    pub json: Units,
+}
+
+static OUTPUT_UNITS: OnceLock<OutputUnits> = OnceLock::new();
+
+/// 启动时设置一次各类输出的单位
+pub fn configure(units: OutputUnits) {
+    let _ = OUTPUT_UNITS.set(units);
+}
+
+pub fn output_units() -> OutputUnits {
+    OUTPUT_UNITS.get().copied().unwrap_or_default()
+}
+
+pub fn csv() -> Units {
+    output_units().csv
+}
+
+pub fn json() -> Units {
+    output_units().json
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_units() {
+        let units = Units::parse("alt=ft,speed=kt,coords=dms").unwrap();
+        assert_eq!(units, Units { altitude: AltitudeUnit::Feet, speed: SpeedUnit::Knots, coordinates: CoordinateFormat::Dms });
+        assert_eq!(Units::parse("speed = kmh").unwrap().speed, SpeedUnit::KilometersPerHour);
+    }
+
+    #[test]
+    fn test_parse_keeps_unlisted_defaults() {
+        assert_eq!(Units::parse("").unwrap(), Units::default());
+        let units = Units::parse("altitude=ft,").unwrap();
+        assert_eq!((units.speed, units.coordinates), (SpeedUnit::MetersPerSecond, CoordinateFormat::Decimal));
+    }
+
+    #[test]
+    fn test_parse_rejects_unknown() {
+        assert!(Units::parse("alt=furlong").is_none());
+        assert!(Units::parse("depth=m").is_none());
+        assert!(Units::parse("alt").is_none());
+    }
+
+    #[test]
+    fn test_altitude_conversion() {
+        let feet = Units::parse("alt=ft").unwrap();
+        assert!((feet.altitude(100.0) - 328.084).abs() < 1e-6);
+        assert_eq!(feet.altitude_suffix(), "ft");
+        assert_eq!(Units::default().altitude(100.0), 100.0);
+        assert_eq!(Units::default().altitude_suffix(), "m");
+    }
+
+    #[test]
+    fn test_speed_conversion() {
+        assert!((Units::default().speed_from_knots(10.0) - 5.14444).abs() < 1e-6);
+        assert_eq!(Units::parse("speed=kt").unwrap().speed_from_knots(20.0), 20.0);
+        let kmh = Units::parse("speed=km/h").unwrap();
+        assert!((kmh.speed_from_knots(10.0) - 18.519_984).abs() < 1e-6);
+        assert_eq!(kmh.speed_suffix(), "kmh");
+    }
+
+    #[test]
+    fn test_decimal_coordinate() {
+        assert_eq!(Units::default().coordinate(31.2, true), "31.2000000");
+        assert_eq!(Units::default().coordinate(-121.5125, false), "-121.5125000");
+    }
+
+    #[test]
+    fn test_dms_coordinate_hemispheres() {
+        let dms = Units::parse("coords=dms").unwrap();
+        assert_eq!(dms.coordinate(31.2, true), "31°12′00.00″N");
+        assert_eq!(dms.coordinate(-33.5, true), "33°30′00.00″S");
+        assert_eq!(dms.coordinate(121.5125, false), "121°30′45.00″E");
+        assert_eq!(dms.coordinate(-121.5125, false), "121°30′45.00″W");
+    }
+
+    #[test]
+    fn test_dms_rounding_carries_into_minutes() {
+        let dms = Units::parse("coords=dms").unwrap();
+        // 59.999″ 四舍五入到百分之一秒后进位为整分
+        assert_eq!(dms.coordinate(10.0 + 59.999 / 3600.0, true), "10°01′00.00″N");
+    }
+
+    #[test]
+    fn test_output_units_deserialize_partial() {
+        let units: OutputUnits = toml::from_str("[csv]\naltitude = \"ft\"\nspeed = \"kt\"\n").unwrap();
+        assert_eq!(units.csv.altitude, AltitudeUnit::Feet);
+        assert_eq!(units.csv.coordinates, CoordinateFormat::Decimal);
+        assert_eq!(units.json, Units::default());
+    }
+}
@@ -0,0 +1,576 @@
+use std::collections::VecDeque;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use reqwest::blocking::Client;
+use serde::{Deserialize, Serialize};
+use tracing::{error, info, warn};
+
+use crate::canonical;
+use crate::clock;
+use crate::config::Config;
+use crate::egress::{self, Priority};
+use crate::event_log::DecodedEvent;
+use crate::formats::Format;
+use crate::logging::DATA_TARGET;
+use crate::playback::PlaybackControl;
+use crate::privacy::Feed;
+use crate::shutdown;
+use crate::signing::{self, BatchSignature, SigningKey};
+
+const ENDPOINT: &str = "https://mx-lasm-lafs-dev.mxnavi.com/collect/api/v1/data/collect/rid";
+const MAX_BACKOFF: Duration = Duration::from_secs(300);
+/// 出站预算不足时批次留在队列中，隔这么久再试
+const DEFER_DELAY: Duration = Duration::from_secs(5);
+/// 重新上传结束后等待上传线程送出剩余批次的最长时间
+pub const REPLAY_DRAIN_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// 上传请求携带的 `Idempotency-Key` 请求头
+pub const IDEMPOTENCY_HEADER: &str = "Idempotency-Key";
+
+/// 上传的幂等键：`<传感器 ID>-<帧哈希>`
+///
+/// 帧哈希为 FNV-1a 64 位哈希（16 位十六进制），输入依次为重组后的 Remote ID 负载、
+/// 发射端 MAC 和接收时间 (Unix 毫秒)。同一条记录无论重发多少次、由哪次运行重发，
+/// 键都相同；不同时间收到的相同负载键不同。后端按此键去重即可安全地重复接收。
+pub fn idempotency_key(sensor: &str, event: &DecodedEvent) -> String {
+    let hash = canonical::fnv1a64(&[
+        event.record.raw_payload.as_bytes(),
+        event.record.source_mac.as_bytes(),
+        &event.received_at_ms.to_be_bytes(),
+    ]);
+    format!("{}-{:016x}", sensor, hash)
+}
+
+/// 一批记录的幂等键：由批内各记录的幂等键按顺序哈希得到，重发同一批时不变
+pub fn batch_key(sensor: &str, events: &[DecodedEvent]) -> String {
+    let keys: Vec<String> = events.iter().map(|event| idempotency_key(sensor, event)).collect();
+    let parts: Vec<&[u8]> = keys.iter().map(|key| key.as_bytes()).collect();
+    format!("{}-{:016x}", sensor, canonical::fnv1a64(&parts))
+}
+
+/// 上传设置
+///
+/// 记录按批以 `format` 编码后 POST 到 `url`，批次带 `Idempotency-Key` 请求头。发送失败的批次
+/// 按指数退避重试；设置 `queue_dir` 时批次先落盘，网络中断或进程重启后继续发送。
+/// 设置 `signing_key` 时每批在编码后即用接收站密钥签名，签名和密钥 ID 随批次落盘并随请求发送，
+/// 见 `signing::SigningKey`。
+///
+/// ```toml
+/// [upload]
+/// url = "https://example.com/api/v1/rid"
+/// token = "..."               # 可选，以 Bearer 令牌发送
+/// format = "json"             # json/ndjson/cbor/protobuf/csv，见 formats::Format
+/// batch_size = 50
+/// batch_ms = 1000             # 未满一批时最长等待
+/// queue_dir = "upload-queue"
+/// max_queue_mb = 256          # 队列超出时丢弃最早的批次
+/// signing_key = "/etc/wifi-capture/sensor.key"
+/// key_id = "roof-2"           # 可选，默认取公钥指纹
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UploadConfig {
+    #[serde(default = "default_url")]
+    pub url: String,
+    #[serde(default)]
+    pub token: Option<String>,
+    #[serde(default)]
+    pub format: Format,
+    #[serde(default = "default_batch_size")]
+    pub batch_size: usize,
+    #[serde(default = "default_batch_ms")]
+    pub batch_ms: u64,
+    #[serde(default)]
+    pub queue_dir: Option<PathBuf>,
+    #[serde(default = "default_max_queue_mb")]
+    pub max_queue_mb: u64,
+    #[serde(default)]
+    pub signing_key: Option<PathBuf>,
+    #[serde(default)]
+    pub key_id: Option<String>,
+}
+
+fn default_url() -> String {
+    ENDPOINT.to_string()
+}
+
+fn default_batch_size() -> usize {
+    50
+}
+
+fn default_batch_ms() -> u64 {
+    1000
+}
+
+fn default_max_queue_mb() -> u64 {
+    256
+}
+
+impl Default for UploadConfig {
+    fn default() -> Self {
+        Self {
+            url: default_url(),
+            token: None,
+            format: Format::default(),
+            batch_size: default_batch_size(),
+            batch_ms: default_batch_ms(),
+            queue_dir: None,
+            max_queue_mb: default_max_queue_mb(),
+            signing_key: None,
+            key_id: None,
+        }
+    }
+}
+
+impl UploadConfig {
+    /// `signing_key` 配置的上传签名密钥
+    pub fn load_signing_key(&self) -> Result<Option<SigningKey>, String> {
+        self.signing_key.as_deref()
+            .map(|path| SigningKey::load(path, self.key_id.as_deref()))
+            .transpose()
+    }
+}
+
+/// 待发送的一批记录
+#[derive(Debug, Clone, PartialEq)]
+pub struct Batch {
+    pub key: String,
+    pub format: Format,
+    pub body: Vec<u8>,
+    pub signature: Option<BatchSignature>,
+}
+
+impl Batch {
+    pub fn new(sensor: &str, events: &[DecodedEvent], format: Format) -> Result<Self, String> {
+        let body = format.encode(events.iter().map(|event| &event.record))?;
+        Ok(Self { key: batch_key(sensor, events), format, body, signature: None })
+    }
+
+    /// 编码一批记录；设置 `max_body` 时正文超出的批次对半拆分，单条记录无法再拆
+    pub fn split(sensor: &str, events: &[DecodedEvent], format: Format, max_body: Option<usize>) -> Result<Vec<Self>, String> {
+        let batch = Self::new(sensor, events, format)?;
+        match max_body {
+            Some(max) if batch.body.len() > max && events.len() > 1 => {
+                let (first, second) = events.split_at(events.len() / 2);
+                let mut batches = Self::split(sensor, first, format, max_body)?;
+                batches.extend(Self::split(sensor, second, format, max_body)?);
+                Ok(batches)
+            }
+            _ => Ok(vec![batch]),
+        }
+    }
+
+    /// 对请求正文签名
+    pub fn sign(mut self, key: &SigningKey) -> Self {
+        self.signature = Some(key.sign(&self.body));
+        self
+    }
+}
+
+/// 发送队列：按先进先出保存未送达的批次
+///
+/// 有目录时每批一个文件（首行为幂等键、格式及可选的密钥 ID 和签名，其余为请求正文），打开时载入上次未发送完的批次；
+/// 总大小超出上限时丢弃最早的批次。
+pub struct UploadQueue {
+    dir: Option<PathBuf>,
+    max_bytes: u64,
+    bytes: u64,
+    pending: VecDeque<(Batch, Option<PathBuf>)>,
+    sequence: u64,
+}
+
+impl UploadQueue {
+    pub fn open(dir: Option<&Path>, max_bytes: u64) -> io::Result<Self> {
+        let mut queue = Self { dir: dir.map(Path::to_path_buf), max_bytes, bytes: 0, pending: VecDeque::new(), sequence: 0 };
+        let Some(dir) = dir else { return Ok(queue) };
+        fs::create_dir_all(dir)?;
+        let mut files: Vec<PathBuf> = fs::read_dir(dir)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "batch"))
+            .collect();
+        files.sort();
+        for path in files {
+            let Some(batch) = read_batch(&fs::read(&path)?) else {
+                warn!("discarding unreadable upload batch {}", path.display());
+                fs::remove_file(&path)?;
+                continue;
+            };
+            queue.bytes += batch.body.len() as u64;
+            queue.pending.push_back((batch, Some(path)));
+        }
+        if !queue.pending.is_empty() {
+            info!("{} upload batches pending from previous run", queue.pending.len());
+        }
+        Ok(queue)
+    }
+
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    pub fn push(&mut self, batch: Batch) -> io::Result<()> {
+        let path = match &self.dir {
+            Some(dir) => {
+                self.sequence += 1;
+                let path = dir.join(format!("{:013}-{:06}.batch", clock::now_ms().0, self.sequence % 1_000_000));
+                let mut header = format!("{} {}", batch.key, batch.format.name());
+                if let Some(signed) = &batch.signature {
+                    header = format!("{} {} {}", header, signed.key_id, signed.signature);
+                }
+                let mut contents = format!("{}\n", header).into_bytes();
+                contents.extend_from_slice(&batch.body);
+                fs::write(&path, contents)?;
+                Some(path)
+            }
+            None => None,
+        };
+        self.bytes += batch.body.len() as u64;
+        self.pending.push_back((batch, path));
+        let mut dropped = 0;
+        while self.bytes > self.max_bytes && self.pending.len() > 1 {
+            self.pop()?;
+            dropped += 1;
+        }
+        if dropped > 0 {
+            warn!("upload queue over {} bytes, dropped {} oldest batches", self.max_bytes, dropped);
+        }
+        Ok(())
+    }
+
+    pub fn front(&self) -> Option<&Batch> {
+        self.pending.front().map(|(batch, _)| batch)
+    }
+
+    /// 移除最早的批次及其文件
+    pub fn pop(&mut self) -> io::Result<()> {
+        if let Some((batch, path)) = self.pending.pop_front() {
+            self.bytes -= batch.body.len() as u64;
+            if let Some(path) = path {
+                fs::remove_file(path)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 解析队列文件；不带格式的旧文件为 JSON
+fn read_batch(contents: &[u8]) -> Option<Batch> {
+    let newline = contents.iter().position(|&b| b == b'\n')?;
+    let header = std::str::from_utf8(&contents[..newline]).ok()?;
+    let mut fields = header.split(' ');
+    let key = fields.next()?;
+    let format = match fields.next() {
+        Some(format) => Format::parse(format)?,
+        None => Format::Json,
+    };
+    let signature = match (fields.next(), fields.next()) {
+        (Some(key_id), Some(signature)) => Some(BatchSignature { key_id: key_id.to_string(), signature: signature.to_string() }),
+        _ => None,
+    };
+    Some(Batch { key: key.to_string(), format, body: contents[newline + 1..].to_vec(), signature })
+}
+
+/// 后台上传：解码线程只把事件交给通道，攒批、发送和重试都在上传线程中进行
+pub struct Uploader {
+    sender: mpsc::Sender<DecodedEvent>,
+    handle: JoinHandle<()>,
+    queued: Arc<AtomicUsize>,
+}
+
+impl Uploader {
+    /// `signing` 为 `upload.signing_key` 读出的密钥，此后编码的每批都带签名
+    pub fn start(config: UploadConfig, sensor: String, signing: Option<SigningKey>) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let queued = Arc::new(AtomicUsize::new(0));
+        let depth = queued.clone();
+        let handle = thread::spawn(move || run(config, sensor, signing, receiver, &depth));
+        Self { sender, handle, queued }
+    }
+
+    pub fn send(&self, event: DecodedEvent) {
+        let _ = self.sender.send(event);
+    }
+
+    /// 等待送出的批次数（含磁盘队列中的）
+    pub fn queued(&self) -> usize {
+        self.queued.load(Ordering::Relaxed)
+    }
+
+    /// 停止接收事件，等待上传线程送出未满的批次和队列中的批次；超时返回 false
+    ///
+    /// 送不出的批次留在磁盘队列中（配置了 `queue_dir` 时），下次启动后继续上传。
+    pub fn close(self, timeout: Duration) -> bool {
+        drop(self.sender);
+        shutdown::join_timeout(self.handle, timeout)
+    }
+}
+
+/// 按接收时间 (`[from_ms, to_ms)`) 和 UAS ID 挑出要重新上传的记录，按接收时间排序
+pub fn replay_selection(events: Vec<DecodedEvent>, from_ms: Option<i64>, to_ms: Option<i64>, uas: Option<&str>) -> Vec<DecodedEvent> {
+    let in_range = |at: i64| from_ms.is_none_or(|t| at >= t) && to_ms.is_none_or(|t| at < t);
+    let mut events: Vec<DecodedEvent> = events.into_iter()
+        .filter(|e| in_range(e.received_at_ms) && uas.is_none_or(|id| e.record.rid == id))
+        .collect();
+    events.sort_by_key(|e| e.received_at_ms);
+    events
+}
+
+/// 重新上传记录：按原来的间隔 (`speed` 倍速) 经上传的模糊化设置后交给上传线程
+///
+/// 记录保留原来的记录 ID，后端可按幂等键去掉已经收到过的批次。结束后最多等待
+/// [`REPLAY_DRAIN_TIMEOUT`]，未能送出全部批次时返回 false，未送出的批次留在上传队列中。
+/// `signing` 为 [`UploadConfig::load_signing_key`] 读出的密钥。
+pub fn replay(events: &[DecodedEvent], speed: f64, config: &Config, signing: Option<SigningKey>) -> bool {
+    let sensor_id = canonical::sensor_id(config.sensor_id.as_deref());
+    let uploader = Uploader::start(config.upload.clone(), sensor_id, signing);
+    let mut feed = Feed::new(config.privacy.upload.clone());
+    let pacing = PlaybackControl::new(speed);
+    let mut previous_ms = None;
+    for event in events {
+        if let Some(previous_ms) = previous_ms {
+            pacing.wait(event.received_at_ms - previous_ms);
+        }
+        previous_ms = Some(event.received_at_ms);
+        for event in feed.release(Some(event), Utc::now().timestamp_millis()) {
+            uploader.send(event.into_owned());
+        }
+    }
+    uploader.close(REPLAY_DRAIN_TIMEOUT)
+}
+
+enum Delivery {
+    Done,
+    /// 出站预算不足，批次留在队列中稍后再发
+    Deferred,
+    Retry(String),
+}
+
+/// 按发送结果处理队首批次：只有送达（或被服务端拒绝）的批次才移出队列
+fn settle(queue: &mut UploadQueue, delivery: &Delivery) -> io::Result<()> {
+    match delivery {
+        Delivery::Done => queue.pop(),
+        Delivery::Deferred | Delivery::Retry(_) => Ok(()),
+    }
+}
+
+fn deliver(client: &Client, config: &UploadConfig, batch: &Batch) -> Delivery {
+    if !egress::allow(Priority::Bulk, batch.body.len() + egress::HTTP_OVERHEAD) {
+        return Delivery::Deferred;
+    }
+    let mut request = client.post(&config.url)
+        .header(IDEMPOTENCY_HEADER, &batch.key)
+        .header(reqwest::header::CONTENT_TYPE, batch.format.content_type())
+        .body(batch.body.clone());
+    if let Some(token) = &config.token {
+        request = request.bearer_auth(token);
+    }
+    if let Some(signed) = &batch.signature {
+        request = request.header(signing::SIGNATURE_HEADER, &signed.signature)
+            .header(signing::KEY_ID_HEADER, &signed.key_id);
+    }
+    match request.send() {
+        Ok(response) if response.status().is_success() => Delivery::Done,
+        // 服务端错误和限流稍后重试，其它拒绝重试也不会成功
+        Ok(response) if response.status().is_server_error() || response.status().as_u16() == 429 => {
+            Delivery::Retry(format!("status {}", response.status()))
+        }
+        Ok(response) => {
+            error!("上传被拒绝 {}: {}", response.status(), response.text().unwrap_or_default());
+            Delivery::Done
+        }
+        Err(e) => Delivery::Retry(e.to_string()),
+    }
+}
+
+fn run(config: UploadConfig, sensor: String, signing: Option<SigningKey>, receiver: mpsc::Receiver<DecodedEvent>, queued: &AtomicUsize) {
+    let client = Client::builder()
+        .timeout(Duration::from_secs(10)) // 设置超时
+        .build().unwrap();
+    let max_bytes = config.max_queue_mb * 1024 * 1024;
+    let mut queue = UploadQueue::open(config.queue_dir.as_deref(), max_bytes).unwrap_or_else(|e| {
+        error!("无法打开上传队列目录，改为内存队列: {}", e);
+        UploadQueue { dir: None, max_bytes, bytes: 0, pending: VecDeque::new(), sequence: 0 }
+    });
+    let batch_wait = Duration::from_millis(config.batch_ms);
+    let mut batch = Vec::new();
+    let mut batch_started = Instant::now();
+    let mut backoff = Duration::from_secs(1);
+    let mut next_attempt = Instant::now();
+    loop {
+        let now = Instant::now();
+        let mut wait = if batch.is_empty() { Duration::from_secs(1) } else { (batch_started + batch_wait).saturating_duration_since(now) };
+        if !queue.is_empty() {
+            wait = wait.min(next_attempt.saturating_duration_since(now));
+        }
+        let disconnected = match receiver.recv_timeout(wait) {
+            Ok(event) => {
+                if batch.is_empty() {
+                    batch_started = Instant::now();
+                }
+                info!(target: DATA_TARGET, "json: {}", canonical::to_json(&event.record).unwrap_or_default());
+                batch.push(event);
+                false
+            }
+            Err(RecvTimeoutError::Timeout) => false,
+            Err(RecvTimeoutError::Disconnected) => true,
+        };
+        if !batch.is_empty() && (batch.len() >= config.batch_size.max(1) || batch_started.elapsed() >= batch_wait || disconnected) {
+            // 批次拆到出站预算中批量数据的份额以内，否则预算再充足也发不出去
+            let max_body = egress::share(Priority::Bulk).map(|share| share.saturating_sub(egress::HTTP_OVERHEAD));
+            match Batch::split(&sensor, &batch, config.format, max_body) {
+                Ok(encoded) => {
+                    for encoded in encoded {
+                        let encoded = match &signing {
+                            Some(key) => encoded.sign(key),
+                            None => encoded,
+                        };
+                        if let Err(e) = queue.push(encoded) {
+                            error!("写入上传队列失败: {}", e);
+                        }
+                    }
+                }
+                Err(e) => error!("编码上传批次失败: {}", e),
+            }
+            batch.clear();
+        }
+        queued.store(queue.len(), Ordering::Relaxed);
+        // 通道关闭（退出）时不等退避，立即尝试送出队列中的批次
+        while (disconnected || Instant::now() >= next_attempt) && let Some(front) = queue.front() {
+            let delivery = deliver(&client, &config, front);
+            if let Err(e) = settle(&mut queue, &delivery) {
+                error!("删除已上传批次失败: {}", e);
+            }
+            match delivery {
+                Delivery::Done => backoff = Duration::from_secs(1),
+                Delivery::Deferred => {
+                    info!("egress budget exhausted, {} upload batches queued, deferring {:?}", queue.len(), DEFER_DELAY);
+                    next_attempt = Instant::now() + DEFER_DELAY;
+                    break;
+                }
+                Delivery::Retry(reason) => {
+                    warn!("upload failed ({}), {} batches queued, retrying in {:?}", reason, queue.len(), backoff);
+                    next_attempt = Instant::now() + backoff;
+                    backoff = (backoff * 2).min(MAX_BACKOFF);
+                    if disconnected {
+                        break;
+                    }
+                }
+            }
+        }
+        queued.store(queue.len(), Ordering::Relaxed);
+        if disconnected {
+            if !queue.is_empty() {
+                warn!("{} upload batches still queued at shutdown", queue.len());
+            }
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::upload_data::UploadData;
+
+    #[test]
+    fn test_idempotency_key() {
+        let event = DecodedEvent {
+            received_at_ms: 1_700_000_000_000,
+            record: UploadData { raw_payload: "f11903".into(), source_mac: "02:11:22:33:44:55".into(), ..Default::default() },
+        };
+        let key = idempotency_key("sensor-1", &event);
+        assert!(key.starts_with("sensor-1-") && key.len() == "sensor-1-".len() + 16);
+        assert_eq!(key, idempotency_key("sensor-1", &event.clone()));
+        let later = DecodedEvent { received_at_ms: event.received_at_ms + 1, ..event.clone() };
+        assert_ne!(key, idempotency_key("sensor-1", &later));
+        assert_ne!(key, idempotency_key("sensor-2", &event));
+    }
+
+    #[test]
+    fn test_replay_selection() {
+        let event = |received_at_ms: i64, rid: &str| DecodedEvent {
+            received_at_ms,
+            record: UploadData { rid: rid.into(), ..Default::default() },
+        };
+        let events = vec![event(3_000, "A"), event(1_000, "A"), event(2_000, "B"), event(4_000, "A")];
+        let selected = replay_selection(events.clone(), Some(1_000), Some(4_000), Some("A"));
+        assert_eq!(selected.iter().map(|e| e.received_at_ms).collect::<Vec<_>>(), [1_000, 3_000]);
+        let all = replay_selection(events, None, None, None);
+        assert_eq!(all.iter().map(|e| e.received_at_ms).collect::<Vec<_>>(), [1_000, 2_000, 3_000, 4_000]);
+    }
+
+    #[test]
+    fn test_upload_queue_persists() {
+        let dir = std::env::temp_dir().join("wifi-capture-upload-queue-test");
+        fs::remove_dir_all(&dir).ok();
+        let event = |rid: &str| DecodedEvent {
+            received_at_ms: 1_700_000_000_000,
+            record: UploadData { rid: rid.into(), ..Default::default() },
+        };
+        let first = Batch::new("sensor-1", &[event("A"), event("B")], Format::Json).unwrap();
+        let body = String::from_utf8(first.body.clone()).unwrap();
+        assert!(body.starts_with("[{") && body.contains(r#""rid":"B""#));
+        let key = SigningKey::from_seed_hex(&"07".repeat(32), Some("roof-2")).unwrap();
+        let csv = Batch::new("sensor-1", &[event("C")], Format::Csv).unwrap().sign(&key);
+        {
+            let mut queue = UploadQueue::open(Some(&dir), 1 << 20).unwrap();
+            queue.push(first.clone()).unwrap();
+            queue.push(csv.clone()).unwrap();
+        }
+        // 重新打开后按原顺序载入，发送完的批次删除文件
+        let mut queue = UploadQueue::open(Some(&dir), 1 << 20).unwrap();
+        assert_eq!((queue.len(), queue.front()), (2, Some(&first)));
+        queue.pop().unwrap();
+        assert_eq!((fs::read_dir(&dir).unwrap().count(), queue.front()), (1, Some(&csv)));
+
+        // 超出上限时丢弃最早的批次
+        let mut queue = UploadQueue::open(None, first.body.len() as u64).unwrap();
+        queue.push(csv).unwrap();
+        queue.push(first.clone()).unwrap();
+        assert_eq!((queue.len(), queue.front()), (1, Some(&first)));
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_deferred_batch_stays_queued() {
+        let dir = std::env::temp_dir().join("wifi-capture-upload-defer-test");
+        fs::remove_dir_all(&dir).ok();
+        let event = DecodedEvent { received_at_ms: 1_700_000_000_000, record: UploadData::default() };
+        let batch = Batch::new("sensor-1", &[event], Format::Json).unwrap();
+        let mut queue = UploadQueue::open(Some(&dir), 1 << 20).unwrap();
+        queue.push(batch.clone()).unwrap();
+        settle(&mut queue, &Delivery::Deferred).unwrap();
+        assert_eq!((queue.len(), fs::read_dir(&dir).unwrap().count()), (1, 1));
+        // 重启后仍在队列中，送达后才删除文件
+        let mut queue = UploadQueue::open(Some(&dir), 1 << 20).unwrap();
+        assert_eq!(queue.front(), Some(&batch));
+        settle(&mut queue, &Delivery::Done).unwrap();
+        assert_eq!((queue.len(), fs::read_dir(&dir).unwrap().count()), (0, 0));
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_batch_split_fits_budget() {
+        let events: Vec<DecodedEvent> = (0..8)
+            .map(|i| DecodedEvent { received_at_ms: i, record: UploadData { rid: format!("RID{i}"), ..Default::default() } })
+            .collect();
+        let whole = Batch::new("sensor-1", &events, Format::Json).unwrap();
+        let max = whole.body.len() / 3;
+        let batches = Batch::split("sensor-1", &events, Format::Json, Some(max)).unwrap();
+        assert!(batches.len() > 1 && batches.iter().all(|b| b.body.len() <= max));
+        assert_eq!(Batch::split("sensor-1", &events, Format::Json, None).unwrap(), vec![whole]);
+        // 单条记录无法再拆，仍作为一批
+        assert_eq!(Batch::split("sensor-1", &events[..1], Format::Json, Some(1)).unwrap().len(), 1);
+    }
+}
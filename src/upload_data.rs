@@ -1,7 +1,195 @@
-use serde::Serialize;
-#[derive(Serialize)]
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::authorization::AuthorizationStatus;
+use crate::auth_verify::AuthVerification;
+use crate::clock::TimeSource;
+use crate::config::Tags;
+use crate::fleet::Annotation;
+use crate::localization::PositionEstimate;
+use crate::message::accuracy::AccuracyBounds;
+use crate::message::auth_message::AuthMessage;
+use crate::message::classification::Classification;
+use crate::message::codes::{HeightReference, IdType, OperationalStatus, UaType};
+use crate::message::profile::FormatProfile;
+use crate::message::position_vector_message::PositionVectorMessage;
+use crate::position::PositionStatus;
+use crate::receiver::ReceiverPosition;
+use crate::rssi::{RangeBin, RssiTrend};
+use crate::ssid_check::SsidMatch;
+use crate::message::system_message::SystemMessage;
+use crate::remote_id::to_hex;
+
+/// 控制站（操作员）位置，来自 SystemMessage
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct OperatorPosition {
+    pub location_type: u8,   // 控制站位置类型
+    pub latitude: i32,       // 纬度 (1e-7 度)
+    pub longitude: i32,      // 经度 (1e-7 度)
+    pub altitude: u16,       // 控制站高度 (0.1 米)
+}
+
+impl From<&SystemMessage> for OperatorPosition {
+    fn from(sm: &SystemMessage) -> Self {
+        Self {
+            location_type: sm.station_type,
+            latitude: sm.latitude,
+            longitude: sm.longitude,
+            altitude: sm.station_altitude,
+        }
+    }
+}
+
+/// 位置向量按物理单位换算后的值，原始编码仍在记录的同名字段中
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct Kinematics {
+    pub operational_status: OperationalStatus,
+    pub latitude_deg: f64,
+    pub longitude_deg: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pressure_altitude_m: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub geometric_altitude_m: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub height_m: Option<f32>,                // 距地高度 (米)
+    pub height_reference: HeightReference,
+    pub ground_speed_mps: f32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vertical_speed_mps: Option<f32>,
+    pub track_deg: u16,                       // 航迹角 (度, 正北顺时针)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp_ms: Option<i64>,            // 报文时间戳 (Unix 毫秒)，按接收时间补全到小时
+}
+
+impl Kinematics {
+    /// `received_at_ms` 用于补全只有整点后秒数的报文时间戳
+    pub fn new(pvm: &PositionVectorMessage, received_at_ms: i64) -> Self {
+        Self {
+            operational_status: pvm.operational_status(),
+            latitude_deg: pvm.latitude_deg(),
+            longitude_deg: pvm.longitude_deg(),
+            pressure_altitude_m: pvm.pressure_altitude_m(),
+            geometric_altitude_m: pvm.geometric_altitude_m(),
+            height_m: pvm.height_m(),
+            height_reference: pvm.height_reference(),
+            ground_speed_mps: (pvm.ground_speed_mps() * 100.0).round() / 100.0,
+            vertical_speed_mps: pvm.vertical_speed_mps(),
+            track_deg: pvm.track_angle_deg(),
+            timestamp_ms: chrono::DateTime::from_timestamp_millis(received_at_ms)
+                .and_then(|reference| pvm.timestamp(reference))
+                .map(|time| time.timestamp_millis()),
+        }
+    }
+}
+
+/// 记录来源：本机接收的广播 Remote ID，或经 USS/其他传感器网络获取的网络 Remote ID
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordSource {
+    #[default]
+    Broadcast,
+    Network,
+}
+
+/// 认证消息的一页数据
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct AuthPage {
+    pub page: u8,
+    pub data: String,        // 本页认证数据 (十六进制)
+}
+
+/// 某个网卡收到该帧时的信号强度
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct InterfaceRssi {
+    pub interface: String,
+    pub rssi: f32,           // dBm
+}
+
+/// 一次接收中收到的认证消息，第 0 页的头部字段与各页数据
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct Authentication {
+    pub auth_type: u8,               // 认证类型
+    pub last_page_index: Option<u8>, // 末页序号，收到第 0 页时才有
+    pub length: Option<u8>,          // 认证数据总长度 (字节)
+    pub timestamp: Option<u32>,      // 2019-01-01 00:00:00 UTC 起的秒数
+    pub pages: Vec<AuthPage>,
+}
+
+impl Authentication {
+    pub fn add_page(&mut self, message: &AuthMessage) {
+        if message.page_number == 0 {
+            self.last_page_index = message.last_page_index;
+            self.length = message.length;
+            self.timestamp = message.timestamp;
+        }
+        self.pages.push(AuthPage { page: message.page_number, data: to_hex(&message.data) });
+        self.pages.sort_by_key(|p| p.page);
+    }
+}
+
+impl From<&AuthMessage> for Authentication {
+    fn from(message: &AuthMessage) -> Self {
+        let mut auth = Self { auth_type: message.auth_type, last_page_index: None, length: None, timestamp: None, pages: Vec::new() };
+        auth.add_page(message);
+        auth
+    }
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema)]
 pub struct UploadData {
+    pub format_version: u32,
+    #[serde(default)]
+    pub record_id: String,        // 稳定记录 ID，见 canonical::record_id
+    #[serde(default, skip_serializing_if = "Tags::is_empty")]
+    pub tags: Tags,               // 配置文件中的租户/站点/部署标签
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub annotation: Option<Annotation>,   // 机队标注表中该 UAS ID 的名称/所属单位/颜色
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub authorization: Option<AuthorizationStatus>,   // 配置了授权查询时该 UAS ID 的飞行授权状态
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth_verification: Option<AuthVerification>,  // 配置了信任库时认证消息签名的校验结果
+    #[serde(default)]
+    pub time_source: TimeSource,  // 接收时间的来源（系统时钟或 GPS 校时）
     pub rid: String,
+    pub source_mac: String,       // 发射端 MAC (信标帧源地址)
+    pub id_collision: bool,       // 同一 UAS ID 同时出现在其它源 MAC 上
+    pub track_id: String,         // 逻辑轨迹 ID，启用 MAC 关联时可能跨多个源 MAC
+    pub message_counter: u8,      // 负载头中的消息计数器
+    pub rssi: Option<f32>,        // 接收信号强度 (dBm)
+    pub channel: Option<u8>,      // 接收信道
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub noise_dbm: Option<f32>,   // 接收噪声 (dBm)，驱动不报告时为空
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mac_timestamp_us: Option<u64>,   // 网卡 MAC 时间戳 (radiotap TSFT，微秒)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub received_at_ms: Option<i64>,     // 接收时间 (Unix 毫秒)
+    pub rssi_trend: Option<RssiTrend>,     // 信号强度趋势（接近/远离）
+    pub estimated_range_m: Option<f32>,    // 按路径损耗模型估算的距离
+    pub range_bin: Option<RangeBin>,
+    pub bearing_deg: Option<f32>,          // 定向天线测向得到的方位
+    pub bearing_confidence: Option<f32>,   // 方位置信度 (0-1)
+    pub rid_lossy: bool,          // UAS ID 经宽松 UTF-8 解码
+    pub rid_raw: Option<String>,  // 宽松解码时附带原始 20 字节 (十六进制)
+    #[serde(default)]
+    pub ua_type: Option<u8>,      // Basic ID 报文中的 UA 类型
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ua_type_name: Option<UaType>,     // UA 类型的名称
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uas_id_type: Option<IdType>,      // Basic ID 报文中的 UAS ID 类型
+    #[serde(default)]
+    pub self_id_type: Option<u8>,       // Self-ID 描述类型
+    #[serde(default)]
+    pub self_id: Option<String>,        // Self-ID 描述文本
+    #[serde(default)]
+    pub operator_id_type: Option<u8>,   // 运营人 ID 类型
+    #[serde(default)]
+    pub operator_id: Option<String>,    // 运营人 ID
+    #[serde(default)]
+    pub auth: Option<Authentication>,   // 认证消息
+    #[serde(default)]
+    pub heuristic: bool,          // 来自数据帧深度扫描的启发式命中，可能是误报
+    #[serde(default)]
+    pub source: RecordSource,     // 广播接收或网络 Remote ID
     pub run_status: u8,
     pub reserved_flag: bool,
     pub height_type: u8,
@@ -21,4 +209,110 @@ pub struct UploadData {
     pub timestamp: u16,
     pub timestamp_accuracy: u8,
     pub reserved: u8,
-}
\ No newline at end of file
+    pub operator: Option<OperatorPosition>,
+    pub classification: Option<Classification>,
+    pub accuracy_bounds: AccuracyBounds,
+    /// 位置是否可用，见 [`crate::position::classify`]
+    #[serde(default)]
+    pub position_status: PositionStatus,
+    /// SSID 中的序列号与 UAS ID 是否一致，见 [`crate::ssid_check::compare`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ssid_match: Option<SsidMatch>,
+    /// 解析位置向量和系统消息所用的报文格式，数值已统一换算为国标格式的单位
+    #[serde(default)]
+    pub format_profile: FormatProfile,
+    /// 检测质量评分 (0-1)，综合 FCS 校验、信号强度、解析失败、字段校验和计数器连续性，见 [`crate::quality`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quality: Option<f32>,
+    /// 无人机到控制站的水平距离 (米)，见 [`crate::position::operator_distance_m`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub operator_distance_m: Option<f32>,
+    /// 多网卡收到同一帧时各网卡的信号强度 (dBm)，见 [`crate::dedup::DuplicateFilter`]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub interface_rssi: Vec<InterfaceRssi>,
+    /// 收到该记录时接收站的位置（GPS 或 `--site`），见 [`crate::receiver`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub receiver: Option<ReceiverPosition>,
+    /// 无人机到接收站的水平距离 (米) 和方位 (度)，见 [`crate::receiver::relative_position`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub receiver_distance_m: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub receiver_bearing_deg: Option<f32>,
+    /// 配置了电子围栏时是否位于围栏内及所在的区域，见 [`crate::geofence::Geofence`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inside_geofence: Option<bool>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub geofence_zones: Vec<String>,
+    /// 控制站是否位于围栏内及所在的区域
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub operator_inside_geofence: Option<bool>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub operator_geofence_zones: Vec<String>,
+    /// 位置向量按物理单位换算的值，见 [`Kinematics`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kinematics: Option<Kinematics>,
+    /// 组网汇聚时由多个接收站的观测粗略估计的位置，见 [`crate::localization::MultiSensorLocator`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub localized: Option<PositionEstimate>,
+    /// 广播位置与估计位置的偏差得出的仿冒评分 (0-1)，见 [`PositionEstimate::spoof_score`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spoof_score: Option<f32>,
+    pub raw_payload: String,        // 重组后的完整 Remote ID 负载 (十六进制)
+    pub raw_messages: Vec<String>,  // 每条 25 字节消息 (十六进制)
+}
+
+impl UploadData {
+    /// 地速 (节)，已按速度乘数换算
+    pub fn ground_speed_knots(&self) -> f64 {
+        self.ground_speed as f64 * if self.speed_multiplier { 10.0 } else { 1.0 }
+    }
+
+    /// 记录中的位置向量字段，用于按物理单位读取
+    pub fn position_vector(&self) -> PositionVectorMessage {
+        PositionVectorMessage {
+            run_status: self.run_status,
+            reserved_flag: self.reserved_flag,
+            height_type: self.height_type,
+            track_direction: self.track_direction,
+            speed_multiplier: self.speed_multiplier,
+            track_angle: self.track_angle,
+            ground_speed: self.ground_speed,
+            vertical_speed: self.vertical_speed,
+            latitude: self.latitude,
+            longitude: self.longitude,
+            pressure_altitude: self.pressure_altitude,
+            geometric_altitude: self.geometric_altitude,
+            ground_altitude: self.ground_altitude,
+            vertical_accuracy: self.vertical_accuracy,
+            horizontal_accuracy: self.horizontal_accuracy,
+            speed_accuracy: self.speed_accuracy,
+            timestamp: self.timestamp,
+            timestamp_accuracy: self.timestamp_accuracy,
+            reserved: self.reserved,
+        }
+    }
+
+    /// 用位置向量消息填充位置相关字段（含精度编码及其解码上限）
+    pub fn apply_position(&mut self, pvm: &PositionVectorMessage) {
+        self.run_status = pvm.run_status;
+        self.reserved_flag = pvm.reserved_flag;
+        self.height_type = pvm.height_type;
+        self.track_direction = pvm.track_direction;
+        self.speed_multiplier = pvm.speed_multiplier;
+        self.track_angle = pvm.track_angle;
+        self.ground_speed = pvm.ground_speed;
+        self.vertical_speed = pvm.vertical_speed;
+        self.latitude = pvm.latitude;
+        self.longitude = pvm.longitude;
+        self.pressure_altitude = pvm.pressure_altitude;
+        self.geometric_altitude = pvm.geometric_altitude;
+        self.ground_altitude = pvm.ground_altitude;
+        self.vertical_accuracy = pvm.vertical_accuracy;
+        self.horizontal_accuracy = pvm.horizontal_accuracy;
+        self.speed_accuracy = pvm.speed_accuracy;
+        self.timestamp = pvm.timestamp;
+        self.timestamp_accuracy = pvm.timestamp_accuracy;
+        self.reserved = pvm.reserved;
+        self.accuracy_bounds = pvm.accuracy_bounds();
+    }
+}
@@ -0,0 +1,191 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use tracing::{error, info};
+
+use crate::wifi;
+
+/// 抓包线程每收到一帧调用一次 `beat`
+#[derive(Clone)]
+pub struct Heartbeat {
+    epoch: Instant,
+    last_ms: Arc<AtomicU64>,
+}
+
+impl Heartbeat {
+    pub fn beat(&self) {
+        self.last_ms.store(self.epoch.elapsed().as_millis() as u64, Ordering::Relaxed);
+    }
+}
+
+/// 一个网卡的收帧情况，见 [`Watchdog::activity`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct InterfaceActivity {
+    pub interface: String,
+    pub idle: Duration,
+    pub restarts: u32,
+}
+
+struct Monitored {
+    interface: String,
+    fixed_channel: Option<u8>,
+    last_ms: Arc<AtomicU64>,
+    restarts: u32,
+    next_check_ms: u64,
+}
+
+/// 抓包停滞看门狗
+///
+/// 网卡在设定时间内收不到任何帧（驱动卡死或退出了监听模式）时，
+/// 输出健康告警并重新初始化网卡；连续重启时检查间隔逐次加倍，最长 10 分钟。
+pub struct Watchdog {
+    epoch: Instant,
+    timeout: Duration,
+    monitored: Mutex<Vec<Monitored>>,
+}
+
+impl Watchdog {
+    const MAX_BACKOFF: Duration = Duration::from_secs(600);
+
+    pub fn new(timeout: Duration) -> Arc<Self> {
+        Arc::new(Self { epoch: Instant::now(), timeout, monitored: Mutex::new(Vec::new()) })
+    }
+
+    /// 登记一个网卡，返回抓包线程使用的心跳；`fixed_channel` 为重新初始化后需恢复的固定信道
+    pub fn register(&self, interface: &str, fixed_channel: Option<u8>) -> Heartbeat {
+        let now_ms = self.now_ms();
+        let last_ms = Arc::new(AtomicU64::new(now_ms));
+        self.monitored.lock().unwrap().push(Monitored {
+            interface: interface.to_string(),
+            fixed_channel,
+            last_ms: last_ms.clone(),
+            restarts: 0,
+            next_check_ms: now_ms,
+        });
+        Heartbeat { epoch: self.epoch, last_ms }
+    }
+
+    /// 各网卡距最近一次收到帧（尚未收到时为登记时）的时长和连续重新初始化次数
+    pub fn activity(&self) -> Vec<InterfaceActivity> {
+        let now_ms = self.now_ms();
+        self.monitored.lock().unwrap().iter().map(|m| InterfaceActivity {
+            interface: m.interface.clone(),
+            idle: Duration::from_millis(now_ms.saturating_sub(m.last_ms.load(Ordering::Relaxed))),
+            restarts: m.restarts,
+        }).collect()
+    }
+
+    fn now_ms(&self) -> u64 {
+        self.epoch.elapsed().as_millis() as u64
+    }
+
+    /// 返回已停滞、需要重新初始化的网卡，并推迟它们的下一次检查
+    fn stalled(&self, now_ms: u64) -> Vec<(String, Option<u8>, u32)> {
+        let timeout_ms = self.timeout.as_millis() as u64;
+        let mut stalled = Vec::new();
+        for m in self.monitored.lock().unwrap().iter_mut() {
+            let last_ms = m.last_ms.load(Ordering::Relaxed);
+            if now_ms.saturating_sub(last_ms) < timeout_ms {
+                if m.restarts > 0 {
+                    info!("{} receiving frames again", m.interface);
+                }
+                m.restarts = 0;
+                continue;
+            }
+            if now_ms < m.next_check_ms {
+                continue;
+            }
+            m.restarts += 1;
+            let backoff = (timeout_ms << (m.restarts - 1).min(16)).min(Self::MAX_BACKOFF.as_millis() as u64);
+            m.next_check_ms = now_ms + backoff;
+            stalled.push((m.interface.clone(), m.fixed_channel, m.restarts));
+        }
+        stalled
+    }
+
+    pub fn spawn(self: &Arc<Self>) {
+        let watchdog = self.clone();
+        thread::spawn(move || loop {
+            thread::sleep(Duration::from_secs(1));
+            for (interface, fixed_channel, attempt) in watchdog.stalled(watchdog.now_ms()) {
+                error!("传感器健康告警: {} 已 {} 秒未收到任何帧，第 {} 次重新初始化网卡",
+                    interface, watchdog.timeout.as_secs(), attempt);
+                let result = wifi::reinit_monitor(&interface)
+                    .and_then(|_| fixed_channel.map_or(Ok(()), |c| wifi::set_channel(&interface, c)));
+                if let Err(e) = result {
+                    error!("重新初始化 {} 失败: {}", interface, e);
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stalled_after_timeout() {
+        let watchdog = Watchdog::new(Duration::from_secs(10));
+        watchdog.register("wlan1", Some(6));
+        assert!(watchdog.stalled(5_000).is_empty());
+        assert_eq!(watchdog.stalled(10_000), vec![("wlan1".to_string(), Some(6), 1)]);
+    }
+
+    #[test]
+    fn test_stalled_backoff_doubles() {
+        let watchdog = Watchdog::new(Duration::from_secs(10));
+        watchdog.register("wlan1", None);
+        assert_eq!(watchdog.stalled(10_000), vec![("wlan1".to_string(), None, 1)]);
+        // 第二次重启在 10 秒后，第三次再等 20 秒
+        assert!(watchdog.stalled(15_000).is_empty());
+        assert_eq!(watchdog.stalled(20_000), vec![("wlan1".to_string(), None, 2)]);
+        assert!(watchdog.stalled(30_000).is_empty());
+        assert_eq!(watchdog.stalled(40_000), vec![("wlan1".to_string(), None, 3)]);
+    }
+
+    #[test]
+    fn test_stalled_backoff_capped() {
+        let watchdog = Watchdog::new(Duration::from_secs(400));
+        watchdog.register("wlan1", None);
+        assert_eq!(watchdog.stalled(400_000).len(), 1);
+        assert_eq!(watchdog.stalled(800_000).len(), 1);
+        // 800 秒的间隔被截到 10 分钟
+        assert!(watchdog.stalled(1_399_999).is_empty());
+        assert_eq!(watchdog.stalled(1_400_000), vec![("wlan1".to_string(), None, 3)]);
+    }
+
+    #[test]
+    fn test_beat_resets_restarts() {
+        let watchdog = Watchdog::new(Duration::from_secs(10));
+        let heartbeat = watchdog.register("wlan1", None);
+        let now_ms = watchdog.now_ms();
+        assert_eq!(watchdog.stalled(now_ms + 10_000).len(), 1);
+        assert_eq!(watchdog.activity()[0].restarts, 1);
+
+        heartbeat.beat();
+        assert!(watchdog.stalled(watchdog.now_ms() + 1_000).is_empty());
+        assert_eq!(watchdog.activity()[0].restarts, 0);
+    }
+
+    #[test]
+    fn test_interfaces_checked_independently() {
+        let watchdog = Watchdog::new(Duration::from_secs(10));
+        watchdog.register("wlan1", None);
+        let heartbeat = watchdog.register("wlan2", None);
+        heartbeat.last_ms.store(8_000, Ordering::Relaxed);
+        assert_eq!(watchdog.stalled(12_000), vec![("wlan1".to_string(), None, 1)]);
+    }
+
+    #[test]
+    fn test_activity_lists_registered_interfaces() {
+        let watchdog = Watchdog::new(Duration::from_secs(10));
+        watchdog.register("wlan1", None);
+        watchdog.register("wlan2", None);
+        let activity = watchdog.activity();
+        assert_eq!(activity.iter().map(|a| a.interface.as_str()).collect::<Vec<_>>(), vec!["wlan1", "wlan2"]);
+        assert!(activity.iter().all(|a| a.idle < Duration::from_secs(1) && a.restarts == 0));
+    }
+}
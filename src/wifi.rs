@@ -1,3 +1,7 @@
+pub mod hopper;
+#[cfg(feature = "monitor")]
+pub mod monitor;
+
 pub fn frequency_to_channel(freq: u16) -> u8 {
     match freq {
         // 2.4 GHz 频段 (802.11b/g/n/ax)
@@ -112,4 +116,184 @@ pub fn frequency_to_channel(freq: u16) -> u8 {
 
         _ => 0,  // 未知频率
     }
-}
\ No newline at end of file
+}
+
+/// 信道号对应的中心频率 (MHz)，只覆盖 2.4 GHz 和 5 GHz 频段
+pub fn channel_to_frequency(channel: u8) -> Option<u32> {
+    match channel {
+        1..=13 => Some(2407 + 5 * channel as u32),
+        14 => Some(2484),
+        32..=177 => Some(5000 + 5 * channel as u32),
+        _ => None,
+    }
+}
+
+/// 通过 nl80211 设置监听网卡的信道
+#[cfg(feature = "monitor")]
+pub fn set_channel(interface: &str, channel: u8) -> std::io::Result<()> {
+    monitor::set_channel(interface, channel).map_err(std::io::Error::other)
+}
+
+/// 通过 `iw` 设置监听网卡的信道
+#[cfg(all(target_os = "linux", not(feature = "monitor")))]
+pub fn set_channel(interface: &str, channel: u8) -> std::io::Result<()> {
+    run_tool("iw", &["dev", interface, "set", "channel", &channel.to_string()])
+}
+
+/// 通过 `airport` 工具设置 Wi-Fi 网卡的信道（macOS 只有一块 Wi-Fi 网卡，不区分网卡）
+#[cfg(target_os = "macos")]
+pub fn set_channel(_interface: &str, channel: u8) -> std::io::Result<()> {
+    const AIRPORT: &str = "/System/Library/PrivateFrameworks/Apple80211.framework/Versions/Current/Resources/airport";
+    run_tool(AIRPORT, &[&format!("-c{}", channel)])
+}
+
+/// 通过 Npcap 附带的 `WlanHelper` 设置网卡的信道
+#[cfg(windows)]
+pub fn set_channel(interface: &str, channel: u8) -> std::io::Result<()> {
+    run_tool("WlanHelper.exe", &[npcap_guid(interface), "channel", &channel.to_string()])
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+pub fn set_channel(_interface: &str, _channel: u8) -> std::io::Result<()> {
+    Err(std::io::Error::new(std::io::ErrorKind::Unsupported, "此系统不支持切换信道"))
+}
+
+#[cfg(any(not(feature = "monitor"), not(target_os = "linux")))]
+fn run_tool(program: &str, args: &[&str]) -> std::io::Result<()> {
+    let status = std::process::Command::new(program).args(args).status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(std::io::Error::other(format!("{} 退出状态 {}", program, status)))
+    }
+}
+
+/// 是否为无线网卡（内核为其注册了 phy80211）
+#[cfg(target_os = "linux")]
+pub fn is_wireless(interface: &str) -> bool {
+    std::path::Path::new(&format!("/sys/class/net/{}/phy80211", interface)).exists()
+}
+
+/// 是否为无线网卡（`networksetup` 列出的 Wi-Fi 硬件端口）
+#[cfg(target_os = "macos")]
+pub fn is_wireless(interface: &str) -> bool {
+    std::process::Command::new("networksetup").arg("-listallhardwareports").output()
+        .is_ok_and(|output| parse_hardware_ports(&String::from_utf8_lossy(&output.stdout)).iter().any(|d| d == interface))
+}
+
+/// 是否为无线网卡（`netsh wlan` 列出的 WLAN 网卡，按 Npcap 设备名中的 GUID 匹配）
+#[cfg(windows)]
+pub fn is_wireless(interface: &str) -> bool {
+    std::process::Command::new("netsh").args(["wlan", "show", "interfaces"]).output()
+        .is_ok_and(|output| parse_netsh_guids(&String::from_utf8_lossy(&output.stdout)).iter()
+            .any(|guid| npcap_guid(interface).eq_ignore_ascii_case(guid)))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+pub fn is_wireless(_interface: &str) -> bool {
+    false
+}
+
+/// `networksetup -listallhardwareports` 输出中 Wi-Fi 端口的设备名 (如 `en0`)
+pub fn parse_hardware_ports(text: &str) -> Vec<String> {
+    let mut devices = Vec::new();
+    let mut wifi = false;
+    for line in text.lines().map(str::trim) {
+        if let Some(port) = line.strip_prefix("Hardware Port:") {
+            wifi = matches!(port.trim(), "Wi-Fi" | "AirPort");
+        } else if let Some(device) = line.strip_prefix("Device:").filter(|_| wifi) {
+            devices.push(device.trim().to_string());
+            wifi = false;
+        }
+    }
+    devices
+}
+
+/// `netsh wlan show interfaces` 输出中各 WLAN 网卡的 GUID
+pub fn parse_netsh_guids(text: &str) -> Vec<String> {
+    text.lines()
+        .filter_map(|line| line.split_once(':'))
+        .filter(|(key, _)| key.trim() == "GUID")
+        .map(|(_, guid)| guid.trim().to_string())
+        .collect()
+}
+
+/// Npcap 设备名 `\Device\NPF_{GUID}` 中的 GUID，其它名称原样返回
+pub fn npcap_guid(interface: &str) -> &str {
+    interface.split_once('{')
+        .and_then(|(_, rest)| rest.split_once('}'))
+        .map_or(interface, |(guid, _)| guid)
+}
+
+/// 读取网卡所在 phy 的可用信道（通过 `iw phy <phy> info`）
+#[cfg(target_os = "linux")]
+pub fn supported_channels(interface: &str) -> std::io::Result<Vec<u8>> {
+    let phy = std::fs::read_to_string(format!("/sys/class/net/{}/phy80211/name", interface))?;
+    let output = std::process::Command::new("iw")
+        .args(["phy", phy.trim(), "info"])
+        .output()?;
+    Ok(crate::regdomain::parse_iw_channels(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// 其它系统上没有读取网卡可用信道的接口，调用方按管制域和默认信道处理
+#[cfg(not(target_os = "linux"))]
+pub fn supported_channels(_interface: &str) -> std::io::Result<Vec<u8>> {
+    Err(std::io::Error::new(std::io::ErrorKind::Unsupported, "此系统不支持读取网卡的可用信道"))
+}
+
+/// 重新初始化监听网卡：关闭、重新设置为监听模式并启用
+#[cfg(target_os = "linux")]
+pub fn reinit_monitor(interface: &str) -> std::io::Result<()> {
+    let steps: [&[&str]; 3] = [
+        &["ip", "link", "set", interface, "down"],
+        &["iw", "dev", interface, "set", "type", "monitor"],
+        &["ip", "link", "set", interface, "up"],
+    ];
+    for step in steps {
+        let status = std::process::Command::new(step[0]).args(&step[1..]).status()?;
+        if !status.success() {
+            return Err(std::io::Error::other(format!("{} 退出状态 {}", step.join(" "), status)));
+        }
+    }
+    Ok(())
+}
+
+/// Windows/macOS 上 libpcap 每次重新打开网卡时都以 rfmon 方式打开，不需要单独重新设置
+#[cfg(not(target_os = "linux"))]
+pub fn reinit_monitor(_interface: &str) -> std::io::Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_hardware_ports() {
+        let ports = "Hardware Port: Ethernet\nDevice: en1\nEthernet Address: a8:60:b6:00:00:01\n\n\
+                     Hardware Port: Wi-Fi\nDevice: en0\nEthernet Address: a8:60:b6:00:00:02\n\n\
+                     Hardware Port: Thunderbolt Bridge\nDevice: bridge0\n";
+        assert_eq!(parse_hardware_ports(ports), ["en0"]);
+        // 旧版 macOS 称为 AirPort
+        assert_eq!(parse_hardware_ports("Hardware Port: AirPort\nDevice: en1\n"), ["en1"]);
+        assert!(parse_hardware_ports("").is_empty());
+    }
+
+    #[test]
+    fn test_parse_netsh_guids() {
+        let netsh = "    Name                   : Wi-Fi\r\n    Description            : Intel(R) Wi-Fi 6 AX201 160MHz\r\n\
+                     \x20   GUID                   : 2b7e8c3a-5d1f-4f0e-9a6b-0c1d2e3f4a5b\r\n    State                  : connected\r\n\
+                     \x20   Name                   : Wi-Fi 2\r\n\
+                     \x20   GUID                   : 0a1b2c3d-0000-0000-0000-000000000001\r\n";
+        assert_eq!(parse_netsh_guids(netsh), ["2b7e8c3a-5d1f-4f0e-9a6b-0c1d2e3f4a5b", "0a1b2c3d-0000-0000-0000-000000000001"]);
+        assert!(parse_netsh_guids("There is no wireless interface on the system.").is_empty());
+    }
+
+    #[test]
+    fn test_npcap_guid() {
+        assert!(npcap_guid(r"\Device\NPF_{2B7E8C3A-5D1F-4F0E-9A6B-0C1D2E3F4A5B}")
+            .eq_ignore_ascii_case("2b7e8c3a-5d1f-4f0e-9a6b-0c1d2e3f4a5b"));
+        assert_eq!(npcap_guid("wlan1"), "wlan1");
+        assert_eq!(npcap_guid("{unterminated"), "{unterminated");
+    }
+}
@@ -0,0 +1,163 @@
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use tracing::{debug, error, info, warn};
+
+use crate::config::Band;
+use crate::control::RuntimeControl;
+use crate::shutdown;
+
+/// 解析 `--hop` 的信道列表：逗号分隔的信道号或频段名，频段展开为其默认信道
+///
+/// 例如 `1,6,11,149` 或 `2.4ghz,5ghz`，重复的信道只保留第一次出现的位置。
+pub fn parse_channels(text: &str) -> Option<Vec<u8>> {
+    let mut channels = Vec::new();
+    for part in text.split(',').map(str::trim) {
+        let expanded = match part.to_ascii_lowercase().as_str() {
+            "2.4ghz" => Band::Ghz2_4.default_channels(),
+            "5ghz" => Band::Ghz5.default_channels(),
+            _ => vec![part.parse().ok().filter(|c| *c != 0)?],
+        };
+        for channel in expanded {
+            if !channels.contains(&channel) {
+                channels.push(channel);
+            }
+        }
+    }
+    Some(channels)
+}
+
+/// 信道轮换：按停留时间在信道计划中循环切换监听网卡，并记录当前所在信道
+///
+/// 抓包线程收到帧时读取 [`Hopper::current`]，驱动未在 radiotap 中报告信道时以此标注记录。
+/// 控制接口固定了该网卡的信道时暂停轮换。轮换线程在 `Hopper` 释放或收到退出信号时结束，
+/// 释放时等待线程退出，抓包重启后不会留下与新线程争抢网卡的旧线程。
+pub struct Hopper {
+    current: Arc<AtomicU8>,
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Hopper {
+    /// 计划为空时不切换信道；只有一个信道时设置一次；多个信道时启动轮换线程
+    pub fn start(interface: &str, plan: Vec<u8>, dwell: Duration, control: Arc<RuntimeControl>) -> Self {
+        let mut hopper = Self { current: Arc::new(AtomicU8::new(0)), stop: Arc::new(AtomicBool::new(false)), handle: None };
+        match plan.as_slice() {
+            [] => {}
+            [channel] => match super::set_channel(interface, *channel) {
+                Ok(()) => hopper.current.store(*channel, Ordering::Relaxed),
+                Err(e) => error!("设置 {} 信道 {} 失败: {}", interface, channel, e),
+            },
+            _ => {
+                info!("hopping {} across channels {:?}, {:?} each", interface, plan, dwell);
+                let name = interface.to_string();
+                let current = hopper.current.clone();
+                let stop = hopper.stop.clone();
+                hopper.handle = Some(thread::spawn(move || {
+                    for channel in plan.iter().cycle() {
+                        if let Some(fixed) = control.channel_override(&name) {
+                            current.store(fixed, Ordering::Relaxed);
+                        } else {
+                            match super::set_channel(&name, *channel) {
+                                Ok(()) => current.store(*channel, Ordering::Relaxed),
+                                Err(e) => {
+                                    current.store(0, Ordering::Relaxed);
+                                    warn!("failed to set {} to channel {}: {}", name, channel, e);
+                                }
+                            }
+                        }
+                        if !dwell_on(&stop, dwell) {
+                            break;
+                        }
+                    }
+                    debug!("channel hopping on {} stopped", name);
+                }));
+            }
+        }
+        hopper
+    }
+
+    /// 网卡当前所在信道，未知时为 None
+    pub fn current(&self) -> Option<u8> {
+        Some(self.current.load(Ordering::Relaxed)).filter(|c| *c != 0)
+    }
+}
+
+impl Drop for Hopper {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            handle.thread().unpark();
+            let _ = handle.join();
+        }
+    }
+}
+
+/// 在当前信道停留 `dwell`；要求停止或收到退出信号时提前返回 false
+fn dwell_on(stop: &AtomicBool, dwell: Duration) -> bool {
+    let deadline = Instant::now() + dwell;
+    loop {
+        if stop.load(Ordering::Relaxed) || shutdown::requested() {
+            return false;
+        }
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return true;
+        }
+        thread::park_timeout(remaining.min(SHUTDOWN_CHECK));
+    }
+}
+
+/// 停留期间检查退出信号的间隔
+const SHUTDOWN_CHECK: Duration = Duration::from_millis(200);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_channel_numbers() {
+        assert_eq!(parse_channels("1,6,11,149"), Some(vec![1, 6, 11, 149]));
+        assert_eq!(parse_channels("5"), Some(vec![5]));
+    }
+
+    #[test]
+    fn test_band_names_expand() {
+        assert_eq!(parse_channels("2.4ghz, 36"), Some(vec![1, 6, 11, 36]));
+        assert_eq!(parse_channels("5GHz"), Some(Band::Ghz5.default_channels()));
+    }
+
+    #[test]
+    fn test_duplicate_channels_keep_first_position() {
+        assert_eq!(parse_channels("6, 2.4ghz, 1"), Some(vec![6, 1, 11]));
+    }
+
+    #[test]
+    fn test_invalid_channels() {
+        assert_eq!(parse_channels("1,x"), None);
+        assert_eq!(parse_channels("0"), None);
+        assert_eq!(parse_channels("256"), None);
+        assert_eq!(parse_channels("1,,6"), None);
+    }
+
+    #[test]
+    fn test_empty_plan_leaves_channel_unknown() {
+        let hopper = Hopper::start("nosuchwlan9", Vec::new(), Duration::from_millis(200), Arc::default());
+        assert_eq!(hopper.current(), None);
+        // 设置失败时仍为未知
+        let hopper = Hopper::start("nosuchwlan9", vec![6], Duration::from_millis(200), Arc::default());
+        assert_eq!(hopper.current(), None);
+    }
+
+    #[test]
+    fn test_dropping_stops_hopping_thread() {
+        let hopper = Hopper::start("nosuchwlan9", vec![1, 6], Duration::from_secs(30), Arc::default());
+        assert!(hopper.handle.as_ref().is_some_and(|handle| !handle.is_finished()));
+        // 释放时唤醒停留中的线程并等待其退出，不必等满停留时间
+        let started = Instant::now();
+        drop(hopper);
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+}
@@ -0,0 +1,529 @@
+use std::ffi::CString;
+use std::fmt;
+use std::io;
+use std::mem::size_of;
+use std::os::raw::c_int;
+
+use tracing::{info, warn};
+
+const NLMSG_HDRLEN: usize = 16;
+const NLA_HDRLEN: usize = 4;
+/// 属性类型中的嵌套/字节序标志位
+const NLA_TYPE_MASK: u16 = 0x3fff;
+
+const NLM_F_REQUEST: u16 = 0x01;
+const NLM_F_ACK: u16 = 0x04;
+const NLMSG_ERROR: u16 = 2;
+const NLMSG_DONE: u16 = 3;
+
+const GENL_ID_CTRL: u16 = 16;
+const CTRL_CMD_GETFAMILY: u8 = 3;
+const CTRL_ATTR_FAMILY_ID: u16 = 1;
+const CTRL_ATTR_FAMILY_NAME: u16 = 2;
+
+const RTM_NEWLINK: u16 = 16;
+const RTM_GETLINK: u16 = 18;
+
+const NL80211_CMD_GET_WIPHY: u8 = 1;
+const NL80211_CMD_GET_INTERFACE: u8 = 5;
+const NL80211_CMD_SET_INTERFACE: u8 = 6;
+const NL80211_CMD_NEW_INTERFACE: u8 = 7;
+const NL80211_CMD_DEL_INTERFACE: u8 = 8;
+const NL80211_CMD_SET_CHANNEL: u8 = 65;
+
+const NL80211_ATTR_WIPHY: u16 = 1;
+const NL80211_ATTR_IFINDEX: u16 = 3;
+const NL80211_ATTR_IFNAME: u16 = 4;
+const NL80211_ATTR_IFTYPE: u16 = 5;
+const NL80211_ATTR_SUPPORTED_IFTYPES: u16 = 32;
+const NL80211_ATTR_WIPHY_FREQ: u16 = 38;
+const NL80211_ATTR_WIPHY_CHANNEL_TYPE: u16 = 39;
+
+const NL80211_IFTYPE_MONITOR: u32 = 6;
+/// 20 MHz，不使用 HT
+const NL80211_CHAN_NO_HT: u32 = 0;
+
+/// 配置监听网卡失败的原因
+#[derive(Debug)]
+pub enum MonitorError {
+    /// 网卡不存在
+    NoDevice { interface: String },
+    /// 不是由 nl80211 管理的无线网卡，或内核未加载 cfg80211
+    NotWireless { interface: String },
+    /// 没有配置网卡的权限（需要 root 或 CAP_NET_ADMIN）
+    PermissionDenied { interface: String },
+    /// 驱动不支持监听模式
+    Unsupported { interface: String },
+    /// 网卡正被其他程序使用，驱动拒绝切换模式
+    Busy { interface: String },
+    /// 驱动拒绝切换到该信道
+    ChannelRejected { interface: String, channel: u8, source: io::Error },
+    Netlink { interface: String, source: io::Error },
+}
+
+impl MonitorError {
+    /// 按 netlink 返回的 errno 归类
+    fn from_io(interface: &str, source: io::Error) -> Self {
+        let interface = interface.to_string();
+        match source.raw_os_error() {
+            Some(libc::EPERM | libc::EACCES) => Self::PermissionDenied { interface },
+            Some(libc::ENODEV) => Self::NoDevice { interface },
+            Some(libc::EOPNOTSUPP) => Self::Unsupported { interface },
+            Some(libc::EBUSY) => Self::Busy { interface },
+            _ => Self::Netlink { interface, source },
+        }
+    }
+}
+
+impl fmt::Display for MonitorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoDevice { interface } => write!(f, "{}: 网卡不存在", interface),
+            Self::NotWireless { interface } => write!(f, "{}: 不是 nl80211 无线网卡 (或内核未加载 cfg80211)", interface),
+            Self::PermissionDenied { interface } => write!(f, "{}: 没有配置网卡的权限 (需要 root 或 CAP_NET_ADMIN)", interface),
+            Self::Unsupported { interface } => write!(f, "{}: 驱动不支持监听模式，请换用支持 monitor 的网卡", interface),
+            Self::Busy { interface } =>
+                write!(f, "{}: 网卡正被占用，请先停止 NetworkManager/wpa_supplicant 对该网卡的管理", interface),
+            Self::ChannelRejected { interface, channel, source } =>
+                write!(f, "{}: 无法切换到信道 {} (网卡不支持或受管制域限制): {}", interface, channel, source),
+            Self::Netlink { interface, source } => write!(f, "{}: nl80211 请求失败: {}", interface, source),
+        }
+    }
+}
+
+impl std::error::Error for MonitorError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::ChannelRejected { source, .. } | Self::Netlink { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+/// 追加一个 netlink 属性，按 4 字节对齐
+fn put_attr(buf: &mut Vec<u8>, kind: u16, data: &[u8]) {
+    buf.extend_from_slice(&((NLA_HDRLEN + data.len()) as u16).to_ne_bytes());
+    buf.extend_from_slice(&kind.to_ne_bytes());
+    buf.extend_from_slice(data);
+    buf.resize(buf.len().next_multiple_of(4), 0);
+}
+
+fn put_u32(buf: &mut Vec<u8>, kind: u16, value: u32) {
+    put_attr(buf, kind, &value.to_ne_bytes());
+}
+
+/// 字符串属性以 NUL 结尾
+fn put_str(buf: &mut Vec<u8>, kind: u16, value: &str) {
+    let mut data = value.as_bytes().to_vec();
+    data.push(0);
+    put_attr(buf, kind, &data);
+}
+
+/// 拆分属性流为 (类型, 内容)，遇到长度不合法的属性时停止
+fn attrs(mut buf: &[u8]) -> impl Iterator<Item = (u16, &[u8])> {
+    std::iter::from_fn(move || {
+        let len = u16::from_ne_bytes(buf.get(0..2)?.try_into().ok()?) as usize;
+        let kind = u16::from_ne_bytes(buf.get(2..4)?.try_into().ok()?) & NLA_TYPE_MASK;
+        let data = buf.get(NLA_HDRLEN..len)?;
+        buf = buf.get(len.next_multiple_of(4)..).unwrap_or_default();
+        Some((kind, data))
+    })
+}
+
+fn attr_u32(buf: &[u8], kind: u16) -> Option<u32> {
+    attrs(buf).find(|(k, _)| *k == kind).and_then(|(_, data)| Some(u32::from_ne_bytes(data.get(..4)?.try_into().ok()?)))
+}
+
+/// 打开的 netlink 套接字，Drop 时关闭
+struct Netlink {
+    fd: c_int,
+    seq: u32,
+    buf: Vec<u8>,
+}
+
+impl Netlink {
+    fn open(protocol: c_int) -> io::Result<Self> {
+        let fd = unsafe { libc::socket(libc::AF_NETLINK, libc::SOCK_RAW | libc::SOCK_CLOEXEC, protocol) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let socket = Self { fd, seq: 0, buf: vec![0; 32 * 1024] };
+        let mut addr: libc::sockaddr_nl = unsafe { std::mem::zeroed() };
+        addr.nl_family = libc::AF_NETLINK as libc::sa_family_t;
+        let bound = unsafe {
+            libc::bind(fd, &addr as *const libc::sockaddr_nl as *const libc::sockaddr,
+                       size_of::<libc::sockaddr_nl>() as libc::socklen_t)
+        };
+        if bound < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(socket)
+    }
+
+    /// 发送一条请求并读取回复直到确认，返回各回复消息的内容（去掉 netlink 头）
+    fn request(&mut self, kind: u16, payload: &[u8]) -> io::Result<Vec<Vec<u8>>> {
+        self.seq = self.seq.wrapping_add(1);
+        let mut message = Vec::with_capacity(NLMSG_HDRLEN + payload.len());
+        message.extend_from_slice(&((NLMSG_HDRLEN + payload.len()) as u32).to_ne_bytes());
+        message.extend_from_slice(&kind.to_ne_bytes());
+        message.extend_from_slice(&(NLM_F_REQUEST | NLM_F_ACK).to_ne_bytes());
+        message.extend_from_slice(&self.seq.to_ne_bytes());
+        message.extend_from_slice(&0u32.to_ne_bytes());
+        message.extend_from_slice(payload);
+        if unsafe { libc::send(self.fd, message.as_ptr().cast(), message.len(), 0) } < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let mut replies = Vec::new();
+        loop {
+            let len = unsafe { libc::recv(self.fd, self.buf.as_mut_ptr().cast(), self.buf.len(), 0) };
+            if len < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            let mut rest = &self.buf[..len as usize];
+            while rest.len() >= NLMSG_HDRLEN {
+                let msg_len = u32::from_ne_bytes(rest[0..4].try_into().unwrap()) as usize;
+                let msg_type = u16::from_ne_bytes(rest[4..6].try_into().unwrap());
+                let seq = u32::from_ne_bytes(rest[8..12].try_into().unwrap());
+                if msg_len < NLMSG_HDRLEN || msg_len > rest.len() {
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, "netlink 消息长度不合法"));
+                }
+                let body = &rest[NLMSG_HDRLEN..msg_len];
+                rest = rest.get(msg_len.next_multiple_of(4)..).unwrap_or_default();
+                if seq != self.seq {
+                    continue;
+                }
+                match msg_type {
+                    NLMSG_ERROR => {
+                        let errno = body.get(..4).map(|b| i32::from_ne_bytes(b.try_into().unwrap())).unwrap_or(0);
+                        return match errno {
+                            0 => Ok(replies),
+                            errno => Err(io::Error::from_raw_os_error(-errno)),
+                        };
+                    }
+                    NLMSG_DONE => return Ok(replies),
+                    _ => replies.push(body.to_vec()),
+                }
+            }
+        }
+    }
+}
+
+impl Drop for Netlink {
+    fn drop(&mut self) {
+        unsafe { libc::close(self.fd) };
+    }
+}
+
+/// nl80211 通用 netlink 连接
+struct Nl80211 {
+    socket: Netlink,
+    family: u16,
+}
+
+impl Nl80211 {
+    fn open() -> io::Result<Self> {
+        let mut socket = Netlink::open(libc::NETLINK_GENERIC)?;
+        let mut payload = vec![CTRL_CMD_GETFAMILY, 1, 0, 0];
+        put_str(&mut payload, CTRL_ATTR_FAMILY_NAME, "nl80211");
+        let family = socket.request(GENL_ID_CTRL, &payload)?.iter()
+            .find_map(|reply| attrs(reply.get(4..)?).find(|(k, _)| *k == CTRL_ATTR_FAMILY_ID)
+                .and_then(|(_, data)| Some(u16::from_ne_bytes(data.get(..2)?.try_into().ok()?))))
+            .ok_or_else(|| io::Error::from_raw_os_error(libc::ENOENT))?;
+        Ok(Self { socket, family })
+    }
+
+    /// 执行 nl80211 命令，返回各回复的属性流
+    fn command(&mut self, cmd: u8, attrs: &[u8]) -> io::Result<Vec<Vec<u8>>> {
+        let mut payload = vec![cmd, 0, 0, 0];
+        payload.extend_from_slice(attrs);
+        let replies = self.socket.request(self.family, &payload)?;
+        Ok(replies.into_iter().map(|mut reply| reply.split_off(4.min(reply.len()))).collect())
+    }
+
+    /// 网卡的 (接口类型, phy 编号)
+    fn interface(&mut self, ifindex: u32) -> io::Result<(u32, u32)> {
+        let mut request = Vec::new();
+        put_u32(&mut request, NL80211_ATTR_IFINDEX, ifindex);
+        let replies = self.command(NL80211_CMD_GET_INTERFACE, &request)?;
+        let reply = replies.first().ok_or_else(|| io::Error::from_raw_os_error(libc::ENODEV))?;
+        let iftype = attr_u32(reply, NL80211_ATTR_IFTYPE).unwrap_or(0);
+        let wiphy = attr_u32(reply, NL80211_ATTR_WIPHY).ok_or_else(|| io::Error::from_raw_os_error(libc::ENODEV))?;
+        Ok((iftype, wiphy))
+    }
+
+    /// phy 是否支持监听模式
+    fn supports_monitor(&mut self, wiphy: u32) -> io::Result<bool> {
+        let mut request = Vec::new();
+        put_u32(&mut request, NL80211_ATTR_WIPHY, wiphy);
+        let replies = self.command(NL80211_CMD_GET_WIPHY, &request)?;
+        Ok(replies.iter().any(|reply| supported_iftypes(reply).contains(&(NL80211_IFTYPE_MONITOR as u16))))
+    }
+
+    fn set_iftype(&mut self, ifindex: u32, iftype: u32) -> io::Result<()> {
+        let mut request = Vec::new();
+        put_u32(&mut request, NL80211_ATTR_IFINDEX, ifindex);
+        put_u32(&mut request, NL80211_ATTR_IFTYPE, iftype);
+        self.command(NL80211_CMD_SET_INTERFACE, &request).map(drop)
+    }
+
+    fn new_monitor(&mut self, wiphy: u32, name: &str) -> io::Result<()> {
+        let mut request = Vec::new();
+        put_u32(&mut request, NL80211_ATTR_WIPHY, wiphy);
+        put_str(&mut request, NL80211_ATTR_IFNAME, name);
+        put_u32(&mut request, NL80211_ATTR_IFTYPE, NL80211_IFTYPE_MONITOR);
+        self.command(NL80211_CMD_NEW_INTERFACE, &request).map(drop)
+    }
+
+    fn del_interface(&mut self, ifindex: u32) -> io::Result<()> {
+        let mut request = Vec::new();
+        put_u32(&mut request, NL80211_ATTR_IFINDEX, ifindex);
+        self.command(NL80211_CMD_DEL_INTERFACE, &request).map(drop)
+    }
+
+    fn set_frequency(&mut self, ifindex: u32, freq: u32) -> io::Result<()> {
+        let mut request = Vec::new();
+        put_u32(&mut request, NL80211_ATTR_IFINDEX, ifindex);
+        put_u32(&mut request, NL80211_ATTR_WIPHY_FREQ, freq);
+        put_u32(&mut request, NL80211_ATTR_WIPHY_CHANNEL_TYPE, NL80211_CHAN_NO_HT);
+        self.command(NL80211_CMD_SET_CHANNEL, &request).map(drop)
+    }
+}
+
+/// GET_WIPHY 回复中支持的接口类型（嵌套属性的类型即接口类型）
+fn supported_iftypes(reply: &[u8]) -> Vec<u16> {
+    attrs(reply)
+        .filter(|(kind, _)| *kind == NL80211_ATTR_SUPPORTED_IFTYPES)
+        .flat_map(|(_, nested)| attrs(nested).map(|(iftype, _)| iftype))
+        .collect()
+}
+
+/// 读取或设置网卡的启用状态（rtnetlink）
+fn link_up(ifindex: u32, up: Option<bool>) -> io::Result<bool> {
+    let mut socket = Netlink::open(libc::NETLINK_ROUTE)?;
+    // ifinfomsg: 协议族(1) 填充(1) 类型(2) 序号(4) 标志(4) 变更掩码(4)
+    let mut ifinfo = vec![libc::AF_UNSPEC as u8, 0, 0, 0];
+    ifinfo.extend_from_slice(&(ifindex as i32).to_ne_bytes());
+    let Some(up) = up else {
+        ifinfo.extend_from_slice(&[0; 8]);
+        let replies = socket.request(RTM_GETLINK, &ifinfo)?;
+        let flags = replies.first().and_then(|r| r.get(8..12)).map(|b| u32::from_ne_bytes(b.try_into().unwrap()));
+        return Ok(flags.ok_or_else(|| io::Error::from_raw_os_error(libc::ENODEV))? & libc::IFF_UP as u32 != 0);
+    };
+    let flags = if up { libc::IFF_UP as u32 } else { 0 };
+    ifinfo.extend_from_slice(&flags.to_ne_bytes());
+    ifinfo.extend_from_slice(&(libc::IFF_UP as u32).to_ne_bytes());
+    socket.request(RTM_NEWLINK, &ifinfo)?;
+    Ok(up)
+}
+
+fn ifindex(interface: &str) -> Option<u32> {
+    let name = CString::new(interface).ok()?;
+    Some(unsafe { libc::if_nametoindex(name.as_ptr()) }).filter(|i| *i != 0)
+}
+
+/// 通过 nl80211 设置网卡信道
+pub fn set_channel(interface: &str, channel: u8) -> Result<(), MonitorError> {
+    let index = ifindex(interface).ok_or_else(|| MonitorError::NoDevice { interface: interface.to_string() })?;
+    let freq = super::channel_to_frequency(channel).ok_or_else(|| MonitorError::ChannelRejected {
+        interface: interface.to_string(),
+        channel,
+        source: io::Error::from_raw_os_error(libc::EINVAL),
+    })?;
+    let mut nl = Nl80211::open().map_err(|_| MonitorError::NotWireless { interface: interface.to_string() })?;
+    nl.set_frequency(index, freq)
+        .map_err(|source| MonitorError::ChannelRejected { interface: interface.to_string(), channel, source })
+}
+
+/// 退出时如何还原网卡
+enum Restore {
+    /// 原本就是监听模式
+    Unchanged { was_up: bool },
+    /// 原网卡切换成了监听模式
+    Switched { iftype: u32, was_up: bool },
+    /// 在同一 phy 上新建了监听接口
+    Created,
+}
+
+/// 已就绪的监听网卡，Drop 时还原网卡原来的模式和启用状态
+///
+/// 优先把网卡本身切换为监听模式；驱动不允许时（网卡忙或不支持切换），在同一 phy 上
+/// 新建 `<网卡>mon` 监听接口，抓包应使用 [`MonitorGuard::interface`]。
+pub struct MonitorGuard {
+    interface: String,
+    ifindex: u32,
+    restore: Restore,
+}
+
+impl MonitorGuard {
+    /// 抓包使用的网卡名
+    pub fn interface(&self) -> &str {
+        &self.interface
+    }
+
+    pub fn set_channel(&self, channel: u8) -> Result<(), MonitorError> {
+        set_channel(&self.interface, channel)
+    }
+
+    fn restore(&self) -> Result<(), MonitorError> {
+        let err = |e| MonitorError::from_io(&self.interface, e);
+        let mut nl = Nl80211::open().map_err(err)?;
+        match self.restore {
+            Restore::Unchanged { was_up } => {
+                if !was_up {
+                    link_up(self.ifindex, Some(false)).map_err(err)?;
+                }
+            }
+            Restore::Switched { iftype, was_up } => {
+                link_up(self.ifindex, Some(false)).map_err(err)?;
+                nl.set_iftype(self.ifindex, iftype).map_err(err)?;
+                if was_up {
+                    link_up(self.ifindex, Some(true)).map_err(err)?;
+                }
+            }
+            Restore::Created => nl.del_interface(self.ifindex).map_err(err)?,
+        }
+        Ok(())
+    }
+}
+
+impl Drop for MonitorGuard {
+    fn drop(&mut self) {
+        match self.restore() {
+            Ok(()) => info!("restored {} to its original mode", self.interface),
+            Err(e) => warn!("failed to restore {}: {}", self.interface, e),
+        }
+    }
+}
+
+/// 把网卡配置为启用的监听模式
+pub fn setup(interface: &str) -> Result<MonitorGuard, MonitorError> {
+    let err = |e| MonitorError::from_io(interface, e);
+    let index = ifindex(interface).ok_or_else(|| MonitorError::NoDevice { interface: interface.to_string() })?;
+    let mut nl = Nl80211::open().map_err(|_| MonitorError::NotWireless { interface: interface.to_string() })?;
+    let (iftype, wiphy) = nl.interface(index).map_err(|e| match e.raw_os_error() {
+        Some(libc::ENODEV | libc::EINVAL) => MonitorError::NotWireless { interface: interface.to_string() },
+        _ => err(e),
+    })?;
+    let was_up = link_up(index, None).map_err(err)?;
+    if iftype == NL80211_IFTYPE_MONITOR {
+        link_up(index, Some(true)).map_err(err)?;
+        info!("{} already in monitor mode", interface);
+        return Ok(MonitorGuard { interface: interface.to_string(), ifindex: index, restore: Restore::Unchanged { was_up } });
+    }
+    if !nl.supports_monitor(wiphy).map_err(err)? {
+        return Err(MonitorError::Unsupported { interface: interface.to_string() });
+    }
+
+    link_up(index, Some(false)).map_err(err)?;
+    let switch_error = match nl.set_iftype(index, NL80211_IFTYPE_MONITOR) {
+        Ok(()) => {
+            let guard = MonitorGuard {
+                interface: interface.to_string(),
+                ifindex: index,
+                restore: Restore::Switched { iftype, was_up },
+            };
+            // 启用失败时由 guard 的 Drop 还原
+            link_up(index, Some(true)).map_err(err)?;
+            info!("switched {} to monitor mode", interface);
+            return Ok(guard);
+        }
+        Err(e) => err(e),
+    };
+    if was_up {
+        link_up(index, Some(true)).map_err(err)?;
+    }
+    if !matches!(switch_error, MonitorError::Busy { .. } | MonitorError::Unsupported { .. }) {
+        return Err(switch_error);
+    }
+
+    // 接口名最长 15 字节
+    let name: String = format!("{}mon", interface.chars().take(12).collect::<String>());
+    nl.new_monitor(wiphy, &name).map_err(|e| match e.raw_os_error() {
+        Some(libc::EOPNOTSUPP | libc::EBUSY) => switch_error,
+        _ => MonitorError::from_io(&name, e),
+    })?;
+    let created = ifindex(&name).ok_or_else(|| MonitorError::NoDevice { interface: name.clone() })?;
+    let guard = MonitorGuard { interface: name.clone(), ifindex: created, restore: Restore::Created };
+    link_up(created, Some(true)).map_err(|e| MonitorError::from_io(&name, e))?;
+    info!("created monitor interface {} on the phy of {}", name, interface);
+    Ok(guard)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn interface_attrs() -> Vec<u8> {
+        let mut buf = Vec::new();
+        put_u32(&mut buf, NL80211_ATTR_IFINDEX, 7);
+        put_str(&mut buf, NL80211_ATTR_IFNAME, "wlan0mon");
+        put_u32(&mut buf, NL80211_ATTR_WIPHY_FREQ, 2437);
+        buf
+    }
+
+    #[test]
+    fn test_attributes_are_padded() {
+        // 4 + 4，4 + 9 对齐到 16，4 + 4
+        let buf = interface_attrs();
+        assert_eq!(buf.len(), 32);
+        assert_eq!(&buf[8..10], &13u16.to_ne_bytes());
+        assert_eq!(&buf[17..24], &[b'm', b'o', b'n', 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_parse_attributes() {
+        let buf = interface_attrs();
+        let parsed: Vec<_> = attrs(&buf).map(|(kind, data)| (kind, data.len())).collect();
+        assert_eq!(parsed, [(NL80211_ATTR_IFINDEX, 4), (NL80211_ATTR_IFNAME, 9), (NL80211_ATTR_WIPHY_FREQ, 4)]);
+        assert_eq!(attr_u32(&buf, NL80211_ATTR_WIPHY_FREQ), Some(2437));
+        assert_eq!(attr_u32(&buf, NL80211_ATTR_IFTYPE), None);
+    }
+
+    #[test]
+    fn test_short_u32_attribute() {
+        let mut buf = Vec::new();
+        put_attr(&mut buf, NL80211_ATTR_IFTYPE, &[6, 0]);
+        assert_eq!(attr_u32(&buf, NL80211_ATTR_IFTYPE), None);
+    }
+
+    #[test]
+    fn test_truncated_attributes() {
+        let buf = interface_attrs();
+        assert_eq!(attrs(&buf[..30]).count(), 2);
+        // 长度小于属性头时停止
+        assert_eq!(attrs(&[2, 0, 1, 0, 8, 0, 1, 0]).count(), 0);
+    }
+
+    #[test]
+    fn test_nested_supported_iftypes() {
+        // 嵌套的接口类型列表，带 NLA_F_NESTED 标志
+        let mut nested = Vec::new();
+        put_attr(&mut nested, 2, &[]);
+        put_attr(&mut nested, 6, &[]);
+        let mut wiphy = Vec::new();
+        put_u32(&mut wiphy, NL80211_ATTR_WIPHY, 0);
+        put_attr(&mut wiphy, NL80211_ATTR_SUPPORTED_IFTYPES | 0x8000, &nested);
+        assert_eq!(supported_iftypes(&wiphy), [2, 6]);
+        assert!(supported_iftypes(&interface_attrs()).is_empty());
+    }
+
+    #[test]
+    fn test_errno_classification() {
+        let classify = |errno| MonitorError::from_io("wlan0", io::Error::from_raw_os_error(errno));
+        assert!(matches!(classify(libc::EPERM), MonitorError::PermissionDenied { .. }));
+        assert!(matches!(classify(libc::EACCES), MonitorError::PermissionDenied { .. }));
+        assert!(matches!(classify(libc::ENODEV), MonitorError::NoDevice { .. }));
+        assert!(matches!(classify(libc::EOPNOTSUPP), MonitorError::Unsupported { .. }));
+        assert!(matches!(classify(libc::EBUSY), MonitorError::Busy { .. }));
+        let other = classify(libc::EINVAL);
+        assert!(matches!(other, MonitorError::Netlink { .. }));
+        assert!(std::error::Error::source(&other).is_some());
+        assert!(classify(libc::EBUSY).to_string().starts_with("wlan0: 网卡正被占用"));
+    }
+
+    #[test]
+    fn test_set_channel_on_missing_interface() {
+        let error = set_channel("nosuchwlan9", 6).unwrap_err();
+        assert!(matches!(error, MonitorError::NoDevice { ref interface } if interface == "nosuchwlan9"));
+    }
+}
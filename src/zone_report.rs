@@ -0,0 +1,180 @@
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+use chrono::SecondsFormat;
+
+use crate::event_log::DecodedEvent;
+use crate::geo::degrees;
+use crate::geofence::Zone;
+use crate::position;
+use crate::time_format;
+
+/// 某架无人机在某个区域内的占用情况
+#[derive(Debug, Clone)]
+pub struct Occupancy {
+    pub first_inside_ms: Option<i64>,
+    pub last_inside_ms: Option<i64>,
+    pub duration_ms: i64,
+    pub closest_approach_m: f64,
+    last_sample_inside: Option<i64>,
+}
+
+impl Default for Occupancy {
+    fn default() -> Self {
+        Self {
+            first_inside_ms: None,
+            last_inside_ms: None,
+            duration_ms: 0,
+            closest_approach_m: f64::INFINITY,
+            last_sample_inside: None,
+        }
+    }
+}
+
+/// 区域占用报表
+///
+/// 对每个 (区域, 无人机) 统计首次/末次在区内时间、累计停留时长和最近距离。
+/// 相邻两个在区内的样本间隔不超过 `max_gap_ms` 时计入停留时长。
+pub struct ZoneReport {
+    zones: Vec<Zone>,
+    max_gap_ms: i64,
+    occupancy: BTreeMap<(String, String), Occupancy>,
+}
+
+impl ZoneReport {
+    pub fn new(zones: Vec<Zone>) -> Self {
+        Self { zones, max_gap_ms: 60_000, occupancy: BTreeMap::new() }
+    }
+
+    pub fn add(&mut self, event: &DecodedEvent) {
+        let record = &event.record;
+        if !position::has_coordinates(record) {
+            return;
+        }
+        let (lat, lon) = (degrees(record.latitude), degrees(record.longitude));
+        let drone = if record.rid.is_empty() { &record.track_id } else { &record.rid };
+        let now = event.received_at_ms;
+        for zone in &self.zones {
+            let distance = zone.distance_m(lat, lon);
+            let entry = self.occupancy.entry((zone.name.clone(), drone.clone())).or_default();
+            entry.closest_approach_m = entry.closest_approach_m.min(distance);
+            if distance > 0.0 {
+                entry.last_sample_inside = None;
+                continue;
+            }
+            if let Some(previous) = entry.last_sample_inside
+                && now - previous <= self.max_gap_ms
+            {
+                entry.duration_ms += now - previous;
+            }
+            entry.first_inside_ms.get_or_insert(now);
+            entry.last_inside_ms = Some(now);
+            entry.last_sample_inside = Some(now);
+        }
+    }
+
+    /// 按 (区域, 无人机) 的占用情况
+    pub fn occupancy(&self) -> &BTreeMap<(String, String), Occupancy> {
+        &self.occupancy
+    }
+
+    pub fn write_csv<W: Write>(&self, mut out: W) -> io::Result<()> {
+        writeln!(out, "zone,drone,first_inside,last_inside,duration_s,closest_approach_m")?;
+        for ((zone, drone), o) in &self.occupancy {
+            writeln!(out, "{},{},{},{},{:.1},{:.1}",
+                zone, drone,
+                o.first_inside_ms.map(iso).unwrap_or_default(),
+                o.last_inside_ms.map(iso).unwrap_or_default(),
+                o.duration_ms as f64 / 1000.0,
+                o.closest_approach_m)?;
+        }
+        Ok(())
+    }
+}
+
+fn iso(ms: i64) -> String {
+    time_format::format_ms(ms, SecondsFormat::Secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geofence::Shape;
+    use crate::upload_data::UploadData;
+
+    fn report() -> ZoneReport {
+        ZoneReport::new(vec![Zone {
+            name: "square".to_string(),
+            shape: Shape::Polygon(vec![(31.0, 121.0), (31.0, 121.01), (31.01, 121.01), (31.01, 121.0)]),
+        }])
+    }
+
+    fn fix(rid: &str, at: i64, lat: f64, lon: f64) -> DecodedEvent {
+        let record = UploadData {
+            rid: rid.to_string(),
+            latitude: (lat * 1e7).round() as i32,
+            longitude: (lon * 1e7).round() as i32,
+            ..Default::default()
+        };
+        DecodedEvent { received_at_ms: at, record }
+    }
+
+    fn occupancy<'a>(report: &'a ZoneReport, drone: &str) -> &'a Occupancy {
+        &report.occupancy()[&("square".to_string(), drone.to_string())]
+    }
+
+    #[test]
+    fn test_dwell_time_accumulates_inside() {
+        let mut report = report();
+        for at in [0, 20_000, 40_000] {
+            report.add(&fix("A", at, 31.005, 121.005));
+        }
+        let o = occupancy(&report, "A");
+        assert_eq!((o.first_inside_ms, o.last_inside_ms, o.duration_ms), (Some(0), Some(40_000), 40_000));
+        assert_eq!(o.closest_approach_m, 0.0);
+    }
+
+    #[test]
+    fn test_gap_or_exit_is_not_counted() {
+        let mut report = report();
+        report.add(&fix("A", 0, 31.005, 121.005));
+        report.add(&fix("A", 90_000, 31.005, 121.005));   // 间隔超过 60 秒
+        report.add(&fix("A", 100_000, 30.999, 121.005));  // 离开区域
+        report.add(&fix("A", 110_000, 31.005, 121.005));
+        let o = occupancy(&report, "A");
+        assert_eq!(o.duration_ms, 0);
+        assert_eq!(o.last_inside_ms, Some(110_000));
+    }
+
+    #[test]
+    fn test_closest_approach_outside() {
+        let mut report = report();
+        report.add(&fix("A", 0, 30.998, 121.005));
+        report.add(&fix("A", 1_000, 30.999, 121.005));
+        let o = occupancy(&report, "A");
+        assert_eq!((o.first_inside_ms, o.duration_ms), (None, 0));
+        assert!((o.closest_approach_m - 111.2).abs() < 1.0, "{}", o.closest_approach_m);
+    }
+
+    #[test]
+    fn test_records_without_fix_are_ignored() {
+        let mut report = report();
+        report.add(&fix("A", 0, 0.0, 0.0));
+        assert!(report.occupancy().is_empty());
+    }
+
+    #[test]
+    fn test_write_csv() {
+        let mut report = report();
+        report.add(&fix("A", 0, 31.005, 121.005));
+        report.add(&fix("A", 1_500, 31.005, 121.005));
+        report.add(&fix("B", 0, 30.999, 121.005));
+        let mut out = Vec::new();
+        report.write_csv(&mut out).unwrap();
+        let csv = String::from_utf8(out).unwrap();
+        let lines: Vec<_> = csv.lines().collect();
+        assert_eq!(lines[0], "zone,drone,first_inside,last_inside,duration_s,closest_approach_m");
+        assert_eq!(lines[1], "square,A,1970-01-01T00:00:00Z,1970-01-01T00:00:01Z,1.5,0.0");
+        assert!(lines[2].starts_with("square,B,,,0.0,111."), "{}", lines[2]);
+    }
+}